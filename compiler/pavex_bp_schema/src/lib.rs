@@ -121,6 +121,10 @@ pub struct RoutesImport {
     pub relative_to: String,
     pub created_at: CreatedAt,
     pub registered_at: Location,
+    /// Lint settings for all the components registered via this import.
+    ///
+    /// They can be overridden on a component-by-component basis.
+    pub lints: BTreeMap<Lint, LintSetting>,
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq, Eq)]
@@ -133,6 +137,10 @@ pub struct Import {
     pub relative_to: String,
     pub created_at: CreatedAt,
     pub registered_at: Location,
+    /// Lint settings for all the components registered via this import.
+    ///
+    /// They can be overridden on a component-by-component basis.
+    pub lints: BTreeMap<Lint, LintSetting>,
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq, Eq)]
@@ -143,6 +151,8 @@ pub struct Route {
     pub registered_at: Location,
     /// The callable in charge of processing errors returned by this route, if any.
     pub error_handler: Option<ErrorHandler>,
+    /// Lint settings for this route.
+    pub lints: BTreeMap<Lint, LintSetting>,
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq, Eq)]
@@ -154,6 +164,8 @@ pub struct Fallback {
     pub registered_at: Location,
     /// The callable in charge of processing errors returned by this fallback, if any.
     pub error_handler: Option<ErrorHandler>,
+    /// Lint settings for this fallback.
+    pub lints: BTreeMap<Lint, LintSetting>,
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq, Eq)]
@@ -370,6 +382,31 @@ pub enum Lint {
     ///
     /// [1]: https://pavex.dev/docs/guide/errors/error_handlers/#fallback-error-handler
     ErrorFallback,
+    /// The type extracted by a body extractor (e.g. `JsonBody<T>` or `UrlEncodedBody<T>`)
+    /// doesn't use `#[serde(deny_unknown_fields)]`, therefore it silently ignores unknown fields
+    /// in the incoming payload.
+    ///
+    /// This lint is allowed by default.
+    MissingDenyUnknownFields,
+    /// A component marked as `CloneIfNecessary` had to be cloned to satisfy the borrow checker.
+    ///
+    /// This lint is allowed by default.
+    RedundantClone,
+    /// A large singleton is taken by value, therefore it must be copied or cloned
+    /// for every incoming request.
+    ///
+    /// This lint is allowed by default.
+    LargeSingletonByValue,
+    /// A fallback registered under a path prefix is never invoked, since all the
+    /// requests it would handle are already matched by other routes.
+    ///
+    /// This lint is allowed by default.
+    UnreachableFallback,
+    /// A route can never be reached for some of the domains it was registered for,
+    /// since a more specific domain constraint takes precedence.
+    ///
+    /// This lint is allowed by default.
+    ShadowedRoute,
 }
 
 #[derive(
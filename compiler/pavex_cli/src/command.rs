@@ -1,4 +1,4 @@
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand};
 use clap_stdin::MaybeStdin;
 use pavexc_cli_client::commands::new::TemplateName;
 use redact::Secret;
@@ -100,6 +100,35 @@ impl From<Color> for pavexc_cli_client::config::Color {
     }
 }
 
+/// Options for exporting the application-wide component graph.
+#[derive(Args, Debug)]
+pub struct ComponentGraphArgs {
+    /// Optional.
+    /// If provided, Pavex will export an application-wide graph of the dependencies
+    /// between components to the specified path.
+    /// The format is determined by the file extension: `.dot` (or `.gv`) for DOT,
+    /// `.mmd` (or `.mermaid`) for Mermaid, `.json` for JSON.
+    #[clap(long, env = "PAVEX_COMPONENT_GRAPH", value_parser)]
+    pub component_graph: Option<PathBuf>,
+    /// Only include the components defined in this crate in the component graph.
+    /// It can be specified multiple times.
+    #[clap(
+        long = "component-graph-crate",
+        value_name = "CRATE",
+        requires = "component_graph"
+    )]
+    pub component_graph_crates: Vec<String>,
+    /// Only include the components used by routes whose path starts with this prefix
+    /// in the component graph.
+    /// It can be specified multiple times.
+    #[clap(
+        long = "component-graph-route-prefix",
+        value_name = "PREFIX",
+        requires = "component_graph"
+    )]
+    pub component_graph_route_prefixes: Vec<String>,
+}
+
 #[derive(Subcommand)]
 pub enum Command {
     /// Generate the server SDK code for an application blueprint.
@@ -112,11 +141,18 @@ pub enum Command {
         /// the application to the specified path.
         #[clap(long, env = "PAVEX_DIAGNOSTICS", value_parser)]
         diagnostics: Option<PathBuf>,
+        #[clap(flatten)]
+        component_graph: ComponentGraphArgs,
         #[clap(long)]
         /// Verify that the generated server SDK is up-to-date.
         /// If it isn't, `pavex` will return an error without updating
         /// the server SDK code.
         check: bool,
+        #[clap(long, env = "PAVEX_CONFIG_SCHEMA")]
+        /// Emit a JSON Schema for `ApplicationConfig` (`config.schema.json`) and an annotated
+        /// reference configuration file (`config.reference.yml`) in the root directory
+        /// of the generated server SDK crate.
+        config_schema: bool,
         /// The directory that will contain the newly generated server SDK crate.
         /// If the directory path is relative,
         /// it is interpreted as relative to the root of the current workspace.
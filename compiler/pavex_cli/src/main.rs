@@ -17,7 +17,7 @@ use pavex_cli::activation::{
 };
 use pavex_cli::cargo_install::{GitSourceRevision, Source, cargo_install};
 use pavex_cli::cli_kind::CliKind;
-use pavex_cli::command::{Cli, Color, Command, SelfCommands};
+use pavex_cli::command::{Cli, Color, Command, ComponentGraphArgs, SelfCommands};
 use pavex_cli::locator::PavexLocator;
 use pavex_cli::package_graph::compute_package_graph;
 use pavex_cli::pavexc::{get_or_install_from_graph, get_or_install_from_version};
@@ -81,10 +81,21 @@ fn _main(cli: Cli) -> Result<ExitCode, miette::Error> {
         Command::Generate {
             blueprint,
             diagnostics,
+            component_graph,
             check,
+            config_schema,
             output,
-        } => generate(client, &locator, blueprint, diagnostics, output, check)
-            .map_err(|e| e.into_miette().into()),
+        } => generate(
+            client,
+            &locator,
+            blueprint,
+            diagnostics,
+            component_graph,
+            output,
+            check,
+            config_schema,
+        )
+        .map_err(|e| e.into_miette().into()),
         Command::New { path, template } => {
             scaffold_project(client, &locator, path, template).map_err(|e| e.into_miette().into())
         }
@@ -154,14 +165,17 @@ fn pavexc_client(cli: &Cli) -> Client {
     client
 }
 
+#[allow(clippy::too_many_arguments)]
 #[tracing::instrument("Generate server sdk", skip(client, locator))]
 fn generate(
     mut client: Client,
     locator: &PavexLocator,
     blueprint: PathBuf,
     diagnostics: Option<PathBuf>,
+    component_graph: ComponentGraphArgs,
     output: PathBuf,
     check: bool,
+    config_schema: bool,
 ) -> Result<ExitCode, anyhow::Error> {
     let pavexc_cli_path = if let Some(pavexc_override) = pavex_cli::env::pavexc_override() {
         pavexc_override
@@ -179,9 +193,21 @@ fn generate(
     if let Some(diagnostics) = diagnostics {
         cmd = cmd.diagnostics_path(diagnostics)
     };
+    if let Some(path) = component_graph.component_graph {
+        cmd = cmd.component_graph_path(path);
+        for krate in component_graph.component_graph_crates {
+            cmd = cmd.component_graph_crate(krate);
+        }
+        for prefix in component_graph.component_graph_route_prefixes {
+            cmd = cmd.component_graph_route_prefix(prefix);
+        }
+    }
     if check {
         cmd = cmd.check();
     }
+    if config_schema {
+        cmd = cmd.config_schema();
+    }
 
     match cmd.execute() {
         Ok(()) => Ok(ExitCode::SUCCESS),
@@ -160,6 +160,11 @@ pub fn run_tests(
                 let trial = code_generation_diagnostics_test(name, data);
                 trials.push(trial);
 
+                if data.configuration.component_graph.is_some() {
+                    let trial = component_graph_test(name, data);
+                    trials.push(trial);
+                }
+
                 let trial = application_code_test(name, data);
                 trials.push(trial);
                 (name.to_owned(), (trials, true))
@@ -592,6 +597,22 @@ struct TestConfig {
     /// Ignore the test if set to `true`.
     #[serde(default)]
     ignore: bool,
+    /// If set, code generation will export the component graph of the application
+    /// and the test runner will compare it with `expectations/component_graph.dot`.
+    #[serde(default)]
+    component_graph: Option<ComponentGraphConfig>,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+/// The filters applied to the exported component graph.
+struct ComponentGraphConfig {
+    /// Only include the components defined in these crates.
+    #[serde(default)]
+    crates: Vec<String>,
+    /// Only include the components used by routes whose path starts with these prefixes.
+    #[serde(default)]
+    route_prefixes: Vec<String>,
 }
 
 #[derive(serde::Deserialize)]
@@ -744,6 +765,23 @@ impl TestData {
         // We manage here the code generator binary for each UI test
         // to avoid code drifting
         {
+            let mut component_graph = String::new();
+            if let Some(config) = &self.configuration.component_graph {
+                component_graph
+                    .push_str("\n        .component_graph_path(\"component_graph.dot\".into())");
+                for krate in &config.crates {
+                    write!(
+                        &mut component_graph,
+                        "\n        .component_graph_crate({krate:?}.into())"
+                    )?;
+                }
+                for prefix in &config.route_prefixes {
+                    write!(
+                        &mut component_graph,
+                        "\n        .component_graph_route_prefix({prefix:?}.into())"
+                    )?;
+                }
+            }
             let main_rs = format!(
                 r##"//! This code is generated by `pavex_test_runner`,
 //! Do NOT modify it manually.
@@ -757,7 +795,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {{
         .color(Color::Always)
         .pavex_cli_path(std::env::var("PAVEX_TEST_CLI_PATH").unwrap().into())
         .generate(blueprint(), ui_test_dir.join("generated_app"))
-        .diagnostics_path("diagnostics.dot".into())
+        .diagnostics_path("diagnostics.dot".into()){}
         .execute();
     match outcome {{
         Ok(_) => {{}},
@@ -770,7 +808,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {{
     Ok(())
 }}
 "##,
-                self.name_hash
+                self.name_hash, component_graph
             );
             persist_if_changed(
                 &self.blueprint_directory().join("src").join("main.rs"),
@@ -952,6 +990,36 @@ fn code_generation_diagnostics_test(test_name: &str, test: &TestData) -> Trial {
     }
 }
 
+fn component_graph_test(test_name: &str, test: &TestData) -> Trial {
+    let test_name = format!("{test_name}::codegen_component_graph");
+    let expectations_directory = test.expectations_directory();
+    let component_graph_snapshot = SnapshotTest::new(
+        expectations_directory.join("component_graph.dot"),
+        test.blueprint_crate_name(),
+    );
+    let actual_component_graph = match fs_err::read_to_string(
+        test.definition_directory.join("component_graph.dot"),
+    ) {
+        Ok(d) => d,
+        Err(e) => {
+            let msg = format!(
+                "Code generation didn't produce a component graph in the expected location.\n{e:?}"
+            );
+            return Trial::test(test_name, move || Err(Failed::from(msg)));
+        }
+    };
+    if component_graph_snapshot
+        .verify(&actual_component_graph)
+        .is_err()
+    {
+        let msg = "The component graph exported by code generation doesn't match what we expected."
+            .to_string();
+        Trial::test(test_name, move || Err(Failed::from(msg)))
+    } else {
+        Trial::test(test_name, || Ok(()))
+    }
+}
+
 fn application_code_test(test_name: &str, test: &TestData) -> Trial {
     let test_name = format!("{test_name}::app_code");
     let expectations_directory = test.expectations_directory();
@@ -77,8 +77,10 @@ fn missing_trait_implementation(
         across all worker threads."
             .into()
     };
+    let bound_help = e.unsatisfied_bound_help();
     let diagnostic = CompilerDiagnostic::builder(e)
         .optional_source(source)
+        .optional_help(bound_help)
         .help(help)
         .build();
     diagnostics.push(diagnostic);
@@ -80,31 +80,40 @@ fn must_be_cloneable(
         ("CloneIfNecessary", "NeverClone")
     };
     let output_type = type_.display_for_error();
+    // We can't always prove that a type doesn't implement `Clone`—e.g. if one of the relevant
+    // bounds can't be resolved. We don't claim more than we know.
+    let doesnt_implement = if e.undetermined.is_some() {
+        "may not implement"
+    } else {
+        "doesn't implement"
+    };
     let error_msg = match kind {
         ComponentKind::Constructor => {
             let callable_path = &computation_db[user_id];
             format!(
-                "`{output_type}` doesn't implement the `Clone` trait, but its constructor, `{callable_path}`, is marked as `{clone_if_necessary}`."
+                "`{output_type}` {doesnt_implement} the `Clone` trait, but its constructor, `{callable_path}`, is marked as `{clone_if_necessary}`."
             )
         }
         ComponentKind::PrebuiltType => {
             format!(
-                "`{output_type}` doesn't implement the `Clone` trait, but it's marked as `{clone_if_necessary}`."
+                "`{output_type}` {doesnt_implement} the `Clone` trait, but it's marked as `{clone_if_necessary}`."
             )
         }
         ComponentKind::ConfigType => {
             format!(
                 "All configuration types must be cloneable.\n\
-                `{output_type}` is a configuration type, but it doesn't implement the `Clone` trait.",
+                `{output_type}` is a configuration type, but it {doesnt_implement} the `Clone` trait.",
             )
         }
         _ => unreachable!(),
     };
+    let bound_help = e.unsatisfied_bound_help();
     let e = anyhow::anyhow!(e).context(error_msg);
     let optional_help = (kind != ComponentKind::ConfigType)
         .then(|| format!("Alternatively, mark it as `{never_clone}`.",));
     let diagnostic = CompilerDiagnostic::builder(e)
         .optional_source(source)
+        .optional_help(bound_help)
         .help(format!(
             "Implement (or derive) the `Clone` trait for `{output_type}`."
         ))
@@ -579,6 +579,7 @@ impl ComponentDb {
         );
         let path = callable;
         let output = output.display_for_error();
+        let bound_help = e.unsatisfied_bound_help();
         let error = anyhow::Error::from(e).context(format!(
             "`{output}` doesn't implement `pavex::IntoResponse`.\n\
             It is returned by `{path}`, one of your {kind}s.\n\
@@ -592,6 +593,7 @@ impl ComponentDb {
         let diagnostic = CompilerDiagnostic::builder(error)
             .optional_source(source)
             .optional_source(def_source)
+            .optional_help(bound_help)
             .help(help)
             .build();
         diagnostics.push(diagnostic);
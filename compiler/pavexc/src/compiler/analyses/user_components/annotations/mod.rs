@@ -171,6 +171,16 @@ pub(super) fn register_imported_components(
                 continue;
             };
 
+            // Lint settings specified on the import act as defaults:
+            // they don't override the ones attached to the component itself.
+            let import_lints = &aux.imports[*import_id].lints;
+            if !import_lints.is_empty() {
+                let lints = aux.id2lints.entry(user_component_id).or_default();
+                for (lint, setting) in import_lints {
+                    lints.entry(*lint).or_insert(*setting);
+                }
+            }
+
             if !matches!(item.inner, ItemEnum::Function(_)) {
                 continue;
             }
@@ -205,12 +205,14 @@ fn _process_blueprint<'a>(
                 created_at,
                 registered_at,
                 relative_to,
+                lints,
             })
             | Component::Import(Import {
                 sources,
                 created_at,
                 relative_to,
                 registered_at,
+                lints,
             }) => {
                 let kind = if matches!(component, Component::Import(_)) {
                     ImportKind::OrderIndependentComponents
@@ -228,6 +230,7 @@ fn _process_blueprint<'a>(
                     created_at: created_at.to_owned(),
                     relative_to: relative_to.to_owned(),
                     registered_at: registered_at.to_owned(),
+                    lints: lints.to_owned(),
                     kind,
                 });
             }
@@ -251,6 +254,7 @@ fn _process_blueprint<'a>(
             //   components.
             registered_at: bp.creation_location.clone(),
             error_handler: None,
+            lints: Default::default(),
         })
     });
     if let Some(fallback) = fallback {
@@ -313,6 +317,9 @@ fn process_route(
         .insert(request_handler_id, current_middleware_chain.to_owned());
     aux.handler_id2error_observer_ids
         .insert(request_handler_id, current_observer_chain.to_owned());
+    if !route.lints.is_empty() {
+        aux.id2lints.insert(request_handler_id, route.lints.clone());
+    }
 
     // Path validation will happen during annotation resolution
 
@@ -366,6 +373,9 @@ fn process_fallback(
         .insert(fallback_id, path_prefix.map(|s| s.to_owned()));
     aux.fallback_id2domain_guard
         .insert(fallback_id, domain_guard);
+    if !fallback.lints.is_empty() {
+        aux.id2lints.insert(fallback_id, fallback.lints.clone());
+    }
 
     process_component_specific_error_handler(
         aux,
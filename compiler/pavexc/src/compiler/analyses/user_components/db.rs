@@ -98,7 +98,7 @@ impl UserComponentDb {
         /// Exit early if there is at least one error.
         macro_rules! exit_on_errors {
             ($var:ident) => {
-                if !$var.is_empty() {
+                if $var.has_errored() {
                     return Err(());
                 }
             };
@@ -1,8 +1,8 @@
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 
 use guppy::PackageId;
 use guppy::graph::PackageGraph;
-use pavex_bp_schema::{CreatedAt, Location, Sources};
+use pavex_bp_schema::{CreatedAt, Lint, LintSetting, Location, Sources};
 use syn::Token;
 use syn::ext::IdentExt;
 use syn::punctuated::Punctuated;
@@ -32,6 +32,8 @@ pub struct UnresolvedImport {
     pub created_at: CreatedAt,
     /// The location at which the import was registered against the blueprint.
     pub registered_at: Location,
+    /// Lint settings for all the imported components.
+    pub lints: BTreeMap<Lint, LintSetting>,
     /// Which component kinds are being imported.
     pub kind: ImportKind,
 }
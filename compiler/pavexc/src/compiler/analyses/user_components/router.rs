@@ -6,14 +6,15 @@ use bimap::BiHashMap;
 use indexmap::{IndexMap, IndexSet};
 use itertools::Itertools;
 use matchit::InsertError;
-use pavex_bp_schema::MethodGuard;
+use miette::Severity;
+use pavex_bp_schema::{Lint, LintSetting, MethodGuard};
 
 use crate::DiagnosticSink;
 use crate::compiler::analyses::computations::ComputationDb;
 use crate::compiler::analyses::domain::DomainGuard;
 use crate::compiler::analyses::route_path::RoutePath;
 use crate::compiler::analyses::user_components::{ScopeGraph, ScopeId, UserComponentId};
-use crate::diagnostic::{self, ComponentKind, TargetSpan};
+use crate::diagnostic::{self, ComponentKind, TargetSpan, lint_severity};
 use crate::diagnostic::{
     CompilerDiagnostic, OptionalLabeledSpanExt, OptionalSourceSpanExt, ZeroBasedOrdinal,
 };
@@ -257,19 +258,20 @@ impl DomainRouter {
         };
 
         let mut domain2path_router = BTreeMap::new();
-        for (domain, components) in domain2components {
+        for (domain, components) in &domain2components {
             let path_router = PathRouter::new(
-                &components,
+                components,
                 db,
                 computation_db,
                 scope_graph,
                 scope_based_fallback_tree,
                 diagnostics,
             )?;
-            domain2path_router.insert(domain, path_router);
+            domain2path_router.insert(domain.clone(), path_router);
         }
 
         Self::detect_domain_conflicts(db, diagnostics)?;
+        Self::detect_shadowed_routes(db, &domain2components, diagnostics);
 
         Ok(Self {
             domain2path_router,
@@ -310,6 +312,116 @@ impl DomainRouter {
         if has_errored { Err(()) } else { Ok(()) }
     }
 
+    /// Find routes that can't be reached for some of the hosts matched by their domain guard.
+    ///
+    /// A host may match multiple domain guards (e.g. `api.example.com` matches both
+    /// `api.example.com` and `{sub}.example.com`), but requests are always routed
+    /// to the most specific one. If the most specific domain doesn't have a route
+    /// for a certain path, the routes registered for that path under the
+    /// less specific domains are shadowed.
+    fn detect_shadowed_routes(
+        aux: &AuxiliaryData,
+        domain2components: &BTreeMap<DomainGuard, Vec<UserComponentId>>,
+        diagnostics: &crate::diagnostic::DiagnosticSink,
+    ) {
+        let mut domain_router = matchit::Router::new();
+        for guard in domain2components.keys() {
+            // Conflicts have already been detected and reported.
+            let _ = domain_router.insert(guard.matchit_pattern(), guard);
+        }
+
+        for (specific, specific_ids) in domain2components {
+            // A host that's matched by `specific`, obtained by replacing each
+            // domain parameter with a placeholder value.
+            let host = sample_host(&specific.matchit_pattern());
+            if domain_router.at(&host).map(|m| *m.value) != Ok(specific) {
+                continue;
+            }
+            let mut specific_paths = matchit::Router::new();
+            for id in specific_ids {
+                if let UserComponent::RequestHandler { router_key, .. } = &aux[id] {
+                    let _ = specific_paths.insert(router_key.path.clone(), ());
+                }
+            }
+
+            for (generic, generic_ids) in domain2components {
+                if generic == specific {
+                    continue;
+                }
+                let mut generic_router = matchit::Router::new();
+                if generic_router
+                    .insert(generic.matchit_pattern(), ())
+                    .is_err()
+                    || generic_router.at(&host).is_err()
+                {
+                    continue;
+                }
+                for id in generic_ids {
+                    let UserComponent::RequestHandler { router_key, .. } = &aux[id] else {
+                        continue;
+                    };
+                    if specific_paths.at(&router_key.path).is_ok() {
+                        continue;
+                    }
+                    let Some(severity) = lint_severity(
+                        aux.id2lints.get(id),
+                        Lint::ShadowedRoute,
+                        LintSetting::Allow,
+                    ) else {
+                        continue;
+                    };
+                    Self::push_shadowed_route_diagnostic(
+                        aux,
+                        *id,
+                        specific,
+                        generic,
+                        severity,
+                        diagnostics,
+                    );
+                }
+            }
+        }
+    }
+
+    fn push_shadowed_route_diagnostic(
+        aux: &AuxiliaryData,
+        route_id: UserComponentId,
+        specific: &DomainGuard,
+        generic: &DomainGuard,
+        severity: Severity,
+        diagnostics: &crate::diagnostic::DiagnosticSink,
+    ) {
+        let UserComponent::RequestHandler { router_key, .. } = &aux[route_id] else {
+            unreachable!()
+        };
+        let route_repr = router_key.diagnostic_repr();
+        let path = &router_key.path;
+        let error = anyhow::anyhow!(
+            "`{route_repr}` can't be reached for requests whose host matches `{specific}`.\n\
+            They are routed to the handlers registered for `{specific}`, since it's more specific \
+            than `{generic}`, and none of those handlers matches `{path}`."
+        );
+        let route_source =
+            diagnostics.annotated(aux.registration_target(&route_id), "The shadowed route");
+        let domain_source = {
+            let location = aux.domain_guard2locations[specific].first().unwrap();
+            diagnostics.source(location).map(|s| {
+                diagnostic::domain_span(s.source(), location)
+                    .labeled("The more specific domain".to_string())
+                    .attach(s)
+            })
+        };
+        let diagnostic = CompilerDiagnostic::builder(error)
+            .optional_source(route_source)
+            .optional_source(domain_source)
+            .severity(severity)
+            .help(format!(
+                "Register a route for `{path}` under `{specific}` if you want to serve \
+                it for those hosts too."
+            ));
+        diagnostics.push(diagnostic.build());
+    }
+
     fn push_domain_conflict_diagnostic(
         aux: &AuxiliaryData,
         domain_1: &DomainGuard,
@@ -418,12 +530,64 @@ impl PathRouter {
                 .or_insert_with(|| LeafRouter::new(fallback_id));
         }
 
+        Self::detect_unreachable_fallbacks(
+            component_ids,
+            root_fallback_id,
+            &path2method_router,
+            aux,
+            computation_db,
+            diagnostics,
+        );
+
         Ok(Self {
             root_fallback_id,
             path2method_router,
         })
     }
 
+    /// Find the fallbacks registered under a path prefix that are never going to be invoked,
+    /// since all the requests they would handle are matched by other routes (e.g. a catch-all).
+    fn detect_unreachable_fallbacks(
+        component_ids: &[UserComponentId],
+        root_fallback_id: UserComponentId,
+        path2method_router: &BTreeMap<String, LeafRouter>,
+        aux: &AuxiliaryData,
+        computation_db: &ComputationDb,
+        diagnostics: &DiagnosticSink,
+    ) {
+        let reachable: BTreeSet<_> = path2method_router
+            .values()
+            .map(|r| r.fallback_id)
+            .chain(std::iter::once(root_fallback_id))
+            .collect();
+        for id in component_ids {
+            let UserComponent::Fallback { .. } = &aux[id] else {
+                continue;
+            };
+            let Some(path_prefix) = &aux.fallback_id2path_prefix[id] else {
+                continue;
+            };
+            if reachable.contains(id) {
+                continue;
+            }
+            let Some(severity) = lint_severity(
+                aux.id2lints.get(id),
+                Lint::UnreachableFallback,
+                LintSetting::Allow,
+            ) else {
+                continue;
+            };
+            push_unreachable_fallback_diagnostic(
+                aux,
+                computation_db,
+                *id,
+                path_prefix,
+                severity,
+                diagnostics,
+            );
+        }
+    }
+
     /// Examine the registered paths and methods guards to make sure that we don't
     /// have any conflicts—i.e. multiple handlers registered for the same path+method combination.
     fn detect_method_conflicts(
@@ -867,6 +1031,57 @@ static METHODS: [&str; 9] = [
     "GET", "POST", "PUT", "DELETE", "PATCH", "HEAD", "OPTIONS", "CONNECT", "TRACE",
 ];
 
+fn push_unreachable_fallback_diagnostic(
+    db: &AuxiliaryData,
+    computation_db: &ComputationDb,
+    fallback_id: UserComponentId,
+    path_prefix: &str,
+    severity: Severity,
+    diagnostics: &crate::diagnostic::DiagnosticSink,
+) {
+    let fallback = {
+        let mut s = String::new();
+        computation_db[fallback_id].render_for_error(&mut s);
+        s
+    };
+    let error = anyhow::anyhow!(
+        "`{fallback}` is never going to be invoked.\n\
+        You registered it as the fallback handler for all unmatched incoming requests with a path \
+        that begins in `{path_prefix}`, but all those requests are already matched by another route."
+    );
+    let source = diagnostics.annotated(
+        db.registration_target(&fallback_id),
+        "The unreachable fallback",
+    );
+    let diagnostic = CompilerDiagnostic::builder(error)
+        .optional_source(source)
+        .severity(severity)
+        .help(
+            "Remove the fallback or, if it's intentional, remove the `.warn`/`.deny` call \
+            on `Lint::UnreachableFallback`."
+                .into(),
+        );
+    diagnostics.push(diagnostic.build());
+}
+
+/// Replace each parameter in a `matchit` pattern with a placeholder value.
+fn sample_host(pattern: &str) -> String {
+    let mut host = String::with_capacity(pattern.len());
+    let mut in_parameter = false;
+    for c in pattern.chars() {
+        match c {
+            '{' => {
+                in_parameter = true;
+                host.push('x');
+            }
+            '}' => in_parameter = false,
+            _ if !in_parameter => host.push(c),
+            _ => {}
+        }
+    }
+    host
+}
+
 fn push_fallback_ambiguity_diagnostic(
     db: &AuxiliaryData,
     computation_db: &ComputationDb,
@@ -24,8 +24,10 @@ use crate::compiler::analyses::processing_pipeline::RequestHandlerPipeline;
 use crate::compiler::analyses::router::Router;
 use crate::compiler::analyses::unused::detect_unused;
 use crate::compiler::analyses::user_components::UserComponentDb;
+use crate::compiler::component_graph::{ComponentGraph, ComponentGraphFilter, component_graph};
+use crate::compiler::config_schema::ConfigSchema;
 use crate::compiler::generated_app::GeneratedApp;
-use crate::compiler::{codegen, path_parameters};
+use crate::compiler::{clones, codegen, path_parameters, serde_shapes};
 use crate::diagnostic::DiagnosticSink;
 use crate::rustdoc::CrateCollection;
 
@@ -43,6 +45,7 @@ pub struct App {
     framework_item_db: FrameworkItemDb,
     application_state: ApplicationState,
     application_config: ApplicationConfig,
+    config_schema: ConfigSchema,
     codegen_deps: HashMap<String, guppy::PackageId>,
     component_db: ComponentDb,
     computation_db: ComputationDb,
@@ -141,6 +144,20 @@ impl App {
             &krate_collection,
             &diagnostics,
         );
+        serde_shapes::verify_serde_shapes(
+            &handler_id2pipeline,
+            &computation_db,
+            &component_db,
+            &krate_collection,
+            &diagnostics,
+        );
+        clones::lint_clones(
+            &handler_id2pipeline,
+            &computation_db,
+            &component_db,
+            &krate_collection,
+            &diagnostics,
+        );
         let application_state = ApplicationState::new(
             &handler_id2pipeline,
             &framework_item_db,
@@ -182,6 +199,7 @@ impl App {
             &diagnostics,
         );
         exit_on_errors!(diagnostics);
+        let config_schema = ConfigSchema::new(&application_config, &krate_collection);
         Ok((
             Self {
                 package_graph,
@@ -193,6 +211,7 @@ impl App {
                 framework_item_db,
                 application_state,
                 application_config,
+                config_schema,
                 codegen_deps,
             },
             diagnostics,
@@ -223,6 +242,7 @@ impl App {
             &package_ids2deps,
             &self.application_state,
             &self.application_config,
+            &self.config_schema.secret_keys(),
             &self.codegen_deps,
             &self.component_db,
             &self.computation_db,
@@ -231,6 +251,8 @@ impl App {
         Ok(GeneratedApp {
             lib_rs,
             cargo_toml,
+            config_schema: self.config_schema.clone(),
+            emit_config_schema: false,
             package_graph: self.package_graph.clone(),
         })
     }
@@ -276,6 +298,20 @@ impl App {
             application_state: application_state_graph,
         }
     }
+
+    /// A deduplicated, application-wide view of the dependencies between components,
+    /// restricted to the subset of the application matched by `filter`.
+    pub fn component_graph(&self, filter: &ComponentGraphFilter) -> ComponentGraph {
+        component_graph(
+            &self.router,
+            &self.handler_id2pipeline,
+            &self.application_state_call_graph.call_graph.call_graph,
+            &self.component_db,
+            &self.computation_db,
+            &self.package_graph,
+            filter,
+        )
+    }
 }
 
 /// A representation of an `App` geared towards debugging and testing.
@@ -0,0 +1,404 @@
+use anyhow::anyhow;
+use indexmap::{IndexMap, IndexSet};
+use itertools::Itertools;
+use miette::Severity;
+use pavex_bp_schema::{Lifecycle, Lint, LintSetting};
+use petgraph::Direction;
+use petgraph::visit::EdgeRef;
+use rustdoc_resolver::{TypeAliasResolution, resolve_type};
+use rustdoc_types::{ItemEnum, StructKind, VariantKind};
+
+use crate::compiler::analyses::call_graph::{
+    CallGraphEdgeMetadata, CallGraphNode, InputParameterSource,
+};
+use crate::compiler::analyses::components::component::Component;
+use crate::compiler::analyses::components::{ComponentDb, ComponentId, HydratedComponent};
+use crate::compiler::analyses::computations::ComputationDb;
+use crate::compiler::analyses::processing_pipeline::RequestHandlerPipeline;
+use crate::compiler::analyses::user_components::UserComponentId;
+use crate::compiler::computation::Computation;
+use crate::diagnostic::{CompilerDiagnostic, DiagnosticSink, lint_severity};
+use crate::language::{Callable, PathType, ScalarPrimitive, Type};
+use crate::rustdoc::{CrateCollection, GlobalItemId};
+use crate::utils::comma_separated_list;
+
+use super::path_parameters::consumer_ids;
+use super::serde_shapes::{generic_bindings, type_arguments};
+
+/// Singletons whose estimated size exceeds this threshold, in bytes, are considered "large".
+///
+/// It matches the default threshold used by `clippy::large_types_passed_by_value`.
+const LARGE_SINGLETON_THRESHOLD: usize = 256;
+
+/// Report values that are cloned or copied for every incoming request:
+///
+/// - `CloneIfNecessary` components that had to be cloned to satisfy the borrow checker
+///   ([`Lint::RedundantClone`]);
+/// - large singletons taken by value ([`Lint::LargeSingletonByValue`]).
+///
+/// Both lints are allowed by default.
+#[tracing::instrument(name = "Lint clones", skip_all)]
+pub(crate) fn lint_clones(
+    handler_id2pipeline: &IndexMap<ComponentId, RequestHandlerPipeline>,
+    computation_db: &ComputationDb,
+    component_db: &ComponentDb,
+    krate_collection: &CrateCollection,
+    diagnostics: &DiagnosticSink,
+) {
+    // The same component may be cloned in multiple pipelines, so we collect
+    // all consumers before reporting, to avoid duplicates.
+    let mut cloned_id2consumers: IndexMap<ComponentId, IndexSet<ComponentId>> = IndexMap::new();
+    let mut singleton_id2consumers: IndexMap<ComponentId, IndexSet<ComponentId>> = IndexMap::new();
+    for pipeline in handler_id2pipeline.values() {
+        for graph in pipeline.graph_iter() {
+            let graph = &graph.call_graph;
+            for node_id in graph.node_indices() {
+                match &graph[node_id] {
+                    CallGraphNode::Compute { component_id, .. } => {
+                        let Some(cloned_id) =
+                            cloned_component_id(*component_id, component_db, computation_db)
+                        else {
+                            continue;
+                        };
+                        cloned_id2consumers
+                            .entry(cloned_id)
+                            .or_default()
+                            .extend(consumer_ids(graph, node_id));
+                    }
+                    CallGraphNode::InputParameter {
+                        source: InputParameterSource::Component(component_id),
+                        ..
+                    } => {
+                        if component_db.lifecycle(*component_id) != Lifecycle::Singleton {
+                            continue;
+                        }
+                        let consumers = graph
+                            .edges_directed(node_id, Direction::Outgoing)
+                            .filter(|e| matches!(e.weight(), CallGraphEdgeMetadata::Move))
+                            .filter_map(|e| match &graph[e.target()] {
+                                CallGraphNode::Compute { component_id, .. } => Some(*component_id),
+                                _ => None,
+                            })
+                            // Singletons are moved into the framework-generated state
+                            // of each middleware stage: that doesn't cost a copy.
+                            .filter(|id| user_component_id(*id, component_db).is_some())
+                            .collect_vec();
+                        if consumers.is_empty() {
+                            continue;
+                        }
+                        singleton_id2consumers
+                            .entry(*component_id)
+                            .or_default()
+                            .extend(consumers);
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    for (cloned_id, consumers) in cloned_id2consumers {
+        let origin_id = component_db.derived_from(&cloned_id).unwrap_or(cloned_id);
+        let Some(severity) = lint_severity(
+            component_db.lints(origin_id),
+            Lint::RedundantClone,
+            LintSetting::Allow,
+        ) else {
+            continue;
+        };
+        let Some(type_) = component_db
+            .hydrated_component(cloned_id, computation_db)
+            .output_type()
+            .cloned()
+        else {
+            continue;
+        };
+        report_redundant_clone(
+            origin_id,
+            &type_,
+            &consumers,
+            severity,
+            component_db,
+            computation_db,
+            diagnostics,
+        );
+    }
+
+    for (singleton_id, consumers) in singleton_id2consumers {
+        let origin_id = component_db
+            .derived_from(&singleton_id)
+            .unwrap_or(singleton_id);
+        let Some(severity) = lint_severity(
+            component_db.lints(origin_id),
+            Lint::LargeSingletonByValue,
+            LintSetting::Allow,
+        ) else {
+            continue;
+        };
+        let Some(type_) = component_db
+            .hydrated_component(singleton_id, computation_db)
+            .output_type()
+            .cloned()
+        else {
+            continue;
+        };
+        let size = estimated_size(krate_collection, &type_, 0);
+        if size <= LARGE_SINGLETON_THRESHOLD {
+            continue;
+        }
+        report_large_singleton(
+            origin_id,
+            &type_,
+            size,
+            &consumers,
+            severity,
+            component_db,
+            computation_db,
+            diagnostics,
+        );
+    }
+}
+
+/// If the component invokes `Clone::clone` on the output of another component,
+/// return the id of the cloned component.
+fn cloned_component_id(
+    id: ComponentId,
+    component_db: &ComponentDb,
+    computation_db: &ComputationDb,
+) -> Option<ComponentId> {
+    let Component::Transformer {
+        transformed_component_id,
+        ..
+    } = &component_db[id]
+    else {
+        return None;
+    };
+    let HydratedComponent::Transformer(Computation::Callable(callable), _) =
+        component_db.hydrated_component(id, computation_db)
+    else {
+        return None;
+    };
+    let Callable::TraitMethod(method) = callable.as_ref() else {
+        return None;
+    };
+    (method.path.trait_name == "Clone" && method.path.method_name == "clone")
+        .then_some(*transformed_component_id)
+}
+
+/// A conservative (i.e. lower-bound) estimate of the size of a type, in bytes,
+/// on a 64-bit target.
+///
+/// Padding is ignored, as well as the size of types we can't inspect.
+fn estimated_size(krate_collection: &CrateCollection, type_: &Type, depth: usize) -> usize {
+    // Give up on deeply nested (or recursive) types.
+    if depth > 8 {
+        return 0;
+    }
+    match type_ {
+        Type::ScalarPrimitive(p) => match p {
+            ScalarPrimitive::U8 | ScalarPrimitive::I8 | ScalarPrimitive::Bool => 1,
+            ScalarPrimitive::U16 | ScalarPrimitive::I16 => 2,
+            ScalarPrimitive::U32
+            | ScalarPrimitive::I32
+            | ScalarPrimitive::F32
+            | ScalarPrimitive::Char => 4,
+            ScalarPrimitive::U64
+            | ScalarPrimitive::I64
+            | ScalarPrimitive::F64
+            | ScalarPrimitive::Usize
+            | ScalarPrimitive::Isize => 8,
+            ScalarPrimitive::U128 | ScalarPrimitive::I128 => 16,
+            ScalarPrimitive::Str => 0,
+        },
+        Type::Reference(r) => match r.inner.as_ref() {
+            Type::Slice(_) | Type::ScalarPrimitive(ScalarPrimitive::Str) => 16,
+            _ => 8,
+        },
+        Type::RawPointer(_) | Type::FunctionPointer(_) => 8,
+        Type::Array(a) => a.len * estimated_size(krate_collection, &a.element_type, depth + 1),
+        Type::Tuple(t) => t
+            .elements
+            .iter()
+            .map(|e| estimated_size(krate_collection, e, depth + 1))
+            .sum(),
+        Type::Path(p) => path_size(krate_collection, p, depth),
+        Type::Slice(_) | Type::Generic(_) | Type::TypeAlias(_) => 0,
+    }
+}
+
+fn path_size(krate_collection: &CrateCollection, t: &PathType, depth: usize) -> usize {
+    let path = t.base_type.iter().map(|s| s.as_str()).collect_vec();
+    match path.as_slice() {
+        ["alloc", "boxed", "Box"] | ["alloc", "rc", "Rc"] | ["alloc", "sync", "Arc"] => {
+            return 8;
+        }
+        ["alloc", "string", "String"]
+        | ["alloc", "vec", "Vec"]
+        | ["alloc", "collections", "vec_deque", "VecDeque"]
+        | ["alloc", "collections", "btree", "map", "BTreeMap"]
+        | ["alloc", "collections", "btree", "set", "BTreeSet"] => return 24,
+        ["std", "collections", "hash", "map", "HashMap"]
+        | ["std", "collections", "hash", "set", "HashSet"] => return 48,
+        ["core", "option", "Option"] => {
+            return type_arguments(t)
+                .next()
+                .map(|inner| estimated_size(krate_collection, inner, depth + 1))
+                .unwrap_or_default();
+        }
+        _ => {}
+    }
+
+    let Some(item_id) = t.rustdoc_id else {
+        return 0;
+    };
+    let item = krate_collection.get_item_by_global_type_id(&GlobalItemId {
+        rustdoc_item_id: item_id,
+        package_id: t.package_id.clone(),
+    });
+    let get_item = |id| {
+        krate_collection.get_item_by_global_type_id(&GlobalItemId {
+            rustdoc_item_id: id,
+            package_id: t.package_id.clone(),
+        })
+    };
+    let fields_size = |generics, field_ids: &[rustdoc_types::Id]| -> usize {
+        let bindings = generic_bindings(generics, t);
+        field_ids
+            .iter()
+            .map(|field_id| {
+                let field = get_item(*field_id);
+                let ItemEnum::StructField(field_type) = &field.inner else {
+                    return 0;
+                };
+                resolve_type(
+                    field_type,
+                    &t.package_id,
+                    krate_collection,
+                    &bindings,
+                    TypeAliasResolution::ResolveThrough,
+                )
+                .map(|ty| estimated_size(krate_collection, &ty, depth + 1))
+                .unwrap_or_default()
+            })
+            .sum()
+    };
+    match &item.inner {
+        ItemEnum::Struct(s) => match &s.kind {
+            StructKind::Unit => 0,
+            StructKind::Tuple(fields) => {
+                fields_size(&s.generics, &fields.iter().flatten().copied().collect_vec())
+            }
+            StructKind::Plain { fields, .. } => fields_size(&s.generics, fields),
+        },
+        ItemEnum::Enum(e) => e
+            .variants
+            .iter()
+            .map(|variant_id| {
+                let variant = get_item(*variant_id);
+                let ItemEnum::Variant(v) = &variant.inner else {
+                    return 0;
+                };
+                match &v.kind {
+                    VariantKind::Plain => 0,
+                    VariantKind::Tuple(fields) => {
+                        fields_size(&e.generics, &fields.iter().flatten().copied().collect_vec())
+                    }
+                    VariantKind::Struct { fields, .. } => fields_size(&e.generics, fields),
+                }
+            })
+            .max()
+            .unwrap_or_default(),
+        _ => 0,
+    }
+}
+
+fn report_redundant_clone(
+    cloned_id: ComponentId,
+    type_: &Type,
+    consumers: &IndexSet<ComponentId>,
+    severity: Severity,
+    component_db: &ComponentDb,
+    computation_db: &ComputationDb,
+    diagnostics: &DiagnosticSink,
+) {
+    let consumers = consumer_names(consumers, component_db, computation_db);
+    let error = anyhow!(
+        "`{type_:?}` is cloned for every incoming request that requires it, in order to satisfy \
+        the borrow checker.\n\
+        The cloned value is consumed by {consumers}."
+    );
+    let source = component_db.registration_span(
+        cloned_id,
+        diagnostics,
+        "The cloned type was registered here".into(),
+    );
+    let d = CompilerDiagnostic::builder(error)
+        .optional_source(source)
+        .severity(severity)
+        .help(format!(
+            "Take `&{type_:?}` as input instead of `{type_:?}`, if possible, \
+            to avoid the clone."
+        ))
+        .build();
+    diagnostics.push(d);
+}
+
+#[allow(clippy::too_many_arguments)]
+fn report_large_singleton(
+    singleton_id: ComponentId,
+    type_: &Type,
+    size: usize,
+    consumers: &IndexSet<ComponentId>,
+    severity: Severity,
+    component_db: &ComponentDb,
+    computation_db: &ComputationDb,
+    diagnostics: &DiagnosticSink,
+) {
+    let consumers = consumer_names(consumers, component_db, computation_db);
+    let error = anyhow!(
+        "`{type_:?}` is a singleton, but it's taken by value by {consumers}.\n\
+        It must be copied or cloned for every incoming request that requires it, \
+        and it's large: at least {size} bytes."
+    );
+    let source = component_db.registration_span(
+        singleton_id,
+        diagnostics,
+        "The singleton was registered here".into(),
+    );
+    let d = CompilerDiagnostic::builder(error)
+        .optional_source(source)
+        .severity(severity)
+        .help(format!(
+            "Take `&{type_:?}` as input instead of `{type_:?}`, or wrap it in an `Arc`."
+        ))
+        .build();
+    diagnostics.push(d);
+}
+
+/// A human-readable list of the user components that consume a value.
+fn consumer_names(
+    consumers: &IndexSet<ComponentId>,
+    component_db: &ComponentDb,
+    computation_db: &ComputationDb,
+) -> String {
+    let names = consumers
+        .iter()
+        .filter_map(|id| {
+            let user_id = user_component_id(*id, component_db)?;
+            Some(format!("`{}`", computation_db[user_id]))
+        })
+        .unique()
+        .collect_vec();
+    if names.is_empty() {
+        return "other components".into();
+    }
+    let mut buffer = String::new();
+    comma_separated_list(&mut buffer, names.iter(), |n| n.clone(), "and").unwrap();
+    buffer
+}
+
+/// The user component a component was derived from, if any.
+fn user_component_id(id: ComponentId, component_db: &ComponentDb) -> Option<UserComponentId> {
+    let id = component_db.derived_from(&id).unwrap_or(id);
+    component_db.user_component_id(id)
+}
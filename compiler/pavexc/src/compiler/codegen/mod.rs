@@ -12,7 +12,7 @@ use quote::quote;
 use router::codegen_router;
 use state::{
     define_application_config, define_application_state, define_application_state_error,
-    get_application_state_new, get_application_state_private_new,
+    get_application_state_new, get_application_state_private_new, impl_config_secrets,
 };
 use syn::{ItemFn, ItemStruct};
 
@@ -47,6 +47,7 @@ pub(crate) fn codegen_app(
     package_id2name: &BiHashMap<PackageId, String>,
     application_state: &ApplicationState,
     application_config: &ApplicationConfig,
+    secret_keys: &[String],
     codegen_deps: &HashMap<String, PackageId>,
     component_db: &ComponentDb,
     computation_db: &ComputationDb,
@@ -56,6 +57,7 @@ pub(crate) fn codegen_app(
     let application_state_def = define_application_state(application_state, package_id2name);
     let application_config_def =
         define_application_config(application_config, package_id2name, &sdk_deps);
+    let impl_config_secrets = impl_config_secrets(secret_keys, &sdk_deps);
     if tracing::event_enabled!(tracing::Level::TRACE) {
         eprintln!(
             "Application state definition:\n{}",
@@ -132,6 +134,7 @@ pub(crate) fn codegen_app(
         #alloc_extern_import
         #define_server_state
         #application_config_def
+        #impl_config_secrets
         #application_state_def
         impl ApplicationState {
             #application_state_new
@@ -4,7 +4,7 @@ use bimap::BiHashMap;
 use guppy::PackageId;
 use indexmap::IndexMap;
 use quote::{format_ident, quote};
-use syn::{ItemEnum, ItemFn, ItemStruct};
+use syn::{ItemEnum, ItemFn, ItemImpl, ItemStruct};
 
 use crate::compiler::{
     analyses::{
@@ -75,6 +75,18 @@ pub(super) fn define_application_config(
     .unwrap()
 }
 
+/// Implement `pavex::config::ConfigSecrets` for `ApplicationConfig`, to let
+/// `ConfigLoader::explain` redact the values of secret keys.
+pub(super) fn impl_config_secrets(secret_keys: &[String], sdk_deps: &ServerSdkDeps) -> ItemImpl {
+    let pavex = sdk_deps.pavex_ident();
+    syn::parse2(quote! {
+        impl #pavex::config::ConfigSecrets for ApplicationConfig {
+            const SECRET_KEYS: &'static [&'static str] = &[#(#secret_keys),*];
+        }
+    })
+    .unwrap()
+}
+
 pub(super) fn define_application_state_error(
     error_types: &IndexMap<String, Type>,
     package_id2name: &BiHashMap<PackageId, String>,
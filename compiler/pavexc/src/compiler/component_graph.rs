@@ -0,0 +1,548 @@
+use std::fmt::Write as _;
+use std::path::Path;
+
+use ahash::{HashSet, HashSetExt};
+use guppy::PackageId;
+use guppy::graph::PackageGraph;
+use indexmap::{IndexMap, IndexSet};
+use itertools::Itertools;
+use pavex_bp_schema::Lifecycle;
+use persist_if_changed::persist_if_changed;
+use petgraph::Direction;
+use petgraph::graph::NodeIndex;
+use petgraph::visit::EdgeRef;
+use serde::Serialize;
+
+use crate::compiler::analyses::call_graph::{
+    CallGraphEdgeMetadata, CallGraphNode, InputParameterSource, RawCallGraph,
+};
+use crate::compiler::analyses::components::component::Component;
+use crate::compiler::analyses::components::{ComponentDb, ComponentId, HydratedComponent};
+use crate::compiler::analyses::computations::ComputationDb;
+use crate::compiler::analyses::processing_pipeline::RequestHandlerPipeline;
+use crate::compiler::analyses::router::Router;
+use crate::compiler::computation::Computation;
+use crate::language::Type;
+
+/// An application-wide view of the dependencies between components.
+///
+/// Unlike the call graphs in [`AppDiagnostics`](crate::compiler::app::AppDiagnostics),
+/// there is a single node for each component, no matter how many request handlers rely on it.
+#[derive(Debug, Clone, Serialize)]
+pub struct ComponentGraph {
+    pub nodes: Vec<ComponentNode>,
+    pub edges: Vec<ComponentEdge>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ComponentNode {
+    /// The index of this node in [`ComponentGraph::nodes`].
+    pub id: usize,
+    /// The path of the underlying callable or, for prebuilt and configuration types,
+    /// the path of the type itself.
+    pub label: String,
+    pub kind: ComponentNodeKind,
+    pub lifecycle: Lifecycle,
+    /// The name of the crate the component was defined in.
+    #[serde(rename = "crate")]
+    pub crate_name: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ComponentNodeKind {
+    Constructor,
+    PrebuiltType,
+    ConfigType,
+    RequestHandler,
+    WrappingMiddleware,
+    PreProcessingMiddleware,
+    PostProcessingMiddleware,
+    ErrorHandler,
+    ErrorObserver,
+}
+
+impl std::fmt::Display for ComponentNodeKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            ComponentNodeKind::Constructor => "constructor",
+            ComponentNodeKind::PrebuiltType => "prebuilt type",
+            ComponentNodeKind::ConfigType => "config type",
+            ComponentNodeKind::RequestHandler => "request handler",
+            ComponentNodeKind::WrappingMiddleware => "wrapping middleware",
+            ComponentNodeKind::PreProcessingMiddleware => "pre-processing middleware",
+            ComponentNodeKind::PostProcessingMiddleware => "post-processing middleware",
+            ComponentNodeKind::ErrorHandler => "error handler",
+            ComponentNodeKind::ErrorObserver => "error observer",
+        };
+        f.write_str(s)
+    }
+}
+
+/// The component at `to` takes the output of the component at `from` as one of its inputs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub struct ComponentEdge {
+    pub from: usize,
+    pub to: usize,
+    pub kind: ComponentEdgeKind,
+}
+
+/// How a component uses the output of one of its dependencies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ComponentEdgeKind {
+    /// The output is taken by value.
+    Owned,
+    /// The output is taken by shared reference (`&`).
+    SharedBorrow,
+    /// The output is taken by mutable reference (`&mut`).
+    ExclusiveBorrow,
+}
+
+/// Restrict the [`ComponentGraph`] to a subset of the application.
+///
+/// Empty filters match everything.
+#[derive(Debug, Clone, Default)]
+pub struct ComponentGraphFilter {
+    /// Only keep components defined in one of these crates.
+    ///
+    /// Edges are kept if both their endpoints are kept.
+    pub crates: Vec<String>,
+    /// Only keep the components used by routes whose path starts with one of these prefixes,
+    /// as well as the singletons they depend on.
+    pub route_prefixes: Vec<String>,
+}
+
+/// The serialization formats supported for a [`ComponentGraph`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComponentGraphFormat {
+    /// Graphviz's DOT format.
+    Dot,
+    /// A Mermaid flowchart.
+    Mermaid,
+    Json,
+}
+
+impl ComponentGraphFormat {
+    /// Determine the format from the extension of the output file:
+    /// `.dot`/`.gv` for DOT, `.mmd`/`.mermaid` for Mermaid and `.json` for JSON.
+    pub fn from_path(path: &Path) -> Result<Self, anyhow::Error> {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("dot" | "gv") => Ok(ComponentGraphFormat::Dot),
+            Some("mmd" | "mermaid") => Ok(ComponentGraphFormat::Mermaid),
+            Some("json") => Ok(ComponentGraphFormat::Json),
+            _ => Err(anyhow::anyhow!(
+                "Can't determine the format of the component graph from the extension of `{}`.\n\
+                Use `.dot` or `.gv` for DOT, `.mmd` or `.mermaid` for Mermaid and `.json` for JSON.",
+                path.display()
+            )),
+        }
+    }
+}
+
+impl ComponentGraph {
+    /// Render the graph using the specified format.
+    pub fn render(&self, format: ComponentGraphFormat) -> String {
+        match format {
+            ComponentGraphFormat::Dot => self.dot(),
+            ComponentGraphFormat::Mermaid => self.mermaid(),
+            ComponentGraphFormat::Json => serde_json::to_string_pretty(self)
+                .expect("Failed to serialize the component graph as JSON"),
+        }
+    }
+
+    /// Save the graph to disk.
+    /// The format is determined by the extension of `filepath`—see [`ComponentGraphFormat::from_path`].
+    pub fn persist(&self, filepath: &Path) -> Result<(), anyhow::Error> {
+        let format = ComponentGraphFormat::from_path(filepath)?;
+        persist_if_changed(filepath, self.render(format).as_bytes())?;
+        Ok(())
+    }
+
+    fn dot(&self) -> String {
+        let mut out = String::from("digraph components {\n");
+        for node in &self.nodes {
+            let label = format!("{}\\n{}", node.label, node.annotation());
+            writeln!(out, "    {} [label = \"{}\"]", node.id, dot_escape(&label)).unwrap();
+        }
+        for edge in &self.edges {
+            write!(out, "    {} -> {}", edge.from, edge.to).unwrap();
+            match edge.kind {
+                ComponentEdgeKind::Owned => out.push('\n'),
+                ComponentEdgeKind::SharedBorrow => out.push_str(" [label = \"&\"]\n"),
+                ComponentEdgeKind::ExclusiveBorrow => out.push_str(" [label = \"&mut\"]\n"),
+            }
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    fn mermaid(&self) -> String {
+        let mut out = String::from("flowchart LR\n");
+        for node in &self.nodes {
+            writeln!(
+                out,
+                "    n{}[\"{}<br/>{}\"]",
+                node.id,
+                mermaid_escape(&node.label),
+                mermaid_escape(&node.annotation())
+            )
+            .unwrap();
+        }
+        for edge in &self.edges {
+            match edge.kind {
+                ComponentEdgeKind::Owned => {
+                    writeln!(out, "    n{} --> n{}", edge.from, edge.to).unwrap();
+                }
+                ComponentEdgeKind::SharedBorrow => {
+                    writeln!(out, "    n{} -->|\"#amp;\"| n{}", edge.from, edge.to).unwrap();
+                }
+                ComponentEdgeKind::ExclusiveBorrow => {
+                    writeln!(out, "    n{} -->|\"#amp;mut\"| n{}", edge.from, edge.to).unwrap();
+                }
+            }
+        }
+        out
+    }
+}
+
+impl ComponentNode {
+    fn annotation(&self) -> String {
+        match &self.crate_name {
+            Some(krate) => format!("{}, {}, {krate}", self.kind, self.lifecycle),
+            None => format!("{}, {}", self.kind, self.lifecycle),
+        }
+    }
+}
+
+fn dot_escape(s: &str) -> String {
+    // `\n` is left untouched: it's the line separator in DOT labels.
+    s.replace('"', "\\\"")
+}
+
+fn mermaid_escape(s: &str) -> String {
+    s.replace('&', "#amp;")
+        .replace('"', "#quot;")
+        .replace('<', "#lt;")
+        .replace('>', "#gt;")
+}
+
+/// Build the [`ComponentGraph`] for the application, starting from the call graphs
+/// of its request handlers and the call graph used to build its state.
+pub(crate) fn component_graph(
+    router: &Router,
+    handler_id2pipeline: &IndexMap<ComponentId, RequestHandlerPipeline>,
+    application_state_graph: &RawCallGraph,
+    component_db: &ComponentDb,
+    computation_db: &ComputationDb,
+    package_graph: &PackageGraph,
+    filter: &ComponentGraphFilter,
+) -> ComponentGraph {
+    let mut builder = GraphBuilder {
+        component_db,
+        computation_db,
+        package_graph,
+        key2node: IndexMap::new(),
+    };
+
+    let route_infos = router.route_infos();
+    let mut node_ids = IndexSet::new();
+    let mut edges = IndexSet::new();
+    for (handler_id, pipeline) in handler_id2pipeline {
+        if !filter.route_prefixes.is_empty() {
+            let path = &route_infos[*handler_id].path;
+            if !filter.route_prefixes.iter().any(|p| path.starts_with(p)) {
+                continue;
+            }
+        }
+        for graph in pipeline.graph_iter() {
+            builder.add_graph(&graph.call_graph, &mut node_ids, &mut edges);
+        }
+    }
+
+    let mut state_node_ids = IndexSet::new();
+    let mut state_edges = IndexSet::new();
+    builder.add_graph(
+        application_state_graph,
+        &mut state_node_ids,
+        &mut state_edges,
+    );
+    if filter.route_prefixes.is_empty() {
+        node_ids.extend(state_node_ids);
+        edges.extend(state_edges);
+    } else {
+        // Only keep the singletons that are (transitively) needed by the selected routes.
+        loop {
+            let n_edges = edges.len();
+            for edge in &state_edges {
+                if node_ids.contains(&edge.to) {
+                    node_ids.insert(edge.from);
+                    edges.insert(*edge);
+                }
+            }
+            if edges.len() == n_edges {
+                break;
+            }
+        }
+    }
+
+    // Nodes are sorted by label, to get a stable output that doesn't depend
+    // on the order in which call graphs were visited.
+    let nodes: Vec<_> = builder
+        .key2node
+        .into_values()
+        .sorted_by(|a, b| (&a.label, a.kind).cmp(&(&b.label, b.kind)))
+        .collect();
+    let mut old2new = IndexMap::new();
+    let mut graph = ComponentGraph {
+        nodes: Vec::new(),
+        edges: Vec::new(),
+    };
+    for node in nodes {
+        if !node_ids.contains(&node.id) {
+            continue;
+        }
+        if !filter.crates.is_empty()
+            && !node
+                .crate_name
+                .as_ref()
+                .is_some_and(|c| filter.crates.contains(c))
+        {
+            continue;
+        }
+        let id = graph.nodes.len();
+        old2new.insert(node.id, id);
+        graph.nodes.push(ComponentNode { id, ..node });
+    }
+    for edge in edges {
+        let (Some(from), Some(to)) = (old2new.get(&edge.from), old2new.get(&edge.to)) else {
+            continue;
+        };
+        graph.edges.push(ComponentEdge {
+            from: *from,
+            to: *to,
+            kind: edge.kind,
+        });
+    }
+    graph.edges.sort_by_key(|e| (e.from, e.to));
+    graph
+}
+
+struct GraphBuilder<'a> {
+    component_db: &'a ComponentDb,
+    computation_db: &'a ComputationDb,
+    package_graph: &'a PackageGraph,
+    /// Components are deduplicated using their kind and their label.
+    key2node: IndexMap<(ComponentNodeKind, String), ComponentNode>,
+}
+
+impl GraphBuilder<'_> {
+    /// Add the components in `graph`, and the dependencies between them, to the sets
+    /// of node ids and edges.
+    fn add_graph(
+        &mut self,
+        graph: &RawCallGraph,
+        node_ids: &mut IndexSet<usize>,
+        edges: &mut IndexSet<ComponentEdge>,
+    ) {
+        for index in graph.node_indices() {
+            let Some(to) = self.node_id(graph, index) else {
+                continue;
+            };
+            node_ids.insert(to);
+            if !matches!(graph[index], CallGraphNode::Compute { .. }) {
+                continue;
+            }
+            for edge in graph.edges_directed(index, Direction::Incoming) {
+                let kind = match edge.weight() {
+                    CallGraphEdgeMetadata::Move => ComponentEdgeKind::Owned,
+                    CallGraphEdgeMetadata::SharedBorrow => ComponentEdgeKind::SharedBorrow,
+                    CallGraphEdgeMetadata::ExclusiveBorrow => ComponentEdgeKind::ExclusiveBorrow,
+                    CallGraphEdgeMetadata::HappensBefore => continue,
+                };
+                for from in self.dependencies(graph, edge.source()) {
+                    // E.g. a fallible constructor and the `Ok` branch of its output.
+                    if from != to {
+                        edges.insert(ComponentEdge { from, to, kind });
+                    }
+                }
+            }
+        }
+    }
+
+    /// Find the components behind a node in the call graph, looking through
+    /// the nodes that don't map to a component (e.g. framework-generated transformers
+    /// or branching nodes).
+    fn dependencies(&mut self, graph: &RawCallGraph, start: NodeIndex) -> IndexSet<usize> {
+        let mut dependencies = IndexSet::new();
+        let mut visited = HashSet::new();
+        let mut to_visit = vec![start];
+        while let Some(index) = to_visit.pop() {
+            if !visited.insert(index) {
+                continue;
+            }
+            if let Some(id) = self.node_id(graph, index) {
+                dependencies.insert(id);
+                continue;
+            }
+            to_visit.extend(
+                graph
+                    .edges_directed(index, Direction::Incoming)
+                    .filter(|e| !matches!(e.weight(), CallGraphEdgeMetadata::HappensBefore))
+                    .map(|e| e.source()),
+            );
+        }
+        dependencies
+    }
+
+    fn node_id(&mut self, graph: &RawCallGraph, index: NodeIndex) -> Option<usize> {
+        match &graph[index] {
+            CallGraphNode::Compute { component_id, .. }
+            | CallGraphNode::InputParameter {
+                source: InputParameterSource::Component(component_id),
+                ..
+            } => self.intern(*component_id),
+            CallGraphNode::InputParameter {
+                source: InputParameterSource::External,
+                ..
+            }
+            | CallGraphNode::MatchBranching => None,
+        }
+    }
+
+    /// Return the node id for a component, if it should be represented in the graph.
+    fn intern(&mut self, id: ComponentId) -> Option<usize> {
+        let id = self.component_db.derived_from(&id).unwrap_or(id);
+        let kind = match &self.component_db[id] {
+            Component::RequestHandler { .. } => ComponentNodeKind::RequestHandler,
+            Component::PrebuiltType { .. } => ComponentNodeKind::PrebuiltType,
+            Component::ConfigType { .. } => ComponentNodeKind::ConfigType,
+            Component::WrappingMiddleware { .. } => ComponentNodeKind::WrappingMiddleware,
+            Component::PreProcessingMiddleware { .. } => ComponentNodeKind::PreProcessingMiddleware,
+            Component::PostProcessingMiddleware { .. } => {
+                ComponentNodeKind::PostProcessingMiddleware
+            }
+            Component::ErrorObserver { .. } => ComponentNodeKind::ErrorObserver,
+            Component::Constructor { .. } => ComponentNodeKind::Constructor,
+            Component::Transformer { .. } if self.component_db.is_error_handler(id) => {
+                ComponentNodeKind::ErrorHandler
+            }
+            // Conversions and clones added by the framework.
+            Component::Transformer { .. } => return None,
+        };
+        let hydrated = self
+            .component_db
+            .hydrated_component(id, self.computation_db);
+        let (label, package_id) = match &hydrated {
+            HydratedComponent::PrebuiltType(_) | HydratedComponent::ConfigType(_) => {
+                let ty = hydrated.output_type().unwrap();
+                (ty.display_for_error(), type_package_id(ty))
+            }
+            _ => match hydrated.computation() {
+                Computation::Callable(c) => {
+                    let mut label = String::new();
+                    c.render_for_error(&mut label);
+                    (label, Some(c.package_id().to_owned()))
+                }
+                Computation::PrebuiltType(ty) => (ty.display_for_error(), type_package_id(&ty)),
+                // The `Ok`/`Err` branches of the output of a fallible request handler
+                // or middleware.
+                Computation::MatchResult(_) => return None,
+            },
+        };
+
+        let n_nodes = self.key2node.len();
+        let node = self
+            .key2node
+            .entry((kind, label.clone()))
+            .or_insert_with(|| ComponentNode {
+                id: n_nodes,
+                label,
+                kind,
+                lifecycle: self.component_db.lifecycle(id),
+                crate_name: package_id.and_then(|id| {
+                    self.package_graph
+                        .metadata(&id)
+                        .ok()
+                        .map(|m| m.name().to_owned())
+                }),
+            });
+        Some(node.id)
+    }
+}
+
+fn type_package_id(ty: &Type) -> Option<PackageId> {
+    match ty {
+        Type::Path(p) => Some(p.package_id.clone()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph() -> ComponentGraph {
+        ComponentGraph {
+            nodes: vec![
+                ComponentNode {
+                    id: 0,
+                    label: "app::Pool::new".into(),
+                    kind: ComponentNodeKind::Constructor,
+                    lifecycle: Lifecycle::Singleton,
+                    crate_name: Some("app".into()),
+                },
+                ComponentNode {
+                    id: 1,
+                    label: "app::handler".into(),
+                    kind: ComponentNodeKind::RequestHandler,
+                    lifecycle: Lifecycle::RequestScoped,
+                    crate_name: Some("app".into()),
+                },
+            ],
+            edges: vec![ComponentEdge {
+                from: 0,
+                to: 1,
+                kind: ComponentEdgeKind::SharedBorrow,
+            }],
+        }
+    }
+
+    #[test]
+    fn dot() {
+        let expected = "digraph components {
+    0 [label = \"app::Pool::new\\nconstructor, singleton, app\"]
+    1 [label = \"app::handler\\nrequest handler, request-scoped, app\"]
+    0 -> 1 [label = \"&\"]
+}
+";
+        assert_eq!(graph().render(ComponentGraphFormat::Dot), expected);
+    }
+
+    #[test]
+    fn dot_exclusive_borrow() {
+        let mut graph = graph();
+        graph.edges[0].kind = ComponentEdgeKind::ExclusiveBorrow;
+        let dot = graph.render(ComponentGraphFormat::Dot);
+        assert!(dot.contains("    0 -> 1 [label = \"&mut\"]\n"), "{dot}");
+    }
+
+    #[test]
+    fn mermaid() {
+        let expected = "flowchart LR
+    n0[\"app::Pool::new<br/>constructor, singleton, app\"]
+    n1[\"app::handler<br/>request handler, request-scoped, app\"]
+    n0 -->|\"#amp;\"| n1
+";
+        assert_eq!(graph().render(ComponentGraphFormat::Mermaid), expected);
+    }
+
+    #[test]
+    fn format_is_inferred_from_the_extension() {
+        let format = |p: &str| ComponentGraphFormat::from_path(Path::new(p)).ok();
+        assert_eq!(format("graph.gv"), Some(ComponentGraphFormat::Dot));
+        assert_eq!(format("graph.mmd"), Some(ComponentGraphFormat::Mermaid));
+        assert_eq!(format("graph.json"), Some(ComponentGraphFormat::Json));
+        assert_eq!(format("graph"), None);
+    }
+}
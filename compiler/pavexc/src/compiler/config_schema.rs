@@ -0,0 +1,1560 @@
+use std::fmt::Write as _;
+use std::path::Path;
+
+use ahash::{HashMap, HashMapExt};
+use indexmap::IndexMap;
+use itertools::Itertools;
+use rustdoc_resolver::{TypeAliasResolution, resolve_type};
+use rustdoc_types::{ItemEnum, StructKind, VariantKind};
+use serde_json::{Value, json};
+
+use crate::compiler::analyses::application_config::ApplicationConfig;
+use crate::language::{PathType, ScalarPrimitive, Type};
+use crate::rustdoc::{CrateCollection, GlobalItemId};
+
+use super::serde_shapes::{derives_deserialize, generic_bindings, serde_arguments, type_arguments};
+
+/// The name of the file containing the JSON Schema for `ApplicationConfig`.
+pub(crate) const SCHEMA_FILENAME: &str = "config.schema.json";
+/// The name of the annotated reference configuration file.
+pub(crate) const REFERENCE_FILENAME: &str = "config.reference.yml";
+
+/// A description of the configuration files accepted by the generated `ApplicationConfig`.
+///
+/// It is derived from the definitions of the configuration types, as seen by `rustdoc`:
+/// it accounts for `serde` attributes (e.g. `rename_all` or `default`) and it
+/// carries over their doc comments.
+/// Types with a custom `serde::Deserialize` implementation are treated as opaque—any
+/// value is accepted.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ConfigSchema {
+    /// One entry for each top-level configuration key.
+    keys: Vec<Field>,
+    /// The named types referenced by the schema.
+    definitions: IndexMap<String, Definition>,
+}
+
+#[derive(Debug, Clone)]
+struct Definition {
+    description: Option<String>,
+    schema: Schema,
+}
+
+#[derive(Debug, Clone)]
+enum Schema {
+    /// Any value is accepted.
+    Any,
+    Null,
+    Boolean,
+    Integer {
+        minimum: Option<u8>,
+    },
+    Number,
+    String,
+    Optional(Box<Schema>),
+    Array {
+        items: Box<Schema>,
+        unique: bool,
+    },
+    Tuple(Vec<Schema>),
+    Map(Box<Schema>),
+    /// A value that must not be disclosed—e.g. a `secrecy::SecretString`.
+    Secret(Box<Schema>),
+    /// A type stored in [`ConfigSchema::definitions`].
+    Ref(String),
+    Object(ObjectSchema),
+    Enum(EnumSchema),
+}
+
+#[derive(Debug, Clone, Default)]
+struct ObjectSchema {
+    fields: Vec<Field>,
+    /// The schemas of `#[serde(flatten)]`ed fields, merged into the parent object.
+    flattened: Vec<Schema>,
+    deny_unknown_fields: bool,
+}
+
+#[derive(Debug, Clone)]
+struct Field {
+    name: String,
+    description: Option<String>,
+    schema: Schema,
+    required: bool,
+    /// The default value, if it is known statically.
+    default: Option<Value>,
+}
+
+#[derive(Debug, Clone)]
+struct EnumSchema {
+    representation: Representation,
+    variants: Vec<Variant>,
+}
+
+/// How `serde` represents an enum—see <https://serde.rs/enum-representations.html>.
+#[derive(Debug, Clone)]
+enum Representation {
+    External,
+    Internal { tag: String },
+    Adjacent { tag: String, content: String },
+    Untagged,
+}
+
+#[derive(Debug, Clone)]
+struct Variant {
+    name: String,
+    description: Option<String>,
+    payload: Payload,
+}
+
+#[derive(Debug, Clone)]
+enum Payload {
+    Unit,
+    Newtype(Schema),
+    Tuple(Vec<Schema>),
+    Struct(ObjectSchema),
+}
+
+impl ConfigSchema {
+    /// Build the schema for all the configuration types in `ApplicationConfig`.
+    #[tracing::instrument(name = "Build configuration schema", skip_all)]
+    pub(crate) fn new(config: &ApplicationConfig, krate_collection: &CrateCollection) -> Self {
+        let mut builder = SchemaBuilder {
+            krate_collection,
+            definitions: IndexMap::new(),
+            type2name: HashMap::new(),
+        };
+        let keys = config
+            .bindings()
+            .iter()
+            .sorted_by_key(|(ident, _)| ident.to_string())
+            .map(|(ident, type_)| {
+                let schema = builder.schema(type_);
+                Field {
+                    name: ident.to_string(),
+                    description: None,
+                    required: !config.should_default(ident) && !schema.is_optional(),
+                    default: None,
+                    schema,
+                }
+            })
+            .collect();
+        Self {
+            keys,
+            definitions: builder.definitions,
+        }
+    }
+
+    /// Save the JSON Schema and the reference configuration file in `directory`.
+    pub(crate) fn persist(
+        &self,
+        directory: &Path,
+        writer: &mut crate::AppWriter,
+    ) -> Result<(), anyhow::Error> {
+        let mut schema = serde_json::to_string_pretty(&self.json_schema())?;
+        schema.push('\n');
+        writer.persist_if_changed(&directory.join(SCHEMA_FILENAME), schema.as_bytes())?;
+        writer.persist_if_changed(
+            &directory.join(REFERENCE_FILENAME),
+            self.reference_yaml().as_bytes(),
+        )?;
+        Ok(())
+    }
+
+    /// A [JSON Schema](https://json-schema.org/draft/2020-12) describing the configuration
+    /// files that can be deserialized into `ApplicationConfig`.
+    pub(crate) fn json_schema(&self) -> Value {
+        let root = ObjectSchema {
+            fields: self.keys.clone(),
+            flattened: vec![],
+            deny_unknown_fields: false,
+        };
+        let mut schema = json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "title": "ApplicationConfig",
+        });
+        merge(&mut schema, root.json());
+        if !self.definitions.is_empty() {
+            let definitions = self
+                .definitions
+                .iter()
+                .map(|(name, definition)| {
+                    let mut schema = definition.schema.json();
+                    if let Some(description) = &definition.description {
+                        merge(&mut schema, json!({ "description": description }));
+                    }
+                    (name.clone(), schema)
+                })
+                .collect::<serde_json::Map<_, _>>();
+            schema["$defs"] = Value::Object(definitions);
+        }
+        schema
+    }
+
+    /// The dotted paths to the configuration values wrapped in a `secrecy` type—e.g.
+    /// `postgres.password`.
+    ///
+    /// `*` stands for any segment—e.g. the keys of a map.
+    /// Arrays are reported as a single value: if any of their elements is secret, the whole
+    /// array is.
+    pub(crate) fn secret_keys(&self) -> Vec<String> {
+        let mut out = Vec::new();
+        let mut path = Vec::new();
+        let mut stack = Vec::new();
+        for field in &self.keys {
+            path.push(field.name.clone());
+            self.collect_secrets(&field.schema, &mut path, &mut stack, &mut out);
+            path.pop();
+        }
+        out.sort();
+        out.dedup();
+        out
+    }
+
+    fn collect_secrets(
+        &self,
+        schema: &Schema,
+        path: &mut Vec<String>,
+        stack: &mut Vec<String>,
+        out: &mut Vec<String>,
+    ) {
+        match schema {
+            Schema::Secret(_) => out.push(path.join(".")),
+            Schema::Optional(inner) | Schema::Array { items: inner, .. } => {
+                self.collect_secrets(inner, path, stack, out)
+            }
+            Schema::Tuple(elements) => {
+                for element in elements {
+                    self.collect_secrets(element, path, stack, out);
+                }
+            }
+            Schema::Map(values) => {
+                path.push("*".into());
+                self.collect_secrets(values, path, stack, out);
+                path.pop();
+            }
+            Schema::Ref(name) => {
+                // Recursive types would otherwise be expanded indefinitely.
+                if stack.contains(name) {
+                    return;
+                }
+                stack.push(name.clone());
+                self.collect_secrets(&self.definitions[name].schema, path, stack, out);
+                stack.pop();
+            }
+            Schema::Object(object) => self.collect_object_secrets(object, path, stack, out),
+            Schema::Enum(EnumSchema {
+                representation,
+                variants,
+            }) => {
+                for variant in variants {
+                    let nested = match representation {
+                        Representation::External => Some(variant.name.clone()),
+                        Representation::Adjacent { content, .. } => Some(content.clone()),
+                        Representation::Internal { .. } | Representation::Untagged => None,
+                    };
+                    if let Some(segment) = &nested {
+                        path.push(segment.clone());
+                    }
+                    match &variant.payload {
+                        Payload::Unit => {}
+                        Payload::Newtype(schema) => self.collect_secrets(schema, path, stack, out),
+                        Payload::Tuple(elements) => {
+                            for element in elements {
+                                self.collect_secrets(element, path, stack, out);
+                            }
+                        }
+                        Payload::Struct(object) => {
+                            self.collect_object_secrets(object, path, stack, out)
+                        }
+                    }
+                    if nested.is_some() {
+                        path.pop();
+                    }
+                }
+            }
+            Schema::Any
+            | Schema::Null
+            | Schema::Boolean
+            | Schema::Integer { .. }
+            | Schema::Number
+            | Schema::String => {}
+        }
+    }
+
+    fn collect_object_secrets(
+        &self,
+        object: &ObjectSchema,
+        path: &mut Vec<String>,
+        stack: &mut Vec<String>,
+        out: &mut Vec<String>,
+    ) {
+        for field in &object.fields {
+            path.push(field.name.clone());
+            self.collect_secrets(&field.schema, path, stack, out);
+            path.pop();
+        }
+        for flattened in &object.flattened {
+            self.collect_secrets(flattened, path, stack, out);
+        }
+    }
+
+    /// An annotated configuration file listing every configuration key, with its
+    /// documentation and its default value (if any).
+    ///
+    /// Required keys are set to a placeholder value. Optional keys without a known default
+    /// are commented out.
+    pub(crate) fn reference_yaml(&self) -> String {
+        let mut out = format!(
+            "# yaml-language-server: $schema={SCHEMA_FILENAME}\n\
+            #\n\
+            # Do NOT edit this file.\n\
+            # It was automatically generated by Pavex and lists all the configuration keys\n\
+            # supported by your application.\n"
+        );
+        let mut stack = Vec::new();
+        for field in &self.keys {
+            out.push('\n');
+            for line in self.entry_lines(field, &mut stack) {
+                out.push_str(&line);
+                out.push('\n');
+            }
+        }
+        out
+    }
+
+    /// The YAML lines for a single key, including its doc comments.
+    fn entry_lines(&self, field: &Field, stack: &mut Vec<String>) -> Vec<String> {
+        let mut lines = Vec::new();
+        let description = field
+            .description
+            .as_deref()
+            .or_else(|| match &field.schema {
+                Schema::Ref(name) => self.definitions[name].description.as_deref(),
+                _ => None,
+            });
+        if let Some(description) = description {
+            lines.extend(
+                description
+                    .lines()
+                    .map(|l| format!("# {l}").trim_end().to_owned()),
+            );
+        }
+        if let Some(allowed) = self.allowed_values(&field.schema) {
+            lines.push(format!("# Allowed values: {allowed}."));
+        }
+        if field.required {
+            lines.push("# Required.".into());
+        }
+        if field.schema.is_secret() {
+            lines.push("# Secret: it's redacted when the configuration is explained.".into());
+        }
+
+        let value = match &field.default {
+            Some(default) => YamlValue::Inline(yaml_scalar(default)),
+            None => self.example(&field.schema, stack),
+        };
+        let commented = !field.required && field.default.is_none();
+        let prefix = if commented { "# " } else { "" };
+        match value {
+            YamlValue::Inline(v) => lines.push(format!("{prefix}{}: {v}", field.name)),
+            YamlValue::Block(block) => {
+                lines.push(format!("{prefix}{}:", field.name));
+                lines.extend(block.into_iter().map(|l| {
+                    if l.is_empty() {
+                        prefix.trim_end().to_owned()
+                    } else {
+                        format!("{prefix}  {l}")
+                    }
+                }));
+            }
+        }
+        lines
+    }
+
+    /// An example value for the given schema, used in the reference configuration file.
+    fn example(&self, schema: &Schema, stack: &mut Vec<String>) -> YamlValue {
+        match schema {
+            Schema::Any | Schema::Null => YamlValue::Inline("~".into()),
+            Schema::Boolean => YamlValue::Inline("false".into()),
+            Schema::Integer { minimum } => YamlValue::Inline(minimum.unwrap_or(0).to_string()),
+            Schema::Number => YamlValue::Inline("0.0".into()),
+            Schema::String => YamlValue::Inline("\"\"".into()),
+            Schema::Optional(inner) | Schema::Secret(inner) => self.example(inner, stack),
+            Schema::Array { .. } | Schema::Tuple(_) => YamlValue::Inline("[]".into()),
+            Schema::Map(_) => YamlValue::Inline("{}".into()),
+            Schema::Ref(name) => {
+                // Don't expand recursive types indefinitely.
+                if stack.contains(name) {
+                    return YamlValue::Inline("{}".into());
+                }
+                stack.push(name.clone());
+                let example = self.example(&self.definitions[name].schema, stack);
+                stack.pop();
+                example
+            }
+            Schema::Object(object) => self.object_example(object, stack),
+            Schema::Enum(e) => {
+                let Some(variant) = e.variants.first() else {
+                    return YamlValue::Inline("~".into());
+                };
+                let tag = YamlValue::Inline(json_string(&variant.name));
+                match (&e.representation, &variant.payload) {
+                    (Representation::External, Payload::Unit) => tag,
+                    (Representation::Untagged, Payload::Unit) => YamlValue::Inline("~".into()),
+                    (Representation::Untagged, Payload::Newtype(s)) => self.example(s, stack),
+                    (Representation::Untagged, Payload::Struct(o)) => self.object_example(o, stack),
+                    (Representation::Internal { tag }, Payload::Struct(o)) => {
+                        let mut block = vec![format!("{tag}: {}", json_string(&variant.name))];
+                        if let YamlValue::Block(fields) = self.object_example(o, stack) {
+                            block.extend(fields);
+                        }
+                        YamlValue::Block(block)
+                    }
+                    (Representation::Internal { tag }, _)
+                    | (Representation::Adjacent { tag, .. }, Payload::Unit) => {
+                        YamlValue::Block(vec![format!("{tag}: {}", json_string(&variant.name))])
+                    }
+                    (Representation::Adjacent { tag, content }, payload) => {
+                        let mut block = vec![format!("{tag}: {}", json_string(&variant.name))];
+                        block.extend(self.payload_example(content, payload, stack));
+                        YamlValue::Block(block)
+                    }
+                    (Representation::External, payload) => {
+                        YamlValue::Block(self.payload_example(&variant.name, payload, stack))
+                    }
+                    (Representation::Untagged, Payload::Tuple(_)) => YamlValue::Inline("[]".into()),
+                }
+            }
+        }
+    }
+
+    fn payload_example(
+        &self,
+        key: &str,
+        payload: &Payload,
+        stack: &mut Vec<String>,
+    ) -> Vec<String> {
+        let value = match payload {
+            Payload::Unit => YamlValue::Inline("~".into()),
+            Payload::Newtype(s) => self.example(s, stack),
+            Payload::Tuple(_) => YamlValue::Inline("[]".into()),
+            Payload::Struct(o) => self.object_example(o, stack),
+        };
+        match value {
+            YamlValue::Inline(v) => vec![format!("{key}: {v}")],
+            YamlValue::Block(block) => std::iter::once(format!("{key}:"))
+                .chain(block.into_iter().map(|l| format!("  {l}")))
+                .collect(),
+        }
+    }
+
+    fn object_example(&self, object: &ObjectSchema, stack: &mut Vec<String>) -> YamlValue {
+        let mut block = Vec::new();
+        for field in &object.fields {
+            block.extend(self.entry_lines(field, stack));
+        }
+        for flattened in &object.flattened {
+            if let YamlValue::Block(fields) = self.example(flattened, stack) {
+                block.extend(fields);
+            }
+        }
+        if block.is_empty() {
+            YamlValue::Inline("{}".into())
+        } else {
+            YamlValue::Block(block)
+        }
+    }
+
+    /// The list of allowed values, if the schema is an enum with unit variants only.
+    fn allowed_values(&self, schema: &Schema) -> Option<String> {
+        match schema {
+            Schema::Optional(inner) => self.allowed_values(inner),
+            Schema::Ref(name) => self.allowed_values(&self.definitions[name].schema),
+            Schema::Enum(EnumSchema {
+                representation: Representation::External,
+                variants,
+            }) if variants.iter().all(|v| matches!(v.payload, Payload::Unit)) => {
+                Some(variants.iter().map(|v| format!("`{}`", v.name)).join(", "))
+            }
+            _ => None,
+        }
+    }
+}
+
+enum YamlValue {
+    /// A value that fits on the same line as its key.
+    Inline(String),
+    /// A nested mapping, one line per entry (without indentation).
+    Block(Vec<String>),
+}
+
+/// Render a scalar default value as YAML.
+fn yaml_scalar(value: &Value) -> String {
+    match value {
+        Value::Null => "~".into(),
+        Value::Array(a) if a.is_empty() => "[]".into(),
+        Value::Object(o) if o.is_empty() => "{}".into(),
+        // JSON is a subset of YAML.
+        v => v.to_string(),
+    }
+}
+
+fn json_string(s: &str) -> String {
+    Value::String(s.to_owned()).to_string()
+}
+
+impl Schema {
+    fn is_optional(&self) -> bool {
+        matches!(self, Schema::Optional(_))
+    }
+
+    fn is_secret(&self) -> bool {
+        match self {
+            Schema::Secret(_) => true,
+            Schema::Optional(inner) => inner.is_secret(),
+            _ => false,
+        }
+    }
+
+    fn json(&self) -> Value {
+        match self {
+            Schema::Any => json!({}),
+            Schema::Null => json!({ "type": "null" }),
+            Schema::Boolean => json!({ "type": "boolean" }),
+            Schema::Integer { minimum: None } => json!({ "type": "integer" }),
+            Schema::Integer {
+                minimum: Some(minimum),
+            } => json!({ "type": "integer", "minimum": minimum }),
+            Schema::Number => json!({ "type": "number" }),
+            Schema::String => json!({ "type": "string" }),
+            Schema::Optional(inner) => json!({ "anyOf": [inner.json(), { "type": "null" }] }),
+            Schema::Secret(inner) => {
+                let mut schema = inner.json();
+                merge(&mut schema, json!({ "writeOnly": true }));
+                schema
+            }
+            Schema::Array { items, unique } => {
+                let mut schema = json!({ "type": "array", "items": items.json() });
+                if *unique {
+                    schema["uniqueItems"] = Value::Bool(true);
+                }
+                schema
+            }
+            Schema::Tuple(elements) => json!({
+                "type": "array",
+                "prefixItems": elements.iter().map(Schema::json).collect_vec(),
+                "minItems": elements.len(),
+                "maxItems": elements.len(),
+            }),
+            Schema::Map(value) => json!({ "type": "object", "additionalProperties": value.json() }),
+            Schema::Ref(name) => json!({ "$ref": format!("#/$defs/{name}") }),
+            Schema::Object(object) => object.json(),
+            Schema::Enum(e) => e.json(),
+        }
+    }
+}
+
+impl ObjectSchema {
+    fn json(&self) -> Value {
+        let properties = self
+            .fields
+            .iter()
+            .map(|field| (field.name.clone(), field.json()))
+            .collect::<serde_json::Map<_, _>>();
+        let required = self
+            .fields
+            .iter()
+            .filter(|f| f.required)
+            .map(|f| f.name.as_str())
+            .collect_vec();
+        let mut schema = json!({ "type": "object", "properties": properties });
+        if !required.is_empty() {
+            schema["required"] = json!(required);
+        }
+        if self.flattened.is_empty() {
+            if self.deny_unknown_fields {
+                schema["additionalProperties"] = Value::Bool(false);
+            }
+            schema
+        } else {
+            let mut all_of = vec![schema];
+            all_of.extend(self.flattened.iter().map(Schema::json));
+            json!({ "allOf": all_of })
+        }
+    }
+}
+
+impl Field {
+    fn json(&self) -> Value {
+        let mut schema = self.schema.json();
+        if let Some(description) = &self.description {
+            merge(&mut schema, json!({ "description": description }));
+        }
+        if let Some(default) = &self.default {
+            merge(&mut schema, json!({ "default": default }));
+        }
+        schema
+    }
+}
+
+impl EnumSchema {
+    fn json(&self) -> Value {
+        if matches!(self.representation, Representation::External)
+            && self
+                .variants
+                .iter()
+                .all(|v| matches!(v.payload, Payload::Unit))
+        {
+            let names = self.variants.iter().map(|v| v.name.as_str()).collect_vec();
+            return json!({ "type": "string", "enum": names });
+        }
+        let variants = self
+            .variants
+            .iter()
+            .map(|variant| {
+                let mut schema = self.variant_json(variant);
+                if let Some(description) = &variant.description {
+                    merge(&mut schema, json!({ "description": description }));
+                }
+                schema
+            })
+            .collect_vec();
+        json!({ "oneOf": variants })
+    }
+
+    fn variant_json(&self, variant: &Variant) -> Value {
+        let name = &variant.name;
+        let payload = || match &variant.payload {
+            Payload::Unit => Schema::Null.json(),
+            Payload::Newtype(s) => s.json(),
+            Payload::Tuple(elements) => Schema::Tuple(elements.clone()).json(),
+            Payload::Struct(object) => object.json(),
+        };
+        let tagged = |tag: &str, extra: Option<(&str, Value)>| {
+            let mut properties = serde_json::Map::new();
+            properties.insert(tag.to_owned(), json!({ "const": name }));
+            let mut required = vec![tag.to_owned()];
+            if let Some((key, value)) = extra {
+                properties.insert(key.to_owned(), value);
+                required.push(key.to_owned());
+            }
+            json!({ "type": "object", "properties": properties, "required": required })
+        };
+        match (&self.representation, &variant.payload) {
+            (Representation::External, Payload::Unit) => json!({ "const": name }),
+            (Representation::External, _) => {
+                let mut schema = tagged(name, None);
+                // The variant name is the key, the payload is the value.
+                schema["properties"][name] = payload();
+                schema["additionalProperties"] = Value::Bool(false);
+                schema
+            }
+            (Representation::Internal { tag }, Payload::Unit) => tagged(tag, None),
+            (Representation::Internal { tag }, _) => {
+                json!({ "allOf": [tagged(tag, None), payload()] })
+            }
+            (Representation::Adjacent { tag, .. }, Payload::Unit) => tagged(tag, None),
+            (Representation::Adjacent { tag, content }, _) => {
+                tagged(tag, Some((content, payload())))
+            }
+            (Representation::Untagged, _) => payload(),
+        }
+    }
+}
+
+/// Add the keys of `extra` to `schema`.
+fn merge(schema: &mut Value, extra: Value) {
+    if let (Value::Object(schema), Value::Object(extra)) = (schema, extra) {
+        schema.extend(extra);
+    }
+}
+
+struct SchemaBuilder<'a> {
+    krate_collection: &'a CrateCollection,
+    definitions: IndexMap<String, Definition>,
+    /// The name of the definition associated with each named type we have already visited.
+    type2name: HashMap<Type, String>,
+}
+
+impl SchemaBuilder<'_> {
+    fn schema(&mut self, type_: &Type) -> Schema {
+        match type_ {
+            Type::ScalarPrimitive(p) => primitive_schema(p),
+            Type::Reference(r) => self.schema(&r.inner),
+            Type::Slice(s) => Schema::Array {
+                items: Box::new(self.schema(&s.element_type)),
+                unique: false,
+            },
+            Type::Array(a) => Schema::Array {
+                items: Box::new(self.schema(&a.element_type)),
+                unique: false,
+            },
+            Type::Tuple(t) if t.elements.is_empty() => Schema::Null,
+            Type::Tuple(t) => Schema::Tuple(t.elements.iter().map(|e| self.schema(e)).collect()),
+            Type::Path(t) => self.path_schema(t, type_),
+            Type::TypeAlias(_)
+            | Type::RawPointer(_)
+            | Type::FunctionPointer(_)
+            | Type::Generic(_) => Schema::Any,
+        }
+    }
+
+    fn path_schema(&mut self, t: &PathType, type_: &Type) -> Schema {
+        if let Some(schema) = self.well_known_schema(t) {
+            return schema;
+        }
+        if let Some(name) = self.type2name.get(type_) {
+            return Schema::Ref(name.clone());
+        }
+
+        let Some(item_id) = t.rustdoc_id else {
+            return Schema::Any;
+        };
+        let item = self
+            .krate_collection
+            .get_item_by_global_type_id(&GlobalItemId {
+                rustdoc_item_id: item_id,
+                package_id: t.package_id.clone(),
+            })
+            .into_owned();
+        let (impls, generics) = match &item.inner {
+            ItemEnum::Struct(s) => (&s.impls, &s.generics),
+            ItemEnum::Enum(e) => (&e.impls, &e.generics),
+            _ => return Schema::Any,
+        };
+        if !derives_deserialize(self.krate_collection, t, impls) {
+            return Schema::Any;
+        }
+        let container_attrs = serde_arguments(&item.attrs);
+        let has = |key: &str| container_attrs.iter().any(|(k, _)| k == key);
+        let value = |key: &str| {
+            container_attrs
+                .iter()
+                .find(|(k, _)| k == key)
+                .and_then(|(_, v)| v.clone())
+        };
+        if has("from") || has("try_from") {
+            // The shape of the serialized data is determined by another type.
+            return Schema::Any;
+        }
+        let bindings = generic_bindings(generics, t);
+        let krate_collection = self.krate_collection;
+        let resolve = |field_type: &rustdoc_types::Type| {
+            resolve_type(
+                field_type,
+                &t.package_id,
+                krate_collection,
+                &bindings,
+                TypeAliasResolution::ResolveThrough,
+            )
+            .ok()
+            .filter(|ty| !ty.is_a_template())
+        };
+        let get_item = |id| {
+            krate_collection
+                .get_item_by_global_type_id(&GlobalItemId {
+                    rustdoc_item_id: id,
+                    package_id: t.package_id.clone(),
+                })
+                .into_owned()
+        };
+
+        // Tuple and unit structs are represented inline.
+        if let ItemEnum::Struct(s) = &item.inner {
+            match &s.kind {
+                StructKind::Unit => return Schema::Null,
+                StructKind::Tuple(fields) => {
+                    let fields = fields
+                        .iter()
+                        .map(|f| {
+                            f.and_then(|id| match get_item(id).inner {
+                                ItemEnum::StructField(ty) => resolve(&ty),
+                                _ => None,
+                            })
+                        })
+                        .collect_vec();
+                    return match fields.as_slice() {
+                        // Newtypes are transparent.
+                        [Some(inner)] => self.schema(inner),
+                        _ if fields.iter().all(Option::is_some) => {
+                            let elements = fields.into_iter().flatten().collect_vec();
+                            Schema::Tuple(elements.iter().map(|e| self.schema(e)).collect())
+                        }
+                        _ => Schema::Any,
+                    };
+                }
+                StructKind::Plain { .. } => {}
+            }
+        }
+
+        // Reserve a name before looking at the fields, to handle recursive types.
+        let name = self.definition_name(item.name.as_deref().unwrap_or("Unknown"));
+        self.type2name.insert(type_.to_owned(), name.clone());
+
+        let rename_all = value("rename_all");
+        let schema = match &item.inner {
+            ItemEnum::Struct(s) => {
+                let StructKind::Plain {
+                    fields,
+                    has_stripped_fields,
+                } = &s.kind
+                else {
+                    unreachable!()
+                };
+                if *has_stripped_fields {
+                    Schema::Any
+                } else {
+                    let mut object = self.object_schema(
+                        fields,
+                        rename_all.as_deref(),
+                        has("default"),
+                        &resolve,
+                        &get_item,
+                    );
+                    object.deny_unknown_fields = has("deny_unknown_fields");
+                    if has("transparent") {
+                        match object.fields.pop() {
+                            Some(field) if object.fields.is_empty() => field.schema,
+                            _ => Schema::Any,
+                        }
+                    } else {
+                        Schema::Object(object)
+                    }
+                }
+            }
+            ItemEnum::Enum(e) => {
+                let representation = match (value("tag"), value("content")) {
+                    _ if has("untagged") => Representation::Untagged,
+                    (Some(tag), Some(content)) => Representation::Adjacent { tag, content },
+                    (Some(tag), None) => Representation::Internal { tag },
+                    _ => Representation::External,
+                };
+                let rename_all_fields = value("rename_all_fields");
+                let variants = e
+                    .variants
+                    .iter()
+                    .filter_map(|id| {
+                        let variant = get_item(*id);
+                        let ItemEnum::Variant(v) = &variant.inner else {
+                            return None;
+                        };
+                        let attrs = serde_arguments(&variant.attrs);
+                        let has = |key: &str| attrs.iter().any(|(k, _)| k == key);
+                        if has("skip") || has("skip_deserializing") || has("other") {
+                            return None;
+                        }
+                        let original = variant.name.clone().unwrap_or_default();
+                        let name = attrs
+                            .iter()
+                            .find(|(k, _)| k == "rename")
+                            .and_then(|(_, v)| v.clone())
+                            .unwrap_or_else(|| rename_variant(&original, rename_all.as_deref()));
+                        let fields_rename_all = attrs
+                            .iter()
+                            .find(|(k, _)| k == "rename_all")
+                            .and_then(|(_, v)| v.clone())
+                            .or_else(|| rename_all_fields.clone());
+                        let payload = match &v.kind {
+                            VariantKind::Plain => Payload::Unit,
+                            VariantKind::Tuple(fields) => {
+                                let mut schemas = fields
+                                    .iter()
+                                    .map(|f| {
+                                        let ty = f.and_then(|id| match get_item(id).inner {
+                                            ItemEnum::StructField(ty) => resolve(&ty),
+                                            _ => None,
+                                        });
+                                        ty.map_or(Schema::Any, |ty| self.schema(&ty))
+                                    })
+                                    .collect_vec();
+                                if schemas.len() == 1 {
+                                    Payload::Newtype(schemas.pop().unwrap())
+                                } else {
+                                    Payload::Tuple(schemas)
+                                }
+                            }
+                            VariantKind::Struct { fields, .. } => {
+                                Payload::Struct(self.object_schema(
+                                    fields,
+                                    fields_rename_all.as_deref(),
+                                    false,
+                                    &resolve,
+                                    &get_item,
+                                ))
+                            }
+                        };
+                        Some(Variant {
+                            name,
+                            description: variant.docs.as_deref().and_then(summary),
+                            payload,
+                        })
+                    })
+                    .collect();
+                Schema::Enum(EnumSchema {
+                    representation,
+                    variants,
+                })
+            }
+            _ => unreachable!(),
+        };
+        self.definitions.insert(
+            name.clone(),
+            Definition {
+                description: item.docs.as_deref().and_then(summary),
+                schema,
+            },
+        );
+        Schema::Ref(name)
+    }
+
+    fn object_schema(
+        &mut self,
+        field_ids: &[rustdoc_types::Id],
+        rename_all: Option<&str>,
+        container_default: bool,
+        resolve: &dyn Fn(&rustdoc_types::Type) -> Option<Type>,
+        get_item: &dyn Fn(rustdoc_types::Id) -> rustdoc_types::Item,
+    ) -> ObjectSchema {
+        let mut object = ObjectSchema::default();
+        for field_id in field_ids {
+            let field = get_item(*field_id);
+            let ItemEnum::StructField(field_type) = &field.inner else {
+                continue;
+            };
+            let attrs = serde_arguments(&field.attrs);
+            let has = |key: &str| attrs.iter().any(|(k, _)| k == key);
+            if has("skip") || has("skip_deserializing") {
+                continue;
+            }
+            let custom_deserializer = has("with") || has("deserialize_with");
+            let schema = if custom_deserializer {
+                Schema::Any
+            } else {
+                resolve(field_type).map_or(Schema::Any, |ty| self.schema(&ty))
+            };
+            if has("flatten") {
+                object.flattened.push(schema);
+                continue;
+            }
+            let original = field.name.clone().unwrap_or_default();
+            let name = attrs
+                .iter()
+                .find(|(k, _)| k == "rename")
+                .and_then(|(_, v)| v.clone())
+                .unwrap_or_else(|| rename_field(&original, rename_all));
+            let field_default = attrs.iter().find(|(k, _)| k == "default");
+            // `serde` treats missing `Option` fields as `None`, unless a custom
+            // deserializer is used.
+            let implicit_none = schema.is_optional() && !custom_deserializer;
+            // `#[serde(default)]` uses `Default::default()`, whose output we know for
+            // some common types. We can't evaluate custom default functions.
+            let default = match field_default {
+                Some((_, None)) => default_value(&schema),
+                _ if implicit_none => Some(Value::Null),
+                _ => None,
+            };
+            let required = field_default.is_none() && !container_default && !implicit_none;
+            object.fields.push(Field {
+                name,
+                description: field.docs.as_deref().and_then(summary),
+                schema,
+                required,
+                default,
+            });
+        }
+        object
+    }
+
+    /// Schemas for common types that don't derive `serde::Deserialize`, or whose
+    /// serialized representation doesn't match their definition.
+    fn well_known_schema(&mut self, t: &PathType) -> Option<Schema> {
+        let path = t.base_type.iter().map(|s| s.as_str()).collect_vec();
+        let type_args = type_arguments(t).cloned().collect_vec();
+        let arg = |builder: &mut Self, i: usize| {
+            type_args
+                .get(i)
+                .map_or(Schema::Any, |ty| builder.schema(ty))
+        };
+        let schema = match path.as_slice() {
+            ["alloc", "string", "String"] => Schema::String,
+            ["alloc", "borrow", "Cow"]
+            | ["alloc", "boxed", "Box"]
+            | ["alloc", "rc", "Rc"]
+            | ["alloc", "sync", "Arc"] => arg(self, 0),
+            ["core", "option", "Option"] => Schema::Optional(Box::new(arg(self, 0))),
+            // `SecretString` is an alias for `SecretBox<str>`.
+            ["secrecy", .., "SecretBox"] => Schema::Secret(Box::new(arg(self, 0))),
+            ["secrecy", .., "SecretString"] => Schema::Secret(Box::new(Schema::String)),
+            ["pavex", "config", .., "Reloadable"] => arg(self, 0),
+            ["alloc", "vec", "Vec"]
+            | ["alloc", "collections", "vec_deque", "VecDeque"]
+            | ["alloc", "collections", "linked_list", "LinkedList"] => Schema::Array {
+                items: Box::new(arg(self, 0)),
+                unique: false,
+            },
+            ["alloc", "collections", "btree", "set", "BTreeSet"]
+            | ["std", "collections", "hash", "set", "HashSet"]
+            | ["indexmap", "set", "IndexSet"] => Schema::Array {
+                items: Box::new(arg(self, 0)),
+                unique: true,
+            },
+            ["alloc", "collections", "btree", "map", "BTreeMap"]
+            | ["std", "collections", "hash", "map", "HashMap"]
+            | ["indexmap", "map", "IndexMap"] => Schema::Map(Box::new(arg(self, 1))),
+            ["std", "path", "PathBuf"] | ["std", "ffi", "os_str", "OsString"] => Schema::String,
+            [
+                "core" | "std",
+                "net",
+                ..,
+                "IpAddr" | "Ipv4Addr" | "Ipv6Addr",
+            ]
+            | [
+                "core" | "std",
+                "net",
+                ..,
+                "SocketAddr" | "SocketAddrV4" | "SocketAddrV6",
+            ] => Schema::String,
+            ["core", "num", .., name] if name.starts_with("NonZero") => {
+                Schema::Integer { minimum: Some(1) }
+            }
+            // Pavex deserializes spans and durations from their "friendly" string
+            // representation—e.g. "1h 30m".
+            [
+                "jiff",
+                ..,
+                "Span" | "SignedDuration" | "Timestamp" | "Zoned",
+            ] => Schema::String,
+            _ => return None,
+        };
+        Some(schema)
+    }
+
+    /// A unique name for a new definition, based on the name of the type.
+    fn definition_name(&self, type_name: &str) -> String {
+        let mut name = type_name.to_owned();
+        let mut counter = 1;
+        while self.definitions.contains_key(&name) || self.type2name.values().any(|n| n == &name) {
+            counter += 1;
+            name = format!("{type_name}{counter}");
+        }
+        name
+    }
+}
+
+fn primitive_schema(p: &ScalarPrimitive) -> Schema {
+    use ScalarPrimitive::*;
+    match p {
+        Usize | U8 | U16 | U32 | U64 | U128 => Schema::Integer { minimum: Some(0) },
+        Isize | I8 | I16 | I32 | I64 | I128 => Schema::Integer { minimum: None },
+        F32 | F64 => Schema::Number,
+        Bool => Schema::Boolean,
+        Char | Str => Schema::String,
+    }
+}
+
+/// The output of `Default::default()` for the given schema, if it is known statically.
+fn default_value(schema: &Schema) -> Option<Value> {
+    match schema {
+        Schema::Null | Schema::Optional(_) => Some(Value::Null),
+        Schema::Boolean => Some(json!(false)),
+        Schema::Integer {
+            minimum: None | Some(0),
+        } => Some(json!(0)),
+        Schema::Number => Some(json!(0.0)),
+        Schema::String => Some(json!("")),
+        Schema::Array { .. } => Some(json!([])),
+        Schema::Map(_) => Some(json!({})),
+        _ => None,
+    }
+}
+
+/// The first section of a doc comment, up to the first heading or code block,
+/// with intra-doc links replaced by their text.
+fn summary(docs: &str) -> Option<String> {
+    let mut lines = Vec::new();
+    for line in docs.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with('#') || trimmed.starts_with("```") {
+            break;
+        }
+        // Link reference definitions, e.g. `[Foo]: crate::Foo`.
+        if trimmed.starts_with('[') && trimmed.contains("]: ") {
+            continue;
+        }
+        lines.push(line.trim_end());
+    }
+    let summary = strip_intra_doc_links(lines.join("\n").trim());
+    (!summary.is_empty()).then_some(summary)
+}
+
+/// Replace `[text](path)` and `[text]` with `text`, unless `path` is a URL.
+fn strip_intra_doc_links(docs: &str) -> String {
+    let mut out = String::with_capacity(docs.len());
+    let mut rest = docs;
+    while let Some(start) = rest.find('[') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let Some(end) = after.find(']') else {
+            out.push_str(&rest[start..]);
+            return out;
+        };
+        let text = &after[..end];
+        let tail = &after[end + 1..];
+        if let Some(target) = tail.strip_prefix('(')
+            && let Some(close) = target.find(')')
+        {
+            let target = &target[..close];
+            if target.starts_with("http://") || target.starts_with("https://") {
+                let _ = write!(out, "[{text}]({target})");
+            } else {
+                out.push_str(text);
+            }
+            rest = &tail[close + 2..];
+        } else {
+            out.push_str(text);
+            rest = tail;
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Apply a `#[serde(rename_all = "..")]` rule to a variant name, which is assumed
+/// to be in `PascalCase`.
+///
+/// It mirrors the behaviour of `serde_derive`.
+fn rename_variant(variant: &str, rule: Option<&str>) -> String {
+    let snake = || {
+        let mut snake = String::new();
+        for (i, c) in variant.char_indices() {
+            if i > 0 && c.is_uppercase() {
+                snake.push('_');
+            }
+            snake.push(c.to_ascii_lowercase());
+        }
+        snake
+    };
+    match rule {
+        Some("lowercase") => variant.to_ascii_lowercase(),
+        Some("UPPERCASE") => variant.to_ascii_uppercase(),
+        Some("camelCase") => {
+            let mut chars = variant.chars();
+            match chars.next() {
+                Some(first) => first.to_ascii_lowercase().to_string() + chars.as_str(),
+                None => String::new(),
+            }
+        }
+        Some("snake_case") => snake(),
+        Some("SCREAMING_SNAKE_CASE") => snake().to_ascii_uppercase(),
+        Some("kebab-case") => snake().replace('_', "-"),
+        Some("SCREAMING-KEBAB-CASE") => snake().to_ascii_uppercase().replace('_', "-"),
+        _ => variant.to_owned(),
+    }
+}
+
+/// Apply a `#[serde(rename_all = "..")]` rule to a field name, which is assumed
+/// to be in `snake_case`.
+///
+/// It mirrors the behaviour of `serde_derive`.
+fn rename_field(field: &str, rule: Option<&str>) -> String {
+    let pascal = || {
+        let mut pascal = String::new();
+        let mut capitalize = true;
+        for c in field.chars() {
+            if c == '_' {
+                capitalize = true;
+            } else if capitalize {
+                pascal.push(c.to_ascii_uppercase());
+                capitalize = false;
+            } else {
+                pascal.push(c);
+            }
+        }
+        pascal
+    };
+    match rule {
+        Some("UPPERCASE") | Some("SCREAMING_SNAKE_CASE") => field.to_ascii_uppercase(),
+        Some("PascalCase") => pascal(),
+        Some("camelCase") => {
+            let pascal = pascal();
+            let mut chars = pascal.chars();
+            match chars.next() {
+                Some(first) => first.to_ascii_lowercase().to_string() + chars.as_str(),
+                None => String::new(),
+            }
+        }
+        Some("kebab-case") => field.replace('_', "-"),
+        Some("SCREAMING-KEBAB-CASE") => field.to_ascii_uppercase().replace('_', "-"),
+        _ => field.to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schema() -> ConfigSchema {
+        let server = ObjectSchema {
+            fields: vec![
+                Field {
+                    name: "port".into(),
+                    description: Some("The port to listen on.".into()),
+                    schema: Schema::Integer { minimum: Some(0) },
+                    required: true,
+                    default: None,
+                },
+                Field {
+                    name: "host".into(),
+                    description: None,
+                    schema: Schema::String,
+                    required: false,
+                    default: Some(json!("")),
+                },
+                Field {
+                    name: "tls".into(),
+                    description: Some("TLS settings.\nTLS is disabled if missing.".into()),
+                    schema: Schema::Optional(Box::new(Schema::Ref("TlsConfig".into()))),
+                    required: false,
+                    default: Some(Value::Null),
+                },
+            ],
+            flattened: vec![],
+            deny_unknown_fields: true,
+        };
+        let tls = ObjectSchema {
+            fields: vec![Field {
+                name: "certificate".into(),
+                description: None,
+                schema: Schema::String,
+                required: true,
+                default: None,
+            }],
+            ..Default::default()
+        };
+        let level = EnumSchema {
+            representation: Representation::External,
+            variants: ["info", "debug"]
+                .into_iter()
+                .map(|name| Variant {
+                    name: name.into(),
+                    description: None,
+                    payload: Payload::Unit,
+                })
+                .collect(),
+        };
+        let definitions = [
+            (
+                "ServerConfig",
+                "Configure the HTTP server.",
+                Schema::Object(server),
+            ),
+            ("TlsConfig", "Certificates.", Schema::Object(tls)),
+            (
+                "LogLevel",
+                "How verbose logs should be.",
+                Schema::Enum(level),
+            ),
+        ]
+        .into_iter()
+        .map(|(name, description, schema)| {
+            (
+                name.to_owned(),
+                Definition {
+                    description: Some(description.into()),
+                    schema,
+                },
+            )
+        })
+        .collect();
+        ConfigSchema {
+            keys: vec![
+                Field {
+                    name: "log_level".into(),
+                    description: None,
+                    schema: Schema::Ref("LogLevel".into()),
+                    required: false,
+                    default: None,
+                },
+                Field {
+                    name: "server".into(),
+                    description: None,
+                    schema: Schema::Ref("ServerConfig".into()),
+                    required: true,
+                    default: None,
+                },
+            ],
+            definitions,
+        }
+    }
+
+    #[test]
+    fn json_schema() {
+        let expected = json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "title": "ApplicationConfig",
+            "type": "object",
+            "properties": {
+                "log_level": { "$ref": "#/$defs/LogLevel" },
+                "server": { "$ref": "#/$defs/ServerConfig" }
+            },
+            "required": ["server"],
+            "$defs": {
+                "ServerConfig": {
+                    "type": "object",
+                    "properties": {
+                        "port": {
+                            "type": "integer",
+                            "minimum": 0,
+                            "description": "The port to listen on."
+                        },
+                        "host": { "type": "string", "default": "" },
+                        "tls": {
+                            "anyOf": [{ "$ref": "#/$defs/TlsConfig" }, { "type": "null" }],
+                            "description": "TLS settings.\nTLS is disabled if missing.",
+                            "default": null
+                        }
+                    },
+                    "required": ["port"],
+                    "additionalProperties": false,
+                    "description": "Configure the HTTP server."
+                },
+                "TlsConfig": {
+                    "type": "object",
+                    "properties": { "certificate": { "type": "string" } },
+                    "required": ["certificate"],
+                    "description": "Certificates."
+                },
+                "LogLevel": {
+                    "type": "string",
+                    "enum": ["info", "debug"],
+                    "description": "How verbose logs should be."
+                }
+            }
+        });
+        assert_eq!(schema().json_schema(), expected);
+    }
+
+    #[test]
+    fn reference_yaml() {
+        let expected = "# yaml-language-server: $schema=config.schema.json
+#
+# Do NOT edit this file.
+# It was automatically generated by Pavex and lists all the configuration keys
+# supported by your application.
+
+# How verbose logs should be.
+# Allowed values: `info`, `debug`.
+# log_level: \"info\"
+
+# Configure the HTTP server.
+# Required.
+server:
+  # The port to listen on.
+  # Required.
+  port: 0
+  host: \"\"
+  # TLS settings.
+  # TLS is disabled if missing.
+  tls: ~
+";
+        assert_eq!(schema().reference_yaml(), expected);
+    }
+
+    #[test]
+    fn secrets_are_write_only() {
+        let field = Field {
+            name: "password".into(),
+            description: None,
+            schema: Schema::Optional(Box::new(Schema::Secret(Box::new(Schema::String)))),
+            required: false,
+            default: Some(Value::Null),
+        };
+        assert_eq!(
+            field.schema.json(),
+            json!({ "anyOf": [{ "type": "string", "writeOnly": true }, { "type": "null" }] })
+        );
+
+        let schema = ConfigSchema {
+            keys: vec![field],
+            definitions: IndexMap::new(),
+        };
+        assert_eq!(
+            schema.entry_lines(&schema.keys[0], &mut vec![]),
+            vec![
+                "# Secret: it's redacted when the configuration is explained.",
+                "password: ~",
+            ]
+        );
+    }
+
+    #[test]
+    fn secret_keys() {
+        let field = |name: &str, schema| Field {
+            name: name.into(),
+            description: None,
+            schema,
+            required: true,
+            default: None,
+        };
+        let secret = || Schema::Secret(Box::new(Schema::String));
+        let tenant = ObjectSchema {
+            fields: vec![field("name", Schema::String), field("api_key", secret())],
+            ..Default::default()
+        };
+        let schema = ConfigSchema {
+            keys: vec![
+                field("password", Schema::Optional(Box::new(secret()))),
+                field(
+                    "tenants",
+                    Schema::Map(Box::new(Schema::Ref("Tenant".into()))),
+                ),
+                field(
+                    "tokens",
+                    Schema::Array {
+                        items: Box::new(secret()),
+                        unique: false,
+                    },
+                ),
+                field("port", Schema::Integer { minimum: Some(0) }),
+            ],
+            definitions: IndexMap::from([(
+                "Tenant".into(),
+                Definition {
+                    description: None,
+                    schema: Schema::Object(tenant),
+                },
+            )]),
+        };
+        assert_eq!(
+            schema.secret_keys(),
+            vec!["password", "tenants.*.api_key", "tokens"]
+        );
+    }
+
+    #[test]
+    fn enum_representations() {
+        let variant = |payload| Variant {
+            name: "File".into(),
+            description: None,
+            payload,
+        };
+        let path = || {
+            Payload::Struct(ObjectSchema {
+                fields: vec![Field {
+                    name: "path".into(),
+                    description: None,
+                    schema: Schema::String,
+                    required: true,
+                    default: None,
+                }],
+                ..Default::default()
+            })
+        };
+        let path_object = json!({
+            "type": "object",
+            "properties": { "path": { "type": "string" } },
+            "required": ["path"]
+        });
+
+        let external = EnumSchema {
+            representation: Representation::External,
+            variants: vec![variant(path())],
+        };
+        assert_eq!(
+            external.json(),
+            json!({ "oneOf": [{
+                "type": "object",
+                "properties": { "File": path_object },
+                "required": ["File"],
+                "additionalProperties": false
+            }]})
+        );
+
+        let internal = EnumSchema {
+            representation: Representation::Internal { tag: "kind".into() },
+            variants: vec![variant(path())],
+        };
+        assert_eq!(
+            internal.json(),
+            json!({ "oneOf": [{ "allOf": [
+                {
+                    "type": "object",
+                    "properties": { "kind": { "const": "File" } },
+                    "required": ["kind"]
+                },
+                path_object
+            ]}]})
+        );
+
+        let adjacent = EnumSchema {
+            representation: Representation::Adjacent {
+                tag: "kind".into(),
+                content: "options".into(),
+            },
+            variants: vec![variant(Payload::Unit)],
+        };
+        assert_eq!(
+            adjacent.json(),
+            json!({ "oneOf": [{
+                "type": "object",
+                "properties": { "kind": { "const": "File" } },
+                "required": ["kind"]
+            }]})
+        );
+    }
+
+    #[test]
+    fn rename_rules() {
+        assert_eq!(
+            rename_variant("CookieFallback", Some("snake_case")),
+            "cookie_fallback"
+        );
+        assert_eq!(
+            rename_variant("CookieFallback", Some("kebab-case")),
+            "cookie-fallback"
+        );
+        assert_eq!(
+            rename_variant("CookieFallback", Some("camelCase")),
+            "cookieFallback"
+        );
+        assert_eq!(
+            rename_variant("CookieFallback", Some("lowercase")),
+            "cookiefallback"
+        );
+        assert_eq!(rename_variant("CookieFallback", None), "CookieFallback");
+        assert_eq!(
+            rename_field("reload_interval", Some("camelCase")),
+            "reloadInterval"
+        );
+        assert_eq!(
+            rename_field("reload_interval", Some("PascalCase")),
+            "ReloadInterval"
+        );
+        assert_eq!(
+            rename_field("reload_interval", Some("SCREAMING-KEBAB-CASE")),
+            "RELOAD-INTERVAL"
+        );
+        assert_eq!(
+            rename_field("reload_interval", Some("snake_case")),
+            "reload_interval"
+        );
+    }
+
+    #[test]
+    fn doc_summaries() {
+        let docs = "Configure how [`JwtVerifier`](super::JwtVerifier) validates [tokens].\n\
+            See [RFC 7519](https://datatracker.ietf.org/doc/html/rfc7519).\n\
+            \n\
+            # Example\n\
+            \n\
+            ```yaml\n\
+            jwt: {}\n\
+            ```";
+        assert_eq!(
+            summary(docs).unwrap(),
+            "Configure how `JwtVerifier` validates tokens.\n\
+            See [RFC 7519](https://datatracker.ietf.org/doc/html/rfc7519)."
+        );
+        assert_eq!(summary("# Example\nfoo"), None);
+    }
+}
@@ -8,6 +8,7 @@ use serde::Serialize;
 use toml_edit::ser::ValueSerializer;
 
 use crate::AppWriter;
+use crate::compiler::config_schema::ConfigSchema;
 
 #[derive(Clone)]
 /// The manifest and the code for a generated application.
@@ -16,6 +17,8 @@ use crate::AppWriter;
 pub struct GeneratedApp {
     pub(crate) lib_rs: TokenStream,
     pub(crate) cargo_toml: GeneratedManifest,
+    pub(crate) config_schema: ConfigSchema,
+    pub(crate) emit_config_schema: bool,
     pub(crate) package_graph: PackageGraph,
 }
 
@@ -55,13 +58,28 @@ impl GeneratedManifest {
 }
 
 impl GeneratedApp {
+    /// Save the JSON Schema for `ApplicationConfig` (`config.schema.json`) and the
+    /// annotated reference configuration file (`config.reference.yml`) alongside the generated code.
+    ///
+    /// They are not emitted by default.
+    pub fn emit_config_schema(mut self) -> Self {
+        self.emit_config_schema = true;
+        self
+    }
+
     /// Save the code and the manifest for the generated application to disk.
     /// The newly created library crate is also injected as a member into the current workspace.
+    ///
+    /// If [`GeneratedApp::emit_config_schema`] has been called, the JSON Schema for
+    /// `ApplicationConfig` and the reference configuration file are saved
+    /// in the root directory of the generated crate as well.
     #[tracing::instrument(skip_all, level = tracing::Level::INFO)]
     pub fn persist(self, directory: &Path, writer: &mut AppWriter) -> Result<(), anyhow::Error> {
         let Self {
             lib_rs,
             mut cargo_toml,
+            config_schema,
+            emit_config_schema,
             package_graph,
         } = self;
         let workspace = package_graph.workspace();
@@ -81,6 +99,9 @@ impl GeneratedApp {
 
         let lib_rs = prettyplease::unparse(&syn::parse2(lib_rs)?);
         writer.persist_if_changed(&source_directory.join("lib.rs"), lib_rs.as_bytes())?;
+        if emit_config_schema {
+            config_schema.persist(&pkg_directory, writer)?;
+        }
 
         Ok(())
     }
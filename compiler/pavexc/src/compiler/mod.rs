@@ -1,15 +1,23 @@
 #![allow(clippy::too_many_arguments)]
 
 pub use app::App;
+pub use component_graph::{
+    ComponentEdge, ComponentEdgeKind, ComponentGraph, ComponentGraphFilter, ComponentGraphFormat,
+    ComponentNode, ComponentNodeKind,
+};
 
 mod analyses;
 mod app;
+mod clones;
 mod codegen;
 mod codegen_utils;
 mod component;
+mod component_graph;
 mod computation;
+mod config_schema;
 mod framework_rustdoc;
 mod generated_app;
 mod interner;
 mod path_parameters;
+mod serde_shapes;
 mod traits;
@@ -3,7 +3,8 @@ use indexmap::{IndexMap, IndexSet};
 use itertools::Itertools;
 use petgraph::Direction;
 use petgraph::graph::NodeIndex;
-use rustdoc_types::{ItemEnum, StructKind};
+use rustdoc_resolver::{GenericBindings, TypeAliasResolution, resolve_type};
+use rustdoc_types::{ItemEnum, StructKind, VariantKind};
 
 use crate::compiler::analyses::call_graph::{CallGraphNode, RawCallGraph};
 use crate::compiler::analyses::components::HydratedComponent;
@@ -16,7 +17,7 @@ use crate::compiler::computation::{Computation, MatchResultVariant};
 use crate::compiler::framework_rustdoc::resolve_type_path;
 use crate::diagnostic::CompilerDiagnostic;
 use crate::diagnostic::DiagnosticSink;
-use crate::language::{GenericArgument, Type};
+use crate::language::{GenericArgument, PathType, ScalarPrimitive, Type};
 use crate::rustdoc::{CrateCollection, GlobalItemId};
 use crate::utils::comma_separated_list;
 
@@ -24,8 +25,9 @@ use super::analyses::route_path::RoutePath;
 use super::traits::assert_trait_is_implemented;
 
 /// For each handler, check if path parameters are extracted from the URL of the incoming request.
-/// If so, check that the type of the path parameter is a struct with named fields and
-/// that each named field maps to a path parameter for the corresponding handler.
+/// If so, check that the shape of the type of the path parameter is compatible with the
+/// path parameters for the corresponding handler—e.g. that each named field maps to a path
+/// parameter, or that a tuple has one element for each path parameter.
 #[tracing::instrument(name = "Verify path parameters", skip_all)]
 pub(crate) fn verify_path_parameters(
     router: &Router,
@@ -88,7 +90,7 @@ pub(crate) fn verify_path_parameters(
             unreachable!()
         };
 
-        let Ok(struct_item) = must_be_a_plain_struct(
+        let Ok(shape) = path_params_shape(
             component_db,
             computation_db,
             krate_collection,
@@ -96,22 +98,10 @@ pub(crate) fn verify_path_parameters(
             graph,
             ok_path_params_node_id,
             extracted_type,
+            &structural_deserialize,
         ) else {
             continue;
         };
-        let ItemEnum::Struct(struct_inner_item) = &struct_item.inner else {
-            unreachable!()
-        };
-
-        // We only want to check alignment between struct fields and the path parameters in the
-        // template if the struct implements `StructuralDeserialize`, our marker trait that stands
-        // for "this struct implements serde::Deserialize using a #[derive(serde::Deserialize)] with
-        // no customizations (e.g. renames)".
-        if assert_trait_is_implemented(krate_collection, extracted_type, &structural_deserialize)
-            .is_err()
-        {
-            continue;
-        }
 
         let path = &infos[handler_id].path;
         let parsed_path = RoutePath::parse(path.to_owned());
@@ -120,48 +110,79 @@ pub(crate) fn verify_path_parameters(
             .keys()
             .cloned()
             .collect::<IndexSet<_>>();
-        let struct_field_names = {
-            let mut struct_field_names = IndexSet::new();
-            let Type::Path(extracted_path_type) = &extracted_type else {
-                unreachable!()
-            };
-            let StructKind::Plain {
-                fields: field_ids, ..
-            } = &struct_inner_item.kind
-            else {
-                unreachable!()
-            };
-            for field_id in field_ids {
-                let field_item = krate_collection.get_item_by_global_type_id(&GlobalItemId {
-                    rustdoc_item_id: *field_id,
-                    package_id: extracted_path_type.package_id.clone(),
-                });
-                struct_field_names.insert(field_item.name.clone().unwrap());
-            }
-            struct_field_names
-        };
 
-        let non_existing_path_parameters = struct_field_names
-            .into_iter()
-            .filter(|f| !path_parameter_names.contains(f.as_str()))
-            .collect::<IndexSet<_>>();
+        match shape {
+            PathParamsShape::NamedFields { field_names } => {
+                let non_existing_path_parameters = field_names
+                    .into_iter()
+                    .filter(|f| !path_parameter_names.contains(f.as_str()))
+                    .collect::<IndexSet<_>>();
 
-        if !non_existing_path_parameters.is_empty() {
-            report_non_existing_path_parameters(
-                component_db,
-                computation_db,
-                diagnostics,
-                path,
-                graph,
-                ok_path_params_node_id,
-                path_parameter_names,
-                non_existing_path_parameters,
-                extracted_type,
-            )
+                if !non_existing_path_parameters.is_empty() {
+                    report_non_existing_path_parameters(
+                        component_db,
+                        computation_db,
+                        diagnostics,
+                        path,
+                        graph,
+                        ok_path_params_node_id,
+                        path_parameter_names,
+                        non_existing_path_parameters,
+                        extracted_type,
+                    )
+                }
+            }
+            PathParamsShape::SingleValue => {
+                if path_parameter_names.len() != 1 {
+                    report_wrong_number_of_path_parameters(
+                        component_db,
+                        computation_db,
+                        diagnostics,
+                        path,
+                        graph,
+                        ok_path_params_node_id,
+                        path_parameter_names,
+                        1,
+                        extracted_type,
+                    )
+                }
+            }
+            PathParamsShape::Positional { arity } => {
+                if path_parameter_names.len() != arity {
+                    report_wrong_number_of_path_parameters(
+                        component_db,
+                        computation_db,
+                        diagnostics,
+                        path,
+                        graph,
+                        ok_path_params_node_id,
+                        path_parameter_names,
+                        arity,
+                        extracted_type,
+                    )
+                }
+            }
+            PathParamsShape::Opaque => {}
         }
     }
 }
 
+/// The shape of `T` in `PathParams<T>`, as far as path parameter extraction is concerned.
+enum PathParamsShape {
+    /// A struct with named fields.
+    /// Each field is matched, by name, against the path parameters in the route template.
+    NamedFields { field_names: IndexSet<String> },
+    /// A single value—e.g. a primitive, a newtype wrapper or an enum with unit variants only.
+    /// It requires the route template to have exactly one path parameter.
+    SingleValue,
+    /// A tuple or a tuple struct.
+    /// Its elements are matched, positionally, against the path parameters in the route template.
+    Positional { arity: usize },
+    /// We can't make any assumption on how the type is going to be deserialized—e.g. it has a
+    /// custom implementation of `serde::Deserialize`.
+    Opaque,
+}
+
 /// Report an error on each compute node that consumes the `PathParams` extractor
 /// while trying to extract one or more path parameters that are not present in
 /// the respective path pattern.
@@ -179,7 +200,7 @@ fn report_non_existing_path_parameters(
     assert!(!non_existing_path_parameters.is_empty());
     // Find the compute nodes that consume the `PathParams` extractor and report
     // an error on each of them.
-    let consuming_ids = path_params_consumer_ids(call_graph, ok_path_params_node_id);
+    let consuming_ids = consumer_ids(call_graph, ok_path_params_node_id);
     for component_id in consuming_ids {
         let Some(user_id) = component_db.user_component_id(component_id) else {
             continue;
@@ -252,56 +273,98 @@ fn report_non_existing_path_parameters(
     }
 }
 
-/// Checks that the type of the path parameter is a struct with named fields.
-/// If it is, returns the rustdoc item for the type.
-/// If it isn't, reports an error diagnostic on each compute node that consumes the
-/// `PathParams` extractor.
-fn must_be_a_plain_struct(
+/// Report an error on each compute node that consumes the `PathParams` extractor
+/// if the number of values expected by the extracted type doesn't match the number of
+/// path parameters in the respective path pattern.
+#[allow(clippy::too_many_arguments)]
+fn report_wrong_number_of_path_parameters(
     component_db: &ComponentDb,
     computation_db: &ComputationDb,
-    krate_collection: &CrateCollection,
     diagnostics: &DiagnosticSink,
+    path: &str,
     call_graph: &RawCallGraph,
     ok_path_params_node_id: NodeIndex,
+    path_parameter_names: IndexSet<String>,
+    expected: usize,
     extracted_type: &Type,
-) -> Result<rustdoc_types::Item, ()> {
-    let error_suffix = match extracted_type {
-        Type::Path(t) | Type::TypeAlias(t) => {
-            let Some(item_id) = t.rustdoc_id else {
-                unreachable!()
-            };
-            let item = krate_collection.get_item_by_global_type_id(&GlobalItemId {
-                rustdoc_item_id: item_id,
-                package_id: t.package_id.clone(),
-            });
-            match item.inner {
-                ItemEnum::Union(_) => format!("`{t:?}` is an union"),
-                ItemEnum::Enum(_) => format!("`{t:?}` is an enum"),
-                ItemEnum::Struct(ref s) => match &s.kind {
-                    StructKind::Unit => {
-                        format!("`{t:?}` is a struct with no fields (a.k.a. unit struct)")
-                    }
-                    StructKind::Tuple(_) => format!("`{t:?}` is a tuple struct"),
-                    StructKind::Plain { .. } => return Ok(item.into_owned()),
-                },
-                _ => unreachable!(),
+) {
+    let consuming_ids = consumer_ids(call_graph, ok_path_params_node_id);
+    for component_id in consuming_ids {
+        let Some(user_id) = component_db.user_component_id(component_id) else {
+            continue;
+        };
+        let callable = &computation_db[user_id];
+        let kind = component_db.user_db()[user_id].kind();
+        let source = diagnostics.annotated(
+            component_db.registration_target(user_id),
+            format!("The {kind} asking for `PathParams<{extracted_type:?}>`",),
+        );
+        let expected_msg = if expected == 1 {
+            format!("`{extracted_type:?}` is extracted from a single path parameter")
+        } else {
+            format!("`{extracted_type:?}` expects {expected} path parameters")
+        };
+        let found_msg = match path_parameter_names.len() {
+            0 => format!("there are no path parameters in `{path}`"),
+            n => {
+                let path_parameters = path_parameter_names
+                    .iter()
+                    .map(|p| format!("- `{p}`"))
+                    .join("\n");
+                let n_parameters = if n == 1 {
+                    "is 1 path parameter".to_string()
+                } else {
+                    format!("are {n} path parameters")
+                };
+                format!("there {n_parameters} in `{path}`:\n{path_parameters}\n")
             }
-        }
-        Type::Reference(r) => format!("`{r:?}` is a reference"),
-        Type::Tuple(t) => format!("`{t:?}` is a tuple"),
-        Type::ScalarPrimitive(s) => format!("`{s:?}` is a primitive"),
-        Type::Slice(s) => format!("`{s:?}` is a slice"),
-        Type::Array(a) => format!("`{a:?}` is an array"),
-        Type::RawPointer(r) => format!("`{r:?}` is a raw pointer"),
-        Type::FunctionPointer(fp) => format!("`{fp:?}` is a function pointer"),
-        Type::Generic(_) => {
-            unreachable!()
-        }
-    };
+        };
+        let error = anyhow!(
+            "`{}` is trying to extract path parameters using `PathParams<{extracted_type:?}>`.\n\
+                {expected_msg}, but {found_msg}\n\
+                This is going to cause a runtime error!",
+            callable,
+        );
+        let help = if expected == 1 {
+            "Use a plain struct with named fields (or a tuple) to extract multiple path parameters."
+                .to_string()
+        } else {
+            format!(
+                "Make sure that `{extracted_type:?}` has exactly one element for each path parameter, \
+                in the same order they appear in the path pattern."
+            )
+        };
+        let d = CompilerDiagnostic::builder(error)
+            .optional_source(source)
+            .help(help)
+            .build();
+        diagnostics.push(d);
+    }
+}
+
+/// Determine the shape of the type of the path parameter.
+/// If it's a shape we don't support, it reports an error diagnostic on each compute node that
+/// consumes the `PathParams` extractor.
+#[allow(clippy::too_many_arguments)]
+fn path_params_shape(
+    component_db: &ComponentDb,
+    computation_db: &ComputationDb,
+    krate_collection: &CrateCollection,
+    diagnostics: &DiagnosticSink,
+    call_graph: &RawCallGraph,
+    ok_path_params_node_id: NodeIndex,
+    extracted_type: &Type,
+    structural_deserialize: &PathType,
+) -> Result<PathParamsShape, ()> {
+    let error_suffix =
+        match path_params_shape_inner(krate_collection, extracted_type, structural_deserialize) {
+            Ok(shape) => return Ok(shape),
+            Err(suffix) => suffix,
+        };
 
     // Find the compute nodes that consume the `PathParams` extractor and report
     // an error on each of them.
-    let consuming_ids = path_params_consumer_ids(call_graph, ok_path_params_node_id);
+    let consuming_ids = consumer_ids(call_graph, ok_path_params_node_id);
 
     for component_id in consuming_ids {
         let Some(user_id) = component_db.user_component_id(component_id) else {
@@ -315,10 +378,10 @@ fn must_be_a_plain_struct(
         );
         let error = anyhow!(
             "Path parameters must be extracted using a plain struct with named fields, \
-            where the name of each field matches one of the path parameters specified \
-            in the route for the respective request handler.\n\
+            a tuple (or a tuple struct) or, if there is a single path parameter, a single value \
+            (e.g. a primitive, a newtype or an enum with unit variants only).\n\
             `{}` is trying to extract `PathParams<{extracted_type:?}>`, but \
-            {error_suffix}, not a plain struct type. I don't support this: the extraction would \
+            {error_suffix}. I don't support this: the extraction would \
             fail at runtime, when trying to process an incoming request.",
             callable
         );
@@ -335,15 +398,170 @@ fn must_be_a_plain_struct(
     Err(())
 }
 
-/// Return the set of user component ids that consume a certain instance of the `PathParams` extractor
-/// as input parameter.
-fn path_params_consumer_ids(
+/// Determine the shape of `extracted_type`.
+/// It returns an error message suffix if the shape is not supported.
+fn path_params_shape_inner(
+    krate_collection: &CrateCollection,
+    extracted_type: &Type,
+    structural_deserialize: &PathType,
+) -> Result<PathParamsShape, String> {
+    match extracted_type {
+        Type::Path(t) | Type::TypeAlias(t) => {
+            // Owned strings are deserialized from a single path segment.
+            if t.base_type == ["alloc", "string", "String"]
+                || t.base_type == ["alloc", "borrow", "Cow"]
+            {
+                return Ok(PathParamsShape::SingleValue);
+            }
+            let Some(item_id) = t.rustdoc_id else {
+                unreachable!()
+            };
+            let item = krate_collection.get_item_by_global_type_id(&GlobalItemId {
+                rustdoc_item_id: item_id,
+                package_id: t.package_id.clone(),
+            });
+            // We only want to make assumptions on the way the type is going to be deserialized
+            // if it implements `StructuralDeserialize`, our marker trait that stands
+            // for "this type implements serde::Deserialize using a #[derive(serde::Deserialize)] with
+            // no customizations (e.g. renames)".
+            let is_structural = || {
+                assert_trait_is_implemented(
+                    krate_collection,
+                    extracted_type,
+                    structural_deserialize,
+                )
+                .is_ok()
+            };
+            match &item.inner {
+                ItemEnum::Union(_) => Err(format!("`{t:?}` is an union")),
+                ItemEnum::Enum(e) => {
+                    if !is_structural() {
+                        return Ok(PathParamsShape::Opaque);
+                    }
+                    let mut non_unit_variants = vec![];
+                    for variant_id in &e.variants {
+                        let variant = krate_collection.get_item_by_global_type_id(&GlobalItemId {
+                            rustdoc_item_id: *variant_id,
+                            package_id: t.package_id.clone(),
+                        });
+                        let ItemEnum::Variant(v) = &variant.inner else {
+                            unreachable!()
+                        };
+                        if !matches!(v.kind, VariantKind::Plain) {
+                            non_unit_variants.push(variant.name.clone().unwrap_or_default());
+                        }
+                    }
+                    if non_unit_variants.is_empty() {
+                        Ok(PathParamsShape::SingleValue)
+                    } else {
+                        let mut msg = format!("`{t:?}` is an enum with ");
+                        if non_unit_variants.len() == 1 {
+                            msg.push_str("a non-unit variant, ");
+                        } else {
+                            msg.push_str("non-unit variants, ");
+                        }
+                        comma_separated_list(
+                            &mut msg,
+                            non_unit_variants.iter(),
+                            |v| format!("`{v}`"),
+                            "and",
+                        )
+                        .unwrap();
+                        Err(msg)
+                    }
+                }
+                ItemEnum::Struct(s) => match &s.kind {
+                    StructKind::Unit => Err(format!(
+                        "`{t:?}` is a struct with no fields (a.k.a. unit struct)"
+                    )),
+                    StructKind::Tuple(fields) => {
+                        if !is_structural() {
+                            return Ok(PathParamsShape::Opaque);
+                        }
+                        match fields.as_slice() {
+                            // Newtypes are transparent: the shape is determined by the
+                            // wrapped type.
+                            [Some(field_id)] => {
+                                let field =
+                                    krate_collection.get_item_by_global_type_id(&GlobalItemId {
+                                        rustdoc_item_id: *field_id,
+                                        package_id: t.package_id.clone(),
+                                    });
+                                let ItemEnum::StructField(field_type) = &field.inner else {
+                                    unreachable!()
+                                };
+                                match resolve_type(
+                                    field_type,
+                                    &t.package_id,
+                                    krate_collection,
+                                    &GenericBindings::default(),
+                                    TypeAliasResolution::ResolveThrough,
+                                ) {
+                                    Ok(inner) if !inner.is_a_template() => path_params_shape_inner(
+                                        krate_collection,
+                                        &inner,
+                                        structural_deserialize,
+                                    ),
+                                    _ => Ok(PathParamsShape::Opaque),
+                                }
+                            }
+                            [None] => Ok(PathParamsShape::Opaque),
+                            fields => Ok(PathParamsShape::Positional {
+                                arity: fields.len(),
+                            }),
+                        }
+                    }
+                    StructKind::Plain {
+                        fields: field_ids, ..
+                    } => {
+                        if !is_structural() {
+                            return Ok(PathParamsShape::Opaque);
+                        }
+                        let mut field_names = IndexSet::new();
+                        for field_id in field_ids {
+                            let field_item =
+                                krate_collection.get_item_by_global_type_id(&GlobalItemId {
+                                    rustdoc_item_id: *field_id,
+                                    package_id: t.package_id.clone(),
+                                });
+                            field_names.insert(field_item.name.clone().unwrap());
+                        }
+                        Ok(PathParamsShape::NamedFields { field_names })
+                    }
+                },
+                _ => Ok(PathParamsShape::Opaque),
+            }
+        }
+        Type::Reference(r) => {
+            if let Type::ScalarPrimitive(ScalarPrimitive::Str) = r.inner.as_ref() {
+                Ok(PathParamsShape::SingleValue)
+            } else {
+                Err(format!("`{r:?}` is a reference"))
+            }
+        }
+        Type::Tuple(t) => Ok(PathParamsShape::Positional {
+            arity: t.elements.len(),
+        }),
+        Type::ScalarPrimitive(_) => Ok(PathParamsShape::SingleValue),
+        Type::Slice(s) => Err(format!("`{s:?}` is a slice")),
+        Type::Array(a) => Err(format!("`{a:?}` is an array")),
+        Type::RawPointer(r) => Err(format!("`{r:?}` is a raw pointer")),
+        Type::FunctionPointer(fp) => Err(format!("`{fp:?}` is a function pointer")),
+        Type::Generic(_) => {
+            unreachable!()
+        }
+    }
+}
+
+/// Return the set of user component ids that consume a certain instance of an extractor
+/// (e.g. `PathParams`) as input parameter.
+pub(super) fn consumer_ids(
     call_graph: &RawCallGraph,
-    ok_path_params_node_id: NodeIndex,
+    extractor_node_id: NodeIndex,
 ) -> IndexSet<ComponentId> {
     let mut consumer_ids = IndexSet::new();
     let mut descendant_ids = call_graph
-        .neighbors_directed(ok_path_params_node_id, Direction::Outgoing)
+        .neighbors_directed(extractor_node_id, Direction::Outgoing)
         .collect::<IndexSet<_>>();
     while let Some(descendant_id) = descendant_ids.pop() {
         let descendant_node = &call_graph[descendant_id];
@@ -0,0 +1,823 @@
+use ahash::{HashSet, HashSetExt};
+use anyhow::anyhow;
+use indexmap::{IndexMap, IndexSet};
+use itertools::Itertools;
+use miette::Severity;
+use pavex_bp_schema::{Lint, LintSetting};
+use rustdoc_resolver::{GenericBindings, TypeAliasResolution, resolve_type};
+use rustdoc_types::{Attribute, GenericParamDefKind, Generics, ItemEnum, StructKind, VariantKind};
+
+use crate::compiler::analyses::call_graph::CallGraphNode;
+use crate::compiler::analyses::components::HydratedComponent;
+use crate::compiler::analyses::components::{ComponentDb, ComponentId};
+use crate::compiler::analyses::computations::ComputationDb;
+use crate::compiler::analyses::processing_pipeline::RequestHandlerPipeline;
+use crate::compiler::component::Constructor;
+use crate::compiler::computation::{Computation, MatchResultVariant};
+use crate::diagnostic::{CompilerDiagnostic, DiagnosticSink, lint_severity};
+use crate::language::{GenericArgument, PathType, ScalarPrimitive, Type};
+use crate::rustdoc::{CrateCollection, GlobalItemId};
+
+use super::path_parameters::consumer_ids;
+
+/// Check that the types extracted via `QueryParams<T>`, `UrlEncodedBody<T>` and `JsonBody<T>`
+/// can actually be deserialized from the respective data format.
+///
+/// We only make assumptions on types that derive `serde::Deserialize`—custom implementations
+/// are treated as opaque and never rejected.
+#[tracing::instrument(name = "Verify serde shapes", skip_all)]
+pub(crate) fn verify_serde_shapes(
+    handler_id2pipeline: &IndexMap<ComponentId, RequestHandlerPipeline>,
+    computation_db: &ComputationDb,
+    component_db: &ComponentDb,
+    krate_collection: &CrateCollection,
+    diagnostics: &DiagnosticSink,
+) {
+    // The same extractor instance may be used by multiple request handlers (e.g. in
+    // a middleware), so we collect all of them before reporting, to avoid duplicates.
+    let mut extractor_id2info: IndexMap<ComponentId, (Extractor, Type, IndexSet<ComponentId>)> =
+        IndexMap::new();
+    for pipeline in handler_id2pipeline.values() {
+        for graph in pipeline.graph_iter() {
+            let graph = &graph.call_graph;
+            for node_id in graph.node_indices() {
+                let CallGraphNode::Compute { component_id, .. } = &graph[node_id] else {
+                    continue;
+                };
+                let HydratedComponent::Constructor(Constructor(Computation::MatchResult(m))) =
+                    component_db.hydrated_component(*component_id, computation_db)
+                else {
+                    continue;
+                };
+                if m.variant != MatchResultVariant::Ok {
+                    continue;
+                }
+                let Type::Path(ty_) = &m.output else {
+                    continue;
+                };
+                let Some(extractor) = Extractor::from_base_type(&ty_.base_type) else {
+                    continue;
+                };
+                let Some(extracted_type) = type_arguments(ty_).next() else {
+                    continue;
+                };
+                let (_, _, consumers) = extractor_id2info
+                    .entry(*component_id)
+                    .or_insert_with(|| (extractor, extracted_type.to_owned(), IndexSet::new()));
+                consumers.extend(consumer_ids(graph, node_id));
+            }
+        }
+    }
+
+    for (extractor_id, (extractor, extracted_type, consumers)) in extractor_id2info {
+        let mut analyzer = ShapeAnalyzer {
+            krate_collection,
+            visited: HashSet::new(),
+            violations: Vec::new(),
+            unsupported_root: false,
+        };
+        match extractor.format() {
+            Format::Form => analyzer.check_form_root(&extracted_type),
+            Format::Json => analyzer.check_json(&extracted_type),
+        }
+        if !analyzer.violations.is_empty() {
+            report_unsupported_shape(
+                component_db,
+                computation_db,
+                diagnostics,
+                &consumers,
+                extractor,
+                &extracted_type,
+                &analyzer.violations,
+                analyzer.unsupported_root,
+            );
+            continue;
+        }
+
+        if !extractor.is_body() {
+            continue;
+        }
+        // Lints are attached to the constructor registered by the user, while `extractor_id`
+        // points at the `Ok` variant of its output—e.g. `JsonBody<T>` out of
+        // `Result<JsonBody<T>, ExtractJsonBodyError>`.
+        // We must also walk back from the concrete instance of a generic constructor
+        // (e.g. `JsonBody::<MyType>::extract`) to the template that was registered.
+        let fallible_id = component_db.fallible_id(extractor_id);
+        let constructor_id = component_db
+            .derived_from(&fallible_id)
+            .unwrap_or(fallible_id);
+        let Some(severity) = lint_severity(
+            component_db.lints(constructor_id),
+            Lint::MissingDenyUnknownFields,
+            LintSetting::Allow,
+        ) else {
+            continue;
+        };
+        if let Shape::Struct(s) = shape(krate_collection, &extracted_type)
+            && !s.deny_unknown_fields
+            // `deny_unknown_fields` is not supported in combination with `flatten`.
+            && !s.fields.iter().any(|f| f.flatten)
+        {
+            report_missing_deny_unknown_fields(
+                component_db,
+                computation_db,
+                diagnostics,
+                &consumers,
+                extractor,
+                &extracted_type,
+                severity,
+            );
+        }
+    }
+}
+
+/// The `pavex` extractors whose payload we know how to validate.
+#[derive(Debug, Clone, Copy)]
+enum Extractor {
+    QueryParams,
+    UrlEncodedBody,
+    JsonBody,
+}
+
+/// The data format used by an [`Extractor`].
+enum Format {
+    /// A list of key-value pairs, deserialized via `serde_html_form`.
+    Form,
+    /// A JSON document, deserialized via `serde_json`.
+    Json,
+}
+
+impl Extractor {
+    fn from_base_type(base_type: &[String]) -> Option<Self> {
+        match base_type {
+            [pavex, request, query, name]
+                if pavex == "pavex"
+                    && request == "request"
+                    && query == "query"
+                    && name == "QueryParams" =>
+            {
+                Some(Self::QueryParams)
+            }
+            [pavex, request, body, name]
+                if pavex == "pavex" && request == "request" && body == "body" =>
+            {
+                match name.as_str() {
+                    "UrlEncodedBody" => Some(Self::UrlEncodedBody),
+                    "JsonBody" => Some(Self::JsonBody),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Extractor::QueryParams => "QueryParams",
+            Extractor::UrlEncodedBody => "UrlEncodedBody",
+            Extractor::JsonBody => "JsonBody",
+        }
+    }
+
+    fn format(&self) -> Format {
+        match self {
+            Extractor::QueryParams | Extractor::UrlEncodedBody => Format::Form,
+            Extractor::JsonBody => Format::Json,
+        }
+    }
+
+    fn is_body(&self) -> bool {
+        match self {
+            Extractor::QueryParams => false,
+            Extractor::UrlEncodedBody | Extractor::JsonBody => true,
+        }
+    }
+
+    /// A human-readable description of the payload, e.g. "a query string".
+    fn payload(&self) -> &'static str {
+        match self {
+            Extractor::QueryParams => "a query string",
+            Extractor::UrlEncodedBody => "a URL-encoded body",
+            Extractor::JsonBody => "a JSON body",
+        }
+    }
+}
+
+/// The shape of a type, from the point of view of `serde`'s data model.
+enum Shape {
+    /// A type that's deserialized from a single value—e.g. a number, a string or
+    /// an enum with unit variants only.
+    Value,
+    Optional(Type),
+    Sequence(Type),
+    Map {
+        key: Type,
+        value: Type,
+    },
+    Struct(StructShape),
+    Tuple(Vec<Type>),
+    /// An enum with at least one variant that carries data.
+    DataEnum,
+    /// We can't make any assumption on how the type is going to be deserialized—e.g. it has a
+    /// custom implementation of `serde::Deserialize`.
+    Opaque,
+}
+
+struct StructShape {
+    fields: Vec<FieldShape>,
+    deny_unknown_fields: bool,
+}
+
+struct FieldShape {
+    name: String,
+    /// `None` if the field type can't be resolved or if it uses a custom deserializer
+    /// (e.g. `#[serde(deserialize_with = "..")]`).
+    type_: Option<Type>,
+    flatten: bool,
+}
+
+impl Shape {
+    /// What kind of data this shape represents—e.g. "a struct" or "a sequence".
+    fn kind(&self) -> &'static str {
+        match self {
+            Shape::Value | Shape::Opaque => "a single value",
+            Shape::Optional(_) => "an optional value",
+            Shape::Sequence(_) => "a sequence",
+            Shape::Map { .. } => "a map",
+            Shape::Struct(_) => "a struct",
+            Shape::Tuple(_) => "a tuple",
+            Shape::DataEnum => "an enum with data-carrying variants",
+        }
+    }
+
+    fn describe(&self, type_: &Type) -> String {
+        format!("{}, `{type_:?}`", self.kind())
+    }
+}
+
+struct ShapeAnalyzer<'a> {
+    krate_collection: &'a CrateCollection,
+    /// Types we have already inspected, to avoid infinite recursion on recursive types.
+    visited: HashSet<Type>,
+    violations: Vec<String>,
+    /// Set if the type is not a struct with named fields (or a map) where one is expected—i.e.
+    /// the problem is the type itself rather than (some of) its fields.
+    unsupported_root: bool,
+}
+
+impl ShapeAnalyzer<'_> {
+    /// The root of a query string (or a URL-encoded body) is a list of key-value pairs.
+    /// It can only be deserialized into a struct with named fields or a map.
+    fn check_form_root(&mut self, type_: &Type) {
+        let location = format!("`{type_:?}`");
+        match shape(self.krate_collection, type_) {
+            Shape::Opaque => {}
+            Shape::Optional(inner) => self.check_form_root(&inner),
+            Shape::Struct(s) => self.check_form_struct(type_, s),
+            Shape::Map { key, value } => {
+                self.check_map_key(&key, type_);
+                self.check_form_value(&value, &format!("each value in `{type_:?}`"));
+            }
+            s => {
+                self.unsupported_root = true;
+                self.violations.push(format!(
+                    "{location} is {}, not a struct with named fields or a map",
+                    s.kind()
+                ));
+            }
+        }
+    }
+
+    fn check_form_struct(&mut self, type_: &Type, s: StructShape) {
+        if !self.visited.insert(type_.to_owned()) {
+            return;
+        }
+        for field in s.fields {
+            let Some(field_type) = field.type_ else {
+                continue;
+            };
+            if field.flatten {
+                // The fields of a flattened struct (or map) are merged into the parent.
+                self.check_form_root(&field_type);
+            } else {
+                let location = format!("the `{}` field of `{type_:?}`", field.name);
+                self.check_form_value(&field_type, &location);
+            }
+        }
+    }
+
+    /// Each value in a query string is a string, possibly repeated to represent a sequence.
+    fn check_form_value(&mut self, type_: &Type, location: &str) {
+        match shape(self.krate_collection, type_) {
+            Shape::Value | Shape::Opaque => {}
+            Shape::Optional(inner) => self.check_form_value(&inner, location),
+            Shape::Sequence(element) => match shape(self.krate_collection, &element) {
+                Shape::Value | Shape::Opaque => {}
+                Shape::Optional(inner)
+                    if matches!(
+                        shape(self.krate_collection, &inner),
+                        Shape::Value | Shape::Opaque
+                    ) => {}
+                s => self.violations.push(format!(
+                    "{location} is a sequence whose element type, `{element:?}`, is {}",
+                    s.kind()
+                )),
+            },
+            s => self
+                .violations
+                .push(format!("{location} is {}", s.describe(type_))),
+        }
+    }
+
+    /// JSON can represent arbitrarily nested structures, but object keys are always strings.
+    fn check_json(&mut self, type_: &Type) {
+        match shape(self.krate_collection, type_) {
+            Shape::Value | Shape::Opaque | Shape::DataEnum => {}
+            Shape::Optional(inner) | Shape::Sequence(inner) => self.check_json(&inner),
+            Shape::Map { key, value } => {
+                self.check_map_key(&key, type_);
+                self.check_json(&value);
+            }
+            Shape::Tuple(elements) => {
+                for element in elements {
+                    self.check_json(&element);
+                }
+            }
+            Shape::Struct(s) => {
+                if !self.visited.insert(type_.to_owned()) {
+                    return;
+                }
+                for field in s.fields {
+                    let Some(field_type) = field.type_ else {
+                        continue;
+                    };
+                    self.check_json(&field_type);
+                }
+            }
+        }
+    }
+
+    /// Map keys are always deserialized from a string, both in query strings and in JSON.
+    fn check_map_key(&mut self, key: &Type, map: &Type) {
+        match shape(self.krate_collection, key) {
+            Shape::Value | Shape::Opaque => {}
+            s => self.violations.push(format!(
+                "each key in `{map:?}` is {}, but map keys must be deserializable from a string",
+                s.describe(key)
+            )),
+        }
+    }
+}
+
+/// Determine the shape of a type, as far as `serde` is concerned.
+fn shape(krate_collection: &CrateCollection, type_: &Type) -> Shape {
+    match type_ {
+        Type::ScalarPrimitive(_) => Shape::Value,
+        Type::Reference(r) => match r.inner.as_ref() {
+            Type::ScalarPrimitive(ScalarPrimitive::Str) => Shape::Value,
+            // Borrowed bytes.
+            Type::Slice(s)
+                if matches!(
+                    s.element_type.as_ref(),
+                    Type::ScalarPrimitive(ScalarPrimitive::U8)
+                ) =>
+            {
+                Shape::Value
+            }
+            inner => shape(krate_collection, inner),
+        },
+        Type::Slice(s) => Shape::Sequence(s.element_type.as_ref().to_owned()),
+        Type::Tuple(t) if t.elements.is_empty() => Shape::Opaque,
+        Type::Tuple(t) => Shape::Tuple(t.elements.clone()),
+        Type::Path(t) => path_shape(krate_collection, t),
+        Type::TypeAlias(_)
+        | Type::Array(_)
+        | Type::RawPointer(_)
+        | Type::FunctionPointer(_)
+        | Type::Generic(_) => Shape::Opaque,
+    }
+}
+
+fn path_shape(krate_collection: &CrateCollection, t: &PathType) -> Shape {
+    let path = t.base_type.iter().map(|s| s.as_str()).collect_vec();
+    let mut type_args = type_arguments(t).cloned();
+    match path.as_slice() {
+        ["alloc", "string", "String"] => return Shape::Value,
+        ["alloc", "borrow", "Cow"]
+        | ["alloc", "boxed", "Box"]
+        | ["alloc", "rc", "Rc"]
+        | ["alloc", "sync", "Arc"] => {
+            return match type_args.next() {
+                Some(inner) => shape(krate_collection, &inner),
+                None => Shape::Opaque,
+            };
+        }
+        ["core", "option", "Option"] => {
+            return match type_args.next() {
+                Some(inner) => Shape::Optional(inner),
+                None => Shape::Opaque,
+            };
+        }
+        ["alloc", "vec", "Vec"]
+        | ["alloc", "collections", "vec_deque", "VecDeque"]
+        | ["alloc", "collections", "linked_list", "LinkedList"]
+        | ["alloc", "collections", "btree", "set", "BTreeSet"]
+        | ["std", "collections", "hash", "set", "HashSet"]
+        | ["indexmap", "set", "IndexSet"] => {
+            return match type_args.next() {
+                Some(element) => Shape::Sequence(element),
+                None => Shape::Opaque,
+            };
+        }
+        ["alloc", "collections", "btree", "map", "BTreeMap"]
+        | ["std", "collections", "hash", "map", "HashMap"]
+        | ["indexmap", "map", "IndexMap"] => {
+            return match (type_args.next(), type_args.next()) {
+                (Some(key), Some(value)) => Shape::Map { key, value },
+                _ => Shape::Opaque,
+            };
+        }
+        _ => {}
+    }
+
+    let Some(item_id) = t.rustdoc_id else {
+        return Shape::Opaque;
+    };
+    let item = krate_collection.get_item_by_global_type_id(&GlobalItemId {
+        rustdoc_item_id: item_id,
+        package_id: t.package_id.clone(),
+    });
+    let (impls, generics) = match &item.inner {
+        ItemEnum::Struct(s) => (&s.impls, &s.generics),
+        ItemEnum::Enum(e) => (&e.impls, &e.generics),
+        _ => return Shape::Opaque,
+    };
+    if !derives_deserialize(krate_collection, t, impls) {
+        return Shape::Opaque;
+    }
+    let container_attrs = serde_attributes(&item.attrs);
+    if container_attrs.iter().any(|a| {
+        matches!(
+            a.as_str(),
+            "from" | "try_from" | "transparent" | "untagged" | "tag" | "content"
+        )
+    }) {
+        // The shape of the type doesn't match the shape of the serialized data.
+        return Shape::Opaque;
+    }
+    let bindings = generic_bindings(generics, t);
+    let get_item = |id| {
+        krate_collection.get_item_by_global_type_id(&GlobalItemId {
+            rustdoc_item_id: id,
+            package_id: t.package_id.clone(),
+        })
+    };
+
+    match &item.inner {
+        ItemEnum::Enum(e) => {
+            let has_data = e.variants.iter().any(|variant_id| {
+                let variant = get_item(*variant_id);
+                match &variant.inner {
+                    ItemEnum::Variant(v) => !matches!(v.kind, VariantKind::Plain),
+                    _ => false,
+                }
+            });
+            if has_data {
+                Shape::DataEnum
+            } else {
+                Shape::Value
+            }
+        }
+        ItemEnum::Struct(s) => {
+            let resolve = |field_id| {
+                let field = get_item(field_id);
+                let ItemEnum::StructField(field_type) = &field.inner else {
+                    return (field.name.clone(), None, vec![]);
+                };
+                let field_attrs = serde_attributes(&field.attrs);
+                let resolved = if field_attrs
+                    .iter()
+                    .any(|a| a == "with" || a == "deserialize_with")
+                {
+                    None
+                } else {
+                    resolve_type(
+                        field_type,
+                        &t.package_id,
+                        krate_collection,
+                        &bindings,
+                        TypeAliasResolution::ResolveThrough,
+                    )
+                    .ok()
+                    .filter(|ty| !ty.is_a_template())
+                };
+                (field.name.clone(), resolved, field_attrs)
+            };
+            match &s.kind {
+                StructKind::Unit => Shape::Value,
+                StructKind::Tuple(fields) => {
+                    let fields = fields
+                        .iter()
+                        .map(|f| f.map(|id| resolve(id).1))
+                        .collect_vec();
+                    match fields.as_slice() {
+                        // Newtypes are transparent.
+                        [Some(Some(inner))] => shape(krate_collection, inner),
+                        _ if fields.iter().all(|f| matches!(f, Some(Some(_)))) => {
+                            Shape::Tuple(fields.into_iter().map(|f| f.unwrap().unwrap()).collect())
+                        }
+                        _ => Shape::Opaque,
+                    }
+                }
+                StructKind::Plain {
+                    fields,
+                    has_stripped_fields,
+                } => {
+                    if *has_stripped_fields {
+                        return Shape::Opaque;
+                    }
+                    let fields = fields
+                        .iter()
+                        .filter_map(|field_id| {
+                            let (name, type_, attrs) = resolve(*field_id);
+                            if attrs
+                                .iter()
+                                .any(|a| a == "skip" || a == "skip_deserializing")
+                            {
+                                return None;
+                            }
+                            Some(FieldShape {
+                                name: name.unwrap_or_default(),
+                                type_,
+                                flatten: attrs.iter().any(|a| a == "flatten"),
+                            })
+                        })
+                        .collect();
+                    Shape::Struct(StructShape {
+                        fields,
+                        deny_unknown_fields: container_attrs
+                            .iter()
+                            .any(|a| a == "deny_unknown_fields"),
+                    })
+                }
+            }
+        }
+        _ => Shape::Opaque,
+    }
+}
+
+/// The canonical paths of `serde::Deserialize`.
+/// It's defined in `serde_core` and re-exported by `serde` since `serde` 1.0.220.
+const DESERIALIZE_PATHS: [[&str; 3]; 2] = [
+    ["serde", "de", "Deserialize"],
+    ["serde_core", "de", "Deserialize"],
+];
+
+/// Returns `true` if `serde::Deserialize` is implemented for the type via
+/// `#[derive(serde::Deserialize)]`.
+pub(super) fn derives_deserialize(
+    krate_collection: &CrateCollection,
+    t: &PathType,
+    impls: &[rustdoc_types::Id],
+) -> bool {
+    impls.iter().any(|impl_id| {
+        let impl_item = krate_collection.get_item_by_global_type_id(&GlobalItemId {
+            rustdoc_item_id: *impl_id,
+            package_id: t.package_id.clone(),
+        });
+        let ItemEnum::Impl(impl_) = &impl_item.inner else {
+            return false;
+        };
+        let Some(trait_) = &impl_.trait_ else {
+            return false;
+        };
+        if !impl_item
+            .attrs
+            .iter()
+            .any(|a| matches!(a, Attribute::AutomaticallyDerived))
+        {
+            return false;
+        }
+        // Any trait named `Deserialize` would match if we only looked at the last path segment.
+        let Ok((_, trait_path)) =
+            krate_collection.get_canonical_path_by_local_type_id(&t.package_id, &trait_.id, None)
+        else {
+            return false;
+        };
+        DESERIALIZE_PATHS.iter().any(|p| trait_path == p)
+    })
+}
+
+/// Extract the keys used in `#[serde(..)]` attributes—e.g. `rename` and `flatten` for
+/// `#[serde(rename = "a", flatten)]`.
+fn serde_attributes(attrs: &[Attribute]) -> Vec<String> {
+    serde_arguments(attrs).into_iter().map(|(k, _)| k).collect()
+}
+
+/// Extract the arguments of `#[serde(..)]` attributes, as key-value pairs—e.g.
+/// `[("rename", Some("a")), ("flatten", None)]` for `#[serde(rename = "a", flatten)]`.
+///
+/// For arguments that can be specified separately for serialization and deserialization
+/// (e.g. `rename(deserialize = "a")`), the value used for deserialization is returned.
+pub(super) fn serde_arguments(attrs: &[Attribute]) -> Vec<(String, Option<String>)> {
+    let mut arguments = Vec::new();
+    for attr in attrs {
+        let Attribute::Other(attr) = attr else {
+            continue;
+        };
+        let Some(args) = attr
+            .trim()
+            .strip_prefix("#[serde(")
+            .and_then(|a| a.strip_suffix(")]"))
+        else {
+            continue;
+        };
+        arguments.extend(
+            split_arguments(args)
+                .into_iter()
+                .filter_map(|a| parse_argument(a, "deserialize")),
+        );
+    }
+    arguments
+}
+
+/// Split on top-level commas, ignoring the ones inside string literals or parentheses.
+fn split_arguments(args: &str) -> Vec<&str> {
+    let (mut depth, mut in_string, mut start) = (0usize, false, 0);
+    let mut arguments = Vec::new();
+    for (i, c) in args.char_indices() {
+        match c {
+            '"' => in_string = !in_string,
+            '(' if !in_string => depth += 1,
+            ')' if !in_string => depth = depth.saturating_sub(1),
+            ',' if !in_string && depth == 0 => {
+                arguments.push(&args[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    arguments.push(&args[start..]);
+    arguments
+}
+
+/// Parse `key`, `key = "value"` or `key(nested = "value", ..)`.
+/// For the latter, the value associated with `nested` is returned.
+fn parse_argument(argument: &str, nested: &str) -> Option<(String, Option<String>)> {
+    let argument = argument.trim();
+    let key_end = argument.find(['=', '(']).unwrap_or(argument.len());
+    let key = argument[..key_end].trim();
+    if key.is_empty() {
+        return None;
+    }
+    let rest = argument[key_end..].trim();
+    let value = if let Some(value) = rest.strip_prefix('=') {
+        value
+            .trim()
+            .strip_prefix('"')
+            .and_then(|v| v.strip_suffix('"'))
+            .map(ToOwned::to_owned)
+    } else if let Some(inner) = rest.strip_prefix('(').and_then(|r| r.strip_suffix(')')) {
+        split_arguments(inner)
+            .into_iter()
+            .filter_map(|a| parse_argument(a, nested))
+            .find(|(k, _)| k == nested)
+            .and_then(|(_, v)| v)
+    } else {
+        None
+    };
+    Some((key.to_owned(), value))
+}
+
+/// Map the generic parameters of a type definition to the concrete arguments of `t`.
+pub(super) fn generic_bindings(generics: &Generics, t: &PathType) -> GenericBindings {
+    let mut bindings = GenericBindings::default();
+    for (param, arg) in generics.params.iter().zip(t.generic_arguments.iter()) {
+        if let (GenericParamDefKind::Type { .. }, GenericArgument::TypeParameter(type_)) =
+            (&param.kind, arg)
+        {
+            bindings.types.insert(param.name.clone(), type_.to_owned());
+        }
+    }
+    bindings
+}
+
+/// The type arguments of `t`, skipping lifetimes and const generics.
+pub(super) fn type_arguments(t: &PathType) -> impl Iterator<Item = &Type> {
+    t.generic_arguments.iter().filter_map(|arg| match arg {
+        GenericArgument::TypeParameter(t) => Some(t),
+        _ => None,
+    })
+}
+
+fn report_unsupported_shape(
+    component_db: &ComponentDb,
+    computation_db: &ComputationDb,
+    diagnostics: &DiagnosticSink,
+    consumers: &IndexSet<ComponentId>,
+    extractor: Extractor,
+    extracted_type: &Type,
+    violations: &[String],
+    unsupported_root: bool,
+) {
+    let extractor_name = extractor.name();
+    let (constraints, help) = match extractor.format() {
+        Format::Form => (
+            format!(
+                "{} is a flat list of key-value pairs: it can only be deserialized into a struct \
+                with named fields (or a map) whose values are either single values \
+                (e.g. numbers or strings), optional single values or sequences of single values.",
+                capitalize(extractor.payload())
+            ),
+            if unsupported_root {
+                format!(
+                    "Extract a struct with named fields (or a map) instead of `{extracted_type:?}`, \
+                    with one field for each key you expect in {}.",
+                    extractor.payload()
+                )
+            } else {
+                format!(
+                    "Use `#[serde(flatten)]` to merge the fields of nested structs into \
+                    `{extracted_type:?}`, or reshape it to only contain single values and \
+                    sequences of single values."
+                )
+            },
+        ),
+        Format::Json => (
+            "JSON object keys are always strings: maps can only use keys that can be \
+            deserialized from a string (e.g. strings, numbers or enums with unit variants only)."
+                .to_string(),
+            "Use a string-like type as the key type of your maps.".to_string(),
+        ),
+    };
+    let violations = violations.iter().map(|v| format!("- {v}")).join("\n");
+    for component_id in consumers {
+        let Some(user_id) = component_db.user_component_id(*component_id) else {
+            continue;
+        };
+        let callable = &computation_db[user_id];
+        let kind = component_db.user_db()[user_id].kind();
+        let source = diagnostics.annotated(
+            component_db.registration_target(user_id),
+            format!("The {kind} asking for `{extractor_name}<{extracted_type:?}>`"),
+        );
+        let error = anyhow!(
+            "`{callable}` is trying to extract `{extractor_name}<{extracted_type:?}>`, but \
+            `{extracted_type:?}` can't be deserialized from {}.\n\
+            {constraints}\n\
+            That's not the case here:\n{violations}\n\n\
+            This is going to cause a runtime error!",
+            extractor.payload()
+        );
+        let d = CompilerDiagnostic::builder(error)
+            .optional_source(source)
+            .help(help.clone())
+            .build();
+        diagnostics.push(d);
+    }
+}
+
+fn report_missing_deny_unknown_fields(
+    component_db: &ComponentDb,
+    computation_db: &ComputationDb,
+    diagnostics: &DiagnosticSink,
+    consumers: &IndexSet<ComponentId>,
+    extractor: Extractor,
+    extracted_type: &Type,
+    severity: Severity,
+) {
+    let extractor_name = extractor.name();
+    for component_id in consumers {
+        let Some(user_id) = component_db.user_component_id(*component_id) else {
+            continue;
+        };
+        let callable = &computation_db[user_id];
+        let kind = component_db.user_db()[user_id].kind();
+        let source = diagnostics.annotated(
+            component_db.registration_target(user_id),
+            format!("The {kind} asking for `{extractor_name}<{extracted_type:?}>`"),
+        );
+        let error = anyhow!(
+            "`{callable}` is extracting `{extractor_name}<{extracted_type:?}>`, but \
+            `{extracted_type:?}` doesn't reject unknown fields.\n\
+            Incoming requests with fields that don't exist in `{extracted_type:?}` will be \
+            accepted, and the unknown fields will be silently ignored."
+        );
+        let d = CompilerDiagnostic::builder(error)
+            .optional_source(source)
+            .severity(severity)
+            .help(format!(
+                "Add `#[serde(deny_unknown_fields)]` to the definition of `{extracted_type:?}`."
+            ))
+            .build();
+        diagnostics.push(d);
+    }
+}
+
+fn capitalize(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
@@ -1,9 +1,12 @@
 use std::fmt::Formatter;
 
 use guppy::PackageId;
-use rustdoc_types::{GenericParamDefKind, ItemEnum, Type as RustdocType};
+use rustdoc_types::{
+    GenericBound, GenericParamDefKind, Generics, Impl, ItemEnum, TraitBoundModifier,
+    Type as RustdocType, WherePredicate,
+};
 
-use crate::language::{PathType, Type};
+use crate::language::{GenericArgument, PathType, ScalarPrimitive, Type};
 use crate::rustdoc::{Crate, CrateCollection};
 use rustdoc_resolver::{GenericBindings, TypeAliasResolution, resolve_type};
 
@@ -16,32 +19,43 @@ pub(crate) fn assert_trait_is_implemented(
     type_: &Type,
     expected_trait: &PathType,
 ) -> Result<(), MissingTraitImplementationError> {
-    match implements_trait(krate_collection, type_, expected_trait) {
-        Ok(implements) => {
-            if implements {
-                Ok(())
-            } else {
-                Err(MissingTraitImplementationError {
-                    type_: type_.to_owned(),
-                    trait_: expected_trait.to_owned().into(),
-                })
+    let error = |unsatisfied_bound: Option<UnsatisfiedBound>, undetermined: Option<String>| {
+        MissingTraitImplementationError {
+            type_: type_.to_owned(),
+            trait_: expected_trait.to_owned().into(),
+            unsatisfied_bound: unsatisfied_bound.map(Box::new),
+            undetermined,
+        }
+    };
+    match check_trait_implementation(krate_collection, type_, expected_trait, 0) {
+        Ok(TraitImplementation::Found) => Ok(()),
+        Ok(TraitImplementation::Missing { unsatisfied_bound }) => {
+            Err(error(unsatisfied_bound, None))
+        }
+        Ok(TraitImplementation::Unknown { reason }) => {
+            // Generic templates can't be fully checked: the check is performed again
+            // on each of their concrete instances, once their generic parameters have been bound.
+            if !type_.unassigned_generic_type_parameters().is_empty() {
+                return Ok(());
             }
+            Err(error(None, Some(reason)))
         }
         Err(e) => {
             tracing::trace!(
-                "Failing to determine if `{:?}` implements `{:?}`. Assuming it does not—{:?}",
+                "Failing to determine if `{:?}` implements `{:?}`—{:?}",
                 type_,
                 expected_trait,
                 e
             );
-            Err(MissingTraitImplementationError {
-                type_: type_.to_owned(),
-                trait_: expected_trait.to_owned().into(),
-            })
+            Err(error(None, Some(ANALYSIS_FAILURE.to_owned())))
         }
     }
 }
 
+/// The reason we report when we fail to process the rustdoc JSON required
+/// to evaluate a trait implementation.
+const ANALYSIS_FAILURE: &str = "Pavex failed to analyze the relevant trait implementations";
+
 fn get_crate_by_package_id<'a>(
     krate_collection: &'a CrateCollection,
     package_id: &'a PackageId,
@@ -55,17 +69,78 @@ const COPY_TRAIT_PATH: [&str; 3] = ["core", "marker", "Copy"];
 const SEND_TRAIT_PATH: [&str; 3] = ["core", "marker", "Send"];
 const SYNC_TRAIT_PATH: [&str; 3] = ["core", "marker", "Sync"];
 const UNPIN_TRAIT_PATH: [&str; 3] = ["core", "marker", "Unpin"];
+const SIZED_TRAIT_PATH: [&str; 3] = ["core", "marker", "Sized"];
 const CLONE_TRAIT_PATH: [&str; 3] = ["core", "clone", "Clone"];
 
-/// It returns `true` if `type_` implements the specified trait.
+/// How deep we are willing to go when checking the bounds of an implementation
+/// (e.g. `Vec<Vec<T>>: Clone` requires `Vec<T>: Clone`, which in turn requires `T: Clone`).
+///
+/// It guards us against infinite recursion when dealing with mutually recursive blanket
+/// implementations.
+const MAX_BOUND_DEPTH: usize = 32;
+
+/// The outcome of a trait implementation check.
+enum TraitImplementation {
+    Found,
+    Missing {
+        /// If set, we found an implementation of the trait that targets the type
+        /// we are checking, but one of its bounds is not satisfied.
+        unsatisfied_bound: Option<UnsatisfiedBound>,
+    },
+    /// We can't determine if the trait is implemented—e.g. the type is an unbound
+    /// generic parameter or one of the relevant bounds couldn't be resolved.
+    Unknown {
+        /// Why we couldn't reach a conclusion, in a form suitable for user-facing diagnostics.
+        reason: String,
+    },
+}
+
+impl TraitImplementation {
+    fn missing() -> Self {
+        Self::Missing {
+            unsatisfied_bound: None,
+        }
+    }
+}
+
+/// Determine if `type_` implements the specified trait.
 ///
 /// The trait path must be fully resolved: it should NOT point to a re-export
 /// (e.g. `std::marker::Sync` won't work, you should use `core::marker::Sync`).
-pub(crate) fn implements_trait(
+fn check_trait_implementation(
     krate_collection: &CrateCollection,
     type_: &Type,
     expected_trait: &PathType,
-) -> Result<bool, anyhow::Error> {
+    depth: usize,
+) -> Result<TraitImplementation, anyhow::Error> {
+    if depth > MAX_BOUND_DEPTH {
+        tracing::trace!(
+            "Reached the maximum depth ({MAX_BOUND_DEPTH}) while checking the bounds of the implementations of `{:?}` for `{:?}`",
+            expected_trait,
+            type_
+        );
+        return Ok(TraitImplementation::Unknown {
+            reason: format!(
+                "the bounds of the relevant implementations are nested more than {MAX_BOUND_DEPTH} levels deep"
+            ),
+        });
+    }
+    let implements = |t: &Type| nested_check(krate_collection, t, expected_trait, depth + 1);
+
+    // `Sized` is implemented by the compiler for all types with a size known at compile time.
+    // It never shows up in the list of implementations in the JSON docs.
+    if expected_trait.base_type == SIZED_TRAIT_PATH {
+        let is_sized = !matches!(
+            type_,
+            Type::Slice(_) | Type::ScalarPrimitive(ScalarPrimitive::Str)
+        );
+        return Ok(if is_sized {
+            TraitImplementation::Found
+        } else {
+            TraitImplementation::missing()
+        });
+    }
+
     let trait_definition_crate =
         get_crate_by_package_id(krate_collection, &expected_trait.package_id)?;
     let trait_item_id = trait_definition_crate
@@ -75,6 +150,20 @@ pub(crate) fn implements_trait(
         unreachable!()
     };
 
+    let is_builtin_marker = expected_trait.base_type == SEND_TRAIT_PATH
+        || expected_trait.base_type == SYNC_TRAIT_PATH
+        || expected_trait.base_type == UNPIN_TRAIT_PATH;
+    let is_copy_or_clone =
+        expected_trait.base_type == COPY_TRAIT_PATH || expected_trait.base_type == CLONE_TRAIT_PATH;
+
+    // The first candidate implementation that targets `type_` but whose bounds
+    // are not satisfied.
+    // It is used to explain to the user why the trait is not implemented.
+    let mut unsatisfied_bound = None;
+    // Set if we couldn't determine whether one of the candidate implementations applies.
+    // If none of the other candidates applies, we can't conclude that the trait is missing.
+    let mut undetermined = None;
+
     // Due to Rust's orphan rule, a trait implementation for a type can live in two places:
     // - In the crate where the type was defined;
     // - In the crate where the trait was defined.
@@ -125,9 +214,9 @@ pub(crate) fn implements_trait(
                     &generic_bindings,
                     TypeAliasResolution::ResolveThrough,
                 )?;
-                if implements_trait(krate_collection, &type_, expected_trait)? {
-                    return Ok(true);
-                }
+                // The alias has been fully resolved: the outcome of the check for the aliased type
+                // is the outcome for the alias itself.
+                return check_trait_implementation(krate_collection, &type_, expected_trait, depth);
             }
             let impls = match &type_item.inner {
                 ItemEnum::Struct(s) => &s.impls,
@@ -137,32 +226,49 @@ pub(crate) fn implements_trait(
                     unreachable!()
                 }
             };
+            // The "impls" for a rustdoc item include the synthetic implementations of auto traits
+            // (e.g. `impl<T> Send for Foo<T> where T: Send`), blanket implementations
+            // (e.g. `impl<T: Display> ToString for T`) as well as implementations for references
+            // to the type!
+            // `check_impl` takes care of verifying that each implementation actually applies to
+            // our type, bounds included.
             for impl_id in impls {
                 let item = type_definition_crate.get_item_by_local_type_id(impl_id);
-                let (trait_id, implementer_type) = match &item.inner {
-                    ItemEnum::Impl(impl_) => {
-                        if impl_.is_negative {
-                            continue;
-                        }
-                        (impl_.trait_.as_ref().map(|p| &p.id), &impl_.for_)
-                    }
-                    _ => unreachable!(),
+                let ItemEnum::Impl(impl_) = &item.inner else {
+                    unreachable!()
                 };
-                if let Some(trait_id) = trait_id
-                    && let Ok((_, trait_path)) = krate_collection
-                        .get_canonical_path_by_local_type_id(
-                            &our_path_type.package_id,
-                            trait_id,
-                            None,
-                        )
-                        && trait_path == expected_trait.base_type
-                            // The "impls" for a rustdoc item include implementations for
-                            // references to the type!
-                            // Therefore we must verify that the implementer type is indeed the
-                            // "owned" version of our type.
-                            && is_equivalent(implementer_type, type_, krate_collection, &our_path_type.package_id)
-                {
-                    return Ok(true);
+                if impl_.is_negative {
+                    continue;
+                }
+                let Some(trait_) = &impl_.trait_ else {
+                    continue;
+                };
+                let Ok((_, trait_path)) = krate_collection.get_canonical_path_by_local_type_id(
+                    &our_path_type.package_id,
+                    &trait_.id,
+                    None,
+                ) else {
+                    continue;
+                };
+                if trait_path != expected_trait.base_type {
+                    continue;
+                }
+                match check_impl(
+                    krate_collection,
+                    impl_,
+                    &our_path_type.package_id,
+                    type_,
+                    expected_trait,
+                    depth,
+                ) {
+                    ImplApplicability::Applies => return Ok(TraitImplementation::Found),
+                    ImplApplicability::BoundNotSatisfied(b) => {
+                        unsatisfied_bound.get_or_insert(b);
+                    }
+                    ImplApplicability::Undetermined(reason) => {
+                        undetermined.get_or_insert(reason);
+                    }
+                    ImplApplicability::DoesNotApply => {}
                 }
             }
         }
@@ -170,102 +276,89 @@ pub(crate) fn implements_trait(
             // Tuple trait implementations in std are somewhat magical
             // (see https://doc.rust-lang.org/std/primitive.tuple.html#trait-implementations-1).
             // We handle the ones we know we care about (marker traits and Clone).
-            if (expected_trait.base_type == SEND_TRAIT_PATH
-                || expected_trait.base_type == SYNC_TRAIT_PATH
-                || expected_trait.base_type == COPY_TRAIT_PATH
-                || expected_trait.base_type == UNPIN_TRAIT_PATH
-                || expected_trait.base_type == CLONE_TRAIT_PATH)
-                && t.elements
-                    .iter()
-                    .all(|t| match implements_trait(krate_collection, t, expected_trait) {
-                        Ok(t) => t,
-                        Err(e) => {
-                            tracing::trace!("Failing to determine if `{:?}` implements `{:?}`. Assuming it does not—{:?}", t, expected_trait, e);
-                            false
-                        }
-                    })
-            {
-                return Ok(true);
+            if is_builtin_marker || is_copy_or_clone {
+                return Ok(all_implement(t.elements.iter().map(implements)));
             }
         }
         Type::Reference(r) => {
-            // `& &T` is `Send` if `&T` is `Send`, therefore `&T` is `Sync` if `T` if `Sync`.
-            if (expected_trait.base_type == SYNC_TRAIT_PATH
-                || expected_trait.base_type == SEND_TRAIT_PATH)
-                && implements_trait(krate_collection, &r.inner, expected_trait)?
-            {
-                return Ok(true);
+            // `&T` is `Send` if `T` is `Sync`, while `&mut T` is `Send` if `T` is `Send`.
+            // Both `&T` and `&mut T` are `Sync` if `T` is `Sync`.
+            // See https://doc.rust-lang.org/std/marker/trait.Sync.html
+            if expected_trait.base_type == SEND_TRAIT_PATH {
+                let inner_trait = if r.is_mutable {
+                    expected_trait.to_owned()
+                } else {
+                    sibling_marker_trait(expected_trait, SYNC_TRAIT_PATH)
+                };
+                let outcome = nested_check(krate_collection, &r.inner, &inner_trait, depth + 1);
+                if !matches!(outcome, TraitImplementation::Missing { .. }) {
+                    return Ok(outcome);
+                }
+            }
+            if expected_trait.base_type == SYNC_TRAIT_PATH {
+                let outcome = implements(&r.inner);
+                if !matches!(outcome, TraitImplementation::Missing { .. }) {
+                    return Ok(outcome);
+                }
+            }
+            // References are always `Unpin`, regardless of the type they point to.
+            if expected_trait.base_type == UNPIN_TRAIT_PATH {
+                return Ok(TraitImplementation::Found);
             }
             // `&T` is always `Copy`, but `&mut T` is never `Copy`.
             // See https://doc.rust-lang.org/std/marker/trait.Copy.html#impl-Copy-for-%26T and
             // https://doc.rust-lang.org/std/marker/trait.Copy.html#when-cant-my-type-be-copy
             // `Copy` implies `Clone`.
-            if !r.is_mutable && (expected_trait.base_type == COPY_TRAIT_PATH)
-                || (expected_trait.base_type == CLONE_TRAIT_PATH)
-            {
-                return Ok(true);
+            if is_copy_or_clone {
+                return Ok(if r.is_mutable {
+                    TraitImplementation::missing()
+                } else {
+                    TraitImplementation::Found
+                });
             }
-            // TODO: Unpin and other traits
-        }
-        Type::ScalarPrimitive(_) => {
-            if expected_trait.base_type == SEND_TRAIT_PATH
-                || expected_trait.base_type == SYNC_TRAIT_PATH
-                || expected_trait.base_type == COPY_TRAIT_PATH
-                || expected_trait.base_type == UNPIN_TRAIT_PATH
-                || expected_trait.base_type == CLONE_TRAIT_PATH
-            {
-                return Ok(true);
+        }
+        Type::ScalarPrimitive(p) => {
+            // `str` is unsized, therefore it can't be `Copy` or `Clone`.
+            let is_str = *p == ScalarPrimitive::Str;
+            if is_builtin_marker || (is_copy_or_clone && !is_str) {
+                return Ok(TraitImplementation::Found);
             }
-            // TODO: handle other traits
         }
         Type::Slice(s) => {
-            if (expected_trait.base_type == SEND_TRAIT_PATH
-                || expected_trait.base_type == SYNC_TRAIT_PATH)
-                && implements_trait(krate_collection, &s.element_type, expected_trait)?
-            {
-                return Ok(true);
+            // Slices are unsized, therefore they can't be `Copy` or `Clone`.
+            // They are `Send`, `Sync` and `Unpin` if their element type is.
+            if is_builtin_marker {
+                return Ok(implements(&s.element_type));
             }
-            // TODO: handle Unpin + other traits
         }
         Type::Array(a) => {
             // Arrays implement Send/Sync/Copy/Clone/Unpin if their element type does.
-            if (expected_trait.base_type == SEND_TRAIT_PATH
-                || expected_trait.base_type == SYNC_TRAIT_PATH
-                || expected_trait.base_type == COPY_TRAIT_PATH
-                || expected_trait.base_type == CLONE_TRAIT_PATH
-                || expected_trait.base_type == UNPIN_TRAIT_PATH)
-                && implements_trait(krate_collection, &a.element_type, expected_trait)?
-            {
-                return Ok(true);
+            if is_builtin_marker || is_copy_or_clone {
+                return Ok(implements(&a.element_type));
             }
-            // TODO: handle other traits
         }
         Type::RawPointer(_) => {
-            // Raw pointers are `Copy` and `Clone`, but not `Send` or `Sync`.
-            if expected_trait.base_type == COPY_TRAIT_PATH
-                || expected_trait.base_type == CLONE_TRAIT_PATH
-                || expected_trait.base_type == UNPIN_TRAIT_PATH
-            {
-                return Ok(true);
+            // Raw pointers are `Copy`, `Clone` and `Unpin`, but not `Send` or `Sync`.
+            if is_copy_or_clone || expected_trait.base_type == UNPIN_TRAIT_PATH {
+                return Ok(TraitImplementation::Found);
             }
-            // TODO: handle other traits
         }
         Type::FunctionPointer(_) => {
             // Function pointers implement Copy, Clone, Send, Sync, and Unpin.
-            if expected_trait.base_type == COPY_TRAIT_PATH
-                || expected_trait.base_type == CLONE_TRAIT_PATH
-                || expected_trait.base_type == SEND_TRAIT_PATH
-                || expected_trait.base_type == SYNC_TRAIT_PATH
-                || expected_trait.base_type == UNPIN_TRAIT_PATH
-            {
-                return Ok(true);
+            if is_copy_or_clone || is_builtin_marker {
+                return Ok(TraitImplementation::Found);
             }
-            // TODO: handle other traits
         }
-        Type::Generic(_) => {
-            // TODO: handle blanket implementations. As a first approximation,
-            //   we assume that if the type is generic, it implements all traits.
-            return Ok(true);
+        Type::Generic(g) => {
+            // An unassigned generic parameter may be bound to any type that satisfies
+            // the constraints of the component it belongs to.
+            // We can't tell if it implements the trait until it has been bound to a concrete type.
+            return Ok(TraitImplementation::Unknown {
+                reason: format!(
+                    "`{}` is a generic parameter that hasn't been bound to a concrete type",
+                    g.name
+                ),
+            });
         }
     }
 
@@ -275,38 +368,372 @@ pub(crate) fn implements_trait(
     // Auto-traits (e.g. Send, Sync, etc.) always appear as implemented in the crate where
     // the implementer is defined.
     if trait_item.is_auto {
-        return Ok(false);
+        return Ok(missing_or_unknown(unsatisfied_bound, undetermined));
     }
 
     for impl_id in &trait_item.implementations {
         let impl_item = trait_definition_crate.get_item_by_local_type_id(impl_id);
-        let implementer = match &impl_item.inner {
+        let impl_ = match &impl_item.inner {
             ItemEnum::Impl(impl_) => {
                 if impl_.is_negative {
                     continue;
                 }
-                &impl_.for_
+                impl_
             }
             n => {
                 dbg!(n);
                 unreachable!()
             }
         };
-        if is_equivalent(
-            implementer,
-            type_,
+        match check_impl(
             krate_collection,
+            impl_,
             &trait_definition_crate.core.package_id,
+            type_,
+            expected_trait,
+            depth,
+        ) {
+            ImplApplicability::Applies => return Ok(TraitImplementation::Found),
+            ImplApplicability::BoundNotSatisfied(b) => {
+                unsatisfied_bound.get_or_insert(b);
+            }
+            ImplApplicability::Undetermined(reason) => {
+                undetermined.get_or_insert(reason);
+            }
+            ImplApplicability::DoesNotApply => {}
+        }
+    }
+    Ok(missing_or_unknown(unsatisfied_bound, undetermined))
+}
+
+/// The outcome of a check where no candidate implementation was found to apply.
+fn missing_or_unknown(
+    unsatisfied_bound: Option<UnsatisfiedBound>,
+    undetermined: Option<String>,
+) -> TraitImplementation {
+    match undetermined {
+        Some(reason) => TraitImplementation::Unknown { reason },
+        None => TraitImplementation::Missing { unsatisfied_bound },
+    }
+}
+
+/// Like [`check_trait_implementation`], but errors are reported as an [`TraitImplementation::Unknown`]
+/// outcome rather than being propagated.
+fn nested_check(
+    krate_collection: &CrateCollection,
+    type_: &Type,
+    expected_trait: &PathType,
+    depth: usize,
+) -> TraitImplementation {
+    check_trait_implementation(krate_collection, type_, expected_trait, depth).unwrap_or_else(|e| {
+        tracing::trace!(
+            "Failing to determine if `{:?}` implements `{:?}`—{:?}",
+            type_,
+            expected_trait,
+            e
+        );
+        TraitImplementation::Unknown {
+            reason: ANALYSIS_FAILURE.to_owned(),
+        }
+    })
+}
+
+/// Combine the outcomes for the elements of a compound type (e.g. a tuple)
+/// that implements a trait if all its elements do.
+///
+/// A single missing implementation is enough to rule the trait out, even if we couldn't
+/// determine the outcome for some of the other elements.
+fn all_implement(outcomes: impl Iterator<Item = TraitImplementation>) -> TraitImplementation {
+    let mut undetermined = None;
+    for outcome in outcomes {
+        match outcome {
+            TraitImplementation::Found => {}
+            TraitImplementation::Missing { .. } => return TraitImplementation::missing(),
+            TraitImplementation::Unknown { reason } => {
+                undetermined.get_or_insert(reason);
+            }
+        }
+    }
+    match undetermined {
+        Some(reason) => TraitImplementation::Unknown { reason },
+        None => TraitImplementation::Found,
+    }
+}
+
+/// Build the path to another marker trait defined in `core::marker`, using the same package
+/// as `marker_trait`.
+fn sibling_marker_trait(marker_trait: &PathType, path: [&str; 3]) -> PathType {
+    PathType {
+        package_id: marker_trait.package_id.clone(),
+        rustdoc_id: None,
+        base_type: path.into_iter().map(ToOwned::to_owned).collect(),
+        generic_arguments: vec![],
+    }
+}
+
+/// Does a given `impl` block apply to the type we are checking?
+enum ImplApplicability {
+    Applies,
+    DoesNotApply,
+    /// The `impl` block targets the type we are checking, but one of its bounds
+    /// is not satisfied.
+    BoundNotSatisfied(UnsatisfiedBound),
+    /// The `impl` block targets the type we are checking, but we couldn't determine
+    /// if its bounds are satisfied.
+    Undetermined(String),
+}
+
+/// Determine if `impl_` applies to `type_`.
+///
+/// The implementer type of the `impl` block is used as a template: if it can be matched
+/// against `type_`, we evaluate the bounds of the `impl` block (both inline and in `where`
+/// clauses) using the generic bindings we obtained from the match.
+fn check_impl(
+    krate_collection: &CrateCollection,
+    impl_: &Impl,
+    impl_package_id: &PackageId,
+    type_: &Type,
+    expected_trait: &PathType,
+    depth: usize,
+) -> ImplApplicability {
+    // Blanket implementations (e.g. `impl<T: Display> ToString for T`) are inlined by rustdoc
+    // in the list of implementations of every type they could apply to.
+    // For those, `for_` is set to the type itself, while `blanket_impl` holds the type
+    // the implementation was actually written for.
+    let implementer = impl_.blanket_impl.as_ref().unwrap_or(&impl_.for_);
+    // A cheap check to weed out implementations that can't possibly apply.
+    if !is_equivalent(
+        implementer,
+        type_,
+        &impl_.generics,
+        krate_collection,
+        impl_package_id,
+    ) {
+        return ImplApplicability::DoesNotApply;
+    }
+    let implementer = match resolve_type(
+        implementer,
+        impl_package_id,
+        krate_collection,
+        &GenericBindings::default(),
+        TypeAliasResolution::ResolveThrough,
+    ) {
+        Ok(t) => t,
+        Err(e) => {
+            tracing::trace!(
+                "Failed to resolve the implementer type of an implementation of `{:?}` \
+                that may apply to `{:?}`—{:?}",
+                expected_trait,
+                type_,
+                e
+            );
+            return ImplApplicability::Undetermined(ANALYSIS_FAILURE.to_owned());
+        }
+    };
+    // `std` implementations are often written against the full list of generic parameters
+    // (e.g. `impl<T, A: Allocator> Clone for Vec<T, A>`), while our types omit the defaulted ones
+    // (e.g. `Vec<T>` rather than `Vec<T, Global>`).
+    // We spell out the defaults on both sides to be able to match them against each other.
+    let Some(bindings) = with_default_generic_arguments(krate_collection, &implementer)
+        .is_a_template_for(&with_default_generic_arguments(krate_collection, type_))
+    else {
+        return ImplApplicability::DoesNotApply;
+    };
+    let generic_bindings = GenericBindings {
+        types: bindings,
+        ..Default::default()
+    };
+
+    for (bounded_type, bound) in impl_bounds(impl_) {
+        let GenericBound::TraitBound {
+            trait_, modifier, ..
+        } = bound
+        else {
+            // Lifetime bounds are not relevant to us, since we erase lifetimes
+            // when checking trait implementations.
+            continue;
+        };
+        // `?Sized` relaxes a bound, it doesn't introduce a new one.
+        if *modifier == TraitBoundModifier::Maybe {
+            continue;
+        }
+        let bound_trait = match krate_collection.get_canonical_path_by_local_type_id(
+            impl_package_id,
+            &trait_.id,
+            None,
+        ) {
+            Ok((global_id, path)) => PathType {
+                package_id: global_id.package_id,
+                rustdoc_id: Some(global_id.rustdoc_item_id),
+                base_type: path.to_vec(),
+                generic_arguments: vec![],
+            },
+            Err(e) => {
+                tracing::trace!(
+                    "Failed to resolve the trait path for a bound (`{}`)—{:?}",
+                    trait_.path,
+                    e
+                );
+                return ImplApplicability::Undetermined(format!(
+                    "Pavex failed to resolve the `{}` bound on one of its implementations",
+                    trait_.path
+                ));
+            }
+        };
+        let concrete_bounded_type = match resolve_type(
+            &bounded_type,
+            impl_package_id,
+            krate_collection,
+            &generic_bindings,
+            TypeAliasResolution::ResolveThrough,
         ) {
-            return Ok(true);
+            Ok(t) => t,
+            Err(e) => {
+                tracing::trace!(
+                    "Failed to resolve the type constrained by a `{:?}` bound—{:?}",
+                    bound_trait,
+                    e
+                );
+                return ImplApplicability::Undetermined(format!(
+                    "Pavex failed to resolve the type constrained by a `{}` bound on one of its implementations",
+                    trait_.path
+                ));
+            }
+        };
+        match nested_check(
+            krate_collection,
+            &concrete_bounded_type,
+            &bound_trait,
+            depth + 1,
+        ) {
+            TraitImplementation::Found => {}
+            TraitImplementation::Missing { .. } => {
+                let template_bounded_type = resolve_type(
+                    &bounded_type,
+                    impl_package_id,
+                    krate_collection,
+                    &GenericBindings::default(),
+                    TypeAliasResolution::ResolveThrough,
+                )
+                .unwrap_or_else(|_| concrete_bounded_type.clone());
+                return ImplApplicability::BoundNotSatisfied(UnsatisfiedBound {
+                    implementer,
+                    bounded_type: template_bounded_type,
+                    concrete_bounded_type,
+                    bound: bound_trait.into(),
+                });
+            }
+            TraitImplementation::Unknown { reason } => {
+                return ImplApplicability::Undetermined(reason);
+            }
         }
     }
-    Ok(false)
+    ImplApplicability::Applies
 }
 
+/// Append the default value of the trailing generic parameters that `type_` doesn't specify
+/// (e.g. `Arc<T>` becomes `Arc<T, alloc::alloc::Global>`).
+///
+/// `type_` is returned unchanged if it's not a path type, if it already specifies all
+/// its generic parameters, or if we fail to resolve the relevant defaults.
+fn with_default_generic_arguments(krate_collection: &CrateCollection, type_: &Type) -> Type {
+    let Type::Path(path_type) = type_ else {
+        return type_.to_owned();
+    };
+    let Some(item) = krate_collection
+        .get_crate_by_package_id(&path_type.package_id)
+        .and_then(|krate| {
+            krate
+                .get_item_id_by_path(&path_type.base_type, krate_collection)
+                .ok()?
+                .ok()
+        })
+        .map(|id| krate_collection.get_item_by_global_type_id(&id))
+    else {
+        return type_.to_owned();
+    };
+    let params = match &item.inner {
+        ItemEnum::Struct(s) => &s.generics.params,
+        ItemEnum::Enum(e) => &e.generics.params,
+        ItemEnum::Union(u) => &u.generics.params,
+        _ => return type_.to_owned(),
+    };
+    if params.len() <= path_type.generic_arguments.len() {
+        return type_.to_owned();
+    }
+
+    // Defaults may refer to the parameters that precede them (e.g. `struct Foo<T, U = T>`).
+    let mut bindings = GenericBindings::default();
+    for (param, arg) in params.iter().zip(path_type.generic_arguments.iter()) {
+        if let (GenericParamDefKind::Type { .. }, GenericArgument::TypeParameter(t)) =
+            (&param.kind, arg)
+        {
+            bindings.types.insert(param.name.clone(), t.to_owned());
+        }
+    }
+    let mut padded = path_type.to_owned();
+    for param in &params[path_type.generic_arguments.len()..] {
+        let GenericParamDefKind::Type {
+            default: Some(default),
+            ..
+        } = &param.kind
+        else {
+            return type_.to_owned();
+        };
+        let Ok(default) = resolve_type(
+            default,
+            &path_type.package_id,
+            krate_collection,
+            &bindings,
+            TypeAliasResolution::ResolveThrough,
+        ) else {
+            return type_.to_owned();
+        };
+        bindings.types.insert(param.name.clone(), default.clone());
+        padded
+            .generic_arguments
+            .push(GenericArgument::TypeParameter(default));
+    }
+    Type::Path(padded)
+}
+
+/// Collect all the trait bounds of an `impl` block, both inline (e.g. `impl<T: Clone>`)
+/// and in `where` clauses (e.g. `where T: Clone`).
+fn impl_bounds(impl_: &Impl) -> Vec<(std::borrow::Cow<'_, RustdocType>, &GenericBound)> {
+    let mut bounds = vec![];
+    for param in &impl_.generics.params {
+        if let GenericParamDefKind::Type {
+            bounds: param_bounds,
+            ..
+        } = &param.kind
+        {
+            let bounded_type = RustdocType::Generic(param.name.clone());
+            for bound in param_bounds {
+                bounds.push((std::borrow::Cow::Owned(bounded_type.clone()), bound));
+            }
+        }
+    }
+    for predicate in &impl_.generics.where_predicates {
+        if let WherePredicate::BoundPredicate {
+            type_,
+            bounds: predicate_bounds,
+            ..
+        } = predicate
+        {
+            for bound in predicate_bounds {
+                bounds.push((std::borrow::Cow::Borrowed(type_), bound));
+            }
+        }
+    }
+    bounds
+}
+
+/// A cheap check to determine if `rustdoc_type`, taken from an `impl` block with the specified
+/// generic parameters, may refer to `our_type`.
 fn is_equivalent(
     rustdoc_type: &RustdocType,
     our_type: &Type,
+    impl_generics: &Generics,
     krate_collection: &CrateCollection,
     used_by_package_id: &PackageId,
 ) -> bool {
@@ -328,6 +755,7 @@ fn is_equivalent(
                 return is_equivalent(
                     &type_alias.type_,
                     our_type,
+                    impl_generics,
                     krate_collection,
                     used_by_package_id,
                 );
@@ -355,6 +783,7 @@ fn is_equivalent(
                     && is_equivalent(
                         inner_type,
                         &type_.inner,
+                        impl_generics,
                         krate_collection,
                         used_by_package_id,
                     );
@@ -371,6 +800,7 @@ fn is_equivalent(
                     if !is_equivalent(
                         rustdoc_tuple_element,
                         our_tuple_element,
+                        impl_generics,
                         krate_collection,
                         used_by_package_id,
                     ) {
@@ -390,6 +820,7 @@ fn is_equivalent(
                 return is_equivalent(
                     s,
                     &our_slice.element_type,
+                    impl_generics,
                     krate_collection,
                     used_by_package_id,
                 );
@@ -403,11 +834,19 @@ fn is_equivalent(
                     && is_equivalent(
                         type_,
                         &our_array.element_type,
+                        impl_generics,
                         krate_collection,
                         used_by_package_id,
                     );
             }
         }
+        // Blanket implementations (e.g. `impl<T> Foo for T`) may apply to any type.
+        // Their bounds are checked separately, in `check_impl`.
+        RustdocType::Generic(name) => {
+            return impl_generics.params.iter().any(|param| {
+                &param.name == name && matches!(param.kind, GenericParamDefKind::Type { .. })
+            });
+        }
         n => {
             tracing::trace!("We don't handle {:?} yet", n);
         }
@@ -419,12 +858,73 @@ fn is_equivalent(
 pub(crate) struct MissingTraitImplementationError {
     pub type_: Type,
     pub trait_: Type,
+    /// Set if there is an implementation of the trait that targets `type_`,
+    /// but one of its bounds is not satisfied.
+    pub unsatisfied_bound: Option<Box<UnsatisfiedBound>>,
+    /// Set if we couldn't determine whether `type_` implements `trait_`.
+    /// It explains why.
+    pub undetermined: Option<String>,
 }
 
-impl std::error::Error for MissingTraitImplementationError {}
+impl MissingTraitImplementationError {
+    /// A help message explaining which bound prevented a candidate implementation from
+    /// applying to `type_`, if any, or why we couldn't determine if `type_` implements `trait_`.
+    pub(crate) fn unsatisfied_bound_help(&self) -> Option<String> {
+        if let Some(reason) = &self.undetermined {
+            let type_ = self.type_.display_for_error();
+            let trait_ = self.trait_.display_for_error();
+            return Some(format!(
+                "Pavex couldn't determine if `{type_}` implements `{trait_}`: {reason}.\n\
+                Pavex doesn't assume that the trait is implemented when it can't prove it."
+            ));
+        }
+        let UnsatisfiedBound {
+            implementer,
+            bounded_type,
+            concrete_bounded_type,
+            bound,
+        } = self.unsatisfied_bound.as_deref()?;
+        let trait_ = self.trait_.display_for_error();
+        let implementer = implementer.display_for_error();
+        let bounded_type = bounded_type.display_for_error();
+        let concrete_bounded_type = concrete_bounded_type.display_for_error();
+        let bound = bound.display_for_error();
+        let mut help = format!(
+            "There is an implementation of `{trait_}` for `{implementer}`, but it requires `{bounded_type}: {bound}`."
+        );
+        if bounded_type != concrete_bounded_type {
+            help.push_str(&format!(
+                "\n`{bounded_type}` is `{concrete_bounded_type}` here, which doesn't implement `{bound}`."
+            ));
+        }
+        Some(help)
+    }
+}
 
+/// A bound on an `impl` block that isn't satisfied by the type we are checking.
+#[derive(Debug, Clone)]
+pub(crate) struct UnsatisfiedBound {
+    /// The implementer type, as written in the `impl` block—e.g. `Vec<T>`.
+    pub implementer: Type,
+    /// The constrained type, as written in the `impl` block—e.g. `T`.
+    pub bounded_type: Type,
+    /// The constrained type, after binding the generic parameters of the `impl`
+    /// block—e.g. `MyType` when checking `Vec<MyType>`.
+    pub concrete_bounded_type: Type,
+    /// The trait that `bounded_type` is required to implement.
+    pub bound: Type,
+}
+
+impl std::error::Error for MissingTraitImplementationError {}
 impl std::fmt::Display for MissingTraitImplementationError {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.undetermined.is_some() {
+            return write!(
+                f,
+                "Pavex couldn't determine if `{:?}` implements the `{:?}` trait.",
+                &self.type_, &self.trait_
+            );
+        }
         write!(
             f,
             "`{:?}` doesn't implement the `{:?}` trait.",
@@ -0,0 +1,24 @@
+use std::collections::BTreeMap;
+
+use miette::Severity;
+use pavex_bp_schema::{Lint, LintSetting};
+
+/// Determine the severity of the diagnostic that should be emitted when `lint` triggers,
+/// taking into account the overrides specified by the user.
+///
+/// Returns `None` if the lint is allowed.
+pub(crate) fn lint_severity(
+    overrides: Option<&BTreeMap<Lint, LintSetting>>,
+    lint: Lint,
+    default: LintSetting,
+) -> Option<Severity> {
+    let setting = overrides
+        .and_then(|lints| lints.get(&lint))
+        .copied()
+        .unwrap_or(default);
+    match setting {
+        LintSetting::Allow => None,
+        LintSetting::Warn => Some(Severity::Warning),
+        LintSetting::Deny => Some(Severity::Error),
+    }
+}
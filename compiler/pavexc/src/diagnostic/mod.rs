@@ -5,6 +5,7 @@ pub(crate) use self::miette::{
 };
 pub(crate) use callable_definition::CallableDefSource;
 pub(crate) use kind::ComponentKind;
+pub(crate) use lint::lint_severity;
 pub(crate) use ordinals::ZeroBasedOrdinal;
 pub(crate) use pavex_cli_diagnostic::{
     AnnotatedSource, CompilerDiagnostic, CompilerDiagnosticBuilder, HelpWithSnippet,
@@ -21,6 +22,7 @@ pub(crate) use source_file::{LocationExt, ParsedSourceFile, read_source_file};
 
 mod callable_definition;
 mod kind;
+mod lint;
 mod miette;
 mod ordinals;
 mod proc_macro_utils;
@@ -5,8 +5,8 @@ pub use krate_name::{
 pub(crate) use resolved_type::{
     Callable, CallableInput, CanonicalType, EnumVariantConstructorPath, EnumVariantInit, FnHeader,
     GenericArgument, GenericLifetimeParameter, InherentMethod, InherentMethodPath, Lifetime,
-    LifetimeGenerator, PathType, PathTypeExt, RustIdentifier, StructLiteralInit, TraitMethod,
-    TraitMethodPath, Type, TypeReference, get_err_variant, get_ok_variant,
+    LifetimeGenerator, PathType, PathTypeExt, RustIdentifier, ScalarPrimitive, StructLiteralInit,
+    TraitMethod, TraitMethodPath, Type, TypeReference, get_err_variant, get_ok_variant,
 };
 
 mod krate_name;
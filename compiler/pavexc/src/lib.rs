@@ -1,7 +1,10 @@
 #![allow(clippy::too_many_arguments)]
 extern crate core;
 
-pub use compiler::App;
+pub use compiler::{
+    App, ComponentEdge, ComponentEdgeKind, ComponentGraph, ComponentGraphFilter,
+    ComponentGraphFormat, ComponentNode, ComponentNodeKind,
+};
 pub use diagnostic::DiagnosticSink;
 pub use persistence::AppWriter;
 
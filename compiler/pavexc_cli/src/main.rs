@@ -20,7 +20,10 @@ use pavex_cli_deps::{IfAutoinstallable, RustdocJson, RustupToolchain, verify_ins
 use pavex_cli_diagnostic::AnyhowBridge;
 use pavex_cli_shell::try_init_shell;
 use pavexc::rustdoc::{CrateCollection, CrateCollectionExt};
-use pavexc::{App, AppWriter, DEFAULT_DOCS_TOOLCHAIN, DiagnosticSink};
+use pavexc::{
+    App, AppWriter, ComponentGraphFilter, ComponentGraphFormat, DEFAULT_DOCS_TOOLCHAIN,
+    DiagnosticSink,
+};
 use pavexc_cli_client::commands::new::TemplateName;
 use supports_color::Stream;
 use telemetry::Filtered;
@@ -127,6 +130,29 @@ enum Commands {
         /// the application to the specified path.
         #[clap(long, env = "PAVEXC_DIAGNOSTICS", value_parser)]
         diagnostics: Option<PathBuf>,
+        /// Optional. If provided, pavexc will export an application-wide graph of the
+        /// dependencies between components to the specified path.
+        /// The format is determined by the file extension: `.dot` (or `.gv`) for DOT,
+        /// `.mmd` (or `.mermaid`) for Mermaid, `.json` for JSON.
+        #[clap(long, env = "PAVEXC_COMPONENT_GRAPH", value_parser)]
+        component_graph: Option<PathBuf>,
+        /// Only include the components defined in this crate in the component graph.
+        /// It can be specified multiple times.
+        #[clap(
+            long = "component-graph-crate",
+            value_name = "CRATE",
+            requires = "component_graph"
+        )]
+        component_graph_crates: Vec<String>,
+        /// Only include the components used by routes whose path starts with this prefix
+        /// in the component graph.
+        /// It can be specified multiple times.
+        #[clap(
+            long = "component-graph-route-prefix",
+            value_name = "PREFIX",
+            requires = "component_graph"
+        )]
+        component_graph_route_prefixes: Vec<String>,
         /// The path to the directory that will contain the manifest and the source code for the generated application crate.
         /// If the provided path is relative, it is interpreted as relative to the root of the current workspace.
         #[clap(short, long, value_parser)]
@@ -136,6 +162,11 @@ enum Commands {
         /// If it isn't, `pavexc` will return an error without updating
         /// the server SDK code.
         check: bool,
+        #[clap(long, env = "PAVEXC_CONFIG_SCHEMA")]
+        /// Emit a JSON Schema for `ApplicationConfig` (`config.schema.json`) and an annotated
+        /// reference configuration file (`config.reference.yml`) in the root directory
+        /// of the generated server SDK crate.
+        config_schema: bool,
         #[clap(long, env = "PAVEXC_DOCS_TOOLCHAIN", default_value = DEFAULT_DOCS_TOOLCHAIN)]
         /// The name of the `rustup` toolchain that `pavexc` will use to generate the JSON documentation
         /// for the crates in the dependency graph of this project.
@@ -310,8 +341,12 @@ fn _main(cli: Cli) -> Result<ExitCode, miette::Error> {
         Commands::Generate {
             blueprint,
             diagnostics,
+            component_graph,
+            component_graph_crates,
+            component_graph_route_prefixes,
             output,
             check,
+            config_schema,
             docs_toolchain,
             precomputed_metadata,
             cache_workspace_packages,
@@ -319,11 +354,19 @@ fn _main(cli: Cli) -> Result<ExitCode, miette::Error> {
             blueprint,
             docs_toolchain,
             diagnostics,
+            component_graph.map(|path| {
+                let filter = ComponentGraphFilter {
+                    crates: component_graph_crates,
+                    route_prefixes: component_graph_route_prefixes,
+                };
+                (path, filter)
+            }),
             output,
             cli.color,
             cache_workspace_packages,
             precomputed_metadata,
             check,
+            config_schema,
         )
         .map_err(|e| e.into_miette().into()),
         Commands::New { path, template } => {
@@ -351,12 +394,18 @@ fn generate(
     blueprint: PathBuf,
     docs_toolchain: String,
     diagnostics: Option<PathBuf>,
+    component_graph: Option<(PathBuf, ComponentGraphFilter)>,
     output: PathBuf,
     color_profile: Color,
     cache_workspace_packages: bool,
     precomputed_metadata: Option<PathBuf>,
     check: bool,
+    config_schema: bool,
 ) -> Result<ExitCode, anyhow::Error> {
+    if let Some((path, _)) = &component_graph {
+        // Fail early, rather than after the (expensive) analysis of the blueprint.
+        ComponentGraphFormat::from_path(path)?;
+    }
     let blueprint: Blueprint = {
         let file = fs_err::OpenOptions::new().read(true).open(blueprint)?;
         ron::de::from_reader(&file)?
@@ -395,7 +444,15 @@ fn generate(
             .persist_flat(&diagnostic_path)
             .context("Failed to persist diagnostic information to disk")?;
     }
-    let generated_app = app.codegen()?;
+    if let Some((path, filter)) = component_graph {
+        app.component_graph(&filter)
+            .persist(&path)
+            .context("Failed to persist the component graph to disk")?;
+    }
+    let mut generated_app = app.codegen()?;
+    if config_schema {
+        generated_app = generated_app.emit_config_schema();
+    }
     let mut writer = if check {
         AppWriter::check_mode()
     } else {
@@ -11,9 +11,13 @@ use pavex::Blueprint;
 pub struct GenerateBuilder {
     cmd: Command,
     diagnostics_path: Option<PathBuf>,
+    component_graph_path: Option<PathBuf>,
+    component_graph_crates: Vec<String>,
+    component_graph_route_prefixes: Vec<String>,
     blueprint: BlueprintArgument,
     output_directory: PathBuf,
     check: bool,
+    config_schema: bool,
 }
 
 pub enum BlueprintArgument {
@@ -32,10 +36,14 @@ impl GenerateBuilder {
     ) -> Self {
         Self {
             diagnostics_path: None,
+            component_graph_path: None,
+            component_graph_crates: Vec::new(),
+            component_graph_route_prefixes: Vec::new(),
             blueprint,
             cmd,
             output_directory,
             check: false,
+            config_schema: false,
         }
     }
 
@@ -101,9 +109,21 @@ impl GenerateBuilder {
         if let Some(path) = self.diagnostics_path {
             self.cmd.arg("--diagnostics").arg(path);
         }
+        if let Some(path) = self.component_graph_path {
+            self.cmd.arg("--component-graph").arg(path);
+            for krate in self.component_graph_crates {
+                self.cmd.arg("--component-graph-crate").arg(krate);
+            }
+            for prefix in self.component_graph_route_prefixes {
+                self.cmd.arg("--component-graph-route-prefix").arg(prefix);
+            }
+        }
         if self.check {
             self.cmd.arg("--check");
         }
+        if self.config_schema {
+            self.cmd.arg("--config-schema");
+        }
         Ok(self.cmd)
     }
 
@@ -118,6 +138,37 @@ impl GenerateBuilder {
         self
     }
 
+    /// Set the path to the file that Pavex will use to export an application-wide
+    /// graph of the dependencies between components.
+    ///
+    /// The format is determined by the file extension: `.dot` (or `.gv`) for DOT,
+    /// `.mmd` (or `.mermaid`) for Mermaid, `.json` for JSON.
+    ///
+    /// If this is not set, Pavex will not export the component graph.
+    pub fn component_graph_path(mut self, path: PathBuf) -> Self {
+        self.component_graph_path = Some(path);
+        self
+    }
+
+    /// Only include the components defined in the specified crate in the component graph.
+    ///
+    /// It can be invoked multiple times to include components from several crates.
+    /// It has no effect unless [`GenerateBuilder::component_graph_path`] is set.
+    pub fn component_graph_crate(mut self, name: String) -> Self {
+        self.component_graph_crates.push(name);
+        self
+    }
+
+    /// Only include the components used by routes whose path starts with the specified prefix
+    /// in the component graph.
+    ///
+    /// It can be invoked multiple times to include several prefixes.
+    /// It has no effect unless [`GenerateBuilder::component_graph_path`] is set.
+    pub fn component_graph_route_prefix(mut self, prefix: String) -> Self {
+        self.component_graph_route_prefixes.push(prefix);
+        self
+    }
+
     /// Enable check mode.
     ///
     /// In check mode, `pavexc generate` verifies that the generated server SDK is up-to-date.
@@ -134,6 +185,22 @@ impl GenerateBuilder {
         self.check = false;
         self
     }
+
+    /// Emit a JSON Schema for `ApplicationConfig` (`config.schema.json`) and an annotated
+    /// reference configuration file (`config.reference.yml`) in the root directory
+    /// of the generated server SDK crate.
+    pub fn config_schema(mut self) -> Self {
+        self.config_schema = true;
+        self
+    }
+
+    /// Don't emit the JSON Schema and the reference configuration file for `ApplicationConfig`.
+    ///
+    /// This is the default behavior.
+    pub fn no_config_schema(mut self) -> Self {
+        self.config_schema = false;
+        self
+    }
 }
 
 #[derive(Debug, thiserror::Error)]
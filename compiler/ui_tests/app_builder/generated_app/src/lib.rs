@@ -8,6 +8,9 @@ struct ServerState {
 }
 #[derive(Debug, Clone, serde::Deserialize)]
 pub struct ApplicationConfig {}
+impl pavex::config::ConfigSecrets for ApplicationConfig {
+    const SECRET_KEYS: &'static [&'static str] = &[];
+}
 pub struct ApplicationState {
     pub http_client: app_f10df8fa::HttpClient,
 }
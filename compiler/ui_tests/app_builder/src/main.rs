@@ -11,6 +11,9 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         .pavex_cli_path(std::env::var("PAVEX_TEST_CLI_PATH").unwrap().into())
         .generate(blueprint(), ui_test_dir.join("generated_app"))
         .diagnostics_path("diagnostics.dot".into())
+        .component_graph_path("component_graph.dot".into())
+        .component_graph_crate("app_f10df8fa".into())
+        .component_graph_route_prefix("/home".into())
         .execute();
     match outcome {
         Ok(_) => {},
@@ -0,0 +1,35 @@
+use pavex::Response;
+use pavex::{blueprint::from, Blueprint};
+
+#[derive(Clone)]
+pub struct Wrapper<T>(T);
+
+#[derive(Clone)]
+pub struct A;
+
+pub struct B;
+
+// `A` is `Clone`, therefore `Wrapper<A>` is `Clone` too.
+#[pavex::request_scoped(clone_if_necessary)]
+pub fn wrapped_a() -> Wrapper<A> {
+    todo!()
+}
+
+// `B` isn't `Clone`, therefore the bound on the derived `Clone`
+// implementation for `Wrapper<T>` isn't satisfied.
+#[pavex::request_scoped(clone_if_necessary)]
+pub fn wrapped_b() -> Wrapper<B> {
+    todo!()
+}
+
+#[pavex::get(path = "/")]
+pub fn handler() -> Response {
+    todo!()
+}
+
+pub fn blueprint() -> Blueprint {
+    let mut bp = Blueprint::new();
+    bp.import(from![crate]);
+    bp.routes(from![crate]);
+    bp
+}
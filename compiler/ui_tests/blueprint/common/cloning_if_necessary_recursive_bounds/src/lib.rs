@@ -0,0 +1,34 @@
+use pavex::Response;
+use pavex::{blueprint::from, Blueprint};
+
+pub struct Node<T>(T);
+
+// Checking `Node<T>: Clone` requires checking `Node<Vec<T>>: Clone`,
+// which requires checking `Node<Vec<Vec<T>>>: Clone`, and so on.
+impl<T> Clone for Node<T>
+where
+    Node<Vec<T>>: Clone,
+{
+    fn clone(&self) -> Self {
+        todo!()
+    }
+}
+
+pub struct A;
+
+#[pavex::request_scoped(clone_if_necessary)]
+pub fn node() -> Node<A> {
+    todo!()
+}
+
+#[pavex::get(path = "/")]
+pub fn handler() -> Response {
+    todo!()
+}
+
+pub fn blueprint() -> Blueprint {
+    let mut bp = Blueprint::new();
+    bp.import(from![crate]);
+    bp.routes(from![crate]);
+    bp
+}
@@ -0,0 +1,43 @@
+use pavex::http::StatusCode;
+use pavex::{blueprint::from, Blueprint};
+use std::sync::Arc;
+
+// `Arc<T>`, `Box<T>` and `Vec<T>` are generic over an allocator,
+// which is omitted here since it has a default value.
+// Pavex must see through it when checking their `Clone`, `Send` and `Sync` implementations.
+
+/// Not `Clone`, but `Arc<A>` is.
+pub struct A;
+
+#[derive(Clone)]
+pub struct B;
+
+#[derive(Clone)]
+pub struct C;
+
+#[pavex::singleton(clone_if_necessary)]
+pub fn arc() -> Arc<A> {
+    Arc::new(A)
+}
+
+#[pavex::singleton(clone_if_necessary)]
+pub fn boxed() -> Box<B> {
+    Box::new(B)
+}
+
+#[pavex::singleton(clone_if_necessary)]
+pub fn vec() -> Vec<C> {
+    vec![C]
+}
+
+#[pavex::get(path = "/")]
+pub fn route_handler(_a: &Arc<A>, _b: &Box<B>, _c: &Vec<C>) -> StatusCode {
+    todo!()
+}
+
+pub fn blueprint() -> Blueprint {
+    let mut bp = Blueprint::new();
+    bp.import(from![crate]);
+    bp.routes(from![crate]);
+    bp
+}
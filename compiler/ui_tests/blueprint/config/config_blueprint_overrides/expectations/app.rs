@@ -12,6 +12,9 @@ pub struct ApplicationConfig {
     #[serde(default)]
     pub a: app::A,
 }
+impl pavex::config::ConfigSecrets for ApplicationConfig {
+    const SECRET_KEYS: &'static [&'static str] = &[];
+}
 pub struct ApplicationState {}
 impl ApplicationState {
     pub async fn new(
@@ -12,6 +12,9 @@ pub struct ApplicationConfig {
     #[serde(default)]
     pub a: app_dd1270fd::A,
 }
+impl pavex::config::ConfigSecrets for ApplicationConfig {
+    const SECRET_KEYS: &'static [&'static str] = &[];
+}
 pub struct ApplicationState {}
 impl ApplicationState {
     pub async fn new(
@@ -10,6 +10,9 @@ struct ServerState {
 pub struct ApplicationConfig {
     pub a: app_4587d126::A,
 }
+impl pavex::config::ConfigSecrets for ApplicationConfig {
+    const SECRET_KEYS: &'static [&'static str] = &[];
+}
 pub struct ApplicationState {
     pub b: app_4587d126::B,
 }
@@ -16,6 +16,9 @@ pub struct ApplicationConfig {
     pub f: dep_29415e2f::F,
     pub g: dep_29415e2f::Z,
 }
+impl pavex::config::ConfigSecrets for ApplicationConfig {
+    const SECRET_KEYS: &'static [&'static str] = &[];
+}
 pub struct ApplicationState {
     pub a: app_b5d21f5b::A,
     pub b: app_b5d21f5b::B,
@@ -0,0 +1,23 @@
+use pavex::config::Reloadable;
+use pavex::Response;
+use pavex::{blueprint::from, Blueprint};
+
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct RateLimit {
+    pub requests_per_minute: u32,
+}
+
+#[pavex::config(key = "rate_limit", id = "RATE_LIMIT")]
+pub type ReloadableRateLimit = Reloadable<RateLimit>;
+
+#[pavex::get(path = "/")]
+pub fn handler(_rate_limit: &Reloadable<RateLimit>) -> Response {
+    todo!()
+}
+
+pub fn blueprint() -> Blueprint {
+    let mut bp = Blueprint::new();
+    bp.import(from![crate]);
+    bp.routes(from![crate]);
+    bp
+}
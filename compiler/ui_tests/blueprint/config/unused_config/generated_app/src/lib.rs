@@ -11,6 +11,9 @@ struct ServerState {
 pub struct ApplicationConfig {
     pub b: app_d96899ec::B,
 }
+impl pavex::config::ConfigSecrets for ApplicationConfig {
+    const SECRET_KEYS: &'static [&'static str] = &[];
+}
 pub struct ApplicationState {}
 impl ApplicationState {
     pub async fn new(
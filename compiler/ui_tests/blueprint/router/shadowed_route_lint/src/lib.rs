@@ -0,0 +1,36 @@
+use pavex::blueprint::Lint;
+use pavex::Blueprint;
+
+pub fn blueprint() -> Blueprint {
+    let mut bp = Blueprint::new();
+    bp.domain("api.company.com").nest({
+        let mut bp = Blueprint::new();
+        bp.route(API_ROOT);
+        bp
+    });
+    bp.domain("{sub}.company.com").nest({
+        let mut bp = Blueprint::new();
+        // Not shadowed: `api.company.com` has a route for `/`.
+        bp.route(SUB_ROOT).deny(Lint::ShadowedRoute);
+        // Shadowed: `api.company.com/login` is routed to `api.company.com`,
+        // which doesn't have a route for `/login`.
+        bp.route(SUB_LOGIN).deny(Lint::ShadowedRoute);
+        bp
+    });
+    bp
+}
+
+#[pavex::get(path = "/")]
+pub fn api_root() -> pavex::Response {
+    todo!()
+}
+
+#[pavex::get(path = "/")]
+pub fn sub_root() -> pavex::Response {
+    todo!()
+}
+
+#[pavex::get(path = "/login")]
+pub fn sub_login() -> pavex::Response {
+    todo!()
+}
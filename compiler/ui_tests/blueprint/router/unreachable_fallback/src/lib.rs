@@ -0,0 +1,24 @@
+use pavex::blueprint::Lint;
+use pavex::Blueprint;
+
+#[pavex::get(path = "/users/{*rest}")]
+pub fn users() -> pavex::Response {
+    todo!()
+}
+
+#[pavex::fallback]
+pub fn user_not_found() -> pavex::Response {
+    todo!()
+}
+
+pub fn blueprint() -> Blueprint {
+    let mut bp = Blueprint::new();
+    bp.route(USERS);
+    bp.prefix("/users/{id}").nest({
+        let mut bp = Blueprint::new();
+        // All `/users/{id}`-prefixed requests are matched by `USERS`.
+        bp.fallback(USER_NOT_FOUND).deny(Lint::UnreachableFallback);
+        bp
+    });
+    bp
+}
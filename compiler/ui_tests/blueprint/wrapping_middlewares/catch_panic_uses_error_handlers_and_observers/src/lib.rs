@@ -0,0 +1,28 @@
+use pavex::middleware::{Panic, CATCH_PANIC};
+use pavex::Response;
+use pavex::{blueprint::from, Blueprint};
+
+#[pavex::get(path = "/")]
+pub fn handler() -> Response {
+    todo!()
+}
+
+#[pavex::error_handler(default = false)]
+pub fn panic_response(_panic: &Panic) -> Response {
+    todo!()
+}
+
+#[pavex::error_observer]
+pub fn error_observer(_err: &pavex::Error) {
+    todo!()
+}
+
+pub fn blueprint() -> Blueprint {
+    let mut bp = Blueprint::new();
+    bp.import(from![crate]);
+    bp.error_observer(ERROR_OBSERVER);
+    // The custom error handler takes precedence over `Panic`'s default one.
+    bp.wrap(CATCH_PANIC).error_handler(PANIC_RESPONSE);
+    bp.routes(from![crate]);
+    bp
+}
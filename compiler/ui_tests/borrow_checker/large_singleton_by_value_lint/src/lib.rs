@@ -0,0 +1,42 @@
+use pavex::blueprint::{from, Lint};
+use pavex::{Blueprint, Response};
+
+#[derive(Clone)]
+pub struct Big {
+    pub buffer: [u8; 1024],
+}
+
+// Small singletons taken by value are fine.
+#[derive(Clone)]
+pub struct Small {
+    pub id: u64,
+}
+
+pub struct A;
+
+#[pavex::singleton(clone_if_necessary)]
+pub fn big() -> Big {
+    todo!()
+}
+
+#[pavex::singleton(clone_if_necessary)]
+pub fn small() -> Small {
+    todo!()
+}
+
+#[pavex::request_scoped]
+pub fn a(_big: Big, _small: Small) -> A {
+    todo!()
+}
+
+#[pavex::get(path = "/")]
+pub fn handler(_a: A) -> Response {
+    todo!()
+}
+
+pub fn blueprint() -> Blueprint {
+    let mut bp = Blueprint::new();
+    bp.import(from![crate]).deny(Lint::LargeSingletonByValue);
+    bp.routes(from![crate]);
+    bp
+}
@@ -0,0 +1,52 @@
+use pavex::blueprint::{from, Lint};
+use pavex::{Blueprint, Response};
+
+// `A` and `B` are both moved into `c` and into the request handler.
+// Pavex must clone them to satisfy the borrow checker, but only `A`
+// is reported since the lint is only enabled for the `linted` module.
+
+pub mod linted {
+    #[derive(Clone)]
+    pub struct A;
+
+    #[pavex::request_scoped(clone_if_necessary)]
+    pub fn a() -> A {
+        todo!()
+    }
+}
+
+pub mod unlinted {
+    #[derive(Clone)]
+    pub struct B;
+
+    #[pavex::request_scoped(clone_if_necessary)]
+    pub fn b() -> B {
+        todo!()
+    }
+}
+
+pub mod other {
+    use super::linted::A;
+    use super::unlinted::B;
+
+    pub struct C;
+
+    #[pavex::request_scoped]
+    pub fn c(_a: A, _b: B) -> C {
+        todo!()
+    }
+}
+
+#[pavex::get(path = "/")]
+pub fn handler(_a: linted::A, _b: unlinted::B, _c: other::C) -> Response {
+    todo!()
+}
+
+pub fn blueprint() -> Blueprint {
+    let mut bp = Blueprint::new();
+    bp.import(from![crate::linted]).deny(Lint::RedundantClone);
+    bp.import(from![crate::unlinted]);
+    bp.import(from![crate::other]);
+    bp.routes(from![crate]);
+    bp
+}
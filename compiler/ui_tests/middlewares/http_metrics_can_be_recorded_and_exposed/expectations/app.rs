@@ -0,0 +1,323 @@
+//! Do NOT edit this code.
+//! It was automatically generated by Pavex.
+//! All manual edits will be lost next time the code is generated.
+extern crate alloc;
+struct ServerState {
+    router: Router,
+    application_state: ApplicationState,
+}
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct ApplicationConfig {}
+impl pavex::config::ConfigSecrets for ApplicationConfig {
+    const SECRET_KEYS: &'static [&'static str] = &[];
+}
+pub struct ApplicationState {
+    pub http_metrics: pavex::telemetry::metrics::HttpMetrics,
+}
+impl ApplicationState {
+    pub async fn new(
+        _app_config: crate::ApplicationConfig,
+    ) -> Result<crate::ApplicationState, crate::ApplicationStateError> {
+        Ok(Self::_new().await)
+    }
+    async fn _new() -> crate::ApplicationState {
+        let v0 = pavex::telemetry::metrics::HttpMetrics::new();
+        crate::ApplicationState {
+            http_metrics: v0,
+        }
+    }
+}
+#[derive(Debug, thiserror::Error)]
+pub enum ApplicationStateError {}
+pub fn run(
+    server_builder: pavex::server::Server,
+    application_state: ApplicationState,
+) -> pavex::server::ServerHandle {
+    async fn handler(
+        request: http::Request<hyper::body::Incoming>,
+        connection_info: Option<pavex::connection::ConnectionInfo>,
+        server_state: std::sync::Arc<ServerState>,
+    ) -> pavex::Response {
+        let (router, state) = (&server_state.router, &server_state.application_state);
+        router.route(request, connection_info, state).await
+    }
+    let router = Router::new();
+    let server_state = std::sync::Arc::new(ServerState {
+        router,
+        application_state,
+    });
+    server_builder.serve(handler, server_state)
+}
+struct Router {
+    router: matchit::Router<u32>,
+}
+impl Router {
+    /// Create a new router instance.
+    ///
+    /// This method is invoked once, when the server starts.
+    pub fn new() -> Self {
+        Self { router: Self::router() }
+    }
+    fn router() -> matchit::Router<u32> {
+        let mut router = matchit::Router::new();
+        router.insert("/metrics", 0u32).unwrap();
+        router
+    }
+    pub async fn route(
+        &self,
+        request: http::Request<hyper::body::Incoming>,
+        _connection_info: Option<pavex::connection::ConnectionInfo>,
+        #[allow(unused)]
+        state: &ApplicationState,
+    ) -> pavex::Response {
+        let (request_head, _) = request.into_parts();
+        let request_head: pavex::request::RequestHead = request_head.into();
+        let Ok(matched_route) = self.router.at(&request_head.target.path()) else {
+            let allowed_methods: pavex::router::AllowedMethods = pavex::router::MethodAllowList::from_iter(
+                    vec![],
+                )
+                .into();
+            let matched_route_template = pavex::request::path::MatchedPathPattern::new(
+                "*",
+            );
+            return route_0::entrypoint(
+                    matched_route_template,
+                    &allowed_methods,
+                    &state.http_metrics,
+                    &request_head,
+                )
+                .await;
+        };
+        match matched_route.value {
+            0u32 => {
+                match &request_head.method {
+                    &pavex::http::Method::GET => {
+                        let matched_route_template = pavex::request::path::MatchedPathPattern::new(
+                            "/metrics",
+                        );
+                        route_1::entrypoint(
+                                matched_route_template,
+                                &state.http_metrics,
+                                &request_head,
+                            )
+                            .await
+                    }
+                    _ => {
+                        let allowed_methods: pavex::router::AllowedMethods = pavex::router::MethodAllowList::from_iter([
+                                pavex::http::Method::GET,
+                            ])
+                            .into();
+                        let matched_route_template = pavex::request::path::MatchedPathPattern::new(
+                            "/metrics",
+                        );
+                        route_0::entrypoint(
+                                matched_route_template,
+                                &allowed_methods,
+                                &state.http_metrics,
+                                &request_head,
+                            )
+                            .await
+                    }
+                }
+            }
+            i => unreachable!("Unknown route id: {}", i),
+        }
+    }
+}
+pub mod route_0 {
+    pub async fn entrypoint<'a, 'b, 'c>(
+        s_0: pavex::request::path::MatchedPathPattern,
+        s_1: &'a pavex::router::AllowedMethods,
+        s_2: &'b pavex::telemetry::metrics::HttpMetrics,
+        s_3: &'c pavex::request::RequestHead,
+    ) -> pavex::Response {
+        let response = wrapping_0(s_0, s_1, s_2, s_3).await;
+        response
+    }
+    async fn stage_1<'a, 'b, 'c>(
+        s_0: &'a pavex::router::AllowedMethods,
+        s_1: pavex::request::path::MatchedPathPattern,
+        s_2: &'b pavex::telemetry::metrics::HttpMetrics,
+        s_3: &'c pavex::request::RequestHead,
+    ) -> pavex::Response {
+        let response = wrapping_1(s_1, s_0, s_2, s_3).await;
+        response
+    }
+    async fn stage_2<'a>(s_0: &'a pavex::router::AllowedMethods) -> pavex::Response {
+        let response = handler(s_0).await;
+        response
+    }
+    async fn wrapping_0(
+        v0: pavex::request::path::MatchedPathPattern,
+        v1: &pavex::router::AllowedMethods,
+        v2: &pavex::telemetry::metrics::HttpMetrics,
+        v3: &pavex::request::RequestHead,
+    ) -> pavex::Response {
+        let v4 = crate::route_0::Next0 {
+            s_0: v1,
+            s_1: v0,
+            s_2: v2,
+            s_3: v3,
+            next: stage_1,
+        };
+        let v5 = pavex::middleware::Next::new(v4);
+        let v6 = pavex::middleware::wrap_noop(v5).await;
+        <pavex::Response as pavex::IntoResponse>::into_response(v6)
+    }
+    async fn wrapping_1(
+        v0: pavex::request::path::MatchedPathPattern,
+        v1: &pavex::router::AllowedMethods,
+        v2: &pavex::telemetry::metrics::HttpMetrics,
+        v3: &pavex::request::RequestHead,
+    ) -> pavex::Response {
+        let v4 = crate::route_0::Next1 {
+            s_0: v1,
+            next: stage_2,
+        };
+        let v5 = pavex::middleware::Next::new(v4);
+        let v6 = pavex::telemetry::metrics::record_http_metrics(v5, v2, v3, v0).await;
+        <pavex::Response as pavex::IntoResponse>::into_response(v6)
+    }
+    async fn handler(v0: &pavex::router::AllowedMethods) -> pavex::Response {
+        let v1 = pavex::router::default_fallback(v0).await;
+        <pavex::Response as pavex::IntoResponse>::into_response(v1)
+    }
+    struct Next0<'a, 'b, 'c, T>
+    where
+        T: std::future::Future<Output = pavex::Response>,
+    {
+        s_0: &'a pavex::router::AllowedMethods,
+        s_1: pavex::request::path::MatchedPathPattern,
+        s_2: &'b pavex::telemetry::metrics::HttpMetrics,
+        s_3: &'c pavex::request::RequestHead,
+        next: fn(
+            &'a pavex::router::AllowedMethods,
+            pavex::request::path::MatchedPathPattern,
+            &'b pavex::telemetry::metrics::HttpMetrics,
+            &'c pavex::request::RequestHead,
+        ) -> T,
+    }
+    impl<'a, 'b, 'c, T> std::future::IntoFuture for Next0<'a, 'b, 'c, T>
+    where
+        T: std::future::Future<Output = pavex::Response>,
+    {
+        type Output = pavex::Response;
+        type IntoFuture = T;
+        fn into_future(self) -> Self::IntoFuture {
+            (self.next)(self.s_0, self.s_1, self.s_2, self.s_3)
+        }
+    }
+    struct Next1<'a, T>
+    where
+        T: std::future::Future<Output = pavex::Response>,
+    {
+        s_0: &'a pavex::router::AllowedMethods,
+        next: fn(&'a pavex::router::AllowedMethods) -> T,
+    }
+    impl<'a, T> std::future::IntoFuture for Next1<'a, T>
+    where
+        T: std::future::Future<Output = pavex::Response>,
+    {
+        type Output = pavex::Response;
+        type IntoFuture = T;
+        fn into_future(self) -> Self::IntoFuture {
+            (self.next)(self.s_0)
+        }
+    }
+}
+pub mod route_1 {
+    pub async fn entrypoint<'a, 'b>(
+        s_0: pavex::request::path::MatchedPathPattern,
+        s_1: &'a pavex::telemetry::metrics::HttpMetrics,
+        s_2: &'b pavex::request::RequestHead,
+    ) -> pavex::Response {
+        let response = wrapping_0(s_0, s_1, s_2).await;
+        response
+    }
+    async fn stage_1<'a, 'b>(
+        s_0: &'a pavex::telemetry::metrics::HttpMetrics,
+        s_1: pavex::request::path::MatchedPathPattern,
+        s_2: &'b pavex::request::RequestHead,
+    ) -> pavex::Response {
+        let response = wrapping_1(s_1, s_0, s_2).await;
+        response
+    }
+    async fn stage_2<'a>(
+        s_0: &'a pavex::telemetry::metrics::HttpMetrics,
+    ) -> pavex::Response {
+        let response = handler(s_0).await;
+        response
+    }
+    async fn wrapping_0(
+        v0: pavex::request::path::MatchedPathPattern,
+        v1: &pavex::telemetry::metrics::HttpMetrics,
+        v2: &pavex::request::RequestHead,
+    ) -> pavex::Response {
+        let v3 = crate::route_1::Next0 {
+            s_0: v1,
+            s_1: v0,
+            s_2: v2,
+            next: stage_1,
+        };
+        let v4 = pavex::middleware::Next::new(v3);
+        let v5 = pavex::middleware::wrap_noop(v4).await;
+        <pavex::Response as pavex::IntoResponse>::into_response(v5)
+    }
+    async fn wrapping_1(
+        v0: pavex::request::path::MatchedPathPattern,
+        v1: &pavex::telemetry::metrics::HttpMetrics,
+        v2: &pavex::request::RequestHead,
+    ) -> pavex::Response {
+        let v3 = crate::route_1::Next1 {
+            s_0: v1,
+            next: stage_2,
+        };
+        let v4 = pavex::middleware::Next::new(v3);
+        let v5 = pavex::telemetry::metrics::record_http_metrics(v4, v1, v2, v0).await;
+        <pavex::Response as pavex::IntoResponse>::into_response(v5)
+    }
+    async fn handler(v0: &pavex::telemetry::metrics::HttpMetrics) -> pavex::Response {
+        let v1 = pavex::telemetry::metrics::prometheus_metrics(v0);
+        <pavex::Response as pavex::IntoResponse>::into_response(v1)
+    }
+    struct Next0<'a, 'b, T>
+    where
+        T: std::future::Future<Output = pavex::Response>,
+    {
+        s_0: &'a pavex::telemetry::metrics::HttpMetrics,
+        s_1: pavex::request::path::MatchedPathPattern,
+        s_2: &'b pavex::request::RequestHead,
+        next: fn(
+            &'a pavex::telemetry::metrics::HttpMetrics,
+            pavex::request::path::MatchedPathPattern,
+            &'b pavex::request::RequestHead,
+        ) -> T,
+    }
+    impl<'a, 'b, T> std::future::IntoFuture for Next0<'a, 'b, T>
+    where
+        T: std::future::Future<Output = pavex::Response>,
+    {
+        type Output = pavex::Response;
+        type IntoFuture = T;
+        fn into_future(self) -> Self::IntoFuture {
+            (self.next)(self.s_0, self.s_1, self.s_2)
+        }
+    }
+    struct Next1<'a, T>
+    where
+        T: std::future::Future<Output = pavex::Response>,
+    {
+        s_0: &'a pavex::telemetry::metrics::HttpMetrics,
+        next: fn(&'a pavex::telemetry::metrics::HttpMetrics) -> T,
+    }
+    impl<'a, T> std::future::IntoFuture for Next1<'a, T>
+    where
+        T: std::future::Future<Output = pavex::Response>,
+    {
+        type Output = pavex::Response;
+        type IntoFuture = T;
+        fn into_future(self) -> Self::IntoFuture {
+            (self.next)(self.s_0)
+        }
+    }
+}
\ No newline at end of file
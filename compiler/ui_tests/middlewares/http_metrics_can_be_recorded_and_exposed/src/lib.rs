@@ -0,0 +1,10 @@
+use pavex::telemetry::metrics::{HTTP_METRICS_NEW, PROMETHEUS_METRICS, RECORD_HTTP_METRICS};
+use pavex::Blueprint;
+
+pub fn blueprint() -> Blueprint {
+    let mut bp = Blueprint::new();
+    bp.constructor(HTTP_METRICS_NEW);
+    bp.wrap(RECORD_HTTP_METRICS);
+    bp.route(PROMETHEUS_METRICS);
+    bp
+}
@@ -0,0 +1,28 @@
+use pavex::http::StatusCode;
+use pavex::request::path::PathParams;
+use pavex::{blueprint::from, Blueprint};
+
+#[pavex::get(path = "/b/{x}/{y}")]
+pub fn tuple(_params: PathParams<(u32, u32, u32)>) -> StatusCode {
+    todo!()
+}
+
+#[pavex::get(path = "/a/{x}/{y}")]
+pub fn primitive(_params: PathParams<u32>) -> StatusCode {
+    todo!()
+}
+
+#[PathParams]
+pub struct TupleStruct(u32, u32);
+
+#[pavex::get(path = "/f/{x}")]
+pub fn tuple_struct(_params: PathParams<TupleStruct>) -> StatusCode {
+    todo!()
+}
+
+pub fn blueprint() -> Blueprint {
+    let mut bp = Blueprint::new();
+    bp.import(from![pavex]);
+    bp.routes(from![crate]);
+    bp
+}
@@ -8,6 +8,9 @@ struct ServerState {
 }
 #[derive(Debug, Clone, serde::Deserialize)]
 pub struct ApplicationConfig {}
+impl pavex::config::ConfigSecrets for ApplicationConfig {
+    const SECRET_KEYS: &'static [&'static str] = &[];
+}
 pub struct ApplicationState {
     pub arc_custom: alloc::sync::Arc<app_933292bd::Custom>,
     pub arc_mutex: alloc::sync::Arc<std::sync::Mutex<app_933292bd::Custom>>,
@@ -0,0 +1,20 @@
+use pavex::jwt::Claims;
+use pavex::Response;
+use pavex::{blueprint::from, Blueprint};
+
+#[derive(serde::Deserialize)]
+pub struct ServiceClaims {
+    pub sub: String,
+}
+
+#[pavex::get(path = "/")]
+pub fn handler(claims: &Claims<ServiceClaims>) -> Response {
+    Response::ok().set_typed_body(claims.0.sub.clone())
+}
+
+pub fn blueprint() -> Blueprint {
+    let mut bp = Blueprint::new();
+    bp.import(from![pavex]);
+    bp.routes(from![crate]);
+    bp
+}
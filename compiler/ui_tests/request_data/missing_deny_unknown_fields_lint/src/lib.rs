@@ -0,0 +1,41 @@
+use pavex::blueprint::Lint;
+use pavex::http::StatusCode;
+use pavex::request::body::{JsonBody, UrlEncodedBody, JSON_BODY_EXTRACT, URL_ENCODED_BODY_EXTRACT};
+use pavex::{blueprint::from, Blueprint};
+
+#[derive(serde::Deserialize)]
+pub struct Signup {
+    pub username: String,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StrictSignup {
+    pub username: String,
+}
+
+#[pavex::post(path = "/json")]
+pub fn json(_body: JsonBody<Signup>) -> StatusCode {
+    todo!()
+}
+
+#[pavex::post(path = "/strict_json")]
+pub fn strict_json(_body: JsonBody<StrictSignup>) -> StatusCode {
+    todo!()
+}
+
+#[pavex::post(path = "/form")]
+pub fn form(_body: UrlEncodedBody<Signup>) -> StatusCode {
+    todo!()
+}
+
+pub fn blueprint() -> Blueprint {
+    let mut bp = Blueprint::new();
+    bp.import(from![pavex]);
+    bp.constructor(JSON_BODY_EXTRACT)
+        .deny(Lint::MissingDenyUnknownFields);
+    bp.constructor(URL_ENCODED_BODY_EXTRACT)
+        .allow(Lint::MissingDenyUnknownFields);
+    bp.routes(from![crate]);
+    bp
+}
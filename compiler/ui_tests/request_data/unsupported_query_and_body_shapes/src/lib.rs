@@ -0,0 +1,83 @@
+use std::collections::BTreeMap;
+
+use pavex::http::StatusCode;
+use pavex::request::body::{JsonBody, UrlEncodedBody};
+use pavex::request::query::QueryParams;
+use pavex::{blueprint::from, Blueprint};
+
+#[derive(serde::Deserialize)]
+pub struct Range {
+    pub start: u32,
+    pub end: u32,
+}
+
+#[derive(serde::Deserialize)]
+pub struct Filters {
+    pub range: Range,
+    pub tags: Vec<String>,
+    pub ids: Vec<(u32, u32)>,
+}
+
+#[pavex::get(path = "/nested")]
+pub fn nested(_params: QueryParams<Filters>) -> StatusCode {
+    todo!()
+}
+
+#[pavex::get(path = "/tuple")]
+pub fn tuple(_params: QueryParams<(u32, u32)>) -> StatusCode {
+    todo!()
+}
+
+#[derive(serde::Deserialize)]
+pub struct Flattened {
+    #[serde(flatten)]
+    pub range: Range,
+    pub page: Option<u32>,
+}
+
+#[pavex::get(path = "/flattened")]
+pub fn flattened(_params: QueryParams<Flattened>) -> StatusCode {
+    todo!()
+}
+
+#[derive(serde::Deserialize)]
+pub enum Shape {
+    Circle { radius: u32 },
+    Square(u32),
+}
+
+#[derive(serde::Deserialize)]
+pub struct Form {
+    pub name: String,
+    pub shape: Shape,
+}
+
+#[pavex::post(path = "/form")]
+pub fn form(_body: UrlEncodedBody<Form>) -> StatusCode {
+    todo!()
+}
+
+#[derive(serde::Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Point {
+    pub x: i32,
+    pub y: i32,
+}
+
+#[derive(serde::Deserialize)]
+pub struct Payload {
+    pub by_point: BTreeMap<Point, u64>,
+    pub ranges: Vec<Range>,
+    pub shape: Shape,
+}
+
+#[pavex::post(path = "/json")]
+pub fn json(_body: JsonBody<Payload>) -> StatusCode {
+    todo!()
+}
+
+pub fn blueprint() -> Blueprint {
+    let mut bp = Blueprint::new();
+    bp.import(from![pavex]);
+    bp.routes(from![crate]);
+    bp
+}
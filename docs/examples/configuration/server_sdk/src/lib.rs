@@ -11,6 +11,9 @@ pub struct ApplicationConfig {
     pub postgres: configuration::postgres::PostgresConfig,
     pub server: configuration::server::ServerConfig,
 }
+impl pavex::config::ConfigSecrets for ApplicationConfig {
+    const SECRET_KEYS: &'static [&'static str] = &[];
+}
 pub struct ApplicationState {
     pub postgres_config: configuration::postgres::PostgresConfig,
     pub server_config: configuration::server::ServerConfig,
@@ -13,6 +13,9 @@ pub struct ApplicationConfig {
     #[serde(default)]
     pub session: pavex_session::SessionConfig,
 }
+impl pavex::config::ConfigSecrets for ApplicationConfig {
+    const SECRET_KEYS: &'static [&'static str] = &[];
+}
 pub struct ApplicationState {
     pub processor: biscotti::Processor,
     pub session_config: pavex_session::SessionConfig,
@@ -173,7 +176,7 @@ pub mod route_0 {
             }
         };
         let v8 = pavex_session::SessionConfig::cookie_config(v3);
-        let v9 = pavex_session::IncomingSession::extract(&v7, v8);
+        let v9 = pavex_session::IncomingSession::extract(&v7, v8, v0);
         let v10 = pavex_session::Session::new(v4, v3, v9);
         let v11 = pavex_session::finalize_session(v2, v5, v0, v10).await;
         let v12 = match v11 {
@@ -13,6 +13,9 @@ pub struct ApplicationConfig {
     #[serde(default)]
     pub session: pavex_session::SessionConfig,
 }
+impl pavex::config::ConfigSecrets for ApplicationConfig {
+    const SECRET_KEYS: &'static [&'static str] = &[];
+}
 pub struct ApplicationState {
     pub processor: biscotti::Processor,
     pub session_config: pavex_session::SessionConfig,
@@ -483,7 +486,7 @@ pub mod route_0 {
             }
         };
         let v8 = pavex_session::SessionConfig::cookie_config(v3);
-        let v9 = pavex_session::IncomingSession::extract(&v7, v8);
+        let v9 = pavex_session::IncomingSession::extract(&v7, v8, v0);
         let v10 = pavex_session::Session::new(v4, v3, v9);
         let v11 = pavex_session::finalize_session(v2, v5, v0, v10).await;
         let v12 = match v11 {
@@ -585,7 +588,7 @@ pub mod route_1 {
             }
         };
         let v6 = pavex_session::SessionConfig::cookie_config(v2);
-        let v7 = pavex_session::IncomingSession::extract(&v5, v6);
+        let v7 = pavex_session::IncomingSession::extract(&v5, v6, v0);
         let v8 = pavex_session::Session::new(v3, v2, v7);
         let v9 = pavex::cookie::ResponseCookies::new();
         let v10 = crate::route_1::Next0 {
@@ -711,7 +714,7 @@ pub mod route_2 {
             }
         };
         let v6 = pavex_session::SessionConfig::cookie_config(v2);
-        let v7 = pavex_session::IncomingSession::extract(&v5, v6);
+        let v7 = pavex_session::IncomingSession::extract(&v5, v6, v0);
         let v8 = pavex_session::Session::new(v3, v2, v7);
         let v9 = pavex::cookie::ResponseCookies::new();
         let v10 = crate::route_2::Next0 {
@@ -837,7 +840,7 @@ pub mod route_3 {
             }
         };
         let v6 = pavex_session::SessionConfig::cookie_config(v2);
-        let v7 = pavex_session::IncomingSession::extract(&v5, v6);
+        let v7 = pavex_session::IncomingSession::extract(&v5, v6, v0);
         let v8 = pavex_session::Session::new(v3, v2, v7);
         let v9 = pavex::cookie::ResponseCookies::new();
         let v10 = crate::route_3::Next0 {
@@ -963,7 +966,7 @@ pub mod route_4 {
             }
         };
         let v6 = pavex_session::SessionConfig::cookie_config(v2);
-        let v7 = pavex_session::IncomingSession::extract(&v5, v6);
+        let v7 = pavex_session::IncomingSession::extract(&v5, v6, v0);
         let v8 = pavex_session::Session::new(v3, v2, v7);
         let v9 = pavex::cookie::ResponseCookies::new();
         let v10 = crate::route_4::Next0 {
@@ -1089,7 +1092,7 @@ pub mod route_5 {
             }
         };
         let v6 = pavex_session::SessionConfig::cookie_config(v2);
-        let v7 = pavex_session::IncomingSession::extract(&v5, v6);
+        let v7 = pavex_session::IncomingSession::extract(&v5, v6, v0);
         let v8 = pavex_session::Session::new(v3, v2, v7);
         let v9 = pavex::cookie::ResponseCookies::new();
         let v10 = crate::route_5::Next0 {
@@ -1215,7 +1218,7 @@ pub mod route_6 {
             }
         };
         let v6 = pavex_session::SessionConfig::cookie_config(v2);
-        let v7 = pavex_session::IncomingSession::extract(&v5, v6);
+        let v7 = pavex_session::IncomingSession::extract(&v5, v6, v0);
         let v8 = pavex_session::Session::new(v3, v2, v7);
         let v9 = pavex::cookie::ResponseCookies::new();
         let v10 = crate::route_6::Next0 {
@@ -1341,7 +1344,7 @@ pub mod route_7 {
             }
         };
         let v6 = pavex_session::SessionConfig::cookie_config(v2);
-        let v7 = pavex_session::IncomingSession::extract(&v5, v6);
+        let v7 = pavex_session::IncomingSession::extract(&v5, v6, v0);
         let v8 = pavex_session::Session::new(v3, v2, v7);
         let v9 = pavex::cookie::ResponseCookies::new();
         let v10 = crate::route_7::Next0 {
@@ -1467,7 +1470,7 @@ pub mod route_8 {
             }
         };
         let v6 = pavex_session::SessionConfig::cookie_config(v2);
-        let v7 = pavex_session::IncomingSession::extract(&v5, v6);
+        let v7 = pavex_session::IncomingSession::extract(&v5, v6, v0);
         let v8 = pavex_session::Session::new(v3, v2, v7);
         let v9 = pavex::cookie::ResponseCookies::new();
         let v10 = crate::route_8::Next0 {
@@ -1593,7 +1596,7 @@ pub mod route_9 {
             }
         };
         let v6 = pavex_session::SessionConfig::cookie_config(v2);
-        let v7 = pavex_session::IncomingSession::extract(&v5, v6);
+        let v7 = pavex_session::IncomingSession::extract(&v5, v6, v0);
         let v8 = pavex_session::Session::new(v3, v2, v7);
         let v9 = pavex::cookie::ResponseCookies::new();
         let v10 = crate::route_9::Next0 {
@@ -1719,7 +1722,7 @@ pub mod route_10 {
             }
         };
         let v6 = pavex_session::SessionConfig::cookie_config(v2);
-        let v7 = pavex_session::IncomingSession::extract(&v5, v6);
+        let v7 = pavex_session::IncomingSession::extract(&v5, v6, v0);
         let v8 = pavex_session::Session::new(v3, v2, v7);
         let v9 = pavex::cookie::ResponseCookies::new();
         let v10 = crate::route_10::Next0 {
@@ -1845,7 +1848,7 @@ pub mod route_11 {
             }
         };
         let v6 = pavex_session::SessionConfig::cookie_config(v2);
-        let v7 = pavex_session::IncomingSession::extract(&v5, v6);
+        let v7 = pavex_session::IncomingSession::extract(&v5, v6, v0);
         let v8 = pavex_session::Session::new(v3, v2, v7);
         let v9 = pavex::cookie::ResponseCookies::new();
         let v10 = crate::route_11::Next0 {
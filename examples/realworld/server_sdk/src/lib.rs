@@ -14,6 +14,9 @@ pub struct ApplicationConfig {
     pub database: app::configuration::DatabaseConfig,
     pub server: app::configuration::ServerConfig,
 }
+impl pavex::config::ConfigSecrets for ApplicationConfig {
+    const SECRET_KEYS: &'static [&'static str] = &[];
+}
 pub struct ApplicationState {
     pub encoding_key: jsonwebtoken::EncodingKey,
     pub pool: sqlx_core::pool::Pool<sqlx_postgres::Postgres>,
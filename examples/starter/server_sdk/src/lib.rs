@@ -11,6 +11,9 @@ pub struct ApplicationConfig {
     pub greet: app::configuration::GreetConfig,
     pub server: app::configuration::ServerConfig,
 }
+impl pavex::config::ConfigSecrets for ApplicationConfig {
+    const SECRET_KEYS: &'static [&'static str] = &[];
+}
 pub struct ApplicationState {
     pub greet_config: app::configuration::GreetConfig,
 }
@@ -181,6 +181,7 @@ impl Blueprint {
             relative_to: import.relative_to.to_owned(),
             created_at: created_at2created_at(import.created_at),
             registered_at: Location::caller(),
+            lints: Default::default(),
         };
         let component_id = self.push_component(import);
         RegisteredImport {
@@ -270,6 +271,7 @@ impl Blueprint {
             relative_to: import.relative_to.to_owned(),
             created_at: created_at2created_at(import.created_at),
             registered_at: Location::caller(),
+            lints: Default::default(),
         };
         let component_id = self.push_component(import);
         RegisteredRoutes {
@@ -384,6 +386,7 @@ impl Blueprint {
             coordinates: coordinates2coordinates(route.coordinates),
             registered_at: Location::caller(),
             error_handler: None,
+            lints: Default::default(),
         };
         let component_id = self.push_component(registered);
         RegisteredRoute {
@@ -1216,6 +1219,7 @@ impl Blueprint {
             coordinates: coordinates2coordinates(fallback.coordinates),
             registered_at: Location::caller(),
             error_handler: None,
+            lints: Default::default(),
         };
         let component_id = self.push_component(registered);
         RegisteredFallback {
@@ -40,6 +40,11 @@ pub(super) fn lint2lint(lint: Lint) -> pavex_bp_schema::Lint {
     match lint {
         Lint::Unused => pavex_bp_schema::Lint::Unused,
         Lint::ErrorFallback => pavex_bp_schema::Lint::ErrorFallback,
+        Lint::MissingDenyUnknownFields => pavex_bp_schema::Lint::MissingDenyUnknownFields,
+        Lint::RedundantClone => pavex_bp_schema::Lint::RedundantClone,
+        Lint::LargeSingletonByValue => pavex_bp_schema::Lint::LargeSingletonByValue,
+        Lint::UnreachableFallback => pavex_bp_schema::Lint::UnreachableFallback,
+        Lint::ShadowedRoute => pavex_bp_schema::Lint::ShadowedRoute,
     }
 }
 
@@ -1,6 +1,6 @@
-use crate::blueprint::ErrorHandler;
-use crate::blueprint::conversions::coordinates2coordinates;
-use pavex_bp_schema::{Blueprint as BlueprintSchema, Component, Location};
+use crate::blueprint::conversions::{coordinates2coordinates, lint2lint};
+use crate::blueprint::{ErrorHandler, Lint};
+use pavex_bp_schema::{Blueprint as BlueprintSchema, Component, LintSetting, Location};
 
 use super::reflection::AnnotationCoordinates;
 
@@ -90,6 +90,31 @@ impl RegisteredFallback<'_> {
         self
     }
 
+    /// Silence a specific [`Lint`] for this fallback.
+    pub fn allow(mut self, lint: Lint) -> Self {
+        self.fallback()
+            .lints
+            .insert(lint2lint(lint), LintSetting::Allow);
+        self
+    }
+
+    /// Emit a warning if a specific [`Lint`] triggers for this fallback.
+    pub fn warn(mut self, lint: Lint) -> Self {
+        self.fallback()
+            .lints
+            .insert(lint2lint(lint), LintSetting::Warn);
+        self
+    }
+
+    /// Fail the build if a specific [`Lint`] triggers
+    /// for this fallback.
+    pub fn deny(mut self, lint: Lint) -> Self {
+        self.fallback()
+            .lints
+            .insert(lint2lint(lint), LintSetting::Deny);
+        self
+    }
+
     fn fallback(&mut self) -> &mut pavex_bp_schema::Fallback {
         let component = &mut self.blueprint.components[self.component_id];
         let Component::FallbackRequestHandler(fallback) = component else {
@@ -1,4 +1,7 @@
-use pavex_bp_schema::Blueprint as BlueprintSchema;
+use pavex_bp_schema::{Blueprint as BlueprintSchema, Component, LintSetting};
+
+use crate::blueprint::Lint;
+use crate::blueprint::conversions::lint2lint;
 
 use super::reflection::{CreatedAt, Sources};
 
@@ -42,9 +45,49 @@ pub struct Import {
 ///
 /// [`Blueprint::import`]: crate::Blueprint::import
 pub struct RegisteredImport<'a> {
-    #[allow(unused)]
     pub(crate) blueprint: &'a mut BlueprintSchema,
     /// The index of the registered import in the blueprint's `components` vector.
-    #[allow(unused)]
     pub(crate) component_id: usize,
 }
+
+impl RegisteredImport<'_> {
+    /// Silence a specific [`Lint`] for all the components registered via this import.
+    ///
+    /// The setting can be overridden on a component-by-component basis.
+    pub fn allow(mut self, lint: Lint) -> Self {
+        self.import()
+            .lints
+            .insert(lint2lint(lint), LintSetting::Allow);
+        self
+    }
+
+    /// Emit a warning if a specific [`Lint`] triggers for any of the components
+    /// registered via this import.
+    ///
+    /// The setting can be overridden on a component-by-component basis.
+    pub fn warn(mut self, lint: Lint) -> Self {
+        self.import()
+            .lints
+            .insert(lint2lint(lint), LintSetting::Warn);
+        self
+    }
+
+    /// Fail the build if a specific [`Lint`] triggers for any of the components
+    /// registered via this import.
+    ///
+    /// The setting can be overridden on a component-by-component basis.
+    pub fn deny(mut self, lint: Lint) -> Self {
+        self.import()
+            .lints
+            .insert(lint2lint(lint), LintSetting::Deny);
+        self
+    }
+
+    fn import(&mut self) -> &mut pavex_bp_schema::Import {
+        let component = &mut self.blueprint.components[self.component_id];
+        let Component::Import(i) = component else {
+            unreachable!("The component should be an import")
+        };
+        i
+    }
+}
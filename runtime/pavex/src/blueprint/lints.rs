@@ -15,4 +15,74 @@ pub enum Lint {
     ///
     /// [1]: https://pavex.dev/docs/guide/errors/error_handlers/#fallback-error-handler
     ErrorFallback,
+    /// The type extracted by a body extractor (e.g. `JsonBody<T>` or `UrlEncodedBody<T>`)
+    /// doesn't use `#[serde(deny_unknown_fields)]`, therefore it silently ignores unknown fields
+    /// in the incoming payload.
+    ///
+    /// This lint is allowed by default. You can enable it on the constructor of the body extractor:
+    ///
+    /// ```rust
+    /// use pavex::Blueprint;
+    /// use pavex::blueprint::Lint;
+    /// use pavex::request::body::JSON_BODY_EXTRACT;
+    ///
+    /// let mut bp = Blueprint::new();
+    /// bp.constructor(JSON_BODY_EXTRACT)
+    ///     .warn(Lint::MissingDenyUnknownFields);
+    /// ```
+    MissingDenyUnknownFields,
+    /// A type marked as [`CloneIfNecessary`] had to be cloned
+    /// to satisfy the borrow checker.
+    ///
+    /// Cloning is often cheap, but it may be a sign that one of your components
+    /// is taking by value an input that it could borrow instead.
+    ///
+    /// This lint is allowed by default. You can enable it for all the constructors
+    /// registered via an import:
+    ///
+    /// ```rust
+    /// use pavex::{Blueprint, blueprint::{from, Lint}};
+    ///
+    /// let mut bp = Blueprint::new();
+    /// bp.import(from![crate]).warn(Lint::RedundantClone);
+    /// ```
+    ///
+    /// [`CloneIfNecessary`]: crate::blueprint::CloningPolicy::CloneIfNecessary
+    RedundantClone,
+    /// A large singleton is taken by value by one of your request-scoped components.
+    ///
+    /// Singletons are stored in the application state: taking one by value
+    /// forces Pavex to copy or clone it for every incoming request.
+    /// Consider taking a reference instead.
+    ///
+    /// This lint is allowed by default.
+    LargeSingletonByValue,
+    /// A fallback registered under a path prefix can never be invoked,
+    /// since all the requests it would handle are already matched by
+    /// other routes (e.g. a route with a catch-all parameter).
+    ///
+    /// This lint is allowed by default. You can enable it on the fallback:
+    ///
+    /// ```rust
+    /// use pavex::{Blueprint, blueprint::Lint, fallback, Response};
+    ///
+    /// #[fallback]
+    /// pub fn not_found() -> Response {
+    ///     Response::not_found()
+    /// }
+    ///
+    /// let mut bp = Blueprint::new();
+    /// bp.fallback(NOT_FOUND).warn(Lint::UnreachableFallback);
+    /// ```
+    UnreachableFallback,
+    /// A route can never be reached for some of the hosts matched by its domain constraint,
+    /// since requests for those hosts are routed to a more specific domain
+    /// that doesn't have a route for the same path.
+    ///
+    /// E.g. a route for `/` registered under `{sub}.example.com` won't be invoked for
+    /// `api.example.com/` requests if `api.example.com` is registered as a domain
+    /// without a route for `/`.
+    ///
+    /// This lint is allowed by default.
+    ShadowedRoute,
 }
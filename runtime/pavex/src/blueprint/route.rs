@@ -1,6 +1,6 @@
-use crate::blueprint::ErrorHandler;
-use crate::blueprint::conversions::coordinates2coordinates;
-use pavex_bp_schema::{Blueprint as BlueprintSchema, Component, Location};
+use crate::blueprint::conversions::{coordinates2coordinates, lint2lint};
+use crate::blueprint::{ErrorHandler, Lint};
+use pavex_bp_schema::{Blueprint as BlueprintSchema, Component, LintSetting, Location};
 
 use super::reflection::AnnotationCoordinates;
 
@@ -82,6 +82,31 @@ impl RegisteredRoute<'_> {
         self
     }
 
+    /// Silence a specific [`Lint`] for this route.
+    pub fn allow(mut self, lint: Lint) -> Self {
+        self.route()
+            .lints
+            .insert(lint2lint(lint), LintSetting::Allow);
+        self
+    }
+
+    /// Emit a warning if a specific [`Lint`] triggers for this route.
+    pub fn warn(mut self, lint: Lint) -> Self {
+        self.route()
+            .lints
+            .insert(lint2lint(lint), LintSetting::Warn);
+        self
+    }
+
+    /// Fail the build if a specific [`Lint`] triggers
+    /// for this route.
+    pub fn deny(mut self, lint: Lint) -> Self {
+        self.route()
+            .lints
+            .insert(lint2lint(lint), LintSetting::Deny);
+        self
+    }
+
     fn route(&mut self) -> &mut pavex_bp_schema::Route {
         let component = &mut self.blueprint.components[self.component_id];
         let Component::Route(c) = component else {
@@ -1,18 +1,57 @@
-use pavex_bp_schema::Blueprint as BlueprintSchema;
+use pavex_bp_schema::{Blueprint as BlueprintSchema, Component, LintSetting};
+
+use crate::blueprint::Lint;
+use crate::blueprint::conversions::lint2lint;
 
 /// The type returned by [`Blueprint::routes`].
 ///
-/// # Future-proofing
-///
-/// As of today, [`RegisteredRoutes`] doesn't provide any additional functionality.\
-/// It is included to allow introducing new configuration for route groups without having
-/// to change the signature of [`Blueprint::routes`].
+/// It allows you to further configure the behaviour of the imported routes.
 ///
 /// [`Blueprint::routes`]: crate::Blueprint::routes
 pub struct RegisteredRoutes<'a> {
-    #[allow(unused)]
     pub(crate) blueprint: &'a mut BlueprintSchema,
     /// The index of the registered routes import in the blueprint's `components` vector.
-    #[allow(unused)]
     pub(crate) component_id: usize,
 }
+
+impl RegisteredRoutes<'_> {
+    /// Silence a specific [`Lint`] for all the routes registered via this import.
+    ///
+    /// The setting can be overridden on a component-by-component basis.
+    pub fn allow(mut self, lint: Lint) -> Self {
+        self.routes_import()
+            .lints
+            .insert(lint2lint(lint), LintSetting::Allow);
+        self
+    }
+
+    /// Emit a warning if a specific [`Lint`] triggers for any of the routes
+    /// registered via this import.
+    ///
+    /// The setting can be overridden on a component-by-component basis.
+    pub fn warn(mut self, lint: Lint) -> Self {
+        self.routes_import()
+            .lints
+            .insert(lint2lint(lint), LintSetting::Warn);
+        self
+    }
+
+    /// Fail the build if a specific [`Lint`] triggers for any of the routes
+    /// registered via this import.
+    ///
+    /// The setting can be overridden on a component-by-component basis.
+    pub fn deny(mut self, lint: Lint) -> Self {
+        self.routes_import()
+            .lints
+            .insert(lint2lint(lint), LintSetting::Deny);
+        self
+    }
+
+    fn routes_import(&mut self) -> &mut pavex_bp_schema::RoutesImport {
+        let component = &mut self.blueprint.components[self.component_id];
+        let Component::RoutesImport(i) = component else {
+            unreachable!("The component should be a routes import")
+        };
+        i
+    }
+}
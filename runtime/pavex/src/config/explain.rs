@@ -0,0 +1,228 @@
+use std::fmt;
+
+use figment::{
+    Figment, Profile, Provider,
+    value::{Dict, Value},
+};
+
+use super::ConfigSource;
+
+#[derive(Debug, Clone)]
+/// The provenance of every configuration value, as returned by
+/// [`ConfigLoader::explain`](super::ConfigLoader::explain).
+///
+/// Use its [`Display`](fmt::Display) implementation to print it, or [`ConfigReport::trace`]
+/// to emit it as structured `tracing` events.
+pub struct ConfigReport {
+    pub(super) profile: String,
+    pub(super) entries: Vec<ConfigEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+/// A configuration value, alongside the source that set it.
+pub struct ConfigEntry {
+    /// The dotted path to the value—e.g. `postgres.pool.max_size`.
+    pub key: String,
+    /// The value, serialized as JSON.
+    ///
+    /// It's `None` if the value has been redacted.
+    pub value: Option<String>,
+    /// The source that set the value, if it can be determined.
+    pub source: Option<ConfigSource>,
+    /// The variable that set the value, if it comes from environment variables,
+    /// a `.env` file or a secret file—e.g. `PX_POSTGRES__POOL__MAX_SIZE`.
+    pub variable: Option<String>,
+}
+
+impl ConfigReport {
+    /// The configuration profile.
+    pub fn profile(&self) -> &str {
+        &self.profile
+    }
+
+    /// One entry for each leaf value, sorted by key.
+    pub fn entries(&self) -> &[ConfigEntry] {
+        &self.entries
+    }
+
+    /// Emit one `INFO` event for each configuration value.
+    ///
+    /// Each event carries the `config.key`, `config.value` and `config.source` fields.
+    /// Redacted values are reported as `[redacted]`.
+    pub fn trace(&self) {
+        for entry in &self.entries {
+            tracing::info!(
+                config.profile = %self.profile,
+                config.key = %entry.key,
+                config.value = %entry.value(),
+                config.source = %entry.source(),
+                "Configuration value",
+            );
+        }
+    }
+}
+
+impl ConfigEntry {
+    fn value(&self) -> &str {
+        self.value.as_deref().unwrap_or("[redacted]")
+    }
+
+    fn source(&self) -> String {
+        let Some(source) = &self.source else {
+            return "unknown source".into();
+        };
+        match (source, &self.variable) {
+            (ConfigSource::EnvVars, Some(variable)) => {
+                format!("environment variable `{variable}`")
+            }
+            (ConfigSource::DotEnvFile { .. }, Some(variable)) => {
+                format!("`{variable}` in {source}")
+            }
+            _ => source.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for ConfigReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Configuration profile: {}", self.profile)?;
+        for entry in &self.entries {
+            writeln!(f, "{entry}")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for ConfigEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} = {} ({})", self.key, self.value(), self.source())
+    }
+}
+
+/// The configuration keys that hold secret values.
+///
+/// Pavex implements this trait for the `ApplicationConfig` type in your server SDK crate:
+/// it lists the keys whose type is wrapped in a [`secrecy`](https://docs.rs/secrecy) type—e.g.
+/// `SecretString`.
+/// [`ConfigLoader::explain`](super::ConfigLoader::explain) redacts their values.
+pub trait ConfigSecrets {
+    /// Dotted key patterns—e.g. `postgres.password`.
+    ///
+    /// `*` matches any segment—e.g. `tenants.*.api_key`.
+    const SECRET_KEYS: &'static [&'static str];
+}
+
+/// The keys whose values must not be shown.
+#[derive(Debug, Clone, Default)]
+pub(super) struct Redactions {
+    /// Dotted key patterns. `*` matches any segment.
+    /// A pattern matches the keys it's a prefix of, segment-wise.
+    patterns: Vec<String>,
+}
+
+/// Key segments that hint at a secret value.
+static SECRET_HINTS: [&str; 7] = [
+    "password",
+    "passwd",
+    "secret",
+    "token",
+    "api_key",
+    "private_key",
+    "credential",
+];
+
+impl Redactions {
+    pub(super) fn push(&mut self, pattern: String) {
+        self.patterns.push(pattern);
+    }
+
+    pub(super) fn extend(&mut self, patterns: &[&str]) {
+        self.patterns
+            .extend(patterns.iter().map(|pattern| (*pattern).to_owned()));
+    }
+
+    pub(super) fn is_redacted(&self, key: &str) -> bool {
+        let segments: Vec<_> = key.split('.').collect();
+        let hinted = segments.iter().any(|segment| {
+            let segment = segment.to_ascii_lowercase();
+            SECRET_HINTS.iter().any(|hint| segment.contains(hint))
+        });
+        hinted
+            || self.patterns.iter().any(|pattern| {
+                let pattern: Vec<_> = pattern.split('.').collect();
+                pattern.len() <= segments.len()
+                    && pattern
+                        .iter()
+                        .zip(&segments)
+                        .all(|(p, s)| *p == "*" || p == s)
+            })
+    }
+}
+
+/// Build the report for the values in `figment`, attributing each one to one of `sources`.
+pub(super) fn report(
+    figment: &Figment,
+    profile: String,
+    sources: &[ConfigSource],
+    redactions: &Redactions,
+) -> Result<ConfigReport, anyhow::Error> {
+    let data = figment.data()?;
+    let mut keys = Vec::new();
+    for profile in [Profile::Default, Profile::Global] {
+        if let Some(dict) = data.get(&profile) {
+            leaf_keys(dict, &mut Vec::new(), &mut keys);
+        }
+    }
+    keys.sort();
+    keys.dedup();
+
+    let mut entries = Vec::with_capacity(keys.len());
+    for key in keys {
+        let Ok(value) = figment.find_value(&key) else {
+            continue;
+        };
+        // The key was overridden by a table, whose leaves have their own entries.
+        if matches!(&value, Value::Dict(_, dict) if !dict.is_empty()) {
+            continue;
+        }
+        let metadata = figment.get_metadata(value.tag());
+        let source = metadata.and_then(|metadata| {
+            // Later sources take precedence, so they are the most likely to match.
+            sources.iter().rev().find(|s| s.provided(metadata)).cloned()
+        });
+        let variable = match (&source, metadata) {
+            (Some(source), Some(metadata)) if source.is_variable() => {
+                let segments: Vec<_> = key.split('.').collect();
+                Some(metadata.interpolate(&Profile::Default, &segments))
+            }
+            _ => None,
+        };
+        let redacted =
+            redactions.is_redacted(&key) || matches!(source, Some(ConfigSource::SecretFile { .. }));
+        let value = if redacted {
+            None
+        } else {
+            Some(serde_json::to_string(&value)?)
+        };
+        entries.push(ConfigEntry {
+            key,
+            value,
+            source,
+            variable,
+        });
+    }
+    Ok(ConfigReport { profile, entries })
+}
+
+/// Collect the dotted paths to all the leaf values in `dict`.
+fn leaf_keys(dict: &Dict, path: &mut Vec<String>, out: &mut Vec<String>) {
+    for (key, value) in dict {
+        path.push(key.clone());
+        match value {
+            Value::Dict(_, nested) if !nested.is_empty() => leaf_keys(nested, path, out),
+            _ => out.push(path.join(".")),
+        }
+        path.pop();
+    }
+}
@@ -6,14 +6,33 @@
 //!
 //! Check out [the guide](https://pavex.dev/docs/guide/configuration/)
 //! for a thorough introduction to Pavex configuration system.
-use std::{path::PathBuf, str::FromStr};
+use std::{collections::BTreeMap, path::PathBuf, str::FromStr, sync::Arc};
 
 use anyhow::Context;
+use explain::Redactions;
 use figment::{
-    Figment,
-    providers::{Env, Format, Yaml},
+    Figment, Provider,
+    providers::{Env, Format, Json, Toml, Yaml},
+    value::Value,
 };
 use serde::de::DeserializeOwned;
+use sources::{
+    CustomProvider, DOTENV_FILE, ENV_PREFIX, ENV_VARS, FILE_SUFFIX, SECRET_FILE, Variables,
+    WithMetadata,
+};
+
+pub use explain::{ConfigEntry, ConfigReport, ConfigSecrets};
+pub use reload::{ConfigReloader, Reloadable};
+pub use sources::{ConfigFormat, ConfigSource};
+
+/// Re-export of [`figment`], to implement custom configuration providers.
+///
+/// Check out [`ConfigLoader::provider`] for more details.
+pub use figment;
+
+mod explain;
+mod reload;
+mod sources;
 
 #[derive(Clone, Debug)]
 /// A utility to load hierarchical configuration in a Pavex application.
@@ -47,6 +66,10 @@ use serde::de::DeserializeOwned;
 pub struct ConfigLoader<Profile> {
     configuration_dir: Option<PathBuf>,
     profile: Option<Profile>,
+    dotenv_file: Option<PathBuf>,
+    env_file_indirection: bool,
+    providers: Vec<CustomProvider>,
+    redactions: Redactions,
 }
 
 /// A macro to derive an implementation of the [`ConfigProfile`] trait.
@@ -202,6 +225,10 @@ where
         Self {
             configuration_dir: None,
             profile: None,
+            dotenv_file: None,
+            env_file_indirection: false,
+            providers: Vec::new(),
+            redactions: Redactions::default(),
         }
     }
 
@@ -235,15 +262,117 @@ where
         self
     }
 
-    /// Load the configuration for the application by merging together three sources:
+    /// Load `PX_*` variables from a `.env` file, on top of the ones set in the environment.
+    ///
+    /// It's primarily meant for local development.
+    /// Variables set in the environment take precedence over the ones listed in the file.
+    /// The `PX_PROFILE` entry is honored too, if the variable isn't set in the environment
+    /// and the profile hasn't been [specified manually](Self::profile).
+    ///
+    /// Entries without the `PX_` prefix are ignored.
+    /// A missing file is not an error, while a file that can't be parsed is.
+    ///
+    /// The path is resolved relative to the current working directory.
+    pub fn dotenv_file<P>(mut self, path: P) -> Self
+    where
+        P: Into<PathBuf>,
+    {
+        self.dotenv_file = Some(path.into());
+        self
+    }
+
+    /// Read configuration values from the files referenced by `PX_*_FILE` variables.
+    ///
+    /// When enabled, `PX_POSTGRES__PASSWORD_FILE=/run/secrets/db_password` sets `postgres.password`
+    /// to the contents of `/run/secrets/db_password`, minus its trailing newline.
+    /// This is the convention used by most container orchestrators to mount secrets.
+    ///
+    /// The contents of a secret file are always used as a string, they aren't parsed.
+    /// It's an error if a referenced file can't be read.
+    ///
+    /// # Default value
+    ///
+    /// Disabled. When disabled, a `PX_*_FILE` variable sets the key ending in `_file`, like
+    /// any other environment variable.
+    pub fn env_file_indirection(mut self, enabled: bool) -> Self {
+        self.env_file_indirection = enabled;
+        self
+    }
+
+    /// Add a custom [`Provider`] to the list of configuration sources.
+    ///
+    /// Custom providers take precedence over configuration files, but they are overridden by
+    /// `.env` files and environment variables.
+    /// If you register more than one, each one takes precedence over the previous ones.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use pavex::config::{ConfigLoader, ConfigProfile, figment::providers::Serialized};
+    ///
+    /// #[derive(ConfigProfile, Debug, Clone, Copy, PartialEq, Eq)]
+    /// pub enum Profile {
+    ///     Development,
+    ///     Production,
+    /// }
+    ///
+    /// #[derive(Debug, Clone, serde::Deserialize)]
+    /// pub struct Config {
+    ///     database_url: String,
+    /// }
+    ///
+    /// # fn main() -> anyhow::Result<()> {
+    /// let config: Config = ConfigLoader::<Profile>::new()
+    ///     .provider(Serialized::default("database_url", "postgres://localhost/app"))
+    ///     .load()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn provider<P>(mut self, provider: P) -> Self
+    where
+        P: Provider + Send + Sync + 'static,
+    {
+        self.providers.push(CustomProvider(Arc::new(provider)));
+        self
+    }
+
+    /// Redact the value of `key` in the output of [`ConfigLoader::explain`].
+    ///
+    /// `key` is a dotted path—e.g. `postgres.password`. It redacts all the values nested
+    /// under it, if it points at a table. Use `*` to match any segment—e.g. `tenants.*.api_key`.
+    pub fn redact<K>(mut self, key: K) -> Self
+    where
+        K: Into<String>,
+    {
+        self.redactions.push(key.into());
+        self
+    }
+
+    /// The sources that [`ConfigLoader::load`] would merge together, ordered from
+    /// the lowest to the highest precedence.
+    ///
+    /// Configuration files and `.env` files that don't exist are omitted.
+    /// The profile is resolved following the same rules used by [`ConfigLoader::load`].
+    pub fn sources(&self) -> Result<Vec<ConfigSource>, errors::ConfigLoadError> {
+        self.plan()
+            .map(|plan| plan.sources)
+            .map_err(errors::ConfigLoadError)
+    }
+
+    /// Load the configuration for the application by merging together the following sources:
     ///
-    /// 1. Environment variables (`PX_*`)
-    /// 2. Profile-specific configuration file (`{configuration_dir}/{profile}.yml`)
-    /// 3. Base configuration file (`{configuration_dir}/base.yml`)
+    /// 1. Secret files (`PX_*_FILE`), if enabled via [`ConfigLoader::env_file_indirection`]
+    /// 2. Environment variables (`PX_*`)
+    /// 3. `.env` file, if registered via [`ConfigLoader::dotenv_file`]
+    /// 4. Custom providers, registered via [`ConfigLoader::provider`]
+    /// 5. Profile-specific configuration file (`{configuration_dir}/{profile}.{yml,yaml,toml,json}`)
+    /// 6. Base configuration file (`{configuration_dir}/base.{yml,yaml,toml,json}`)
     ///
-    /// The list above is ordered by precedence: environment variables take precedence
-    /// over profile-specific configuration files, which in turn take precedence
-    /// over the base configuration file.
+    /// The list above is ordered by precedence: secret files take precedence over environment
+    /// variables, which in turn take precedence over `.env` files, and so on, down to the
+    /// base configuration file.
+    /// The format of each configuration file is inferred from its extension.
+    /// Use [`ConfigLoader::sources`] to inspect the sources that will be used.
     ///
     /// # Guide
     ///
@@ -254,51 +383,263 @@ where
     where
         Config: DeserializeOwned,
     {
-        let profile = match self.profile {
-            Some(profile) => profile,
-            None => Profile::load().map_err(|e| errors::ConfigLoadError(e.into()))?,
+        let plan = self.plan().map_err(errors::ConfigLoadError)?;
+        let span = tracing::info_span!(
+            "Loading configuration",
+            configuration.directory = %plan.configuration_dir.display(),
+            configuration.profile = %plan.profile,
+        );
+        let _guard = span.enter();
+        let configuration: Config = self
+            .figment(&plan)
+            .and_then(|figment| {
+                figment
+                    .extract()
+                    .context("Failed to load hierarchical configuration")
+            })
+            .map_err(errors::ConfigLoadError)?;
+        Ok(configuration)
+    }
+
+    /// Report the final value of every configuration key, alongside the source that set it.
+    ///
+    /// It merges the same sources used by [`ConfigLoader::load`], without deserializing
+    /// the outcome: it works even if the configuration is invalid, which makes it a
+    /// useful debugging tool.
+    ///
+    /// # Redaction
+    ///
+    /// The following values are redacted:
+    ///
+    /// - values read from secret files (see [`ConfigLoader::env_file_indirection`])
+    /// - keys registered via [`ConfigLoader::redact`]
+    /// - the [secret keys](ConfigSecrets) of `Config`—i.e. the fields of your `ApplicationConfig`
+    ///   wrapped in a [`secrecy`](https://docs.rs/secrecy) type
+    /// - keys whose name suggests a secret—e.g. `password`, `token` or `api_key`
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use pavex::config::{ConfigLoader, ConfigProfile};
+    ///
+    /// #[derive(ConfigProfile, Debug, Clone, Copy, PartialEq, Eq)]
+    /// pub enum Profile {
+    ///     Development,
+    ///     Production,
+    /// }
+    ///
+    /// # struct ApplicationConfig;
+    /// # impl pavex::config::ConfigSecrets for ApplicationConfig {
+    /// #     const SECRET_KEYS: &'static [&'static str] = &[];
+    /// # }
+    /// # fn main() -> anyhow::Result<()> {
+    /// let loader = ConfigLoader::<Profile>::new().redact("postgres.connection_string");
+    /// // Emit one `tracing` event per configuration value.
+    /// // `ApplicationConfig` is the configuration type generated by Pavex
+    /// // in your server SDK crate.
+    /// loader.explain::<ApplicationConfig>()?.trace();
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn explain<Config>(&self) -> Result<ConfigReport, errors::ConfigLoadError>
+    where
+        Config: ConfigSecrets,
+    {
+        let plan = self.plan().map_err(errors::ConfigLoadError)?;
+        let mut redactions = self.redactions.clone();
+        redactions.extend(Config::SECRET_KEYS);
+        self.figment(&plan)
+            .and_then(|figment| explain::report(&figment, plan.profile, &plan.sources, &redactions))
+            .context("Failed to explain the hierarchical configuration")
+            .map_err(errors::ConfigLoadError)
+    }
+
+    /// Determine the profile and the sources to be merged.
+    fn plan(&self) -> Result<Plan, anyhow::Error> {
+        let dotenv = match &self.dotenv_file {
+            Some(path) => sources::read_dotenv(path)?.map(|entries| (path.to_owned(), entries)),
+            None => None,
+        };
+        let profile = match &self.profile {
+            Some(profile) => profile.as_ref().to_owned(),
+            None => self.load_profile(dotenv.as_ref().map(|(_, entries)| &entries[..]))?,
         };
         let configuration_dir = self
             .configuration_dir
+            .clone()
             .unwrap_or_else(|| PathBuf::from("configuration"));
-        let span = tracing::info_span!(
-            "Loading configuration",
-            configuration.directory = %configuration_dir.display(),
-            configuration.profile = %profile.as_ref(),
+
+        let mut sources = Vec::new();
+        for stem in ["base", profile.as_str()] {
+            if let Some((path, format)) = sources::find_file(&configuration_dir, stem)? {
+                sources.push(ConfigSource::File { path, format });
+            }
+        }
+        sources.extend(
+            self.providers
+                .iter()
+                .map(|p| ConfigSource::Provider { name: p.name() }),
         );
-        let _guard = span.enter();
-        // Load configuration files from the specified directory
-        // and return a `Config` instance.
-        let base_filepath = configuration_dir.join("base.yml");
-        let profile_filepath = configuration_dir.join(format!("{}.yml", profile.as_ref()));
+        if let Some((path, _)) = &dotenv {
+            sources.push(ConfigSource::DotEnvFile {
+                path: path.to_owned(),
+            });
+        }
+        sources.push(ConfigSource::EnvVars);
+        if self.env_file_indirection {
+            // Environment variables take precedence over `.env` entries.
+            let mut secrets = BTreeMap::new();
+            let dotenv_entries = dotenv
+                .iter()
+                .flat_map(|(_, entries)| entries.iter().cloned());
+            let env_vars = std::env::vars_os().map(|(k, v)| {
+                (
+                    k.to_string_lossy().into_owned(),
+                    v.to_string_lossy().into_owned(),
+                )
+            });
+            for (variable, path) in dotenv_entries.chain(env_vars) {
+                if let Some(name) = sources::strip_file_suffix(&variable)
+                    && sources::variable_key(name).is_some()
+                {
+                    secrets.insert(name.to_ascii_uppercase(), (variable, PathBuf::from(path)));
+                }
+            }
+            sources.extend(
+                secrets
+                    .into_values()
+                    .map(|(variable, path)| ConfigSource::SecretFile { variable, path }),
+            );
+        }
+
+        Ok(Plan {
+            profile,
+            configuration_dir,
+            dotenv: dotenv.map(|(_, entries)| entries),
+            sources,
+        })
+    }
 
-        let prefix = "PX_";
-        let env_source = Env::prefixed(prefix)
-            .split("__")
-            // We explicitly filter out the `PX_PROFILE` environment variable
-            // to allow users to set `#[serde(deny_unknown_fields)]` on their configuration type.
-            // Without this `ignore`, `serde` would complain about `PX_PROFILE` being unknown.
-            .ignore(&[PROFILE_ENV_VAR.strip_prefix(prefix).unwrap()]);
-        let figment = Figment::new()
-            .merge(Yaml::file(base_filepath))
-            .merge(Yaml::file(profile_filepath))
-            .merge(env_source);
+    /// Load the profile from the `PX_PROFILE` environment variable,
+    /// falling back to the `.env` file if the variable isn't set.
+    fn load_profile(&self, dotenv: Option<&[(String, String)]>) -> Result<String, anyhow::Error> {
+        let from_dotenv = dotenv
+            .into_iter()
+            .flatten()
+            .find(|(name, _)| name == PROFILE_ENV_VAR)
+            .map(|(_, value)| value);
+        let profile = match from_dotenv {
+            Some(value) if std::env::var_os(PROFILE_ENV_VAR).is_none() => {
+                Profile::from_str(value).map_err(|e| {
+                    anyhow::anyhow!(e).context(format!(
+                        "Failed to parse the configuration profile from the `{PROFILE_ENV_VAR}` entry in the `.env` file"
+                    ))
+                })?
+            }
+            _ => Profile::load()?,
+        };
+        Ok(profile.as_ref().to_owned())
+    }
 
-        let configuration: Config = figment
-            .extract()
-            .context("Failed to load hierarchical configuration")
-            .map_err(errors::ConfigLoadError)?;
-        Ok(configuration)
+    /// Assemble the [`Figment`] that merges all the sources in `plan`, in order.
+    fn figment(&self, plan: &Plan) -> Result<Figment, anyhow::Error> {
+        let indirection = self.env_file_indirection;
+        // `PX_*_FILE` variables are handled as secret file references when indirection is enabled.
+        let is_secret_ref =
+            move |name: &str| indirection && sources::strip_file_suffix(name).is_some();
+        // `.env` entries are treated like environment variables:
+        // we skip `PX_PROFILE`, as well as secret file references.
+        let is_value =
+            |name: &str| !name.eq_ignore_ascii_case(PROFILE_ENV_VAR) && !is_secret_ref(name);
+        let mut providers = self.providers.iter();
+        let mut figment = Figment::new();
+        for source in &plan.sources {
+            figment = match source {
+                ConfigSource::File { path, format } => match format {
+                    ConfigFormat::Yaml => figment.merge(Yaml::file_exact(path)),
+                    ConfigFormat::Toml => figment.merge(Toml::file_exact(path)),
+                    ConfigFormat::Json => figment.merge(Json::file_exact(path)),
+                },
+                ConfigSource::Provider { .. } => {
+                    let provider = providers.next().expect("One source per custom provider");
+                    figment.merge(provider)
+                }
+                ConfigSource::DotEnvFile { path } => {
+                    let values = plan
+                        .dotenv
+                        .iter()
+                        .flatten()
+                        .filter(|(name, _)| is_value(name))
+                        .filter_map(|(name, value)| {
+                            let key = sources::variable_key(name)?;
+                            Some((key, value.parse().expect("infallible")))
+                        })
+                        .collect();
+                    figment.merge(Variables {
+                        metadata: sources::variables_metadata(DOTENV_FILE, Some(path), ""),
+                        values,
+                    })
+                }
+                ConfigSource::EnvVars => {
+                    let env_source = Env::prefixed(ENV_PREFIX)
+                        // We explicitly filter out the `PX_PROFILE` environment variable
+                        // to allow users to set `#[serde(deny_unknown_fields)]` on their configuration type.
+                        // Without this `ignore`, `serde` would complain about `PX_PROFILE` being unknown.
+                        .ignore(&[PROFILE_ENV_VAR.strip_prefix(ENV_PREFIX).unwrap()])
+                        .filter(move |key| !is_secret_ref(key.as_str()))
+                        .split("__");
+                    figment.merge(WithMetadata {
+                        provider: env_source,
+                        metadata: sources::variables_metadata(ENV_VARS, None, ""),
+                    })
+                }
+                ConfigSource::SecretFile { variable, path } => {
+                    let key = sources::strip_file_suffix(variable)
+                        .and_then(sources::variable_key)
+                        .expect("Secret file variables map to a valid key");
+                    let contents = std::fs::read_to_string(path).with_context(|| {
+                        format!(
+                            "Failed to read the secret file `{}`, referenced by `{variable}`",
+                            path.display()
+                        )
+                    })?;
+                    let value = contents
+                        .strip_suffix('\n')
+                        .map(|v| v.strip_suffix('\r').unwrap_or(v))
+                        .unwrap_or(&contents);
+                    figment.merge(Variables {
+                        metadata: sources::variables_metadata(SECRET_FILE, Some(path), FILE_SUFFIX),
+                        values: vec![(key, Value::from(value.to_owned()))],
+                    })
+                }
+            };
+        }
+        Ok(figment)
     }
 }
 
+/// The outcome of [`ConfigLoader::plan`].
+struct Plan {
+    profile: String,
+    configuration_dir: PathBuf,
+    dotenv: Option<Vec<(String, String)>>,
+    sources: Vec<ConfigSource>,
+}
+
 /// Errors that can occur when loading configuration.
 pub mod errors {
     #[derive(Debug, thiserror::Error)]
     #[error("Failed to load configuration")]
-    /// The error returned by [`ConfigLoader::load`](super::ConfigLoader::load).
+    /// The error returned by [`ConfigLoader::load`](super::ConfigLoader::load),
+    /// [`ConfigLoader::sources`](super::ConfigLoader::sources)
+    /// and [`ConfigLoader::explain`](super::ConfigLoader::explain).
     pub struct ConfigLoadError(#[source] pub(super) anyhow::Error);
 
+    #[derive(Debug, thiserror::Error)]
+    #[error("Failed to reload configuration")]
+    /// The error returned by [`ConfigReloader::reload`](super::ConfigReloader::reload).
+    pub struct ConfigReloadError(#[source] pub(super) anyhow::Error);
+
     #[derive(Debug, thiserror::Error)]
     #[error(transparent)]
     /// The error returned by [`ConfigProfile::load`](super::ConfigProfile::load).
@@ -0,0 +1,456 @@
+use std::{
+    any::{Any, TypeId},
+    cell::RefCell,
+    collections::HashMap,
+    fmt,
+    marker::PhantomData,
+    sync::{Arc, PoisonError, RwLock},
+};
+
+use arc_swap::ArcSwap;
+use serde::{Deserialize, Deserializer, de::DeserializeOwned};
+
+use super::{
+    ConfigLoader, ConfigProfile,
+    errors::{ConfigLoadError, ConfigReloadError},
+};
+
+/// A configuration value that can be updated while the application is running.
+///
+/// `Reloadable<T>` deserializes exactly like `T`. Mark a configuration type as reloadable
+/// by registering `Reloadable<T>` as configuration, rather than `T`:
+///
+/// ```rust
+/// use pavex::config::Reloadable;
+///
+/// #[derive(Debug, Clone, serde::Deserialize)]
+/// pub struct RateLimitConfig {
+///     pub requests_per_minute: u32,
+/// }
+///
+/// #[pavex::config(key = "rate_limit")]
+/// pub type ReloadableRateLimit = Reloadable<RateLimitConfig>;
+/// ```
+///
+/// Components can then take `&Reloadable<RateLimitConfig>` as input and call
+/// [`Reloadable::snapshot`] to get the current value.
+/// The value is updated when [`ConfigReloader::reload`] succeeds.
+///
+/// All clones of a `Reloadable` share the same value.
+pub struct Reloadable<T> {
+    /// The generation of configuration values this `Reloadable` reads from.
+    ///
+    /// It's shared by all the `Reloadable` values tracked by the same [`ConfigReloader`].
+    generation: Arc<ArcSwap<Generation>>,
+    _value: PhantomData<fn() -> Arc<T>>,
+}
+
+impl<T: Send + Sync + 'static> Reloadable<T> {
+    /// Wrap a value.
+    ///
+    /// The new instance isn't tracked by any [`ConfigReloader`]: it won't be updated
+    /// when the configuration is reloaded.
+    pub fn new(value: T) -> Self {
+        let mut generation = Generation::default();
+        generation.insert(value);
+        Self::from_generation(Arc::new(ArcSwap::from_pointee(generation)))
+    }
+
+    /// The current value.
+    ///
+    /// It's cheap to call: it clones an `Arc`, without taking any lock.
+    /// The snapshot isn't affected by later reloads,
+    /// so a request handler sees a consistent value from start to finish.
+    pub fn snapshot(&self) -> Arc<T> {
+        self.generation.load().get::<T>()
+    }
+
+    fn from_generation(generation: Arc<ArcSwap<Generation>>) -> Self {
+        Self {
+            generation,
+            _value: PhantomData,
+        }
+    }
+
+    /// Wrap a freshly deserialized value, tracking it if there is an ongoing [`ConfigReloader`] session.
+    fn tracked(value: T) -> Self {
+        SESSION.with_borrow_mut(|session| match session {
+            Some(session) => session.track(value),
+            None => Self::new(value),
+        })
+    }
+}
+
+impl<T> Clone for Reloadable<T> {
+    fn clone(&self) -> Self {
+        Self {
+            generation: self.generation.clone(),
+            _value: PhantomData,
+        }
+    }
+}
+
+impl<T: fmt::Debug + Send + Sync + 'static> fmt::Debug for Reloadable<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Reloadable").field(&self.snapshot()).finish()
+    }
+}
+
+impl<T> Default for Reloadable<T>
+where
+    T: Default + Send + Sync + 'static,
+{
+    fn default() -> Self {
+        Self::tracked(T::default())
+    }
+}
+
+impl<'de, T> Deserialize<'de> for Reloadable<T>
+where
+    T: Deserialize<'de> + Send + Sync + 'static,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        T::deserialize(deserializer).map(Self::tracked)
+    }
+}
+
+/// The values of all the tracked `Reloadable`s, keyed by the type they wrap.
+///
+/// A reload publishes a new generation as a whole: a value from a previous generation
+/// is never mixed with a value from the next one.
+#[derive(Clone, Default)]
+struct Generation {
+    /// Each entry is an `Arc<T>`, where `T` is the type identified by the key.
+    values: HashMap<TypeId, Arc<dyn Any + Send + Sync>>,
+}
+
+impl Generation {
+    /// Add a value to this generation.
+    ///
+    /// It returns `false` if there already was a value of the same type.
+    fn insert<T: Send + Sync + 'static>(&mut self, value: T) -> bool {
+        self.values
+            .insert(TypeId::of::<T>(), Arc::new(value))
+            .is_none()
+    }
+
+    fn get<T: Send + Sync + 'static>(&self) -> Arc<T> {
+        let value = self
+            .values
+            .get(&TypeId::of::<T>())
+            .cloned()
+            .unwrap_or_else(|| {
+                panic!(
+                    "There is no `{}` value in the current configuration generation",
+                    std::any::type_name::<T>()
+                )
+            });
+        value
+            .downcast::<T>()
+            .expect("A configuration value was stored under the wrong type id")
+    }
+}
+
+/// Reload the configuration of a running application.
+///
+/// Use [`ConfigReloader::load`] instead of [`ConfigLoader::load`] to load the configuration
+/// when the application starts: it keeps track of all the [`Reloadable`] values it contains.
+/// [`ConfigReloader::reload`] can then be used to re-run the loader and update those values.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let reloader = ConfigReloader::new(ConfigLoader::<Profile>::new());
+/// let config: ApplicationConfig = reloader.load()?;
+/// // [...] Build the application state and launch the server.
+/// // Reload the configuration every time the process receives a `SIGHUP` signal.
+/// reloader.reload_on_sighup()?;
+/// ```
+pub struct ConfigReloader<Config> {
+    inner: Arc<Inner<Config>>,
+}
+
+type Validator<Config> = Box<dyn Fn(&Config) -> Result<(), anyhow::Error> + Send + Sync>;
+
+struct Inner<Config> {
+    load: Box<dyn Fn() -> Result<Config, ConfigLoadError> + Send + Sync>,
+    validator: RwLock<Option<Validator<Config>>>,
+    /// The generation read by the `Reloadable` values tracked by [`ConfigReloader::load`].
+    current: Arc<ArcSwap<Generation>>,
+}
+
+impl<Config> Clone for ConfigReloader<Config> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<Config> ConfigReloader<Config>
+where
+    Config: DeserializeOwned + 'static,
+{
+    /// Create a new reloader, using `loader` every time the configuration must be loaded.
+    pub fn new<Profile>(loader: ConfigLoader<Profile>) -> Self
+    where
+        Profile: ConfigProfile + Clone + Send + Sync + 'static,
+    {
+        Self {
+            inner: Arc::new(Inner {
+                load: Box::new(move || loader.clone().load()),
+                validator: RwLock::new(None),
+                current: Arc::new(ArcSwap::from_pointee(Generation::default())),
+            }),
+        }
+    }
+
+    /// Check the new configuration before applying it.
+    ///
+    /// If the validator returns an error, the reload is aborted and all [`Reloadable`] values
+    /// are left unchanged.
+    pub fn validator<F>(self, validator: F) -> Self
+    where
+        F: Fn(&Config) -> Result<(), anyhow::Error> + Send + Sync + 'static,
+    {
+        *self
+            .inner
+            .validator
+            .write()
+            .unwrap_or_else(PoisonError::into_inner) = Some(Box::new(validator));
+        self
+    }
+
+    /// Load the configuration, tracking all the [`Reloadable`] values it contains.
+    ///
+    /// Each type can only be wrapped in [`Reloadable`] once: it's an error
+    /// if two configuration keys share the same `Reloadable<T>` type.
+    pub fn load(&self) -> Result<Config, ConfigLoadError> {
+        let (config, session) = Session::run(self.inner.current.clone(), &self.inner.load);
+        let config = config?;
+        if let Some(type_name) = session.duplicates.first() {
+            return Err(ConfigLoadError(anyhow::anyhow!(
+                "`Reloadable<{type_name}>` is used for more than one configuration key. \
+                Each type can only be wrapped in `Reloadable` once."
+            )));
+        }
+        self.inner.current.store(Arc::new(session.generation));
+        Ok(config)
+    }
+
+    /// Re-run the configuration loader and update all the [`Reloadable`] values
+    /// tracked by [`ConfigReloader::load`].
+    ///
+    /// The new configuration is applied only if it can be loaded successfully and
+    /// it passes [validation](ConfigReloader::validator). Otherwise, the current
+    /// values are left untouched.
+    ///
+    /// All values are published at once, as a single generation: a snapshot taken via
+    /// [`Reloadable::snapshot`] after `reload` returns never contains a value
+    /// from the previous configuration.
+    /// Values that aren't wrapped in [`Reloadable`] are not affected by a reload.
+    ///
+    /// Loading is blocking: prefer [`ConfigReloader::reload_on_sighup`] or
+    /// [`tokio::task::spawn_blocking`] if you're calling it from an async context.
+    pub fn reload(&self) -> Result<(), ConfigReloadError> {
+        // The new configuration reads from its own generation, so that it can be validated
+        // before touching the one seen by the running application.
+        let staged = Arc::new(ArcSwap::from_pointee(Generation::default()));
+        let (config, session) = Session::run(staged.clone(), &self.inner.load);
+        let config = config.map_err(|e| ConfigReloadError(e.into()))?;
+        staged.store(Arc::new(session.generation));
+        if let Some(validator) = &*self
+            .inner
+            .validator
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+        {
+            validator(&config).map_err(|e| {
+                ConfigReloadError(e.context("The new configuration failed validation"))
+            })?;
+        }
+        let staged = staged.load_full();
+        let n_updated = staged.values.len();
+        self.inner.current.rcu(|current| {
+            // Values that are missing from the new configuration (e.g. an optional key
+            // that has been removed) keep their current value.
+            let mut next = Generation::clone(current);
+            next.values
+                .extend(staged.values.iter().map(|(k, v)| (*k, v.clone())));
+            next
+        });
+        tracing::info!(
+            config.reloaded_values = n_updated,
+            "The configuration has been reloaded"
+        );
+        Ok(())
+    }
+
+    /// Reload the configuration every time the process receives a `SIGHUP` signal.
+    ///
+    /// Reload failures are logged and the current values are kept.
+    /// The configuration is loaded on `tokio`'s blocking thread pool, to avoid stalling
+    /// the worker that received the signal.
+    /// It must be called from within a `tokio` runtime.
+    #[cfg(unix)]
+    pub fn reload_on_sighup(&self) -> Result<tokio::task::JoinHandle<()>, std::io::Error>
+    where
+        Config: Send + Sync,
+    {
+        use tokio::signal::unix::{SignalKind, signal};
+        use tracing_log_error::log_error;
+
+        let mut sighup = signal(SignalKind::hangup())?;
+        let reloader = self.clone();
+        Ok(tokio::spawn(async move {
+            while sighup.recv().await.is_some() {
+                tracing::info!("Received SIGHUP, reloading the configuration");
+                let r = reloader.clone();
+                match tokio::task::spawn_blocking(move || r.reload()).await {
+                    Ok(Ok(())) => {}
+                    Ok(Err(e)) => {
+                        log_error!(
+                            e,
+                            "Failed to reload the configuration, keeping the current one"
+                        );
+                    }
+                    Err(e) => {
+                        log_error!(
+                            e,
+                            "The configuration reload task panicked, keeping the current configuration"
+                        );
+                    }
+                }
+            }
+        }))
+    }
+}
+
+thread_local! {
+    /// The ongoing [`ConfigReloader`] session, if any.
+    ///
+    /// Loading is synchronous, so all the `Reloadable` values of a configuration
+    /// are deserialized on the thread that started the session.
+    static SESSION: RefCell<Option<Session>> = const { RefCell::new(None) };
+}
+
+struct Session {
+    /// The generation that the `Reloadable` values created in this session read from.
+    target: Arc<ArcSwap<Generation>>,
+    /// The values deserialized in this session.
+    /// They're published to `target` once loading has completed.
+    generation: Generation,
+    /// The types that were wrapped in `Reloadable` more than once.
+    duplicates: Vec<&'static str>,
+}
+
+impl Session {
+    fn run<Config>(
+        target: Arc<ArcSwap<Generation>>,
+        load: impl FnOnce() -> Result<Config, ConfigLoadError>,
+    ) -> (Result<Config, ConfigLoadError>, Session) {
+        let session = Session {
+            target,
+            generation: Generation::default(),
+            duplicates: Vec::new(),
+        };
+        let previous = SESSION.replace(Some(session));
+        let config = load();
+        let session = SESSION
+            .replace(previous)
+            .expect("The reload session was unset while loading the configuration");
+        (config, session)
+    }
+
+    fn track<T: Send + Sync + 'static>(&mut self, value: T) -> Reloadable<T> {
+        if !self.generation.insert(value) {
+            self.duplicates.push(std::any::type_name::<T>());
+        }
+        Reloadable::from_generation(self.target.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use arc_swap::ArcSwap;
+
+    use super::{Generation, Reloadable, Session};
+
+    #[derive(Debug, Clone, PartialEq, serde::Deserialize)]
+    struct RateLimit {
+        requests_per_minute: u32,
+    }
+
+    #[derive(Debug, Clone, PartialEq, serde::Deserialize)]
+    struct Timeout {
+        seconds: u32,
+    }
+
+    #[derive(Debug, Clone, serde::Deserialize)]
+    struct Config {
+        rate_limit: Reloadable<RateLimit>,
+        timeout: Reloadable<Timeout>,
+    }
+
+    fn parse(json: &str) -> Config {
+        serde_json::from_str(json).unwrap()
+    }
+
+    fn config_json(requests_per_minute: u32, seconds: u32) -> String {
+        format!(
+            r#"{{"rate_limit": {{"requests_per_minute": {requests_per_minute}}}, "timeout": {{"seconds": {seconds}}}}}"#
+        )
+    }
+
+    #[test]
+    fn reloadable_values_deserialize_transparently() {
+        let config = parse(&config_json(10, 5));
+        assert_eq!(config.rate_limit.snapshot().requests_per_minute, 10);
+        assert_eq!(config.timeout.snapshot().seconds, 5);
+    }
+
+    #[test]
+    fn values_are_published_when_the_generation_is_stored() {
+        let current = Arc::new(ArcSwap::from_pointee(Generation::default()));
+        let (config, session) = Session::run(current.clone(), || Ok(parse(&config_json(10, 5))));
+        let config = config.unwrap();
+        current.store(Arc::new(session.generation));
+        let before = config.rate_limit.snapshot();
+
+        let (_, staged) = Session::run(
+            Arc::new(ArcSwap::from_pointee(Generation::default())),
+            || Ok(parse(&config_json(20, 6))),
+        );
+        // Nothing changes until the new generation is published.
+        assert_eq!(config.rate_limit.snapshot().requests_per_minute, 10);
+        assert_eq!(config.timeout.snapshot().seconds, 5);
+
+        current.store(Arc::new(staged.generation));
+        assert_eq!(config.rate_limit.snapshot().requests_per_minute, 20);
+        assert_eq!(config.timeout.snapshot().seconds, 6);
+        // Snapshots taken before the reload are unaffected.
+        assert_eq!(before.requests_per_minute, 10);
+    }
+
+    #[test]
+    fn duplicated_types_are_detected() {
+        #[derive(serde::Deserialize)]
+        struct Config {
+            #[allow(dead_code)]
+            a: Reloadable<RateLimit>,
+            #[allow(dead_code)]
+            b: Reloadable<RateLimit>,
+        }
+
+        let json = r#"{"a": {"requests_per_minute": 1}, "b": {"requests_per_minute": 2}}"#;
+        let target = Arc::new(ArcSwap::from_pointee(Generation::default()));
+        let (_, session) =
+            Session::run(target, || Ok(serde_json::from_str::<Config>(json).unwrap()));
+        assert_eq!(session.duplicates.len(), 1);
+    }
+}
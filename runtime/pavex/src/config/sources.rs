@@ -0,0 +1,352 @@
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::Context;
+use figment::{
+    Metadata, Profile, Provider, Source,
+    value::{Dict, Map, Tag, Value},
+};
+
+pub(super) static ENV_PREFIX: &str = "PX_";
+/// The suffix of the environment variables that point at a secret file.
+pub(super) static FILE_SUFFIX: &str = "_FILE";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+/// One of the sources that [`ConfigLoader`](super::ConfigLoader) merges together
+/// to assemble the configuration of the application.
+///
+/// Check out [`ConfigLoader::sources`](super::ConfigLoader::sources) for more details.
+pub enum ConfigSource {
+    /// A configuration file in the configuration directory.
+    File {
+        /// The path to the file.
+        path: PathBuf,
+        /// The format of the file, inferred from its extension.
+        format: ConfigFormat,
+    },
+    /// A custom provider, registered via [`ConfigLoader::provider`](super::ConfigLoader::provider).
+    Provider {
+        /// The name of the provider, as reported by its metadata.
+        name: String,
+    },
+    /// The `PX_*` entries listed in a `.env` file,
+    /// registered via [`ConfigLoader::dotenv_file`](super::ConfigLoader::dotenv_file).
+    DotEnvFile {
+        /// The path to the `.env` file.
+        path: PathBuf,
+    },
+    /// The `PX_*` environment variables.
+    EnvVars,
+    /// A secret file, referenced by a `PX_*_FILE` environment variable.
+    ///
+    /// Check out [`ConfigLoader::env_file_indirection`](super::ConfigLoader::env_file_indirection)
+    /// for more details.
+    SecretFile {
+        /// The name of the variable that references the secret file.
+        variable: String,
+        /// The path to the secret file.
+        path: PathBuf,
+    },
+}
+
+impl fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigSource::File { path, format } => {
+                write!(f, "{format} file `{}`", path.display())
+            }
+            ConfigSource::Provider { name } => write!(f, "custom provider `{name}`"),
+            ConfigSource::DotEnvFile { path } => write!(f, ".env file `{}`", path.display()),
+            ConfigSource::EnvVars => write!(f, "`{ENV_PREFIX}*` environment variables"),
+            ConfigSource::SecretFile { variable, path } => {
+                write!(
+                    f,
+                    "secret file `{}` referenced by `{variable}`",
+                    path.display()
+                )
+            }
+        }
+    }
+}
+
+impl ConfigSource {
+    /// Whether the values tagged with `metadata` were provided by this source.
+    pub(super) fn provided(&self, metadata: &Metadata) -> bool {
+        let file = match &metadata.source {
+            Some(Source::File(path)) => Some(path.as_path()),
+            _ => None,
+        };
+        match self {
+            ConfigSource::File { path, format } => {
+                file == Some(path.as_path()) && metadata.name == format!("{format} file")
+            }
+            ConfigSource::Provider { name } => metadata.name == name.as_str(),
+            ConfigSource::DotEnvFile { path } => {
+                file == Some(path.as_path()) && metadata.name == DOTENV_FILE
+            }
+            ConfigSource::EnvVars => metadata.name == ENV_VARS,
+            ConfigSource::SecretFile { path, .. } => {
+                file == Some(path.as_path()) && metadata.name == SECRET_FILE
+            }
+        }
+    }
+
+    /// Whether the values provided by this source are set via `PX_*` variables.
+    pub(super) fn is_variable(&self) -> bool {
+        matches!(
+            self,
+            ConfigSource::DotEnvFile { .. }
+                | ConfigSource::EnvVars
+                | ConfigSource::SecretFile { .. }
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+/// The formats supported for configuration files.
+pub enum ConfigFormat {
+    /// [YAML](https://yaml.org/), for files ending in `.yml` or `.yaml`.
+    Yaml,
+    /// [TOML](https://toml.io/), for files ending in `.toml`.
+    Toml,
+    /// [JSON](https://www.json.org/), for files ending in `.json`.
+    Json,
+}
+
+impl ConfigFormat {
+    /// The file extensions associated with each format, in lookup order.
+    pub(super) const EXTENSIONS: [(&'static str, ConfigFormat); 4] = [
+        ("yml", ConfigFormat::Yaml),
+        ("yaml", ConfigFormat::Yaml),
+        ("toml", ConfigFormat::Toml),
+        ("json", ConfigFormat::Json),
+    ];
+}
+
+impl fmt::Display for ConfigFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ConfigFormat::Yaml => "YAML",
+            ConfigFormat::Toml => "TOML",
+            ConfigFormat::Json => "JSON",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Find the configuration file named `{stem}.{extension}` in `dir`, for any of the supported
+/// extensions.
+///
+/// Relative directories are resolved against the current working directory first,
+/// then against each of its ancestors, stopping at the first hit.
+/// It returns `None` if there is no matching file, and an error if there is more
+/// than one.
+pub(super) fn find_file(
+    dir: &Path,
+    stem: &str,
+) -> Result<Option<(PathBuf, ConfigFormat)>, anyhow::Error> {
+    let mut matches = Vec::new();
+    for (extension, format) in ConfigFormat::EXTENSIONS {
+        let candidate = dir.join(format!("{stem}.{extension}"));
+        if let Some(path) = search(&candidate) {
+            matches.push((path, format));
+        }
+    }
+    if matches.len() > 1 {
+        let paths = matches
+            .iter()
+            .map(|(path, _)| format!("- {}", path.display()))
+            .collect::<Vec<_>>()
+            .join("\n");
+        anyhow::bail!(
+            "There are multiple `{stem}` configuration files, one per format. \
+            Keep only one of them:\n{paths}"
+        );
+    }
+    Ok(matches.pop())
+}
+
+/// Mirrors the lookup strategy of [`figment::providers::Data::file`].
+fn search(path: &Path) -> Option<PathBuf> {
+    if path.is_absolute() {
+        return path.is_file().then(|| path.to_owned());
+    }
+    let cwd = std::env::current_dir().ok()?;
+    cwd.ancestors()
+        .map(|dir| dir.join(path))
+        .find(|candidate| candidate.is_file())
+}
+
+/// Read the `PX_*` entries of a `.env` file.
+///
+/// It returns `None` if the file doesn't exist.
+pub(super) fn read_dotenv(path: &Path) -> Result<Option<Vec<(String, String)>>, anyhow::Error> {
+    let iter = match dotenvy::from_path_iter(path) {
+        Ok(iter) => iter,
+        Err(e) if e.not_found() => return Ok(None),
+        Err(e) => {
+            return Err(e).with_context(|| format!("Failed to open `{}`", path.display()));
+        }
+    };
+    let mut entries = Vec::new();
+    for entry in iter {
+        let (name, value) =
+            entry.with_context(|| format!("Failed to parse `{}`", path.display()))?;
+        if strip_prefix(&name).is_some() {
+            entries.push((name, value));
+        }
+    }
+    Ok(Some(entries))
+}
+
+/// Strip the `PX_` prefix from a variable name, ignoring case.
+fn strip_prefix(name: &str) -> Option<&str> {
+    let prefix = name.get(..ENV_PREFIX.len())?;
+    prefix
+        .eq_ignore_ascii_case(ENV_PREFIX)
+        .then(|| &name[ENV_PREFIX.len()..])
+}
+
+/// Strip the `_FILE` suffix from a variable name, ignoring case.
+pub(super) fn strip_file_suffix(name: &str) -> Option<&str> {
+    let split = name.len().checked_sub(FILE_SUFFIX.len())?;
+    let suffix = name.get(split..)?;
+    suffix
+        .eq_ignore_ascii_case(FILE_SUFFIX)
+        .then(|| &name[..split])
+}
+
+/// Convert a `PX_*` variable name into a dotted configuration key,
+/// following the same convention used for environment variables:
+/// `PX_SERVER__PORT` becomes `server.port`.
+///
+/// It returns `None` if the name doesn't map to a valid key.
+pub(super) fn variable_key(name: &str) -> Option<String> {
+    let key = strip_prefix(name)?.replace("__", ".").to_ascii_lowercase();
+    if key.split('.').any(|segment| segment.is_empty()) {
+        return None;
+    }
+    Some(key)
+}
+
+/// A set of configuration values keyed by the name of the variable they were read from.
+///
+/// It is used for the sources that behave like environment variables without being part
+/// of the process environment—`.env` files and secret files.
+pub(super) struct Variables {
+    pub(super) metadata: Metadata,
+    /// `(key, value)` pairs, where `key` is a dotted configuration key.
+    pub(super) values: Vec<(String, Value)>,
+}
+
+/// The metadata name of the values read from `.env` files.
+pub(super) static DOTENV_FILE: &str = ".env file";
+/// The metadata name of the values read from environment variables.
+pub(super) static ENV_VARS: &str = "environment variable";
+/// The metadata name of the values read from secret files.
+pub(super) static SECRET_FILE: &str = "secret file";
+
+/// The metadata for variable-based sources, interpolating keys back
+/// into the names of the variables they were read from—e.g. `PX_SERVER__PORT`.
+pub(super) fn variables_metadata(
+    name: &'static str,
+    path: Option<&Path>,
+    suffix: &'static str,
+) -> Metadata {
+    let mut metadata = Metadata::named(name).interpolater(move |_: &Profile, keys: &[&str]| {
+        let keys: Vec<_> = keys.iter().map(|k| k.to_ascii_uppercase()).collect();
+        format!("{ENV_PREFIX}{}{suffix}", keys.join("__"))
+    });
+    if let Some(path) = path {
+        metadata = metadata.source(Source::File(path.to_owned()));
+    }
+    metadata
+}
+
+impl Provider for Variables {
+    fn metadata(&self) -> Metadata {
+        self.metadata.clone()
+    }
+
+    fn data(&self) -> Result<Map<Profile, Dict>, figment::Error> {
+        // We build the dictionary by hand: values must be untagged,
+        // otherwise `Figment` wouldn't attribute them to this provider.
+        let mut dict = Dict::new();
+        for (key, value) in &self.values {
+            let segments: Vec<_> = key.split('.').collect();
+            insert(&mut dict, &segments, value.clone());
+        }
+        Ok(Profile::Default.collect(dict))
+    }
+}
+
+/// Insert `value` at the nested `path`, creating (or replacing) intermediate tables as needed.
+fn insert(dict: &mut Dict, path: &[&str], value: Value) {
+    let [head, rest @ ..] = path else {
+        return;
+    };
+    if rest.is_empty() {
+        dict.insert((*head).to_owned(), value);
+        return;
+    }
+    let entry = dict
+        .entry((*head).to_owned())
+        .or_insert_with(|| Value::Dict(Tag::Default, Dict::new()));
+    if !matches!(entry, Value::Dict(..)) {
+        *entry = Value::Dict(Tag::Default, Dict::new());
+    }
+    if let Value::Dict(_, nested) = entry {
+        insert(nested, rest, value);
+    }
+}
+
+/// A provider whose metadata has been replaced.
+pub(super) struct WithMetadata<P> {
+    pub(super) provider: P,
+    pub(super) metadata: Metadata,
+}
+
+impl<P: Provider> Provider for WithMetadata<P> {
+    fn metadata(&self) -> Metadata {
+        self.metadata.clone()
+    }
+
+    fn data(&self) -> Result<Map<Profile, Dict>, figment::Error> {
+        self.provider.data()
+    }
+}
+
+#[derive(Clone)]
+/// A provider registered via [`ConfigLoader::provider`](super::ConfigLoader::provider).
+pub(super) struct CustomProvider(pub(super) Arc<dyn Provider + Send + Sync>);
+
+impl CustomProvider {
+    pub(super) fn name(&self) -> String {
+        self.0.metadata().name.into_owned()
+    }
+}
+
+impl Provider for CustomProvider {
+    fn metadata(&self) -> Metadata {
+        self.0.metadata()
+    }
+
+    fn data(&self) -> Result<Map<Profile, Dict>, figment::Error> {
+        self.0.data()
+    }
+
+    fn profile(&self) -> Option<Profile> {
+        self.0.profile()
+    }
+}
+
+impl fmt::Debug for CustomProvider {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("CustomProvider").field(&self.name()).finish()
+    }
+}
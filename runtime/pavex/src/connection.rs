@@ -1,5 +1,7 @@
 //! Extract data concerning the HTTP connection.
 use std::net::SocketAddr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 /// Information relating to the current underlying HTTP connection.
 ///
@@ -12,6 +14,7 @@ use std::net::SocketAddr;
 #[derive(Clone, Debug)]
 pub struct ConnectionInfo {
     pub(crate) peer_addr: SocketAddr,
+    pub(crate) draining: Arc<AtomicBool>,
 }
 
 impl ConnectionInfo {
@@ -35,4 +38,13 @@ impl ConnectionInfo {
     pub fn peer_addr(&self) -> SocketAddr {
         self.peer_addr
     }
+
+    /// Returns `true` if the server has started shutting down.
+    ///
+    /// The server no longer accepts new connections: the current one will be closed
+    /// as soon as in-flight requests have been processed.
+    /// Check out [`ServerHandle::shutdown`](crate::server::ServerHandle::shutdown) for more details.
+    pub fn is_draining(&self) -> bool {
+        self.draining.load(Ordering::Relaxed)
+    }
 }
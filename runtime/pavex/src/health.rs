@@ -0,0 +1,324 @@
+//! Liveness and readiness probes, for orchestrators like Kubernetes.
+//!
+//! # Registration
+//!
+//! Mount [`LIVENESS_PROBE`] and [`READINESS_PROBE`] on your [`Blueprint`](crate::Blueprint),
+//! and register a singleton constructor for [`HealthChecks`]:
+//!
+//! ```rust
+//! use pavex::health::{HealthChecks, LIVENESS_PROBE, READINESS_PROBE};
+//! use pavex::{Blueprint, singleton};
+//! # #[derive(Clone)] pub struct DbPool;
+//! # impl DbPool { async fn ping(&self) -> Result<(), std::io::Error> { Ok(()) } }
+//!
+//! #[singleton]
+//! pub fn health_checks(pool: &DbPool) -> HealthChecks {
+//!     let pool = pool.clone();
+//!     HealthChecks::new().check("database", move || {
+//!         let pool = pool.clone();
+//!         async move { pool.ping().await }
+//!     })
+//! }
+//!
+//! let mut bp = Blueprint::new();
+//! bp.constructor(HEALTH_CHECKS);
+//! bp.route(LIVENESS_PROBE);
+//! bp.route(READINESS_PROBE);
+//! ```
+//!
+//! Checks can capture any singleton from your application state, as shown above
+//! for the database pool.
+//!
+//! # Semantics
+//!
+//! - `GET /healthz` reports whether the process is alive. It doesn't run any check:
+//!   a failing dependency shouldn't cause the orchestrator to restart your application.
+//! - `GET /readyz` reports whether the application can serve traffic.
+//!   It runs all checks concurrently, each with its own timeout, and fails if any of them does.
+//!   It fails without running any check as soon as [`ServerHandle::shutdown`] has been called,
+//!   so that traffic is routed elsewhere while in-flight requests are drained.
+//!
+//! Both return a JSON report, with a `200 OK` status if healthy and a
+//! `503 Service Unavailable` status otherwise:
+//!
+//! ```json
+//! {
+//!   "status": "fail",
+//!   "checks": {
+//!     "database": { "status": "pass", "duration_ms": 1.2 },
+//!     "redis": { "status": "fail", "duration_ms": 500.0, "error": "Timed out after 500ms" }
+//!   }
+//! }
+//! ```
+//!
+//! Use [`Blueprint::prefix`](crate::Blueprint::prefix) to mount the probes under a different path.
+//!
+//! [`ServerHandle::shutdown`]: crate::server::ServerHandle::shutdown
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use futures_util::future::join_all;
+use pavex_macros::get;
+
+use crate::Response;
+use crate::connection::ConnectionInfo;
+use crate::response::body::Json;
+
+type CheckFn =
+    dyn Fn() -> Pin<Box<dyn Future<Output = Result<(), anyhow::Error>> + Send>> + Send + Sync;
+
+#[derive(Clone)]
+/// A registry of the checks performed by [`readiness_probe`].
+///
+/// Check out the [module documentation](self) for more details.
+pub struct HealthChecks {
+    checks: Vec<Check>,
+    default_timeout: Duration,
+}
+
+#[derive(Clone)]
+struct Check {
+    name: String,
+    timeout: Option<Duration>,
+    run: Arc<CheckFn>,
+}
+
+impl std::fmt::Debug for HealthChecks {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let names: Vec<_> = self.checks.iter().map(|c| &c.name).collect();
+        f.debug_struct("HealthChecks")
+            .field("checks", &names)
+            .field("default_timeout", &self.default_timeout)
+            .finish()
+    }
+}
+
+impl Default for HealthChecks {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HealthChecks {
+    /// Create an empty registry.
+    ///
+    /// Checks time out after 2 seconds, unless configured otherwise
+    /// via [`default_timeout`](Self::default_timeout) or
+    /// [`check_with_timeout`](Self::check_with_timeout).
+    pub fn new() -> Self {
+        Self {
+            checks: Vec::new(),
+            default_timeout: Duration::from_secs(2),
+        }
+    }
+
+    /// Set the timeout for checks that don't specify their own.
+    pub fn default_timeout(mut self, timeout: Duration) -> Self {
+        self.default_timeout = timeout;
+        self
+    }
+
+    /// Register a check.
+    ///
+    /// `check` is invoked every time the readiness probe is queried.
+    /// The check passes if the returned future resolves to `Ok(())` before the timeout expires.
+    pub fn check<F, Fut, E>(self, name: impl Into<String>, check: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), E>> + Send + 'static,
+        E: Into<anyhow::Error>,
+    {
+        self.register(name.into(), None, check)
+    }
+
+    /// Register a check with a custom timeout.
+    ///
+    /// Check out [`check`](Self::check) for more details.
+    pub fn check_with_timeout<F, Fut, E>(
+        self,
+        name: impl Into<String>,
+        timeout: Duration,
+        check: F,
+    ) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), E>> + Send + 'static,
+        E: Into<anyhow::Error>,
+    {
+        self.register(name.into(), Some(timeout), check)
+    }
+
+    fn register<F, Fut, E>(mut self, name: String, timeout: Option<Duration>, check: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), E>> + Send + 'static,
+        E: Into<anyhow::Error>,
+    {
+        let run: Arc<CheckFn> = Arc::new(move || {
+            let future = check();
+            Box::pin(async move { future.await.map_err(Into::into) })
+        });
+        self.checks.push(Check { name, timeout, run });
+        self
+    }
+
+    /// Run all checks concurrently and collect their outcomes.
+    pub async fn run(&self) -> HealthReport {
+        let outcomes = join_all(self.checks.iter().map(|check| async move {
+            let timeout = check.timeout.unwrap_or(self.default_timeout);
+            let started_at = Instant::now();
+            let outcome = tokio::time::timeout(timeout, (check.run)()).await;
+            let duration = started_at.elapsed();
+            let error = match outcome {
+                Ok(Ok(())) => None,
+                Ok(Err(e)) => Some(format!("{e:#}")),
+                Err(_) => Some(format!("Timed out after {}ms", timeout.as_millis())),
+            };
+            let report = CheckReport {
+                status: if error.is_none() {
+                    HealthStatus::Pass
+                } else {
+                    HealthStatus::Fail
+                },
+                duration_ms: duration.as_micros() as f64 / 1000.0,
+                error,
+            };
+            (check.name.clone(), report)
+        }))
+        .await;
+        let checks: BTreeMap<_, _> = outcomes.into_iter().collect();
+        let status = if checks.values().all(|c| c.status == HealthStatus::Pass) {
+            HealthStatus::Pass
+        } else {
+            HealthStatus::Fail
+        };
+        HealthReport {
+            status,
+            draining: false,
+            checks,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+/// The outcome of a health check, or of a group of them.
+pub enum HealthStatus {
+    /// Healthy.
+    Pass,
+    /// Unhealthy.
+    Fail,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+#[non_exhaustive]
+/// The aggregated outcome of the checks registered with [`HealthChecks`].
+pub struct HealthReport {
+    /// [`HealthStatus::Pass`] if all checks passed and the server isn't shutting down.
+    pub status: HealthStatus,
+    /// Whether the server is shutting down.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub draining: bool,
+    /// The outcome of each check, keyed by name.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub checks: BTreeMap<String, CheckReport>,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+#[non_exhaustive]
+/// The outcome of a single health check.
+pub struct CheckReport {
+    /// Whether the check passed.
+    pub status: HealthStatus,
+    /// How long the check took, in milliseconds.
+    pub duration_ms: f64,
+    /// Why the check failed, if it did.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl HealthReport {
+    /// Convert the report into a JSON response—`200 OK` if healthy,
+    /// `503 Service Unavailable` otherwise.
+    pub fn into_response(self) -> Response {
+        let response = match self.status {
+            HealthStatus::Pass => Response::ok(),
+            HealthStatus::Fail => Response::service_unavailable(),
+        };
+        match Json::new(&self) {
+            Ok(body) => response.set_typed_body(body),
+            // Serializing a report never fails, but we don't want to panic if it does.
+            Err(_) => response,
+        }
+    }
+}
+
+/// A request handler for liveness probes.
+///
+/// It always succeeds, with a `200 OK` status and a `{"status":"pass"}` JSON body.
+/// Check out the [module documentation](self) for more details.
+#[get(path = "/healthz", pavex = crate)]
+pub fn liveness_probe() -> Response {
+    HealthReport {
+        status: HealthStatus::Pass,
+        draining: false,
+        checks: BTreeMap::new(),
+    }
+    .into_response()
+}
+
+/// A request handler for readiness probes.
+///
+/// It runs the registered [`HealthChecks`] and fails as soon as the server starts
+/// shutting down.
+/// Check out the [module documentation](self) for more details.
+#[get(path = "/readyz", pavex = crate)]
+pub async fn readiness_probe(checks: &HealthChecks, connection_info: &ConnectionInfo) -> Response {
+    if connection_info.is_draining() {
+        return HealthReport {
+            status: HealthStatus::Fail,
+            draining: true,
+            checks: BTreeMap::new(),
+        }
+        .into_response();
+    }
+    checks.run().await.into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn checks_are_aggregated() {
+        let checks = HealthChecks::new()
+            .check("database", || async { Ok::<_, std::io::Error>(()) })
+            .check("cache", || async {
+                Err(anyhow::anyhow!("Connection refused"))
+            })
+            .check_with_timeout("queue", Duration::from_millis(10), || async {
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                Ok::<_, anyhow::Error>(())
+            });
+        let report = checks.run().await;
+        assert_eq!(report.status, HealthStatus::Fail);
+        assert_eq!(report.checks["database"].status, HealthStatus::Pass);
+        assert_eq!(
+            report.checks["cache"].error.as_deref(),
+            Some("Connection refused")
+        );
+        assert_eq!(
+            report.checks["queue"].error.as_deref(),
+            Some("Timed out after 10ms")
+        );
+
+        let report = HealthChecks::new()
+            .check("database", || async { Ok::<_, std::io::Error>(()) })
+            .run()
+            .await;
+        assert_eq!(report.status, HealthStatus::Pass);
+    }
+}
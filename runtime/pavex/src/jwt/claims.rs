@@ -0,0 +1,173 @@
+use crate::request::RequestHead;
+use http::header::AUTHORIZATION;
+use pavex_macros::methods;
+use serde::de::DeserializeOwned;
+
+use super::{
+    JwtVerifier,
+    errors::{ClaimsErrorKind, ExtractClaimsError},
+};
+
+#[derive(Debug)]
+/// The verified claims of the bearer token attached to the incoming request.
+///
+/// The token is extracted from the `Authorization: Bearer <token>` header and
+/// verified by [`JwtVerifier`]. `T` is the type the claims are deserialized into.
+///
+/// # Example
+///
+/// ```rust
+/// use pavex::jwt::Claims;
+/// use pavex::Response;
+///
+/// #[derive(serde::Deserialize)]
+/// pub struct ServiceClaims {
+///     pub sub: String,
+/// }
+///
+/// pub fn handler(claims: &Claims<ServiceClaims>) -> Response {
+///     let Claims(claims) = claims;
+///     Response::ok().set_typed_body(format!("Hello, {}!", claims.sub))
+/// }
+/// ```
+pub struct Claims<T>(pub T);
+
+#[methods]
+impl<T> Claims<T> {
+    /// Extract the bearer token from the `Authorization` header and verify it.
+    ///
+    /// It fails with an [`ExtractClaimsError`] if the header is missing, malformed,
+    /// or if the token doesn't pass verification.
+    #[request_scoped(pavex = crate, id = "CLAIMS_EXTRACT")]
+    pub fn extract(
+        request_head: &RequestHead,
+        verifier: &JwtVerifier,
+    ) -> Result<Self, ExtractClaimsError>
+    where
+        T: DeserializeOwned,
+    {
+        let token = bearer_token(request_head).map_err(|kind| verifier.error(kind))?;
+        verifier.verify(token).map(Claims)
+    }
+}
+
+/// Retrieve the bearer token from the `Authorization` header.
+///
+/// The authentication scheme is case-insensitive, as prescribed by
+/// [RFC 9110](https://datatracker.ietf.org/doc/html/rfc9110#section-11.1).
+fn bearer_token(request_head: &RequestHead) -> Result<&str, ClaimsErrorKind> {
+    let Some(header) = request_head.headers.get(AUTHORIZATION) else {
+        return Err(ClaimsErrorKind::MissingToken);
+    };
+    let header = header
+        .to_str()
+        .map_err(|_| ClaimsErrorKind::MalformedHeader)?;
+    let (scheme, token) = header
+        .split_once(' ')
+        .ok_or(ClaimsErrorKind::MalformedHeader)?;
+    if !scheme.eq_ignore_ascii_case("bearer") {
+        return Err(ClaimsErrorKind::MissingToken);
+    }
+    let token = token.trim();
+    if token.is_empty() {
+        return Err(ClaimsErrorKind::MalformedHeader);
+    }
+    Ok(token)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    use http::header::{AUTHORIZATION, WWW_AUTHENTICATE};
+    use jsonwebtoken::{Algorithm, EncodingKey, Header};
+    use serde_json::{Value, json};
+
+    use super::Claims;
+    use crate::jwt::{JwtConfig, JwtKeyConfig, JwtVerifier, errors::ClaimsErrorKind};
+    use crate::request::RequestHead;
+
+    fn verifier() -> JwtVerifier {
+        JwtVerifier::new(&JwtConfig {
+            keys: vec![JwtKeyConfig::hmac(Algorithm::HS256, "secret")],
+            realm: Some("billing".into()),
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    fn request_head(authorization: Option<&str>) -> RequestHead {
+        let mut headers = http::HeaderMap::new();
+        if let Some(value) = authorization {
+            headers.insert(AUTHORIZATION, value.parse().unwrap());
+        }
+        RequestHead {
+            method: http::Method::GET,
+            target: "/".parse().unwrap(),
+            version: http::Version::HTTP_11,
+            headers,
+        }
+    }
+
+    fn token() -> String {
+        let exp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+            + 60;
+        jsonwebtoken::encode(
+            &Header::new(Algorithm::HS256),
+            &json!({"sub": "search", "exp": exp}),
+            &EncodingKey::from_secret(b"secret"),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn bearer_scheme_is_case_insensitive() {
+        let head = request_head(Some(&format!("bearer {}", token())));
+        let Claims(claims) = Claims::<Value>::extract(&head, &verifier()).unwrap();
+        assert_eq!(claims["sub"], "search");
+    }
+
+    #[test]
+    fn missing_token_gets_a_bare_challenge() {
+        let err = Claims::<Value>::extract(&request_head(None), &verifier()).unwrap_err();
+        assert_eq!(err.kind(), &ClaimsErrorKind::MissingToken);
+
+        let response = err.into_response();
+        assert_eq!(response.status(), http::StatusCode::UNAUTHORIZED);
+        assert_eq!(
+            response.headers()[WWW_AUTHENTICATE],
+            r#"Bearer realm="billing""#
+        );
+    }
+
+    #[test]
+    fn other_schemes_are_treated_as_missing_tokens() {
+        let head = request_head(Some("Basic dXNlcjpwYXNz"));
+        let err = Claims::<Value>::extract(&head, &verifier()).unwrap_err();
+        assert_eq!(err.kind(), &ClaimsErrorKind::MissingToken);
+    }
+
+    #[test]
+    fn invalid_token_gets_an_error_code() {
+        let head = request_head(Some("Bearer not-a-jwt"));
+        let err = Claims::<Value>::extract(&head, &verifier()).unwrap_err();
+        assert_eq!(err.kind(), &ClaimsErrorKind::MalformedToken);
+
+        let response = err.into_response();
+        assert_eq!(response.status(), http::StatusCode::UNAUTHORIZED);
+        assert_eq!(
+            response.headers()[WWW_AUTHENTICATE],
+            r#"Bearer realm="billing", error="invalid_token", error_description="The token is malformed""#
+        );
+    }
+
+    #[test]
+    fn empty_bearer_token_is_malformed() {
+        let head = request_head(Some("Bearer "));
+        let err = Claims::<Value>::extract(&head, &verifier()).unwrap_err();
+        assert_eq!(err.kind(), &ClaimsErrorKind::MalformedHeader);
+    }
+}
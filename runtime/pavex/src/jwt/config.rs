@@ -0,0 +1,192 @@
+use std::{path::PathBuf, time::Duration};
+
+use jsonwebtoken::Algorithm;
+use serde::Deserialize;
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+/// Configure how [`JwtVerifier`](super::JwtVerifier) validates incoming tokens.
+///
+/// # Example
+///
+/// ```yaml
+/// jwt:
+///   keys:
+///     - kid: "2024-05"
+///       algorithm: EdDSA
+///       public_key: |
+///         -----BEGIN PUBLIC KEY-----
+///         MCowBQYDK2VwAyEA[...]
+///         -----END PUBLIC KEY-----
+///   jwks:
+///     path: /etc/my-service/jwks.json
+///   issuers: ["https://auth.example.com"]
+///   audiences: ["billing"]
+///   leeway: 30s
+/// ```
+#[crate::config(key = "jwt", pavex = crate)]
+pub struct JwtConfig {
+    /// The keys used to verify incoming tokens.
+    #[serde(default)]
+    pub keys: Vec<JwtKeyConfig>,
+    /// A local JWKS file holding additional verification keys.
+    ///
+    /// By default, no JWKS file is used.
+    #[serde(default)]
+    pub jwks: Option<JwksFileConfig>,
+    /// The signing algorithms accepted for incoming tokens.
+    ///
+    /// If empty, every algorithm supported by the matching key is accepted.
+    /// By default, it is empty.
+    #[serde(default)]
+    pub algorithms: Vec<Algorithm>,
+    /// The accepted values for the `iss` claim.
+    ///
+    /// If empty, the `iss` claim is not validated.
+    /// By default, it is empty.
+    #[serde(default)]
+    pub issuers: Vec<String>,
+    /// The accepted values for the `aud` claim.
+    ///
+    /// If empty, the `aud` claim is not validated.
+    /// By default, it is empty.
+    #[serde(default)]
+    pub audiences: Vec<String>,
+    /// The clock skew tolerated when validating the `exp` and `nbf` claims.
+    ///
+    /// By default, it is set to 60 seconds.
+    #[serde(deserialize_with = "deserialize_duration", default = "default_leeway")]
+    pub leeway: Duration,
+    /// The `realm` advertised in the `WWW-Authenticate` header of `401 Unauthorized` responses.
+    ///
+    /// By default, no realm is advertised.
+    #[serde(default)]
+    pub realm: Option<String>,
+}
+
+impl Default for JwtConfig {
+    fn default() -> Self {
+        Self {
+            keys: Vec::new(),
+            jwks: None,
+            algorithms: Vec::new(),
+            issuers: Vec::new(),
+            audiences: Vec::new(),
+            leeway: default_leeway(),
+            realm: None,
+        }
+    }
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+/// A key used to verify incoming tokens.
+///
+/// Symmetric algorithms (`HS*`) require a [`secret`](Self::secret), while
+/// asymmetric ones (`RS*`, `PS*`, `ES*` and `EdDSA`) require a PEM-encoded [`public_key`](Self::public_key).
+pub struct JwtKeyConfig {
+    /// The key identifier.
+    ///
+    /// If set, the key is only used to verify tokens with a matching `kid` header.
+    #[serde(default)]
+    pub kid: Option<String>,
+    /// The algorithm the key must be used with.
+    pub algorithm: Algorithm,
+    /// The shared secret, for `HS*` algorithms.
+    #[serde(default)]
+    pub secret: Option<String>,
+    /// The PEM-encoded public key, for asymmetric algorithms.
+    #[serde(default)]
+    pub public_key: Option<String>,
+}
+
+impl JwtKeyConfig {
+    /// A key for symmetric algorithms (`HS*`).
+    pub fn hmac(algorithm: Algorithm, secret: impl Into<String>) -> Self {
+        Self {
+            kid: None,
+            algorithm,
+            secret: Some(secret.into()),
+            public_key: None,
+        }
+    }
+
+    /// A PEM-encoded public key for asymmetric algorithms.
+    pub fn pem(algorithm: Algorithm, public_key: impl Into<String>) -> Self {
+        Self {
+            kid: None,
+            algorithm,
+            secret: None,
+            public_key: Some(public_key.into()),
+        }
+    }
+
+    /// Set the key identifier.
+    pub fn with_kid(mut self, kid: impl Into<String>) -> Self {
+        self.kid = Some(kid.into());
+        self
+    }
+}
+
+impl std::fmt::Debug for JwtKeyConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("JwtKeyConfig")
+            .field("kid", &self.kid)
+            .field("algorithm", &self.algorithm)
+            .field("secret", &self.secret.as_ref().map(|_| "<redacted>"))
+            .field("public_key", &self.public_key)
+            .finish()
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+/// A local [JWKS](https://datatracker.ietf.org/doc/html/rfc7517#section-5) file.
+///
+/// The file is checked for changes periodically, in a background thread, allowing you
+/// to rotate keys without restarting the application.
+/// If the new version of the file can't be loaded, the previous keys are kept.
+pub struct JwksFileConfig {
+    /// The path to the JWKS file.
+    pub path: PathBuf,
+    /// How often the file should be checked for changes.
+    ///
+    /// Intervals shorter than 10 milliseconds are rounded up to 10 milliseconds.
+    /// By default, it is set to 60 seconds.
+    #[serde(
+        deserialize_with = "deserialize_duration",
+        default = "default_reload_interval"
+    )]
+    pub reload_interval: Duration,
+}
+
+impl JwksFileConfig {
+    /// Use the JWKS file at the given path, with the default reload interval.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            reload_interval: default_reload_interval(),
+        }
+    }
+}
+
+fn default_leeway() -> Duration {
+    Duration::from_secs(60)
+}
+
+fn default_reload_interval() -> Duration {
+    Duration::from_secs(60)
+}
+
+fn deserialize_duration<'de, D>(deserializer: D) -> Result<Duration, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let span = crate::time::Span::deserialize(deserializer)?;
+    span.to_duration(crate::time::SpanRelativeTo::days_are_24_hours())
+        .and_then(|d| d.try_into())
+        .map_err(serde::de::Error::custom)
+}
@@ -0,0 +1,136 @@
+//! Errors that can occur while verifying JSON Web Tokens.
+use std::path::PathBuf;
+
+use crate::Response;
+use http::{HeaderValue, header::WWW_AUTHENTICATE};
+use pavex_macros::methods;
+
+#[derive(Debug, thiserror::Error)]
+#[error("{kind}")]
+/// The error returned by [`Claims::extract`] when the request doesn't carry a valid token.
+///
+/// [`Claims::extract`]: super::Claims::extract
+pub struct ExtractClaimsError {
+    kind: ClaimsErrorKind,
+    realm: Option<String>,
+    #[source]
+    source: Option<jsonwebtoken::errors::Error>,
+}
+
+impl ExtractClaimsError {
+    pub(super) fn new(kind: ClaimsErrorKind, realm: Option<String>) -> Self {
+        Self {
+            kind,
+            realm,
+            source: None,
+        }
+    }
+
+    pub(super) fn with_source(mut self, source: jsonwebtoken::errors::Error) -> Self {
+        self.source = Some(source);
+        self
+    }
+
+    /// Why the token was rejected.
+    pub fn kind(&self) -> &ClaimsErrorKind {
+        &self.kind
+    }
+
+    /// The value of the `WWW-Authenticate` header sent alongside the `401 Unauthorized` response.
+    pub fn www_authenticate(&self) -> String {
+        let mut challenge = "Bearer".to_owned();
+        let mut params = Vec::new();
+        if let Some(realm) = &self.realm {
+            params.push(format!("realm=\"{}\"", escape(realm)));
+        }
+        // Requests without credentials shouldn't get an error code, as prescribed by
+        // https://datatracker.ietf.org/doc/html/rfc6750#section-3.1
+        if self.kind != ClaimsErrorKind::MissingToken {
+            params.push("error=\"invalid_token\"".to_owned());
+            params.push(format!("error_description=\"{}\"", self.kind));
+        }
+        if !params.is_empty() {
+            challenge.push(' ');
+            challenge.push_str(&params.join(", "));
+        }
+        challenge
+    }
+}
+
+#[methods]
+impl ExtractClaimsError {
+    /// Convert an [`ExtractClaimsError`] into a `401 Unauthorized` response,
+    /// with a `WWW-Authenticate` header describing the problem.
+    #[error_handler(pavex = crate)]
+    pub fn into_response(&self) -> Response {
+        let response = Response::unauthorized();
+        match HeaderValue::from_str(&self.www_authenticate()) {
+            Ok(challenge) => response.insert_header(WWW_AUTHENTICATE, challenge),
+            Err(_) => response.insert_header(WWW_AUTHENTICATE, HeaderValue::from_static("Bearer")),
+        }
+    }
+}
+
+/// Escape a value to be used as a quoted string in a `WWW-Authenticate` header.
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[non_exhaustive]
+/// The reasons why a bearer token can be rejected.
+pub enum ClaimsErrorKind {
+    #[error("The request doesn't carry a bearer token")]
+    MissingToken,
+    #[error("The `Authorization` header doesn't contain a valid bearer token")]
+    MalformedHeader,
+    #[error("The token is malformed")]
+    MalformedToken,
+    #[error("The token is signed with an algorithm that isn't accepted")]
+    UnsupportedAlgorithm,
+    #[error("No verification key matches the token")]
+    UnknownKey,
+    #[error("The token signature is invalid")]
+    InvalidSignature,
+    #[error("The token has expired")]
+    Expired,
+    #[error("The token is not valid yet")]
+    NotYetValid,
+    #[error("The token was issued by an untrusted issuer")]
+    InvalidIssuer,
+    #[error("The token was issued for a different audience")]
+    InvalidAudience,
+    #[error("The token claims are invalid")]
+    InvalidClaims,
+}
+
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+/// The error returned by [`JwtVerifier::new`] when the configured keys can't be loaded.
+///
+/// [`JwtVerifier::new`]: super::JwtVerifier::new
+pub enum JwtVerifierError {
+    #[error("The key #{index} in the JWT configuration must specify a `{expected}`")]
+    MissingKeyMaterial {
+        index: usize,
+        expected: &'static str,
+    },
+    #[error("The key #{index} in the JWT configuration is invalid")]
+    InvalidKey {
+        index: usize,
+        #[source]
+        source: jsonwebtoken::errors::Error,
+    },
+    #[error("Failed to read the JWKS file at `{path}`")]
+    JwksRead {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("The JWKS file at `{path}` is invalid")]
+    InvalidJwks {
+        path: PathBuf,
+        #[source]
+        source: anyhow::Error,
+    },
+}
@@ -0,0 +1,214 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::{Arc, Weak},
+    time::{Duration, SystemTime},
+};
+
+use arc_swap::ArcSwap;
+use jsonwebtoken::{
+    Algorithm, DecodingKey,
+    jwk::{AlgorithmParameters, Jwk, JwkSet},
+};
+use tracing_log_error::log_error;
+
+use super::{
+    config::{JwksFileConfig, JwtKeyConfig},
+    errors::JwtVerifierError,
+};
+
+/// A key that can be used to verify incoming tokens.
+pub(super) struct VerificationKey {
+    pub(super) kid: Option<String>,
+    /// The algorithms the key can be used with.
+    pub(super) algorithms: Vec<Algorithm>,
+    pub(super) key: DecodingKey,
+}
+
+impl VerificationKey {
+    pub(super) fn from_config(
+        index: usize,
+        config: &JwtKeyConfig,
+    ) -> Result<Self, JwtVerifierError> {
+        let algorithm = config.algorithm;
+        let key =
+            match family(algorithm) {
+                Family::Hmac => {
+                    let secret =
+                        config
+                            .secret
+                            .as_deref()
+                            .ok_or(JwtVerifierError::MissingKeyMaterial {
+                                index,
+                                expected: "secret",
+                            })?;
+                    Ok(DecodingKey::from_secret(secret.as_bytes()))
+                }
+                family => {
+                    let pem = config.public_key.as_deref().ok_or(
+                        JwtVerifierError::MissingKeyMaterial {
+                            index,
+                            expected: "public_key",
+                        },
+                    )?;
+                    match family {
+                        Family::Rsa => DecodingKey::from_rsa_pem(pem.as_bytes()),
+                        Family::Ec => DecodingKey::from_ec_pem(pem.as_bytes()),
+                        Family::Ed => DecodingKey::from_ed_pem(pem.as_bytes()),
+                        Family::Hmac => unreachable!(),
+                    }
+                }
+            }
+            .map_err(|source| JwtVerifierError::InvalidKey { index, source })?;
+        Ok(Self {
+            kid: config.kid.clone(),
+            algorithms: vec![algorithm],
+            key,
+        })
+    }
+
+    fn from_jwk(jwk: &Jwk) -> Result<Self, jsonwebtoken::errors::Error> {
+        let key = DecodingKey::from_jwk(jwk)?;
+        // If the JWK doesn't pin an algorithm, we accept all the algorithms
+        // compatible with its key type.
+        let algorithms = match jwk
+            .common
+            .key_algorithm
+            .and_then(|a| a.to_string().parse::<Algorithm>().ok())
+        {
+            Some(algorithm) => vec![algorithm],
+            None => match &jwk.algorithm {
+                AlgorithmParameters::RSA(_) => Family::Rsa.algorithms(),
+                AlgorithmParameters::EllipticCurve(_) => Family::Ec.algorithms(),
+                AlgorithmParameters::OctetKeyPair(_) => Family::Ed.algorithms(),
+                AlgorithmParameters::OctetKey(_) => Family::Hmac.algorithms(),
+            },
+        };
+        Ok(Self {
+            kid: jwk.common.key_id.clone(),
+            algorithms,
+            key,
+        })
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Family {
+    Hmac,
+    Rsa,
+    Ec,
+    Ed,
+}
+
+impl Family {
+    fn algorithms(self) -> Vec<Algorithm> {
+        use Algorithm::*;
+        match self {
+            Family::Hmac => vec![HS256, HS384, HS512],
+            Family::Rsa => vec![RS256, RS384, RS512, PS256, PS384, PS512],
+            Family::Ec => vec![ES256, ES384],
+            Family::Ed => vec![EdDSA],
+        }
+    }
+}
+
+fn family(algorithm: Algorithm) -> Family {
+    use Algorithm::*;
+    match algorithm {
+        HS256 | HS384 | HS512 => Family::Hmac,
+        RS256 | RS384 | RS512 | PS256 | PS384 | PS512 => Family::Rsa,
+        ES256 | ES384 => Family::Ec,
+        EdDSA => Family::Ed,
+    }
+}
+
+/// The keys stored in a local JWKS file.
+///
+/// A background thread checks the file for changes and swaps in the new keys,
+/// so that retrieving the current set of keys never blocks on the filesystem.
+pub(super) struct JwksFile {
+    keys: Arc<ArcSwap<Vec<VerificationKey>>>,
+}
+
+impl JwksFile {
+    /// Load the keys from the JWKS file and start watching it for changes.
+    ///
+    /// It fails if the file can't be read or parsed.
+    pub(super) fn open(config: &JwksFileConfig) -> Result<Self, JwtVerifierError> {
+        let modified_at = modified_at(&config.path);
+        let keys = Arc::new(ArcSwap::from_pointee(load_jwks(&config.path)?));
+        let watcher = Watcher {
+            path: config.path.clone(),
+            reload_interval: config.reload_interval.max(MIN_RELOAD_INTERVAL),
+            modified_at,
+            keys: Arc::downgrade(&keys),
+        };
+        if let Err(e) = std::thread::Builder::new()
+            .name("pavex-jwks-reload".into())
+            .spawn(move || watcher.run())
+        {
+            log_error!(e, level: tracing::Level::WARN, "Failed to spawn the JWKS reload thread, the JWKS file won't be reloaded");
+        }
+        Ok(Self { keys })
+    }
+
+    /// The current set of keys.
+    pub(super) fn keys(&self) -> Arc<Vec<VerificationKey>> {
+        self.keys.load_full()
+    }
+}
+
+/// The shortest interval between two checks of the JWKS file.
+const MIN_RELOAD_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Reload the JWKS file when it changes, until the owning [`JwksFile`] is dropped.
+struct Watcher {
+    path: PathBuf,
+    reload_interval: Duration,
+    modified_at: Option<SystemTime>,
+    keys: Weak<ArcSwap<Vec<VerificationKey>>>,
+}
+
+impl Watcher {
+    fn run(mut self) {
+        loop {
+            std::thread::sleep(self.reload_interval);
+            let Some(keys) = self.keys.upgrade() else {
+                return;
+            };
+            let modified_at = modified_at(&self.path);
+            if modified_at == self.modified_at {
+                continue;
+            }
+            match load_jwks(&self.path) {
+                Ok(new_keys) => {
+                    tracing::info!(path = %self.path.display(), "Reloaded the JWKS file");
+                    keys.store(Arc::new(new_keys));
+                    self.modified_at = modified_at;
+                }
+                Err(e) => {
+                    log_error!(e, level: tracing::Level::WARN, "Failed to reload the JWKS file, keeping the previous keys");
+                }
+            }
+        }
+    }
+}
+
+fn modified_at(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+fn load_jwks(path: &Path) -> Result<Vec<VerificationKey>, JwtVerifierError> {
+    let contents = std::fs::read(path).map_err(|source| JwtVerifierError::JwksRead {
+        path: path.to_owned(),
+        source,
+    })?;
+    let invalid = |source: anyhow::Error| JwtVerifierError::InvalidJwks {
+        path: path.to_owned(),
+        source,
+    };
+    let set: JwkSet = serde_json::from_slice(&contents).map_err(|e| invalid(e.into()))?;
+    set.keys
+        .iter()
+        .map(|jwk| VerificationKey::from_jwk(jwk).map_err(|e| invalid(e.into())))
+        .collect()
+}
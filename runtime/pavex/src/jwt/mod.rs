@@ -0,0 +1,43 @@
+//! Verify [JSON Web Tokens](https://datatracker.ietf.org/doc/html/rfc7519) carried as bearer tokens.
+//!
+//! # Overview
+//!
+//! Take [`Claims<T>`] as an input in your request handlers to require a valid
+//! `Authorization: Bearer <token>` header:
+//!
+//! ```rust
+//! use pavex::jwt::Claims;
+//! use pavex::Response;
+//!
+//! #[derive(serde::Deserialize)]
+//! pub struct ServiceClaims {
+//!     pub sub: String,
+//!     pub scope: String,
+//! }
+//!
+//! pub fn handler(claims: &Claims<ServiceClaims>) -> Response {
+//!     let Claims(claims) = claims;
+//!     // [...]
+//!     Response::ok()
+//! }
+//! ```
+//!
+//! Tokens are verified by [`JwtVerifier`] against the keys listed in [`JwtConfig`],
+//! either inline or in a local [JWKS](https://datatracker.ietf.org/doc/html/rfc7517#section-5) file.
+//! Requests with a missing or invalid token are rejected with a `401 Unauthorized` response,
+//! carrying a `WWW-Authenticate` header as prescribed by [RFC 6750](https://datatracker.ietf.org/doc/html/rfc6750#section-3).
+//!
+//! # Implementation details
+//!
+//! Verification is performed using the
+//! [`jsonwebtoken@10`](https://docs.rs/jsonwebtoken/10) crate.
+mod claims;
+mod config;
+pub mod errors;
+mod keys;
+mod verifier;
+
+pub use claims::Claims;
+pub use config::{JwksFileConfig, JwtConfig, JwtKeyConfig};
+pub use jsonwebtoken::Algorithm;
+pub use verifier::JwtVerifier;
@@ -24,7 +24,11 @@ pub mod connection;
 #[cfg_attr(docsrs, doc(cfg(feature = "cookie")))]
 pub mod cookie;
 pub mod error;
+pub mod health;
 pub mod http;
+#[cfg(feature = "jwt")]
+#[cfg_attr(docsrs, doc(cfg(feature = "jwt")))]
+pub mod jwt;
 pub mod middleware;
 pub mod request;
 pub mod response;
@@ -9,6 +9,10 @@ use std::future::IntoFuture;
 
 use crate::{IntoResponse, Response};
 
+pub use catch_panic::{CATCH_PANIC, Panic, catch_panic};
+
+mod catch_panic;
+
 /// A handle to trigger the execution of the rest of the request processing pipeline.
 ///
 /// It is used by wrapping middlewares to delegate the processing of the request to the next
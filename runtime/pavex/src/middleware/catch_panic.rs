@@ -0,0 +1,221 @@
+use std::any::Any;
+use std::backtrace::Backtrace;
+use std::cell::{Cell, RefCell};
+use std::future::{Future, IntoFuture};
+use std::panic::{AssertUnwindSafe, catch_unwind};
+use std::pin::Pin;
+use std::sync::Once;
+use std::task::{Context, Poll};
+
+use pavex_macros::{methods, wrap};
+use pin_project_lite::pin_project;
+
+use super::Next;
+use crate::Response;
+
+/// A wrapping middleware that catches panics raised while processing a request.
+///
+/// Without it, a panic in a request handler (or in any of the components it depends on)
+/// tears down the connection: the client sees a reset and no error observer is invoked.
+///
+/// With it, the panic is converted into a [`Panic`] error.
+/// Error observers are invoked, as for any other error, and the registered
+/// error handler is used to build the response—a `500 Internal Server Error`,
+/// by default.
+///
+/// # Registration
+///
+/// Register it as the outermost middleware, right after the one that instruments
+/// the request with its root span, to cover the entire request processing pipeline:
+///
+/// ```rust
+/// use pavex::Blueprint;
+/// use pavex::middleware::CATCH_PANIC;
+///
+/// let mut bp = Blueprint::new();
+/// bp.wrap(CATCH_PANIC);
+/// ```
+///
+/// Use a custom error handler to customize the response:
+///
+/// ```rust
+/// use pavex::{Blueprint, Response, error_handler};
+/// use pavex::middleware::{CATCH_PANIC, Panic};
+///
+/// #[error_handler]
+/// pub fn panic_response(_panic: &Panic) -> Response {
+///     Response::internal_server_error().set_typed_body("Something went very wrong")
+/// }
+///
+/// let mut bp = Blueprint::new();
+/// bp.wrap(CATCH_PANIC).error_handler(PANIC_RESPONSE);
+/// ```
+///
+/// # Backtraces
+///
+/// The backtrace of a panic must be captured by a panic hook, before the stack is unwound.
+/// The first time a request goes through it, `catch_panic` installs a process-wide panic hook
+/// that captures the backtrace of the panics it's going to catch and then delegates to
+/// the hook that was previously installed.
+///
+/// If you call [`std::panic::set_hook`] afterwards, our hook is replaced:
+/// panics are still caught, but [`Panic::backtrace`] returns `None`.
+/// Install your own hook before the server starts, or chain it to the current one via
+/// [`std::panic::take_hook`].
+///
+/// # Limitations
+///
+/// Only unwinding panics can be caught: it has no effect if your application
+/// is compiled with `panic = "abort"`.
+/// Panics raised while streaming the response body, after the middleware
+/// has returned, aren't caught either.
+///
+/// Panics that escape the request processing pipeline (e.g. if `catch_panic` isn't registered)
+/// are caught by the server as a last resort: it returns a `500 Internal Server Error`,
+/// but no error observer is invoked.
+#[wrap(pavex = crate)]
+pub async fn catch_panic<C>(next: Next<C>) -> Result<Response, Panic>
+where
+    C: IntoFuture<Output = Response>,
+{
+    install_backtrace_hook();
+    CatchUnwind {
+        inner: next.into_future(),
+    }
+    .await
+}
+
+#[derive(thiserror::Error)]
+#[error("A panic occurred while processing the request: {message}")]
+/// The error returned by [`catch_panic`] when the request processing pipeline panics.
+///
+/// Its [`Debug`] representation includes the backtrace captured at the panic site,
+/// so error observers that record it—e.g. via `pavex_tracing::fields::error_details`—attach
+/// both the panic payload and the backtrace to the root span.
+pub struct Panic {
+    message: String,
+    backtrace: Option<Backtrace>,
+}
+
+impl Panic {
+    fn new(payload: Box<dyn Any + Send>, backtrace: Option<Backtrace>) -> Self {
+        let message = if let Some(message) = payload.downcast_ref::<&str>() {
+            (*message).to_owned()
+        } else if let Some(message) = payload.downcast_ref::<String>() {
+            message.clone()
+        } else {
+            "Box<dyn Any>".to_owned()
+        };
+        Self { message, backtrace }
+    }
+
+    /// The panic payload, as a string.
+    ///
+    /// It's set to `Box<dyn Any>` if the payload is neither a `&str` nor a `String`,
+    /// mirroring the behavior of Rust's default panic hook.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// The backtrace captured at the panic site.
+    ///
+    /// It's set to `None` if the panic hook installed by [`catch_panic`] has been
+    /// replaced by a later call to [`std::panic::set_hook`].
+    /// See [`catch_panic`'s documentation](catch_panic#backtraces) for more details.
+    pub fn backtrace(&self) -> Option<&Backtrace> {
+        self.backtrace.as_ref()
+    }
+}
+
+impl std::fmt::Debug for Panic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "A panic occurred while processing the request: {}",
+            self.message
+        )?;
+        if let Some(backtrace) = &self.backtrace {
+            write!(f, "\n\nStack backtrace:\n{backtrace}")?;
+        }
+        Ok(())
+    }
+}
+
+#[methods]
+impl Panic {
+    /// Return an opaque `500 Internal Server Error` to the caller.
+    #[error_handler(pavex = crate)]
+    pub fn into_response(&self) -> Response {
+        Response::internal_server_error()
+    }
+}
+
+thread_local! {
+    /// Set while a future wrapped by [`catch_panic`] is being polled on this thread.
+    static CATCHING: Cell<bool> = const { Cell::new(false) };
+    /// The backtrace captured by our panic hook for the last caught panic.
+    static BACKTRACE: RefCell<Option<Backtrace>> = const { RefCell::new(None) };
+}
+
+/// Chain a panic hook that captures a backtrace for the panics we're going to catch.
+///
+/// The backtrace must be captured by the hook: the stack has already been unwound
+/// by the time `catch_unwind` returns.
+///
+/// The hook is global and it's installed lazily, the first time [`catch_panic`] is invoked.
+/// Panic hooks can't be composed after the fact: a later call to [`std::panic::set_hook`]
+/// replaces ours, and [`Panic::backtrace`] returns `None` from then on.
+fn install_backtrace_hook() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            if CATCHING.get() {
+                BACKTRACE.set(Some(Backtrace::force_capture()));
+            }
+            previous(info);
+        }));
+    });
+}
+
+pin_project! {
+    /// Poll the inner future, converting panics into [`Panic`] errors.
+    struct CatchUnwind<F> {
+        #[pin]
+        inner: F,
+    }
+}
+
+impl<F: Future<Output = Response>> Future for CatchUnwind<F> {
+    type Output = Result<Response, Panic>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let inner = self.project().inner;
+        // Nested `catch_panic` middlewares may be polling us.
+        let was_catching = CATCHING.replace(true);
+        let outcome = catch_unwind(AssertUnwindSafe(|| inner.poll(cx)));
+        CATCHING.set(was_catching);
+        match outcome {
+            Ok(poll) => poll.map(Ok),
+            Err(payload) => Poll::Ready(Err(Panic::new(payload, BACKTRACE.take()))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn panics_are_converted_into_errors() {
+        let next = Next::new(async { panic!("Oh no: {}", 42) });
+        let panic = catch_panic(next).await.unwrap_err();
+        assert_eq!(panic.message(), "Oh no: 42");
+        assert!(panic.backtrace().is_some());
+        assert!(format!("{panic:?}").contains("Stack backtrace"));
+
+        let next = Next::new(async { Response::ok() });
+        let response = catch_panic(next).await.unwrap();
+        assert_eq!(response.status(), crate::http::StatusCode::OK);
+    }
+}
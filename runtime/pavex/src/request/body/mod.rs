@@ -3,10 +3,10 @@
 //! Check the [relevant section of the guide](https://pavex.dev/docs/guide/request_data/body/)
 //! for a thorough introduction to Pavex's body extractors.
 pub use buffered_body::BufferedBody;
-pub use json::JsonBody;
+pub use json::{JSON_BODY_EXTRACT, JsonBody};
 pub use limit::BodySizeLimit;
 pub use raw_body::RawIncomingBody;
-pub use url_encoded::UrlEncodedBody;
+pub use url_encoded::{URL_ENCODED_BODY_EXTRACT, UrlEncodedBody};
 
 mod buffered_body;
 pub mod errors;
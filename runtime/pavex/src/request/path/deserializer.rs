@@ -33,7 +33,7 @@ use std::borrow::Cow;
 
 use serde::{
     Deserializer,
-    de::{self, DeserializeSeed, EnumAccess, Error, MapAccess, VariantAccess, Visitor},
+    de::{self, DeserializeSeed, EnumAccess, Error, MapAccess, SeqAccess, VariantAccess, Visitor},
     forward_to_deserialize_any,
 };
 
@@ -52,6 +52,17 @@ macro_rules! unsupported_type {
     };
 }
 
+macro_rules! single_value {
+    ($trait_fn:ident) => {
+        fn $trait_fn<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+        where
+            V: Visitor<'request>,
+        {
+            self.single_value()?.$trait_fn(visitor)
+        }
+    };
+}
+
 pub(super) struct PathDeserializer<'server, 'request, 'de> {
     url_params: &'de [(&'server str, Cow<'request, str>)],
 }
@@ -61,6 +72,46 @@ impl<'server, 'request, 'de> PathDeserializer<'server, 'request, 'de> {
     pub(super) fn new(url_params: &'de [(&'server str, Cow<'request, str>)]) -> Self {
         PathDeserializer { url_params }
     }
+
+    /// Single values (e.g. primitives or unit-only enums) can only be extracted
+    /// if there is exactly one path parameter.
+    fn single_value(&self) -> Result<ValueDeserializer<'request>, PathDeserializationError>
+    where
+        'server: 'request,
+    {
+        match self.url_params {
+            [(key, value)] => Ok(ValueDeserializer {
+                key: Some(Key(key)),
+                value: value.clone(),
+            }),
+            _ => Err(PathDeserializationError::new(
+                ErrorKind::WrongNumberOfParameters {
+                    got: self.url_params.len(),
+                    expected: 1,
+                },
+            )),
+        }
+    }
+
+    /// Tuples (and tuple structs) are matched positionally against the path parameters:
+    /// they must have exactly one element for each path parameter.
+    fn positional<V>(self, len: usize, visitor: V) -> Result<V::Value, PathDeserializationError>
+    where
+        V: Visitor<'request>,
+        'server: 'request,
+    {
+        if len != self.url_params.len() {
+            return Err(PathDeserializationError::new(
+                ErrorKind::WrongNumberOfParameters {
+                    got: self.url_params.len(),
+                    expected: len,
+                },
+            ));
+        }
+        visitor.visit_seq(SeqDeserializer {
+            params: self.url_params,
+        })
+    }
 }
 
 impl<'server, 'request> Deserializer<'request> for PathDeserializer<'server, 'request, '_>
@@ -69,31 +120,32 @@ where
 {
     type Error = PathDeserializationError;
 
-    unsupported_type!(deserialize_bytes);
     unsupported_type!(deserialize_option);
     unsupported_type!(deserialize_identifier);
     unsupported_type!(deserialize_ignored_any);
     unsupported_type!(deserialize_unit);
     unsupported_type!(deserialize_seq);
-    unsupported_type!(deserialize_bool);
-    unsupported_type!(deserialize_i8);
-    unsupported_type!(deserialize_i16);
-    unsupported_type!(deserialize_i32);
-    unsupported_type!(deserialize_i64);
-    unsupported_type!(deserialize_i128);
-    unsupported_type!(deserialize_u8);
-    unsupported_type!(deserialize_u16);
-    unsupported_type!(deserialize_u32);
-    unsupported_type!(deserialize_u64);
-    unsupported_type!(deserialize_u128);
-    unsupported_type!(deserialize_f32);
-    unsupported_type!(deserialize_f64);
-    unsupported_type!(deserialize_string);
-    unsupported_type!(deserialize_byte_buf);
-    unsupported_type!(deserialize_char);
-    unsupported_type!(deserialize_str);
     unsupported_type!(deserialize_any);
 
+    single_value!(deserialize_bool);
+    single_value!(deserialize_i8);
+    single_value!(deserialize_i16);
+    single_value!(deserialize_i32);
+    single_value!(deserialize_i64);
+    single_value!(deserialize_i128);
+    single_value!(deserialize_u8);
+    single_value!(deserialize_u16);
+    single_value!(deserialize_u32);
+    single_value!(deserialize_u64);
+    single_value!(deserialize_u128);
+    single_value!(deserialize_f32);
+    single_value!(deserialize_f64);
+    single_value!(deserialize_string);
+    single_value!(deserialize_byte_buf);
+    single_value!(deserialize_bytes);
+    single_value!(deserialize_char);
+    single_value!(deserialize_str);
+
     fn deserialize_unit_struct<V>(
         self,
         _name: &'static str,
@@ -110,37 +162,32 @@ where
     fn deserialize_newtype_struct<V>(
         self,
         _name: &'static str,
-        _visitor: V,
+        visitor: V,
     ) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'request>,
     {
-        Err(PathDeserializationError::unsupported_type(type_name::<
-            V::Value,
-        >()))
+        // Newtypes are transparent: `UserId(u64)` is extracted as a `u64`.
+        visitor.visit_newtype_struct(self)
     }
 
-    fn deserialize_tuple<V>(self, _len: usize, _visitor: V) -> Result<V::Value, Self::Error>
+    fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'request>,
     {
-        Err(PathDeserializationError::unsupported_type(type_name::<
-            V::Value,
-        >()))
+        self.positional(len, visitor)
     }
 
     fn deserialize_tuple_struct<V>(
         self,
         _name: &'static str,
-        _len: usize,
-        _visitor: V,
+        len: usize,
+        visitor: V,
     ) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'request>,
     {
-        Err(PathDeserializationError::unsupported_type(type_name::<
-            V::Value,
-        >()))
+        self.positional(len, visitor)
     }
 
     fn deserialize_map<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
@@ -170,16 +217,44 @@ where
 
     fn deserialize_enum<V>(
         self,
-        _name: &'static str,
-        _variants: &'static [&'static str],
-        _visitor: V,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
     ) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'request>,
     {
-        Err(PathDeserializationError::unsupported_type(type_name::<
-            V::Value,
-        >()))
+        self.single_value()?
+            .deserialize_enum(name, variants, visitor)
+    }
+}
+
+struct SeqDeserializer<'de, 'request> {
+    params: &'de [(&'request str, Cow<'request, str>)],
+}
+
+impl<'request> SeqAccess<'request> for SeqDeserializer<'_, 'request> {
+    type Error = PathDeserializationError;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
+    where
+        T: DeserializeSeed<'request>,
+    {
+        match self.params.split_first() {
+            Some(((key, value), tail)) => {
+                self.params = tail;
+                seed.deserialize(ValueDeserializer {
+                    key: Some(Key(key)),
+                    value: value.clone(),
+                })
+                .map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.params.len())
     }
 }
 
@@ -655,43 +730,127 @@ mod tests {
     }
 
     macro_rules! check_single_value {
-        ($ty:ty, $value_str:literal) => {
+        ($ty:ty, $value_str:literal, $expected:expr) => {
             #[allow(clippy::bool_assert_comparison)]
             {
                 let raw_params = vec![("value", $value_str)];
                 let url_params = create_url_params(&raw_params);
                 let deserializer = PathDeserializer::new(&url_params);
-                assert!(matches!(
-                    <$ty>::deserialize(deserializer).unwrap_err().kind,
-                    ErrorKind::UnsupportedType { .. }
-                ));
+                assert_eq!(<$ty>::deserialize(deserializer).unwrap(), $expected);
             }
         };
     }
 
     #[test]
-    fn test_parse_errors_for_single_values() {
-        check_single_value!(bool, "true");
-        check_single_value!(bool, "false");
-        check_single_value!(i8, "-123");
-        check_single_value!(i16, "-123");
-        check_single_value!(i32, "-123");
-        check_single_value!(i64, "-123");
-        check_single_value!(i128, "123");
-        check_single_value!(u8, "123");
-        check_single_value!(u16, "123");
-        check_single_value!(u32, "123");
-        check_single_value!(u64, "123");
-        check_single_value!(u128, "123");
-        check_single_value!(f32, "123");
-        check_single_value!(f64, "123");
-        check_single_value!(String, "abc");
-        check_single_value!(String, "one%20two");
-        check_single_value!(&str, "abc");
-        check_single_value!(Cow<'_, str>, "abc");
-        check_single_value!(Cow<'_, str>, "one%20two");
-        check_single_value!(char, "a");
-        check_single_value!(MyEnum, "B");
+    fn test_parse_single_values() {
+        check_single_value!(bool, "true", true);
+        check_single_value!(bool, "false", false);
+        check_single_value!(i8, "-123", -123);
+        check_single_value!(i16, "-123", -123);
+        check_single_value!(i32, "-123", -123);
+        check_single_value!(i64, "-123", -123);
+        check_single_value!(i128, "123", 123);
+        check_single_value!(u8, "123", 123);
+        check_single_value!(u16, "123", 123);
+        check_single_value!(u32, "123", 123);
+        check_single_value!(u64, "123", 123);
+        check_single_value!(u128, "123", 123);
+        check_single_value!(f32, "123", 123.);
+        check_single_value!(f64, "123", 123.);
+        check_single_value!(String, "abc", "abc".to_owned());
+        check_single_value!(String, "one%20two", "one two".to_owned());
+        check_single_value!(&str, "abc", "abc");
+        check_single_value!(Cow<'_, str>, "abc", Cow::Borrowed("abc"));
+        check_single_value!(
+            Cow<'_, str>,
+            "one%20two",
+            Cow::<str>::Owned("one two".into())
+        );
+        check_single_value!(char, "a", 'a');
+        check_single_value!(MyEnum, "B", MyEnum::B);
+        check_single_value!(MyEnum, "c", MyEnum::C);
+    }
+
+    #[test]
+    fn test_parse_newtype() {
+        #[derive(Debug, Deserialize, Eq, PartialEq)]
+        struct UserId(u64);
+
+        #[derive(Debug, Deserialize, Eq, PartialEq)]
+        struct Wrapper(Params);
+
+        #[derive(Debug, Deserialize, Eq, PartialEq)]
+        struct Params {
+            a: u32,
+            b: String,
+        }
+
+        let raw_params = vec![("id", "42")];
+        let url_params = create_url_params(&raw_params);
+        assert_eq!(
+            UserId::deserialize(PathDeserializer::new(&url_params)).unwrap(),
+            UserId(42)
+        );
+
+        let raw_params = vec![("a", "1"), ("b", "abc")];
+        let url_params = create_url_params(&raw_params);
+        assert_eq!(
+            Wrapper::deserialize(PathDeserializer::new(&url_params)).unwrap(),
+            Wrapper(Params {
+                a: 1,
+                b: "abc".into()
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_tuples() {
+        let raw_params = vec![("a", "1"), ("b", "true"), ("c", "abc")];
+        let url_params = create_url_params(&raw_params);
+        assert_eq!(
+            <(i32, bool, String)>::deserialize(PathDeserializer::new(&url_params)).unwrap(),
+            (1, true, "abc".to_owned())
+        );
+
+        #[derive(Debug, Deserialize, Eq, PartialEq)]
+        struct TupleStruct(i32, bool, String);
+        assert_eq!(
+            TupleStruct::deserialize(PathDeserializer::new(&url_params)).unwrap(),
+            TupleStruct(1, true, "abc".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_single_value_requires_exactly_one_parameter() {
+        test_parse_error!(
+            vec![("a", "1"), ("b", "2")],
+            u32,
+            ErrorKind::WrongNumberOfParameters {
+                got: 2,
+                expected: 1
+            }
+        );
+        test_parse_error!(
+            vec![],
+            MyEnum,
+            ErrorKind::WrongNumberOfParameters {
+                got: 0,
+                expected: 1
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_error_in_tuple_element() {
+        test_parse_error!(
+            vec![("a", "1"), ("b", "abc")],
+            (u32, u32),
+            ErrorKind::ParseErrorAtKey {
+                key: "b".to_owned(),
+                value: "abc".to_owned(),
+                expected_type: "u32",
+            }
+        );
     }
 
     #[test]
@@ -738,24 +897,6 @@ mod tests {
                 name: "alloc::vec::Vec<pavex::request::path::deserializer::tests::MyEnum>"
             }
         );
-
-        #[derive(Debug, Deserialize, Eq, PartialEq)]
-        struct TupleStruct(i32, bool, String);
-        test_parse_error!(
-            vec![("a", "1"), ("b", "true"), ("c", "abc")],
-            TupleStruct,
-            ErrorKind::UnsupportedType {
-                name: "pavex::request::path::deserializer::tests::test_unsupported_seq::TupleStruct"
-            }
-        );
-
-        test_parse_error!(
-            vec![("a", "1"), ("b", "true"), ("c", "abc")],
-            (i32, bool, String),
-            ErrorKind::UnsupportedType {
-                name: "(i32, bool, alloc::string::String)"
-            }
-        );
     }
 
     #[test]
@@ -774,7 +915,10 @@ mod tests {
         test_parse_error!(
             vec![("a", "1")],
             (u32, u32),
-            ErrorKind::UnsupportedType { name: "(u32, u32)" }
+            ErrorKind::WrongNumberOfParameters {
+                got: 1,
+                expected: 2
+            }
         );
     }
 
@@ -103,10 +103,10 @@ impl ExtractPathParamsError {
                 // We put the "custom" message variant here as well because it's not clear
                 // whether it's a programmer error or not. We err on the side of safety and
                 // prefer to return a 500 with an opaque error message.
-                ErrorKind::Message(_) | ErrorKind::UnsupportedType { .. } => {
-                    Response::internal_server_error()
-                        .set_typed_body("Something went wrong when trying to process the request")
-                }
+                ErrorKind::Message(_)
+                | ErrorKind::UnsupportedType { .. }
+                | ErrorKind::WrongNumberOfParameters { .. } => Response::internal_server_error()
+                    .set_typed_body("Something went wrong when trying to process the request"),
             },
         }
     }
@@ -192,7 +192,7 @@ pub enum ErrorKind {
         expected_type: &'static str,
     },
 
-    /// Tried to serialize into an unsupported type such as collections, tuples or nested maps.
+    /// Tried to serialize into an unsupported type such as collections or nested maps.
     ///
     /// This error kind is caused by programmer errors and thus gets converted into a `500 Internal
     /// Server Error` response.
@@ -201,6 +201,19 @@ pub enum ErrorKind {
         name: &'static str,
     },
 
+    /// The number of path parameters doesn't match the number of values expected by the
+    /// target type—e.g. a 2-element tuple for a route with 3 path parameters, or a single
+    /// primitive value for a route with 2 path parameters.
+    ///
+    /// This error kind is caused by programmer errors and thus gets converted into a `500 Internal
+    /// Server Error` response.
+    WrongNumberOfParameters {
+        /// The number of path parameters in the matched route.
+        got: usize,
+        /// The number of values expected by the target type.
+        expected: usize,
+    },
+
     /// Catch-all variant for errors that don't fit any other variant.
     Message(String),
 }
@@ -213,8 +226,16 @@ impl std::fmt::Display for ErrorKind {
                 write!(
                     f,
                     "`{name}` is not a supported type for the `PathParams` extractor. \
-                    The type `T` in `Path<T>` must be a struct (with one public field for each \
-                    templated path segment) or a map (e.g. `HashMap<&'a str, Cow<'a, str>>`)."
+                    The type `T` in `PathParams<T>` must be a struct (with one public field for each \
+                    templated path segment), a tuple (with one element for each templated path segment) \
+                    or a single value (e.g. a primitive, a newtype or a unit-only enum) if there is \
+                    exactly one templated path segment."
+                )
+            }
+            ErrorKind::WrongNumberOfParameters { got, expected } => {
+                write!(
+                    f,
+                    "Expected {expected} path parameter(s), but the matched route has {got}"
                 )
             }
             ErrorKind::ParseErrorAtKey {
@@ -37,6 +37,17 @@ use super::RawPathParams;
 ///
 /// `home_id` will be set to `1` for an incoming `/home/1` request.
 /// Extraction will fail, instead, if we receive an `/home/abc` request.
+///
+/// # Supported types
+///
+/// Structs with named fields are the recommended choice for `T`, but they're not the only option:
+///
+/// - If the route has exactly one path parameter, `T` can be a single value—e.g. a primitive
+///   (`PathParams<u64>`), a newtype (`PathParams<UserId>`, where `struct UserId(u64)`)
+///   or an enum with unit variants only.
+/// - `T` can be a tuple or a tuple struct (e.g. `PathParams<(String, u32)>`).
+///   Its elements are matched positionally against the path parameters, in the order they appear
+///   in the route template.
 #[doc(alias = "Path")]
 #[doc(alias = "RouteParams")]
 #[doc(alias = "UrlParams")]
@@ -3,6 +3,8 @@ use std::io::Error;
 use std::marker::PhantomData;
 use std::net::SocketAddr;
 use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::task::Poll;
 use std::thread;
 
@@ -45,6 +47,7 @@ use super::{IncomingStream, ShutdownMode};
 #[derive(Clone)]
 pub struct ServerHandle {
     command_outbox: tokio::sync::mpsc::Sender<ServerCommand>,
+    draining: Arc<AtomicBool>,
 }
 
 impl ServerHandle {
@@ -63,14 +66,30 @@ impl ServerHandle {
         ApplicationState: Clone + Send + Sync + 'static,
     {
         let (command_outbox, command_inbox) = tokio::sync::mpsc::channel(32);
-        let acceptor = Acceptor::new(config, incoming, handler, application_state, command_inbox);
+        let draining = Arc::new(AtomicBool::new(false));
+        let acceptor = Acceptor::new(
+            config,
+            incoming,
+            handler,
+            application_state,
+            command_inbox,
+            draining.clone(),
+        );
         let _ = acceptor.spawn();
-        Self { command_outbox }
+        Self {
+            command_outbox,
+            draining,
+        }
     }
 
     /// Instruct the [`Server`](super::Server) to stop accepting new connections.
+    ///
+    /// From this point onwards, [`ConnectionInfo::is_draining`] returns `true` for
+    /// the requests that are still being processed—e.g. to fail readiness probes
+    /// while in-flight requests are drained.
     #[doc(alias("stop"))]
     pub async fn shutdown(self, mode: ShutdownMode) {
+        self.draining.store(true, Ordering::Relaxed);
         let (completion_notifier, completion) = tokio::sync::oneshot::channel();
         if self
             .command_outbox
@@ -120,6 +139,8 @@ struct Acceptor<HandlerFuture, ApplicationState> {
         ApplicationState,
     ) -> HandlerFuture,
     application_state: ApplicationState,
+    /// Set when a shutdown is initiated, shared with every [`ConnectionInfo`].
+    draining: Arc<AtomicBool>,
     // We use a `fn() -> HandlerFuture` instead of a `HandlerFuture` because we need `Acceptor`
     // to be `Send` and `Sync`. That wouldn't work with `PhantomData<HandlerFuture>`.
     // In the end, we just need to stash the generic type *somewhere*.
@@ -146,6 +167,7 @@ where
         ) -> HandlerFuture,
         application_state: ApplicationState,
         command_inbox: tokio::sync::mpsc::Receiver<ServerCommand>,
+        draining: Arc<AtomicBool>,
     ) -> Self {
         // TODO: make this configurable
         let max_queue_length = 15;
@@ -168,6 +190,7 @@ where
             handler_output_future: Default::default(),
             next_worker: 0,
             application_state,
+            draining,
         }
     }
 
@@ -220,6 +243,7 @@ where
             max_queue_length,
             handler,
             application_state,
+            draining,
             handler_output_future: _,
         } = self;
 
@@ -284,6 +308,7 @@ where
                     let mut connection_message = ConnectionMessage {
                         connection,
                         peer_addr: remote_peer,
+                        draining: draining.clone(),
                     };
                     for _ in 0..n_workers {
                         // Track if the worker has crashed.
@@ -1,9 +1,13 @@
 use std::future::{Future, poll_fn};
 use std::net::SocketAddr;
+use std::panic::AssertUnwindSafe;
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
 use std::task::Poll;
 use std::thread;
 
 use anyhow::Context;
+use futures_util::FutureExt;
 use hyper_util::rt::TokioIo;
 use hyper_util::server::graceful::GracefulShutdown;
 use tokio::net::TcpStream;
@@ -16,6 +20,7 @@ use crate::server::ShutdownMode;
 pub(super) struct ConnectionMessage {
     pub(super) connection: TcpStream,
     pub(super) peer_addr: SocketAddr,
+    pub(super) draining: Arc<AtomicBool>,
 }
 
 /// A handle to dispatch incoming connections to a worker thread.
@@ -217,14 +222,31 @@ where
         let ConnectionMessage {
             connection,
             peer_addr,
+            draining,
         } = connection_message;
         // A tiny bit of glue to adapt our handler to hyper's service interface.
         let handler = hyper::service::service_fn(move |request| {
             let state = application_state.clone();
+            let connection_info = ConnectionInfo {
+                peer_addr,
+                draining: draining.clone(),
+            };
 
             async move {
-                let handler = (handler)(request, Some(ConnectionInfo { peer_addr }), state);
-                let response = handler.await;
+                let handler = (handler)(request, Some(connection_info), state);
+                // A safety net for panics that escape the request processing pipeline—e.g.
+                // if `CATCH_PANIC` isn't registered or the panic is raised outside of it.
+                // Without it, the connection would be torn down without a response.
+                let response = match AssertUnwindSafe(handler).catch_unwind().await {
+                    Ok(response) => response,
+                    Err(_) => {
+                        tracing::error!(
+                            "A panic occurred while processing the request. \
+                            Returning a `500 Internal Server Error`"
+                        );
+                        crate::Response::internal_server_error()
+                    }
+                };
                 let response = hyper::Response::from(response);
                 Ok::<_, hyper::Error>(response)
             }
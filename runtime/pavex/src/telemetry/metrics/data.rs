@@ -0,0 +1,67 @@
+/// A metric collected by [`HttpMetrics`](super::HttpMetrics), alongside its data points.
+///
+/// It follows [OpenTelemetry's metrics data model](https://opentelemetry.io/docs/specs/otel/metrics/data-model/).
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct Metric {
+    /// The name of the metric—e.g. `http.server.request.duration`.
+    pub name: &'static str,
+    /// A human-readable description of the metric.
+    pub description: &'static str,
+    /// The unit of measurement, in [UCUM](https://ucum.org/) notation—e.g. `s` or `By`.
+    pub unit: &'static str,
+    /// The data points, one for each distinct set of attributes.
+    pub data: MetricData,
+}
+
+/// The data points of a [`Metric`].
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum MetricData {
+    /// The distribution of the recorded values.
+    Histogram(Vec<HistogramDataPoint>),
+    /// A value that can go up and down over time—e.g. the number of in-flight requests.
+    UpDownCounter(Vec<UpDownCounterDataPoint>),
+}
+
+/// The distribution of the values recorded for a set of attributes.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct HistogramDataPoint {
+    /// The attributes shared by all the recorded values, sorted by name.
+    pub attributes: Vec<(&'static str, String)>,
+    /// The upper bounds of the buckets, in ascending order.
+    pub bounds: Vec<f64>,
+    /// The number of values in each bucket.
+    ///
+    /// It has one more element than [`bounds`](Self::bounds): the last bucket holds
+    /// the values greater than the last bound.
+    /// Each bucket is inclusive of its upper bound.
+    pub bucket_counts: Vec<u64>,
+    /// The number of recorded values.
+    pub count: u64,
+    /// The sum of the recorded values.
+    pub sum: f64,
+}
+
+/// The current value of an up-down counter for a set of attributes.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct UpDownCounterDataPoint {
+    /// The attributes of the counter, sorted by name.
+    pub attributes: Vec<(&'static str, String)>,
+    /// The current value.
+    pub value: i64,
+}
+
+/// A destination for the metrics collected by [`HttpMetrics`](super::HttpMetrics)—e.g.
+/// an OTLP collector or a StatsD agent.
+///
+/// Call [`HttpMetrics::export`](super::HttpMetrics::export) to push the current
+/// values to an exporter—e.g. on a timer.
+/// Check out [`PROMETHEUS_METRICS`](super::PROMETHEUS_METRICS) if you'd rather
+/// have Prometheus pull them.
+pub trait MetricsExporter {
+    /// Export a snapshot of the collected metrics.
+    fn export(&self, metrics: &[Metric]) -> Result<(), anyhow::Error>;
+}
@@ -0,0 +1,531 @@
+use std::collections::BTreeMap;
+use std::future::IntoFuture;
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicBool, AtomicI64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Instant;
+
+use http_body::Body;
+use pavex_macros::{methods, wrap};
+
+use crate::Response;
+use crate::http::header::CONTENT_LENGTH;
+use crate::http::{Method, Version};
+use crate::middleware::Next;
+use crate::request::RequestHead;
+use crate::request::path::MatchedPathPattern;
+
+use super::{HistogramDataPoint, Metric, MetricData, MetricsExporter, UpDownCounterDataPoint};
+
+/// The bucket boundaries recommended by OpenTelemetry for `http.server.request.duration`,
+/// in seconds.
+const DURATION_BUCKETS: [f64; 14] = [
+    0.005, 0.01, 0.025, 0.05, 0.075, 0.1, 0.25, 0.5, 0.75, 1.0, 2.5, 5.0, 7.5, 10.0,
+];
+
+/// The default bucket boundaries for body sizes, in bytes.
+const BODY_SIZE_BUCKETS: [f64; 7] = [
+    0.0,
+    100.0,
+    1_000.0,
+    10_000.0,
+    100_000.0,
+    1_000_000.0,
+    10_000_000.0,
+];
+
+/// HTTP server metrics, following
+/// [OpenTelemetry's semantic conventions](https://opentelemetry.io/docs/specs/semconv/http/http-metrics/).
+///
+/// It collects:
+///
+/// - `http.server.request.duration`, a histogram of the time it took to produce a response, in seconds
+/// - `http.server.active_requests`, the number of requests that are currently being processed
+/// - `http.server.request.body.size`, a histogram of request body sizes, in bytes
+/// - `http.server.response.body.size`, a histogram of response body sizes, in bytes
+///
+/// Requests are labeled with their [`MatchedPathPattern`] (`http.route`) rather than
+/// their raw path, to keep the cardinality of the metrics under control.
+/// Body sizes are only recorded when they are known upfront—i.e. via `Content-Length`
+/// for requests or via an exact size hint for responses.
+///
+/// # Registration
+///
+/// `HttpMetrics` is a singleton. Register [`RECORD_HTTP_METRICS`] as a wrapping middleware
+/// to populate it:
+///
+/// ```rust
+/// use pavex::Blueprint;
+/// use pavex::telemetry::metrics::{HTTP_METRICS_NEW, PROMETHEUS_METRICS, RECORD_HTTP_METRICS};
+///
+/// let mut bp = Blueprint::new();
+/// bp.constructor(HTTP_METRICS_NEW);
+/// bp.wrap(RECORD_HTTP_METRICS);
+/// // Expose the metrics to Prometheus on `GET /metrics`.
+/// bp.route(PROMETHEUS_METRICS);
+/// ```
+///
+/// Register it as early as possible, to account for the time spent in the other middlewares.
+///
+/// [`RECORD_HTTP_METRICS`]: super::RECORD_HTTP_METRICS
+#[derive(Debug, Clone)]
+pub struct HttpMetrics {
+    inner: Arc<Inner>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    duration_buckets: Vec<f64>,
+    body_size_buckets: Vec<f64>,
+    /// The histograms are split into shards, one per worker thread (modulo the shard count),
+    /// to avoid contention between concurrent requests.
+    /// They're merged back together when the metrics are collected.
+    shards: Box<[Mutex<Histograms>]>,
+    /// One counter for each of the values returned by [`method`], in the same order
+    /// as [`METHODS`].
+    active_requests: [ActiveRequests; METHODS.len()],
+}
+
+#[derive(Debug, Default, Clone)]
+struct Histograms {
+    duration: BTreeMap<Attributes, Histogram>,
+    request_body_size: BTreeMap<Attributes, Histogram>,
+    response_body_size: BTreeMap<Attributes, Histogram>,
+}
+
+#[derive(Debug, Default)]
+struct ActiveRequests {
+    value: AtomicI64,
+    /// Set when the first request with this method comes in.
+    /// Methods that were never seen are not reported.
+    seen: AtomicBool,
+}
+
+/// The attributes attached to the measurements for a completed request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct Attributes {
+    method: &'static str,
+    route: &'static str,
+    status_code: u16,
+    protocol_version: &'static str,
+}
+
+impl Attributes {
+    /// The attributes as key-value pairs, sorted by key.
+    fn pairs(&self) -> Vec<(&'static str, String)> {
+        let mut pairs = Vec::with_capacity(6);
+        // As prescribed by the semantic conventions, we only set `error.type`
+        // for server errors.
+        if self.status_code >= 500 {
+            pairs.push(("error.type", self.status_code.to_string()));
+        }
+        pairs.extend([
+            ("http.request.method", self.method.to_owned()),
+            ("http.response.status_code", self.status_code.to_string()),
+            ("http.route", self.route.to_owned()),
+            ("network.protocol.name", "http".to_owned()),
+            ("network.protocol.version", self.protocol_version.to_owned()),
+        ]);
+        pairs
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Histogram {
+    bucket_counts: Vec<u64>,
+    count: u64,
+    sum: f64,
+}
+
+impl Histogram {
+    fn new(bounds: &[f64]) -> Self {
+        Self {
+            bucket_counts: vec![0; bounds.len() + 1],
+            count: 0,
+            sum: 0.0,
+        }
+    }
+
+    fn record(&mut self, bounds: &[f64], value: f64) {
+        let bucket = bounds.partition_point(|bound| *bound < value);
+        self.bucket_counts[bucket] += 1;
+        self.count += 1;
+        self.sum += value;
+    }
+
+    fn merge(&mut self, other: &Histogram) {
+        for (count, other) in self.bucket_counts.iter_mut().zip(&other.bucket_counts) {
+            *count += other;
+        }
+        self.count += other.count;
+        self.sum += other.sum;
+    }
+}
+
+impl Histograms {
+    fn merge(&mut self, other: &Histograms) {
+        for (histograms, others) in [
+            (&mut self.duration, &other.duration),
+            (&mut self.request_body_size, &other.request_body_size),
+            (&mut self.response_body_size, &other.response_body_size),
+        ] {
+            for (attributes, other) in others {
+                histograms
+                    .entry(*attributes)
+                    .and_modify(|h| h.merge(other))
+                    .or_insert_with(|| other.clone());
+            }
+        }
+    }
+}
+
+#[methods]
+impl HttpMetrics {
+    /// Create a new, empty, set of HTTP server metrics.
+    ///
+    /// It uses the bucket boundaries recommended by OpenTelemetry for request durations.
+    #[singleton(pavex = crate)]
+    pub fn new() -> Self {
+        Self::with_buckets(DURATION_BUCKETS.to_vec(), BODY_SIZE_BUCKETS.to_vec())
+    }
+
+    /// Create a new, empty, set of HTTP server metrics with custom bucket boundaries
+    /// for request durations (in seconds) and body sizes (in bytes).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the boundaries are not sorted in ascending order.
+    pub fn with_buckets(duration_buckets: Vec<f64>, body_size_buckets: Vec<f64>) -> Self {
+        for buckets in [&duration_buckets, &body_size_buckets] {
+            assert!(
+                buckets.windows(2).all(|w| w[0] < w[1]),
+                "Histogram bucket boundaries must be sorted in ascending order"
+            );
+        }
+        let n_shards = std::thread::available_parallelism().map_or(1, NonZeroUsize::get);
+        Self {
+            inner: Arc::new(Inner {
+                duration_buckets,
+                body_size_buckets,
+                shards: (0..n_shards).map(|_| Mutex::default()).collect(),
+                active_requests: Default::default(),
+            }),
+        }
+    }
+
+    /// Take a snapshot of the metrics collected so far.
+    pub fn collect(&self) -> Vec<Metric> {
+        let mut state = Histograms::default();
+        for shard in &self.inner.shards {
+            state.merge(&lock(shard));
+        }
+        let histogram = |bounds: &[f64], histograms: &BTreeMap<Attributes, Histogram>| {
+            let points = histograms
+                .iter()
+                .map(|(attributes, h)| HistogramDataPoint {
+                    attributes: attributes.pairs(),
+                    bounds: bounds.to_vec(),
+                    bucket_counts: h.bucket_counts.clone(),
+                    count: h.count,
+                    sum: h.sum,
+                })
+                .collect();
+            MetricData::Histogram(points)
+        };
+        let active_requests = METHODS
+            .iter()
+            .zip(&self.inner.active_requests)
+            .filter(|(_, counter)| counter.seen.load(Ordering::Relaxed))
+            .map(|(method, counter)| UpDownCounterDataPoint {
+                attributes: vec![("http.request.method", (*method).to_owned())],
+                value: counter.value.load(Ordering::Relaxed),
+            })
+            .collect();
+        let Inner {
+            duration_buckets,
+            body_size_buckets,
+            ..
+        } = &*self.inner;
+        vec![
+            Metric {
+                name: "http.server.request.duration",
+                description: "Duration of HTTP server requests.",
+                unit: "s",
+                data: histogram(duration_buckets, &state.duration),
+            },
+            Metric {
+                name: "http.server.active_requests",
+                description: "Number of active HTTP server requests.",
+                unit: "{request}",
+                data: MetricData::UpDownCounter(active_requests),
+            },
+            Metric {
+                name: "http.server.request.body.size",
+                description: "Size of HTTP server request bodies.",
+                unit: "By",
+                data: histogram(body_size_buckets, &state.request_body_size),
+            },
+            Metric {
+                name: "http.server.response.body.size",
+                description: "Size of HTTP server response bodies.",
+                unit: "By",
+                data: histogram(body_size_buckets, &state.response_body_size),
+            },
+        ]
+    }
+
+    /// Push a snapshot of the metrics collected so far to `exporter`.
+    pub fn export(&self, exporter: &dyn MetricsExporter) -> Result<(), anyhow::Error> {
+        exporter.export(&self.collect())
+    }
+
+    fn active_requests(&self, method: &'static str) -> &ActiveRequests {
+        let index = METHODS
+            .iter()
+            .position(|m| *m == method)
+            .unwrap_or(METHODS.len() - 1);
+        &self.inner.active_requests[index]
+    }
+
+    fn request_started(&self, method: &'static str) -> ActiveRequest<'_> {
+        let counter = self.active_requests(method);
+        counter.seen.store(true, Ordering::Relaxed);
+        counter.value.fetch_add(1, Ordering::Relaxed);
+        ActiveRequest { counter }
+    }
+
+    fn request_completed(
+        &self,
+        attributes: Attributes,
+        duration: f64,
+        request_body_size: Option<u64>,
+        response_body_size: Option<u64>,
+    ) {
+        let Inner {
+            duration_buckets,
+            body_size_buckets,
+            ..
+        } = &*self.inner;
+        let mut guard = lock(&self.inner.shards[shard_index(self.inner.shards.len())]);
+        let state = &mut *guard;
+        state
+            .duration
+            .entry(attributes)
+            .or_insert_with(|| Histogram::new(duration_buckets))
+            .record(duration_buckets, duration);
+        for (histograms, size) in [
+            (&mut state.request_body_size, request_body_size),
+            (&mut state.response_body_size, response_body_size),
+        ] {
+            if let Some(size) = size {
+                histograms
+                    .entry(attributes)
+                    .or_insert_with(|| Histogram::new(body_size_buckets))
+                    .record(body_size_buckets, size as f64);
+            }
+        }
+    }
+}
+
+impl Default for HttpMetrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Decrements `http.server.active_requests` when dropped, even if the request
+/// is cancelled before completion.
+struct ActiveRequest<'a> {
+    counter: &'a ActiveRequests,
+}
+
+impl Drop for ActiveRequest<'_> {
+    fn drop(&mut self) {
+        self.counter.value.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+fn lock(shard: &Mutex<Histograms>) -> MutexGuard<'_, Histograms> {
+    // The histograms are always left consistent, even if another thread panicked
+    // while holding the lock.
+    shard
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// The index of the shard used by the current thread.
+///
+/// Threads are assigned to shards in a round-robin fashion, the first time they record
+/// a measurement.
+fn shard_index(n_shards: usize) -> usize {
+    static NEXT: AtomicUsize = AtomicUsize::new(0);
+    thread_local! {
+        static INDEX: usize = NEXT.fetch_add(1, Ordering::Relaxed);
+    }
+    INDEX.with(|index| index % n_shards)
+}
+
+/// A wrapping middleware to record [`HttpMetrics`] for every incoming request.
+///
+/// The request duration covers the time it took to produce the response, including
+/// the middlewares that execute after this one. It doesn't include the time spent
+/// streaming the response body to the client.
+///
+/// # Registration
+///
+/// Use [`Blueprint::wrap`] to register `record_http_metrics` as a middleware:
+///
+/// ```rust
+/// use pavex::Blueprint;
+/// use pavex::telemetry::metrics::{HTTP_METRICS_NEW, RECORD_HTTP_METRICS};
+///
+/// let mut bp = Blueprint::new();
+/// bp.constructor(HTTP_METRICS_NEW);
+/// bp.wrap(RECORD_HTTP_METRICS);
+/// ```
+///
+/// [`Blueprint::wrap`]: crate::Blueprint::wrap
+#[wrap(pavex = crate)]
+pub async fn record_http_metrics<C>(
+    next: Next<C>,
+    metrics: &HttpMetrics,
+    request_head: &RequestHead,
+    matched_path_pattern: MatchedPathPattern,
+) -> Response
+where
+    C: IntoFuture<Output = Response>,
+{
+    let method = method(&request_head.method);
+    let request_body_size = request_head
+        .headers
+        .get(CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse().ok());
+    let started_at = Instant::now();
+    let active = metrics.request_started(method);
+
+    let response = next.await;
+
+    let attributes = Attributes {
+        method,
+        route: matched_path_pattern.inner(),
+        status_code: response.status().as_u16(),
+        protocol_version: protocol_version(request_head.version),
+    };
+    metrics.request_completed(
+        attributes,
+        started_at.elapsed().as_secs_f64(),
+        request_body_size,
+        response.body().size_hint().exact(),
+    );
+    drop(active);
+    response
+}
+
+/// All the values returned by [`method`], sorted alphabetically.
+const METHODS: [&str; 10] = [
+    "CONNECT", "DELETE", "GET", "HEAD", "OPTIONS", "PATCH", "POST", "PUT", "TRACE", "_OTHER",
+];
+
+/// The canonical representation of the HTTP method, according to OpenTelemetry's
+/// semantic conventions. Non-standard methods are grouped under `_OTHER`.
+fn method(method: &Method) -> &'static str {
+    match *method {
+        Method::GET => "GET",
+        Method::POST => "POST",
+        Method::PUT => "PUT",
+        Method::TRACE => "TRACE",
+        Method::PATCH => "PATCH",
+        Method::CONNECT => "CONNECT",
+        Method::HEAD => "HEAD",
+        Method::DELETE => "DELETE",
+        Method::OPTIONS => "OPTIONS",
+        _ => "_OTHER",
+    }
+}
+
+fn protocol_version(version: Version) -> &'static str {
+    match version {
+        Version::HTTP_09 => "0.9",
+        Version::HTTP_10 => "1.0",
+        Version::HTTP_11 => "1.1",
+        Version::HTTP_2 => "2",
+        Version::HTTP_3 => "3",
+        _ => "_OTHER",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn values_are_recorded_in_the_right_bucket() {
+        let metrics = HttpMetrics::with_buckets(vec![0.1, 1.0], vec![10.0]);
+        let attributes = Attributes {
+            method: "GET",
+            route: "/home/{id}",
+            status_code: 503,
+            protocol_version: "1.1",
+        };
+        // Buckets are inclusive of their upper bound.
+        metrics.request_completed(attributes, 0.1, Some(10), None);
+        metrics.request_completed(attributes, 0.5, Some(11), None);
+        metrics.request_completed(attributes, 2.0, None, None);
+
+        let collected = metrics.collect();
+        let MetricData::Histogram(points) = &collected[0].data else {
+            panic!("Expected a histogram");
+        };
+        assert_eq!(points.len(), 1);
+        assert_eq!(points[0].bucket_counts, vec![1, 1, 1]);
+        assert_eq!(points[0].count, 3);
+        assert_eq!(points[0].attributes[0], ("error.type", "503".to_owned()));
+
+        let MetricData::Histogram(points) = &collected[2].data else {
+            panic!("Expected a histogram");
+        };
+        assert_eq!(points[0].bucket_counts, vec![1, 1]);
+        assert_eq!(points[0].sum, 21.0);
+        // No response body size was known.
+        assert_eq!(collected[3].data, MetricData::Histogram(vec![]));
+    }
+
+    #[test]
+    fn active_requests_are_decremented_on_drop() {
+        let metrics = HttpMetrics::new();
+        let active = metrics.request_started("POST");
+        let value = |metrics: &HttpMetrics| {
+            let MetricData::UpDownCounter(points) = &metrics.collect()[1].data else {
+                panic!("Expected an up-down counter");
+            };
+            points[0].value
+        };
+        assert_eq!(value(&metrics), 1);
+        drop(active);
+        assert_eq!(value(&metrics), 0);
+    }
+
+    #[test]
+    fn measurements_from_different_threads_are_merged() {
+        let metrics = HttpMetrics::with_buckets(vec![1.0], vec![10.0]);
+        let attributes = Attributes {
+            method: "GET",
+            route: "/",
+            status_code: 200,
+            protocol_version: "1.1",
+        };
+        std::thread::scope(|s| {
+            for _ in 0..4 {
+                s.spawn(|| metrics.request_completed(attributes, 0.5, None, None));
+            }
+        });
+
+        let MetricData::Histogram(points) = &metrics.collect()[0].data else {
+            panic!("Expected a histogram");
+        };
+        assert_eq!(points.len(), 1);
+        assert_eq!(points[0].bucket_counts, vec![4, 0]);
+        assert_eq!(points[0].sum, 2.0);
+    }
+}
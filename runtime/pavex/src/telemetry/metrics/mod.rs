@@ -0,0 +1,30 @@
+//! HTTP server metrics, following [OpenTelemetry's semantic conventions](https://opentelemetry.io/docs/specs/semconv/http/http-metrics/).
+//!
+//! # Overview
+//!
+//! [`HttpMetrics`] collects request durations, active requests and body sizes,
+//! populated by the [`RECORD_HTTP_METRICS`] middleware.
+//!
+//! ```rust
+//! use pavex::Blueprint;
+//! use pavex::telemetry::metrics::{HTTP_METRICS_NEW, PROMETHEUS_METRICS, RECORD_HTTP_METRICS};
+//!
+//! let mut bp = Blueprint::new();
+//! bp.constructor(HTTP_METRICS_NEW);
+//! bp.wrap(RECORD_HTTP_METRICS);
+//! bp.route(PROMETHEUS_METRICS);
+//! ```
+//!
+//! # Exporting
+//!
+//! There are two ways to get the metrics out of your application:
+//!
+//! - Mount [`PROMETHEUS_METRICS`] to let Prometheus scrape them
+//! - Implement [`MetricsExporter`] to push them to another backend, via [`HttpMetrics::export`]
+mod data;
+mod http_metrics;
+mod prometheus;
+
+pub use data::{HistogramDataPoint, Metric, MetricData, MetricsExporter, UpDownCounterDataPoint};
+pub use http_metrics::{HTTP_METRICS_NEW, HttpMetrics, RECORD_HTTP_METRICS, record_http_metrics};
+pub use prometheus::{PROMETHEUS_METRICS, encode_prometheus, prometheus_metrics};
@@ -0,0 +1,179 @@
+use std::fmt::Write;
+
+use pavex_macros::get;
+
+use crate::Response;
+use crate::http::HeaderValue;
+use crate::http::header::CONTENT_TYPE;
+
+use super::{HttpMetrics, Metric, MetricData};
+
+/// The content type of Prometheus' text exposition format.
+static TEXT_FORMAT: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// A request handler that exposes [`HttpMetrics`] in
+/// [Prometheus' text format](https://prometheus.io/docs/instrumenting/exposition_formats/).
+///
+/// # Registration
+///
+/// Use [`Blueprint::route`] to mount it on `GET /metrics`:
+///
+/// ```rust
+/// use pavex::Blueprint;
+/// use pavex::telemetry::metrics::PROMETHEUS_METRICS;
+///
+/// let mut bp = Blueprint::new();
+/// bp.route(PROMETHEUS_METRICS);
+/// ```
+///
+/// Use [`Blueprint::prefix`] to mount it under a different path.
+/// Check out [`encode_prometheus`] if you need to write your own handler—e.g. to
+/// add authentication or to include other metrics.
+///
+/// [`Blueprint::route`]: crate::Blueprint::route
+/// [`Blueprint::prefix`]: crate::Blueprint::prefix
+#[get(path = "/metrics", pavex = crate)]
+pub fn prometheus_metrics(metrics: &HttpMetrics) -> Response {
+    Response::ok()
+        .set_typed_body(encode_prometheus(&metrics.collect()))
+        .insert_header(CONTENT_TYPE, HeaderValue::from_static(TEXT_FORMAT))
+}
+
+/// Encode metrics in [Prometheus' text format](https://prometheus.io/docs/instrumenting/exposition_formats/).
+///
+/// Names are converted according to [OpenTelemetry's compatibility guidelines](https://opentelemetry.io/docs/specs/otel/compatibility/prometheus_and_openmetrics/):
+/// dots become underscores and the unit is appended as a suffix—e.g.
+/// `http.server.request.duration` becomes `http_server_request_duration_seconds`.
+pub fn encode_prometheus(metrics: &[Metric]) -> String {
+    let mut out = String::new();
+    for metric in metrics {
+        let name = prometheus_name(metric);
+        let kind = match &metric.data {
+            MetricData::Histogram(_) => "histogram",
+            MetricData::UpDownCounter(_) => "gauge",
+        };
+        // Writing to a `String` never fails.
+        let _ = writeln!(out, "# HELP {name} {}", metric.description);
+        let _ = writeln!(out, "# TYPE {name} {kind}");
+        match &metric.data {
+            MetricData::Histogram(points) => {
+                for point in points {
+                    let labels = labels(&point.attributes);
+                    let mut cumulative = 0;
+                    let bounds = point.bounds.iter().map(|b| b.to_string());
+                    for (bound, count) in bounds
+                        .chain(std::iter::once("+Inf".to_owned()))
+                        .zip(&point.bucket_counts)
+                    {
+                        cumulative += count;
+                        let le = format!("le=\"{bound}\"");
+                        let labels = if labels.is_empty() {
+                            le
+                        } else {
+                            format!("{labels},{le}")
+                        };
+                        let _ = writeln!(out, "{name}_bucket{{{labels}}} {cumulative}");
+                    }
+                    let labels = braced(&labels);
+                    let _ = writeln!(out, "{name}_sum{labels} {}", point.sum);
+                    let _ = writeln!(out, "{name}_count{labels} {}", point.count);
+                }
+            }
+            MetricData::UpDownCounter(points) => {
+                for point in points {
+                    let labels = braced(&labels(&point.attributes));
+                    let _ = writeln!(out, "{name}{labels} {}", point.value);
+                }
+            }
+        }
+    }
+    out
+}
+
+fn prometheus_name(metric: &Metric) -> String {
+    let mut name = sanitize(metric.name);
+    let suffix = match metric.unit {
+        "s" => "seconds",
+        "By" => "bytes",
+        _ => "",
+    };
+    if !suffix.is_empty() && !name.ends_with(suffix) {
+        name.push('_');
+        name.push_str(suffix);
+    }
+    name
+}
+
+/// Replace the characters that aren't allowed in metric and label names.
+fn sanitize(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+fn labels(attributes: &[(&'static str, String)]) -> String {
+    attributes
+        .iter()
+        .map(|(key, value)| {
+            let value = value
+                .replace('\\', "\\\\")
+                .replace('"', "\\\"")
+                .replace('\n', "\\n");
+            format!("{}=\"{value}\"", sanitize(key))
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn braced(labels: &str) -> String {
+    if labels.is_empty() {
+        String::new()
+    } else {
+        format!("{{{labels}}}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::telemetry::metrics::{HistogramDataPoint, UpDownCounterDataPoint};
+
+    #[test]
+    fn metrics_are_encoded_in_text_format() {
+        let metrics = [
+            Metric {
+                name: "http.server.request.duration",
+                description: "Duration of HTTP server requests.",
+                unit: "s",
+                data: MetricData::Histogram(vec![HistogramDataPoint {
+                    attributes: vec![("http.route", "/say \"hi\"".into())],
+                    bounds: vec![0.1, 1.0],
+                    bucket_counts: vec![2, 1, 1],
+                    count: 4,
+                    sum: 3.5,
+                }]),
+            },
+            Metric {
+                name: "http.server.active_requests",
+                description: "Number of active HTTP server requests.",
+                unit: "{request}",
+                data: MetricData::UpDownCounter(vec![UpDownCounterDataPoint {
+                    attributes: vec![("http.request.method", "GET".into())],
+                    value: 3,
+                }]),
+            },
+        ];
+        insta::assert_snapshot!(encode_prometheus(&metrics), @r#"
+        # HELP http_server_request_duration_seconds Duration of HTTP server requests.
+        # TYPE http_server_request_duration_seconds histogram
+        http_server_request_duration_seconds_bucket{http_route="/say \"hi\"",le="0.1"} 2
+        http_server_request_duration_seconds_bucket{http_route="/say \"hi\"",le="1"} 3
+        http_server_request_duration_seconds_bucket{http_route="/say \"hi\"",le="+Inf"} 4
+        http_server_request_duration_seconds_sum{http_route="/say \"hi\""} 3.5
+        http_server_request_duration_seconds_count{http_route="/say \"hi\""} 4
+        # HELP http_server_active_requests Number of active HTTP server requests.
+        # TYPE http_server_active_requests gauge
+        http_server_active_requests{http_request_method="GET"} 3
+        "#);
+    }
+}
@@ -1,8 +1,20 @@
 //! Tools to instrument and troubleshoot your Pavex applications.
 #[cfg(feature = "server_request_id")]
 #[cfg_attr(docsrs, doc(cfg(feature = "server_request_id")))]
+mod request_id;
+#[cfg(feature = "server_request_id")]
+#[cfg_attr(docsrs, doc(cfg(feature = "server_request_id")))]
 mod server_request_id;
 
+#[cfg(feature = "server_request_id")]
+#[cfg_attr(docsrs, doc(cfg(feature = "server_request_id")))]
+pub use request_id::{
+    ECHO_REQUEST_ID, REQUEST_ID_EXTRACT, RequestId, RequestIdConfig, echo_request_id,
+};
 #[cfg(feature = "server_request_id")]
 #[cfg_attr(docsrs, doc(cfg(feature = "server_request_id")))]
 pub use server_request_id::ServerRequestId;
+
+#[cfg(feature = "metrics")]
+#[cfg_attr(docsrs, doc(cfg(feature = "metrics")))]
+pub mod metrics;
@@ -0,0 +1,291 @@
+use std::fmt::Formatter;
+
+use pavex_macros::{methods, post_process};
+
+use crate::Response;
+use crate::http::{HeaderMap, HeaderName, HeaderValue};
+use crate::request::RequestHead;
+
+use super::ServerRequestId;
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+/// Configure how [`RequestId`] is determined for incoming requests.
+///
+/// # Example
+///
+/// ```yaml
+/// request_id:
+///   header: x-correlation-id
+///   accept_inbound: true
+///   max_length: 64
+/// ```
+#[crate::config(key = "request_id", default_if_missing, pavex = crate)]
+pub struct RequestIdConfig {
+    /// The header carrying the request id, both on incoming requests and
+    /// on outgoing responses.
+    ///
+    /// By default, it's set to `x-request-id`.
+    #[serde(with = "header_name", default = "default_header")]
+    pub header: HeaderName,
+    /// Whether to reuse the request id assigned by an upstream component—e.g. a gateway
+    /// or a load balancer.
+    ///
+    /// Inbound ids are only accepted if they are no longer than [`max_length`](Self::max_length)
+    /// and they are made of ASCII alphanumeric characters or one of `-`, `_`, `.`, `:`, `+`, `/`, `=`, `@`.
+    /// A new id is generated otherwise.
+    ///
+    /// By default, it's set to `true`.
+    #[serde(default = "default_true")]
+    pub accept_inbound: bool,
+    /// The maximum length of an inbound request id, in bytes.
+    ///
+    /// By default, it's set to 128.
+    #[serde(default = "default_max_length")]
+    pub max_length: usize,
+    /// Whether to set the request id header on outgoing responses.
+    ///
+    /// By default, it's set to `true`.
+    #[serde(default = "default_true")]
+    pub echo: bool,
+}
+
+fn default_header() -> HeaderName {
+    HeaderName::from_static("x-request-id")
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_max_length() -> usize {
+    128
+}
+
+impl Default for RequestIdConfig {
+    fn default() -> Self {
+        Self {
+            header: default_header(),
+            accept_inbound: true,
+            max_length: default_max_length(),
+            echo: true,
+        }
+    }
+}
+
+/// The identifier of the current request, either received from an upstream
+/// component or generated by the server.
+///
+/// Unlike [`ServerRequestId`], it honors the request id assigned by your gateway
+/// or load balancer (via the `X-Request-Id` header, by default), so that the same id
+/// can be used to correlate logs across services.
+/// If there is no valid inbound id, the [`ServerRequestId`] of the request is used instead:
+/// the two never disagree for requests that aren't carrying an id.
+/// Check out [`RequestIdConfig`] for the available options.
+///
+/// # Example
+///
+/// Register [`ECHO_REQUEST_ID`] to set the header on outgoing responses and use
+/// [`RequestId::inject`] to propagate the id to the services you call:
+///
+/// ```rust
+/// use pavex::http::HeaderMap;
+/// use pavex::telemetry::{ECHO_REQUEST_ID, RequestId};
+/// use pavex::{Blueprint, blueprint::from};
+///
+/// let mut bp = Blueprint::new();
+/// // Registers the constructors for `RequestId` and `ServerRequestId`, among others.
+/// bp.import(from![pavex]);
+/// bp.post_process(ECHO_REQUEST_ID);
+///
+/// fn outgoing_headers(request_id: &RequestId) -> HeaderMap {
+///     let mut headers = HeaderMap::new();
+///     request_id.inject(&mut headers);
+///     headers
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RequestId {
+    value: Value,
+    header: HeaderName,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Value {
+    Inbound(HeaderValue),
+    Generated(ServerRequestId),
+}
+
+#[methods]
+impl RequestId {
+    /// Reuse the request id carried by the incoming request, if valid,
+    /// or fall back to the [`ServerRequestId`] assigned to the request.
+    #[request_scoped(pavex = crate, clone_if_necessary)]
+    pub fn extract(
+        request_head: &RequestHead,
+        server_request_id: &ServerRequestId,
+        config: &RequestIdConfig,
+    ) -> Self {
+        let inbound = config
+            .accept_inbound
+            .then(|| request_head.headers.get(&config.header))
+            .flatten()
+            .filter(|value| is_valid(value.as_bytes(), config.max_length));
+        let value = match inbound {
+            Some(value) => Value::Inbound(value.clone()),
+            None => Value::Generated(*server_request_id),
+        };
+        Self {
+            value,
+            header: config.header.clone(),
+        }
+    }
+
+    /// The request id, as a string.
+    pub fn as_str(&self) -> std::borrow::Cow<'_, str> {
+        match &self.value {
+            // Inbound values are validated to be ASCII.
+            Value::Inbound(value) => value.to_str().unwrap_or_default().into(),
+            Value::Generated(id) => id.to_string().into(),
+        }
+    }
+
+    /// Whether the id was received from an upstream component,
+    /// rather than generated by the server.
+    pub fn is_inbound(&self) -> bool {
+        matches!(self.value, Value::Inbound(_))
+    }
+
+    /// The request id, as a header value.
+    pub fn header_value(&self) -> HeaderValue {
+        match &self.value {
+            Value::Inbound(value) => value.clone(),
+            Value::Generated(id) => id.header_value(),
+        }
+    }
+
+    /// The name of the header used to propagate the request id, as configured
+    /// in [`RequestIdConfig::header`].
+    pub fn header_name(&self) -> &HeaderName {
+        &self.header
+    }
+
+    /// Set the request id header to propagate the id to an outgoing request.
+    ///
+    /// Existing values for the header are replaced.
+    pub fn inject(&self, headers: &mut HeaderMap) {
+        headers.insert(self.header.clone(), self.header_value());
+    }
+}
+
+impl std::fmt::Display for RequestId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.as_str())
+    }
+}
+
+/// Set the request id header on the outgoing response, unless disabled via
+/// [`RequestIdConfig::echo`].
+///
+/// Register it via [`Blueprint::post_process`](crate::Blueprint::post_process).
+#[post_process(pavex = crate)]
+pub fn echo_request_id(
+    response: Response,
+    request_id: &RequestId,
+    config: &RequestIdConfig,
+) -> Response {
+    if !config.echo {
+        return response;
+    }
+    response.insert_header(request_id.header.clone(), request_id.header_value())
+}
+
+/// Check that an inbound request id is safe to log and propagate.
+fn is_valid(value: &[u8], max_length: usize) -> bool {
+    !value.is_empty()
+        && value.len() <= max_length
+        && value.iter().all(|c| {
+            c.is_ascii_alphanumeric()
+                || matches!(c, b'-' | b'_' | b'.' | b':' | b'+' | b'/' | b'=' | b'@')
+        })
+}
+
+/// (De)serialize a [`HeaderName`] as a string.
+mod header_name {
+    use crate::http::HeaderName;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub(super) fn serialize<S: Serializer>(
+        name: &HeaderName,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(name.as_str())
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<HeaderName, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        HeaderName::from_bytes(name.as_bytes()).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn head(value: Option<&'static str>) -> RequestHead {
+        let mut headers = HeaderMap::new();
+        if let Some(value) = value {
+            headers.insert("x-request-id", HeaderValue::from_static(value));
+        }
+        RequestHead {
+            method: crate::http::Method::GET,
+            target: "/".parse().unwrap(),
+            version: crate::http::Version::HTTP_11,
+            headers,
+        }
+    }
+
+    fn extract(head: &RequestHead, config: &RequestIdConfig) -> RequestId {
+        RequestId::extract(head, &ServerRequestId::generate(), config)
+    }
+
+    #[test]
+    fn valid_inbound_ids_are_reused() {
+        let config = RequestIdConfig::default();
+        let id = extract(&head(Some("f9a2-41:gw@edge")), &config);
+        assert!(id.is_inbound());
+        assert_eq!(id.as_str(), "f9a2-41:gw@edge");
+
+        let mut headers = HeaderMap::new();
+        id.inject(&mut headers);
+        assert_eq!(headers["x-request-id"], "f9a2-41:gw@edge");
+    }
+
+    #[test]
+    fn invalid_or_untrusted_inbound_ids_are_replaced() {
+        let config = RequestIdConfig::default();
+        for value in ["has spaces", "quote\"d", ""] {
+            let id = extract(&head(Some(value)), &config);
+            assert!(!id.is_inbound(), "{value}");
+        }
+        let config = RequestIdConfig {
+            max_length: 4,
+            ..Default::default()
+        };
+        assert!(!extract(&head(Some("12345")), &config).is_inbound());
+
+        let config = RequestIdConfig {
+            accept_inbound: false,
+            ..Default::default()
+        };
+        let server_request_id = ServerRequestId::generate();
+        let id = RequestId::extract(&head(Some("abc")), &server_request_id, &config);
+        assert!(!id.is_inbound());
+        // The server request id is used as a fallback.
+        assert_eq!(id.as_str(), server_request_id.to_string());
+        assert_eq!(id.header_value(), server_request_id.header_value());
+    }
+}
@@ -0,0 +1,308 @@
+use std::path::Path;
+
+use pavex::config::{
+    ConfigFormat, ConfigLoader, ConfigProfile, ConfigSecrets, ConfigSource,
+    figment::providers::Serialized,
+};
+
+#[derive(ConfigProfile, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Profile {
+    #[px(profile = "dev")]
+    Development,
+    #[px(profile = "prod")]
+    Production,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct Config {
+    app: AppConfig,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct AppConfig {
+    name: String,
+    port: u16,
+}
+
+fn write(dir: &Path, filename: &str, contents: &str) {
+    std::fs::write(dir.join(filename), contents).unwrap();
+}
+
+#[test]
+fn files_are_discovered_by_extension() {
+    let dir = tempfile::tempdir().unwrap();
+    write(
+        dir.path(),
+        "base.toml",
+        "[app]\nname = \"base\"\nport = 8000\n",
+    );
+    write(dir.path(), "dev.json", r#"{"app": {"port": 9000}}"#);
+
+    let loader = ConfigLoader::new()
+        .profile(Profile::Development)
+        .configuration_dir(dir.path());
+    assert_eq!(
+        loader.sources().unwrap(),
+        vec![
+            ConfigSource::File {
+                path: dir.path().join("base.toml"),
+                format: ConfigFormat::Toml
+            },
+            ConfigSource::File {
+                path: dir.path().join("dev.json"),
+                format: ConfigFormat::Json
+            },
+            ConfigSource::EnvVars,
+        ]
+    );
+
+    let config: Config = loader.load().unwrap();
+    assert_eq!(config.app.name, "base");
+    assert_eq!(config.app.port, 9000);
+}
+
+#[test]
+fn a_profile_can_only_have_one_file() {
+    let dir = tempfile::tempdir().unwrap();
+    write(dir.path(), "prod.yml", "app:\n  port: 80\n");
+    write(dir.path(), "prod.toml", "[app]\nport = 80\n");
+
+    let err = ConfigLoader::new()
+        .profile(Profile::Production)
+        .configuration_dir(dir.path())
+        .sources()
+        .unwrap_err();
+    let source = std::error::Error::source(&err).unwrap().to_string();
+    assert!(
+        source.contains("multiple `prod` configuration files"),
+        "{source}"
+    );
+}
+
+#[test]
+fn custom_providers_and_dotenv_files_are_layered_in_order() {
+    let dir = tempfile::tempdir().unwrap();
+    write(dir.path(), "base.yml", "app:\n  name: base\n  port: 8000\n");
+    write(
+        dir.path(),
+        ".env",
+        "PX_PROFILE=prod\nPX_APP__PORT=7000\nUNRELATED=value\n",
+    );
+
+    let loader = ConfigLoader::<Profile>::new()
+        .configuration_dir(dir.path())
+        .dotenv_file(dir.path().join(".env"))
+        .provider(Serialized::default("app.name", "first"))
+        .provider(Serialized::default(
+            "app",
+            serde_json::json!({ "name": "second", "port": 1 }),
+        ));
+    let sources = loader.sources().unwrap();
+    assert!(matches!(
+        &sources[..],
+        [
+            ConfigSource::File { .. },
+            ConfigSource::Provider { .. },
+            ConfigSource::Provider { .. },
+            ConfigSource::DotEnvFile { .. },
+            ConfigSource::EnvVars,
+        ]
+    ));
+
+    let config: Config = loader.load().unwrap();
+    assert_eq!(config.app.name, "second");
+    assert_eq!(config.app.port, 7000);
+}
+
+#[test]
+fn missing_dotenv_files_are_skipped() {
+    let dir = tempfile::tempdir().unwrap();
+    let sources = ConfigLoader::new()
+        .profile(Profile::Development)
+        .configuration_dir(dir.path())
+        .dotenv_file(dir.path().join(".env"))
+        .sources()
+        .unwrap();
+    assert_eq!(sources, vec![ConfigSource::EnvVars]);
+}
+
+#[test]
+fn secret_values_can_be_read_from_files() {
+    #[derive(Debug, serde::Deserialize)]
+    struct Config {
+        config_it_secret: SecretConfig,
+    }
+
+    #[derive(Debug, serde::Deserialize)]
+    struct SecretConfig {
+        password: String,
+        username: String,
+    }
+
+    let dir = tempfile::tempdir().unwrap();
+    let secret = dir.path().join("password");
+    std::fs::write(&secret, "12345\n").unwrap();
+    write(
+        dir.path(),
+        ".env",
+        "PX_CONFIG_IT_SECRET__USERNAME_FILE=/does/not/matter\n",
+    );
+    // SAFETY: this test binary only accesses the environment via `std::env`,
+    // which synchronizes reads and writes.
+    unsafe { std::env::set_var("PX_CONFIG_IT_SECRET__PASSWORD_FILE", &secret) };
+
+    let loader = ConfigLoader::new()
+        .profile(Profile::Development)
+        .configuration_dir(dir.path())
+        .provider(Serialized::default("config_it_secret.username", "admin"))
+        .env_file_indirection(true);
+    assert_eq!(
+        loader.sources().unwrap().last(),
+        Some(&ConfigSource::SecretFile {
+            variable: "PX_CONFIG_IT_SECRET__PASSWORD_FILE".into(),
+            path: secret.clone(),
+        })
+    );
+    let config: Config = loader.clone().load().unwrap();
+    // The contents are used verbatim, minus the trailing newline.
+    assert_eq!(config.config_it_secret.password, "12345");
+    assert_eq!(config.config_it_secret.username, "admin");
+
+    // References to missing files are an error.
+    let err = loader
+        .dotenv_file(dir.path().join(".env"))
+        .load::<Config>()
+        .unwrap_err();
+    let source = std::error::Error::source(&err).unwrap().to_string();
+    assert!(source.contains("/does/not/matter"), "{source}");
+
+    // SAFETY: see above.
+    unsafe { std::env::remove_var("PX_CONFIG_IT_SECRET__PASSWORD_FILE") };
+}
+
+impl ConfigSecrets for Config {
+    const SECRET_KEYS: &'static [&'static str] = &["tenants.*.key"];
+}
+
+#[test]
+fn explain_reports_the_source_of_each_value() {
+    let dir = tempfile::tempdir().unwrap();
+    write(
+        dir.path(),
+        "base.yml",
+        "app:\n  name: base\n  port: 8000\ndb:\n  password: hunter2\n  url: postgres://\ntenants:\n  acme:\n    key: abc\n",
+    );
+    write(dir.path(), "dev.toml", "[app]\nname = \"dev\"\n");
+    write(dir.path(), ".env", "PX_APP__PORT=7000\n");
+
+    let report = ConfigLoader::new()
+        .profile(Profile::Development)
+        .configuration_dir(dir.path())
+        .dotenv_file(dir.path().join(".env"))
+        .redact("db.url")
+        .explain::<Config>()
+        .unwrap();
+    assert_eq!(report.profile(), "dev");
+
+    let entries: Vec<_> = report
+        .entries()
+        .iter()
+        .map(|e| (e.key.as_str(), e.value.as_deref(), e.variable.as_deref()))
+        .collect();
+    assert_eq!(
+        entries,
+        vec![
+            ("app.name", Some(r#""dev""#), None),
+            ("app.port", Some("7000"), Some("PX_APP__PORT")),
+            // Redacted because of its name.
+            ("db.password", None, None),
+            // Redacted explicitly.
+            ("db.url", None, None),
+            // Redacted as a secret key of `Config`.
+            ("tenants.acme.key", None, None),
+        ]
+    );
+    assert_eq!(
+        report.entries()[0].source,
+        Some(ConfigSource::File {
+            path: dir.path().join("dev.toml"),
+            format: ConfigFormat::Toml
+        })
+    );
+    assert_eq!(
+        report.entries()[1].source,
+        Some(ConfigSource::DotEnvFile {
+            path: dir.path().join(".env")
+        })
+    );
+    assert_eq!(
+        report.entries()[1].to_string(),
+        format!(
+            "app.port = 7000 (`PX_APP__PORT` in .env file `{}`)",
+            dir.path().join(".env").display()
+        )
+    );
+}
+
+#[test]
+fn reloadable_values_are_updated_on_reload() {
+    use pavex::config::{ConfigReloader, Reloadable};
+
+    #[derive(Debug, serde::Deserialize)]
+    struct Config {
+        limits: Reloadable<Limits>,
+        app: AppConfig,
+    }
+
+    #[derive(Debug, serde::Deserialize)]
+    struct Limits {
+        requests_per_minute: u32,
+    }
+
+    let dir = tempfile::tempdir().unwrap();
+    let base = "app:\n  name: base\n  port: 8000\n";
+    write(
+        dir.path(),
+        "base.yml",
+        &format!("{base}limits:\n  requests_per_minute: 10\n"),
+    );
+    let loader = ConfigLoader::new()
+        .profile(Profile::Development)
+        .configuration_dir(dir.path());
+    let reloader = ConfigReloader::new(loader).validator(|config: &Config| {
+        anyhow::ensure!(
+            config.limits.snapshot().requests_per_minute > 0,
+            "The rate limit can't be zero"
+        );
+        Ok(())
+    });
+    let config: Config = reloader.load().unwrap();
+    // Clones share the same value, e.g. the ones stored in the application state.
+    let limits = config.limits.clone();
+    assert_eq!(limits.snapshot().requests_per_minute, 10);
+
+    write(
+        dir.path(),
+        "base.yml",
+        "app:\n  name: reloaded\n  port: 8000\nlimits:\n  requests_per_minute: 20\n",
+    );
+    reloader.reload().unwrap();
+    assert_eq!(limits.snapshot().requests_per_minute, 20);
+    // Values that aren't reloadable are left untouched.
+    assert_eq!(config.app.name, "base");
+
+    // Invalid configurations are rejected.
+    write(
+        dir.path(),
+        "base.yml",
+        "limits:\n  requests_per_minute: 30\n",
+    );
+    assert!(reloader.reload().is_err());
+    write(
+        dir.path(),
+        "base.yml",
+        &format!("{base}limits:\n  requests_per_minute: 0\n"),
+    );
+    assert!(reloader.reload().is_err());
+    assert_eq!(limits.snapshot().requests_per_minute, 20);
+}
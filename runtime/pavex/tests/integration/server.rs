@@ -91,6 +91,32 @@ async fn serve() {
     reqwest::get(url).await.unwrap().error_for_status().unwrap();
 }
 
+async fn panicking_handler(
+    _request: Request<Incoming>,
+    _connection_info: Option<ConnectionInfo>,
+    _state: (),
+) -> Response {
+    panic!("Something went wrong")
+}
+
+#[tokio::test]
+async fn panics_in_the_handler_are_converted_into_500s() {
+    let (incoming, addr) = test_incoming().await;
+    Server::new()
+        .set_config(test_server_config())
+        .listen(incoming)
+        .serve(panicking_handler, ());
+
+    // The connection is not torn down: we get a response back.
+    let url = format!("http://localhost:{}", addr.port());
+    let response = reqwest::get(&url).await.unwrap();
+    assert_eq!(response.status(), 500);
+
+    // The worker is still alive and can serve other requests.
+    let response = reqwest::get(&url).await.unwrap();
+    assert_eq!(response.status(), 500);
+}
+
 async fn slow_handler(
     _req: Request<Incoming>,
     _connection_info: Option<ConnectionInfo>,
@@ -223,3 +249,44 @@ async fn graceful_but_too_fast() {
         "The server was supposed to shutdown forcefully the slow request, but it waited instead"
     );
 }
+
+async fn draining_handler(
+    _req: Request<Incoming>,
+    connection_info: Option<ConnectionInfo>,
+    state: SlowHandlerState,
+) -> Response {
+    let connection_info = connection_info.unwrap();
+    assert!(!connection_info.is_draining());
+    state.started.send(()).await.unwrap();
+    tokio::time::sleep(state.sleep).await;
+    if connection_info.is_draining() {
+        Response::service_unavailable()
+    } else {
+        Response::ok()
+    }
+}
+
+#[tokio::test]
+async fn in_flight_requests_see_draining() {
+    let (incoming, addr) = test_incoming().await;
+    let delay = Duration::from_millis(100);
+    let (mut has_started, state) = SlowHandlerState::new(delay);
+
+    let server_handle = Server::new()
+        .set_config(test_server_config())
+        .listen(incoming)
+        .serve(draining_handler, state);
+
+    let get_response = tokio::task::spawn(async move {
+        let url = format!("http://localhost:{}", addr.port());
+        reqwest::get(url).await.unwrap().status()
+    });
+
+    // Wait for the connection to be established, then start a graceful shutdown.
+    has_started.recv().await.unwrap();
+    server_handle
+        .shutdown(ShutdownMode::Graceful { timeout: delay * 5 })
+        .await;
+
+    assert_eq!(get_response.await.unwrap(), 503);
+}
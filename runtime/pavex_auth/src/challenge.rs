@@ -0,0 +1,56 @@
+use pavex::http::HeaderValue;
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+/// Configure the `WWW-Authenticate` challenge sent alongside `401 Unauthorized` responses.
+///
+/// Session-based authentication has no registered scheme: pick the one your clients
+/// expect, if any.
+#[pavex::config(key = "authentication", default_if_missing)]
+pub struct AuthenticationConfig {
+    /// The authentication scheme advertised to the client.
+    ///
+    /// By default, it is set to `Session`.
+    #[serde(default = "default_scheme")]
+    pub scheme: String,
+    /// The `realm` advertised alongside the scheme.
+    ///
+    /// By default, no realm is advertised.
+    #[serde(default)]
+    pub realm: Option<String>,
+}
+
+impl Default for AuthenticationConfig {
+    fn default() -> Self {
+        Self {
+            scheme: default_scheme(),
+            realm: None,
+        }
+    }
+}
+
+fn default_scheme() -> String {
+    "Session".to_owned()
+}
+
+impl AuthenticationConfig {
+    /// The value of the `WWW-Authenticate` header sent alongside `401 Unauthorized` responses.
+    pub fn www_authenticate(&self) -> String {
+        match &self.realm {
+            Some(realm) => {
+                let realm = realm.replace('\\', "\\\\").replace('"', "\\\"");
+                format!("{} realm=\"{realm}\"", self.scheme)
+            }
+            None => self.scheme.clone(),
+        }
+    }
+
+    /// [`Self::www_authenticate`], as a header value.
+    ///
+    /// It falls back to the default scheme if the configured challenge isn't a valid header value.
+    pub(crate) fn challenge(&self) -> HeaderValue {
+        HeaderValue::from_str(&self.www_authenticate())
+            .unwrap_or_else(|_| HeaderValue::from_static("Session"))
+    }
+}
@@ -0,0 +1,119 @@
+//! Errors returned by the authentication components.
+use crate::AuthenticationConfig;
+use pavex::{Response, http::header::WWW_AUTHENTICATE, methods};
+use pavex_session::{
+    errors::{ServerGetError, ServerInsertError},
+    store::errors::LoadError,
+};
+
+/// The error returned when the current request can't be associated with an authenticated user.
+///
+/// It's returned by [`AuthenticatedUser::extract`](crate::AuthenticatedUser::extract) and
+/// [`require_authentication`](crate::require_authentication).
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum AuthenticationError {
+    #[error("The request is not authenticated")]
+    Unauthenticated,
+    #[error("The user associated with the current session no longer exists")]
+    UnknownUser,
+    #[error("Failed to retrieve the user ID from the session")]
+    SessionError(#[from] ServerGetError),
+    #[error("Failed to load the authenticated user")]
+    LoaderError(#[source] anyhow::Error),
+}
+
+#[methods]
+impl AuthenticationError {
+    /// Convert the error into a response.
+    ///
+    /// It returns a `401 Unauthorized` if the request isn't authenticated,
+    /// a `500 Internal Server Error` otherwise.
+    /// `401` responses carry a `WWW-Authenticate` header, as configured via [`AuthenticationConfig`].
+    #[error_handler]
+    pub fn into_response(#[px(error_ref)] &self, config: &AuthenticationConfig) -> Response {
+        match self {
+            AuthenticationError::Unauthenticated | AuthenticationError::UnknownUser => {
+                Response::unauthorized().insert_header(WWW_AUTHENTICATE, config.challenge())
+            }
+            AuthenticationError::SessionError(_) | AuthenticationError::LoaderError(_) => {
+                Response::internal_server_error()
+            }
+        }
+    }
+}
+
+/// The error returned when the authenticated user is not allowed to perform the
+/// requested operation.
+///
+/// It's returned by [`AuthenticatedUser::require`](crate::AuthenticatedUser::require).
+#[derive(Debug, thiserror::Error)]
+#[error("The authenticated user is not allowed to perform the requested operation")]
+#[non_exhaustive]
+pub struct AuthorizationError;
+
+impl AuthorizationError {
+    /// Create a new authorization error.
+    ///
+    /// Use it to reject requests in your own authorization checks.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for AuthorizationError {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[methods]
+impl AuthorizationError {
+    /// Convert the error into a `403 Forbidden` response.
+    #[error_handler]
+    pub fn into_response(&self) -> Response {
+        Response::forbidden()
+    }
+}
+
+/// The error returned by [`log_in`](crate::log_in).
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum LoginError {
+    #[error("Failed to retrieve the user ID from the session")]
+    GetError(#[from] ServerGetError),
+    #[error("Failed to clear the state left behind by the previous user")]
+    ClearError(#[from] LoadError),
+    #[error("Failed to store the user ID in the session")]
+    InsertError(#[from] ServerInsertError),
+}
+
+#[methods]
+impl LoginError {
+    /// Convert the error into a response.
+    #[error_handler]
+    pub fn into_response(&self) -> Response {
+        Response::internal_server_error()
+    }
+}
+
+/// The error returned by [`PasswordHasher`](crate::PasswordHasher).
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum PasswordHashError {
+    #[error("The password hashing parameters are invalid")]
+    InvalidParameters(#[source] argon2::Error),
+    #[error("The password hash is malformed")]
+    MalformedHash(#[source] argon2::password_hash::Error),
+    #[error("Failed to hash the password")]
+    HashingError(#[source] argon2::password_hash::Error),
+}
+
+#[methods]
+impl PasswordHashError {
+    /// Convert the error into a response.
+    #[error_handler]
+    pub fn into_response(&self) -> Response {
+        Response::internal_server_error()
+    }
+}
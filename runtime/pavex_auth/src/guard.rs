@@ -0,0 +1,31 @@
+use pavex::{middleware::Processing, pre_process};
+use pavex_session::Session;
+
+use crate::{current_user_id, errors::AuthenticationError};
+
+/// A pre-processing middleware to reject unauthenticated requests with a `401 Unauthorized`.
+///
+/// It only checks that the session is associated with a user, without loading it.
+/// Take [`AuthenticatedUser`](crate::AuthenticatedUser) as an input if you need the user.
+///
+/// # Example
+///
+/// ```rust
+/// use pavex::Blueprint;
+///
+/// fn blueprint() -> Blueprint {
+///     let mut bp = Blueprint::new();
+///     bp.pre_process(pavex_auth::REQUIRE_AUTHENTICATION);
+///     // [...]
+///     bp
+/// }
+/// ```
+#[pre_process]
+pub async fn require_authentication(
+    session: &Session<'_>,
+) -> Result<Processing, AuthenticationError> {
+    match current_user_id(session).await? {
+        Some(_) => Ok(Processing::Continue),
+        None => Err(AuthenticationError::Unauthenticated),
+    }
+}
@@ -0,0 +1,24 @@
+/*!
+Authentication building blocks for Pavex, built on top of [`pavex_session`].
+
+- [`log_in`] and [`log_out`] manage the authentication state of a [`Session`](pavex_session::Session),
+  protecting your application against session fixation attacks.
+- [`AuthenticatedUser`] retrieves the user associated with the current session, using
+  the [`UserLoader`] of your choice.
+- [`require_authentication`] and [`AuthenticatedUser::require`] guard your routes,
+  rejecting requests with a `401 Unauthorized` or a `403 Forbidden` response.
+  The `WWW-Authenticate` challenge is controlled by [`AuthenticationConfig`].
+- [`PasswordHasher`] hashes and verifies passwords using Argon2id.
+*/
+mod challenge;
+pub mod errors;
+mod guard;
+mod login;
+mod password;
+mod user;
+
+pub use challenge::AuthenticationConfig;
+pub use guard::{REQUIRE_AUTHENTICATION, require_authentication};
+pub use login::{current_user_id, log_in, log_out};
+pub use password::{PasswordHasher, PasswordHashingConfig};
+pub use user::{AuthenticatedUser, UserLoader, Users};
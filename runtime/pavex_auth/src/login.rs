@@ -0,0 +1,45 @@
+use pavex_session::{Session, errors::ServerGetError};
+
+use crate::errors::LoginError;
+
+/// The key used to store the ID of the authenticated user in the server-side session state.
+pub(crate) const USER_ID_KEY: &str = "pavex_auth.user_id";
+
+/// Associate the current session with the user who just proved their identity.
+///
+/// The session ID is [cycled](Session::cycle_id), to prevent session fixation attacks:
+/// an attacker who planted a session ID in the user's browser doesn't get access to
+/// the authenticated session.\
+/// If the session belonged to a different user, its state is cleared before logging in
+/// the new one.
+///
+/// Use [`Session::set_principal`] on top of it if you want to track the sessions of each user.
+pub async fn log_in(
+    session: &mut Session<'_>,
+    user_id: impl Into<String>,
+) -> Result<(), LoginError> {
+    let user_id = user_id.into();
+    let previous: Option<String> = session.get(USER_ID_KEY).await?;
+    if previous.is_some_and(|previous| previous != user_id) {
+        session.clear().await?;
+        session.client_mut().clear();
+    }
+    session.cycle_id();
+    session.insert(USER_ID_KEY, user_id).await?;
+    Ok(())
+}
+
+/// Log the current user out.
+///
+/// The session is [invalidated](Session::invalidate): its server-side state is deleted
+/// and the session cookie is removed from the client.
+pub fn log_out(session: &mut Session<'_>) {
+    session.invalidate();
+}
+
+/// The ID of the user associated with the current session, if any.
+///
+/// Use [`AuthenticatedUser`](crate::AuthenticatedUser) if you need the user itself.
+pub async fn current_user_id(session: &Session<'_>) -> Result<Option<String>, ServerGetError> {
+    session.get(USER_ID_KEY).await
+}
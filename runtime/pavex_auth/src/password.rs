@@ -0,0 +1,150 @@
+use argon2::{
+    Algorithm, Argon2, Params, Version,
+    password_hash::{self, PasswordHash, PasswordVerifier, SaltString, rand_core::OsRng},
+};
+use pavex::methods;
+
+use crate::errors::PasswordHashError;
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+/// Configure the cost of hashing passwords with [`PasswordHasher`].
+///
+/// The defaults follow
+/// [OWASP's recommendations for Argon2id](https://cheatsheetseries.owasp.org/cheatsheets/Password_Storage_Cheat_Sheet.html#argon2id).
+#[pavex::config(key = "password_hashing", default_if_missing)]
+pub struct PasswordHashingConfig {
+    /// The amount of memory used to hash a password, in kibibytes.
+    ///
+    /// By default, it is set to 19456 (i.e. 19 MiB).
+    #[serde(default = "default_memory_cost")]
+    pub memory_cost: u32,
+    /// The number of passes over the memory.
+    ///
+    /// By default, it is set to 2.
+    #[serde(default = "default_iterations")]
+    pub iterations: u32,
+    /// The degree of parallelism.
+    ///
+    /// By default, it is set to 1.
+    #[serde(default = "default_parallelism")]
+    pub parallelism: u32,
+}
+
+impl Default for PasswordHashingConfig {
+    fn default() -> Self {
+        Self {
+            memory_cost: default_memory_cost(),
+            iterations: default_iterations(),
+            parallelism: default_parallelism(),
+        }
+    }
+}
+
+fn default_memory_cost() -> u32 {
+    Params::DEFAULT_M_COST
+}
+
+fn default_iterations() -> u32 {
+    Params::DEFAULT_T_COST
+}
+
+fn default_parallelism() -> u32 {
+    Params::DEFAULT_P_COST
+}
+
+/// Hash and verify passwords using Argon2id.
+///
+/// Hashes are encoded in the [PHC string format](https://github.com/P-H-C/phc-string-format/blob/master/phc-sf-spec.md),
+/// so they embed the salt and the parameters they were computed with.
+///
+/// # Performance
+///
+/// Hashing is deliberately expensive: invoke the hasher via
+/// [`tokio::task::spawn_blocking`](https://docs.rs/tokio/latest/tokio/task/fn.spawn_blocking.html)
+/// to avoid stalling the async runtime.
+#[derive(Debug, Clone)]
+pub struct PasswordHasher {
+    config: PasswordHashingConfig,
+}
+
+#[methods]
+impl PasswordHasher {
+    /// Create a new hasher, using the given configuration.
+    #[singleton]
+    pub fn new(config: &PasswordHashingConfig) -> Self {
+        Self {
+            config: config.clone(),
+        }
+    }
+
+    /// Hash a password, using a randomly generated salt.
+    pub fn hash(&self, password: &str) -> Result<String, PasswordHashError> {
+        let salt = SaltString::generate(&mut OsRng);
+        let hash =
+            argon2::PasswordHasher::hash_password(&self.argon2()?, password.as_bytes(), &salt)
+                .map_err(PasswordHashError::HashingError)?;
+        Ok(hash.to_string())
+    }
+
+    /// Check if `password` matches the given hash.
+    ///
+    /// The hash is verified using the parameters it was computed with, rather
+    /// than the ones in the current configuration.
+    pub fn verify(&self, password: &str, hash: &str) -> Result<bool, PasswordHashError> {
+        let hash = PasswordHash::new(hash).map_err(PasswordHashError::MalformedHash)?;
+        match Argon2::default().verify_password(password.as_bytes(), &hash) {
+            Ok(()) => Ok(true),
+            Err(password_hash::Error::Password) => Ok(false),
+            Err(e) => Err(PasswordHashError::MalformedHash(e)),
+        }
+    }
+
+    /// Like [`verify`](Self::verify), but it accepts a missing hash—e.g. when
+    /// nobody signed up with the username submitted in a login form.
+    ///
+    /// A missing hash never matches, but it takes roughly as long as verifying an actual hash:
+    /// attackers can't tell if a user exists by timing your login endpoint.
+    pub fn verify_or_simulate(
+        &self,
+        password: &str,
+        hash: Option<&str>,
+    ) -> Result<bool, PasswordHashError> {
+        match hash {
+            Some(hash) => self.verify(password, hash),
+            None => {
+                self.hash(password)?;
+                Ok(false)
+            }
+        }
+    }
+
+    /// Check if the given hash was computed with different parameters than the
+    /// ones in the current configuration.
+    ///
+    /// Use it after a successful login to upgrade the stored hash, if needed.
+    pub fn needs_rehash(&self, hash: &str) -> Result<bool, PasswordHashError> {
+        let hash = PasswordHash::new(hash).map_err(PasswordHashError::MalformedHash)?;
+        if hash.algorithm != Algorithm::Argon2id.ident() {
+            return Ok(true);
+        }
+        let params = Params::try_from(&hash).map_err(PasswordHashError::MalformedHash)?;
+        let config = &self.config;
+        Ok(params.m_cost() != config.memory_cost
+            || params.t_cost() != config.iterations
+            || params.p_cost() != config.parallelism)
+    }
+
+    fn argon2(&self) -> Result<Argon2<'static>, PasswordHashError> {
+        let config = &self.config;
+        let params = Params::new(
+            config.memory_cost,
+            config.iterations,
+            config.parallelism,
+            None,
+        )
+        .map_err(PasswordHashError::InvalidParameters)?;
+        Ok(Argon2::new(Algorithm::Argon2id, Version::V0x13, params))
+    }
+}
@@ -0,0 +1,176 @@
+use std::{fmt::Debug, ops::Deref, sync::Arc};
+
+use pavex::methods;
+use pavex_session::Session;
+
+use crate::{
+    current_user_id,
+    errors::{AuthenticationError, AuthorizationError},
+};
+
+/// Retrieve a user given their ID.
+///
+/// Implement this trait on top of the storage you use for your users
+/// (e.g. a database connection pool) and wrap it in [`Users`] to make it available
+/// to [`AuthenticatedUser`].
+#[async_trait::async_trait]
+pub trait UserLoader: Send + Sync + Debug {
+    /// The type representing a user of your application.
+    type User;
+
+    /// Load the user with the given ID.
+    ///
+    /// It should return `None` if no such user exists—e.g. if it has been deleted.
+    async fn load(&self, user_id: &str) -> Result<Option<Self::User>, anyhow::Error>;
+}
+
+/// The [`UserLoader`] used to retrieve users of type `T`.
+///
+/// Register it as a singleton, alongside [`AuthenticatedUser`]'s constructor.
+///
+/// # Example
+///
+/// ```rust
+/// use pavex_auth::{UserLoader, Users};
+///
+/// #[derive(Debug)]
+/// pub struct User {
+///     pub id: String,
+///     pub is_admin: bool,
+/// }
+///
+/// #[derive(Debug)]
+/// pub struct InMemoryUsers(Vec<User>);
+///
+/// #[async_trait::async_trait]
+/// impl UserLoader for InMemoryUsers {
+///     type User = User;
+///
+///     async fn load(&self, user_id: &str) -> Result<Option<User>, anyhow::Error> {
+///         let user = self.0.iter().find(|u| u.id == user_id);
+///         Ok(user.map(|u| User { id: u.id.clone(), is_admin: u.is_admin }))
+///     }
+/// }
+///
+/// // Register this function as a singleton constructor.
+/// fn users() -> Users<User> {
+///     Users::new(InMemoryUsers(vec![]))
+/// }
+/// ```
+pub struct Users<T> {
+    loader: Arc<dyn UserLoader<User = T>>,
+}
+
+impl<T> Users<T> {
+    /// Create a new [`Users`] instance, backed by the given loader.
+    pub fn new<L>(loader: L) -> Self
+    where
+        L: UserLoader<User = T> + 'static,
+    {
+        Self {
+            loader: Arc::new(loader),
+        }
+    }
+
+    /// Load the user with the given ID.
+    pub async fn load(&self, user_id: &str) -> Result<Option<T>, anyhow::Error> {
+        self.loader.load(user_id).await
+    }
+}
+
+impl<T> Clone for Users<T> {
+    fn clone(&self) -> Self {
+        Self {
+            loader: self.loader.clone(),
+        }
+    }
+}
+
+impl<T> Debug for Users<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Users")
+            .field("loader", &self.loader)
+            .finish()
+    }
+}
+
+/// The user associated with the current session.
+///
+/// Take it as an input in your request handlers to restrict them to authenticated users:
+/// unauthenticated requests are rejected with a `401 Unauthorized`.
+///
+/// # Example
+///
+/// ```rust
+/// use pavex::Response;
+/// use pavex_auth::{AuthenticatedUser, errors::AuthorizationError};
+///
+/// # pub struct User { pub is_admin: bool }
+/// pub fn admin_dashboard(
+///     user: &AuthenticatedUser<User>,
+/// ) -> Result<Response, AuthorizationError> {
+///     // Non-admins get a `403 Forbidden`.
+///     user.require(|u| u.is_admin)?;
+///     // [...]
+///     Ok(Response::ok())
+/// }
+/// ```
+#[derive(Debug)]
+pub struct AuthenticatedUser<T> {
+    id: String,
+    user: T,
+}
+
+#[methods]
+impl<T> AuthenticatedUser<T> {
+    /// Load the user associated with the current session, using the registered [`Users`].
+    ///
+    /// It fails with [`AuthenticationError::Unauthenticated`] if nobody [logged in](crate::log_in),
+    /// and with [`AuthenticationError::UnknownUser`] if the user no longer exists.
+    #[request_scoped(id = "AUTHENTICATED_USER_EXTRACT")]
+    pub async fn extract(
+        session: &Session<'_>,
+        users: &Users<T>,
+    ) -> Result<Self, AuthenticationError> {
+        let Some(id) = current_user_id(session).await? else {
+            return Err(AuthenticationError::Unauthenticated);
+        };
+        match users.load(&id).await {
+            Ok(Some(user)) => Ok(Self { id, user }),
+            Ok(None) => Err(AuthenticationError::UnknownUser),
+            Err(e) => Err(AuthenticationError::LoaderError(e)),
+        }
+    }
+
+    /// The ID of the authenticated user.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Consume the wrapper, returning the authenticated user.
+    pub fn into_inner(self) -> T {
+        self.user
+    }
+
+    /// Check that the authenticated user satisfies the given condition.
+    ///
+    /// It fails with an [`AuthorizationError`], converted into a `403 Forbidden`, otherwise.
+    pub fn require<F>(&self, condition: F) -> Result<&T, AuthorizationError>
+    where
+        F: FnOnce(&T) -> bool,
+    {
+        if condition(&self.user) {
+            Ok(&self.user)
+        } else {
+            Err(AuthorizationError::new())
+        }
+    }
+}
+
+impl<T> Deref for AuthenticatedUser<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.user
+    }
+}
@@ -0,0 +1,200 @@
+use pavex::{cookie::ResponseCookie, middleware::Processing};
+use pavex_auth::{
+    AuthenticatedUser, AuthenticationConfig, UserLoader, Users, current_user_id,
+    errors::{AuthenticationError, AuthorizationError},
+    log_in, log_out, require_authentication,
+};
+use pavex_session::{IncomingSession, Session, SessionConfig, SessionId, SessionStore};
+use pavex_session_memory_store::InMemorySessionStore;
+
+#[derive(Debug, Clone, PartialEq)]
+struct User {
+    id: String,
+    is_admin: bool,
+}
+
+#[derive(Debug)]
+struct TestUsers(Vec<User>);
+
+#[async_trait::async_trait]
+impl UserLoader for TestUsers {
+    type User = User;
+
+    async fn load(&self, user_id: &str) -> Result<Option<User>, anyhow::Error> {
+        Ok(self.0.iter().find(|u| u.id == user_id).cloned())
+    }
+}
+
+fn users() -> Users<User> {
+    Users::new(TestUsers(vec![
+        User {
+            id: "alice".into(),
+            is_admin: true,
+        },
+        User {
+            id: "bob".into(),
+            is_admin: false,
+        },
+    ]))
+}
+
+fn store() -> SessionStore {
+    SessionStore::new(InMemorySessionStore::default())
+}
+
+/// The session ID stored in a session cookie.
+fn session_id(cookie: &ResponseCookie<'_>) -> SessionId {
+    let value = serde_json::from_str::<serde_json::Value>(cookie.value()).unwrap();
+    serde_json::from_value(value["0"].clone()).unwrap()
+}
+
+/// Simulate a follow-up request, carrying the session cookie set by `session`.
+async fn next_request<'store>(
+    session: Session<'_>,
+    store: &'store SessionStore,
+    config: &'store SessionConfig,
+) -> Session<'store> {
+    let mut session = session;
+    let id = session_id(&session.finalize().await.unwrap().unwrap());
+    Session::new(
+        store,
+        config,
+        Some(IncomingSession::from_parts(id, Default::default())),
+    )
+}
+
+#[tokio::test]
+async fn logged_in_users_can_be_retrieved() {
+    let (store, config) = (store(), SessionConfig::default());
+    let mut session = Session::new(&store, &config, None);
+
+    log_in(&mut session, "alice").await.unwrap();
+
+    let session = next_request(session, &store, &config).await;
+    let user = AuthenticatedUser::extract(&session, &users())
+        .await
+        .unwrap();
+    assert_eq!(user.id(), "alice");
+    assert!(user.is_admin);
+}
+
+#[tokio::test]
+async fn the_session_id_is_cycled_on_login() {
+    let (store, config) = (store(), SessionConfig::default());
+    // An anonymous session, e.g. one planted by an attacker.
+    let mut session = Session::new(&store, &config, None);
+    session.insert("theme", "dark").await.unwrap();
+    let anonymous_id = session_id(&session.finalize().await.unwrap().unwrap());
+    let incoming = IncomingSession::from_parts(anonymous_id, Default::default());
+    let mut session = Session::new(&store, &config, Some(incoming));
+
+    log_in(&mut session, "alice").await.unwrap();
+
+    let authenticated_id = session_id(&session.finalize().await.unwrap().unwrap());
+    assert_ne!(authenticated_id.inner(), anonymous_id.inner());
+    // The state of the anonymous session is preserved.
+    let theme: Option<String> = session.get("theme").await.unwrap();
+    assert_eq!(theme.as_deref(), Some("dark"));
+}
+
+#[tokio::test]
+async fn switching_users_clears_the_session_state() {
+    let (store, config) = (store(), SessionConfig::default());
+    let mut session = Session::new(&store, &config, None);
+    log_in(&mut session, "alice").await.unwrap();
+    session.insert("cart", vec!["book"]).await.unwrap();
+    session.client_mut().insert("theme", "dark").unwrap();
+
+    let mut session = next_request(session, &store, &config).await;
+    log_in(&mut session, "bob").await.unwrap();
+
+    let cart: Option<Vec<String>> = session.get("cart").await.unwrap();
+    assert!(cart.is_none());
+    assert!(session.client().is_empty());
+    assert_eq!(
+        current_user_id(&session).await.unwrap().as_deref(),
+        Some("bob")
+    );
+}
+
+#[tokio::test]
+async fn logging_out_invalidates_the_session() {
+    let (store, config) = (store(), SessionConfig::default());
+    let mut session = Session::new(&store, &config, None);
+    log_in(&mut session, "alice").await.unwrap();
+    let mut session = next_request(session, &store, &config).await;
+
+    log_out(&mut session);
+
+    assert!(session.is_invalidated());
+    assert!(current_user_id(&session).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn anonymous_requests_are_rejected() {
+    let (store, config) = (store(), SessionConfig::default());
+    let session = Session::new(&store, &config, None);
+
+    let err = AuthenticatedUser::extract(&session, &users())
+        .await
+        .unwrap_err();
+    assert!(matches!(err, AuthenticationError::Unauthenticated));
+    let response = err.into_response(&AuthenticationConfig::default());
+    assert_eq!(response.status().as_u16(), 401);
+    assert_eq!(response.headers()["www-authenticate"], "Session");
+
+    let result = require_authentication(&session).await;
+    assert!(matches!(result, Err(AuthenticationError::Unauthenticated)));
+}
+
+#[tokio::test]
+async fn authenticated_requests_go_through_the_guard() {
+    let (store, config) = (store(), SessionConfig::default());
+    let mut session = Session::new(&store, &config, None);
+    log_in(&mut session, "bob").await.unwrap();
+    let session = next_request(session, &store, &config).await;
+
+    let processing = require_authentication(&session).await.unwrap();
+    assert!(matches!(processing, Processing::Continue));
+}
+
+#[tokio::test]
+async fn deleted_users_are_rejected() {
+    let (store, config) = (store(), SessionConfig::default());
+    let mut session = Session::new(&store, &config, None);
+    log_in(&mut session, "carol").await.unwrap();
+    let session = next_request(session, &store, &config).await;
+
+    let err = AuthenticatedUser::extract(&session, &users())
+        .await
+        .unwrap_err();
+    assert!(matches!(err, AuthenticationError::UnknownUser));
+    let response = err.into_response(&AuthenticationConfig::default());
+    assert_eq!(response.status().as_u16(), 401);
+    assert!(response.headers().contains_key("www-authenticate"));
+}
+
+#[test]
+fn the_challenge_can_be_configured() {
+    let config: AuthenticationConfig =
+        serde_json::from_str(r#"{"scheme": "Bearer", "realm": "api \"v2\""}"#).unwrap();
+    assert_eq!(config.www_authenticate(), r#"Bearer realm="api \"v2\"""#);
+
+    let config: AuthenticationConfig = serde_json::from_str("{}").unwrap();
+    assert_eq!(config.www_authenticate(), "Session");
+}
+
+#[tokio::test]
+async fn unauthorized_users_are_forbidden() {
+    let (store, config) = (store(), SessionConfig::default());
+    let mut session = Session::new(&store, &config, None);
+    log_in(&mut session, "bob").await.unwrap();
+    let session = next_request(session, &store, &config).await;
+    let user = AuthenticatedUser::extract(&session, &users())
+        .await
+        .unwrap();
+
+    let err: AuthorizationError = user.require(|u| u.is_admin).unwrap_err();
+    assert_eq!(err.into_response().status().as_u16(), 403);
+    assert!(user.require(|u| u.id == "bob").is_ok());
+}
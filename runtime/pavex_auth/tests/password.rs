@@ -0,0 +1,87 @@
+use pavex_auth::{PasswordHasher, PasswordHashingConfig, errors::PasswordHashError};
+
+/// A cheap configuration, to keep the test suite fast.
+fn config(iterations: u32) -> PasswordHashingConfig {
+    let mut config = PasswordHashingConfig::default();
+    config.memory_cost = 64;
+    config.iterations = iterations;
+    config
+}
+
+#[test]
+fn hashes_can_be_verified() {
+    let hasher = PasswordHasher::new(&config(1));
+    let hash = hasher.hash("correct horse battery staple").unwrap();
+
+    assert!(hash.starts_with("$argon2id$"));
+    assert!(
+        hasher
+            .verify("correct horse battery staple", &hash)
+            .unwrap()
+    );
+    assert!(!hasher.verify("Tr0ub4dor&3", &hash).unwrap());
+}
+
+#[test]
+fn hashes_are_salted() {
+    let hasher = PasswordHasher::new(&config(1));
+
+    let first = hasher.hash("password").unwrap();
+    let second = hasher.hash("password").unwrap();
+
+    assert_ne!(first, second);
+}
+
+#[test]
+fn missing_hashes_never_match() {
+    let hasher = PasswordHasher::new(&config(1));
+    let hash = hasher.hash("password").unwrap();
+
+    assert!(hasher.verify_or_simulate("password", Some(&hash)).unwrap());
+    assert!(!hasher.verify_or_simulate("password", None).unwrap());
+}
+
+#[test]
+fn malformed_hashes_are_rejected() {
+    let hasher = PasswordHasher::new(&config(1));
+
+    let err = hasher.verify("password", "not a hash").unwrap_err();
+
+    assert!(matches!(err, PasswordHashError::MalformedHash(_)));
+}
+
+#[test]
+fn hashes_computed_with_outdated_parameters_need_rehashing() {
+    let old = PasswordHasher::new(&config(1));
+    let new = PasswordHasher::new(&config(2));
+    let hash = old.hash("password").unwrap();
+
+    assert!(!old.needs_rehash(&hash).unwrap());
+    assert!(new.needs_rehash(&hash).unwrap());
+    // Old hashes can still be verified.
+    assert!(new.verify("password", &hash).unwrap());
+}
+
+#[test]
+fn invalid_parameters_are_reported() {
+    let mut config = config(1);
+    config.parallelism = 0;
+    let hasher = PasswordHasher::new(&config);
+
+    let err = hasher.hash("password").unwrap_err();
+
+    assert!(matches!(err, PasswordHashError::InvalidParameters(_)));
+}
+
+#[test]
+fn hashing_cost_can_be_configured() {
+    let config: PasswordHashingConfig =
+        serde_json::from_str(r#"{"memory_cost": 65536, "iterations": 3}"#).unwrap();
+    assert_eq!(config.memory_cost, 65536);
+    assert_eq!(config.iterations, 3);
+    assert_eq!(config.parallelism, 1);
+
+    let default = PasswordHashingConfig::default();
+    assert_eq!(default.memory_cost, 19 * 1024);
+    assert_eq!(default.iterations, 2);
+}
@@ -9,6 +9,7 @@ use syn::Ident;
 pub struct InputSchema {
     pub path: String,
     pub id: Option<Ident>,
+    pub pavex: Option<Ident>,
     pub error_handler: Option<String>,
     pub allow: Option<ShorthandAllows>,
 }
@@ -26,12 +27,14 @@ impl TryFrom<InputSchema> for Properties {
             path,
             error_handler,
             id,
+            pavex,
             allow,
         } = input;
         let allow_error_fallback = allow.as_ref().map(|a| a.error_fallback.is_present());
         Ok(Properties {
             path,
             id,
+            pavex,
             error_handler,
             allow_error_fallback,
         })
@@ -43,6 +46,7 @@ struct Properties {
     pub path: String,
     pub error_handler: Option<String>,
     pub id: Option<Ident>,
+    pub pavex: Option<Ident>,
     pub allow_error_fallback: Option<bool>,
 }
 
@@ -133,6 +137,7 @@ fn emit(
         path,
         error_handler,
         id,
+        pavex,
         allow_error_fallback,
     } = properties;
 
@@ -159,7 +164,7 @@ fn emit(
     AnnotationCodegen {
         id_def: Some(callable_id_def(
             &id,
-            None,
+            pavex.as_ref(),
             "route",
             "Route",
             "a route",
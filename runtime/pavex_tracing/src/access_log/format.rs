@@ -0,0 +1,283 @@
+use std::fmt::Write;
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use pavex::time::Timestamp;
+use pavex::time::tz::TimeZone;
+
+use super::AccessLogError;
+
+/// The data captured for each request.
+pub(super) struct AccessLogRecord {
+    pub(super) timestamp: Timestamp,
+    pub(super) peer_addr: SocketAddr,
+    pub(super) method: String,
+    pub(super) target: String,
+    pub(super) protocol: &'static str,
+    pub(super) status: u16,
+    /// `None` if the size of the response body isn't known upfront.
+    pub(super) response_size: Option<u64>,
+    pub(super) duration: Duration,
+    pub(super) user_agent: Option<String>,
+    pub(super) referer: Option<String>,
+    pub(super) request_id: String,
+}
+
+/// The placeholders available in custom templates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Timestamp,
+    PeerAddr,
+    PeerIp,
+    Method,
+    Target,
+    Protocol,
+    Status,
+    ResponseSize,
+    DurationMs,
+    UserAgent,
+    Referer,
+    RequestId,
+}
+
+impl Field {
+    const ALL: [(&'static str, Field); 12] = [
+        ("timestamp", Field::Timestamp),
+        ("peer_addr", Field::PeerAddr),
+        ("peer_ip", Field::PeerIp),
+        ("method", Field::Method),
+        ("target", Field::Target),
+        ("protocol", Field::Protocol),
+        ("status", Field::Status),
+        ("response_size", Field::ResponseSize),
+        ("duration_ms", Field::DurationMs),
+        ("user_agent", Field::UserAgent),
+        ("referer", Field::Referer),
+        ("request_id", Field::RequestId),
+    ];
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Literal(String),
+    Field(Field),
+}
+
+/// A parsed custom template—e.g. `{method} {target} {status} {duration_ms}ms`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct Template(Vec<Segment>);
+
+impl Template {
+    /// Parse a template. Use `{{` and `}}` for literal braces.
+    pub(super) fn parse(template: &str) -> Result<Self, AccessLogError> {
+        let invalid = |reason: String| AccessLogError::InvalidTemplate {
+            template: template.to_owned(),
+            reason,
+        };
+        let mut segments = Vec::new();
+        let mut literal = String::new();
+        let mut chars = template.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '{' => {
+                    let mut name = String::new();
+                    let mut closed = false;
+                    for c in chars.by_ref() {
+                        if c == '}' {
+                            closed = true;
+                            break;
+                        }
+                        name.push(c);
+                    }
+                    if !closed {
+                        return Err(invalid(format!("`{{{name}` is never closed")));
+                    }
+                    let Some((_, field)) = Field::ALL.iter().find(|(n, _)| *n == name) else {
+                        let known: Vec<_> = Field::ALL.iter().map(|(n, _)| *n).collect();
+                        return Err(invalid(format!(
+                            "`{{{name}}}` is not a known placeholder. Use one of: {}",
+                            known.join(", ")
+                        )));
+                    };
+                    if !literal.is_empty() {
+                        segments.push(Segment::Literal(std::mem::take(&mut literal)));
+                    }
+                    segments.push(Segment::Field(*field));
+                }
+                '}' => {
+                    return Err(invalid(
+                        "unmatched `}`. Use `}}` for a literal brace".into(),
+                    ));
+                }
+                c => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            segments.push(Segment::Literal(literal));
+        }
+        Ok(Self(segments))
+    }
+}
+
+/// The timestamp format used by the Common Log Format—e.g. `10/Oct/2000:13:55:36 +0000`.
+fn clf_timestamp(timestamp: Timestamp) -> String {
+    timestamp
+        .to_zoned(TimeZone::UTC)
+        .strftime("%d/%b/%Y:%H:%M:%S %z")
+        .to_string()
+}
+
+/// Escape a value to be used within double quotes in Common/Combined Log Format.
+fn quoted(value: Option<&str>) -> String {
+    match value {
+        None => "-".to_owned(),
+        Some(value) => value
+            .chars()
+            .flat_map(|c| match c {
+                '"' => vec!['\\', '"'],
+                '\\' => vec!['\\', '\\'],
+                c if c.is_control() => c.escape_default().collect(),
+                c => vec![c],
+            })
+            .collect(),
+    }
+}
+
+impl AccessLogRecord {
+    /// Format the record in [Common Log Format](https://httpd.apache.org/docs/current/logs.html#common).
+    pub(super) fn common(&self) -> String {
+        let size = match self.response_size {
+            Some(size) if size > 0 => size.to_string(),
+            _ => "-".to_owned(),
+        };
+        format!(
+            "{} - - [{}] \"{} {} {}\" {} {size}",
+            self.peer_addr.ip(),
+            clf_timestamp(self.timestamp),
+            self.method,
+            quoted(Some(&self.target)),
+            self.protocol,
+            self.status,
+        )
+    }
+
+    /// Format the record in [Combined Log Format](https://httpd.apache.org/docs/current/logs.html#combined).
+    pub(super) fn combined(&self) -> String {
+        format!(
+            "{} \"{}\" \"{}\"",
+            self.common(),
+            quoted(self.referer.as_deref()),
+            quoted(self.user_agent.as_deref()),
+        )
+    }
+
+    /// Format the record as a single-line JSON object.
+    pub(super) fn json(&self) -> String {
+        serde_json::json!({
+            "timestamp": self.timestamp.to_string(),
+            "peer_addr": self.peer_addr.to_string(),
+            "method": self.method,
+            "target": self.target,
+            "protocol": self.protocol,
+            "status": self.status,
+            "response_size": self.response_size,
+            "duration_ms": self.duration_ms(),
+            "user_agent": self.user_agent,
+            "referer": self.referer,
+            "request_id": self.request_id,
+        })
+        .to_string()
+    }
+
+    /// Format the record according to a custom template.
+    pub(super) fn template(&self, template: &Template) -> String {
+        let mut out = String::new();
+        for segment in &template.0 {
+            // Writing to a `String` never fails.
+            let _ = match segment {
+                Segment::Literal(literal) => out.write_str(literal),
+                Segment::Field(field) => match field {
+                    Field::Timestamp => write!(out, "{}", self.timestamp),
+                    Field::PeerAddr => write!(out, "{}", self.peer_addr),
+                    Field::PeerIp => write!(out, "{}", self.peer_addr.ip()),
+                    Field::Method => out.write_str(&self.method),
+                    Field::Target => out.write_str(&self.target),
+                    Field::Protocol => out.write_str(self.protocol),
+                    Field::Status => write!(out, "{}", self.status),
+                    Field::ResponseSize => match self.response_size {
+                        Some(size) => write!(out, "{size}"),
+                        None => out.write_str("-"),
+                    },
+                    Field::DurationMs => write!(out, "{}", self.duration_ms()),
+                    Field::UserAgent => out.write_str(self.user_agent.as_deref().unwrap_or("-")),
+                    Field::Referer => out.write_str(self.referer.as_deref().unwrap_or("-")),
+                    Field::RequestId => out.write_str(&self.request_id),
+                },
+            };
+        }
+        out
+    }
+
+    /// The duration in milliseconds, with microsecond precision.
+    fn duration_ms(&self) -> f64 {
+        self.duration.as_micros() as f64 / 1000.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record() -> AccessLogRecord {
+        AccessLogRecord {
+            timestamp: "2000-10-10T13:55:36.5Z".parse().unwrap(),
+            peer_addr: "127.0.0.1:5400".parse().unwrap(),
+            method: "GET".into(),
+            target: "/apache_pb.gif?size=\"big\"".into(),
+            protocol: "HTTP/1.1",
+            status: 200,
+            response_size: Some(2326),
+            duration: Duration::from_micros(1500),
+            user_agent: Some("Mozilla/4.08".into()),
+            referer: None,
+            request_id: "sri_01h455vb4pex5vsknk084sn02q".into(),
+        }
+    }
+
+    #[test]
+    fn combined_log_format() {
+        assert_eq!(
+            record().combined(),
+            r#"127.0.0.1 - - [10/Oct/2000:13:55:36 +0000] "GET /apache_pb.gif?size=\"big\" HTTP/1.1" 200 2326 "-" "Mozilla/4.08""#
+        );
+    }
+
+    #[test]
+    fn json_lines() {
+        let line: serde_json::Value = serde_json::from_str(&record().json()).unwrap();
+        assert_eq!(line["timestamp"], "2000-10-10T13:55:36.5Z");
+        assert_eq!(line["peer_addr"], "127.0.0.1:5400");
+        assert_eq!(line["duration_ms"], 1.5);
+        assert_eq!(line["referer"], serde_json::Value::Null);
+        assert_eq!(line["request_id"], "sri_01h455vb4pex5vsknk084sn02q");
+    }
+
+    #[test]
+    fn custom_templates() {
+        let template = Template::parse("{{{peer_ip}}} {method} {status} {duration_ms}ms").unwrap();
+        assert_eq!(record().template(&template), "{127.0.0.1} GET 200 1.5ms");
+
+        let err = Template::parse("{method} {path}").unwrap_err();
+        assert!(err.to_string().contains("`{path}`"), "{err}");
+        assert!(Template::parse("{method}}").is_err());
+        assert!(Template::parse("{method").is_err());
+    }
+}
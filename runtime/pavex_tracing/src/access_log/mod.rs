@@ -0,0 +1,336 @@
+//! Access logs: one record for each request handled by your application.
+//!
+//! # Registration
+//!
+//! Register the [`ACCESS_LOG`] middleware and the [`AccessLogger`] constructor.
+//! The middleware takes a [`RequestId`] as input: its constructor must be registered too.
+//!
+//! ```rust
+//! use pavex::{Blueprint, blueprint::from};
+//! use pavex_tracing::access_log::{ACCESS_LOG, ACCESS_LOGGER_NEW};
+//!
+//! let mut bp = Blueprint::new();
+//! // Registers the constructors for `RequestId` and `ServerRequestId`, among others.
+//! bp.import(from![pavex]);
+//! bp.constructor(ACCESS_LOGGER_NEW);
+//! bp.wrap(ACCESS_LOG);
+//! ```
+//!
+//! Register the middleware as early as possible, to capture the time spent
+//! in the other middlewares.
+//!
+//! # Configuration
+//!
+//! The format and the destination of the records are controlled by [`AccessLogConfig`].
+//! By default, records are emitted as `tracing` events in
+//! [Combined Log Format](https://httpd.apache.org/docs/current/logs.html#combined).
+//!
+//! ```yaml
+//! access_log:
+//!   format: json
+//!   output:
+//!     file:
+//!       path: logs/access.log
+//!       rotation: daily
+//!       max_files: 7
+//! ```
+//!
+//! # Available data
+//!
+//! Each record captures the peer address (from [`ConnectionInfo`]), the method,
+//! the target, the protocol version, the response status, the size of the response body
+//! (when known upfront), the duration, the `User-Agent` and `Referer` headers and
+//! the [`RequestId`]—i.e. the id assigned by your gateway, if any, or the
+//! [`ServerRequestId`](pavex::telemetry::ServerRequestId) otherwise.
+//!
+//! The Common and Combined formats follow their standard definitions, for compatibility
+//! with existing tooling. Use [`AccessLogFormat::Json`] or a [custom template](AccessLogFormat::Template)
+//! to include the duration and the request id.
+use std::future::IntoFuture;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Instant;
+
+use pavex::Response;
+use pavex::connection::ConnectionInfo;
+use pavex::http::{Version, header};
+use pavex::middleware::Next;
+use pavex::request::RequestHead;
+use pavex::response::body::raw::RawBody;
+use pavex::telemetry::RequestId;
+use pavex::time::Timestamp;
+use pavex::{methods, wrap};
+
+use format::{AccessLogRecord, Template};
+use writer::FileWriter;
+
+mod format;
+mod writer;
+
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+/// Configure the format and the destination of access log records.
+#[pavex::config(key = "access_log", default_if_missing)]
+pub struct AccessLogConfig {
+    /// How records are formatted.
+    ///
+    /// By default, it's set to [`AccessLogFormat::Combined`].
+    #[serde(default)]
+    pub format: AccessLogFormat,
+    /// Where records are written.
+    ///
+    /// By default, it's set to [`AccessLogOutput::Tracing`].
+    #[serde(default)]
+    pub output: AccessLogOutput,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+/// The format of access log records.
+pub enum AccessLogFormat {
+    /// [Common Log Format](https://httpd.apache.org/docs/current/logs.html#common).
+    ///
+    /// E.g. `127.0.0.1 - - [10/Oct/2000:13:55:36 +0000] "GET /index.html HTTP/1.1" 200 2326`.
+    Common,
+    /// [Combined Log Format](https://httpd.apache.org/docs/current/logs.html#combined): Common Log Format,
+    /// followed by the `Referer` and `User-Agent` headers.
+    #[default]
+    Combined,
+    /// One JSON object per line ([JSON Lines](https://jsonlines.org/)), with the
+    /// `timestamp`, `peer_addr`, `method`, `target`, `protocol`, `status`, `response_size`,
+    /// `duration_ms`, `user_agent`, `referer` and `request_id` keys.
+    Json,
+    /// A custom template, where placeholders are replaced with the corresponding values—e.g.
+    /// `{peer_ip} {method} {target} {status} {duration_ms}ms`.
+    ///
+    /// The available placeholders are the keys of [`AccessLogFormat::Json`], plus `peer_ip`.
+    /// Use `{{` and `}}` for literal braces.
+    ///
+    /// ```yaml
+    /// access_log:
+    ///   format:
+    ///     template: "{peer_ip} {method} {target} {status} {duration_ms}ms"
+    /// ```
+    Template(String),
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+/// The destination of access log records.
+pub enum AccessLogOutput {
+    /// Emit an `INFO` event for each record, with `access_log` as its target.
+    ///
+    /// Use the target to route the records to a dedicated destination via your `tracing`
+    /// subscriber.
+    #[default]
+    Tracing,
+    /// Append the records to a file, without blocking request processing.
+    File(FileOutputConfig),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+/// Configure [`AccessLogOutput::File`].
+pub struct FileOutputConfig {
+    /// The path to the log file.
+    ///
+    /// If the file is rotated, the period is appended to the path—e.g.
+    /// `access.log.2024-05-01` for daily rotation.
+    pub path: PathBuf,
+    /// How often a new file is started.
+    ///
+    /// By default, it's set to [`Rotation::Never`].
+    #[serde(default)]
+    pub rotation: Rotation,
+    /// The maximum number of rotated files to keep. The oldest ones are deleted first.
+    ///
+    /// Only files named after `path`, with a suffix matching the rotation period,
+    /// are taken into account. Other files in the same directory are never deleted.
+    ///
+    /// By default, all files are kept.
+    #[serde(default)]
+    pub max_files: Option<usize>,
+    /// The number of records that can be queued for writing.
+    /// Records are dropped, with a warning, if the queue is full.
+    ///
+    /// By default, it's set to 8192.
+    #[serde(default = "default_buffer_size")]
+    pub buffer_size: usize,
+}
+
+fn default_buffer_size() -> usize {
+    8192
+}
+
+impl FileOutputConfig {
+    /// Write to the file at `path`, without rotation.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            rotation: Rotation::default(),
+            max_files: None,
+            buffer_size: default_buffer_size(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+/// How often the access log file is rotated. Periods are computed in UTC.
+pub enum Rotation {
+    /// Always write to the same file.
+    #[default]
+    Never,
+    /// Start a new file every hour.
+    Hourly,
+    /// Start a new file every day.
+    Daily,
+}
+
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+/// The error returned by [`AccessLogger::new`] if the configuration can't be honored.
+pub enum AccessLogError {
+    /// The custom template is invalid.
+    #[error("The access log template `{template}` is invalid: {reason}")]
+    InvalidTemplate {
+        /// The template, as configured.
+        template: String,
+        /// Why it's invalid.
+        reason: String,
+    },
+    /// The log file can't be opened.
+    #[error("Failed to open the access log file at `{}`", path.display())]
+    Io {
+        /// The path to the log file.
+        path: PathBuf,
+        #[source]
+        /// The underlying I/O error.
+        source: std::io::Error,
+    },
+}
+
+/// Formats access log records and writes them to the configured destination.
+///
+/// Check out the [module documentation](self) for more details.
+#[derive(Debug, Clone)]
+pub struct AccessLogger {
+    inner: Arc<Inner>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    format: Format,
+    sink: Sink,
+}
+
+#[derive(Debug)]
+enum Format {
+    Common,
+    Combined,
+    Json,
+    Template(Template),
+}
+
+#[derive(Debug)]
+enum Sink {
+    Tracing,
+    File(FileWriter),
+}
+
+#[methods]
+impl AccessLogger {
+    /// Create a new access logger, according to the provided configuration.
+    ///
+    /// It fails if the custom template is invalid or if the log file can't be opened.
+    #[singleton]
+    pub fn new(config: &AccessLogConfig) -> Result<Self, AccessLogError> {
+        let format = match &config.format {
+            AccessLogFormat::Common => Format::Common,
+            AccessLogFormat::Combined => Format::Combined,
+            AccessLogFormat::Json => Format::Json,
+            AccessLogFormat::Template(template) => Format::Template(Template::parse(template)?),
+        };
+        let sink = match &config.output {
+            AccessLogOutput::Tracing => Sink::Tracing,
+            AccessLogOutput::File(file) => Sink::File(FileWriter::spawn(file)?),
+        };
+        Ok(Self {
+            inner: Arc::new(Inner { format, sink }),
+        })
+    }
+
+    fn log(&self, record: &AccessLogRecord) {
+        let line = match &self.inner.format {
+            Format::Common => record.common(),
+            Format::Combined => record.combined(),
+            Format::Json => record.json(),
+            Format::Template(template) => record.template(template),
+        };
+        match &self.inner.sink {
+            Sink::Tracing => tracing::info!(target: "access_log", "{line}"),
+            Sink::File(writer) => writer.write(line),
+        }
+    }
+}
+
+/// A wrapping middleware that emits an access log record for each request,
+/// via [`AccessLogger`].
+///
+/// The duration covers the time it took to produce the response, including
+/// the middlewares that execute after this one. It doesn't include the time spent
+/// streaming the response body to the client.
+///
+/// Check out the [module documentation](self) for registration instructions.
+#[wrap]
+pub async fn access_log<C>(
+    next: Next<C>,
+    logger: &AccessLogger,
+    request_head: &RequestHead,
+    connection_info: &ConnectionInfo,
+    request_id: &RequestId,
+) -> Response
+where
+    C: IntoFuture<Output = Response>,
+{
+    let timestamp = Timestamp::now();
+    let started_at = Instant::now();
+    let response = next.into_future().await;
+    let header = |name| {
+        request_head
+            .headers
+            .get(name)
+            .map(|v| String::from_utf8_lossy(v.as_bytes()).into_owned())
+    };
+    let record = AccessLogRecord {
+        timestamp,
+        peer_addr: connection_info.peer_addr(),
+        method: request_head.method.to_string(),
+        target: request_head.target.to_string(),
+        protocol: protocol(request_head.version),
+        status: response.status().as_u16(),
+        response_size: response.body().size_hint().exact(),
+        duration: started_at.elapsed(),
+        user_agent: header(header::USER_AGENT),
+        referer: header(header::REFERER),
+        request_id: request_id.to_string(),
+    };
+    logger.log(&record);
+    response
+}
+
+fn protocol(version: Version) -> &'static str {
+    match version {
+        Version::HTTP_09 => "HTTP/0.9",
+        Version::HTTP_10 => "HTTP/1.0",
+        Version::HTTP_11 => "HTTP/1.1",
+        Version::HTTP_2 => "HTTP/2.0",
+        Version::HTTP_3 => "HTTP/3.0",
+        _ => "HTTP/?",
+    }
+}
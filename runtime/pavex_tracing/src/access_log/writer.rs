@@ -0,0 +1,273 @@
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{Receiver, SyncSender, TrySendError};
+
+use pavex::time::Timestamp;
+use pavex::time::fmt::strtime;
+use pavex::time::tz::TimeZone;
+
+use super::{AccessLogError, FileOutputConfig, Rotation};
+
+/// Writes access log lines to a file on a background thread, so that
+/// request processing never waits on disk I/O.
+///
+/// Lines are dropped, rather than buffered indefinitely, if the background thread
+/// can't keep up.
+#[derive(Debug)]
+pub(super) struct FileWriter {
+    sender: SyncSender<String>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl FileWriter {
+    pub(super) fn spawn(config: &FileOutputConfig) -> Result<Self, AccessLogError> {
+        let mut file = RotatingFile {
+            path: config.path.clone(),
+            rotation: config.rotation,
+            max_files: config.max_files,
+            period: String::new(),
+            writer: None,
+        };
+        // Fail early if the file can't be opened, rather than on the first request.
+        file.rotate_if_needed(Timestamp::now())
+            .map_err(|e| AccessLogError::Io {
+                path: config.path.clone(),
+                source: e,
+            })?;
+        let (sender, receiver) = std::sync::mpsc::sync_channel(config.buffer_size.max(1));
+        let dropped = Arc::new(AtomicU64::new(0));
+        let dropped_ = dropped.clone();
+        std::thread::Builder::new()
+            .name("access-log-writer".into())
+            .spawn(move || file.run(receiver, &dropped_))
+            .map_err(|e| AccessLogError::Io {
+                path: config.path.clone(),
+                source: e,
+            })?;
+        Ok(Self { sender, dropped })
+    }
+
+    /// Queue a line to be written.
+    pub(super) fn write(&self, line: String) {
+        match self.sender.try_send(line) {
+            Ok(()) => {}
+            Err(TrySendError::Full(_)) | Err(TrySendError::Disconnected(_)) => {
+                self.dropped.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+}
+
+struct RotatingFile {
+    path: PathBuf,
+    rotation: Rotation,
+    max_files: Option<usize>,
+    /// The period covered by the current file—e.g. `2024-05-01` for daily rotation.
+    period: String,
+    writer: Option<BufWriter<File>>,
+}
+
+impl RotatingFile {
+    fn run(mut self, receiver: Receiver<String>, dropped: &AtomicU64) {
+        // The loop ends when all senders have been dropped.
+        while let Ok(line) = receiver.recv() {
+            self.write(&line);
+            // Drain whatever is already queued before flushing.
+            while let Ok(line) = receiver.try_recv() {
+                self.write(&line);
+            }
+            let dropped = dropped.swap(0, Ordering::Relaxed);
+            if dropped > 0 {
+                tracing::warn!(
+                    dropped_lines = dropped,
+                    "The access log writer couldn't keep up: some lines were dropped"
+                );
+            }
+            if let Some(writer) = &mut self.writer
+                && let Err(e) = writer.flush()
+            {
+                tracing_log_error::log_error!(e, "Failed to flush the access log");
+            }
+        }
+    }
+
+    fn write(&mut self, line: &str) {
+        if let Err(e) = self.rotate_if_needed(Timestamp::now()) {
+            tracing_log_error::log_error!(e, "Failed to rotate the access log");
+        }
+        let Some(writer) = &mut self.writer else {
+            return;
+        };
+        if let Err(e) = writeln!(writer, "{line}") {
+            tracing_log_error::log_error!(e, "Failed to write to the access log");
+        }
+    }
+
+    fn rotate_if_needed(&mut self, now: Timestamp) -> Result<(), std::io::Error> {
+        let period = self.rotation.period(now);
+        if self.writer.is_some() && period == self.period {
+            return Ok(());
+        }
+        if let Some(mut writer) = self.writer.take() {
+            writer.flush()?;
+        }
+        let path = self.rotation.file_path(&self.path, &period);
+        if let Some(parent) = path.parent()
+            && !parent.as_os_str().is_empty()
+        {
+            std::fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        self.writer = Some(BufWriter::new(file));
+        self.period = period;
+        if let Some(max_files) = self.max_files {
+            self.prune(max_files)?;
+        }
+        Ok(())
+    }
+
+    /// Delete the oldest rotated files, keeping at most `max_files`.
+    ///
+    /// Only files named after the configured path, with a period suffix in the
+    /// format used by the configured rotation, are considered.
+    /// Anything else in the same directory is left untouched.
+    fn prune(&self, max_files: usize) -> Result<(), std::io::Error> {
+        let Some(format) = self.rotation.format() else {
+            return Ok(());
+        };
+        let (Some(dir), Some(prefix)) = (self.path.parent(), self.path.file_name()) else {
+            return Ok(());
+        };
+        let dir = if dir.as_os_str().is_empty() {
+            Path::new(".")
+        } else {
+            dir
+        };
+        let prefix = format!("{}.", prefix.to_string_lossy());
+        let mut files: Vec<_> = std::fs::read_dir(dir)?
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.file_name().is_some_and(|name| {
+                    name.to_string_lossy()
+                        .strip_prefix(&prefix)
+                        .is_some_and(|period| is_period(format, period))
+                })
+            })
+            .collect();
+        if files.len() <= max_files {
+            return Ok(());
+        }
+        // Periods sort chronologically.
+        files.sort();
+        for path in &files[..files.len() - max_files] {
+            std::fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+}
+
+/// Check if `period` was formatted using `format`—e.g. `2024-05-01` for `%Y-%m-%d`.
+fn is_period(format: &str, period: &str) -> bool {
+    strtime::parse(format, period).is_ok_and(|parsed| parsed.to_date().is_ok())
+}
+
+impl Rotation {
+    /// The `strftime` format of the period suffix, if files are rotated.
+    fn format(self) -> Option<&'static str> {
+        match self {
+            Rotation::Never => None,
+            Rotation::Daily => Some("%Y-%m-%d"),
+            Rotation::Hourly => Some("%Y-%m-%d-%H"),
+        }
+    }
+
+    /// The period `now` belongs to.
+    fn period(self, now: Timestamp) -> String {
+        match self.format() {
+            Some(format) => now.to_zoned(TimeZone::UTC).strftime(format).to_string(),
+            None => String::new(),
+        }
+    }
+
+    /// The file for a period: the configured path, with the period as a suffix.
+    fn file_path(self, path: &Path, period: &str) -> PathBuf {
+        if period.is_empty() {
+            return path.to_owned();
+        }
+        let mut path = path.as_os_str().to_owned();
+        path.push(".");
+        path.push(period);
+        path.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn files_are_rotated_and_pruned() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut file = RotatingFile {
+            path: dir.path().join("access.log"),
+            rotation: Rotation::Daily,
+            max_files: Some(2),
+            period: String::new(),
+            writer: None,
+        };
+        for day in ["2024-05-01", "2024-05-02", "2024-05-03"] {
+            let now: Timestamp = format!("{day}T12:00:00Z").parse().unwrap();
+            file.rotate_if_needed(now).unwrap();
+            writeln!(file.writer.as_mut().unwrap(), "{day}").unwrap();
+        }
+        file.writer.take().unwrap().flush().unwrap();
+
+        let mut names: Vec<_> = std::fs::read_dir(dir.path())
+            .unwrap()
+            .map(|e| e.unwrap().file_name().into_string().unwrap())
+            .collect();
+        names.sort();
+        assert_eq!(names, ["access.log.2024-05-02", "access.log.2024-05-03"]);
+        let contents = std::fs::read_to_string(dir.path().join("access.log.2024-05-03")).unwrap();
+        assert_eq!(contents, "2024-05-03\n");
+    }
+
+    #[test]
+    fn pruning_ignores_files_without_a_period_suffix() {
+        let dir = tempfile::tempdir().unwrap();
+        let unrelated = [
+            "access.log.bak",
+            "access.log.2024-05-01.gz",
+            "access.log.2024-05-01-12",
+            "access.log.2024-13-01",
+        ];
+        for name in unrelated {
+            std::fs::write(dir.path().join(name), "").unwrap();
+        }
+        let mut file = RotatingFile {
+            path: dir.path().join("access.log"),
+            rotation: Rotation::Daily,
+            max_files: Some(1),
+            period: String::new(),
+            writer: None,
+        };
+        for day in ["2024-05-02", "2024-05-03"] {
+            let now: Timestamp = format!("{day}T12:00:00Z").parse().unwrap();
+            file.rotate_if_needed(now).unwrap();
+        }
+
+        let mut names: Vec<_> = std::fs::read_dir(dir.path())
+            .unwrap()
+            .map(|e| e.unwrap().file_name().into_string().unwrap())
+            .collect();
+        names.sort();
+        let mut expected = Vec::from(unrelated);
+        expected.push("access.log.2024-05-03");
+        expected.sort();
+        assert_eq!(names, expected);
+    }
+}
@@ -21,19 +21,40 @@ use pavex::Response;
 use pavex::http::{Method, Version};
 use pavex::request::RequestHead;
 use pavex::request::path::MatchedPathPattern;
-use pavex::telemetry::ServerRequestId;
 use tracing::Value;
 
+use crate::TraceContext;
+
 // Re-export error-related logging fields and the functions to set them.
 pub use tracing_log_error::fields::*;
 
+/// The field name for the identifier of the span's parent, as received from the caller
+/// via [`TraceContext`].\
+/// It matches the naming used by OpenTelemetry's [span data model](https://opentelemetry.io/docs/specs/otel/trace/api/#span).
+///
+/// Use [`parent_span_id`] to populate the field.
+pub const PARENT_SPAN_ID: &str = "parent_span_id";
+
+/// The field name for the identifier of the span, as assigned by [`TraceContext`],
+/// according to [OpenTelemetry's log data model](https://opentelemetry.io/docs/specs/otel/logs/data-model/#field-spanid).
+///
+/// Use [`span_id`] to populate the field.
+pub const SPAN_ID: &str = "span_id";
+
+/// The field name for the identifier of the distributed trace,
+/// according to [OpenTelemetry's log data model](https://opentelemetry.io/docs/specs/otel/logs/data-model/#field-traceid).
+///
+/// Use [`trace_id`] to populate the field.
+pub const TRACE_ID: &str = "trace_id";
+
 /// The field name for the HTTP method of the incoming request (if canonical),
 /// according to [OpenTelemetry's semantic convention](https://opentelemetry.io/docs/specs/semconv/attributes-registry/http/).
 ///
 /// Use [`http_request_method`] to populate the field.
 pub const HTTP_REQUEST_METHOD: &str = "http.request.method";
 
-/// The field name to record the server-generated identifier for this request.\
+/// The field name to record the identifier for this request—either generated by the server
+/// or received from an upstream component.\
 /// This field doesn't appear in OpenTelemetry's semantic convention specification.
 ///
 /// Use [`http_request_server_id`] to populate the field.
@@ -100,7 +121,12 @@ pub fn http_request_method(request_head: &RequestHead) -> impl Value + use<> {
 }
 
 /// The canonical representation for the value in [`HTTP_REQUEST_SERVER_ID`].
-pub fn http_request_server_id(id: ServerRequestId) -> impl Value {
+///
+/// It accepts both [`ServerRequestId`] and [`RequestId`].
+///
+/// [`ServerRequestId`]: pavex::telemetry::ServerRequestId
+/// [`RequestId`]: pavex::telemetry::RequestId
+pub fn http_request_server_id<Id: std::fmt::Display>(id: Id) -> impl Value {
     tracing::field::display(id)
 }
 
@@ -131,6 +157,23 @@ pub fn network_protocol_version(request_head: &RequestHead) -> impl Value + use<
     }
 }
 
+/// The canonical representation for the value in [`PARENT_SPAN_ID`].
+///
+/// The field is left unset if the trace was started by the current request.
+pub fn parent_span_id(trace_context: &TraceContext) -> impl Value + use<> {
+    trace_context.parent_span_id().map(tracing::field::display)
+}
+
+/// The canonical representation for the value in [`SPAN_ID`].
+pub fn span_id(trace_context: &TraceContext) -> impl Value + use<> {
+    tracing::field::display(trace_context.span_id())
+}
+
+/// The canonical representation for the value in [`TRACE_ID`].
+pub fn trace_id(trace_context: &TraceContext) -> impl Value + use<> {
+    tracing::field::display(trace_context.trace_id())
+}
+
 /// The canonical representation for the value in [`URL_PATH`].
 pub fn url_path(request_head: &RequestHead) -> impl Value + '_ {
     request_head.target.path()
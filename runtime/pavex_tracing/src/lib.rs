@@ -2,9 +2,12 @@
 //!
 //! [`tracing`]:https://docs.rs/tracing/0.1.40/tracing
 //! [Pavex]: https://pavex.dev
+pub mod access_log;
 pub mod fields;
 mod mw;
 mod root_span;
+mod trace_context;
 
 pub use mw::{LOGGER, logger};
 pub use root_span::RootSpan;
+pub use trace_context::{InvalidIdentifier, SpanId, TraceContext, TraceId, TracePropagationConfig};
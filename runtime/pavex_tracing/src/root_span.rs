@@ -82,6 +82,83 @@
 /// your field names (and the way their values are represented) in line with a "standard"
 /// Pavex application.
 ///
+/// # Request ids
+///
+/// Take a [`RequestId`](pavex::telemetry::RequestId) as input, rather than a
+/// [`ServerRequestId`](pavex::telemetry::ServerRequestId), to record the id assigned by your
+/// gateway (if any) instead of a freshly generated one:
+///
+/// ```rust
+/// use pavex::request_scoped;
+/// use pavex::telemetry::RequestId;
+/// use pavex_tracing::RootSpan;
+/// use pavex_tracing::fields::{HTTP_REQUEST_SERVER_ID, http_request_server_id};
+///
+/// #[request_scoped(clone_if_necessary)]
+/// pub fn root_span(request_id: &RequestId) -> RootSpan {
+///     let span = tracing::info_span!(
+///         "HTTP request",
+///         { HTTP_REQUEST_SERVER_ID } = http_request_server_id(request_id),
+///     );
+///     RootSpan::new(span)
+/// }
+/// ```
+///
+/// # Distributed tracing
+///
+/// Add a [`TraceContext`](crate::TraceContext) input to your constructor to continue the
+/// trace propagated by the caller, via W3C Trace Context (or B3) headers.
+/// Record its identifiers on the root span to correlate it with the spans emitted
+/// by the other services involved in the trace:
+///
+/// ```rust
+/// use pavex::request::RequestHead;
+/// use pavex::request_scoped;
+/// use pavex_tracing::fields::{
+///     HTTP_REQUEST_METHOD, PARENT_SPAN_ID, SPAN_ID, TRACE_ID, http_request_method,
+///     parent_span_id, span_id, trace_id,
+/// };
+/// use pavex_tracing::{RootSpan, TraceContext};
+///
+/// #[request_scoped(clone_if_necessary)]
+/// pub fn root_span(request_head: &RequestHead, trace_context: &TraceContext) -> RootSpan {
+///     let span = tracing::info_span!(
+///         "HTTP request",
+///         { HTTP_REQUEST_METHOD } = http_request_method(request_head),
+///         { TRACE_ID } = trace_id(trace_context),
+///         { SPAN_ID } = span_id(trace_context),
+///         { PARENT_SPAN_ID } = parent_span_id(trace_context),
+///     );
+///     RootSpan::new(span)
+/// }
+/// ```
+///
+/// ## OpenTelemetry
+///
+/// `tracing` has no notion of a remote parent: recording the trace context as fields
+/// doesn't make the root span a child of the caller's span.
+/// If you export your spans via [`tracing-opentelemetry`](https://docs.rs/tracing-opentelemetry),
+/// enable the `opentelemetry` feature of `pavex_tracing` and use `RootSpan::set_parent`
+/// to continue the caller's trace:
+///
+/// ```rust,ignore
+/// #[request_scoped(clone_if_necessary)]
+/// pub fn root_span(request_head: &RequestHead, trace_context: &TraceContext) -> RootSpan {
+///     let span = tracing::info_span!(
+///         "HTTP request",
+///         { HTTP_REQUEST_METHOD } = http_request_method(request_head),
+///     );
+///     let root_span = RootSpan::new(span);
+///     root_span.set_parent(trace_context);
+///     root_span
+/// }
+/// ```
+///
+/// The exported span is assigned its own identifier by `tracing-opentelemetry`, which doesn't
+/// match [`TraceContext::span_id`](crate::TraceContext::span_id).
+/// With the `opentelemetry` feature enabled, [`TraceContext::inject`](crate::TraceContext::inject)
+/// propagates the identifiers of the current OpenTelemetry span instead.
+///
 /// [`tracing::Span`]: https://docs.rs/tracing/0.1.40/tracing/struct.Span.html
 #[derive(Debug, Clone)]
 pub struct RootSpan(tracing::Span);
@@ -115,4 +192,24 @@ impl RootSpan {
     pub fn into_inner(self) -> tracing::Span {
         self.0
     }
+
+    /// Make the caller's span, as extracted in [`TraceContext`], the OpenTelemetry parent
+    /// of the root span.
+    ///
+    /// It has no effect if the request didn't carry a trace context, if the root span is
+    /// disabled or if spans are not exported via `tracing-opentelemetry`.
+    /// It must be called before the root span is entered for the first time.
+    ///
+    /// [`TraceContext`]: crate::TraceContext
+    #[cfg(feature = "opentelemetry")]
+    pub fn set_parent(&self, trace_context: &crate::TraceContext) {
+        use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+        let Some(parent) = trace_context.remote_parent() else {
+            return;
+        };
+        if let Err(e) = self.0.set_parent(parent) {
+            tracing::trace!("Failed to set the OpenTelemetry parent of the root span: {e}");
+        }
+    }
 }
@@ -0,0 +1,625 @@
+use std::fmt;
+use std::str::FromStr;
+
+use pavex::http::{HeaderMap, HeaderName, HeaderValue};
+use pavex::methods;
+use pavex::request::RequestHead;
+
+/// The name of the W3C header carrying the trace and parent span identifiers.
+static TRACEPARENT: HeaderName = HeaderName::from_static("traceparent");
+/// The name of the W3C header carrying vendor-specific trace data.
+static TRACESTATE: HeaderName = HeaderName::from_static("tracestate");
+/// The name of the single-header B3 format.
+static B3: HeaderName = HeaderName::from_static("b3");
+static B3_TRACE_ID: HeaderName = HeaderName::from_static("x-b3-traceid");
+static B3_SPAN_ID: HeaderName = HeaderName::from_static("x-b3-spanid");
+static B3_SAMPLED: HeaderName = HeaderName::from_static("x-b3-sampled");
+static B3_FLAGS: HeaderName = HeaderName::from_static("x-b3-flags");
+
+/// The `sampled` bit in the `trace-flags` field of `traceparent`.
+const SAMPLED: u8 = 0x01;
+
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+/// Configure how [`TraceContext`] is extracted from incoming requests.
+///
+/// # Example
+///
+/// ```yaml
+/// trace_propagation:
+///   b3: true
+/// ```
+#[pavex::config(key = "trace_propagation", default_if_missing)]
+pub struct TracePropagationConfig {
+    /// Whether to continue traces propagated via [B3 headers](https://github.com/openzipkin/b3-propagation),
+    /// either in the single-header (`b3`) or in the multi-header (`X-B3-*`) format.
+    ///
+    /// The W3C `traceparent` header takes precedence when both are present.
+    /// By default, B3 headers are ignored.
+    #[serde(default)]
+    pub b3: bool,
+}
+
+/// The distributed tracing context of the current request, following the
+/// [W3C Trace Context](https://www.w3.org/TR/trace-context/) specification.
+///
+/// # Extraction
+///
+/// [`TraceContext::extract`] continues the trace propagated by the caller via the `traceparent`
+/// and `tracestate` headers—or via B3 headers, if enabled in [`TracePropagationConfig`].
+/// If the request doesn't carry a valid trace context, a new trace is started.
+///
+/// Either way, the current request is assigned a new [`span_id`](TraceContext::span_id),
+/// while the span id received from the caller is exposed as
+/// [`parent_span_id`](TraceContext::parent_span_id).
+/// Use the [`TRACE_ID`], [`SPAN_ID`] and [`PARENT_SPAN_ID`] fields to record them
+/// on your [`RootSpan`](crate::RootSpan).
+///
+/// The trace context is not attached to any `tracing` span: if you export your spans
+/// via OpenTelemetry, enable the `opentelemetry` feature and set the parent of your root span
+/// with `RootSpan::set_parent`.
+/// Check out [`RootSpan`'s documentation](crate::RootSpan#opentelemetry) for an example.
+///
+/// # Propagation
+///
+/// Use [`TraceContext::inject`] to propagate the trace context to the services you call.
+/// With the `opentelemetry` feature enabled, the identifiers of the current OpenTelemetry span
+/// are propagated, if there is one.
+///
+/// ```rust
+/// use pavex::http::HeaderMap;
+/// use pavex_tracing::TraceContext;
+///
+/// fn outgoing_headers(trace_context: &TraceContext) -> HeaderMap {
+///     let mut headers = HeaderMap::new();
+///     // Sets `traceparent` and, if there is one, `tracestate`.
+///     trace_context.inject(&mut headers);
+///     headers
+/// }
+/// ```
+///
+/// [`TRACE_ID`]: crate::fields::TRACE_ID
+/// [`SPAN_ID`]: crate::fields::SPAN_ID
+/// [`PARENT_SPAN_ID`]: crate::fields::PARENT_SPAN_ID
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceContext {
+    trace_id: TraceId,
+    span_id: SpanId,
+    parent_span_id: Option<SpanId>,
+    sampled: bool,
+    trace_state: Option<HeaderValue>,
+}
+
+#[methods]
+impl TraceContext {
+    /// Extract the trace context from the headers of the incoming request.
+    ///
+    /// A new trace is started if the request doesn't carry a valid trace context.
+    #[request_scoped(clone_if_necessary)]
+    pub fn extract(request_head: &RequestHead, config: &TracePropagationConfig) -> Self {
+        Self::from_headers(&request_head.headers, config).unwrap_or_else(Self::new_trace)
+    }
+
+    /// Continue the trace context carried by `headers`, if there is a valid one.
+    ///
+    /// The returned context has a new [`span_id`](TraceContext::span_id).
+    pub fn from_headers(headers: &HeaderMap, config: &TracePropagationConfig) -> Option<Self> {
+        if let Some(context) = w3c(headers) {
+            return Some(context);
+        }
+        if config.b3 {
+            return b3(headers);
+        }
+        None
+    }
+
+    /// Start a new trace, with randomly generated identifiers.
+    pub fn new_trace() -> Self {
+        Self {
+            trace_id: TraceId::random(),
+            span_id: SpanId::random(),
+            parent_span_id: None,
+            sampled: true,
+            trace_state: None,
+        }
+    }
+
+    /// The identifier of the trace.
+    pub fn trace_id(&self) -> TraceId {
+        self.trace_id
+    }
+
+    /// The identifier of the span for the current request.
+    pub fn span_id(&self) -> SpanId {
+        self.span_id
+    }
+
+    /// The identifier of the caller's span, if the trace was propagated by the caller.
+    pub fn parent_span_id(&self) -> Option<SpanId> {
+        self.parent_span_id
+    }
+
+    /// Whether the caller may have recorded the trace.
+    ///
+    /// It's always `true` for new traces.
+    pub fn is_sampled(&self) -> bool {
+        self.sampled
+    }
+
+    /// The vendor-specific data received via the `tracestate` header, if any.
+    pub fn trace_state(&self) -> Option<&HeaderValue> {
+        self.trace_state.as_ref()
+    }
+
+    /// The value of the `traceparent` header for outgoing requests.
+    ///
+    /// The span for the current request is used as the parent span.
+    /// With the `opentelemetry` feature enabled, the current OpenTelemetry span is used instead,
+    /// if there is one.
+    pub fn traceparent(&self) -> HeaderValue {
+        let (trace_id, span_id, sampled) = self.outgoing();
+        let flags = if sampled { SAMPLED } else { 0 };
+        let value = format!("00-{trace_id}-{span_id}-{flags:02x}");
+        // Hex digits and dashes are always valid in a header value.
+        HeaderValue::from_str(&value).unwrap()
+    }
+
+    /// The value of the single-header B3 format for outgoing requests.
+    ///
+    /// The span for the current request is used as the parent span.
+    /// With the `opentelemetry` feature enabled, the current OpenTelemetry span is used instead,
+    /// if there is one.
+    pub fn b3(&self) -> HeaderValue {
+        let (trace_id, span_id, sampled) = self.outgoing();
+        let value = format!("{trace_id}-{span_id}-{}", u8::from(sampled));
+        // Hex digits and dashes are always valid in a header value.
+        HeaderValue::from_str(&value).unwrap()
+    }
+
+    /// Set the `traceparent` and `tracestate` headers to propagate the trace context
+    /// to an outgoing request.
+    ///
+    /// Existing values for those headers are replaced.
+    pub fn inject(&self, headers: &mut HeaderMap) {
+        headers.insert(TRACEPARENT.clone(), self.traceparent());
+        match &self.trace_state {
+            Some(trace_state) => {
+                headers.insert(TRACESTATE.clone(), trace_state.clone());
+            }
+            None => {
+                headers.remove(&TRACESTATE);
+            }
+        }
+    }
+
+    /// Set the `b3` header to propagate the trace context to an outgoing request,
+    /// for services that don't support W3C Trace Context.
+    ///
+    /// Existing values for the header are replaced.
+    pub fn inject_b3(&self, headers: &mut HeaderMap) {
+        headers.insert(B3.clone(), self.b3());
+    }
+
+    /// The OpenTelemetry context of the caller's span, to be used as the parent of
+    /// the root span.
+    ///
+    /// It returns `None` if the request didn't carry a trace context.
+    #[cfg(feature = "opentelemetry")]
+    pub fn remote_parent(&self) -> Option<opentelemetry::Context> {
+        use opentelemetry::trace::{self, TraceContextExt};
+
+        let parent_span_id = self.parent_span_id?;
+        let flags = if self.sampled {
+            trace::TraceFlags::SAMPLED
+        } else {
+            trace::TraceFlags::default()
+        };
+        let trace_state = self
+            .trace_state
+            .as_ref()
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse().ok())
+            .unwrap_or_default();
+        let parent = trace::SpanContext::new(
+            trace::TraceId::from_bytes(self.trace_id.to_bytes()),
+            trace::SpanId::from_bytes(parent_span_id.to_bytes()),
+            flags,
+            true,
+            trace_state,
+        );
+        Some(opentelemetry::Context::new().with_remote_span_context(parent))
+    }
+
+    /// The trace id, parent span id and sampling decision to propagate to outgoing requests.
+    #[cfg(not(feature = "opentelemetry"))]
+    fn outgoing(&self) -> (TraceId, SpanId, bool) {
+        (self.trace_id, self.span_id, self.sampled)
+    }
+
+    /// The trace id, parent span id and sampling decision to propagate to outgoing requests.
+    ///
+    /// We use the current OpenTelemetry span, if there is one, since its identifier
+    /// is the one that gets exported.
+    #[cfg(feature = "opentelemetry")]
+    fn outgoing(&self) -> (TraceId, SpanId, bool) {
+        use opentelemetry::trace::TraceContextExt;
+        use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+        let context = tracing::Span::current().context();
+        let span = context.span();
+        let span_context = span.span_context();
+        if span_context.is_valid() {
+            return (
+                TraceId(span_context.trace_id().to_bytes()),
+                SpanId(span_context.span_id().to_bytes()),
+                span_context.is_sampled(),
+            );
+        }
+        (self.trace_id, self.span_id, self.sampled)
+    }
+
+    fn continue_trace(trace_id: TraceId, parent_span_id: SpanId, sampled: bool) -> Self {
+        Self {
+            trace_id,
+            span_id: SpanId::random(),
+            parent_span_id: Some(parent_span_id),
+            sampled,
+            trace_state: None,
+        }
+    }
+}
+
+/// Parse the W3C `traceparent` and `tracestate` headers.
+fn w3c(headers: &HeaderMap) -> Option<TraceContext> {
+    let mut values = headers.get_all(&TRACEPARENT).iter();
+    let traceparent = values.next()?;
+    // Multiple `traceparent` headers are ambiguous.
+    if values.next().is_some() {
+        return None;
+    }
+    let (trace_id, parent_span_id, flags) = parse_traceparent(traceparent.to_str().ok()?)?;
+    let mut context = TraceContext::continue_trace(trace_id, parent_span_id, flags & SAMPLED != 0);
+    context.trace_state = trace_state(headers);
+    Some(context)
+}
+
+/// Parse a `traceparent` value into its trace id, parent id and flags.
+fn parse_traceparent(value: &str) -> Option<(TraceId, SpanId, u8)> {
+    let value = value.trim_matches([' ', '\t']);
+    let version = value.get(..2).and_then(parse_hex::<1>)?[0];
+    match version {
+        0xff => return None,
+        0x00 if value.len() != 55 => return None,
+        // Future versions may append more fields, after a dash.
+        _ if value.len() > 55 && value.as_bytes()[55] != b'-' => return None,
+        _ => {}
+    }
+    let mut parts = value.get(..55)?.split('-');
+    let _version = parts.next()?;
+    let trace_id = parts.next()?.parse().ok()?;
+    let parent_id = parts.next()?.parse().ok()?;
+    let flags = parse_hex::<1>(parts.next()?)?[0];
+    Some((trace_id, parent_id, flags))
+}
+
+/// Combine the `tracestate` headers into a single value.
+fn trace_state(headers: &HeaderMap) -> Option<HeaderValue> {
+    let mut members = Vec::new();
+    for value in headers.get_all(&TRACESTATE) {
+        let value = value.to_str().ok()?;
+        members.extend(
+            value
+                .split(',')
+                .map(|m| m.trim_matches([' ', '\t']))
+                .filter(|m| !m.is_empty()),
+        );
+    }
+    if members.is_empty() {
+        return None;
+    }
+    HeaderValue::from_str(&members.join(",")).ok()
+}
+
+/// Parse the B3 headers, trying the single-header format first.
+fn b3(headers: &HeaderMap) -> Option<TraceContext> {
+    if let Some(value) = headers.get(&B3) {
+        return parse_b3(value.to_str().ok()?);
+    }
+    let trace_id = parse_b3_trace_id(headers.get(&B3_TRACE_ID)?.to_str().ok()?)?;
+    let span_id = headers.get(&B3_SPAN_ID)?.to_str().ok()?.parse().ok()?;
+    let debug = headers.get(&B3_FLAGS).is_some_and(|v| v == "1");
+    let sampled = match headers.get(&B3_SAMPLED).map(|v| v.as_bytes()) {
+        Some(b"0" | b"false") => debug,
+        Some(b"1" | b"true") | None => true,
+        Some(_) => return None,
+    };
+    Some(TraceContext::continue_trace(trace_id, span_id, sampled))
+}
+
+/// Parse a `b3` value: `{trace_id}-{span_id}[-{sampling_state}[-{parent_span_id}]]`.
+///
+/// Values that only carry a sampling decision can't be used to continue a trace.
+fn parse_b3(value: &str) -> Option<TraceContext> {
+    let mut parts = value.trim().split('-');
+    let trace_id = parse_b3_trace_id(parts.next()?)?;
+    let span_id = parts.next()?.parse().ok()?;
+    let sampled = match parts.next() {
+        Some("0") => false,
+        // `d` stands for "debug", which implies sampling.
+        Some("1" | "d") | None => true,
+        Some(_) => return None,
+    };
+    Some(TraceContext::continue_trace(trace_id, span_id, sampled))
+}
+
+/// B3 allows 64-bit trace ids, which are left-padded with zeroes.
+fn parse_b3_trace_id(value: &str) -> Option<TraceId> {
+    if value.len() == 16 {
+        let low = parse_hex::<8>(value)?;
+        let mut bytes = [0; 16];
+        bytes[8..].copy_from_slice(&low);
+        return TraceId::from_bytes(bytes);
+    }
+    value.parse().ok()
+}
+
+/// Parse exactly `N` bytes from lowercase hex digits.
+fn parse_hex<const N: usize>(value: &str) -> Option<[u8; N]> {
+    if value.len() != N * 2 {
+        return None;
+    }
+    let digit = |c: u8| match c {
+        b'0'..=b'9' => Some(c - b'0'),
+        b'a'..=b'f' => Some(c - b'a' + 10),
+        _ => None,
+    };
+    let mut bytes = [0; N];
+    for (byte, pair) in bytes.iter_mut().zip(value.as_bytes().chunks_exact(2)) {
+        *byte = (digit(pair[0])? << 4) | digit(pair[1])?;
+    }
+    Some(bytes)
+}
+
+fn random_bytes<const N: usize>() -> [u8; N] {
+    let mut bytes = [0; N];
+    // Identifiers must be unique, not secret: we can tolerate the (unlikely) failure
+    // of the OS random number generator by retrying.
+    while bytes.iter().all(|b| *b == 0) {
+        getrandom::fill(&mut bytes).expect("Failed to generate a random trace identifier");
+    }
+    bytes
+}
+
+/// A 16-byte trace identifier.
+///
+/// It is represented as 32 lowercase hex digits.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TraceId([u8; 16]);
+
+/// An 8-byte span identifier.
+///
+/// It is represented as 16 lowercase hex digits.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SpanId([u8; 8]);
+
+/// The error returned when parsing an invalid [`TraceId`] or [`SpanId`].
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("`{0}` is not a valid identifier: it must be a non-zero value, in lowercase hex digits")]
+pub struct InvalidIdentifier(String);
+
+macro_rules! identifier {
+    ($name:ident, $n:literal) => {
+        impl $name {
+            /// Build an identifier from its bytes.
+            ///
+            /// It returns `None` if all bytes are zero, since it's not a valid identifier.
+            pub fn from_bytes(bytes: [u8; $n]) -> Option<Self> {
+                bytes.iter().any(|b| *b != 0).then_some(Self(bytes))
+            }
+
+            /// The bytes of the identifier—e.g. to convert it into an OpenTelemetry identifier.
+            pub fn to_bytes(self) -> [u8; $n] {
+                self.0
+            }
+
+            fn random() -> Self {
+                Self(random_bytes())
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = InvalidIdentifier;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                parse_hex::<$n>(s)
+                    .and_then(Self::from_bytes)
+                    .ok_or_else(|| InvalidIdentifier(s.to_owned()))
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                for byte in self.0 {
+                    write!(f, "{byte:02x}")?;
+                }
+                Ok(())
+            }
+        }
+
+        impl fmt::Debug for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "{}({self})", stringify!($name))
+            }
+        }
+    };
+}
+
+identifier!(TraceId, 16);
+identifier!(SpanId, 8);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(pairs: &[(&'static str, &'static str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.append(*name, HeaderValue::from_static(value));
+        }
+        headers
+    }
+
+    fn extract(pairs: &[(&'static str, &'static str)], b3: bool) -> Option<TraceContext> {
+        TraceContext::from_headers(&headers(pairs), &TracePropagationConfig { b3 })
+    }
+
+    #[test]
+    fn traceparent_is_continued() {
+        let context = extract(
+            &[
+                (
+                    "traceparent",
+                    "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+                ),
+                ("tracestate", "congo=t61rcWkgMzE"),
+                ("tracestate", "rojo=00f067aa0ba902b7"),
+            ],
+            false,
+        )
+        .unwrap();
+        assert_eq!(
+            context.trace_id().to_string(),
+            "4bf92f3577b34da6a3ce929d0e0e4736"
+        );
+        assert_eq!(
+            context.parent_span_id().unwrap().to_string(),
+            "00f067aa0ba902b7"
+        );
+        assert_ne!(Some(context.span_id()), context.parent_span_id());
+        assert!(context.is_sampled());
+        assert_eq!(
+            context.trace_state().unwrap(),
+            "congo=t61rcWkgMzE,rojo=00f067aa0ba902b7"
+        );
+
+        let mut outgoing = HeaderMap::new();
+        context.inject(&mut outgoing);
+        assert_eq!(
+            outgoing["traceparent"],
+            format!(
+                "00-4bf92f3577b34da6a3ce929d0e0e4736-{}-01",
+                context.span_id()
+            )
+        );
+        assert_eq!(
+            outgoing["tracestate"],
+            "congo=t61rcWkgMzE,rojo=00f067aa0ba902b7"
+        );
+    }
+
+    #[test]
+    fn invalid_traceparent_values_are_ignored() {
+        for value in [
+            // Uppercase hex digits.
+            "00-4BF92F3577B34DA6A3CE929D0E0E4736-00f067aa0ba902b7-01",
+            // All-zero trace id.
+            "00-00000000000000000000000000000000-00f067aa0ba902b7-01",
+            // All-zero parent id.
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-0000000000000000-01",
+            // Forbidden version.
+            "ff-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+            // Version 00 has no extra fields.
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01-extra",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7",
+            "",
+        ] {
+            assert_eq!(extract(&[("traceparent", value)], false), None, "{value}");
+        }
+        // Multiple `traceparent` headers.
+        let value = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+        assert_eq!(
+            extract(&[("traceparent", value), ("traceparent", value)], false),
+            None
+        );
+    }
+
+    #[test]
+    fn future_versions_are_parsed_leniently() {
+        let context = extract(
+            &[(
+                "traceparent",
+                "cc-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-00-extra",
+            )],
+            false,
+        )
+        .unwrap();
+        assert!(!context.is_sampled());
+        // We always propagate the version we understand.
+        assert!(context.traceparent().to_str().unwrap().starts_with("00-"));
+        assert!(context.traceparent().to_str().unwrap().ends_with("-00"));
+    }
+
+    #[test]
+    fn b3_headers_are_opt_in() {
+        let single = [(
+            "b3",
+            "80f198ee56343ba864fe8b2a57d3eff7-e457b5a2e4d86bd1-0-05e3ac9a4f6e3b90",
+        )];
+        assert_eq!(extract(&single, false), None);
+
+        let context = extract(&single, true).unwrap();
+        assert_eq!(
+            context.trace_id().to_string(),
+            "80f198ee56343ba864fe8b2a57d3eff7"
+        );
+        assert_eq!(
+            context.parent_span_id().unwrap().to_string(),
+            "e457b5a2e4d86bd1"
+        );
+        assert!(!context.is_sampled());
+
+        let multi = [
+            ("x-b3-traceid", "a3ce929d0e0e4736"),
+            ("x-b3-spanid", "00f067aa0ba902b7"),
+            ("x-b3-sampled", "1"),
+        ];
+        let context = extract(&multi, true).unwrap();
+        // 64-bit trace ids are left-padded.
+        assert_eq!(
+            context.trace_id().to_string(),
+            "0000000000000000a3ce929d0e0e4736"
+        );
+        assert!(context.is_sampled());
+
+        // Sampling-only values can't continue a trace.
+        assert_eq!(extract(&[("b3", "1")], true), None);
+    }
+
+    #[test]
+    fn traceparent_takes_precedence_over_b3() {
+        let context = extract(
+            &[
+                (
+                    "traceparent",
+                    "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+                ),
+                ("b3", "80f198ee56343ba864fe8b2a57d3eff7-e457b5a2e4d86bd1-1"),
+            ],
+            true,
+        )
+        .unwrap();
+        assert_eq!(
+            context.trace_id().to_string(),
+            "4bf92f3577b34da6a3ce929d0e0e4736"
+        );
+    }
+
+    #[test]
+    fn new_traces_have_no_parent() {
+        let context = TraceContext::new_trace();
+        assert_eq!(context.parent_span_id(), None);
+        assert!(context.is_sampled());
+        assert_ne!(context.trace_id(), TraceContext::new_trace().trace_id());
+    }
+}
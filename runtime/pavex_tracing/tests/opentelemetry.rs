@@ -0,0 +1,50 @@
+#![cfg(feature = "opentelemetry")]
+use opentelemetry::trace::TracerProvider;
+use opentelemetry_sdk::trace::{InMemorySpanExporter, SdkTracerProvider};
+use pavex::http::{HeaderMap, HeaderValue};
+use pavex_tracing::{RootSpan, TraceContext, TracePropagationConfig};
+use tracing_subscriber::layer::SubscriberExt;
+
+const TRACE_ID: &str = "4bf92f3577b34da6a3ce929d0e0e4736";
+const PARENT_SPAN_ID: &str = "00f067aa0ba902b7";
+
+#[test]
+fn the_exported_root_span_continues_the_callers_trace() {
+    let exporter = InMemorySpanExporter::default();
+    let provider = SdkTracerProvider::builder()
+        .with_simple_exporter(exporter.clone())
+        .build();
+    let subscriber = tracing_subscriber::registry()
+        .with(tracing_opentelemetry::layer().with_tracer(provider.tracer("test")));
+
+    let mut incoming = HeaderMap::new();
+    incoming.insert(
+        "traceparent",
+        HeaderValue::from_str(&format!("00-{TRACE_ID}-{PARENT_SPAN_ID}-01")).unwrap(),
+    );
+    let trace_context =
+        TraceContext::from_headers(&incoming, &TracePropagationConfig::default()).unwrap();
+
+    let outgoing = tracing::subscriber::with_default(subscriber, || {
+        let root_span = RootSpan::new(tracing::info_span!("HTTP request"));
+        root_span.set_parent(&trace_context);
+        let _guard = root_span.enter();
+        let mut outgoing = HeaderMap::new();
+        trace_context.inject(&mut outgoing);
+        outgoing
+    });
+
+    provider.force_flush().unwrap();
+    let spans = exporter.get_finished_spans().unwrap();
+    assert_eq!(spans.len(), 1);
+    let root_span = &spans[0];
+    assert_eq!(root_span.span_context.trace_id().to_string(), TRACE_ID);
+    assert_eq!(root_span.parent_span_id.to_string(), PARENT_SPAN_ID);
+    assert!(root_span.parent_span_is_remote);
+
+    // The exported span is the parent of outgoing requests.
+    assert_eq!(
+        outgoing["traceparent"],
+        format!("00-{TRACE_ID}-{}-01", root_span.span_context.span_id())
+    );
+}
@@ -0,0 +1,302 @@
+//! Serialization formats for the server-side session state.
+//!
+//! Check out [`SessionCodec`] for more details.
+use std::{borrow::Cow, collections::HashMap};
+
+use crate::value::Value;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+/// The format used to serialize the server-side session state
+/// before handing it over to the storage backend.
+///
+/// # Binary formats
+///
+/// Binary formats produce smaller payloads and are faster to (de)serialize,
+/// which makes a difference for large session states.
+/// Each binary format is gated behind a `pavex_session` feature flag:
+///
+/// | Format                                 | Feature flag |
+/// |----------------------------------------|--------------|
+/// | [MessagePack](https://msgpack.org/)    | `msgpack`    |
+/// | [CBOR](https://cbor.io/)               | `cbor`       |
+/// | [postcard](https://docs.rs/postcard)   | `postcard`   |
+///
+/// # Migrating
+///
+/// Every binary payload starts with a header that identifies its format,
+/// while JSON payloads are stored as they are.
+/// A session record can therefore always be decoded, no matter which codec is currently configured:
+/// if you switch to a different codec, existing records are still readable and they'll
+/// be re-encoded using the new codec the next time they're updated.
+///
+/// Make sure that all instances of your application have been upgraded to a version that
+/// supports the new codec before switching to it—older instances won't be able to read
+/// the records written by the newer ones.
+///
+/// # Client-side state
+///
+/// The codec only affects the server-side state.
+/// The client-side state, as well as the server-side state managed by a
+/// [cookie-only store](crate::SessionStore::cookie_only), is always serialized as JSON.
+pub enum SessionCodec {
+    /// Serialize the session state as JSON.
+    ///
+    /// It's the default codec, since it's human-readable and it can be inspected
+    /// (or queried) by the storage backend—e.g. using Postgres' `JSONB` operators.
+    ///
+    /// JSON has no native representation for byte strings, which are stored as arrays of numbers,
+    /// nor for integers that don't fit into 64 bits, which are stored as floating point numbers.
+    #[default]
+    Json,
+    /// Serialize the session state using [MessagePack](https://msgpack.org/).
+    ///
+    /// MessagePack has no native representation for integers that don't fit into 64 bits,
+    /// which are stored as byte strings.
+    #[cfg(feature = "msgpack")]
+    #[serde(rename = "msgpack")]
+    MessagePack,
+    /// Serialize the session state using [CBOR](https://cbor.io/).
+    #[cfg(feature = "cbor")]
+    Cbor,
+    /// Serialize the session state using [postcard](https://docs.rs/postcard).
+    ///
+    /// It's the most compact format out of the ones supported by `pavex_session`.
+    #[cfg(feature = "postcard")]
+    Postcard,
+}
+
+/// The first byte of every binary payload.
+///
+/// JSON payloads never start with a null byte, which allows us to
+/// tell them apart from binary payloads.
+const BINARY_MARKER: u8 = 0x00;
+const MSGPACK_TAG: u8 = 0x01;
+const CBOR_TAG: u8 = 0x02;
+const POSTCARD_TAG: u8 = 0x03;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A session state, serialized using a [`SessionCodec`].
+pub enum EncodedState {
+    /// The state was serialized as JSON.
+    Json(String),
+    /// The state was serialized using a binary format.
+    ///
+    /// The payload includes the header identifying the format.
+    Binary(Vec<u8>),
+}
+
+impl EncodedState {
+    /// The raw bytes of the serialized state.
+    pub fn as_bytes(&self) -> &[u8] {
+        match self {
+            EncodedState::Json(s) => s.as_bytes(),
+            EncodedState::Binary(b) => b,
+        }
+    }
+
+    /// Consume the serialized state, returning its raw bytes.
+    pub fn into_bytes(self) -> Vec<u8> {
+        match self {
+            EncodedState::Json(s) => s.into_bytes(),
+            EncodedState::Binary(b) => b,
+        }
+    }
+}
+
+impl SessionCodec {
+    /// Serialize the session state using this codec.
+    pub fn encode(
+        &self,
+        state: &HashMap<Cow<'static, str>, Value>,
+    ) -> Result<EncodedState, EncodeError> {
+        match self {
+            SessionCodec::Json => serde_json::to_string(state)
+                .map(EncodedState::Json)
+                .map_err(|e| EncodeError(e.into())),
+            #[cfg(feature = "msgpack")]
+            SessionCodec::MessagePack => {
+                let mut buffer = vec![BINARY_MARKER, MSGPACK_TAG];
+                rmp_serde::encode::write(&mut buffer, state).map_err(|e| EncodeError(e.into()))?;
+                Ok(EncodedState::Binary(buffer))
+            }
+            #[cfg(feature = "cbor")]
+            SessionCodec::Cbor => {
+                let mut buffer = vec![BINARY_MARKER, CBOR_TAG];
+                ciborium::into_writer(state, &mut buffer).map_err(|e| EncodeError(e.into()))?;
+                Ok(EncodedState::Binary(buffer))
+            }
+            #[cfg(feature = "postcard")]
+            SessionCodec::Postcard => {
+                let buffer = vec![BINARY_MARKER, POSTCARD_TAG];
+                postcard::to_extend(&postcard_::State(state), buffer)
+                    .map(EncodedState::Binary)
+                    .map_err(|e| EncodeError(e.into()))
+            }
+        }
+    }
+
+    /// Deserialize a session state that was serialized using one of the supported codecs.
+    ///
+    /// The codec is detected from the payload itself, so there is no need to know
+    /// which codec was used to serialize it.
+    pub fn decode(payload: &[u8]) -> Result<HashMap<Cow<'static, str>, Value>, DecodeError> {
+        #[cfg_attr(
+            not(any(feature = "msgpack", feature = "cbor", feature = "postcard")),
+            allow(unused_variables)
+        )]
+        let [BINARY_MARKER, tag, body @ ..] = payload else {
+            return serde_json::from_slice(payload).map_err(|e| DecodeError(e.into()));
+        };
+        match *tag {
+            MSGPACK_TAG => {
+                #[cfg(feature = "msgpack")]
+                return rmp_serde::from_slice(body).map_err(|e| DecodeError(e.into()));
+                #[cfg(not(feature = "msgpack"))]
+                return Err(disabled_codec("MessagePack", "msgpack"));
+            }
+            CBOR_TAG => {
+                #[cfg(feature = "cbor")]
+                return ciborium::from_reader(body).map_err(|e| DecodeError(e.into()));
+                #[cfg(not(feature = "cbor"))]
+                return Err(disabled_codec("CBOR", "cbor"));
+            }
+            POSTCARD_TAG => {
+                #[cfg(feature = "postcard")]
+                return postcard_::decode(body).map_err(|e| DecodeError(e.into()));
+                #[cfg(not(feature = "postcard"))]
+                return Err(disabled_codec("postcard", "postcard"));
+            }
+            tag => Err(DecodeError(anyhow::anyhow!(
+                "The session state was serialized using an unknown format (tag: {tag:#04x})"
+            ))),
+        }
+    }
+}
+
+#[allow(dead_code)]
+fn disabled_codec(format: &str, feature: &str) -> DecodeError {
+    DecodeError(anyhow::anyhow!(
+        "The session state was serialized using {format}, but the `{feature}` feature \
+        of `pavex_session` is not enabled"
+    ))
+}
+
+#[cfg(feature = "postcard")]
+mod postcard_ {
+    //! `postcard` is not a self-describing format: it can't deserialize a [`Value`]
+    //! without knowing its type ahead of time.
+    //! We tag each value with its variant to work around the limitation.
+    use std::{borrow::Cow, collections::HashMap};
+
+    use serde::{Serialize, Serializer};
+
+    use crate::value::Value;
+
+    pub(super) struct State<'a>(pub(super) &'a HashMap<Cow<'static, str>, Value>);
+
+    impl Serialize for State<'_> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.collect_map(self.0.iter().map(|(k, v)| (k, Tagged(v))))
+        }
+    }
+
+    struct Tagged<'a>(&'a Value);
+
+    struct Bytes<'a>(&'a [u8]);
+
+    impl Serialize for Bytes<'_> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.serialize_bytes(self.0)
+        }
+    }
+
+    // The variant indexes must match the ones of `TaggedValue`.
+    impl Serialize for Tagged<'_> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            const NAME: &str = "Value";
+            match self.0 {
+                Value::Null => serializer.serialize_unit_variant(NAME, 0, "Null"),
+                Value::Bool(b) => serializer.serialize_newtype_variant(NAME, 1, "Bool", b),
+                Value::Integer(i) => serializer.serialize_newtype_variant(NAME, 2, "Integer", i),
+                Value::Float(f) => serializer.serialize_newtype_variant(NAME, 3, "Float", f),
+                Value::String(s) => serializer.serialize_newtype_variant(NAME, 4, "String", s),
+                Value::Bytes(b) => {
+                    serializer.serialize_newtype_variant(NAME, 5, "Bytes", &Bytes(b))
+                }
+                Value::Array(a) => {
+                    serializer.serialize_newtype_variant(NAME, 6, "Array", &TaggedSeq(a))
+                }
+                Value::Map(m) => {
+                    serializer.serialize_newtype_variant(NAME, 7, "Map", &TaggedMap(m))
+                }
+            }
+        }
+    }
+
+    struct TaggedSeq<'a>(&'a [Value]);
+
+    impl Serialize for TaggedSeq<'_> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.collect_seq(self.0.iter().map(Tagged))
+        }
+    }
+
+    struct TaggedMap<'a>(&'a std::collections::BTreeMap<String, Value>);
+
+    impl Serialize for TaggedMap<'_> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.collect_map(self.0.iter().map(|(k, v)| (k, Tagged(v))))
+        }
+    }
+
+    #[derive(serde::Deserialize)]
+    enum TaggedValue {
+        Null,
+        Bool(bool),
+        Integer(i128),
+        Float(f64),
+        String(String),
+        Bytes(Vec<u8>),
+        Array(Vec<TaggedValue>),
+        Map(Vec<(String, TaggedValue)>),
+    }
+
+    impl From<TaggedValue> for Value {
+        fn from(value: TaggedValue) -> Self {
+            match value {
+                TaggedValue::Null => Value::Null,
+                TaggedValue::Bool(b) => Value::Bool(b),
+                TaggedValue::Integer(i) => Value::Integer(i),
+                TaggedValue::Float(f) => Value::Float(f),
+                TaggedValue::String(s) => Value::String(s),
+                TaggedValue::Bytes(b) => Value::Bytes(b),
+                TaggedValue::Array(a) => Value::Array(a.into_iter().map(Into::into).collect()),
+                TaggedValue::Map(m) => {
+                    Value::Map(m.into_iter().map(|(k, v)| (k, v.into())).collect())
+                }
+            }
+        }
+    }
+
+    pub(super) fn decode(
+        payload: &[u8],
+    ) -> Result<HashMap<Cow<'static, str>, Value>, postcard::Error> {
+        let state: Vec<(String, TaggedValue)> = postcard::from_bytes(payload)?;
+        Ok(state
+            .into_iter()
+            .map(|(k, v)| (Cow::Owned(k), v.into()))
+            .collect())
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("Failed to serialize the session state")]
+/// The error returned by [`SessionCodec::encode`].
+pub struct EncodeError(#[source] anyhow::Error);
+
+#[derive(Debug, thiserror::Error)]
+#[error("Failed to deserialize the session state")]
+/// The error returned by [`SessionCodec::decode`].
+pub struct DecodeError(#[source] anyhow::Error);
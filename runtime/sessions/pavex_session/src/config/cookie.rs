@@ -1,3 +1,5 @@
+use std::num::NonZeroUsize;
+
 use pavex::cookie::SameSite;
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -45,6 +47,40 @@ pub struct SessionCookieConfig {
     /// By default, it is set to [`SessionCookieKind::Persistent`].
     #[serde(default)]
     pub kind: SessionCookieKind,
+    /// The maximum number of cookies the session state can be split into
+    /// when using a [cookie-only store](crate::SessionStore::cookie_only).
+    ///
+    /// Browsers cap the size of each cookie at roughly 4KB: larger session states
+    /// are split across multiple cookies, named `{name}`, `{name}.1`, `{name}.2`, etc.
+    /// If the state doesn't fit into `max_chunks` cookies, the session can't be finalized.
+    ///
+    /// It has no effect when the server-side state is stored in a storage backend.
+    ///
+    /// By default, it is set to 4.
+    #[serde(default = "default_session_cookie_max_chunks")]
+    pub max_chunks: NonZeroUsize,
+}
+
+impl SessionCookieConfig {
+    /// The names of all the cookies that may be used to store the session state—i.e.
+    /// [`name`](Self::name) followed by the names of the additional chunks used by
+    /// [cookie-only stores](crate::SessionStore::cookie_only).
+    ///
+    /// Use it to populate the crypto rule that encrypts your session cookies.
+    pub fn cookie_names(&self) -> Vec<String> {
+        (0..self.max_chunks.get())
+            .map(|i| self.chunk_name(i))
+            .collect()
+    }
+
+    /// The name of the cookie holding the `i`-th chunk of the session state.
+    pub(crate) fn chunk_name(&self, i: usize) -> String {
+        if i == 0 {
+            self.name.clone()
+        } else {
+            format!("{}.{i}", self.name)
+        }
+    }
 }
 
 impl Default for SessionCookieConfig {
@@ -57,6 +93,7 @@ impl Default for SessionCookieConfig {
             http_only: default_session_cookie_http_only(),
             same_site: default_session_cookie_same_site(),
             kind: Default::default(),
+            max_chunks: default_session_cookie_max_chunks(),
         }
     }
 }
@@ -78,6 +115,10 @@ fn default_session_cookie_path() -> Option<String> {
     Some("/".to_string())
 }
 
+fn default_session_cookie_max_chunks() -> NonZeroUsize {
+    NonZeroUsize::new(4).unwrap()
+}
+
 fn default_session_cookie_same_site() -> Option<SameSite> {
     Some(SameSite::Lax)
 }
@@ -0,0 +1,53 @@
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+/// Configure how [flash messages](crate::flash) are carried over to the next request.
+pub struct FlashConfig {
+    /// Where pending flash messages should be stored.
+    ///
+    /// By default, it is set to [`FlashStorage::Session`].
+    #[serde(default)]
+    pub storage: FlashStorage,
+    /// The name of the cookie used to store flash messages when
+    /// [`FlashStorage::CookieFallback`] is in effect.
+    ///
+    /// By default, the name is set to `flash`.
+    #[serde(default = "default_flash_cookie_name")]
+    pub cookie_name: String,
+}
+
+impl Default for FlashConfig {
+    fn default() -> Self {
+        Self {
+            storage: Default::default(),
+            cookie_name: default_flash_cookie_name(),
+        }
+    }
+}
+
+fn default_flash_cookie_name() -> String {
+    "flash".to_string()
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+/// Where pending flash messages are stored between requests.
+pub enum FlashStorage {
+    /// Flash messages are always stored in the client-side state of the session.
+    ///
+    /// Storing a flash message will start a new session if the request
+    /// wasn't already part of one.
+    ///
+    /// This is the default.
+    #[default]
+    Session,
+    /// Flash messages are stored in the client-side state of the session, if the request
+    /// is part of a session that outlives it.
+    ///
+    /// They are stored in a dedicated cookie otherwise—e.g. for anonymous visitors,
+    /// or when the session has just been invalidated (think of a "You have been logged out"
+    /// message).
+    /// The flash cookie must be configured to be signed or encrypted.
+    CookieFallback,
+}
@@ -1,9 +1,11 @@
 //! Types related to [`SessionConfig`][crate::SessionConfig].
 mod cookie;
+mod flash;
 mod state;
 
 pub use cookie::{SessionCookieConfig, SessionCookieKind};
+pub use flash::{FlashConfig, FlashStorage};
 pub use state::{
-    InvalidTtlExtensionThreshold, MissingServerState, ServerStateCreation, SessionStateConfig,
-    TtlExtensionThreshold, TtlExtensionTrigger,
+    ConcurrentUpdatePolicy, InvalidTtlExtensionThreshold, MissingServerState, ServerStateCreation,
+    SessionStateConfig, TtlExtensionThreshold, TtlExtensionTrigger,
 };
@@ -1,5 +1,7 @@
 use serde::Deserialize;
 
+use crate::codec::SessionCodec;
+
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "snake_case")]
 #[non_exhaustive]
@@ -25,6 +27,38 @@ pub struct SessionStateConfig {
     /// of the current session.
     #[serde(default)]
     pub extend_ttl: TtlExtensionTrigger,
+    /// The maximum lifetime of a session, measured from the moment its
+    /// server-side record was created.
+    ///
+    /// Once the limit is exceeded, the session is invalidated, regardless of how
+    /// active it is—the user will have to authenticate again.
+    /// Check out [`SessionTimeout`] for more details on how timeouts are enforced.
+    ///
+    /// # Default
+    ///
+    /// By default, there is no absolute timeout: a session lives for as long
+    /// as its [TTL](Self::ttl) keeps being extended.
+    ///
+    /// [`SessionTimeout`]: crate::SessionTimeout
+    #[serde(deserialize_with = "deserialize_timeout", default)]
+    pub absolute_timeout: Option<std::time::Duration>,
+    /// The maximum amount of time a session can stay inactive—i.e. without
+    /// its server-side record being refreshed.
+    ///
+    /// Once the limit is exceeded, the session is invalidated.
+    /// The record is refreshed according to your [TTL extension trigger](Self::extend_ttl):
+    /// if it's set to [`OnStateChanges`](TtlExtensionTrigger::OnStateChanges), requests that only
+    /// read the session state don't count as activity.
+    /// Check out [`SessionTimeout`] for more details on how timeouts are enforced.
+    ///
+    /// # Default
+    ///
+    /// By default, there is no idle timeout: inactive sessions are only
+    /// removed when their [TTL](Self::ttl) runs out.
+    ///
+    /// [`SessionTimeout`]: crate::SessionTimeout
+    #[serde(deserialize_with = "deserialize_timeout", default)]
+    pub idle_timeout: Option<std::time::Duration>,
     /// The server will skip TTL extension if the remaining TTL
     /// is greater than this threshold.
     /// The threshold is a ratio between 0 and 1, interpreted as
@@ -60,6 +94,18 @@ pub struct SessionStateConfig {
     /// (e.g. only the client-side state remains or was ever created).
     #[serde(default)]
     pub missing_server_state: MissingServerState,
+    /// Determines what happens when the server-side state was modified by another request
+    /// (e.g. a concurrent request from the same browser) after it was loaded.
+    #[serde(default)]
+    pub concurrent_updates: ConcurrentUpdatePolicy,
+    /// The format used to serialize the server-side state before
+    /// sending it to the storage backend.
+    ///
+    /// # Default
+    ///
+    /// By default, the server-side state is serialized as JSON.
+    #[serde(default)]
+    pub codec: SessionCodec,
 }
 
 impl Default for SessionStateConfig {
@@ -67,13 +113,24 @@ impl Default for SessionStateConfig {
         Self {
             ttl: default_ttl(),
             extend_ttl: Default::default(),
+            absolute_timeout: None,
+            idle_timeout: None,
             ttl_extension_threshold: default_ttl_extension_threshold(),
             server_state_creation: Default::default(),
             missing_server_state: Default::default(),
+            concurrent_updates: Default::default(),
+            codec: Default::default(),
         }
     }
 }
 
+impl SessionStateConfig {
+    /// Returns `true` if at least one session timeout has been configured.
+    pub(crate) fn enforces_timeouts(&self) -> bool {
+        self.absolute_timeout.is_some() || self.idle_timeout.is_some()
+    }
+}
+
 fn deserialize_ttl<'de, D>(deserializer: D) -> Result<std::time::Duration, D::Error>
 where
     D: serde::Deserializer<'de>,
@@ -93,6 +150,22 @@ where
     Ok(ttl)
 }
 
+fn deserialize_timeout<'de, D>(deserializer: D) -> Result<Option<std::time::Duration>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let Some(span) = Option::<pavex::time::Span>::deserialize(deserializer)? else {
+        return Ok(None);
+    };
+    if span.is_negative() || span.is_zero() {
+        return Err(serde::de::Error::custom(
+            "Session timeouts must be positive",
+        ));
+    }
+    let timeout = span.try_into().map_err(serde::de::Error::custom)?;
+    Ok(Some(timeout))
+}
+
 fn default_ttl() -> std::time::Duration {
     // 1 day
     std::time::Duration::from_secs(60 * 60 * 24)
@@ -180,6 +253,33 @@ pub enum MissingServerState {
     Reject,
 }
 
+#[derive(Debug, Default, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+/// Configure how to handle concurrent updates to the server-side state of the same session.
+///
+/// Conflicts can only be detected if your storage backend keeps track of
+/// [record versions](crate::store::SessionRecord::version).
+/// If it doesn't, the last update always wins.
+pub enum ConcurrentUpdatePolicy {
+    /// The changes made while processing the current request are applied, key by key,
+    /// on top of the latest version of the server-side state.
+    ///
+    /// Keys that weren't touched by the current request are left as they are in the store.
+    /// If both requests modified the same key, the last update wins.
+    ///
+    /// This is the default policy.
+    #[default]
+    Merge,
+    /// The update is rejected with a [`SyncError::Conflict`] error.
+    ///
+    /// [`SyncError::Conflict`]: crate::errors::SyncError::Conflict
+    Reject,
+    /// The server-side state in the store is overwritten with the current one,
+    /// discarding any concurrent change.
+    LastWriteWins,
+}
+
 #[derive(Debug, Clone, Copy, serde::Serialize)]
 /// A ratio between 0 and 1, interpreted as a percentage of the time-to-live of a fresh session.
 pub struct TtlExtensionThreshold(f32);
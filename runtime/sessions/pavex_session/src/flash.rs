@@ -0,0 +1,377 @@
+//! One-time messages, carried over from one request to the next.
+//!
+//! Flash messages are usually set right before a redirect (e.g. "Your profile has been updated")
+//! and displayed by the page the user lands on. They are removed as soon as they have been read.
+//!
+//! Use [`OutgoingFlashMessages`] to queue messages for the next request and
+//! [`IncomingFlashMessages`] to read the messages queued by a previous one.
+//! Register [`finalize_flash_messages`] as a post-processing middleware, **before**
+//! [`finalize_session`](crate::finalize_session), to persist the pending messages.
+//!
+//! Check out [`FlashConfig`] to control where
+//! pending messages are stored.
+use std::cell::Cell;
+
+use pavex::{
+    Response,
+    cookie::{Processor, RemovalCookie, RequestCookies, ResponseCookie, ResponseCookies},
+    methods, post_process,
+};
+use pavex_tracing::fields::{ERROR_DETAILS, ERROR_MESSAGE, error_details, error_message};
+
+use crate::{
+    Session, SessionConfig,
+    config::{FlashConfig, FlashStorage, SessionCookieConfig},
+};
+use errors::FinalizeFlashError;
+
+/// The key used to store pending flash messages in the client-side session state.
+const FLASH_KEY: &str = "_flash";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+/// The severity of a [`FlashMessage`].
+pub enum Level {
+    /// A neutral, informative message.
+    Info,
+    /// The requested operation completed successfully.
+    Success,
+    /// Something the user should pay attention to.
+    Warning,
+    /// The requested operation failed.
+    Error,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+/// A one-time message, alongside its [`Level`].
+pub struct FlashMessage {
+    /// The severity of the message.
+    pub level: Level,
+    /// The text of the message.
+    pub message: String,
+}
+
+impl FlashMessage {
+    /// Create a new flash message.
+    pub fn new(level: Level, message: impl Into<String>) -> Self {
+        Self {
+            level,
+            message: message.into(),
+        }
+    }
+}
+
+/// The flash messages queued by previous requests.
+///
+/// Messages are considered consumed as soon as you [iterate](Self::iter) over them:
+/// [`finalize_flash_messages`] will then remove them from the session.
+/// Messages that haven't been read are carried over to the next request.
+#[derive(Debug)]
+pub struct IncomingFlashMessages {
+    messages: Vec<FlashMessage>,
+    read: Cell<bool>,
+    /// Whether (some of) the messages were found in the client-side session state.
+    in_session: bool,
+    /// Whether (some of) the messages were found in the flash cookie.
+    in_cookie: bool,
+}
+
+#[methods]
+impl IncomingFlashMessages {
+    /// Retrieve the flash messages attached to the incoming request.
+    ///
+    /// Messages are read from the client-side session state and from the flash cookie,
+    /// if [`FlashStorage::CookieFallback`] is enabled.
+    /// Invalid payloads are logged and discarded.
+    ///
+    /// The flash cookie is only accepted if `processor` is configured to sign or encrypt it.
+    /// Otherwise, its messages could have been forged by the client: they are discarded
+    /// and the cookie is removed from the client.
+    #[request_scoped]
+    pub fn extract(
+        session: &Session<'_>,
+        request_cookies: &RequestCookies<'_>,
+        config: &FlashConfig,
+        processor: &Processor,
+    ) -> Self {
+        let mut messages = Vec::new();
+        let in_session = session.client().get_raw(FLASH_KEY).is_some();
+        if in_session {
+            match session.client().get::<Vec<FlashMessage>>(FLASH_KEY) {
+                Ok(m) => messages.extend(m.unwrap_or_default()),
+                Err(e) => {
+                    tracing::event!(
+                        tracing::Level::WARN,
+                        { ERROR_MESSAGE } = error_message(&e),
+                        { ERROR_DETAILS } = error_details(&e),
+                        "Invalid flash messages in the client-side session state, discarding them."
+                    );
+                }
+            }
+        }
+        let cookie = match config.storage {
+            FlashStorage::Session => None,
+            FlashStorage::CookieFallback => request_cookies.get(&config.cookie_name),
+        };
+        let in_cookie = cookie.is_some();
+        // An unprotected cookie may have been forged by the client.
+        let cookie = cookie.filter(|_| {
+            let is_protected = processor.will_encrypt(&config.cookie_name)
+                || processor.will_sign(&config.cookie_name);
+            if !is_protected {
+                tracing::event!(
+                    tracing::Level::WARN,
+                    "The flash cookie is neither signed nor encrypted. Discarding it."
+                );
+            }
+            is_protected
+        });
+        if let Some(cookie) = cookie {
+            match serde_json::from_str::<Vec<FlashMessage>>(cookie.value()) {
+                Ok(m) => messages.extend(m),
+                Err(e) => {
+                    tracing::event!(
+                        tracing::Level::WARN,
+                        { ERROR_MESSAGE } = error_message(&e),
+                        { ERROR_DETAILS } = error_details(&e),
+                        "Invalid flash cookie, discarding it."
+                    );
+                }
+            }
+        }
+        Self {
+            messages,
+            read: Cell::new(false),
+            in_session,
+            in_cookie,
+        }
+    }
+
+    /// Iterate over the incoming messages, marking them as read.
+    pub fn iter(&self) -> std::slice::Iter<'_, FlashMessage> {
+        self.read.set(true);
+        self.messages.iter()
+    }
+
+    /// The number of incoming messages.
+    ///
+    /// It doesn't mark the messages as read.
+    pub fn len(&self) -> usize {
+        self.messages.len()
+    }
+
+    /// Returns `true` if there are no incoming messages.
+    ///
+    /// It doesn't mark the messages as read.
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+
+    /// Returns `true` if the incoming messages have been read.
+    pub fn is_read(&self) -> bool {
+        self.read.get()
+    }
+}
+
+impl<'a> IntoIterator for &'a IncomingFlashMessages {
+    type Item = &'a FlashMessage;
+    type IntoIter = std::slice::Iter<'a, FlashMessage>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// The flash messages to be displayed by the next request.
+#[derive(Debug, Default)]
+pub struct OutgoingFlashMessages {
+    messages: Vec<FlashMessage>,
+}
+
+#[methods]
+impl OutgoingFlashMessages {
+    /// Start with an empty set of outgoing messages.
+    #[request_scoped]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queue a message with the given level.
+    pub fn push(&mut self, level: Level, message: impl Into<String>) {
+        self.messages.push(FlashMessage::new(level, message));
+    }
+
+    /// Queue a message with [`Level::Info`].
+    pub fn info(&mut self, message: impl Into<String>) {
+        self.push(Level::Info, message)
+    }
+
+    /// Queue a message with [`Level::Success`].
+    pub fn success(&mut self, message: impl Into<String>) {
+        self.push(Level::Success, message)
+    }
+
+    /// Queue a message with [`Level::Warning`].
+    pub fn warning(&mut self, message: impl Into<String>) {
+        self.push(Level::Warning, message)
+    }
+
+    /// Queue a message with [`Level::Error`].
+    pub fn error(&mut self, message: impl Into<String>) {
+        self.push(Level::Error, message)
+    }
+
+    /// Iterate over the messages queued so far.
+    pub fn iter(&self) -> std::slice::Iter<'_, FlashMessage> {
+        self.messages.iter()
+    }
+
+    /// Returns `true` if no message has been queued.
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+}
+
+/// A post-processing middleware to persist the pending flash messages.
+///
+/// Pending messages are the unread incoming messages, followed by the outgoing ones.
+/// They are stored in the client-side session state or, depending on your
+/// [`FlashConfig`], in the flash cookie.
+///
+/// It must be registered **before** [`finalize_session`](crate::finalize_session),
+/// since it modifies the session.
+#[tracing::instrument(
+    name = "Finalize flash messages",
+    level = tracing::Level::DEBUG, skip_all,
+    fields(flash.pending = tracing::field::Empty)
+)]
+#[post_process]
+pub fn finalize_flash_messages(
+    response: Response,
+    session: &mut Session<'_>,
+    incoming: &IncomingFlashMessages,
+    outgoing: &OutgoingFlashMessages,
+    response_cookies: &mut ResponseCookies,
+    processor: &Processor,
+    config: &SessionConfig,
+) -> Result<Response, FinalizeFlashError> {
+    let unread = if incoming.is_read() {
+        &[][..]
+    } else {
+        &incoming.messages[..]
+    };
+    let pending: Vec<&FlashMessage> = unread.iter().chain(outgoing.iter()).collect();
+    tracing::Span::current().record("flash.pending", pending.len());
+    // The stored messages are still accurate if nothing was read nor queued.
+    let unchanged = outgoing.is_empty() && !incoming.is_read();
+
+    let flash_config = &config.flash;
+    let use_cookie =
+        flash_config.storage == FlashStorage::CookieFallback && !session.outlives_request();
+    if use_cookie {
+        if incoming.in_session {
+            session.client_mut().remove_raw(FLASH_KEY);
+        }
+        if pending.is_empty() {
+            if incoming.in_cookie {
+                response_cookies.insert(removal_cookie(flash_config, &config.cookie));
+            }
+        } else if !unchanged || incoming.in_session {
+            let cookie_name = &flash_config.cookie_name;
+            if !(processor.will_encrypt(cookie_name) || processor.will_sign(cookie_name)) {
+                return Err(FinalizeFlashError::CryptoRequired {
+                    cookie_name: cookie_name.to_owned(),
+                });
+            }
+            let value = serde_json::to_string(&pending)?;
+            response_cookies.insert(flash_cookie(flash_config, &config.cookie, value));
+        }
+    } else {
+        if incoming.in_cookie {
+            response_cookies.insert(removal_cookie(flash_config, &config.cookie));
+        }
+        if pending.is_empty() {
+            if incoming.in_session {
+                session.client_mut().remove_raw(FLASH_KEY);
+            }
+        } else if !unchanged || incoming.in_cookie {
+            session.client_mut().insert(FLASH_KEY, &pending)?;
+        }
+    }
+
+    Ok(response)
+}
+
+/// The flash cookie shares its attributes with the session cookie,
+/// but it never outlives the browser session.
+fn flash_cookie(
+    config: &FlashConfig,
+    cookie_config: &SessionCookieConfig,
+    value: String,
+) -> ResponseCookie<'static> {
+    let mut cookie = ResponseCookie::new(config.cookie_name.clone(), value);
+    if let Some(domain) = cookie_config.domain.as_deref() {
+        cookie = cookie.set_domain(domain.to_owned());
+    }
+    if let Some(path) = cookie_config.path.as_deref() {
+        cookie = cookie.set_path(path.to_owned());
+    }
+    if let Some(same_site) = cookie_config.same_site {
+        cookie = cookie.set_same_site(same_site);
+    }
+    if cookie_config.secure {
+        cookie = cookie.set_secure(true);
+    }
+    if cookie_config.http_only {
+        cookie = cookie.set_http_only(true);
+    }
+    cookie
+}
+
+fn removal_cookie(
+    config: &FlashConfig,
+    cookie_config: &SessionCookieConfig,
+) -> ResponseCookie<'static> {
+    let mut cookie = RemovalCookie::new(config.cookie_name.clone());
+    if let Some(domain) = cookie_config.domain.as_deref() {
+        cookie = cookie.set_domain(domain.to_owned());
+    }
+    if let Some(path) = cookie_config.path.as_deref() {
+        cookie = cookie.set_path(path.to_owned());
+    }
+    cookie.into()
+}
+
+pub mod errors {
+    //! Errors that can occur when persisting flash messages.
+    use pavex::{Response, methods};
+
+    use crate::errors::ValueSerializationError;
+
+    /// The error returned by [`finalize_flash_messages`][super::finalize_flash_messages].
+    #[derive(Debug, thiserror::Error)]
+    #[non_exhaustive]
+    pub enum FinalizeFlashError {
+        #[error("Failed to serialize the pending flash messages")]
+        SerializationError(#[from] serde_json::Error),
+        #[error("Failed to store the pending flash messages in the client-side session state")]
+        ClientStateError(#[from] ValueSerializationError),
+        #[error(
+            "The flash cookie (`{cookie_name}`) is not configured to be signed nor encrypted. \
+            This is a security risk, as flash messages may be forged by an attacker. \
+            Configure the cookie processor to sign or encrypt the flash cookie; check out \
+            https://docs.rs/biscotti/latest/biscotti/struct.ProcessorConfig.html#structfield.crypto_rules \
+            for more information."
+        )]
+        CryptoRequired { cookie_name: String },
+    }
+
+    #[methods]
+    impl FinalizeFlashError {
+        /// Convert the error into a response.
+        #[error_handler]
+        pub fn into_response(&self) -> Response {
+            Response::internal_server_error()
+        }
+    }
+}
@@ -22,6 +22,14 @@ impl SessionId {
         Self(uuid::Uuid::new_v4())
     }
 
+    /// Build a session identifier from a `uuid::Uuid` value.
+    ///
+    /// It is primarily useful when implementing custom session storage backends,
+    /// to rebuild the identifiers of the session records retrieved from the store.
+    pub fn from_uuid(uuid: uuid::Uuid) -> Self {
+        Self(uuid)
+    }
+
     /// Returns the inner `uuid::Uuid` value.
     pub fn inner(&self) -> uuid::Uuid {
         self.0
@@ -1,5 +1,13 @@
-use crate::{SessionId, State, config::SessionCookieConfig, wire::WireClientState};
-use pavex::{cookie::RequestCookies, methods};
+use crate::{
+    SessionId, State,
+    config::SessionCookieConfig,
+    store::SessionRecord,
+    wire::{WireClientState, join_chunks},
+};
+use pavex::{
+    cookie::{Processor, RequestCookies},
+    methods,
+};
 use pavex_tracing::fields::{ERROR_DETAILS, ERROR_MESSAGE, error_details, error_message};
 
 /// The session information attached to the incoming request.
@@ -8,6 +16,47 @@ use pavex_tracing::fields::{ERROR_DETAILS, ERROR_MESSAGE, error_details, error_m
 pub struct IncomingSession {
     pub(crate) id: SessionId,
     pub(crate) client_state: State,
+    /// The server-side state, if it was stored in the session cookie
+    /// by a [cookie-only store](crate::SessionStore::cookie_only).
+    pub(crate) server_state: Option<CookieServerState>,
+    /// The number of cookies the session state was split into.
+    pub(crate) n_chunks: usize,
+}
+
+/// The server-side state, as retrieved from the session cookie.
+pub(crate) struct CookieServerState {
+    pub(crate) state: State,
+    /// When the server-side state expires, as a UNIX timestamp (in seconds).
+    pub(crate) expires_at: i64,
+    /// When the server-side state was created, as a UNIX timestamp (in seconds).
+    pub(crate) created_at: Option<i64>,
+    /// The last time the server-side state was refreshed, as a UNIX timestamp (in seconds).
+    pub(crate) last_seen_at: Option<i64>,
+}
+
+impl CookieServerState {
+    /// Convert into a session record, computing the remaining TTL.
+    ///
+    /// Cookies written before we started tracking timestamps get them backfilled
+    /// to the current time.
+    ///
+    /// It returns `None` if the state has already expired.
+    pub(crate) fn into_record(self) -> Option<SessionRecord> {
+        let now = pavex::time::Timestamp::now().as_second();
+        let timestamp =
+            |ts: Option<i64>| pavex::time::Timestamp::from_second(ts.unwrap_or(now)).ok();
+        let remaining = u64::try_from(self.expires_at.checked_sub(now)?).ok()?;
+        if remaining == 0 {
+            return None;
+        }
+        Some(SessionRecord {
+            state: self.state,
+            ttl: std::time::Duration::from_secs(remaining),
+            version: None,
+            created_at: timestamp(self.created_at),
+            last_seen_at: timestamp(self.last_seen_at),
+        })
+    }
 }
 
 #[methods]
@@ -15,13 +64,62 @@ impl IncomingSession {
     /// Extract a session cookie from the incoming request, if it exists.
     ///
     /// If the cookie is not found, or if the cookie is invalid, this method will return `None`.
+    ///
+    /// Session cookies that carry the server-side state, written by
+    /// [cookie-only stores](crate::SessionStore::cookie_only), are only accepted if
+    /// `processor` is configured to encrypt all of them. Otherwise, the server-side state
+    /// could have been forged by the client: the cookies are ignored and this method
+    /// returns `None`.
     #[request_scoped]
-    pub fn extract(cookies: &RequestCookies<'_>, config: &SessionCookieConfig) -> Option<Self> {
+    pub fn extract(
+        cookies: &RequestCookies<'_>,
+        config: &SessionCookieConfig,
+        processor: &Processor,
+    ) -> Option<Self> {
         let cookie = cookies.get(&config.name)?;
-        match serde_json::from_str::<WireClientState>(cookie.value()) {
+        // Chunked cookies, written by cookie-only stores, start with a header
+        // rather than with a JSON object.
+        let (payload, n_chunks) = if cookie.value().starts_with('{') {
+            (cookie.value().to_owned(), 1)
+        } else {
+            match join_chunks(|i| {
+                cookies
+                    .get(&config.chunk_name(i))
+                    .map(|c| c.value().to_owned())
+            }) {
+                Ok(p) => p,
+                Err(e) => {
+                    tracing::event!(
+                        tracing::Level::WARN,
+                        { ERROR_MESSAGE } = e,
+                        "Invalid client state for session, creating a new session."
+                    );
+                    return None;
+                }
+            }
+        };
+        match serde_json::from_str::<WireClientState>(&payload) {
+            Ok(s)
+                if s.server_state.is_some()
+                    && !(0..n_chunks).all(|i| processor.will_encrypt(&config.chunk_name(i))) =>
+            {
+                tracing::event!(
+                    tracing::Level::WARN,
+                    "The session cookie carries the server-side state, but it's not configured \
+                    to be encrypted. Ignoring it and creating a new session."
+                );
+                None
+            }
             Ok(s) => Some(Self {
                 id: s.session_id,
                 client_state: s.user_values.into_owned(),
+                server_state: s.server_state.map(|s| CookieServerState {
+                    state: s.values.into_owned(),
+                    expires_at: s.expires_at,
+                    created_at: s.created_at,
+                    last_seen_at: s.last_seen_at,
+                }),
+                n_chunks,
             }),
             Err(e) => {
                 tracing::event!(
@@ -40,6 +138,8 @@ impl IncomingSession {
         Self {
             id,
             client_state: state,
+            server_state: None,
+            n_chunks: 1,
         }
     }
 }
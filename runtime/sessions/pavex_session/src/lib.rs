@@ -4,12 +4,16 @@ Session management for Pavex.
 Check out the [session guide in Pavex's documentation](https://pavex.dev/guide/sessions/) for a thorough introduction to sessions
 and how to use them in your application.
 */
+pub mod codec;
 pub mod config;
+pub mod flash;
 mod id;
 mod incoming;
 mod middleware;
 mod session_;
 mod store_;
+mod timeout;
+pub mod value;
 pub(crate) mod wire;
 
 use std::collections::HashMap;
@@ -20,11 +24,15 @@ pub use middleware::{FINALIZE_SESSION, finalize_session};
 use pavex::methods;
 pub use session_::Session;
 pub use store_::SessionStore;
+pub use timeout::SessionTimeout;
+pub use value::Value;
 
 pub mod store {
     //! Types and traits related to [`SessionStore`][super::SessionStore].
     pub use crate::store_::errors;
-    pub use crate::store_::{SessionRecord, SessionRecordRef, SessionStorageBackend};
+    pub use crate::store_::{
+        SessionMetadata, SessionRecord, SessionRecordRef, SessionStorageBackend,
+    };
 }
 
 pub use crate::session_::errors;
@@ -35,7 +43,7 @@ pub mod client {
 }
 
 /// A convenient alias for the shape of the session state.
-pub(crate) type State = HashMap<std::borrow::Cow<'static, str>, serde_json::Value>;
+pub(crate) type State = HashMap<std::borrow::Cow<'static, str>, Value>;
 
 #[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -52,6 +60,9 @@ pub struct SessionConfig {
     #[serde(default)]
     /// Configure how the session state should behave.
     pub state: crate::config::SessionStateConfig,
+    #[serde(default)]
+    /// Configure how flash messages are stored.
+    pub flash: crate::config::FlashConfig,
 }
 
 #[methods]
@@ -72,4 +83,10 @@ impl SessionConfig {
     pub fn state_config(&self) -> &crate::config::SessionStateConfig {
         &self.state
     }
+
+    #[doc(hidden)]
+    #[transient]
+    pub fn flash_config(&self) -> &crate::config::FlashConfig {
+        &self.flash
+    }
 }
@@ -24,15 +24,18 @@ pub async fn finalize_session<'store>(
 ) -> Result<Response, FinalizeError> {
     // If the client-side session state is not empty, we require encryption
     // to minimize the risk of exposure for sensitive data.
-    let must_encrypt = !session.client().is_empty();
-    let cookie = session.finalize().await?;
+    // The same applies to cookie-only sessions, since the session cookies
+    // hold the server-side state as well.
+    let must_encrypt = !session.client().is_empty() || session.is_cookie_only();
+    let cookies = session.finalize_cookies().await?;
 
-    Span::current().record("session.cookie.set", cookie.is_some());
+    Span::current().record("session.cookie.set", !cookies.is_empty());
 
-    if let Some(cookie) = cookie {
+    for cookie in cookies {
         let will_encrypt = processor.will_encrypt(cookie.name());
 
-        if must_encrypt && !will_encrypt {
+        // Removal cookies carry no state, there's nothing to protect.
+        if must_encrypt && !will_encrypt && !cookie.value().is_empty() {
             return Err(FinalizeError::EncryptionRequired {
                 cookie_name: cookie.name().to_string(),
             });
@@ -4,12 +4,11 @@ use errors::{
 };
 use pavex::cookie::{RemovalCookie, ResponseCookie};
 use pavex::methods;
-use pavex::time::SignedDuration;
+use pavex::time::{SignedDuration, Timestamp};
 use serde::Serialize;
 use serde::de::DeserializeOwned;
-use serde_json::Value;
 use std::borrow::Cow;
-use std::cell::OnceCell;
+use std::cell::{Cell, OnceCell};
 use std::collections::HashMap;
 use std::marker::PhantomData;
 use std::sync::MutexGuard;
@@ -17,14 +16,19 @@ use std::sync::MutexGuard;
 use crate::SessionConfig;
 use crate::SessionId;
 use crate::SessionStore;
+use crate::SessionTimeout;
 use crate::State;
+use crate::Value;
 use crate::config::{
-    MissingServerState, ServerStateCreation, SessionCookieKind, TtlExtensionTrigger,
+    ConcurrentUpdatePolicy, MissingServerState, ServerStateCreation, SessionCookieKind,
+    TtlExtensionTrigger,
 };
-use crate::incoming::IncomingSession;
+use crate::incoming::{CookieServerState, IncomingSession};
 use crate::store::SessionRecordRef;
-use crate::store::errors::{ChangeIdError, DeleteError, LoadError};
-use crate::wire::WireClientState;
+use crate::store::errors::{
+    ChangeIdError, DeleteError, LoadError, UnknownIdError, UpdateError, VersionConflictError,
+};
+use crate::wire::{WireClientState, WireServerState, split_into_chunks};
 
 /// The current HTTP session.
 ///
@@ -57,6 +61,19 @@ pub struct Session<'store> {
     invalidated: InvalidationFlag,
     store: &'store SessionStore,
     config: &'store SessionConfig,
+    /// The server-side state found in the session cookie.
+    ///
+    /// It's only used by [cookie-only stores](SessionStore::cookie_only),
+    /// and it's consumed when the server-side state is loaded.
+    cookie_server_state: Cell<Option<CookieServerState>>,
+    /// The number of cookies the incoming session state was split into.
+    incoming_n_chunks: usize,
+    /// The principal the session must be associated with, when syncing.
+    principal: Option<Principal>,
+    /// The timestamps of the server-side record, set when it's loaded.
+    timestamps: Cell<RecordTimestamps>,
+    /// Set if the session was invalidated because it exceeded one of the configured timeouts.
+    timeout: OnceCell<SessionTimeout>,
     /// This field is used to prevent `Send` being implemented for `Session`.
     _unsend: PhantomUnsend,
 }
@@ -68,6 +85,8 @@ impl std::fmt::Debug for Session<'_> {
             .field("server_state", &self.server_state)
             .field("client_state", &self.client_state)
             .field("invalidated", &self.invalidated)
+            .field("principal", &self.principal)
+            .field("timeout", &self.timeout)
             .field("store", &self.store)
             .field("config", &self.config)
             .finish()
@@ -137,25 +156,71 @@ impl CurrentSessionId {
     }
 }
 
+#[derive(Debug, Clone)]
+struct Principal {
+    id: String,
+    user_agent: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct RecordTimestamps {
+    created_at: Option<Timestamp>,
+    last_seen_at: Option<Timestamp>,
+}
+
 #[derive(Debug, Clone)]
 enum ClientState {
     Unchanged { state: State },
     Updated { state: State },
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq)]
 enum ServerState {
     Unchanged {
         state: State,
         ttl: std::time::Duration,
+        /// The version of the record, if the store keeps track of it.
+        version: Option<u64>,
     },
     DoesNotExist,
     MarkedForDeletion,
     Changed {
         state: State,
+        /// The record version the changes were made against.
+        ///
+        /// It's set if conflicting updates must be detected when syncing.
+        base: Option<BaseVersion>,
     },
 }
 
+#[derive(Debug, Clone, PartialEq)]
+struct BaseVersion {
+    version: u64,
+    /// The state, as it was loaded from the store.
+    ///
+    /// It's only kept around if concurrent changes must be merged.
+    state: Option<State>,
+}
+
+impl BaseVersion {
+    /// Determine the base version for the changes about to be applied to a loaded state,
+    /// according to the chosen concurrency policy.
+    fn new(config: &SessionConfig, version: Option<u64>, state: &State) -> Option<Self> {
+        let version = version?;
+        match config.state.concurrent_updates {
+            ConcurrentUpdatePolicy::LastWriteWins => None,
+            ConcurrentUpdatePolicy::Reject => Some(Self {
+                version,
+                state: None,
+            }),
+            ConcurrentUpdatePolicy::Merge => Some(Self {
+                version,
+                state: Some(state.clone()),
+            }),
+        }
+    }
+}
+
 #[methods]
 impl<'store> Session<'store> {
     /// Create a new HTTP session.
@@ -169,10 +234,11 @@ impl<'store> Session<'store> {
         config: &'store SessionConfig,
         incoming_session: Option<IncomingSession>,
     ) -> Self {
-        let (client_state, previous_session_id) = match incoming_session {
-            Some(s) => (s.client_state, Some(s.id)),
-            None => (Default::default(), None),
-        };
+        let (client_state, previous_session_id, cookie_server_state, incoming_n_chunks) =
+            match incoming_session {
+                Some(s) => (s.client_state, Some(s.id), s.server_state, s.n_chunks),
+                None => (Default::default(), None, None, 0),
+            };
         let (id, server_state) = match previous_session_id {
             Some(id) => (CurrentSessionId::Existing(id), None),
             None => (
@@ -189,6 +255,11 @@ impl<'store> Session<'store> {
             invalidated: InvalidationFlag::new(),
             store,
             config,
+            cookie_server_state: Cell::new(cookie_server_state),
+            incoming_n_chunks,
+            principal: None,
+            timestamps: Cell::new(RecordTimestamps::default()),
+            timeout: OnceCell::new(),
             _unsend: Default::default(),
         }
     }
@@ -207,7 +278,7 @@ impl Session<'_> {
     pub async fn get<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>, ServerGetError> {
         self.get_raw(key)
             .await?
-            .map(|value| serde_json::from_value(value.clone()))
+            .map(|value| crate::value::from_value(value.clone()))
             .transpose()
             .map_err(|e| {
                 ValueDeserializationError {
@@ -223,9 +294,9 @@ impl Session<'_> {
     ///
     /// If the state didn't have an entry for this key, the value is inserted and `None` is returned.\
     /// If the state did have an entry for this key, its value is updated and the old
-    /// value is returned in its raw JSON form.
+    /// value is returned in its raw form.
     ///
-    /// The provided value is serialized to JSON prior to being stored. If
+    /// The provided value is converted into a [`Value`] prior to being stored. If
     /// the serialization fails, an error is returned. If you'd prefer to
     /// take care of the serialization yourself, use [`insert_raw`][Self::insert_raw] instead.
     pub async fn insert<T, Key>(
@@ -238,7 +309,7 @@ impl Session<'_> {
         Key: Into<Cow<'static, str>>,
     {
         let key = key.into();
-        let value = match serde_json::to_value(value) {
+        let value = match crate::value::to_value(value) {
             Ok(t) => t,
             Err(source) => {
                 return Err(ValueSerializationError {
@@ -267,7 +338,7 @@ impl Session<'_> {
     ) -> Result<Option<T>, ServerRemoveError> {
         self.remove_raw(key)
             .await?
-            .map(serde_json::from_value)
+            .map(crate::value::from_value)
             .transpose()
             .map_err(|source| ValueDeserializationError {
                 key: key.to_string().into(),
@@ -282,7 +353,7 @@ impl Session<'_> {
         use ServerState::*;
 
         match force_load_ref(self).await? {
-            Unchanged { state, .. } | Changed { state } => Ok(state.is_empty()),
+            Unchanged { state, .. } | Changed { state, .. } => Ok(state.is_empty()),
             DoesNotExist | MarkedForDeletion => Ok(true),
         }
     }
@@ -292,7 +363,7 @@ impl Session<'_> {
         use ServerState::*;
 
         match force_load_ref(self).await? {
-            Unchanged { state, .. } | Changed { state } => Ok(state.get(key)),
+            Unchanged { state, .. } | Changed { state, .. } => Ok(state.get(key)),
             DoesNotExist => Ok(None),
             MarkedForDeletion => {
                 tracing::debug!(session.key = %key, "Tried to access a server-side value on a session marked for deletion.");
@@ -305,9 +376,9 @@ impl Session<'_> {
     ///
     /// If the state didn't have an entry for this key, the value is inserted and `None` is returned.\
     /// If the state did have an entry for this key, its value is updated and the old
-    /// value is returned in its raw JSON form.
+    /// value is returned in its raw form.
     ///
-    /// The provided value must be a JSON value, which will be stored as-is, without any
+    /// The provided value will be stored as-is, without any
     /// further manipulation. If you'd prefer to let `pavex_session` handle the serialization,
     /// use [`insert`][Self::insert] instead.
     pub async fn insert_raw<Key>(
@@ -319,7 +390,9 @@ impl Session<'_> {
         Key: Into<Cow<'static, str>>,
     {
         let mut existing_state;
+        let base;
         let key = key.into();
+        let config = self.config;
 
         use ServerState::*;
         match force_load_mut(self).await? {
@@ -327,16 +400,23 @@ impl Session<'_> {
                 tracing::debug!(session.key = %key, "Tried to insert a server-side value on a session marked for deletion.");
                 return Ok(None);
             }
-            Unchanged { state, .. } | Changed { state } => {
+            Unchanged { state, version, .. } => {
+                base = BaseVersion::new(config, *version, state);
+                existing_state = std::mem::take(state);
+            }
+            Changed { state, base: b } => {
+                base = b.take();
                 existing_state = std::mem::take(state);
             }
             DoesNotExist => {
+                base = None;
                 existing_state = HashMap::new();
             }
         };
         let old_value = existing_state.insert(key, value);
         self.server_state = new_cell_with(Some(ServerState::Changed {
             state: existing_state,
+            base,
         }));
         Ok(old_value)
     }
@@ -348,13 +428,24 @@ impl Session<'_> {
     /// If you want to deserialize the value as a specific type, use [`remove`][Self::remove] instead.
     pub async fn remove_raw(&mut self, key: &str) -> Result<Option<Value>, LoadError> {
         use ServerState::*;
+        let config = self.config;
         match force_load_mut(self).await? {
             MarkedForDeletion => {
                 tracing::debug!(session.key = %key, "Tried to delete a server-side value on a session marked for deletion.");
                 Ok(None)
             }
             DoesNotExist => Ok(None),
-            Unchanged { state, .. } | Changed { state } => Ok(state.remove(key)),
+            Changed { state, .. } => Ok(state.remove(key)),
+            Unchanged { state, version, .. } => {
+                if !state.contains_key(key) {
+                    return Ok(None);
+                }
+                let base = BaseVersion::new(config, *version, state);
+                let mut state = std::mem::take(state);
+                let old_value = state.remove(key);
+                self.server_state = new_cell_with(Some(Changed { state, base }));
+                Ok(old_value)
+            }
         }
     }
 
@@ -375,16 +466,19 @@ impl Session<'_> {
     /// if you want to delete the session altogether.
     pub async fn clear(&mut self) -> Result<(), LoadError> {
         use ServerState::*;
+        let config = self.config;
         match force_load_mut(self).await? {
             MarkedForDeletion | DoesNotExist => {}
-            Unchanged { state, .. } => {
+            Unchanged { state, version, .. } => {
                 if !state.is_empty() {
+                    let base = BaseVersion::new(config, *version, state);
                     self.server_state = new_cell_with(Some(ServerState::Changed {
                         state: HashMap::new(),
+                        base,
                     }));
                 }
             }
-            Changed { state } => {
+            Changed { state, .. } => {
                 state.clear();
             }
         }
@@ -429,6 +523,43 @@ impl Session<'_> {
         };
     }
 
+    /// Associate the session with a principal—e.g. the id of the user who just logged in.
+    ///
+    /// The association is recorded in the store when the session is synced,
+    /// allowing you to [list](SessionStore::list_by_principal) or
+    /// [delete](SessionStore::delete_by_principal) all the sessions of a principal.
+    /// A server-side record is created for the session, if it doesn't exist already.
+    ///
+    /// Syncing fails if the store doesn't support indexing sessions by principal.
+    pub async fn set_principal(
+        &mut self,
+        principal_id: impl Into<String>,
+        user_agent: Option<String>,
+    ) -> Result<(), LoadError> {
+        use ServerState::*;
+
+        match force_load_mut(self).await? {
+            MarkedForDeletion => {
+                tracing::debug!(
+                    "Tried to associate a principal with a session marked for deletion."
+                );
+                return Ok(());
+            }
+            DoesNotExist => {
+                self.server_state = new_cell_with(Some(Changed {
+                    state: HashMap::new(),
+                    base: None,
+                }));
+            }
+            Unchanged { .. } | Changed { .. } => {}
+        }
+        self.principal = Some(Principal {
+            id: principal_id.into(),
+            user_agent,
+        });
+        Ok(())
+    }
+
     /// Invalidate the session.
     ///
     /// The server-side session state will be marked for deletion.
@@ -447,6 +578,19 @@ impl Session<'_> {
     pub fn is_invalidated(&self) -> bool {
         self.invalidated.is_invalidated()
     }
+
+    /// Check if the session was invalidated because it exceeded one of the
+    /// configured [timeouts](SessionTimeout).
+    ///
+    /// Timeouts are checked against the timestamps of the server-side record,
+    /// so the server-side state is loaded if it hasn't been already.
+    ///
+    /// Use it to tell an expired session apart from an anonymous one—e.g. to ask the
+    /// user to log in again rather than returning a generic error.
+    pub async fn timed_out(&self) -> Result<Option<SessionTimeout>, LoadError> {
+        force_load(self).await?;
+        Ok(self.timeout.get().copied())
+    }
 }
 
 /// Control when the server-side state is synchronized with the store.
@@ -466,13 +610,19 @@ impl Session<'_> {
                 || matches!(self.client_state, ClientState::Updated { .. });
             has_client_side && state_config.server_state_creation == ServerStateCreation::NeverSkip
         };
+        // Set when updating a versioned record.
+        let mut merged_state = None;
+        let mut synced_version = None;
         use ServerState::*;
         match self.server_state.get() {
             Some(DoesNotExist) => match self.id {
                 CurrentSessionId::NewlyGenerated(id) | CurrentSessionId::Existing(id) => {
                     if create_if_empty {
                         self.store
-                            .create(&id, SessionRecordRef::empty(fresh_ttl))
+                            .create(
+                                &id,
+                                SessionRecordRef::empty(fresh_ttl, self.config.state.codec),
+                            )
                             .await?;
                     }
                 }
@@ -498,17 +648,12 @@ impl Session<'_> {
             Some(Unchanged {
                 state,
                 ttl: remaining_ttl,
+                ..
             }) => {
                 match self.id {
                     CurrentSessionId::Existing(old) => {
-                        if state_config.extend_ttl == TtlExtensionTrigger::OnStateLoadsAndChanges {
-                            let extend = state_config
-                                .ttl_extension_threshold
-                                .map(|ratio| *remaining_ttl < fresh_ttl.mul_f32(ratio.inner()))
-                                .unwrap_or(true);
-                            if extend {
-                                self.store.update_ttl(&old, fresh_ttl).await?;
-                            }
+                        if self.should_extend_ttl(*remaining_ttl) {
+                            self.store.update_ttl(&old, fresh_ttl).await?;
                         }
                     }
                     CurrentSessionId::ToBeRenamed { old, new } => {
@@ -522,6 +667,7 @@ impl Session<'_> {
                                 let record = SessionRecordRef {
                                     state: Cow::Borrowed(state),
                                     ttl: fresh_ttl,
+                                    codec: self.config.state.codec,
                                 };
                                 self.store.create(&new, record).await?;
                             }
@@ -533,7 +679,10 @@ impl Session<'_> {
                     CurrentSessionId::NewlyGenerated(new) => {
                         if create_if_empty {
                             self.store
-                                .create(&new, SessionRecordRef::empty(fresh_ttl))
+                                .create(
+                                    &new,
+                                    SessionRecordRef::empty(fresh_ttl, self.config.state.codec),
+                                )
                                 .await?;
                         }
 
@@ -563,48 +712,80 @@ impl Session<'_> {
                     )
                 }
             },
-            Some(Changed { state }) => {
+            Some(Changed { state, base }) => {
                 let record = SessionRecordRef {
                     state: Cow::Borrowed(state),
                     ttl: fresh_ttl,
+                    codec: self.config.state.codec,
                 };
-                match self.id {
-                    CurrentSessionId::Existing(id) => {
-                        self.store.update(&id, record).await?;
-                    }
+                let existing_id = match self.id {
+                    CurrentSessionId::Existing(id) => Some(id),
                     CurrentSessionId::ToBeRenamed { old, new } => {
-                        if let Err(e) = self.store.delete(&old).await {
-                            match e {
-                                DeleteError::UnknownId(_) => {
-                                    // The record may have expired between this
-                                    // delete operation and the first (successful)
-                                    // load we performed at the beginning of this
-                                    // request processing task.
-                                    // Since we already have the value in memory,
-                                    // this is not an issue.
-                                }
-                                _ => {
-                                    return Err(e.into());
-                                }
+                        // We move the existing record over to the new id, rather than
+                        // creating a new one, to preserve its creation timestamp.
+                        match self.store.change_id(&old, &new).await {
+                            Ok(_) => Some(new),
+                            Err(ChangeIdError::UnknownId(_)) => {
+                                // The record may have expired between this
+                                // operation and the first (successful)
+                                // load we performed at the beginning of this
+                                // request processing task.
+                                // Since we already have the value in memory,
+                                // this is not an issue.
+                                None
+                            }
+                            Err(e) => {
+                                return Err(e.into());
                             }
                         }
-                        self.store.create(&new, record).await?;
                     }
-                    CurrentSessionId::NewlyGenerated(id) => {
-                        self.store.create(&id, record).await?;
+                    CurrentSessionId::NewlyGenerated(_) => None,
+                };
+                match (existing_id, base) {
+                    (Some(id), Some(base)) => {
+                        let (merged, version) = self.update_if_unchanged(&id, state, base).await?;
+                        merged_state = merged;
+                        synced_version = Some(version);
+                    }
+                    (Some(id), None) => {
+                        self.store.update(&id, record).await?;
+                    }
+                    (None, _) => {
+                        self.store.create(&self.id.new_id(), record).await?;
                     }
                 }
             }
         };
 
+        if let Some(principal) = self.principal.take()
+            && !matches!(self.server_state.get(), Some(MarkedForDeletion))
+        {
+            self.store
+                .associate_principal(
+                    &self.id.new_id(),
+                    &principal.id,
+                    principal.user_agent.as_deref(),
+                )
+                .await?;
+        }
+
         self.server_state = {
             let old_state = self.server_state.take();
             let new_state = old_state.map(|state| match state {
-                Changed { state } => Unchanged {
-                    state,
+                Changed { state, .. } => Unchanged {
+                    state: merged_state.unwrap_or(state),
                     ttl: fresh_ttl,
+                    version: synced_version,
+                },
+                Unchanged {
+                    state,
+                    ttl,
+                    version,
+                } => Unchanged {
+                    state,
+                    ttl,
+                    version,
                 },
-                Unchanged { state, ttl } => Unchanged { state, ttl },
                 MarkedForDeletion => {
                     if self.is_invalidated() {
                         MarkedForDeletion
@@ -617,6 +798,7 @@ impl Session<'_> {
                         Unchanged {
                             state: HashMap::new(),
                             ttl: fresh_ttl,
+                            version: None,
                         }
                     } else {
                         DoesNotExist
@@ -642,90 +824,306 @@ impl Session<'_> {
     /// Sync the current server-side state with the chosen storage backend.
     /// If necessary, it returns a cookie to be attached to the outgoing response
     /// in order to sync the client-side state.
+    ///
+    /// # Cookie-only stores
+    ///
+    /// When using a [cookie-only store](SessionStore::cookie_only), `finalize` returns
+    /// [`FinalizeError::TooManyCookies`] if more than one cookie must be attached to the
+    /// response: the state is too large to fit in a single cookie, or a previously
+    /// larger state was split across multiple cookies and the stale chunks must be removed.
+    /// Whether that happens depends on the data stored in the session, therefore
+    /// you should always use [`finalize_cookies`](Self::finalize_cookies) with
+    /// cookie-only stores—[`finalize_session`](crate::finalize_session) already does.
     #[must_use = "The cookie returned by `finalize` must be attached to the outgoing HTTP response. \
         Failing to do so will push the session into an invalid state."]
     pub async fn finalize(&mut self) -> Result<Option<ResponseCookie<'static>>, FinalizeError> {
+        let mut cookies = self.finalize_cookies().await?;
+        if cookies.len() > 1 {
+            return Err(FinalizeError::TooManyCookies {
+                n_cookies: cookies.len(),
+            });
+        }
+        Ok(cookies.pop())
+    }
+
+    /// Sync the current server-side state with the chosen storage backend.
+    /// It returns the cookies to be attached to the outgoing response
+    /// in order to sync the client-side state.
+    ///
+    /// There is at most one cookie when using a storage backend.
+    /// When using a [cookie-only store](SessionStore::cookie_only), the session state may
+    /// be split across multiple cookies, and stale chunks are removed using
+    /// [removal cookies](RemovalCookie).
+    #[must_use = "The cookies returned by `finalize_cookies` must be attached to the outgoing HTTP response. \
+        Failing to do so will push the session into an invalid state."]
+    pub async fn finalize_cookies(
+        &mut self,
+    ) -> Result<Vec<ResponseCookie<'static>>, FinalizeError> {
+        let cookie_only = self.store.is_cookie_only();
+        // The server-side state must be written back to the cookie, so we need
+        // to load it even if it wasn't touched while processing the request.
+        let was_loaded = self.server_state.get().is_some();
+        if cookie_only {
+            force_load(self).await?;
+        } else if !was_loaded && self.config.state.enforces_timeouts() {
+            // Stale sessions must be invalidated even if the server-side state
+            // wasn't touched while processing the request.
+            force_load(self).await?;
+            if !self.is_invalidated() {
+                // We only loaded the state to check the timeouts: we discard it, to avoid
+                // refreshing the record as if the request had used it.
+                self.server_state = OnceCell::new();
+            }
+        }
+        let changed = matches!(self.server_state.get(), Some(ServerState::Changed { .. }));
+
         self.sync().await?;
 
         let cookie_config = &self.config.cookie;
-        let cookie_name = &cookie_config.name;
 
         if self.invalidated.is_invalidated() {
             if self.id.old_id().is_none() {
                 // This is a new session, so there's nothing on the client-side
                 // to be removed.
-                return Ok(None);
+                return Ok(Vec::new());
             }
-            let mut cookie = RemovalCookie::new(cookie_name.clone());
-            if let Some(domain) = cookie_config.domain.as_deref() {
-                cookie = cookie.set_domain(domain.to_owned());
+            let cookies = (0..self.incoming_n_chunks.max(1))
+                .map(|i| self.removal_cookie(cookie_config.chunk_name(i)))
+                .collect();
+            return Ok(cookies);
+        }
+
+        let (ClientState::Updated {
+            state: client_state,
+        }
+        | ClientState::Unchanged {
+            state: client_state,
+        }) = &self.client_state;
+        let server_record_exists = match &self.server_state.get() {
+            None => None,
+            Some(ServerState::Unchanged { .. }) => Some(true),
+            Some(ServerState::DoesNotExist) => Some(false),
+            Some(ServerState::MarkedForDeletion) | Some(ServerState::Changed { .. }) => {
+                unreachable!("The server state has just been synchronized.")
+            }
+        };
+        // The session is new, we don't have a server-side record, and the client state is empty.
+        // We don't need to create a session cookie in this case.
+        if client_state.is_empty()
+            && self.id.old_id().is_none()
+            && !server_record_exists.unwrap_or(true)
+        {
+            return Ok(Vec::new());
+        }
+        let server_state = match self.server_state.get() {
+            Some(ServerState::Unchanged { state, ttl, .. }) if cookie_only => {
+                // `sync` takes care of extending the TTL when using a storage backend.
+                // We must do it here for cookie-only sessions, but only if the state was
+                // loaded by the application: we loaded it ourselves otherwise.
+                let extend = was_loaded && self.should_extend_ttl(*ttl);
+                let ttl = if extend { self.config.state.ttl } else { *ttl };
+                let ttl = i64::try_from(ttl.as_secs()).unwrap_or(i64::MAX);
+                let now = Timestamp::now();
+                let timestamps = self.timestamps.get();
+                let last_seen_at = match timestamps.last_seen_at {
+                    Some(last_seen_at) if !(changed || extend) => last_seen_at,
+                    _ => now,
+                };
+                Some(WireServerState {
+                    values: Cow::Borrowed(state),
+                    expires_at: now.as_second().saturating_add(ttl),
+                    created_at: Some(timestamps.created_at.unwrap_or(now).as_second()),
+                    last_seen_at: Some(last_seen_at.as_second()),
+                })
             }
-            if let Some(path) = cookie_config.path.as_deref() {
-                cookie = cookie.set_path(path.to_owned());
+            _ => None,
+        };
+        let value = WireClientState {
+            session_id: self.id.new_id(),
+            user_values: Cow::Borrowed(client_state),
+            server_state,
+        };
+        let value = serde_json::to_string(&value)?;
+        let values = if cookie_only {
+            let chunks = split_into_chunks(&value);
+            let max_chunks = cookie_config.max_chunks.get();
+            if chunks.len() > max_chunks {
+                return Err(FinalizeError::StateTooLarge {
+                    n_chunks: chunks.len(),
+                    max_chunks,
+                });
             }
-            Ok(Some(cookie.into()))
+            chunks
         } else {
-            match &self.client_state {
-                ClientState::Updated {
-                    state: client_state,
-                }
-                | ClientState::Unchanged {
-                    state: client_state,
-                } => {
-                    let server_record_exists = match &self.server_state.get() {
-                        None => None,
-                        Some(ServerState::Unchanged { .. }) => Some(true),
-                        Some(ServerState::DoesNotExist) => Some(false),
-                        Some(ServerState::MarkedForDeletion)
-                        | Some(ServerState::Changed { .. }) => {
-                            unreachable!("The server state has just been synchronized.")
-                        }
-                    };
-                    // The session is new, we don't have a server-side record, and the client state is empty.
-                    // We don't need to create a session cookie in this case.
-                    if client_state.is_empty()
-                        && self.id.old_id().is_none()
-                        && !server_record_exists.unwrap_or(true)
-                    {
-                        return Ok(None);
-                    }
-                    let value = WireClientState {
-                        session_id: self.id.new_id(),
-                        user_values: Cow::Borrowed(client_state),
-                    };
-                    let value = serde_json::to_string(&value)?;
-                    let mut cookie = ResponseCookie::new(cookie_name.clone(), value);
-                    if let Some(domain) = cookie_config.domain.as_deref() {
-                        cookie = cookie.set_domain(domain.to_owned());
-                    }
-                    if let Some(path) = cookie_config.path.as_deref() {
-                        cookie = cookie.set_path(path.to_owned());
-                    }
-                    if let Some(same_site) = cookie_config.same_site {
-                        cookie = cookie.set_same_site(same_site);
-                    }
-                    if cookie_config.secure {
-                        cookie = cookie.set_secure(true);
-                    }
-                    if cookie_config.http_only {
-                        cookie = cookie.set_http_only(true);
-                    }
-                    if cookie_config.kind == SessionCookieKind::Persistent {
-                        let max_age: SignedDuration = self
-                            .config
-                            .state
-                            .ttl
-                            .try_into()
-                            .unwrap_or(SignedDuration::MAX);
-                        cookie = cookie.set_max_age(max_age);
-                    }
-                    Ok(Some(cookie))
-                }
+            vec![value]
+        };
+
+        let n_chunks = values.len();
+        let mut cookies: Vec<_> = values
+            .into_iter()
+            .enumerate()
+            .map(|(i, value)| self.session_cookie(cookie_config.chunk_name(i), value))
+            .collect();
+        // Get rid of the chunks that are no longer needed, if the state shrank.
+        cookies.extend(
+            (n_chunks..self.incoming_n_chunks)
+                .map(|i| self.removal_cookie(cookie_config.chunk_name(i))),
+        );
+        Ok(cookies)
+    }
+
+    fn session_cookie(&self, name: String, value: String) -> ResponseCookie<'static> {
+        let cookie_config = &self.config.cookie;
+        let mut cookie = ResponseCookie::new(name, value);
+        if let Some(domain) = cookie_config.domain.as_deref() {
+            cookie = cookie.set_domain(domain.to_owned());
+        }
+        if let Some(path) = cookie_config.path.as_deref() {
+            cookie = cookie.set_path(path.to_owned());
+        }
+        if let Some(same_site) = cookie_config.same_site {
+            cookie = cookie.set_same_site(same_site);
+        }
+        if cookie_config.secure {
+            cookie = cookie.set_secure(true);
+        }
+        if cookie_config.http_only {
+            cookie = cookie.set_http_only(true);
+        }
+        if cookie_config.kind == SessionCookieKind::Persistent {
+            let max_age: SignedDuration = self
+                .config
+                .state
+                .ttl
+                .try_into()
+                .unwrap_or(SignedDuration::MAX);
+            cookie = cookie.set_max_age(max_age);
+        }
+        cookie
+    }
+
+    fn removal_cookie(&self, name: String) -> ResponseCookie<'static> {
+        let cookie_config = &self.config.cookie;
+        let mut cookie = RemovalCookie::new(name);
+        if let Some(domain) = cookie_config.domain.as_deref() {
+            cookie = cookie.set_domain(domain.to_owned());
+        }
+        if let Some(path) = cookie_config.path.as_deref() {
+            cookie = cookie.set_path(path.to_owned());
+        }
+        cookie.into()
+    }
+
+    /// Returns `true` if the session is backed by a [cookie-only store](SessionStore::cookie_only).
+    pub(crate) fn is_cookie_only(&self) -> bool {
+        self.store.is_cookie_only()
+    }
+
+    /// Returns `true` if the session will still be around once the current request
+    /// has been processed—i.e. the request was part of a session that hasn't been invalidated,
+    /// or it started a new session by changing its state.
+    pub(crate) fn outlives_request(&self) -> bool {
+        if self.invalidated.is_invalidated() {
+            return false;
+        }
+        self.id.old_id().is_some()
+            || matches!(self.server_state.get(), Some(ServerState::Changed { .. }))
+            || !self.client().is_empty()
+    }
+
+    /// Whether the TTL of the server-side state should be extended,
+    /// given its remaining TTL.
+    fn should_extend_ttl(&self, remaining_ttl: std::time::Duration) -> bool {
+        let state_config = &self.config.state;
+        if state_config.extend_ttl != TtlExtensionTrigger::OnStateLoadsAndChanges {
+            return false;
+        }
+        let Some(ratio) = state_config.ttl_extension_threshold else {
+            return true;
+        };
+        if remaining_ttl < state_config.ttl.mul_f32(ratio.inner()) {
+            return true;
+        }
+        // The record must be refreshed often enough to prevent an active session
+        // from hitting the idle timeout.
+        match (
+            state_config.idle_timeout,
+            self.timestamps.get().last_seen_at,
+        ) {
+            (Some(idle_timeout), Some(last_seen_at)) => {
+                Timestamp::now() >= last_seen_at + idle_timeout.mul_f32(1. - ratio.inner())
             }
+            _ => false,
+        }
+    }
+
+    /// Update an existing record, as long as nobody else modified it
+    /// since it was loaded.
+    ///
+    /// If there is a conflict, the behaviour depends on the [`ConcurrentUpdatePolicy`]:
+    /// we either give up or merge our changes on top of the latest version of the record.
+    ///
+    /// It returns the merged state, if a merge took place, and the new version of the record.
+    async fn update_if_unchanged(
+        &self,
+        id: &SessionId,
+        state: &State,
+        base: &BaseVersion,
+    ) -> Result<(Option<State>, u64), SyncError> {
+        static MAX_N_ATTEMPTS: usize = 3;
+
+        let mut expected_version = base.version;
+        let mut merged: Option<State> = None;
+        for _ in 0..MAX_N_ATTEMPTS {
+            let record = SessionRecordRef {
+                state: Cow::Borrowed(merged.as_ref().unwrap_or(state)),
+                ttl: self.config.state.ttl,
+                codec: self.config.state.codec,
+            };
+            let e = match self
+                .store
+                .update_if_version(id, record, expected_version)
+                .await
+            {
+                Ok(version) => return Ok((merged, version)),
+                Err(UpdateError::Conflict(e)) => e,
+                Err(e) => return Err(e.into()),
+            };
+            let Some(original) = &base.state else {
+                return Err(e.into());
+            };
+            let Some(latest) = self.store.load(id).await? else {
+                return Err(UpdateError::from(UnknownIdError { id: *id }).into());
+            };
+            let Some(latest_version) = latest.version else {
+                return Err(e.into());
+            };
+            tracing::debug!(
+                "The server-side session state was modified by a concurrent request. Merging changes."
+            );
+            merged = Some(merge_changes(original, state, latest.state));
+            expected_version = latest_version;
         }
+        Err(VersionConflictError { id: *id }.into())
     }
 }
 
+/// Apply the changes that turned `original` into `current` on top of `latest`,
+/// key by key.
+fn merge_changes(original: &State, current: &State, mut latest: State) -> State {
+    for (key, value) in current {
+        if original.get(key) != Some(value) {
+            latest.insert(key.clone(), value.clone());
+        }
+    }
+    for key in original.keys() {
+        if !current.contains_key(key) {
+            latest.remove(key);
+        }
+    }
+    latest
+}
+
 /// APIs for manipulating the client-side session state.
 impl Session<'_> {
     /// Read values from the client-side state attached to this session.
@@ -754,7 +1152,7 @@ impl<'session> ClientSessionState<'session> {
         client_get(self.0, self.1, key)
     }
 
-    /// Get the raw JSON value associated with `key` from the client-side state.
+    /// Get the raw value associated with `key` from the client-side state.
     pub fn get_raw(&self, key: &str) -> Option<&'session Value> {
         client_get_raw(self.0, self.1, key)
     }
@@ -780,7 +1178,7 @@ impl ClientSessionStateMut<'_> {
         client_get(self.0, self.1, key)
     }
 
-    /// Get the raw JSON value associated with `key` from the client-side state.
+    /// Get the raw value associated with `key` from the client-side state.
     pub fn get_raw<'a>(&'a self, key: &str) -> Option<&'a Value> {
         client_get_raw(&*self.0, self.1, key)
     }
@@ -804,7 +1202,7 @@ impl ClientSessionStateMut<'_> {
         Key: Into<Cow<'static, str>>,
     {
         let key = key.into();
-        let value = match serde_json::to_value(value) {
+        let value = match crate::value::to_value(value) {
             Ok(t) => t,
             Err(e) => {
                 return Err(ValueSerializationError {
@@ -852,7 +1250,7 @@ impl ClientSessionStateMut<'_> {
         key: &str,
     ) -> Result<Option<T>, ValueDeserializationError> {
         self.remove_raw(key)
-            .map(|value| serde_json::from_value(value))
+            .map(|value| crate::value::from_value(value))
             .transpose()
             .map_err(|source| ValueDeserializationError {
                 key: key.to_string().into(),
@@ -911,7 +1309,7 @@ fn client_get<T: DeserializeOwned>(
     key: &str,
 ) -> Result<Option<T>, ValueDeserializationError> {
     client_get_raw(state, flag, key)
-        .map(|value| serde_json::from_value(value.clone()))
+        .map(|value| crate::value::from_value(value.clone()))
         .transpose()
         .map_err(|source| ValueDeserializationError {
             location: ValueLocation::Client,
@@ -920,7 +1318,7 @@ fn client_get<T: DeserializeOwned>(
         })
 }
 
-/// Get the raw JSON value associated with `key` from the client-side state.
+/// Get the raw value associated with `key` from the client-side state.
 fn client_get_raw<'session>(
     state: &'session ClientState,
     flag: &'session InvalidationFlag,
@@ -990,12 +1388,34 @@ async fn force_load(session: &Session<'_>) -> Result<(), LoadError> {
     if session.server_state.get().is_some() {
         return Ok(());
     }
-    let record = session.store.load(&session_id).await?;
+    let record = if session.store.is_cookie_only() {
+        session
+            .cookie_server_state
+            .take()
+            .and_then(CookieServerState::into_record)
+    } else {
+        session.store.load(&session_id).await?
+    };
     let mut must_invalidate = false;
+    let mut timestamps = RecordTimestamps::default();
+    let mut timeout = None;
     let server_state = match record {
-        Some(r) => ServerState::Unchanged {
-            state: r.state,
-            ttl: r.ttl,
+        Some(r) => match SessionTimeout::check(&session.config.state, &r) {
+            Some(t) => {
+                timeout = Some((t, r.state));
+                ServerState::MarkedForDeletion
+            }
+            None => {
+                timestamps = RecordTimestamps {
+                    created_at: r.created_at,
+                    last_seen_at: r.last_seen_at,
+                };
+                ServerState::Unchanged {
+                    state: r.state,
+                    ttl: r.ttl,
+                    version: r.version,
+                }
+            }
         },
         None => {
             match session.config.state.missing_server_state {
@@ -1028,6 +1448,16 @@ async fn force_load(session: &Session<'_>) -> Result<(), LoadError> {
             );
             session.invalidated.invalidate();
         }
+        if let Some((t, state)) = timeout {
+            tracing::info!(
+                session.timeout = ?t,
+                "The current session exceeded one of the configured timeouts. Invalidating it."
+            );
+            session.store.notify_timeout(&t, &state);
+            let _ = session.timeout.set(t);
+            session.invalidated.invalidate();
+        }
+        session.timestamps.set(timestamps);
     }
     Ok(())
 }
@@ -1039,7 +1469,8 @@ pub mod errors {
     use pavex::{Response, methods};
 
     use crate::store::errors::{
-        ChangeIdError, CreateError, DeleteError, LoadError, UpdateError, UpdateTtlError,
+        AssociatePrincipalError, ChangeIdError, CreateError, DeleteError, LoadError, UpdateError,
+        UpdateTtlError, VersionConflictError,
     };
 
     #[derive(Debug, thiserror::Error)]
@@ -1056,6 +1487,12 @@ pub mod errors {
         UpdateTtlError(#[from] UpdateTtlError),
         #[error("Failed to change the session id for a session record")]
         ChangeIdError(#[from] ChangeIdError),
+        #[error("Failed to associate a session record with a principal")]
+        AssociatePrincipalError(#[from] AssociatePrincipalError),
+        #[error("Failed to load the latest version of a session record")]
+        LoadError(#[from] LoadError),
+        #[error("The session record was modified by a concurrent request")]
+        Conflict(#[from] VersionConflictError),
     }
 
     #[derive(Debug, thiserror::Error)]
@@ -1101,7 +1538,7 @@ pub mod errors {
         pub(crate) location: ValueLocation,
         #[source]
         /// The underlying deserialization error.
-        pub(crate) source: serde_json::Error,
+        pub(crate) source: crate::value::Error,
     }
 
     #[derive(Debug, thiserror::Error)]
@@ -1117,7 +1554,7 @@ pub mod errors {
         pub(crate) location: ValueLocation,
         #[source]
         /// The underlying serialization error.
-        pub(crate) source: serde_json::Error,
+        pub(crate) source: crate::value::Error,
     }
 
     /// Where the value was stored.
@@ -1161,6 +1598,22 @@ pub mod errors {
             for more information."
         )]
         CryptoRequired { cookie_name: String },
+        #[error("Failed to load the server-side session state")]
+        LoadError(#[from] LoadError),
+        #[error(
+            "The session state doesn't fit in the maximum number of session cookies: \
+            it requires {n_chunks} cookies, but at most {max_chunks} are allowed. \
+            Store less data in the session or raise `max_chunks` in the session cookie configuration."
+        )]
+        StateTooLarge { n_chunks: usize, max_chunks: usize },
+        /// Returned by [`Session::finalize`](crate::Session::finalize) when the session
+        /// state must be synced using more than one cookie.
+        /// Use [`Session::finalize_cookies`](crate::Session::finalize_cookies) instead.
+        #[error(
+            "The session state was split across {n_cookies} cookies. \
+            Use `Session::finalize_cookies` to retrieve all of them."
+        )]
+        TooManyCookies { n_cookies: usize },
     }
 
     #[methods]
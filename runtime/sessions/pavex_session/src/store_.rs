@@ -1,10 +1,15 @@
 use crate::SessionId;
+use crate::State;
+use crate::Value;
+use crate::codec::{EncodeError, EncodedState, SessionCodec};
+use crate::timeout::{SessionTimeout, TimeoutHook};
 use errors::{
-    ChangeIdError, CreateError, DeleteError, DeleteExpiredError, LoadError, UpdateError,
-    UpdateTtlError,
+    AssociatePrincipalError, ChangeIdError, CreateError, DeleteByPrincipalError, DeleteError,
+    DeleteExpiredError, ListByPrincipalError, LoadError, PrincipalIndexUnsupportedError,
+    UpdateError, UpdateTtlError,
 };
-use serde_json::Value;
-use std::{borrow::Cow, collections::HashMap, num::NonZeroUsize};
+use pavex::time::Timestamp;
+use std::{borrow::Cow, collections::HashMap, num::NonZeroUsize, sync::Arc};
 
 /// Where server-side session records are stored.
 ///
@@ -12,8 +17,27 @@ use std::{borrow::Cow, collections::HashMap, num::NonZeroUsize};
 /// [around your chosen storage backend implementation][`SessionStorageBackend`],
 /// removing the need to specify the concrete type of the storage backend
 /// everywhere in your code.
+///
+/// # Cookie-only sessions
+///
+/// If you don't want to rely on a storage backend, you can use [`SessionStore::cookie_only`]:
+/// the whole session state, server-side state included, will be serialized and
+/// stored in the session cookie(s).
+///
+/// # Sessions by principal
+///
+/// Most storage backends can keep track of the principal (e.g. the user) each session
+/// belongs to, via [`Session::set_principal`](crate::Session::set_principal).
+/// You can then [list](Self::list_by_principal) or [delete](Self::delete_by_principal)
+/// all the sessions of a principal—e.g. to log a user out everywhere after a password change.
 #[derive(Debug)]
-pub struct SessionStore(Box<dyn SessionStorageBackend>);
+pub struct SessionStore(Storage, Option<TimeoutHook>);
+
+#[derive(Debug)]
+enum Storage {
+    Backend(Box<dyn SessionStorageBackend>),
+    Cookie,
+}
 
 impl SessionStore {
     /// Creates a new session store using the provided backend.
@@ -21,7 +45,73 @@ impl SessionStore {
     where
         Backend: SessionStorageBackend + 'static,
     {
-        Self(Box::new(backend))
+        Self(Storage::Backend(Box::new(backend)), None)
+    }
+
+    /// Creates a stateless session store.
+    ///
+    /// The server-side state is never sent to a storage backend: it is serialized,
+    /// together with the client-side state, into the session cookie.
+    /// If the serialized state is larger than what a single cookie can hold, it is split
+    /// across multiple cookies—see [`SessionCookieConfig::max_chunks`].
+    ///
+    /// All the methods exposed by a cookie-only store are no-ops, since
+    /// there are no server-side records to manipulate.
+    ///
+    /// # Encryption
+    ///
+    /// Cookie-only sessions must be encrypted, using one of the crypto rules in your
+    /// [`ProcessorConfig`].
+    /// The rule must cover the session cookie as well as all its chunks—e.g. `id`, `id.1`,
+    /// `id.2` and `id.3` with the default configuration.
+    /// Use the rule's fallbacks to rotate keys: the session cookies are re-encrypted
+    /// with the primary key every time they are sent back to the client.
+    /// Session cookies that carry the server-side state are ignored if they are not
+    /// covered by an encryption rule.
+    ///
+    /// # Finalization
+    ///
+    /// The session state may be synced using multiple cookies, therefore
+    /// [`Session::finalize`] may fail with [`FinalizeError::TooManyCookies`].
+    /// Use [`Session::finalize_cookies`], or the [`finalize_session`] middleware, instead.
+    ///
+    /// [`SessionCookieConfig::max_chunks`]: crate::config::SessionCookieConfig::max_chunks
+    /// [`ProcessorConfig`]: pavex::cookie::ProcessorConfig
+    /// [`Session::finalize`]: crate::Session::finalize
+    /// [`Session::finalize_cookies`]: crate::Session::finalize_cookies
+    /// [`FinalizeError::TooManyCookies`]: crate::errors::FinalizeError::TooManyCookies
+    /// [`finalize_session`]: crate::finalize_session
+    pub fn cookie_only() -> Self {
+        Self(Storage::Cookie, None)
+    }
+
+    /// Register a callback to be invoked every time a session is invalidated
+    /// because it exceeded one of the [configured timeouts](SessionTimeout).
+    ///
+    /// The callback is given the reason for the timeout, as well as the server-side
+    /// state of the session, right before it gets deleted.
+    /// Use it to prepare for re-authentication—e.g. to emit an audit log entry or
+    /// to notify the principal the session belonged to.
+    ///
+    /// It replaces any previously registered callback.
+    pub fn on_timeout<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(&SessionTimeout, &HashMap<Cow<'static, str>, Value>) + Send + Sync + 'static,
+    {
+        self.1 = Some(TimeoutHook(Arc::new(hook)));
+        self
+    }
+
+    /// Invoke the timeout callback, if one was registered.
+    pub(crate) fn notify_timeout(&self, timeout: &SessionTimeout, state: &State) {
+        if let Some(hook) = &self.1 {
+            (hook.0)(timeout, state);
+        }
+    }
+
+    /// Returns `true` if the store was created via [`SessionStore::cookie_only`].
+    pub fn is_cookie_only(&self) -> bool {
+        matches!(self.0, Storage::Cookie)
     }
 
     /// Creates a new session record in the store using the provided ID.
@@ -30,7 +120,10 @@ impl SessionStore {
         id: &SessionId,
         record: SessionRecordRef<'_>,
     ) -> Result<(), CreateError> {
-        self.0.create(id, record).await
+        match &self.0 {
+            Storage::Backend(b) => b.create(id, record).await,
+            Storage::Cookie => Ok(()),
+        }
     }
 
     /// Update the state of an existing session in the store.
@@ -41,7 +134,27 @@ impl SessionStore {
         id: &SessionId,
         record: SessionRecordRef<'_>,
     ) -> Result<(), UpdateError> {
-        self.0.update(id, record).await
+        match &self.0 {
+            Storage::Backend(b) => b.update(id, record).await,
+            Storage::Cookie => Ok(()),
+        }
+    }
+
+    /// Update the state of an existing session in the store, but only if
+    /// the record hasn't been modified since it was loaded—i.e. if its version
+    /// is still `expected_version`.
+    ///
+    /// It returns the version of the updated record.
+    pub async fn update_if_version(
+        &self,
+        id: &SessionId,
+        record: SessionRecordRef<'_>,
+        expected_version: u64,
+    ) -> Result<u64, UpdateError> {
+        match &self.0 {
+            Storage::Backend(b) => b.update_if_version(id, record, expected_version).await,
+            Storage::Cookie => Ok(expected_version),
+        }
     }
 
     /// Update the TTL of an existing session record in the store.
@@ -52,7 +165,10 @@ impl SessionStore {
         id: &SessionId,
         ttl: std::time::Duration,
     ) -> Result<(), UpdateTtlError> {
-        self.0.update_ttl(id, ttl).await
+        match &self.0 {
+            Storage::Backend(b) => b.update_ttl(id, ttl).await,
+            Storage::Cookie => Ok(()),
+        }
     }
 
     /// Loads an existing session record from the store using the provided ID.
@@ -61,14 +177,20 @@ impl SessionStore {
     /// does not exist or has been invalidated (e.g., expired), `None` is
     /// returned.
     pub async fn load(&self, id: &SessionId) -> Result<Option<SessionRecord>, LoadError> {
-        self.0.load(id).await
+        match &self.0 {
+            Storage::Backend(b) => b.load(id).await,
+            Storage::Cookie => Ok(None),
+        }
     }
 
     /// Deletes a session record from the store using the provided ID.
     ///
     /// If the session exists, it is removed from the store.
     pub async fn delete(&self, id: &SessionId) -> Result<(), DeleteError> {
-        self.0.delete(id).await
+        match &self.0 {
+            Storage::Backend(b) => b.delete(id).await,
+            Storage::Cookie => Ok(()),
+        }
     }
 
     /// Change the session id associated with an existing session record.
@@ -79,7 +201,10 @@ impl SessionStore {
         old_id: &SessionId,
         new_id: &SessionId,
     ) -> Result<(), ChangeIdError> {
-        self.0.change_id(old_id, new_id).await
+        match &self.0 {
+            Storage::Backend(b) => b.change_id(old_id, new_id).await,
+            Storage::Cookie => Ok(()),
+        }
     }
 
     /// Deletes expired session records from the store.
@@ -87,12 +212,63 @@ impl SessionStore {
         &self,
         batch_size: Option<NonZeroUsize>,
     ) -> Result<usize, DeleteExpiredError> {
-        self.0.delete_expired(batch_size).await
+        match &self.0 {
+            Storage::Backend(b) => b.delete_expired(batch_size).await,
+            Storage::Cookie => Ok(0),
+        }
+    }
+
+    /// Associate an existing session record with a principal (e.g. a user).
+    ///
+    /// It fails if the store doesn't support indexing sessions by principal,
+    /// which is always the case for [cookie-only stores](Self::cookie_only).
+    pub async fn associate_principal(
+        &self,
+        id: &SessionId,
+        principal_id: &str,
+        user_agent: Option<&str>,
+    ) -> Result<(), AssociatePrincipalError> {
+        match &self.0 {
+            Storage::Backend(b) => b.associate_principal(id, principal_id, user_agent).await,
+            Storage::Cookie => Err(PrincipalIndexUnsupportedError.into()),
+        }
+    }
+
+    /// List all the active sessions associated with the given principal.
+    pub async fn list_by_principal(
+        &self,
+        principal_id: &str,
+    ) -> Result<Vec<SessionMetadata>, ListByPrincipalError> {
+        match &self.0 {
+            Storage::Backend(b) => b.list_by_principal(principal_id).await,
+            Storage::Cookie => Err(PrincipalIndexUnsupportedError.into()),
+        }
+    }
+
+    /// Delete all the sessions associated with the given principal.
+    ///
+    /// It returns the number of sessions that have been deleted.
+    pub async fn delete_by_principal(
+        &self,
+        principal_id: &str,
+    ) -> Result<usize, DeleteByPrincipalError> {
+        match &self.0 {
+            Storage::Backend(b) => b.delete_by_principal(principal_id).await,
+            Storage::Cookie => Err(PrincipalIndexUnsupportedError.into()),
+        }
     }
 }
 
 #[async_trait::async_trait]
 /// The interface of a session storage backend.
+///
+/// # Timestamps
+///
+/// Session timeouts can only be enforced if the backend keeps track of when each record
+/// was [created](SessionRecord::created_at) and [last written](SessionRecord::last_seen_at).
+/// Both timestamps must be set when a record is created; the latter must be refreshed by
+/// [`update`](Self::update), [`update_if_version`](Self::update_if_version) and
+/// [`update_ttl`](Self::update_ttl).
 pub trait SessionStorageBackend: std::fmt::Debug + Send + Sync {
     /// Creates a new session record in the store using the provided ID.
     async fn create(&self, id: &SessionId, record: SessionRecordRef<'_>)
@@ -104,6 +280,29 @@ pub trait SessionStorageBackend: std::fmt::Debug + Send + Sync {
     async fn update(&self, id: &SessionId, record: SessionRecordRef<'_>)
     -> Result<(), UpdateError>;
 
+    /// Update the state of an existing session in the store, but only if
+    /// its current version matches `expected_version`.
+    ///
+    /// If the versions don't match, the record must be left untouched and
+    /// a [`Conflict`](UpdateError::Conflict) error must be returned.
+    /// If successful, it returns the new version of the record.
+    ///
+    /// # Optional
+    ///
+    /// Versioning is optional: backends that don't return a [version](SessionRecord::version)
+    /// when loading records will never be asked to perform a conditional update.
+    /// The default implementation ignores `expected_version` and delegates to
+    /// [`update`](Self::update).
+    async fn update_if_version(
+        &self,
+        id: &SessionId,
+        record: SessionRecordRef<'_>,
+        expected_version: u64,
+    ) -> Result<u64, UpdateError> {
+        self.update(id, record).await?;
+        Ok(expected_version)
+    }
+
     /// Update the TTL of an existing session record in the store.
     ///
     /// It leaves the session state unchanged.
@@ -153,6 +352,54 @@ pub trait SessionStorageBackend: std::fmt::Debug + Send + Sync {
         &self,
         batch_size: Option<NonZeroUsize>,
     ) -> Result<usize, DeleteExpiredError>;
+
+    /// Associate an existing session record with a principal (e.g. a user),
+    /// replacing any previous association.
+    ///
+    /// The association must follow the session record: it must be updated when the
+    /// session id changes, and removed when the session record is deleted or expires.
+    ///
+    /// # Optional
+    ///
+    /// Indexing sessions by principal is optional: the default implementation returns
+    /// an [`Unsupported`](AssociatePrincipalError::Unsupported) error.
+    async fn associate_principal(
+        &self,
+        id: &SessionId,
+        principal_id: &str,
+        user_agent: Option<&str>,
+    ) -> Result<(), AssociatePrincipalError> {
+        let _ = (id, principal_id, user_agent);
+        Err(PrincipalIndexUnsupportedError.into())
+    }
+
+    /// List all the active (i.e. non-expired) sessions associated with the given principal.
+    ///
+    /// # Optional
+    ///
+    /// The default implementation returns an [`Unsupported`](ListByPrincipalError::Unsupported) error.
+    async fn list_by_principal(
+        &self,
+        principal_id: &str,
+    ) -> Result<Vec<SessionMetadata>, ListByPrincipalError> {
+        let _ = principal_id;
+        Err(PrincipalIndexUnsupportedError.into())
+    }
+
+    /// Delete all the sessions associated with the given principal.
+    ///
+    /// It returns the number of session records that have been deleted.
+    ///
+    /// # Optional
+    ///
+    /// The default implementation returns an [`Unsupported`](DeleteByPrincipalError::Unsupported) error.
+    async fn delete_by_principal(
+        &self,
+        principal_id: &str,
+    ) -> Result<usize, DeleteByPrincipalError> {
+        let _ = principal_id;
+        Err(PrincipalIndexUnsupportedError.into())
+    }
 }
 
 /// A server-side session record that's going to be stored in the
@@ -163,15 +410,26 @@ pub struct SessionRecordRef<'session> {
     pub state: Cow<'session, HashMap<Cow<'static, str>, Value>>,
     /// The session time-to-live.
     pub ttl: std::time::Duration,
+    /// The format that should be used to serialize the session state,
+    /// for storage backends that need to serialize it.
+    ///
+    /// It's set according to [`SessionStateConfig::codec`](crate::config::SessionStateConfig::codec).
+    pub codec: SessionCodec,
 }
 
 impl SessionRecordRef<'_> {
-    pub(crate) fn empty(ttl: std::time::Duration) -> Self {
+    pub(crate) fn empty(ttl: std::time::Duration, codec: SessionCodec) -> Self {
         Self {
             state: Cow::Owned(HashMap::new()),
             ttl,
+            codec,
         }
     }
+
+    /// Serialize the session state using the [configured codec](Self::codec).
+    pub fn encode_state(&self) -> Result<EncodedState, EncodeError> {
+        self.codec.encode(&self.state)
+    }
 }
 
 /// A server-side session record that was retrieved from the
@@ -182,11 +440,71 @@ pub struct SessionRecord {
     pub state: HashMap<Cow<'static, str>, Value>,
     /// The session time-to-live.
     pub ttl: std::time::Duration,
+    /// The version of the record, if the storage backend keeps track of it.
+    ///
+    /// The version must change every time the session state is modified.
+    /// It's used to detect concurrent updates to the same session record—see
+    /// [`SessionStorageBackend::update_if_version`].
+    pub version: Option<u64>,
+    /// When the record was created, if the storage backend keeps track of it.
+    ///
+    /// It must be preserved when the [session id changes](SessionStorageBackend::change_id).
+    /// It's used to enforce the [absolute timeout](crate::config::SessionStateConfig::absolute_timeout):
+    /// if that's configured, records without it are considered to be timed out.
+    pub created_at: Option<Timestamp>,
+    /// The last time the record was written to the store—i.e. created, updated or
+    /// had its TTL extended—if the storage backend keeps track of it.
+    ///
+    /// It's used to enforce the [idle timeout](crate::config::SessionStateConfig::idle_timeout):
+    /// if that's configured, records without it are considered to be timed out.
+    pub last_seen_at: Option<Timestamp>,
+}
+
+/// An active session associated with a principal,
+/// as returned by [`SessionStorageBackend::list_by_principal`].
+#[derive(Clone, PartialEq, Eq)]
+pub struct SessionMetadata {
+    /// The session id.
+    pub id: SessionId,
+    /// The principal the session is associated with.
+    pub principal_id: String,
+    /// When the session record was created, if the storage backend keeps track of it.
+    ///
+    /// It's `None` for records created before the storage backend started
+    /// tracking creation timestamps.
+    pub created_at: Option<Timestamp>,
+    /// When the session was associated with the principal—e.g. when the user logged in.
+    pub associated_at: Timestamp,
+    /// The last time the session record was written to the store.
+    ///
+    /// Depending on your [TTL extension policy](crate::config::TtlExtensionTrigger),
+    /// the session record may not be written on every request: treat this value as an approximation.
+    pub last_seen_at: Timestamp,
+    /// When the session record is going to expire.
+    pub expires_at: Timestamp,
+    /// The user agent of the client that was using the session
+    /// when it was associated with the principal, if known.
+    pub user_agent: Option<String>,
+}
+
+impl std::fmt::Debug for SessionMetadata {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SessionMetadata")
+            .field("id", &"**redacted**")
+            .field("principal_id", &self.principal_id)
+            .field("created_at", &self.created_at)
+            .field("associated_at", &self.associated_at)
+            .field("last_seen_at", &self.last_seen_at)
+            .field("expires_at", &self.expires_at)
+            .field("user_agent", &self.user_agent)
+            .finish()
+    }
 }
 
 /// Errors that can occur when interacting with a session storage backend.
 pub mod errors {
     use crate::SessionId;
+    use crate::codec::EncodeError;
 
     #[non_exhaustive]
     #[derive(Debug, thiserror::Error)]
@@ -194,7 +512,7 @@ pub mod errors {
     pub enum CreateError {
         /// Failed to serialize the session state.
         #[error("Failed to serialize the session state.")]
-        SerializationError(#[from] serde_json::Error),
+        SerializationError(#[from] EncodeError),
         #[error(transparent)]
         /// A session with the same ID already exists.
         DuplicateId(#[from] DuplicateIdError),
@@ -209,10 +527,13 @@ pub mod errors {
     pub enum UpdateError {
         #[error("Failed to serialize the session state.")]
         /// Failed to serialize the session state.
-        SerializationError(#[from] serde_json::Error),
+        SerializationError(#[from] EncodeError),
         #[error(transparent)]
         /// There is no session with the given ID.
         UnknownIdError(#[from] UnknownIdError),
+        #[error(transparent)]
+        /// The session record was modified after it was loaded.
+        Conflict(#[from] VersionConflictError),
         /// Something else went wrong when updating the session record.
         #[error("Something went wrong when updating the session record.")]
         Other(#[source] anyhow::Error),
@@ -274,6 +595,59 @@ pub mod errors {
     #[error("Something went wrong when deleting expired sessions")]
     pub struct DeleteExpiredError(#[from] anyhow::Error);
 
+    #[non_exhaustive]
+    #[derive(Debug, thiserror::Error)]
+    /// The error returned by [`SessionStorageBackend::associate_principal`][super::SessionStorageBackend::associate_principal].
+    pub enum AssociatePrincipalError {
+        #[error(transparent)]
+        /// There is no session with the given ID.
+        UnknownId(#[from] UnknownIdError),
+        #[error(transparent)]
+        /// The storage backend can't index sessions by principal.
+        Unsupported(#[from] PrincipalIndexUnsupportedError),
+        /// Something else went wrong when associating the session record with a principal.
+        #[error("Something went wrong when associating the session record with a principal.")]
+        Other(#[source] anyhow::Error),
+    }
+
+    #[non_exhaustive]
+    #[derive(Debug, thiserror::Error)]
+    /// The error returned by [`SessionStorageBackend::list_by_principal`][super::SessionStorageBackend::list_by_principal].
+    pub enum ListByPrincipalError {
+        #[error(transparent)]
+        /// The storage backend can't index sessions by principal.
+        Unsupported(#[from] PrincipalIndexUnsupportedError),
+        /// Something else went wrong when listing the sessions of a principal.
+        #[error("Something went wrong when listing the sessions associated with a principal.")]
+        Other(#[source] anyhow::Error),
+    }
+
+    #[non_exhaustive]
+    #[derive(Debug, thiserror::Error)]
+    /// The error returned by [`SessionStorageBackend::delete_by_principal`][super::SessionStorageBackend::delete_by_principal].
+    pub enum DeleteByPrincipalError {
+        #[error(transparent)]
+        /// The storage backend can't index sessions by principal.
+        Unsupported(#[from] PrincipalIndexUnsupportedError),
+        /// Something else went wrong when deleting the sessions of a principal.
+        #[error("Something went wrong when deleting the sessions associated with a principal.")]
+        Other(#[source] anyhow::Error),
+    }
+
+    impl From<ListByPrincipalError> for DeleteByPrincipalError {
+        fn from(e: ListByPrincipalError) -> Self {
+            match e {
+                ListByPrincipalError::Unsupported(e) => Self::Unsupported(e),
+                ListByPrincipalError::Other(e) => Self::Other(e),
+            }
+        }
+    }
+
+    #[derive(Debug, thiserror::Error)]
+    #[error("The session storage backend doesn't support indexing sessions by principal")]
+    /// The session storage backend doesn't support indexing sessions by principal.
+    pub struct PrincipalIndexUnsupportedError;
+
     #[derive(thiserror::Error)]
     #[error("There is no session with the given id")]
     /// There is no session with the given ID.
@@ -287,6 +661,19 @@ pub mod errors {
         }
     }
 
+    #[derive(thiserror::Error)]
+    #[error("The session record was modified by someone else after it was loaded")]
+    /// The session record was modified by someone else after it was loaded.
+    pub struct VersionConflictError {
+        pub id: SessionId,
+    }
+
+    impl std::fmt::Debug for VersionConflictError {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.write_str("VersionConflictError")
+        }
+    }
+
     #[derive(thiserror::Error)]
     #[error("A session with the same ID already exists.")]
     /// A session with the same ID already exists.
@@ -0,0 +1,95 @@
+use std::sync::Arc;
+
+use pavex::time::Timestamp;
+
+use crate::State;
+use crate::config::SessionStateConfig;
+use crate::store::SessionRecord;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+/// The reason why a session was invalidated when its server-side state was loaded.
+///
+/// # Enforcement
+///
+/// Timeouts are checked when the server-side state is loaded, using the timestamps
+/// kept by the storage backend—see [`SessionRecord::created_at`] and [`SessionRecord::last_seen_at`].
+/// If you configured at least one timeout, [`finalize_session`](crate::finalize_session) loads the
+/// server-side state even if your request handler didn't touch the session, to make sure that
+/// stale sessions are invalidated.
+///
+/// If a timeout is configured, records that don't carry the corresponding timestamp are
+/// considered to be timed out—see [`SessionTimeout::Untracked`].
+/// The storage backends provided by Pavex backfill the timestamps of records created by
+/// previous versions on first load, so existing sessions survive the upgrade.
+///
+/// # Re-authentication
+///
+/// You can react to a timeout in two ways:
+///
+/// - In your request handlers, via [`Session::timed_out`](crate::Session::timed_out)—e.g.
+///   to redirect the user to the login page rather than returning a generic error.
+/// - Whenever it happens, via [`SessionStore::on_timeout`](crate::SessionStore::on_timeout)—e.g.
+///   to emit an audit log entry.
+pub enum SessionTimeout {
+    /// The session was inactive for longer than the
+    /// [idle timeout](SessionStateConfig::idle_timeout).
+    Idle {
+        /// The last time the session record was written to the store.
+        last_seen_at: Timestamp,
+    },
+    /// The session outlived the [absolute timeout](SessionStateConfig::absolute_timeout).
+    Absolute {
+        /// When the session record was created.
+        created_at: Timestamp,
+    },
+    /// A timeout is configured, but the session record doesn't carry the timestamp
+    /// required to enforce it.
+    ///
+    /// It usually means that the storage backend doesn't keep track of
+    /// [`SessionRecord::created_at`] or [`SessionRecord::last_seen_at`].
+    Untracked,
+}
+
+impl SessionTimeout {
+    /// Determine if the session record exceeded one of the configured timeouts.
+    ///
+    /// The absolute timeout takes precedence over the idle one.
+    /// Missing timestamps are treated as timed out, rather than letting the session live forever.
+    pub(crate) fn check(config: &SessionStateConfig, record: &SessionRecord) -> Option<Self> {
+        let now = Timestamp::now();
+        if let Some(timeout) = config.absolute_timeout {
+            match record.created_at {
+                Some(created_at) if now >= created_at + timeout => {
+                    return Some(Self::Absolute { created_at });
+                }
+                Some(_) => {}
+                None => return Some(Self::Untracked),
+            }
+        }
+        if let Some(timeout) = config.idle_timeout {
+            match record.last_seen_at {
+                Some(last_seen_at) if now >= last_seen_at + timeout => {
+                    return Some(Self::Idle { last_seen_at });
+                }
+                Some(_) => {}
+                None => return Some(Self::Untracked),
+            }
+        }
+        None
+    }
+}
+
+/// A callback invoked when a session times out.
+///
+/// See [`SessionStore::on_timeout`](crate::SessionStore::on_timeout).
+#[derive(Clone)]
+pub(crate) struct TimeoutHook(pub(crate) Arc<TimeoutCallback>);
+
+type TimeoutCallback = dyn Fn(&SessionTimeout, &State) + Send + Sync;
+
+impl std::fmt::Debug for TimeoutHook {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("TimeoutHook")
+    }
+}
@@ -0,0 +1,302 @@
+use std::collections::BTreeMap;
+
+use serde::de::{
+    DeserializeOwned, Deserializer, EnumAccess, IntoDeserializer, VariantAccess, Visitor,
+    value::{MapDeserializer, SeqDeserializer},
+};
+
+use super::{Error, Value};
+
+/// Interpret a [`Value`] as an instance of type `T`.
+///
+/// # Errors
+///
+/// It fails if the structure of the [`Value`] doesn't match the structure expected by `T`.
+pub fn from_value<T>(value: Value) -> Result<T, Error>
+where
+    T: DeserializeOwned,
+{
+    T::deserialize(value)
+}
+
+impl<'de> IntoDeserializer<'de, Error> for Value {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+impl<'de> Deserializer<'de> for Value {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            Value::Null => visitor.visit_unit(),
+            Value::Bool(b) => visitor.visit_bool(b),
+            Value::Integer(i) => {
+                if let Ok(u) = u64::try_from(i) {
+                    visitor.visit_u64(u)
+                } else if let Ok(i) = i64::try_from(i) {
+                    visitor.visit_i64(i)
+                } else {
+                    visitor.visit_i128(i)
+                }
+            }
+            Value::Float(f) => visitor.visit_f64(f),
+            Value::String(s) => visitor.visit_string(s),
+            Value::Bytes(b) => visitor.visit_byte_buf(b),
+            Value::Array(a) => {
+                let mut deserializer = SeqDeserializer::new(a.into_iter());
+                let value = visitor.visit_seq(&mut deserializer)?;
+                deserializer.end()?;
+                Ok(value)
+            }
+            Value::Map(m) => visit_map(m, visitor),
+        }
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            Value::Null => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            // Unit variants are represented as strings...
+            Value::String(variant) => visitor.visit_enum(variant.into_deserializer()),
+            // ...while all other variants are represented as single-key maps.
+            Value::Map(m) => {
+                let mut entries = m.into_iter();
+                let (Some((variant, value)), None) = (entries.next(), entries.next()) else {
+                    return Err(Error(
+                        "expected a map with a single key to deserialize an enum".into(),
+                    ));
+                };
+                visitor.visit_enum(EnumDeserializer { variant, value })
+            }
+            _ => Err(Error(
+                "expected a string or a map to deserialize an enum".into(),
+            )),
+        }
+    }
+
+    fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_any(visitor)
+    }
+
+    fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_any(visitor)
+    }
+
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        unit unit_struct seq tuple tuple_struct map struct identifier
+    }
+}
+
+fn visit_map<'de, V>(map: BTreeMap<String, Value>, visitor: V) -> Result<V::Value, Error>
+where
+    V: Visitor<'de>,
+{
+    let mut deserializer = MapDeserializer::new(map.into_iter().map(|(k, v)| (MapKey(k), v)));
+    let value = visitor.visit_map(&mut deserializer)?;
+    deserializer.end()?;
+    Ok(value)
+}
+
+/// The variant of an enum represented as a single-key map.
+struct EnumDeserializer {
+    variant: String,
+    value: Value,
+}
+
+impl<'de> EnumAccess<'de> for EnumDeserializer {
+    type Error = Error;
+    type Variant = Value;
+
+    fn variant_seed<S>(self, seed: S) -> Result<(S::Value, Value), Error>
+    where
+        S: serde::de::DeserializeSeed<'de>,
+    {
+        let variant = seed.deserialize(MapKey(self.variant))?;
+        Ok((variant, self.value))
+    }
+}
+
+impl<'de> VariantAccess<'de> for Value {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> {
+        match self {
+            Value::Null => Ok(()),
+            _ => Err(Error("expected a unit variant".into())),
+        }
+    }
+
+    fn newtype_variant_seed<S>(self, seed: S) -> Result<S::Value, Error>
+    where
+        S: serde::de::DeserializeSeed<'de>,
+    {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V>(self, _len: usize, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            Value::Array(_) => self.deserialize_any(visitor),
+            _ => Err(Error(
+                "expected an array to deserialize a tuple variant".into(),
+            )),
+        }
+    }
+
+    fn struct_variant<V>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            Value::Map(m) => visit_map(m, visitor),
+            _ => Err(Error(
+                "expected a map to deserialize a struct variant".into(),
+            )),
+        }
+    }
+}
+
+/// A map key.
+///
+/// Keys are always stored as strings: we parse them back into numbers
+/// or booleans if that's what the caller expects.
+struct MapKey(String);
+
+impl<'de> IntoDeserializer<'de, Error> for MapKey {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+macro_rules! deserialize_parsed_key {
+    ($($method:ident => $visit:ident),* $(,)?) => {
+        $(
+            fn $method<V>(self, visitor: V) -> Result<V::Value, Error>
+            where
+                V: Visitor<'de>,
+            {
+                match self.0.parse() {
+                    Ok(v) => visitor.$visit(v),
+                    Err(_) => visitor.visit_string(self.0),
+                }
+            }
+        )*
+    };
+}
+
+impl<'de> Deserializer<'de> for MapKey {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_string(self.0)
+    }
+
+    deserialize_parsed_key!(
+        deserialize_bool => visit_bool,
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_i128 => visit_i128,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_u128 => visit_u128,
+        deserialize_char => visit_char,
+    );
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_enum(self.0.into_deserializer())
+    }
+
+    serde::forward_to_deserialize_any! {
+        f32 f64 str string bytes byte_buf unit unit_struct seq tuple
+        tuple_struct map struct identifier ignored_any
+    }
+}
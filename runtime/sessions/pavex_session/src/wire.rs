@@ -10,4 +10,94 @@ pub(crate) struct WireClientState<'a> {
     pub(crate) session_id: SessionId,
     #[serde(rename = "1", skip_serializing_if = "HashMap::is_empty", default)]
     pub(crate) user_values: Cow<'a, State>,
+    /// Only populated when using a [cookie-only store](crate::SessionStore::cookie_only).
+    #[serde(rename = "2", skip_serializing_if = "Option::is_none", default)]
+    pub(crate) server_state: Option<WireServerState<'a>>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize)]
+/// The server-side state, when it's stored in the session cookie.
+pub(crate) struct WireServerState<'a> {
+    #[serde(rename = "0", skip_serializing_if = "HashMap::is_empty", default)]
+    pub(crate) values: Cow<'a, State>,
+    /// When the server-side state expires, as a UNIX timestamp (in seconds).
+    #[serde(rename = "1")]
+    pub(crate) expires_at: i64,
+    /// When the server-side state was created, as a UNIX timestamp (in seconds).
+    #[serde(rename = "2", skip_serializing_if = "Option::is_none", default)]
+    pub(crate) created_at: Option<i64>,
+    /// The last time the server-side state was refreshed, as a UNIX timestamp (in seconds).
+    #[serde(rename = "3", skip_serializing_if = "Option::is_none", default)]
+    pub(crate) last_seen_at: Option<i64>,
+}
+
+/// The maximum size of the (unencrypted) payload stored in each session cookie chunk.
+///
+/// Browsers reject cookies larger than 4096 bytes, including name and attributes.
+/// Encryption adds a nonce and an authentication tag (28 bytes), and the result is
+/// base64-encoded (4/3 overhead): a 2800-byte chunk becomes a ~3772-byte cookie value,
+/// leaving enough room for the cookie name and its attributes.
+pub(crate) const MAX_CHUNK_SIZE: usize = 2800;
+
+/// Split a serialized session state into chunks, one per cookie.
+///
+/// Each chunk is prefixed with a header (`{generation}:{index}:{n_chunks}:`).
+/// The generation is chosen at random every time the state is split, to detect
+/// chunks coming from different versions of the session state.
+pub(crate) fn split_into_chunks(payload: &str) -> Vec<String> {
+    let generation = format!("{:x}", uuid::Uuid::new_v4().as_u64_pair().0);
+    let mut bodies = Vec::new();
+    let mut rest = payload;
+    while !rest.is_empty() {
+        let mut end = rest.len().min(MAX_CHUNK_SIZE);
+        while !rest.is_char_boundary(end) {
+            end -= 1;
+        }
+        let (body, tail) = rest.split_at(end);
+        bodies.push(body);
+        rest = tail;
+    }
+    let n_chunks = bodies.len();
+    bodies
+        .into_iter()
+        .enumerate()
+        .map(|(i, body)| format!("{generation}:{i}:{n_chunks}:{body}"))
+        .collect()
+}
+
+/// Reassemble the session state from the chunks returned by `get_chunk`.
+///
+/// It returns the payload and the number of chunks it was split into,
+/// or an error message if the chunks are missing or inconsistent.
+pub(crate) fn join_chunks(
+    mut get_chunk: impl FnMut(usize) -> Option<String>,
+) -> Result<(String, usize), &'static str> {
+    let first = get_chunk(0).ok_or("The session cookie is missing")?;
+    let (generation, index, n_chunks, body) = parse_chunk(&first)?;
+    if index != 0 {
+        return Err("The session cookie doesn't hold the first chunk of the session state");
+    }
+    let mut payload = body.to_owned();
+    for i in 1..n_chunks {
+        let chunk = get_chunk(i).ok_or("One of the session cookie chunks is missing")?;
+        let (chunk_generation, index, chunk_n_chunks, body) = parse_chunk(&chunk)?;
+        if chunk_generation != generation || index != i || chunk_n_chunks != n_chunks {
+            return Err("The session cookie chunks belong to different versions of the session");
+        }
+        payload.push_str(body);
+    }
+    Ok((payload, n_chunks))
+}
+
+fn parse_chunk(chunk: &str) -> Result<(&str, usize, usize, &str), &'static str> {
+    const ERROR: &str = "The session cookie chunk has an invalid header";
+    let mut parts = chunk.splitn(4, ':');
+    let (Some(generation), Some(index), Some(n_chunks), Some(body)) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return Err(ERROR);
+    };
+    let index = index.parse().map_err(|_| ERROR)?;
+    let n_chunks = n_chunks.parse().map_err(|_| ERROR)?;
+    Ok((generation, index, n_chunks, body))
 }
@@ -0,0 +1,178 @@
+//! Verify the serialization formats supported for the server-side state.
+use std::{borrow::Cow, collections::BTreeMap, collections::HashMap};
+
+use pavex_session::{
+    Session, SessionConfig, Value,
+    codec::{EncodedState, SessionCodec},
+    value::{from_value, to_value},
+};
+
+use crate::{fixtures::store, helpers::SetCookie};
+
+#[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+enum Role {
+    Guest,
+    Member { since: u32 },
+    Admin(String),
+}
+
+#[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+struct Profile {
+    name: String,
+    age: Option<u8>,
+    balance: i64,
+    score: f64,
+    roles: Vec<Role>,
+    counters: BTreeMap<u32, bool>,
+}
+
+fn profile() -> Profile {
+    Profile {
+        name: "Ursula".into(),
+        age: None,
+        balance: i64::MIN,
+        score: 1.5,
+        roles: vec![
+            Role::Guest,
+            Role::Member { since: 2021 },
+            Role::Admin("root".into()),
+        ],
+        counters: BTreeMap::from([(1, true), (42, false)]),
+    }
+}
+
+fn state() -> HashMap<Cow<'static, str>, Value> {
+    HashMap::from([
+        ("profile".into(), to_value(profile()).unwrap()),
+        ("avatar".into(), Value::Bytes(vec![0, 1, 2, 255])),
+        ("empty".into(), Value::Null),
+        ("big".into(), Value::from(u64::MAX)),
+        ("negative".into(), Value::from(-7)),
+    ])
+}
+
+fn codecs() -> [SessionCodec; 4] {
+    [
+        SessionCodec::Json,
+        SessionCodec::MessagePack,
+        SessionCodec::Cbor,
+        SessionCodec::Postcard,
+    ]
+}
+
+#[test]
+fn values_roundtrip() {
+    let value = to_value(profile()).unwrap();
+    let Value::Map(fields) = &value else {
+        panic!("Expected a map, got {value:?}");
+    };
+    // Integer keys are converted into strings.
+    assert_eq!(
+        fields["counters"],
+        Value::Map(BTreeMap::from([
+            ("1".into(), Value::Bool(true)),
+            ("42".into(), Value::Bool(false)),
+        ]))
+    );
+    // Unit variants are represented as strings, all other variants as single-key maps.
+    assert_eq!(fields["roles"].as_array().unwrap()[0], Value::from("Guest"));
+    assert_eq!(
+        fields["roles"].as_array().unwrap()[2],
+        Value::Map(BTreeMap::from([("Admin".into(), Value::from("root"))]))
+    );
+
+    assert_eq!(from_value::<Profile>(value).unwrap(), profile());
+}
+
+#[test]
+fn mismatched_values_are_rejected() {
+    let err = from_value::<Profile>(Value::from("not a profile")).unwrap_err();
+    assert!(err.to_string().contains("invalid type"), "{err}");
+}
+
+#[test]
+fn every_codec_roundtrips_the_state() {
+    for codec in codecs() {
+        let encoded = codec.encode(&state()).unwrap();
+        let decoded = SessionCodec::decode(encoded.as_bytes()).unwrap();
+        // JSON has no dedicated representation for bytes.
+        let mut expected = state();
+        if codec == SessionCodec::Json {
+            expected.insert(
+                "avatar".into(),
+                Value::Array(vec![0.into(), 1.into(), 2.into(), 255.into()]),
+            );
+        }
+        assert_eq!(decoded, expected, "Mismatch for codec: {codec:?}");
+
+        let profile: Profile = from_value(decoded["profile"].clone()).unwrap();
+        assert_eq!(profile, self::profile(), "Mismatch for codec: {codec:?}");
+    }
+}
+
+#[test]
+fn cbor_and_postcard_preserve_128_bit_integers() {
+    let state = HashMap::from([("key".into(), Value::from(i128::MIN))]);
+    for codec in [SessionCodec::Cbor, SessionCodec::Postcard] {
+        let encoded = codec.encode(&state).unwrap();
+        let decoded = SessionCodec::decode(encoded.as_bytes()).unwrap();
+        assert_eq!(decoded, state, "Mismatch for codec: {codec:?}");
+    }
+}
+
+#[test]
+fn json_is_stored_as_is() {
+    let state = HashMap::from([("key".into(), Value::from("value"))]);
+    let encoded = SessionCodec::Json.encode(&state).unwrap();
+    assert_eq!(encoded, EncodedState::Json(r#"{"key":"value"}"#.into()));
+
+    // Records written by previous versions of `pavex_session` are plain JSON.
+    let decoded = SessionCodec::decode(br#"{"key": "value", "n": 1.5}"#).unwrap();
+    assert_eq!(decoded["key"], Value::from("value"));
+    assert_eq!(decoded["n"], Value::from(1.5));
+}
+
+#[test]
+fn binary_codecs_are_tagged() {
+    for codec in codecs().into_iter().skip(1) {
+        let encoded = codec.encode(&state()).unwrap();
+        let EncodedState::Binary(bytes) = encoded else {
+            panic!("Expected a binary payload for codec: {codec:?}");
+        };
+        assert_eq!(bytes[0], 0x00, "Missing marker for codec: {codec:?}");
+    }
+}
+
+#[test]
+fn unknown_formats_are_rejected() {
+    let err = SessionCodec::decode(&[0x00, 0xff, 0x01]).unwrap_err();
+    let source = std::error::Error::source(&err).unwrap();
+    assert_eq!(
+        source.to_string(),
+        "The session state was serialized using an unknown format (tag: 0xff)"
+    );
+}
+
+#[test]
+fn codec_can_be_configured() {
+    let config: SessionConfig = serde_json::from_str(r#"{"state": {"codec": "msgpack"}}"#).unwrap();
+    assert_eq!(config.state.codec, SessionCodec::MessagePack);
+    assert_eq!(SessionConfig::default().state.codec, SessionCodec::Json);
+}
+
+#[tokio::test]
+async fn sessions_use_the_configured_codec() {
+    for codec in codecs() {
+        let store = store();
+        let mut config = SessionConfig::default();
+        config.state.codec = codec;
+
+        let mut session = Session::new(&store, &config, None);
+        session.insert("profile", profile()).await.unwrap();
+        let cookie = SetCookie::parse(session.finalize().await.unwrap().unwrap());
+
+        let record = store.load(&cookie.id).await.unwrap().unwrap();
+        let profile: Profile = from_value(record.state["profile"].clone()).unwrap();
+        assert_eq!(profile, self::profile(), "Mismatch for codec: {codec:?}");
+    }
+}
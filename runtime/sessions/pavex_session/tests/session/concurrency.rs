@@ -0,0 +1,154 @@
+//! Verify how concurrent updates to the same session are handled.
+use std::collections::HashMap;
+
+use googletest::{
+    assert_that,
+    prelude::{eq, none, some},
+};
+use pavex_session::{
+    IncomingSession, Session, SessionConfig, SessionStore, Value, config::ConcurrentUpdatePolicy,
+    errors::SyncError, value::from_value,
+};
+
+use crate::fixtures::{SessionFixture, store};
+
+/// Set up a session with a pre-existing server-side state.
+async fn setup(store: &SessionStore) -> SessionFixture {
+    let fixture = SessionFixture {
+        server_state: Some(HashMap::from([
+            ("a".to_owned(), Value::from("initial")),
+            ("b".to_owned(), Value::from("initial")),
+        ])),
+        ..Default::default()
+    };
+    fixture.setup(store).await;
+    fixture
+}
+
+/// The incoming session for a request that belongs to the pre-existing session.
+fn incoming(fixture: &SessionFixture) -> IncomingSession {
+    IncomingSession::from_parts(fixture.id, HashMap::new())
+}
+
+/// Load the server-side state of the session, as it is in the store.
+async fn stored_state(store: &SessionStore, fixture: &SessionFixture) -> HashMap<String, String> {
+    let record = store.load(&fixture.id).await.unwrap().unwrap();
+    record
+        .state
+        .into_iter()
+        .map(|(k, v)| (k.into_owned(), from_value(v).unwrap()))
+        .collect()
+}
+
+#[tokio::test]
+async fn concurrent_changes_to_different_keys_are_merged() {
+    let (store, config) = (store(), SessionConfig::default());
+    let fixture = setup(&store).await;
+
+    let mut first = Session::new(&store, &config, Some(incoming(&fixture)));
+    let mut second = Session::new(&store, &config, Some(incoming(&fixture)));
+    first.force_load().await.unwrap();
+    second.force_load().await.unwrap();
+
+    first.insert("a", "first").await.unwrap();
+    second.insert("c", "second").await.unwrap();
+    second.remove_raw("b").await.unwrap();
+
+    first.sync().await.unwrap();
+    second.sync().await.unwrap();
+
+    let state = stored_state(&store, &fixture).await;
+    assert_that!(state.get("a"), some(eq("first")));
+    assert_that!(state.get("b"), none());
+    assert_that!(state.get("c"), some(eq("second")));
+    // The merged state is visible to the session that performed the merge.
+    let a: Option<String> = second.get("a").await.unwrap();
+    assert_that!(a, some(eq("first")));
+}
+
+#[tokio::test]
+async fn the_last_update_wins_if_both_requests_changed_the_same_key() {
+    let (store, config) = (store(), SessionConfig::default());
+    let fixture = setup(&store).await;
+
+    let mut first = Session::new(&store, &config, Some(incoming(&fixture)));
+    let mut second = Session::new(&store, &config, Some(incoming(&fixture)));
+    first.insert("a", "first").await.unwrap();
+    second.insert("a", "second").await.unwrap();
+
+    first.sync().await.unwrap();
+    second.sync().await.unwrap();
+
+    let state = stored_state(&store, &fixture).await;
+    assert_that!(state.get("a"), some(eq("second")));
+    assert_that!(state.get("b"), some(eq("initial")));
+}
+
+#[tokio::test]
+async fn concurrent_changes_are_rejected_if_the_policy_is_reject() {
+    let (store, mut config) = (store(), SessionConfig::default());
+    config.state.concurrent_updates = ConcurrentUpdatePolicy::Reject;
+    let fixture = setup(&store).await;
+
+    let mut first = Session::new(&store, &config, Some(incoming(&fixture)));
+    let mut second = Session::new(&store, &config, Some(incoming(&fixture)));
+    first.insert("a", "first").await.unwrap();
+    second.insert("c", "second").await.unwrap();
+
+    first.sync().await.unwrap();
+    let err = second.sync().await.unwrap_err();
+    assert!(matches!(err, SyncError::Conflict(_)));
+
+    let state = stored_state(&store, &fixture).await;
+    assert_that!(state.get("a"), some(eq("first")));
+    assert_that!(state.get("c"), none());
+}
+
+#[tokio::test]
+async fn concurrent_changes_are_overwritten_if_the_policy_is_last_write_wins() {
+    let (store, mut config) = (store(), SessionConfig::default());
+    config.state.concurrent_updates = ConcurrentUpdatePolicy::LastWriteWins;
+    let fixture = setup(&store).await;
+
+    let mut first = Session::new(&store, &config, Some(incoming(&fixture)));
+    let mut second = Session::new(&store, &config, Some(incoming(&fixture)));
+    first.insert("a", "first").await.unwrap();
+    second.insert("c", "second").await.unwrap();
+
+    first.sync().await.unwrap();
+    second.sync().await.unwrap();
+
+    let state = stored_state(&store, &fixture).await;
+    assert_that!(state.get("a"), some(eq("initial")));
+    assert_that!(state.get("c"), some(eq("second")));
+}
+
+#[tokio::test]
+async fn a_session_can_be_synced_multiple_times_without_conflicting_with_itself() {
+    let (store, mut config) = (store(), SessionConfig::default());
+    config.state.concurrent_updates = ConcurrentUpdatePolicy::Reject;
+    let fixture = setup(&store).await;
+
+    let mut session = Session::new(&store, &config, Some(incoming(&fixture)));
+    session.insert("a", "first").await.unwrap();
+    session.sync().await.unwrap();
+    session.insert("a", "second").await.unwrap();
+    session.sync().await.unwrap();
+
+    let state = stored_state(&store, &fixture).await;
+    assert_that!(state.get("a"), some(eq("second")));
+}
+
+#[tokio::test]
+async fn removing_a_key_from_a_loaded_state_is_persisted() {
+    let (store, config) = (store(), SessionConfig::default());
+    let fixture = setup(&store).await;
+
+    let mut session = Session::new(&store, &config, Some(incoming(&fixture)));
+    session.remove_raw("a").await.unwrap();
+    session.sync().await.unwrap();
+
+    let state = stored_state(&store, &fixture).await;
+    assert_that!(state.get("a"), none());
+    assert_that!(state.get("b"), some(eq("initial")));
+}
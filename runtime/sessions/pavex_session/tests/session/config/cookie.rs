@@ -4,7 +4,7 @@ use googletest::{
     matchers::anything,
     prelude::{eq, none, some},
 };
-use pavex::cookie::{RequestCookie, RequestCookies, SameSite};
+use pavex::cookie::{ProcessorConfig, RequestCookie, RequestCookies, SameSite};
 use pavex_session::{
     IncomingSession, Session, SessionConfig,
     config::{SessionCookieConfig, SessionCookieKind},
@@ -116,21 +116,24 @@ async fn incoming_looks_for_the_right_cookie_name() {
         cookie.value().to_owned()
     };
 
+    // The cookie only carries the client-side state, no crypto rules are needed.
+    let processor = ProcessorConfig::default().into();
+
     // The cookie name matches, so it's `Some`
     let mut cookie_config = SessionCookieConfig::default();
     cookie_config.name = "my-custom-cookie-name".into();
     let mut request_cookies = RequestCookies::new();
     request_cookies.append(RequestCookie::new(&cookie_config.name, value));
-    assert!(IncomingSession::extract(&request_cookies, &cookie_config).is_some());
+    assert!(IncomingSession::extract(&request_cookies, &cookie_config, &processor).is_some());
 
     // The cookie name doesn't match, now it's `None`
     let mut cookie_config = SessionCookieConfig::default();
     cookie_config.name = "another-name".into();
-    assert!(IncomingSession::extract(&request_cookies, &cookie_config).is_none());
+    assert!(IncomingSession::extract(&request_cookies, &cookie_config, &processor).is_none());
 
     // The cookie name matches, but the value is not a valid state, so it's again `None`
     let cookie_config = SessionCookieConfig::default();
     let mut request_cookies = RequestCookies::new();
     request_cookies.append(RequestCookie::new(&cookie_config.name, "gibberish"));
-    assert!(IncomingSession::extract(&request_cookies, &cookie_config).is_none());
+    assert!(IncomingSession::extract(&request_cookies, &cookie_config, &processor).is_none());
 }
@@ -0,0 +1,263 @@
+//! Verify the behaviour of sessions backed by a cookie-only store.
+use std::num::NonZeroUsize;
+
+use googletest::{
+    assert_that,
+    prelude::{eq, len, none, not, some},
+};
+use insta::assert_snapshot;
+use pavex::{
+    Response,
+    cookie::{
+        Key, ProcessorConfig, RequestCookie, RequestCookies, ResponseCookie, ResponseCookies,
+        config::{CryptoAlgorithm, CryptoRule},
+    },
+};
+use pavex_session::{
+    IncomingSession, Session, SessionConfig, SessionStore, errors::FinalizeError, finalize_session,
+};
+
+use crate::{assertions::is_removal_cookie, fixtures::encrypting_processor};
+
+/// Build the session for the next request, using the cookies
+/// attached to the previous response.
+fn next_incoming(
+    cookies: &[ResponseCookie<'static>],
+    config: &SessionConfig,
+) -> Option<IncomingSession> {
+    let mut request_cookies = RequestCookies::new();
+    for cookie in cookies.iter().filter(|c| !c.value().is_empty()) {
+        request_cookies.append(RequestCookie::new(
+            cookie.name().to_owned(),
+            cookie.value().to_owned(),
+        ));
+    }
+    let processor = encrypting_processor(&config.cookie);
+    IncomingSession::extract(&request_cookies, &config.cookie, &processor)
+}
+
+fn large_value() -> String {
+    "a".repeat(8000)
+}
+
+#[tokio::test]
+async fn server_state_is_stored_in_the_session_cookie() {
+    let (store, config) = (SessionStore::cookie_only(), SessionConfig::default());
+
+    let mut session = Session::new(&store, &config, None);
+    session.insert("server", "yo").await.unwrap();
+    session.client_mut().insert("client", "hey").unwrap();
+
+    let cookies = session.finalize_cookies().await.unwrap();
+    assert_that!(cookies, len(eq(1)));
+    assert_eq!(cookies[0].name(), config.cookie.name);
+
+    let incoming = next_incoming(&cookies, &config).unwrap();
+    let session = Session::new(&store, &config, Some(incoming));
+    let server: Option<String> = session.get("server").await.unwrap();
+    assert_that!(server, some(eq("yo")));
+    let client: Option<String> = session.client().get("client").unwrap();
+    assert_that!(client, some(eq("hey")));
+    assert!(!session.is_invalidated());
+}
+
+#[tokio::test]
+async fn large_states_are_split_across_multiple_cookies() {
+    let (store, config) = (SessionStore::cookie_only(), SessionConfig::default());
+
+    let mut session = Session::new(&store, &config, None);
+    session.insert("server", large_value()).await.unwrap();
+
+    let cookies = session.finalize_cookies().await.unwrap();
+    let names: Vec<_> = cookies.iter().map(|c| c.name().to_owned()).collect();
+    assert_eq!(names, vec!["id", "id.1", "id.2"]);
+
+    let incoming = next_incoming(&cookies, &config).unwrap();
+    let session = Session::new(&store, &config, Some(incoming));
+    let server: Option<String> = session.get("server").await.unwrap();
+    assert_that!(server, some(eq(&large_value())));
+}
+
+#[tokio::test]
+async fn finalize_fails_if_the_state_requires_multiple_cookies() {
+    let (store, config) = (SessionStore::cookie_only(), SessionConfig::default());
+
+    let mut session = Session::new(&store, &config, None);
+    session.insert("server", large_value()).await.unwrap();
+
+    let err = session.finalize().await.unwrap_err();
+    assert!(matches!(
+        err,
+        FinalizeError::TooManyCookies { n_cookies: 3 }
+    ));
+}
+
+#[tokio::test]
+async fn states_larger_than_the_maximum_number_of_chunks_are_rejected() {
+    let (store, mut config) = (SessionStore::cookie_only(), SessionConfig::default());
+    config.cookie.max_chunks = NonZeroUsize::new(2).unwrap();
+
+    let mut session = Session::new(&store, &config, None);
+    session.insert("server", large_value()).await.unwrap();
+
+    let err = session.finalize_cookies().await.unwrap_err();
+    assert_snapshot!(err, @"The session state doesn't fit in the maximum number of session cookies: it requires 3 cookies, but at most 2 are allowed. Store less data in the session or raise `max_chunks` in the session cookie configuration.");
+}
+
+#[tokio::test]
+async fn stale_chunks_are_removed_when_the_state_shrinks() {
+    let (store, config) = (SessionStore::cookie_only(), SessionConfig::default());
+
+    let mut session = Session::new(&store, &config, None);
+    session.insert("server", large_value()).await.unwrap();
+    let cookies = session.finalize_cookies().await.unwrap();
+
+    let incoming = next_incoming(&cookies, &config).unwrap();
+    let mut session = Session::new(&store, &config, Some(incoming));
+    session.insert("server", "small").await.unwrap();
+    let cookies = session.finalize_cookies().await.unwrap();
+
+    assert_that!(cookies, len(eq(3)));
+    assert_that!(&cookies[0], not(is_removal_cookie()));
+    assert_eq!(cookies[1].name(), "id.1");
+    assert_that!(&cookies[1], is_removal_cookie());
+    assert_eq!(cookies[2].name(), "id.2");
+    assert_that!(&cookies[2], is_removal_cookie());
+}
+
+#[tokio::test]
+async fn all_chunks_are_removed_when_the_session_is_invalidated() {
+    let (store, config) = (SessionStore::cookie_only(), SessionConfig::default());
+
+    let mut session = Session::new(&store, &config, None);
+    session.insert("server", large_value()).await.unwrap();
+    let cookies = session.finalize_cookies().await.unwrap();
+
+    let incoming = next_incoming(&cookies, &config).unwrap();
+    let mut session = Session::new(&store, &config, Some(incoming));
+    session.invalidate();
+    let cookies = session.finalize_cookies().await.unwrap();
+
+    let names: Vec<_> = cookies.iter().map(|c| c.name().to_owned()).collect();
+    assert_eq!(names, vec!["id", "id.1", "id.2"]);
+    for cookie in &cookies {
+        assert_that!(cookie, is_removal_cookie());
+    }
+}
+
+#[tokio::test]
+async fn chunks_from_different_versions_of_the_state_are_rejected() {
+    let (store, config) = (SessionStore::cookie_only(), SessionConfig::default());
+
+    let mut first = Session::new(&store, &config, None);
+    first.insert("server", large_value()).await.unwrap();
+    let first = first.finalize_cookies().await.unwrap();
+
+    let mut second = Session::new(&store, &config, None);
+    second.insert("server", large_value()).await.unwrap();
+    let second = second.finalize_cookies().await.unwrap();
+
+    let mixed = vec![first[0].clone(), second[1].clone(), first[2].clone()];
+    assert!(next_incoming(&mixed, &config).is_none());
+
+    // A missing chunk is rejected as well.
+    let truncated = vec![first[0].clone(), first[2].clone()];
+    assert!(next_incoming(&truncated, &config).is_none());
+}
+
+#[tokio::test]
+async fn expired_server_state_is_treated_as_missing() {
+    let (store, mut config) = (SessionStore::cookie_only(), SessionConfig::default());
+    config.state.ttl = std::time::Duration::ZERO;
+
+    let mut session = Session::new(&store, &config, None);
+    session.insert("server", "yo").await.unwrap();
+    let cookies = session.finalize_cookies().await.unwrap();
+
+    // The state has already expired, and missing server-side states are
+    // rejected by default.
+    let incoming = next_incoming(&cookies, &config).unwrap();
+    let session = Session::new(&store, &config, Some(incoming));
+    let server: Option<String> = session.get("server").await.unwrap();
+    assert_that!(server, none());
+    assert!(session.is_invalidated());
+}
+
+#[tokio::test]
+async fn cookie_only_sessions_must_be_encrypted() {
+    let (store, config) = (SessionStore::cookie_only(), SessionConfig::default());
+    let processor = {
+        let mut cookie_config = ProcessorConfig::default();
+        cookie_config.crypto_rules.push(CryptoRule {
+            cookie_names: config.cookie.cookie_names(),
+            algorithm: CryptoAlgorithm::Signing,
+            key: Key::generate(),
+            fallbacks: vec![],
+        });
+        cookie_config.into()
+    };
+
+    let mut session = Session::new(&store, &config, None);
+    session.insert("key", "value").await.unwrap();
+
+    let err = finalize_session(
+        Response::ok(),
+        &mut ResponseCookies::new(),
+        &processor,
+        session,
+    )
+    .await
+    .unwrap_err();
+    assert!(matches!(err, FinalizeError::EncryptionRequired { .. }));
+}
+
+#[tokio::test]
+async fn cookie_only_sessions_work_if_all_chunks_are_encrypted() {
+    let (store, config) = (SessionStore::cookie_only(), SessionConfig::default());
+    let processor = encrypting_processor(&config.cookie);
+    let mut response_cookies = ResponseCookies::new();
+
+    let mut session = Session::new(&store, &config, None);
+    session.insert("key", large_value()).await.unwrap();
+
+    finalize_session(Response::ok(), &mut response_cookies, &processor, session)
+        .await
+        .unwrap();
+    assert_eq!(response_cookies.iter().count(), 3);
+}
+
+#[tokio::test]
+async fn server_state_is_ignored_unless_the_session_cookies_are_encrypted() {
+    let (store, config) = (SessionStore::cookie_only(), SessionConfig::default());
+
+    let mut session = Session::new(&store, &config, None);
+    session.insert("server", large_value()).await.unwrap();
+    let cookies = session.finalize_cookies().await.unwrap();
+    let mut request_cookies = RequestCookies::new();
+    for cookie in &cookies {
+        request_cookies.append(RequestCookie::new(
+            cookie.name().to_owned(),
+            cookie.value().to_owned(),
+        ));
+    }
+
+    // No crypto rules.
+    let processor = ProcessorConfig::default().into();
+    assert!(IncomingSession::extract(&request_cookies, &config.cookie, &processor).is_none());
+
+    // Only the first chunk is encrypted.
+    let processor = {
+        let mut cookie_config = ProcessorConfig::default();
+        cookie_config.crypto_rules.push(CryptoRule {
+            cookie_names: vec![config.cookie.name.clone()],
+            algorithm: CryptoAlgorithm::Encryption,
+            key: Key::generate(),
+            fallbacks: vec![],
+        });
+        cookie_config.into()
+    };
+    assert!(IncomingSession::extract(&request_cookies, &config.cookie, &processor).is_none());
+
+    let processor = encrypting_processor(&config.cookie);
+    assert!(IncomingSession::extract(&request_cookies, &config.cookie, &processor).is_some());
+}
@@ -1,7 +1,12 @@
 use std::{borrow::Cow, collections::HashMap, num::NonZeroUsize, sync::Arc};
 
+use pavex::cookie::{
+    Key, Processor, ProcessorConfig,
+    config::{CryptoAlgorithm, CryptoRule},
+};
 use pavex_session::{
-    IncomingSession, SessionId, SessionStore,
+    IncomingSession, SessionId, SessionStore, Value,
+    config::SessionCookieConfig,
     store::{
         SessionRecord, SessionRecordRef, SessionStorageBackend,
         errors::{
@@ -28,12 +33,24 @@ pub fn spy_store() -> (SessionStore, CallTracker) {
     (SessionStore::new(spy_backend), call_tracker)
 }
 
+/// A cookie processor that encrypts all the session cookies.
+pub fn encrypting_processor(config: &SessionCookieConfig) -> Processor {
+    let mut cookie_config = ProcessorConfig::default();
+    cookie_config.crypto_rules.push(CryptoRule {
+        cookie_names: config.cookie_names(),
+        algorithm: CryptoAlgorithm::Encryption,
+        key: Key::generate(),
+        fallbacks: vec![],
+    });
+    cookie_config.into()
+}
+
 /// A helper to set up a pre-existing session.
 pub struct SessionFixture {
     pub id: SessionId,
-    pub client_state: HashMap<Cow<'static, str>, serde_json::Value>,
+    pub client_state: HashMap<Cow<'static, str>, Value>,
     /// If `None`, no server-side state will be created.
-    pub server_state: Option<HashMap<String, serde_json::Value>>,
+    pub server_state: Option<HashMap<String, Value>>,
     /// If `None`, it'll be defaulted to a value that's high enough
     /// to avoid expiration while we run the test suite.
     pub server_ttl: Option<std::time::Duration>,
@@ -69,6 +86,7 @@ impl SessionFixture {
                     SessionRecordRef {
                         state: Cow::Owned(server_state),
                         ttl,
+                        codec: Default::default(),
                     },
                 )
                 .await
@@ -0,0 +1,415 @@
+//! Verify that flash messages are carried over to the next request, and then cleared.
+use googletest::{assert_that, prelude::not};
+use insta::assert_snapshot;
+use pavex::{
+    Response,
+    cookie::{
+        Key, Processor, ProcessorConfig, RequestCookie, RequestCookies, ResponseCookie,
+        ResponseCookies,
+        config::{CryptoAlgorithm, CryptoRule},
+    },
+};
+use pavex_session::{
+    IncomingSession, Session, SessionConfig, SessionStore,
+    config::FlashStorage,
+    flash::{
+        FlashMessage, IncomingFlashMessages, Level, OutgoingFlashMessages, finalize_flash_messages,
+    },
+};
+
+use crate::{
+    assertions::is_removal_cookie,
+    fixtures::{SessionFixture, store},
+    helpers::SetCookie,
+};
+
+fn signing_processor(config: &SessionConfig) -> Processor {
+    let mut cookie_config = ProcessorConfig::default();
+    cookie_config.crypto_rules.push(CryptoRule {
+        cookie_names: vec![config.flash.cookie_name.clone()],
+        algorithm: CryptoAlgorithm::Signing,
+        key: Key::generate(),
+        fallbacks: vec![],
+    });
+    cookie_config.into()
+}
+
+fn cookie_fallback_config() -> SessionConfig {
+    let mut config = SessionConfig::default();
+    config.flash.storage = FlashStorage::CookieFallback;
+    config
+}
+
+/// Run `finalize_flash_messages` against the given session, returning the
+/// cookies it attached to the response.
+fn finalize(
+    session: &mut Session<'_>,
+    incoming: &IncomingFlashMessages,
+    outgoing: &OutgoingFlashMessages,
+    config: &SessionConfig,
+) -> ResponseCookies {
+    let mut response_cookies = ResponseCookies::new();
+    let processor = signing_processor(config);
+    finalize_flash_messages(
+        Response::ok(),
+        session,
+        incoming,
+        outgoing,
+        &mut response_cookies,
+        &processor,
+        config,
+    )
+    .unwrap();
+    response_cookies
+}
+
+fn flash_cookie(
+    response_cookies: &ResponseCookies,
+    config: &SessionConfig,
+) -> Option<ResponseCookie<'static>> {
+    response_cookies
+        .iter()
+        .find(|c| c.name() == config.flash.cookie_name)
+        .cloned()
+}
+
+/// The messages a subsequent request would see, given the session cookie
+/// and the flash cookie set by the current one.
+async fn next_request_messages(
+    store: &SessionStore,
+    config: &SessionConfig,
+    session_cookie: Option<ResponseCookie<'static>>,
+    flash_cookie: Option<ResponseCookie<'static>>,
+) -> Vec<FlashMessage> {
+    let incoming_session = session_cookie.map(|c| {
+        let c = SetCookie::parse(c);
+        IncomingSession::from_parts(c.id, c.client_state)
+    });
+    let mut request_cookies = RequestCookies::new();
+    if let Some(cookie) = flash_cookie {
+        request_cookies.append(RequestCookie::new(
+            cookie.name().to_owned(),
+            cookie.value().to_owned(),
+        ));
+    }
+    let session = Session::new(store, config, incoming_session);
+    let incoming = IncomingFlashMessages::extract(
+        &session,
+        &request_cookies,
+        &config.flash,
+        &signing_processor(config),
+    );
+    incoming.iter().cloned().collect()
+}
+
+#[tokio::test]
+async fn outgoing_messages_are_stored_in_the_client_side_state() {
+    let (store, config) = (store(), SessionConfig::default());
+    let mut session = Session::new(&store, &config, None);
+    let incoming = IncomingFlashMessages::extract(
+        &session,
+        &RequestCookies::new(),
+        &config.flash,
+        &signing_processor(&config),
+    );
+    let mut outgoing = OutgoingFlashMessages::new();
+    outgoing.success("Profile updated");
+    outgoing.push(Level::Warning, "Your password expires soon");
+
+    let response_cookies = finalize(&mut session, &incoming, &outgoing, &config);
+    assert!(flash_cookie(&response_cookies, &config).is_none());
+
+    let session_cookie = session.finalize().await.unwrap();
+    let messages = next_request_messages(&store, &config, session_cookie, None).await;
+    assert_eq!(
+        messages,
+        vec![
+            FlashMessage::new(Level::Success, "Profile updated"),
+            FlashMessage::new(Level::Warning, "Your password expires soon"),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn read_messages_are_cleared() {
+    let (store, config) = (store(), SessionConfig::default());
+    let fixture = SessionFixture {
+        client_state: [(
+            "_flash".into(),
+            serde_json::json!([{"level": "info", "message": "Welcome back!"}]).into(),
+        )]
+        .into(),
+        ..Default::default()
+    };
+    let incoming_session = fixture.setup(&store).await;
+    let mut session = Session::new(&store, &config, Some(incoming_session));
+    let incoming = IncomingFlashMessages::extract(
+        &session,
+        &RequestCookies::new(),
+        &config.flash,
+        &signing_processor(&config),
+    );
+    assert_eq!(incoming.len(), 1);
+    assert!(!incoming.is_read());
+
+    let messages: Vec<_> = incoming.iter().collect();
+    assert_eq!(
+        messages,
+        vec![&FlashMessage::new(Level::Info, "Welcome back!")]
+    );
+    assert!(incoming.is_read());
+
+    finalize(
+        &mut session,
+        &incoming,
+        &OutgoingFlashMessages::new(),
+        &config,
+    );
+    assert!(session.client().is_empty());
+}
+
+#[tokio::test]
+async fn unread_messages_are_carried_over() {
+    let (store, config) = (store(), SessionConfig::default());
+    let fixture = SessionFixture {
+        client_state: [(
+            "_flash".into(),
+            serde_json::json!([{"level": "error", "message": "Payment failed"}]).into(),
+        )]
+        .into(),
+        ..Default::default()
+    };
+    let incoming_session = fixture.setup(&store).await;
+    let mut session = Session::new(&store, &config, Some(incoming_session));
+    let incoming = IncomingFlashMessages::extract(
+        &session,
+        &RequestCookies::new(),
+        &config.flash,
+        &signing_processor(&config),
+    );
+    let mut outgoing = OutgoingFlashMessages::new();
+    outgoing.info("Try another card");
+
+    finalize(&mut session, &incoming, &outgoing, &config);
+
+    let session_cookie = session.finalize().await.unwrap();
+    let messages = next_request_messages(&store, &config, session_cookie, None).await;
+    assert_eq!(
+        messages,
+        vec![
+            FlashMessage::new(Level::Error, "Payment failed"),
+            FlashMessage::new(Level::Info, "Try another card"),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn a_cookie_is_used_for_requests_without_a_session_if_configured() {
+    let (store, config) = (store(), cookie_fallback_config());
+    let mut session = Session::new(&store, &config, None);
+    let incoming = IncomingFlashMessages::extract(
+        &session,
+        &RequestCookies::new(),
+        &config.flash,
+        &signing_processor(&config),
+    );
+    let mut outgoing = OutgoingFlashMessages::new();
+    outgoing.info("Thanks for subscribing");
+
+    let response_cookies = finalize(&mut session, &incoming, &outgoing, &config);
+
+    // No session is created just to hold the flash messages.
+    assert!(session.finalize().await.unwrap().is_none());
+    let cookie = flash_cookie(&response_cookies, &config).unwrap();
+    assert_that!(&cookie, not(is_removal_cookie()));
+    assert_eq!(cookie.http_only(), Some(true));
+
+    let messages = next_request_messages(&store, &config, None, Some(cookie)).await;
+    assert_eq!(
+        messages,
+        vec![FlashMessage::new(Level::Info, "Thanks for subscribing")]
+    );
+}
+
+#[tokio::test]
+async fn the_session_is_preferred_to_the_cookie_if_the_request_is_part_of_one() {
+    let (store, config) = (store(), cookie_fallback_config());
+    let incoming_session = SessionFixture::default().setup(&store).await;
+    let mut session = Session::new(&store, &config, Some(incoming_session));
+    let incoming = IncomingFlashMessages::extract(
+        &session,
+        &RequestCookies::new(),
+        &config.flash,
+        &signing_processor(&config),
+    );
+    let mut outgoing = OutgoingFlashMessages::new();
+    outgoing.success("Saved");
+
+    let response_cookies = finalize(&mut session, &incoming, &outgoing, &config);
+    assert!(flash_cookie(&response_cookies, &config).is_none());
+
+    let session_cookie = session.finalize().await.unwrap();
+    let messages = next_request_messages(&store, &config, session_cookie, None).await;
+    assert_eq!(messages, vec![FlashMessage::new(Level::Success, "Saved")]);
+}
+
+#[tokio::test]
+async fn messages_survive_session_invalidation_with_the_cookie_fallback() {
+    let (store, config) = (store(), cookie_fallback_config());
+    let incoming_session = SessionFixture::default().setup(&store).await;
+    let mut session = Session::new(&store, &config, Some(incoming_session));
+    let incoming = IncomingFlashMessages::extract(
+        &session,
+        &RequestCookies::new(),
+        &config.flash,
+        &signing_processor(&config),
+    );
+    let mut outgoing = OutgoingFlashMessages::new();
+
+    session.invalidate();
+    outgoing.info("You have been logged out");
+    let response_cookies = finalize(&mut session, &incoming, &outgoing, &config);
+
+    let session_cookie = session.finalize().await.unwrap().unwrap();
+    assert_that!(session_cookie, is_removal_cookie());
+    let cookie = flash_cookie(&response_cookies, &config).unwrap();
+    let messages = next_request_messages(&store, &config, None, Some(cookie)).await;
+    assert_eq!(
+        messages,
+        vec![FlashMessage::new(Level::Info, "You have been logged out")]
+    );
+}
+
+#[tokio::test]
+async fn the_flash_cookie_is_removed_once_read() {
+    let (store, config) = (store(), cookie_fallback_config());
+    let mut request_cookies = RequestCookies::new();
+    request_cookies.append(RequestCookie::new(
+        config.flash.cookie_name.clone(),
+        r#"[{"level":"success","message":"Done"}]"#,
+    ));
+    let mut session = Session::new(&store, &config, None);
+    let incoming = IncomingFlashMessages::extract(
+        &session,
+        &request_cookies,
+        &config.flash,
+        &signing_processor(&config),
+    );
+    assert_eq!(incoming.iter().count(), 1);
+
+    let response_cookies = finalize(
+        &mut session,
+        &incoming,
+        &OutgoingFlashMessages::new(),
+        &config,
+    );
+
+    let cookie = flash_cookie(&response_cookies, &config).unwrap();
+    assert_that!(&cookie, is_removal_cookie());
+}
+
+#[tokio::test]
+async fn the_flash_cookie_is_ignored_unless_the_cookie_fallback_is_enabled() {
+    let (store, config) = (store(), SessionConfig::default());
+    let mut request_cookies = RequestCookies::new();
+    request_cookies.append(RequestCookie::new(
+        config.flash.cookie_name.clone(),
+        r#"[{"level":"success","message":"Done"}]"#,
+    ));
+    let session = Session::new(&store, &config, None);
+
+    let incoming = IncomingFlashMessages::extract(
+        &session,
+        &request_cookies,
+        &config.flash,
+        &signing_processor(&config),
+    );
+
+    assert!(incoming.is_empty());
+}
+
+#[tokio::test]
+async fn unprotected_flash_cookies_are_discarded_and_removed() {
+    let (store, config) = (store(), cookie_fallback_config());
+    let mut request_cookies = RequestCookies::new();
+    request_cookies.append(RequestCookie::new(
+        config.flash.cookie_name.clone(),
+        r#"[{"level":"success","message":"Forged"}]"#,
+    ));
+    let mut session = Session::new(&store, &config, None);
+    let processor = ProcessorConfig::default().into();
+
+    let incoming =
+        IncomingFlashMessages::extract(&session, &request_cookies, &config.flash, &processor);
+
+    assert!(incoming.is_empty());
+    let response_cookies = finalize(
+        &mut session,
+        &incoming,
+        &OutgoingFlashMessages::new(),
+        &config,
+    );
+    let cookie = flash_cookie(&response_cookies, &config).unwrap();
+    assert_that!(&cookie, is_removal_cookie());
+}
+
+#[tokio::test]
+async fn invalid_flash_cookies_are_discarded() {
+    let (store, config) = (store(), cookie_fallback_config());
+    let mut request_cookies = RequestCookies::new();
+    request_cookies.append(RequestCookie::new(
+        config.flash.cookie_name.clone(),
+        "not json",
+    ));
+    let session = Session::new(&store, &config, None);
+
+    let incoming = IncomingFlashMessages::extract(
+        &session,
+        &request_cookies,
+        &config.flash,
+        &signing_processor(&config),
+    );
+
+    assert!(incoming.is_empty());
+}
+
+#[tokio::test]
+async fn flash_cookie_must_be_either_signed_or_encrypted() {
+    let (store, config) = (store(), cookie_fallback_config());
+    let mut session = Session::new(&store, &config, None);
+    let incoming = IncomingFlashMessages::extract(
+        &session,
+        &RequestCookies::new(),
+        &config.flash,
+        &signing_processor(&config),
+    );
+    let mut outgoing = OutgoingFlashMessages::new();
+    outgoing.error("Oops");
+
+    let err = finalize_flash_messages(
+        Response::ok(),
+        &mut session,
+        &incoming,
+        &outgoing,
+        &mut ResponseCookies::new(),
+        &ProcessorConfig::default().into(),
+        &config,
+    )
+    .unwrap_err();
+    assert_snapshot!(err, @"The flash cookie (`flash`) is not configured to be signed nor encrypted. This is a security risk, as flash messages may be forged by an attacker. Configure the cookie processor to sign or encrypt the flash cookie; check out https://docs.rs/biscotti/latest/biscotti/struct.ProcessorConfig.html#structfield.crypto_rules for more information.");
+    assert!(err.into_response().status().is_server_error());
+}
+
+#[test]
+fn flash_storage_can_be_configured() {
+    let config: SessionConfig = serde_json::from_str(
+        r#"{"flash": {"storage": "cookie_fallback", "cookie_name": "notices"}}"#,
+    )
+    .unwrap();
+    assert_eq!(config.flash.storage, FlashStorage::CookieFallback);
+    assert_eq!(config.flash.cookie_name, "notices");
+
+    let default = SessionConfig::default();
+    assert_eq!(default.flash.storage, FlashStorage::Session);
+    assert_eq!(default.flash.cookie_name, "flash");
+}
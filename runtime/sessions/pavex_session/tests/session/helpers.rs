@@ -1,12 +1,12 @@
 use std::{borrow::Cow, collections::HashMap};
 
 use pavex::cookie::ResponseCookie;
-use pavex_session::SessionId;
+use pavex_session::{SessionId, Value};
 
 /// Parse the response cookie created by finalizing the session
 pub struct SetCookie {
     pub id: SessionId,
-    pub client_state: HashMap<Cow<'static, str>, serde_json::Value>,
+    pub client_state: HashMap<Cow<'static, str>, Value>,
 }
 
 impl SetCookie {
@@ -15,7 +15,11 @@ impl SetCookie {
             serde_json::from_str(cookie.value()).unwrap();
         let id: SessionId = serde_json::from_value(cookie_values[&0].clone()).unwrap();
         let client_state = if let Some(value) = cookie_values.remove(&1) {
-            serde_json::from_value(value).unwrap()
+            serde_json::from_value::<HashMap<Cow<'static, str>, serde_json::Value>>(value)
+                .unwrap()
+                .into_iter()
+                .map(|(k, v)| (k, v.into()))
+                .collect()
         } else {
             HashMap::new()
         };
@@ -9,16 +9,22 @@ use googletest::{
 use helpers::SetCookie;
 use itertools::Itertools;
 use pavex_session::{
-    IncomingSession, Session, SessionConfig, SessionId,
+    IncomingSession, Session, SessionConfig, SessionId, Value,
     config::{MissingServerState, ServerStateCreation, TtlExtensionTrigger},
 };
 
 mod assertions;
+mod codec;
+mod concurrency;
 mod config;
+mod cookie_only;
 mod fixtures;
+mod flash;
 mod helpers;
 mod middleware;
 mod operations;
+mod principal;
+mod timeout;
 
 #[tokio::test]
 async fn id_can_be_cycled_for_a_fresh_session() {
@@ -93,7 +99,7 @@ async fn pre_existing_client_state_can_be_accessed() {
 
     let mut fixture = SessionFixture::default();
     let key = "key";
-    let value = serde_json::Value::String("Value".to_owned());
+    let value = Value::String("Value".to_owned());
     fixture.client_state = {
         let mut c = HashMap::new();
         c.insert(key.into(), value.clone());
@@ -120,7 +126,7 @@ async fn pre_existing_client_state_can_be_modified() {
 
     let mut fixture = SessionFixture::default();
     let key = "key";
-    let value = serde_json::Value::String("Value".to_owned());
+    let value = Value::String("Value".to_owned());
     fixture.client_state = {
         let mut c = HashMap::new();
         c.insert(key.into(), value.clone());
@@ -147,10 +153,7 @@ async fn pre_existing_client_state_can_be_cleared_without_invalidating_the_sessi
         client_state: {
             // TODO: generate random client state.
             let mut c = HashMap::new();
-            c.insert(
-                "a key".into(),
-                serde_json::Value::String("Value".to_owned()),
-            );
+            c.insert("a key".into(), Value::String("Value".to_owned()));
             c
         },
         ..Default::default()
@@ -177,10 +180,7 @@ async fn server_state_can_be_cleared_without_invalidating_the_session() {
         server_state: Some({
             // TODO: generate random server state.
             let mut c = HashMap::new();
-            c.insert(
-                "a key".into(),
-                serde_json::Value::String("Value".to_owned()),
-            );
+            c.insert("a key".into(), Value::String("Value".to_owned()));
             c
         }),
         ..Default::default()
@@ -318,10 +318,7 @@ async fn server_state_can_be_deleted_without_invalidating_the_session() {
         server_state: Some({
             // TODO: generate random server state.
             let mut c = HashMap::new();
-            c.insert(
-                "a key".into(),
-                serde_json::Value::String("Value".to_owned()),
-            );
+            c.insert("a key".into(), Value::String("Value".to_owned()));
             c
         }),
         ..Default::default()
@@ -354,10 +351,7 @@ async fn server_state_is_deleted_if_the_session_is_invalidated() {
         server_state: Some({
             // TODO: generate random server state.
             let mut c = HashMap::new();
-            c.insert(
-                "a key".into(),
-                serde_json::Value::String("Value".to_owned()),
-            );
+            c.insert("a key".into(), Value::String("Value".to_owned()));
             c
         }),
         ..Default::default()
@@ -395,9 +389,9 @@ async fn server_state_is_persisted_for_a_fresh_session() {
         record
             .state
             .into_iter()
-            .map(|(k, v)| format!("{k}: {v}"))
+            .map(|(k, v)| format!("{k}: {v:?}"))
             .join("\n"),
-        r#"key: "value""#
+        r#"key: String("value")"#
     );
 }
 
@@ -4,7 +4,7 @@ use googletest::{
     assert_that,
     prelude::{eq, none},
 };
-use pavex_session::{Session, SessionConfig};
+use pavex_session::{Session, SessionConfig, Value};
 
 #[tokio::test]
 async fn fresh_session_is_empty() {
@@ -67,14 +67,8 @@ async fn operation_outcomes_are_immediately_visible() {
     let stored_client_value = session.client().get_raw(&key).unwrap().to_owned();
     let stored_server_value = session.get_raw(&key).await.unwrap().unwrap().to_owned();
 
-    assert_that!(
-        stored_client_value,
-        eq(&serde_json::Value::String(client_value.into()))
-    );
-    assert_that!(
-        stored_server_value,
-        eq(&serde_json::Value::String(server_value.into()))
-    );
+    assert_that!(stored_client_value, eq(&Value::String(client_value.into())));
+    assert_that!(stored_server_value, eq(&Value::String(server_value.into())));
     // Internal consistency
     assert_that!(
         stored_client_value,
@@ -0,0 +1,90 @@
+//! Verify that sessions can be associated with a principal.
+use pavex_session::{
+    Session, SessionConfig, SessionStore, errors::SyncError, store::errors::AssociatePrincipalError,
+};
+
+use crate::{
+    fixtures::{SessionFixture, store},
+    helpers::SetCookie,
+};
+
+#[tokio::test]
+async fn principal_is_associated_when_the_session_is_synced() {
+    let (store, config) = (store(), SessionConfig::default());
+
+    // A fresh session, without any server-side state.
+    let mut session = Session::new(&store, &config, None);
+    session
+        .set_principal("alice", Some("Firefox".into()))
+        .await
+        .unwrap();
+    // Nothing happens until the session is synced.
+    assert!(store.list_by_principal("alice").await.unwrap().is_empty());
+
+    let cookie = session.finalize().await.unwrap().unwrap();
+    let id = SetCookie::parse(cookie).id;
+
+    let sessions = store.list_by_principal("alice").await.unwrap();
+    assert_eq!(sessions.len(), 1);
+    assert!(sessions[0].id == id);
+    assert_eq!(sessions[0].user_agent.as_deref(), Some("Firefox"));
+}
+
+#[tokio::test]
+async fn principal_is_associated_with_the_new_id_if_it_was_cycled() {
+    let (store, config) = (store(), SessionConfig::default());
+    let fixture = SessionFixture::default();
+    let incoming = fixture.setup(&store).await;
+
+    let mut session = Session::new(&store, &config, Some(incoming));
+    session.cycle_id();
+    session.set_principal("alice", None).await.unwrap();
+    let cookie = session.finalize().await.unwrap().unwrap();
+    let new_id = SetCookie::parse(cookie).id;
+    assert!(new_id != fixture.id);
+
+    let sessions = store.list_by_principal("alice").await.unwrap();
+    assert_eq!(sessions.len(), 1);
+    assert!(sessions[0].id == new_id);
+}
+
+#[tokio::test]
+async fn all_sessions_of_a_principal_can_be_deleted() {
+    let (store, config) = (store(), SessionConfig::default());
+    for principal in ["alice", "alice", "bob"] {
+        let mut session = Session::new(&store, &config, None);
+        session.set_principal(principal, None).await.unwrap();
+        let _ = session.finalize().await.unwrap();
+    }
+
+    assert_eq!(store.delete_by_principal("alice").await.unwrap(), 2);
+    assert!(store.list_by_principal("alice").await.unwrap().is_empty());
+    assert_eq!(store.list_by_principal("bob").await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn setting_a_principal_is_a_no_op_on_invalidated_sessions() {
+    let (store, config) = (store(), SessionConfig::default());
+    let fixture = SessionFixture::default();
+    let incoming = fixture.setup(&store).await;
+
+    let mut session = Session::new(&store, &config, Some(incoming));
+    session.invalidate();
+    session.set_principal("alice", None).await.unwrap();
+    let _ = session.finalize().await.unwrap();
+
+    assert!(store.list_by_principal("alice").await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn cookie_only_stores_cannot_index_sessions() {
+    let (store, config) = (SessionStore::cookie_only(), SessionConfig::default());
+
+    let mut session = Session::new(&store, &config, None);
+    session.set_principal("alice", None).await.unwrap();
+    let err = session.sync().await.unwrap_err();
+    assert!(matches!(
+        err,
+        SyncError::AssociatePrincipalError(AssociatePrincipalError::Unsupported(_))
+    ));
+}
@@ -0,0 +1,385 @@
+//! Verify that absolute and idle timeouts are enforced.
+use std::{
+    num::NonZeroUsize,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use googletest::assert_that;
+use pavex::{
+    Response,
+    cookie::{
+        Key, ProcessorConfig, RequestCookie, RequestCookies, ResponseCookies,
+        config::{CryptoAlgorithm, CryptoRule},
+    },
+    time::{SignedDuration, Timestamp},
+};
+use pavex_session::{
+    IncomingSession, Session, SessionConfig, SessionId, SessionStore, SessionTimeout,
+    finalize_session,
+    store::{
+        SessionRecord, SessionRecordRef, SessionStorageBackend,
+        errors::{
+            ChangeIdError, CreateError, DeleteError, DeleteExpiredError, LoadError, UpdateError,
+            UpdateTtlError,
+        },
+    },
+};
+use pavex_session_memory_store::InMemorySessionStore;
+
+use crate::{
+    assertions::is_removal_cookie,
+    fixtures::{SessionFixture, encrypting_processor, store},
+    helpers::SetCookie,
+};
+
+/// A backend that pretends its records were created (and last written to)
+/// earlier than they actually were.
+///
+/// If the offsets are `None`, it pretends that it doesn't keep track of timestamps.
+#[derive(Debug)]
+struct AgedBackend {
+    backend: InMemorySessionStore,
+    created: Option<SignedDuration>,
+    last_seen: Option<SignedDuration>,
+}
+
+#[async_trait::async_trait]
+impl SessionStorageBackend for AgedBackend {
+    async fn create(
+        &self,
+        id: &SessionId,
+        record: SessionRecordRef<'_>,
+    ) -> Result<(), CreateError> {
+        self.backend.create(id, record).await
+    }
+
+    async fn update(
+        &self,
+        id: &SessionId,
+        record: SessionRecordRef<'_>,
+    ) -> Result<(), UpdateError> {
+        self.backend.update(id, record).await
+    }
+
+    async fn update_ttl(&self, id: &SessionId, ttl: Duration) -> Result<(), UpdateTtlError> {
+        self.backend.update_ttl(id, ttl).await
+    }
+
+    async fn load(&self, session_id: &SessionId) -> Result<Option<SessionRecord>, LoadError> {
+        let record = self.backend.load(session_id).await?;
+        Ok(record.map(|mut r| {
+            r.created_at = r.created_at.zip(self.created).map(|(t, d)| t - d);
+            r.last_seen_at = r.last_seen_at.zip(self.last_seen).map(|(t, d)| t - d);
+            r
+        }))
+    }
+
+    async fn delete(&self, session_id: &SessionId) -> Result<(), DeleteError> {
+        self.backend.delete(session_id).await
+    }
+
+    async fn change_id(&self, old_id: &SessionId, new_id: &SessionId) -> Result<(), ChangeIdError> {
+        self.backend.change_id(old_id, new_id).await
+    }
+
+    async fn delete_expired(
+        &self,
+        batch_size: Option<NonZeroUsize>,
+    ) -> Result<usize, DeleteExpiredError> {
+        self.backend.delete_expired(batch_size).await
+    }
+}
+
+/// A store whose records look `created` old, and were last written `last_seen` ago.
+fn aged_store(created: Duration, last_seen: Duration) -> SessionStore {
+    SessionStore::new(AgedBackend {
+        backend: InMemorySessionStore::default(),
+        created: Some(created.try_into().unwrap()),
+        last_seen: Some(last_seen.try_into().unwrap()),
+    })
+}
+
+/// A store whose records don't carry any timestamp.
+fn untracked_store() -> SessionStore {
+    SessionStore::new(AgedBackend {
+        backend: InMemorySessionStore::default(),
+        created: None,
+        last_seen: None,
+    })
+}
+
+fn hours(n: u64) -> Duration {
+    Duration::from_secs(n * 60 * 60)
+}
+
+fn config(absolute_timeout: Option<Duration>, idle_timeout: Option<Duration>) -> SessionConfig {
+    let mut config = SessionConfig::default();
+    config.state.absolute_timeout = absolute_timeout;
+    config.state.idle_timeout = idle_timeout;
+    config
+}
+
+#[tokio::test]
+async fn sessions_are_invalidated_once_they_exceed_the_absolute_timeout() {
+    let store = aged_store(hours(9), hours(0));
+    let config = config(Some(hours(8)), None);
+    let fixture = SessionFixture::default();
+    let incoming = fixture.setup(&store).await;
+
+    let session = Session::new(&store, &config, Some(incoming));
+    let timeout = session.timed_out().await.unwrap();
+
+    assert!(matches!(timeout, Some(SessionTimeout::Absolute { .. })));
+    assert!(session.is_invalidated());
+    let value: Option<String> = session.get("key").await.unwrap();
+    assert!(value.is_none());
+}
+
+#[tokio::test]
+async fn sessions_are_invalidated_once_they_exceed_the_idle_timeout() {
+    let store = aged_store(hours(2), hours(2));
+    let config = config(Some(hours(8)), Some(hours(1)));
+    let fixture = SessionFixture::default();
+    let incoming = fixture.setup(&store).await;
+
+    let mut session = Session::new(&store, &config, Some(incoming));
+    session.force_load().await.unwrap();
+
+    assert!(session.is_invalidated());
+    assert!(matches!(
+        session.timed_out().await.unwrap(),
+        Some(SessionTimeout::Idle { .. })
+    ));
+    // The stale record is removed from the store.
+    session.sync().await.unwrap();
+    assert!(store.load(&fixture.id).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn the_absolute_timeout_takes_precedence() {
+    let store = aged_store(hours(9), hours(2));
+    let config = config(Some(hours(8)), Some(hours(1)));
+    let fixture = SessionFixture::default();
+    let incoming = fixture.setup(&store).await;
+
+    let session = Session::new(&store, &config, Some(incoming));
+
+    assert!(matches!(
+        session.timed_out().await.unwrap(),
+        Some(SessionTimeout::Absolute { .. })
+    ));
+}
+
+#[tokio::test]
+async fn sessions_within_their_timeouts_are_left_untouched() {
+    let store = aged_store(hours(7), Duration::from_secs(60));
+    let config = config(Some(hours(8)), Some(hours(1)));
+    let fixture = SessionFixture::default();
+    let incoming = fixture.setup(&store).await;
+
+    let session = Session::new(&store, &config, Some(incoming));
+
+    assert!(session.timed_out().await.unwrap().is_none());
+    assert!(!session.is_invalidated());
+}
+
+#[tokio::test]
+async fn timeouts_are_not_enforced_unless_configured() {
+    let store = aged_store(hours(24 * 365), hours(24 * 365));
+    let config = SessionConfig::default();
+    let fixture = SessionFixture::default();
+    let incoming = fixture.setup(&store).await;
+
+    let session = Session::new(&store, &config, Some(incoming));
+
+    assert!(session.timed_out().await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn records_without_timestamps_are_treated_as_timed_out() {
+    let store = untracked_store();
+    for config in [config(Some(hours(8)), None), config(None, Some(hours(1)))] {
+        let fixture = SessionFixture::default();
+        let incoming = fixture.setup(&store).await;
+
+        let session = Session::new(&store, &config, Some(incoming));
+
+        assert!(matches!(
+            session.timed_out().await.unwrap(),
+            Some(SessionTimeout::Untracked)
+        ));
+        assert!(session.is_invalidated());
+    }
+}
+
+#[tokio::test]
+async fn records_without_timestamps_are_accepted_unless_a_timeout_is_configured() {
+    let store = untracked_store();
+    let config = SessionConfig::default();
+    let fixture = SessionFixture::default();
+    let incoming = fixture.setup(&store).await;
+
+    let session = Session::new(&store, &config, Some(incoming));
+
+    assert!(session.timed_out().await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn the_timeout_hook_is_invoked_with_the_stale_state() {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let store = aged_store(hours(0), hours(2)).on_timeout({
+        let seen = seen.clone();
+        move |timeout, state| {
+            let user: String = pavex_session::value::from_value(state["user"].clone()).unwrap();
+            seen.lock().unwrap().push((*timeout, user));
+        }
+    });
+    let config = config(None, Some(hours(1)));
+    let fixture = SessionFixture {
+        server_state: Some([("user".to_owned(), "alice".into())].into()),
+        ..Default::default()
+    };
+    let incoming = fixture.setup(&store).await;
+
+    let session = Session::new(&store, &config, Some(incoming));
+    session.timed_out().await.unwrap();
+
+    let seen = seen.lock().unwrap();
+    assert_eq!(seen.len(), 1);
+    assert!(matches!(seen[0].0, SessionTimeout::Idle { .. }));
+    assert_eq!(seen[0].1, "alice");
+}
+
+#[tokio::test]
+async fn finalize_session_enforces_timeouts_even_if_the_state_was_not_loaded() {
+    let store = aged_store(hours(9), hours(0));
+    let config = config(Some(hours(8)), None);
+    let fixture = SessionFixture::default();
+    let incoming = fixture.setup(&store).await;
+    let processor = {
+        let mut cookie_config = ProcessorConfig::default();
+        cookie_config.crypto_rules.push(CryptoRule {
+            cookie_names: vec![config.cookie.name.clone()],
+            algorithm: CryptoAlgorithm::Signing,
+            key: Key::generate(),
+            fallbacks: vec![],
+        });
+        cookie_config.into()
+    };
+    let mut response_cookies = ResponseCookies::new();
+
+    let session = Session::new(&store, &config, Some(incoming));
+    finalize_session(Response::ok(), &mut response_cookies, &processor, session)
+        .await
+        .unwrap();
+
+    assert!(store.load(&fixture.id).await.unwrap().is_none());
+    let cookie = response_cookies
+        .iter()
+        .find(|c| c.name() == config.cookie.name)
+        .unwrap();
+    assert_that!(cookie, is_removal_cookie());
+}
+
+#[tokio::test]
+async fn cycling_the_session_id_preserves_the_creation_timestamp() {
+    let store = store();
+    let config = config(Some(hours(8)), None);
+    let fixture = SessionFixture::default();
+    let incoming = fixture.setup(&store).await;
+    let created_at = store.load(&fixture.id).await.unwrap().unwrap().created_at;
+    assert!(created_at.is_some());
+
+    let mut session = Session::new(&store, &config, Some(incoming));
+    session.insert("key", "value").await.unwrap();
+    session.cycle_id();
+    let new_id = SetCookie::parse(session.finalize().await.unwrap().unwrap()).id;
+
+    let record = store.load(&new_id).await.unwrap().unwrap();
+    assert_eq!(record.created_at, created_at);
+    assert!(store.load(&fixture.id).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn cookie_only_sessions_enforce_timeouts() {
+    let (store, config) = (SessionStore::cookie_only(), config(None, Some(hours(1))));
+    let now = Timestamp::now().as_second();
+    let two_hours_ago = now - 2 * 60 * 60;
+    // The server-side state, as it would be stored in the session cookie.
+    let payload = serde_json::json!({
+        "0": SessionId::random().inner(),
+        "2": {
+            "0": { "key": "value" },
+            "1": now + 60 * 60,
+            "2": two_hours_ago,
+            "3": two_hours_ago,
+        },
+    });
+    let mut request_cookies = RequestCookies::new();
+    request_cookies.append(RequestCookie::new(
+        config.cookie.name.clone(),
+        payload.to_string(),
+    ));
+    let processor = encrypting_processor(&config.cookie);
+    let incoming = IncomingSession::extract(&request_cookies, &config.cookie, &processor).unwrap();
+
+    let session = Session::new(&store, &config, Some(incoming));
+
+    assert!(matches!(
+        session.timed_out().await.unwrap(),
+        Some(SessionTimeout::Idle { .. })
+    ));
+    let value: Option<String> = session.get("key").await.unwrap();
+    assert!(value.is_none());
+}
+
+#[tokio::test]
+async fn legacy_cookie_only_sessions_get_their_timestamps_backfilled() {
+    let (store, config) = (
+        SessionStore::cookie_only(),
+        config(Some(hours(8)), Some(hours(1))),
+    );
+    let now = Timestamp::now().as_second();
+    // A session cookie written before timestamps were tracked.
+    let payload = serde_json::json!({
+        "0": SessionId::random().inner(),
+        "2": {
+            "0": { "key": "value" },
+            "1": now + 60 * 60,
+        },
+    });
+    let mut request_cookies = RequestCookies::new();
+    request_cookies.append(RequestCookie::new(
+        config.cookie.name.clone(),
+        payload.to_string(),
+    ));
+    let processor = encrypting_processor(&config.cookie);
+    let incoming = IncomingSession::extract(&request_cookies, &config.cookie, &processor).unwrap();
+
+    let session = Session::new(&store, &config, Some(incoming));
+
+    assert!(session.timed_out().await.unwrap().is_none());
+    let value: Option<String> = session.get("key").await.unwrap();
+    assert_eq!(value.as_deref(), Some("value"));
+}
+
+#[test]
+fn timeouts_can_be_configured() {
+    let config: SessionConfig =
+        serde_json::from_str(r#"{"state": {"absolute_timeout": "8h", "idle_timeout": "30m"}}"#)
+            .unwrap();
+    assert_eq!(config.state.absolute_timeout, Some(hours(8)));
+    assert_eq!(
+        config.state.idle_timeout,
+        Some(Duration::from_secs(30 * 60))
+    );
+
+    let default = SessionConfig::default();
+    assert_eq!(default.state.absolute_timeout, None);
+    assert_eq!(default.state.idle_timeout, None);
+
+    let err =
+        serde_json::from_str::<SessionConfig>(r#"{"state": {"idle_timeout": "0s"}}"#).unwrap_err();
+    assert!(err.to_string().contains("must be positive"), "{err}");
+}
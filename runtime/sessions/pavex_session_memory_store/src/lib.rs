@@ -1,32 +1,60 @@
-//! An in-memory session store for `pavex_session`, geared towards testing and local development.
+//! An in-memory session store for `pavex_session`.
 use pavex::{methods, time::Timestamp};
-use std::{borrow::Cow, collections::HashMap, num::NonZeroUsize, sync::Arc, time::Duration};
-use tokio::sync::{Mutex, MutexGuard};
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashMap},
+    hash::{BuildHasher, RandomState},
+    num::NonZeroUsize,
+    sync::{
+        Arc, Mutex, MutexGuard, Weak,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Duration,
+};
 
 use pavex_session::{
-    SessionId, SessionStore,
+    SessionId, SessionStore, Value,
     store::{
-        SessionRecord, SessionRecordRef, SessionStorageBackend,
+        SessionMetadata, SessionRecord, SessionRecordRef, SessionStorageBackend,
         errors::{
-            ChangeIdError, CreateError, DeleteError, DeleteExpiredError, DuplicateIdError,
-            LoadError, UnknownIdError, UpdateError, UpdateTtlError,
+            AssociatePrincipalError, ChangeIdError, CreateError, DeleteByPrincipalError,
+            DeleteError, DeleteExpiredError, DuplicateIdError, ListByPrincipalError, LoadError,
+            UnknownIdError, UpdateError, UpdateTtlError, VersionConflictError,
         },
     },
 };
 
+mod tiered;
+
+pub use tiered::{TieredSessionStore, TieredSessionStoreConfig, TieredSessionStoreMetrics};
+
 #[derive(Clone)]
 /// An in-memory session store.
 ///
+/// # Implementation details
+///
+/// Session records are spread across multiple shards, each protected by its own lock,
+/// to reduce contention when serving concurrent requests.
+///
+/// If a [maximum capacity](InMemorySessionStoreConfig::max_capacity) is set, the least recently
+/// used records are evicted to make room for new ones.
+/// Expired records are removed periodically by a background task, spawned on the
+/// Tokio runtime that's active when the store is created—see
+/// [`sweep_interval`](InMemorySessionStoreConfig::sweep_interval).
+///
+/// Use [`metrics`](Self::metrics) to monitor the hit rate, the number of evictions and the size of the store.
+///
 /// # Limitations
 ///
 /// This store won't persist data between server restarts.
 /// It also won't synchronize data between multiple server instances.
-/// It is primarily intended for testing and local development.
-pub struct InMemorySessionStore(Arc<Mutex<HashMap<SessionId, StoreRecord>>>);
+/// It's a good fit for testing, local development and single-instance deployments.
+pub struct InMemorySessionStore(Arc<Inner>);
 
 impl std::fmt::Debug for InMemorySessionStore {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("InMemorySessionStore")
+            .field("config", &self.0.config)
             .finish_non_exhaustive()
     }
 }
@@ -43,52 +71,166 @@ impl From<InMemorySessionStore> for SessionStore {
 // Here for backwards compatibility.
 pub type SessionStoreMemory = InMemorySessionStore;
 
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+/// Configuration options for [`InMemorySessionStore`].
+pub struct InMemorySessionStoreConfig {
+    /// The maximum number of session records held by the store.
+    ///
+    /// When the store is full, the least recently used records are evicted to make
+    /// room for new ones.
+    /// Capacity is enforced shard by shard: each shard can hold up to
+    /// `max_capacity / n_shards` records (rounded up).
+    ///
+    /// # Default
+    ///
+    /// By default, there is no limit.
+    pub max_capacity: Option<NonZeroUsize>,
+    /// The number of shards the session records are spread across.
+    ///
+    /// # Default
+    ///
+    /// Four times the number of available CPU cores.
+    pub n_shards: NonZeroUsize,
+    /// How often expired records are removed from the store.
+    ///
+    /// If set to `None`, expired records are only removed when accessed,
+    /// evicted or when [`delete_expired`](SessionStorageBackend::delete_expired) is invoked.
+    ///
+    /// # Default
+    ///
+    /// Every minute.
+    pub sweep_interval: Option<Duration>,
+}
+
+impl Default for InMemorySessionStoreConfig {
+    fn default() -> Self {
+        let n_shards = std::thread::available_parallelism()
+            .ok()
+            .and_then(|n| n.checked_mul(NonZeroUsize::new(4).unwrap()))
+            .unwrap_or(NonZeroUsize::new(16).unwrap());
+        Self {
+            max_capacity: None,
+            n_shards,
+            sweep_interval: Some(Duration::from_secs(60)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+/// A snapshot of the metrics collected by an [`InMemorySessionStore`].
+pub struct InMemorySessionStoreMetrics {
+    /// The number of times a session record was found when loading it.
+    pub hits: u64,
+    /// The number of times a session record was missing (or expired) when loading it.
+    pub misses: u64,
+    /// The number of records that were evicted to make room for new ones.
+    pub evictions: u64,
+    /// The number of expired records that were removed from the store.
+    pub expirations: u64,
+    /// The number of records currently held by the store, expired ones included.
+    pub size: usize,
+}
+
+impl InMemorySessionStoreMetrics {
+    /// The ratio between hits and load attempts, if there were any.
+    pub fn hit_rate(&self) -> Option<f64> {
+        let total = self.hits + self.misses;
+        (total > 0).then(|| self.hits as f64 / total as f64)
+    }
+}
+
+struct Inner {
+    shards: Box<[Mutex<Shard>]>,
+    /// The maximum number of records in each shard, if bounded.
+    shard_capacity: Option<usize>,
+    hasher: RandomState,
+    counters: Counters,
+    config: InMemorySessionStoreConfig,
+}
+
+#[derive(Default)]
+struct Counters {
+    hits: AtomicU64,
+    misses: AtomicU64,
+    evictions: AtomicU64,
+    expirations: AtomicU64,
+}
+
+#[derive(Default)]
+struct Shard {
+    records: HashMap<SessionId, StoreRecord>,
+    /// The ids of the records in the shard, ordered from the least to the most recently used.
+    lru: BTreeMap<u64, SessionId>,
+    /// Incremented on every access, to keep track of the order of accesses.
+    tick: u64,
+}
+
 #[derive(Debug)]
 struct StoreRecord {
-    state: HashMap<Cow<'static, str>, serde_json::Value>,
+    state: HashMap<Cow<'static, str>, Value>,
     deadline: Timestamp,
+    created_at: Timestamp,
+    last_seen_at: Timestamp,
+    /// Incremented every time the state is modified.
+    version: u64,
+    /// The tick of the last access to this record, used as key in [`Shard::lru`].
+    last_access: u64,
+    principal: Option<PrincipalEntry>,
+}
+
+#[derive(Debug)]
+struct PrincipalEntry {
+    id: String,
+    associated_at: Timestamp,
+    user_agent: Option<String>,
 }
+
 impl StoreRecord {
     fn is_stale(&self) -> bool {
         self.deadline <= Timestamp::now()
     }
 }
 
-impl Default for InMemorySessionStore {
-    fn default() -> Self {
-        Self::new()
+impl Shard {
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
     }
-}
 
-#[methods]
-impl InMemorySessionStore {
-    /// Creates a new (empty) in-memory session store.
-    #[singleton]
-    pub fn new() -> Self {
-        Self(Arc::new(Mutex::new(HashMap::new())))
+    fn insert(&mut self, id: SessionId, mut record: StoreRecord) {
+        record.last_access = self.next_tick();
+        self.lru.insert(record.last_access, id);
+        if let Some(old) = self.records.insert(id, record) {
+            self.lru.remove(&old.last_access);
+        }
     }
 
-    fn get_mut_if_fresh<'a, 'b, 'c: 'a>(
-        guard: &'a mut MutexGuard<'c, HashMap<SessionId, StoreRecord>>,
-        id: &'b SessionId,
-    ) -> Result<&'a mut StoreRecord, UnknownIdError> {
-        let Some(old_record) = guard.get_mut(id) else {
+    fn remove(&mut self, id: &SessionId) -> Option<StoreRecord> {
+        let record = self.records.remove(id)?;
+        self.lru.remove(&record.last_access);
+        Some(record)
+    }
+
+    /// Retrieve a record that hasn't expired yet, marking it as recently used.
+    fn get_mut_if_fresh(&mut self, id: &SessionId) -> Result<&mut StoreRecord, UnknownIdError> {
+        let tick = self.next_tick();
+        let Some(record) = self.records.get_mut(id) else {
             return Err(UnknownIdError { id: id.to_owned() });
         };
-        if old_record.is_stale() {
+        if record.is_stale() {
             return Err(UnknownIdError { id: id.to_owned() });
         }
-        Ok(old_record)
+        self.lru.remove(&record.last_access);
+        self.lru.insert(tick, *id);
+        record.last_access = tick;
+        Ok(record)
     }
 
-    /// Deletes a session record from the store using the provided ID.
-    ///
-    /// If the session exists, it is removed from the store.
-    fn _delete(
-        guard: &mut MutexGuard<'_, HashMap<SessionId, StoreRecord>>,
-        id: &SessionId,
-    ) -> Result<StoreRecord, UnknownIdError> {
-        let Some(old_record) = guard.remove(id) else {
+    /// Remove a record, failing if it doesn't exist or if it has already expired.
+    fn remove_if_fresh(&mut self, id: &SessionId) -> Result<StoreRecord, UnknownIdError> {
+        let Some(old_record) = self.remove(id) else {
             return Err(UnknownIdError { id: id.to_owned() });
         };
         if old_record.is_stale() {
@@ -96,6 +238,166 @@ impl InMemorySessionStore {
         }
         Ok(old_record)
     }
+
+    /// Evict the least recently used records until the shard is within capacity.
+    ///
+    /// It returns the number of evicted records.
+    fn evict(&mut self, capacity: usize) -> u64 {
+        let mut n_evicted = 0;
+        while self.records.len() > capacity {
+            let Some((_, id)) = self.lru.pop_first() else {
+                break;
+            };
+            self.records.remove(&id);
+            n_evicted += 1;
+        }
+        n_evicted
+    }
+
+    /// Remove up to `limit` expired records, returning how many were removed.
+    fn remove_expired(&mut self, now: Timestamp, limit: usize) -> usize {
+        let stale_ids: Vec<_> = self
+            .records
+            .iter()
+            .filter(|(_, record)| record.deadline <= now)
+            .map(|(id, _)| *id)
+            .take(limit)
+            .collect();
+        for id in &stale_ids {
+            self.remove(id);
+        }
+        stale_ids.len()
+    }
+}
+
+impl Inner {
+    fn shard_index(&self, id: &SessionId) -> usize {
+        (self.hasher.hash_one(id) % self.shards.len() as u64) as usize
+    }
+
+    fn lock(&self, index: usize) -> MutexGuard<'_, Shard> {
+        // A panic while holding the lock can't leave a shard in an inconsistent state
+        // that would be worse than losing the store altogether.
+        self.shards[index].lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn shard(&self, id: &SessionId) -> MutexGuard<'_, Shard> {
+        self.lock(self.shard_index(id))
+    }
+
+    fn shards(&self) -> impl Iterator<Item = MutexGuard<'_, Shard>> {
+        (0..self.shards.len()).map(|i| self.lock(i))
+    }
+
+    /// Insert a record, evicting other records if the shard is over capacity.
+    fn insert(&self, shard: &mut Shard, id: SessionId, record: StoreRecord) {
+        shard.insert(id, record);
+        if let Some(capacity) = self.shard_capacity {
+            let n_evicted = shard.evict(capacity);
+            if n_evicted > 0 {
+                tracing::debug!(
+                    n_evicted,
+                    "The in-memory session store is full. Evicted the least recently used session records."
+                );
+                self.counters
+                    .evictions
+                    .fetch_add(n_evicted, Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// Remove up to `limit` expired records across all shards.
+    fn remove_expired(&self, limit: usize) -> usize {
+        let now = Timestamp::now();
+        let mut n_removed = 0;
+        for mut shard in self.shards() {
+            if n_removed >= limit {
+                break;
+            }
+            n_removed += shard.remove_expired(now, limit - n_removed);
+        }
+        self.counters
+            .expirations
+            .fetch_add(n_removed as u64, Ordering::Relaxed);
+        n_removed
+    }
+}
+
+/// Periodically remove expired records, for as long as the store is alive.
+///
+/// It's a no-op if there is no Tokio runtime available.
+fn spawn_sweeper(inner: Weak<Inner>, interval: Duration) {
+    let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+        tracing::debug!(
+            "There is no Tokio runtime available: expired session records won't be removed in the background."
+        );
+        return;
+    };
+    runtime.spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        // The first tick completes immediately.
+        ticker.tick().await;
+        loop {
+            ticker.tick().await;
+            let Some(inner) = inner.upgrade() else {
+                break;
+            };
+            let n_removed = inner.remove_expired(usize::MAX);
+            tracing::trace!(n_removed, "Removed expired session records");
+        }
+    });
+}
+
+impl Default for InMemorySessionStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[methods]
+impl InMemorySessionStore {
+    /// Creates a new (empty) in-memory session store, using the default configuration.
+    #[singleton]
+    pub fn new() -> Self {
+        Self::with_config(InMemorySessionStoreConfig::default())
+    }
+}
+
+impl InMemorySessionStore {
+    /// Creates a new (empty) in-memory session store, using the provided configuration.
+    ///
+    /// If a [sweep interval](InMemorySessionStoreConfig::sweep_interval) is set, the background
+    /// task that removes expired records is spawned on the current Tokio runtime.
+    pub fn with_config(config: InMemorySessionStoreConfig) -> Self {
+        let n_shards = config.n_shards.get();
+        let shard_capacity = config
+            .max_capacity
+            .map(|capacity| capacity.get().div_ceil(n_shards));
+        let inner = Arc::new(Inner {
+            shards: (0..n_shards).map(|_| Mutex::default()).collect(),
+            shard_capacity,
+            hasher: RandomState::new(),
+            counters: Counters::default(),
+            config,
+        });
+        if let Some(interval) = inner.config.sweep_interval {
+            spawn_sweeper(Arc::downgrade(&inner), interval);
+        }
+        Self(inner)
+    }
+
+    /// Returns a snapshot of the metrics collected by the store.
+    pub fn metrics(&self) -> InMemorySessionStoreMetrics {
+        let counters = &self.0.counters;
+        InMemorySessionStoreMetrics {
+            hits: counters.hits.load(Ordering::Relaxed),
+            misses: counters.misses.load(Ordering::Relaxed),
+            evictions: counters.evictions.load(Ordering::Relaxed),
+            expirations: counters.expirations.load(Ordering::Relaxed),
+            size: self.0.shards().map(|shard| shard.records.len()).sum(),
+        }
+    }
 }
 
 #[async_trait::async_trait]
@@ -107,18 +409,22 @@ impl SessionStorageBackend for InMemorySessionStore {
         id: &SessionId,
         record: SessionRecordRef<'_>,
     ) -> Result<(), CreateError> {
-        let mut guard = self.0.lock().await;
-        if Self::get_mut_if_fresh(&mut guard, id).is_ok() {
+        let mut shard = self.0.shard(id);
+        if shard.get_mut_if_fresh(id).is_ok() {
             return Err(CreateError::DuplicateId(DuplicateIdError { id: *id }));
         }
 
-        guard.insert(
-            *id,
-            StoreRecord {
-                state: record.state.into_owned(),
-                deadline: Timestamp::now() + record.ttl,
-            },
-        );
+        let now = Timestamp::now();
+        let record = StoreRecord {
+            state: record.state.into_owned(),
+            deadline: now + record.ttl,
+            created_at: now,
+            last_seen_at: now,
+            version: 0,
+            last_access: 0,
+            principal: None,
+        };
+        self.0.insert(&mut shard, *id, record);
         Ok(())
     }
 
@@ -131,15 +437,38 @@ impl SessionStorageBackend for InMemorySessionStore {
         id: &SessionId,
         record: SessionRecordRef<'_>,
     ) -> Result<(), UpdateError> {
-        let mut guard = self.0.lock().await;
-        let old_record = Self::get_mut_if_fresh(&mut guard, id)?;
-        *old_record = StoreRecord {
-            state: record.state.into_owned(),
-            deadline: Timestamp::now() + record.ttl,
-        };
+        let mut shard = self.0.shard(id);
+        let old_record = shard.get_mut_if_fresh(id)?;
+        let now = Timestamp::now();
+        old_record.state = record.state.into_owned();
+        old_record.deadline = now + record.ttl;
+        old_record.last_seen_at = now;
+        old_record.version += 1;
         Ok(())
     }
 
+    /// Update the state of an existing session in the store, if its
+    /// version matches the expected one.
+    #[tracing::instrument(name = "Conditionally update server-side session record", level = tracing::Level::TRACE, skip_all)]
+    async fn update_if_version(
+        &self,
+        id: &SessionId,
+        record: SessionRecordRef<'_>,
+        expected_version: u64,
+    ) -> Result<u64, UpdateError> {
+        let mut shard = self.0.shard(id);
+        let old_record = shard.get_mut_if_fresh(id)?;
+        if old_record.version != expected_version {
+            return Err(VersionConflictError { id: *id }.into());
+        }
+        let now = Timestamp::now();
+        old_record.state = record.state.into_owned();
+        old_record.deadline = now + record.ttl;
+        old_record.last_seen_at = now;
+        old_record.version += 1;
+        Ok(old_record.version)
+    }
+
     /// Update the TTL of an existing session record in the store.
     ///
     /// It leaves the session state unchanged.
@@ -149,9 +478,11 @@ impl SessionStorageBackend for InMemorySessionStore {
         id: &SessionId,
         ttl: std::time::Duration,
     ) -> Result<(), UpdateTtlError> {
-        let mut guard = self.0.lock().await;
-        let old_record = Self::get_mut_if_fresh(&mut guard, id)?;
-        old_record.deadline = Timestamp::now() + ttl;
+        let mut shard = self.0.shard(id);
+        let old_record = shard.get_mut_if_fresh(id)?;
+        let now = Timestamp::now();
+        old_record.deadline = now + ttl;
+        old_record.last_seen_at = now;
         Ok(())
     }
 
@@ -162,16 +493,24 @@ impl SessionStorageBackend for InMemorySessionStore {
     /// returned.
     #[tracing::instrument(name = "Load server-side session record", level = tracing::Level::TRACE, skip_all)]
     async fn load(&self, session_id: &SessionId) -> Result<Option<SessionRecord>, LoadError> {
-        let mut guard = self.0.lock().await;
-        let outcome = match Self::get_mut_if_fresh(&mut guard, session_id) {
+        let mut shard = self.0.shard(session_id);
+        let outcome = match shard.get_mut_if_fresh(session_id) {
             Ok(old_record) => Some(SessionRecord {
                 state: old_record.state.clone(),
                 ttl: (old_record.deadline - Timestamp::now())
                     .try_into()
                     .unwrap_or(Duration::from_millis(0)),
+                version: Some(old_record.version),
+                created_at: Some(old_record.created_at),
+                last_seen_at: Some(old_record.last_seen_at),
             }),
             Err(_) => None,
         };
+        let counter = match &outcome {
+            Some(_) => &self.0.counters.hits,
+            None => &self.0.counters.misses,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
         Ok(outcome)
     }
 
@@ -180,8 +519,7 @@ impl SessionStorageBackend for InMemorySessionStore {
     /// If the session exists, it is removed from the store.
     #[tracing::instrument(name = "Delete server-side session record", level = tracing::Level::TRACE, skip_all)]
     async fn delete(&self, id: &SessionId) -> Result<(), DeleteError> {
-        let mut guard = self.0.lock().await;
-        Self::_delete(&mut guard, id)?;
+        self.0.shard(id).remove_if_fresh(id)?;
         Ok(())
     }
 
@@ -190,15 +528,36 @@ impl SessionStorageBackend for InMemorySessionStore {
     /// The server-side state is left unchanged.
     #[tracing::instrument(name = "Change id for server-side session record", level = tracing::Level::TRACE, skip_all)]
     async fn change_id(&self, old_id: &SessionId, new_id: &SessionId) -> Result<(), ChangeIdError> {
-        let mut guard = self.0.lock().await;
-        if Self::get_mut_if_fresh(&mut guard, new_id).is_ok() {
+        let (old_index, new_index) = (self.0.shard_index(old_id), self.0.shard_index(new_id));
+        if old_index == new_index {
+            let mut shard = self.0.lock(old_index);
+            if shard.get_mut_if_fresh(new_id).is_ok() {
+                return Err(DuplicateIdError {
+                    id: new_id.to_owned(),
+                }
+                .into());
+            }
+            let record = shard.remove_if_fresh(old_id)?;
+            self.0.insert(&mut shard, *new_id, record);
+            return Ok(());
+        }
+
+        // Always acquire the locks in the same order, to avoid deadlocks.
+        let (mut old_shard, mut new_shard) = if old_index < new_index {
+            let old_shard = self.0.lock(old_index);
+            (old_shard, self.0.lock(new_index))
+        } else {
+            let new_shard = self.0.lock(new_index);
+            (self.0.lock(old_index), new_shard)
+        };
+        if new_shard.get_mut_if_fresh(new_id).is_ok() {
             return Err(DuplicateIdError {
                 id: new_id.to_owned(),
             }
             .into());
         }
-        let record = Self::_delete(&mut guard, old_id)?;
-        guard.insert(*new_id, record);
+        let record = old_shard.remove_if_fresh(old_id)?;
+        self.0.insert(&mut new_shard, *new_id, record);
         Ok(())
     }
 
@@ -208,23 +567,82 @@ impl SessionStorageBackend for InMemorySessionStore {
         &self,
         batch_size: Option<NonZeroUsize>,
     ) -> Result<usize, DeleteExpiredError> {
-        let mut guard = self.0.lock().await;
-        let now = Timestamp::now();
-        let mut stale_ids = Vec::new();
-        for (id, record) in guard.iter() {
-            if record.deadline <= now {
-                stale_ids.push(*id);
-                if let Some(batch_size) = batch_size
-                    && stale_ids.len() >= batch_size.get()
-                {
-                    break;
-                }
-            }
+        let limit = batch_size.map(NonZeroUsize::get).unwrap_or(usize::MAX);
+        Ok(self.0.remove_expired(limit))
+    }
+
+    /// Associate an existing session record with a principal.
+    #[tracing::instrument(name = "Associate server-side session record with a principal", level = tracing::Level::TRACE, skip_all)]
+    async fn associate_principal(
+        &self,
+        id: &SessionId,
+        principal_id: &str,
+        user_agent: Option<&str>,
+    ) -> Result<(), AssociatePrincipalError> {
+        let mut shard = self.0.shard(id);
+        let record = shard.get_mut_if_fresh(id)?;
+        record.principal = Some(PrincipalEntry {
+            id: principal_id.to_owned(),
+            associated_at: Timestamp::now(),
+            user_agent: user_agent.map(ToOwned::to_owned),
+        });
+        Ok(())
+    }
+
+    /// List all the active sessions associated with the given principal.
+    #[tracing::instrument(name = "List server-side session records for a principal", level = tracing::Level::TRACE, skip_all)]
+    async fn list_by_principal(
+        &self,
+        principal_id: &str,
+    ) -> Result<Vec<SessionMetadata>, ListByPrincipalError> {
+        let mut sessions = Vec::new();
+        for shard in self.0.shards() {
+            sessions.extend(
+                shard
+                    .records
+                    .iter()
+                    .filter(|(_, record)| !record.is_stale())
+                    .filter_map(|(id, record)| {
+                        let principal = record.principal.as_ref()?;
+                        (principal.id == principal_id).then(|| SessionMetadata {
+                            id: *id,
+                            principal_id: principal.id.clone(),
+                            created_at: Some(record.created_at),
+                            associated_at: principal.associated_at,
+                            last_seen_at: record.last_seen_at,
+                            expires_at: record.deadline,
+                            user_agent: principal.user_agent.clone(),
+                        })
+                    }),
+            );
         }
-        let num_deleted = stale_ids.len();
-        for id in stale_ids {
-            guard.remove(&id);
+        Ok(sessions)
+    }
+
+    /// Delete all the sessions associated with the given principal.
+    #[tracing::instrument(name = "Delete server-side session records for a principal", level = tracing::Level::TRACE, skip_all)]
+    async fn delete_by_principal(
+        &self,
+        principal_id: &str,
+    ) -> Result<usize, DeleteByPrincipalError> {
+        let mut n_deleted = 0;
+        for mut shard in self.0.shards() {
+            let ids: Vec<_> = shard
+                .records
+                .iter()
+                .filter(|(_, record)| {
+                    record
+                        .principal
+                        .as_ref()
+                        .is_some_and(|principal| principal.id == principal_id)
+                })
+                .map(|(id, _)| *id)
+                .collect();
+            for id in &ids {
+                shard.remove(id);
+            }
+            n_deleted += ids.len();
         }
-        Ok(num_deleted)
+        Ok(n_deleted)
     }
 }
@@ -0,0 +1,538 @@
+use pavex::{config, time::Timestamp};
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashMap},
+    hash::{BuildHasher, RandomState},
+    num::NonZeroUsize,
+    sync::{
+        Arc, Mutex, MutexGuard,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Duration,
+};
+
+use pavex_session::{
+    SessionId, SessionStore, Value,
+    store::{
+        SessionMetadata, SessionRecord, SessionRecordRef, SessionStorageBackend,
+        errors::{
+            AssociatePrincipalError, ChangeIdError, CreateError, DeleteByPrincipalError,
+            DeleteError, DeleteExpiredError, ListByPrincipalError, LoadError, UpdateError,
+            UpdateTtlError,
+        },
+    },
+};
+
+/// A session store that keeps recently used records in memory, in front
+/// of a durable storage backend.
+///
+/// # Implementation details
+///
+/// Loads are served from an in-memory cache, local to the current server instance,
+/// when possible. On a miss, the record is loaded from the durable backend and cached
+/// for a short amount of time—see [`cache_ttl`](TieredSessionStoreConfig::cache_ttl).
+/// The cache is split into shards, keyed by session id, to avoid contention between
+/// concurrent requests.
+///
+/// All writes go straight to the durable backend.
+/// Updates refresh the cached record with the state that was just written.
+/// Every other operation that modifies a record (deletions, id changes) invalidates
+/// the corresponding cache entry. Loads that raced with an invalidation don't populate the cache,
+/// since the record they retrieved may already be outdated.
+///
+/// # Consistency
+///
+/// If you run multiple server instances, a record may be modified by another instance
+/// while it sits in the cache of the current one: it'll look stale for up to
+/// [`cache_ttl`](TieredSessionStoreConfig::cache_ttl).
+/// Stale states are never written back silently, as long as your backend supports
+/// [versioning](SessionRecord::version): conditional updates fail if the record was modified
+/// in the meantime, triggering the [concurrent update policy](pavex_session::config::ConcurrentUpdatePolicy)
+/// you configured.
+/// Keep the cache TTL short if that's not the case.
+///
+/// The same goes for [`delete_by_principal`](SessionStorageBackend::delete_by_principal):
+/// other instances may keep serving the deleted sessions from their cache until their
+/// entries go stale.
+///
+/// # Usage
+///
+/// Wrap your backend of choice and convert the result into a [`SessionStore`]:
+///
+/// ```rust
+/// use pavex_session::SessionStore;
+/// use pavex_session_memory_store::{InMemorySessionStore, TieredSessionStore, TieredSessionStoreConfig};
+///
+/// // Register it as a singleton constructor.
+/// fn session_store(config: TieredSessionStoreConfig) -> SessionStore {
+///     // Swap the in-memory store for your durable backend (e.g. Postgres or Redis).
+///     let backend = InMemorySessionStore::new();
+///     TieredSessionStore::new(backend, config).into()
+/// }
+/// ```
+pub struct TieredSessionStore<Backend> {
+    backend: Backend,
+    cache: Arc<Cache>,
+}
+
+impl<Backend: std::fmt::Debug> std::fmt::Debug for TieredSessionStore<Backend> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TieredSessionStore")
+            .field("backend", &self.backend)
+            .field("config", &self.cache.config)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<Backend> From<TieredSessionStore<Backend>> for SessionStore
+where
+    Backend: SessionStorageBackend + 'static,
+{
+    fn from(value: TieredSessionStore<Backend>) -> Self {
+        SessionStore::new(value)
+    }
+}
+
+#[config(key = "tiered_session_store", default_if_missing)]
+#[derive(Debug, Clone, serde::Deserialize)]
+#[non_exhaustive]
+/// Configuration options for [`TieredSessionStore`].
+pub struct TieredSessionStoreConfig {
+    /// How long a record can be served from the cache before it must be
+    /// loaded again from the durable backend.
+    ///
+    /// # Default
+    ///
+    /// 5 seconds.
+    #[serde(
+        deserialize_with = "deserialize_cache_ttl",
+        default = "default_cache_ttl"
+    )]
+    pub cache_ttl: Duration,
+    /// The maximum number of records held by the cache.
+    ///
+    /// The capacity is split evenly across the cache shards. When a shard is full,
+    /// the records that were cached first are evicted to make room for new ones.
+    ///
+    /// # Default
+    ///
+    /// 10,000 records.
+    #[serde(default = "default_max_capacity")]
+    pub max_capacity: NonZeroUsize,
+}
+
+impl Default for TieredSessionStoreConfig {
+    fn default() -> Self {
+        Self {
+            cache_ttl: default_cache_ttl(),
+            max_capacity: default_max_capacity(),
+        }
+    }
+}
+
+fn default_cache_ttl() -> Duration {
+    Duration::from_secs(5)
+}
+
+fn default_max_capacity() -> NonZeroUsize {
+    NonZeroUsize::new(10_000).unwrap()
+}
+
+fn deserialize_cache_ttl<'de, D>(deserializer: D) -> Result<Duration, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    use serde::Deserialize as _;
+
+    let span = pavex::time::Span::deserialize(deserializer)?;
+    if span.is_negative() {
+        return Err(serde::de::Error::custom("The cache TTL cannot be negative"));
+    }
+    span.to_duration(pavex::time::SpanRelativeTo::days_are_24_hours())
+        .and_then(|d| d.try_into())
+        .map_err(serde::de::Error::custom)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+/// A snapshot of the metrics collected by a [`TieredSessionStore`].
+pub struct TieredSessionStoreMetrics {
+    /// The number of loads served by the cache.
+    pub hits: u64,
+    /// The number of loads that had to go to the durable backend.
+    pub misses: u64,
+    /// The number of records currently held by the cache, stale ones included.
+    pub size: usize,
+}
+
+impl TieredSessionStoreMetrics {
+    /// The ratio between hits and load attempts, if there were any.
+    pub fn hit_rate(&self) -> Option<f64> {
+        let total = self.hits + self.misses;
+        (total > 0).then(|| self.hits as f64 / total as f64)
+    }
+}
+
+struct Cache {
+    shards: Box<[Mutex<Entries>]>,
+    /// Used to pick the shard for a given session id.
+    hasher: RandomState,
+    /// The maximum number of records held by each shard.
+    shard_capacity: usize,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    config: TieredSessionStoreConfig,
+}
+
+#[derive(Default)]
+struct Entries {
+    records: HashMap<SessionId, CachedRecord>,
+    /// The ids of the cached records, ordered from the oldest to the most recent entry.
+    insertion_order: BTreeMap<u64, SessionId>,
+    /// Incremented on every insertion, to keep track of the order of insertions.
+    tick: u64,
+    /// Incremented on every invalidation, to detect loads that raced with one.
+    generation: u64,
+}
+
+struct CachedRecord {
+    state: HashMap<Cow<'static, str>, Value>,
+    version: Option<u64>,
+    created_at: Option<Timestamp>,
+    last_seen_at: Option<Timestamp>,
+    /// When the record expires in the durable backend.
+    deadline: Timestamp,
+    /// When the record must be loaded again from the durable backend.
+    fresh_until: Timestamp,
+    /// The tick of the insertion, used as key in [`Entries::insertion_order`].
+    tick: u64,
+}
+
+impl Entries {
+    fn insert(&mut self, id: SessionId, mut record: CachedRecord, capacity: usize) {
+        self.tick += 1;
+        record.tick = self.tick;
+        self.insertion_order.insert(record.tick, id);
+        if let Some(old) = self.records.insert(id, record) {
+            self.insertion_order.remove(&old.tick);
+        }
+        while self.records.len() > capacity {
+            let Some((_, id)) = self.insertion_order.pop_first() else {
+                break;
+            };
+            self.records.remove(&id);
+        }
+    }
+
+    fn remove(&mut self, id: &SessionId) -> Option<CachedRecord> {
+        let record = self.records.remove(id)?;
+        self.insertion_order.remove(&record.tick);
+        Some(record)
+    }
+
+    /// Remove the entry, if it exists, and bump the generation of the shard.
+    fn invalidate(&mut self, id: &SessionId) -> Option<CachedRecord> {
+        self.generation += 1;
+        self.remove(id)
+    }
+}
+
+impl Cache {
+    fn new(config: TieredSessionStoreConfig) -> Self {
+        let max_capacity = config.max_capacity.get();
+        let n_shards = std::thread::available_parallelism()
+            .map_or(1, NonZeroUsize::get)
+            .saturating_mul(4)
+            .next_power_of_two()
+            .min(max_capacity);
+        Self {
+            shards: (0..n_shards).map(|_| Mutex::default()).collect(),
+            hasher: RandomState::new(),
+            shard_capacity: max_capacity.div_ceil(n_shards),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            config,
+        }
+    }
+
+    /// Lock the shard that holds the entry for the given session id.
+    fn entries(&self, id: &SessionId) -> MutexGuard<'_, Entries> {
+        let index = self.hasher.hash_one(id) as usize % self.shards.len();
+        // The cache can always be rebuilt from the durable backend.
+        self.shards[index].lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// The current generation of the shard that holds the entry for the given session id.
+    ///
+    /// Capture it before loading a record from the durable backend, then pass it to
+    /// [`Cache::insert`].
+    fn generation(&self, id: &SessionId) -> u64 {
+        self.entries(id).generation
+    }
+
+    fn size(&self) -> usize {
+        self.shards
+            .iter()
+            .map(|s| s.lock().unwrap_or_else(|e| e.into_inner()).records.len())
+            .sum()
+    }
+
+    fn get(&self, id: &SessionId) -> Option<SessionRecord> {
+        let now = Timestamp::now();
+        let mut entries = self.entries(id);
+        let record = entries.records.get(id)?;
+        if record.fresh_until <= now || record.deadline <= now {
+            entries.remove(id);
+            return None;
+        }
+        Some(SessionRecord {
+            state: record.state.clone(),
+            ttl: (record.deadline - now).try_into().unwrap_or(Duration::ZERO),
+            version: record.version,
+            created_at: record.created_at,
+            last_seen_at: record.last_seen_at,
+        })
+    }
+
+    /// Cache the record, unless the shard was invalidated after `generation` was captured:
+    /// the record may be outdated in that case.
+    fn insert(&self, id: SessionId, record: &SessionRecord, generation: u64) {
+        let now = Timestamp::now();
+        let cached = CachedRecord {
+            state: record.state.clone(),
+            version: record.version,
+            created_at: record.created_at,
+            last_seen_at: record.last_seen_at,
+            deadline: now + record.ttl,
+            fresh_until: now + self.config.cache_ttl,
+            tick: 0,
+        };
+        let mut entries = self.entries(&id);
+        if entries.generation != generation {
+            return;
+        }
+        entries.insert(id, cached, self.shard_capacity);
+    }
+
+    fn invalidate(&self, id: &SessionId) {
+        self.entries(id).invalidate(id);
+    }
+
+    /// Invalidate the entry, returning it alongside the new generation of its shard.
+    fn take(&self, id: &SessionId) -> (Option<CachedRecord>, u64) {
+        let mut entries = self.entries(id);
+        let record = entries.invalidate(id);
+        (record, entries.generation)
+    }
+}
+
+impl<Backend> TieredSessionStore<Backend> {
+    /// Wrap a durable storage backend with an in-memory cache.
+    pub fn new(backend: Backend, config: TieredSessionStoreConfig) -> Self {
+        Self {
+            backend,
+            cache: Arc::new(Cache::new(config)),
+        }
+    }
+
+    /// Returns a snapshot of the metrics collected by the cache.
+    pub fn metrics(&self) -> TieredSessionStoreMetrics {
+        TieredSessionStoreMetrics {
+            hits: self.cache.hits.load(Ordering::Relaxed),
+            misses: self.cache.misses.load(Ordering::Relaxed),
+            size: self.cache.size(),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl<Backend> SessionStorageBackend for TieredSessionStore<Backend>
+where
+    Backend: SessionStorageBackend,
+{
+    /// Creates a new session record in the durable backend.
+    ///
+    /// The record is cached the first time it's loaded.
+    async fn create(
+        &self,
+        id: &SessionId,
+        record: SessionRecordRef<'_>,
+    ) -> Result<(), CreateError> {
+        self.backend.create(id, record).await?;
+        self.cache.invalidate(id);
+        Ok(())
+    }
+
+    /// Update the state of an existing session in the durable backend.
+    ///
+    /// If successful, the cache entry is refreshed with the new state.
+    /// The new version of the record is unknown, so the cached record has none:
+    /// that's consistent with the way sessions use unconditional updates—i.e. when
+    /// versions aren't tracked or concurrent updates are resolved via last-write-wins.
+    async fn update(
+        &self,
+        id: &SessionId,
+        record: SessionRecordRef<'_>,
+    ) -> Result<(), UpdateError> {
+        let (previous, generation) = self.cache.take(id);
+        let ttl = record.ttl;
+        let state = record.state.clone().into_owned();
+        self.backend.update(id, record).await?;
+        // We can only cache the new state if we know when the record was created.
+        if let Some(previous) = previous {
+            self.cache.insert(
+                *id,
+                &SessionRecord {
+                    state,
+                    ttl,
+                    version: None,
+                    created_at: previous.created_at,
+                    last_seen_at: previous.last_seen_at.map(|_| Timestamp::now()),
+                },
+                generation,
+            );
+        }
+        Ok(())
+    }
+
+    /// Update the state of an existing session in the durable backend, if its
+    /// version matches the expected one.
+    ///
+    /// If successful, the cache entry is refreshed with the new state.
+    async fn update_if_version(
+        &self,
+        id: &SessionId,
+        record: SessionRecordRef<'_>,
+        expected_version: u64,
+    ) -> Result<u64, UpdateError> {
+        let (previous, generation) = self.cache.take(id);
+        let ttl = record.ttl;
+        let state = record.state.clone().into_owned();
+        let version = self
+            .backend
+            .update_if_version(id, record, expected_version)
+            .await?;
+        // We can only cache the new state if we know when the record was created.
+        if let Some(previous) = previous {
+            self.cache.insert(
+                *id,
+                &SessionRecord {
+                    state,
+                    ttl,
+                    version: Some(version),
+                    created_at: previous.created_at,
+                    last_seen_at: previous.last_seen_at.map(|_| Timestamp::now()),
+                },
+                generation,
+            );
+        }
+        Ok(version)
+    }
+
+    /// Update the TTL of an existing session record in the durable backend.
+    ///
+    /// If successful, the cache entry is refreshed with the new TTL.
+    async fn update_ttl(&self, id: &SessionId, ttl: Duration) -> Result<(), UpdateTtlError> {
+        let outcome = self.backend.update_ttl(id, ttl).await;
+        let mut entries = self.cache.entries(id);
+        match (&outcome, entries.records.get_mut(id)) {
+            (Ok(()), Some(cached)) => {
+                let now = Timestamp::now();
+                cached.deadline = now + ttl;
+                cached.last_seen_at = cached.last_seen_at.map(|_| now);
+            }
+            (Err(_), Some(_)) => {
+                entries.invalidate(id);
+            }
+            (_, None) => {}
+        }
+        outcome
+    }
+
+    /// Loads a session record from the cache or, if it's missing or stale,
+    /// from the durable backend.
+    #[tracing::instrument(name = "Load server-side session record via cache", level = tracing::Level::TRACE, skip_all)]
+    async fn load(&self, session_id: &SessionId) -> Result<Option<SessionRecord>, LoadError> {
+        if let Some(record) = self.cache.get(session_id) {
+            self.cache.hits.fetch_add(1, Ordering::Relaxed);
+            return Ok(Some(record));
+        }
+        self.cache.misses.fetch_add(1, Ordering::Relaxed);
+        let generation = self.cache.generation(session_id);
+        let record = self.backend.load(session_id).await?;
+        if let Some(record) = &record {
+            self.cache.insert(*session_id, record, generation);
+        }
+        Ok(record)
+    }
+
+    /// Deletes a session record from both the cache and the durable backend.
+    async fn delete(&self, id: &SessionId) -> Result<(), DeleteError> {
+        let outcome = self.backend.delete(id).await;
+        self.cache.invalidate(id);
+        outcome
+    }
+
+    /// Change the session id associated with an existing session record.
+    ///
+    /// Cache entries for both ids are invalidated.
+    async fn change_id(&self, old_id: &SessionId, new_id: &SessionId) -> Result<(), ChangeIdError> {
+        let outcome = self.backend.change_id(old_id, new_id).await;
+        self.cache.invalidate(old_id);
+        self.cache.invalidate(new_id);
+        outcome
+    }
+
+    /// Delete expired records from the durable backend.
+    ///
+    /// Stale cache entries are dropped when they're accessed or evicted.
+    async fn delete_expired(
+        &self,
+        batch_size: Option<NonZeroUsize>,
+    ) -> Result<usize, DeleteExpiredError> {
+        self.backend.delete_expired(batch_size).await
+    }
+
+    /// Associate an existing session record with a principal.
+    async fn associate_principal(
+        &self,
+        id: &SessionId,
+        principal_id: &str,
+        user_agent: Option<&str>,
+    ) -> Result<(), AssociatePrincipalError> {
+        self.backend
+            .associate_principal(id, principal_id, user_agent)
+            .await
+    }
+
+    /// List all the active sessions associated with the given principal.
+    ///
+    /// It always queries the durable backend.
+    async fn list_by_principal(
+        &self,
+        principal_id: &str,
+    ) -> Result<Vec<SessionMetadata>, ListByPrincipalError> {
+        self.backend.list_by_principal(principal_id).await
+    }
+
+    /// Delete all the sessions associated with the given principal.
+    ///
+    /// The cache entries of the deleted sessions are invalidated, but only in the cache
+    /// of the current process: other server instances may keep serving the deleted
+    /// sessions from their own cache for up to [`cache_ttl`](TieredSessionStoreConfig::cache_ttl).
+    /// Use a short `cache_ttl` if that window is too wide—e.g. when logging a user out
+    /// of all their devices after a password change.
+    async fn delete_by_principal(
+        &self,
+        principal_id: &str,
+    ) -> Result<usize, DeleteByPrincipalError> {
+        // We don't know which records are going to be deleted: we must
+        // ask the durable backend upfront.
+        let sessions = self.backend.list_by_principal(principal_id).await?;
+        let n_deleted = self.backend.delete_by_principal(principal_id).await?;
+        for session in &sessions {
+            self.cache.invalidate(&session.id);
+        }
+        Ok(n_deleted)
+    }
+}
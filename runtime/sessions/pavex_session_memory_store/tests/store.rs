@@ -0,0 +1,129 @@
+use pavex_session::codec::SessionCodec;
+use pavex_session::store::{SessionRecordRef, SessionStorageBackend};
+use pavex_session::{SessionId, Value};
+use pavex_session_memory_store::{InMemorySessionStore, InMemorySessionStoreConfig};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::num::NonZeroUsize;
+use std::time::Duration;
+
+fn record(ttl: Duration) -> SessionRecordRef<'static> {
+    SessionRecordRef {
+        state: Cow::Owned(HashMap::from([(
+            Cow::Borrowed("user_id"),
+            Value::from("test-user-123"),
+        )])),
+        ttl,
+        codec: SessionCodec::Json,
+    }
+}
+
+fn bounded_store(max_capacity: usize) -> InMemorySessionStore {
+    let mut config = InMemorySessionStoreConfig::default();
+    config.max_capacity = NonZeroUsize::new(max_capacity);
+    config.n_shards = NonZeroUsize::new(1).unwrap();
+    config.sweep_interval = None;
+    InMemorySessionStore::with_config(config)
+}
+
+#[tokio::test]
+async fn least_recently_used_records_are_evicted_when_the_store_is_full() {
+    let store = bounded_store(2);
+    let ttl = Duration::from_secs(3600);
+    let (a, b, c) = (
+        SessionId::random(),
+        SessionId::random(),
+        SessionId::random(),
+    );
+
+    store.create(&a, record(ttl)).await.unwrap();
+    store.create(&b, record(ttl)).await.unwrap();
+    // `a` is now the most recently used record.
+    store.load(&a).await.unwrap().unwrap();
+    store.create(&c, record(ttl)).await.unwrap();
+
+    assert!(store.load(&a).await.unwrap().is_some());
+    assert!(store.load(&b).await.unwrap().is_none());
+    assert!(store.load(&c).await.unwrap().is_some());
+
+    let metrics = store.metrics();
+    assert_eq!(metrics.evictions, 1);
+    assert_eq!(metrics.size, 2);
+}
+
+#[tokio::test]
+async fn hits_and_misses_are_tracked() {
+    let store = bounded_store(10);
+    let id = SessionId::random();
+    store
+        .create(&id, record(Duration::from_secs(3600)))
+        .await
+        .unwrap();
+
+    store.load(&id).await.unwrap();
+    store.load(&id).await.unwrap();
+    store.load(&id).await.unwrap();
+    store.load(&SessionId::random()).await.unwrap();
+
+    let metrics = store.metrics();
+    assert_eq!(metrics.hits, 3);
+    assert_eq!(metrics.misses, 1);
+    assert_eq!(metrics.hit_rate(), Some(0.75));
+}
+
+#[tokio::test]
+async fn expired_records_are_removed_in_the_background() {
+    let mut config = InMemorySessionStoreConfig::default();
+    config.sweep_interval = Some(Duration::from_millis(10));
+    let store = InMemorySessionStore::with_config(config);
+    store
+        .create(&SessionId::random(), record(Duration::ZERO))
+        .await
+        .unwrap();
+    store
+        .create(&SessionId::random(), record(Duration::from_secs(3600)))
+        .await
+        .unwrap();
+    assert_eq!(store.metrics().size, 2);
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let metrics = store.metrics();
+    assert_eq!(metrics.size, 1);
+    assert_eq!(metrics.expirations, 1);
+}
+
+#[tokio::test]
+async fn ids_can_be_changed_across_shards() {
+    let mut config = InMemorySessionStoreConfig::default();
+    config.n_shards = NonZeroUsize::new(8).unwrap();
+    let store = InMemorySessionStore::with_config(config);
+    let ttl = Duration::from_secs(3600);
+
+    for _ in 0..32 {
+        let (old_id, new_id) = (SessionId::random(), SessionId::random());
+        store.create(&old_id, record(ttl)).await.unwrap();
+        store.change_id(&old_id, &new_id).await.unwrap();
+        assert!(store.load(&old_id).await.unwrap().is_none());
+        assert!(store.load(&new_id).await.unwrap().is_some());
+    }
+    assert_eq!(store.metrics().size, 32);
+}
+
+#[tokio::test]
+async fn timestamps_are_tracked() {
+    let store = InMemorySessionStore::default();
+    let (id, new_id) = (SessionId::random(), SessionId::random());
+    let ttl = Duration::from_secs(3600);
+    store.create(&id, record(ttl)).await.unwrap();
+
+    let loaded = store.load(&id).await.unwrap().unwrap();
+    let created_at = loaded.created_at.unwrap();
+    assert_eq!(loaded.last_seen_at, Some(created_at));
+
+    store.change_id(&id, &new_id).await.unwrap();
+    store.update(&new_id, record(ttl)).await.unwrap();
+    let loaded = store.load(&new_id).await.unwrap().unwrap();
+    assert_eq!(loaded.created_at, Some(created_at));
+    assert!(loaded.last_seen_at.unwrap() >= created_at);
+}
@@ -0,0 +1,307 @@
+use pavex_session::codec::SessionCodec;
+use pavex_session::store::errors::{
+    ChangeIdError, CreateError, DeleteByPrincipalError, DeleteError, DeleteExpiredError, LoadError,
+    UpdateError, UpdateTtlError,
+};
+use pavex_session::store::{SessionRecord, SessionRecordRef, SessionStorageBackend};
+use pavex_session::{SessionId, Value};
+use pavex_session_memory_store::{
+    InMemorySessionStore, TieredSessionStore, TieredSessionStoreConfig,
+};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::num::NonZeroUsize;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Notify;
+
+fn record(user_id: &str) -> SessionRecordRef<'static> {
+    SessionRecordRef {
+        state: Cow::Owned(HashMap::from([(
+            Cow::Borrowed("user_id"),
+            Value::from(user_id),
+        )])),
+        ttl: Duration::from_secs(3600),
+        codec: SessionCodec::Json,
+    }
+}
+
+/// A tiered store, alongside a handle to its durable backend.
+fn tiered_store(
+    cache_ttl: Duration,
+) -> (
+    TieredSessionStore<InMemorySessionStore>,
+    InMemorySessionStore,
+) {
+    let backend = InMemorySessionStore::default();
+    let mut config = TieredSessionStoreConfig::default();
+    config.cache_ttl = cache_ttl;
+    (TieredSessionStore::new(backend.clone(), config), backend)
+}
+
+/// A durable backend that pauses every load after reading the record, until it's told to resume.
+///
+/// It doesn't support indexing sessions by principal.
+#[derive(Debug, Default)]
+struct GatedBackend {
+    backend: InMemorySessionStore,
+    loaded: Arc<Notify>,
+    resume: Arc<Notify>,
+}
+
+#[async_trait::async_trait]
+impl SessionStorageBackend for GatedBackend {
+    async fn create(
+        &self,
+        id: &SessionId,
+        record: SessionRecordRef<'_>,
+    ) -> Result<(), CreateError> {
+        self.backend.create(id, record).await
+    }
+
+    async fn update(
+        &self,
+        id: &SessionId,
+        record: SessionRecordRef<'_>,
+    ) -> Result<(), UpdateError> {
+        self.backend.update(id, record).await
+    }
+
+    async fn update_ttl(&self, id: &SessionId, ttl: Duration) -> Result<(), UpdateTtlError> {
+        self.backend.update_ttl(id, ttl).await
+    }
+
+    async fn load(&self, session_id: &SessionId) -> Result<Option<SessionRecord>, LoadError> {
+        let record = self.backend.load(session_id).await?;
+        self.loaded.notify_one();
+        self.resume.notified().await;
+        Ok(record)
+    }
+
+    async fn delete(&self, session_id: &SessionId) -> Result<(), DeleteError> {
+        self.backend.delete(session_id).await
+    }
+
+    async fn change_id(&self, old_id: &SessionId, new_id: &SessionId) -> Result<(), ChangeIdError> {
+        self.backend.change_id(old_id, new_id).await
+    }
+
+    async fn delete_expired(
+        &self,
+        batch_size: Option<NonZeroUsize>,
+    ) -> Result<usize, DeleteExpiredError> {
+        self.backend.delete_expired(batch_size).await
+    }
+}
+
+#[tokio::test]
+async fn loads_are_served_from_the_cache() {
+    let (store, backend) = tiered_store(Duration::from_secs(60));
+    let id = SessionId::random();
+    store.create(&id, record("alice")).await.unwrap();
+
+    for _ in 0..3 {
+        let loaded = store.load(&id).await.unwrap().unwrap();
+        assert_eq!(loaded.state["user_id"], Value::from("alice"));
+        assert!(loaded.version.is_some());
+        assert!(loaded.created_at.is_some());
+    }
+
+    let metrics = store.metrics();
+    assert_eq!((metrics.hits, metrics.misses), (2, 1));
+    assert_eq!(backend.metrics().hits, 1);
+}
+
+#[tokio::test]
+async fn cached_records_are_reloaded_once_stale() {
+    let (store, backend) = tiered_store(Duration::from_millis(20));
+    let id = SessionId::random();
+    store.create(&id, record("alice")).await.unwrap();
+    store.load(&id).await.unwrap().unwrap();
+
+    // Another server instance modifies the record in the meantime.
+    backend.update(&id, record("bob")).await.unwrap();
+    let loaded = store.load(&id).await.unwrap().unwrap();
+    assert_eq!(loaded.state["user_id"], Value::from("alice"));
+
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    let loaded = store.load(&id).await.unwrap().unwrap();
+    assert_eq!(loaded.state["user_id"], Value::from("bob"));
+}
+
+#[tokio::test]
+async fn conditional_updates_refresh_the_cache() {
+    let (store, backend) = tiered_store(Duration::from_secs(60));
+    let id = SessionId::random();
+    store.create(&id, record("alice")).await.unwrap();
+    let loaded = store.load(&id).await.unwrap().unwrap();
+
+    let version = store
+        .update_if_version(&id, record("bob"), loaded.version.unwrap())
+        .await
+        .unwrap();
+
+    let loaded = store.load(&id).await.unwrap().unwrap();
+    assert_eq!(loaded.state["user_id"], Value::from("bob"));
+    assert_eq!(loaded.version, Some(version));
+    assert_eq!(store.metrics().hits, 1);
+    // The durable backend was updated as well.
+    let stored = backend.load(&id).await.unwrap().unwrap();
+    assert_eq!(stored.state["user_id"], Value::from("bob"));
+    assert_eq!(stored.version, Some(version));
+}
+
+#[tokio::test]
+async fn updates_refresh_the_cache() {
+    let (store, backend) = tiered_store(Duration::from_secs(60));
+    let id = SessionId::random();
+    store.create(&id, record("alice")).await.unwrap();
+    let created_at = store.load(&id).await.unwrap().unwrap().created_at;
+
+    store.update(&id, record("bob")).await.unwrap();
+
+    let loaded = store.load(&id).await.unwrap().unwrap();
+    assert_eq!(loaded.state["user_id"], Value::from("bob"));
+    assert_eq!(loaded.created_at, created_at);
+    // The new version is assigned by the durable backend.
+    assert_eq!(loaded.version, None);
+    assert_eq!(store.metrics().hits, 1);
+    // The durable backend was updated as well.
+    let stored = backend.load(&id).await.unwrap().unwrap();
+    assert_eq!(stored.state["user_id"], Value::from("bob"));
+}
+
+#[tokio::test]
+async fn stale_cached_records_are_not_written_back() {
+    let (store, backend) = tiered_store(Duration::from_secs(60));
+    let id = SessionId::random();
+    store.create(&id, record("alice")).await.unwrap();
+    let loaded = store.load(&id).await.unwrap().unwrap();
+
+    // Another server instance modifies the record in the meantime.
+    backend.update(&id, record("bob")).await.unwrap();
+
+    let err = store
+        .update_if_version(&id, record("carol"), loaded.version.unwrap())
+        .await
+        .unwrap_err();
+    assert!(matches!(err, UpdateError::Conflict(_)));
+    // The next load goes to the durable backend.
+    let loaded = store.load(&id).await.unwrap().unwrap();
+    assert_eq!(loaded.state["user_id"], Value::from("bob"));
+}
+
+#[tokio::test]
+async fn writes_invalidate_the_cache() {
+    let (store, _backend) = tiered_store(Duration::from_secs(60));
+    let (id, new_id) = (SessionId::random(), SessionId::random());
+    store.create(&id, record("alice")).await.unwrap();
+    store.load(&id).await.unwrap().unwrap();
+
+    store.update(&id, record("bob")).await.unwrap();
+    let loaded = store.load(&id).await.unwrap().unwrap();
+    assert_eq!(loaded.state["user_id"], Value::from("bob"));
+
+    store.change_id(&id, &new_id).await.unwrap();
+    assert!(store.load(&id).await.unwrap().is_none());
+    assert!(store.load(&new_id).await.unwrap().is_some());
+
+    store.delete(&new_id).await.unwrap();
+    assert!(store.load(&new_id).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn ttl_updates_are_reflected_in_the_cache() {
+    let (store, _backend) = tiered_store(Duration::from_secs(60));
+    let id = SessionId::random();
+    store.create(&id, record("alice")).await.unwrap();
+    store.load(&id).await.unwrap().unwrap();
+
+    store
+        .update_ttl(&id, Duration::from_secs(7200))
+        .await
+        .unwrap();
+
+    let loaded = store.load(&id).await.unwrap().unwrap();
+    assert!(loaded.ttl > Duration::from_secs(3600));
+    assert_eq!(store.metrics().hits, 1);
+}
+
+#[tokio::test]
+async fn deleting_by_principal_invalidates_the_cache() {
+    let (store, _backend) = tiered_store(Duration::from_secs(60));
+    let id = SessionId::random();
+    store.create(&id, record("alice")).await.unwrap();
+    store.associate_principal(&id, "alice", None).await.unwrap();
+    store.load(&id).await.unwrap().unwrap();
+
+    assert_eq!(store.delete_by_principal("alice").await.unwrap(), 1);
+    assert!(store.load(&id).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn loads_racing_with_an_invalidation_are_not_cached() {
+    let backend = GatedBackend::default();
+    let (inner, loaded, resume) = (
+        backend.backend.clone(),
+        backend.loaded.clone(),
+        backend.resume.clone(),
+    );
+    let store = TieredSessionStore::new(backend, TieredSessionStoreConfig::default());
+    let id = SessionId::random();
+    store.create(&id, record("alice")).await.unwrap();
+
+    // The record is modified while a load is in flight.
+    let writer = async {
+        loaded.notified().await;
+        store.update(&id, record("bob")).await.unwrap();
+        resume.notify_one();
+    };
+    let (stale, ()) = tokio::join!(store.load(&id), writer);
+
+    assert_eq!(
+        stale.unwrap().unwrap().state["user_id"],
+        Value::from("alice")
+    );
+    // The outdated record didn't make it into the cache.
+    assert_eq!(store.metrics().size, 0);
+    let stored = inner.load(&id).await.unwrap().unwrap();
+    assert_eq!(stored.state["user_id"], Value::from("bob"));
+}
+
+#[tokio::test]
+async fn deleting_by_principal_requires_a_principal_index() {
+    let store =
+        TieredSessionStore::new(GatedBackend::default(), TieredSessionStoreConfig::default());
+
+    let err = store.delete_by_principal("alice").await.unwrap_err();
+    assert!(matches!(err, DeleteByPrincipalError::Unsupported(_)));
+}
+
+#[tokio::test]
+async fn the_cache_is_bounded() {
+    let backend = InMemorySessionStore::default();
+    let mut config = TieredSessionStoreConfig::default();
+    config.max_capacity = NonZeroUsize::new(2).unwrap();
+    let store = TieredSessionStore::new(backend, config);
+
+    for _ in 0..3 {
+        let id = SessionId::random();
+        store.create(&id, record("alice")).await.unwrap();
+        store.load(&id).await.unwrap().unwrap();
+    }
+
+    assert!(store.metrics().size <= 2);
+}
+
+#[test]
+fn the_cache_can_be_configured() {
+    let config: TieredSessionStoreConfig =
+        serde_json::from_str(r#"{"cache_ttl": "250ms", "max_capacity": 100}"#).unwrap();
+    assert_eq!(config.cache_ttl, Duration::from_millis(250));
+    assert_eq!(config.max_capacity.get(), 100);
+
+    let config: TieredSessionStoreConfig = serde_json::from_str("{}").unwrap();
+    assert_eq!(config.cache_ttl, Duration::from_secs(5));
+    assert_eq!(config.max_capacity.get(), 10_000);
+}
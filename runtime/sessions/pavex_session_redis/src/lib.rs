@@ -2,19 +2,22 @@
 //! A Redis-based session store for [`pavex_session`](https://crates.io/crates/pavex_session),
 //! implemented using the [`redis`](https://crates.io/crates/redis) crate.
 use anyhow::Context;
+use pavex::time::Timestamp;
 use pavex::{config, methods};
 use pavex_session::{
     SessionId, SessionStore,
+    codec::SessionCodec,
     store::{
-        SessionRecord, SessionRecordRef, SessionStorageBackend,
+        SessionMetadata, SessionRecord, SessionRecordRef, SessionStorageBackend,
         errors::{
-            ChangeIdError, CreateError, DeleteError, DeleteExpiredError, DuplicateIdError,
-            LoadError, UnknownIdError, UpdateError, UpdateTtlError,
+            AssociatePrincipalError, ChangeIdError, CreateError, DeleteByPrincipalError,
+            DeleteError, DeleteExpiredError, DuplicateIdError, ListByPrincipalError, LoadError,
+            UnknownIdError, UpdateError, UpdateTtlError, VersionConflictError,
         },
     },
 };
 use redis::{AsyncCommands, ExistenceCheck, SetExpiry, SetOptions, Value, aio::ConnectionManager};
-use std::num::NonZeroUsize;
+use std::{collections::HashMap, num::NonZeroUsize};
 
 #[config(key = "redis_session_store", default_if_missing)]
 #[derive(Clone, Debug, Default, serde::Deserialize)]
@@ -41,6 +44,21 @@ pub struct RedisSessionStoreConfig {
 /// Redis keys with TTL set for automatic expiration. If the `namespace` value in [`RedisSessionStoreConfig`]
 /// is `Some`, then all session keys are stored prefixed with this string, allowing multiple applications
 /// to share the same Redis instance.
+///
+/// When a session is associated with a principal, its metadata is stored in a hash
+/// (`principal-meta:<session id>`) that expires together with the session key.
+/// The ids of the sessions associated with a principal are stored in a set (`principal:<principal id>`),
+/// whose stale members are pruned when listing the sessions of the principal.
+///
+/// The creation and last write timestamps of each session record, used to enforce
+/// [session timeouts](pavex_session::SessionTimeout), are stored in another hash
+/// (`timestamps:<session id>`), which expires together with the session key.
+///
+/// The session state is serialized using the [codec](pavex_session::codec::SessionCodec)
+/// you configured, and stored as-is in the session key.
+///
+/// The version of a session record is derived from a hash of its serialized state,
+/// computed by Redis itself when the record is loaded or conditionally updated.
 pub struct RedisSessionStore {
     connection: ConnectionManager,
     config: RedisSessionStoreConfig,
@@ -82,6 +100,148 @@ impl RedisSessionStore {
             id.inner().to_string()
         }
     }
+
+    fn principal_meta_key(&self, id: &SessionId) -> String {
+        format!("{}principal-meta:{}", self.key_prefix(), id.inner())
+    }
+
+    fn timestamps_key(&self, id: &SessionId) -> String {
+        format!("{}timestamps:{}", self.key_prefix(), id.inner())
+    }
+
+    fn principal_set_key(&self, principal_id: &str) -> String {
+        format!("{}{}", self.principal_set_prefix(), principal_id)
+    }
+
+    fn principal_set_prefix(&self) -> String {
+        format!("{}principal:", self.key_prefix())
+    }
+
+    fn key_prefix(&self) -> String {
+        match &self.config.namespace {
+            Some(namespace) => format!("{namespace}:"),
+            None => String::new(),
+        }
+    }
+
+    /// Retrieve the sessions associated with the given principal, returning
+    /// their metadata alongside the ids of stale set members.
+    async fn principal_sessions(
+        &self,
+        principal_id: &str,
+    ) -> Result<(Vec<SessionMetadata>, Vec<String>), anyhow::Error> {
+        let mut conn = self.connection.clone();
+        let members: Vec<String> = conn.smembers(self.principal_set_key(principal_id)).await?;
+        if members.is_empty() {
+            return Ok((Vec::new(), Vec::new()));
+        }
+        let ids = members
+            .iter()
+            .map(|m| {
+                m.parse()
+                    .map(SessionId::from_uuid)
+                    .context("Failed to parse a session id from the principal index")
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut pipe = redis::pipe();
+        for id in &ids {
+            pipe.cmd("PTTL")
+                .arg(self.redis_key(id))
+                .cmd("HGETALL")
+                .arg(self.principal_meta_key(id))
+                .cmd("HGET")
+                .arg(self.timestamps_key(id))
+                .arg("created_at");
+        }
+        let replies: Vec<(i64, HashMap<String, String>, Value)> =
+            pipe.query_async(&mut conn).await?;
+
+        let now = Timestamp::now();
+        let mut sessions = Vec::new();
+        let mut stale = Vec::new();
+        for ((id, member), (pttl, meta, created_at)) in ids.into_iter().zip(members).zip(replies) {
+            // The session has expired, or it has been associated with another principal.
+            if pttl < 0 || meta.get("principal_id").map(String::as_str) != Some(principal_id) {
+                stale.push(member);
+                continue;
+            }
+            let timestamp = |field: &str| -> Result<Timestamp, anyhow::Error> {
+                let unix: i64 = meta
+                    .get(field)
+                    .with_context(|| format!("The session metadata is missing `{field}`"))?
+                    .parse()
+                    .with_context(|| {
+                        format!("Failed to parse `{field}` in the session metadata")
+                    })?;
+                Ok(Timestamp::from_second(unix)?)
+            };
+            sessions.push(SessionMetadata {
+                id,
+                principal_id: principal_id.to_owned(),
+                created_at: parse_timestamp(created_at),
+                associated_at: timestamp("associated_at")?,
+                last_seen_at: timestamp("last_seen_at")?,
+                expires_at: now + std::time::Duration::from_millis(pttl as u64),
+                user_agent: meta.get("user_agent").cloned(),
+            });
+        }
+        Ok((sessions, stale))
+    }
+}
+
+/// Refresh the hashes tracking when a session was last written to, if they exist.
+///
+/// `KEYS` are the timestamps key and the principal metadata key, `ARGV[1]` the current
+/// UNIX timestamp, `ARGV[2]` the new TTL (in seconds).
+const LUA_TOUCH: &str = r#"
+    for _, key in ipairs(KEYS) do
+        if redis.call('EXISTS', key) == 1 then
+            redis.call('HSET', key, 'last_seen_at', ARGV[1])
+            redis.call('EXPIRE', key, ARGV[2])
+        end
+    end
+    return 0
+"#;
+
+/// Load a session record, alongside its TTL, its version and its timestamps.
+///
+/// Records written before we started tracking timestamps get them backfilled
+/// to the current time.
+///
+/// `KEYS[1]` is the session key, `KEYS[2]` the timestamps key.
+/// `ARGV[1]` is the current UNIX timestamp.
+const LUA_LOAD: &str = r#"
+    local ttl = redis.call('TTL', KEYS[1])
+    local state = redis.call('GET', KEYS[1])
+    if not state then
+        return {ttl}
+    end
+    local timestamps = redis.call('HMGET', KEYS[2], 'created_at', 'last_seen_at')
+    if not timestamps[1] or not timestamps[2] then
+        redis.call('HSETNX', KEYS[2], 'created_at', ARGV[1])
+        redis.call('HSETNX', KEYS[2], 'last_seen_at', ARGV[1])
+        if ttl > 0 then
+            redis.call('EXPIRE', KEYS[2], ttl)
+        end
+        timestamps = redis.call('HMGET', KEYS[2], 'created_at', 'last_seen_at')
+    end
+    return {ttl, state, string.sub(redis.sha1hex(state), 1, 16), timestamps[1], timestamps[2]}
+"#;
+
+/// Parse a UNIX timestamp, as stored in the timestamps hash.
+fn parse_timestamp(value: Value) -> Option<Timestamp> {
+    match value {
+        Value::BulkString(raw) => {
+            let seconds = std::str::from_utf8(&raw).ok()?.parse().ok()?;
+            Timestamp::from_second(seconds).ok()
+        }
+        _ => None,
+    }
+}
+
+/// Parse the version of a session record, as computed by our Lua scripts.
+fn parse_version(version: &[u8]) -> Option<u64> {
+    u64::from_str_radix(std::str::from_utf8(version).ok()?, 16).ok()
 }
 
 fn err_unknown(id: &SessionId) -> UnknownIdError {
@@ -121,24 +281,33 @@ impl SessionStorageBackend for RedisSessionStore {
         id: &SessionId,
         record: SessionRecordRef<'_>,
     ) -> Result<(), CreateError> {
-        match self
-            .connection
-            .clone()
-            .set_options(
-                self.redis_key(id),
-                serde_json::to_vec(&record.state)?,
-                SetOptions::default()
-                    .conditional_set(ExistenceCheck::NX)
-                    .with_expiration(SetExpiry::EX(record.ttl.as_secs())),
-            )
+        // Atomically store the state, if the key isn't taken, alongside its timestamps.
+        const LUA_CREATE: &str = r#"
+            if not redis.call('SET', KEYS[1], ARGV[1], 'NX', 'EX', ARGV[2]) then
+                return 0
+            end
+            redis.call('DEL', KEYS[2])
+            redis.call('HSET', KEYS[2], 'created_at', ARGV[3], 'last_seen_at', ARGV[3])
+            redis.call('EXPIRE', KEYS[2], ARGV[2])
+            return 1
+        "#;
+
+        let mut conn = self.connection.clone();
+        let result: i32 = redis::Script::new(LUA_CREATE)
+            .key(self.redis_key(id))
+            .key(self.timestamps_key(id))
+            .arg(record.encode_state()?.into_bytes())
+            .arg(record.ttl.as_secs())
+            .arg(Timestamp::now().as_second())
+            .invoke_async(&mut conn)
             .await
-            .map_err(|e| CreateError::Other(e.into()))?
-        {
-            Value::Okay => Ok(()),
-            Value::Nil => Err(err_duplicate(id).into()),
-            val => Err(CreateError::Other(anyhow::anyhow!(
-                "Redis SET replied with {:?}. Expected Okay or Nil",
-                val
+            .map_err(|e| CreateError::Other(e.into()))?;
+        match result {
+            1 => Ok(()),
+            0 => Err(err_duplicate(id).into()),
+            other => Err(CreateError::Other(anyhow::anyhow!(
+                "The creation script replied {:?}. Expected INT 0 or 1",
+                other
             ))),
         }
     }
@@ -152,19 +321,27 @@ impl SessionStorageBackend for RedisSessionStore {
         id: &SessionId,
         record: SessionRecordRef<'_>,
     ) -> Result<(), UpdateError> {
-        match self
-            .connection
-            .clone()
+        let mut conn = self.connection.clone();
+        let (reply,): (Value,) = redis::pipe()
             .set_options(
                 self.redis_key(id),
-                serde_json::to_vec(&record.state)?,
+                record.encode_state()?.into_bytes(),
                 SetOptions::default()
                     .conditional_set(ExistenceCheck::XX)
                     .with_expiration(SetExpiry::EX(record.ttl.as_secs())),
             )
+            .cmd("EVAL")
+            .arg(LUA_TOUCH)
+            .arg(2)
+            .arg(self.timestamps_key(id))
+            .arg(self.principal_meta_key(id))
+            .arg(Timestamp::now().as_second())
+            .arg(record.ttl.as_secs())
+            .ignore()
+            .query_async(&mut conn)
             .await
-            .map_err(|e| UpdateError::Other(e.into()))?
-        {
+            .map_err(|e| UpdateError::Other(e.into()))?;
+        match reply {
             Value::Okay => Ok(()),
             Value::Nil => Err(err_unknown(id).into()),
             val => Err(UpdateError::Other(anyhow::anyhow!(
@@ -174,6 +351,64 @@ impl SessionStorageBackend for RedisSessionStore {
         }
     }
 
+    /// Update the state of an existing session in the store, if its
+    /// version matches the expected one.
+    #[tracing::instrument(name = "Conditionally update server-side session record", level = tracing::Level::INFO, skip_all)]
+    async fn update_if_version(
+        &self,
+        id: &SessionId,
+        record: SessionRecordRef<'_>,
+        expected_version: u64,
+    ) -> Result<u64, UpdateError> {
+        // Atomically compare the version of the current state with the expected one
+        // and, if they match, overwrite it.
+        // It returns the new version, -1 if the key doesn't exist or -2 if
+        // the versions don't match.
+        const LUA_UPDATE_IF_VERSION: &str = r#"
+            local current = redis.call('GET', KEYS[1])
+            if not current then
+                return -1
+            end
+            if string.sub(redis.sha1hex(current), 1, 16) ~= ARGV[3] then
+                return -2
+            end
+            redis.call('SET', KEYS[1], ARGV[1], 'EX', ARGV[2])
+            for i = 2, 3 do
+                if redis.call('EXISTS', KEYS[i]) == 1 then
+                    redis.call('HSET', KEYS[i], 'last_seen_at', ARGV[4])
+                    redis.call('EXPIRE', KEYS[i], ARGV[2])
+                end
+            end
+            return string.sub(redis.sha1hex(ARGV[1]), 1, 16)
+        "#;
+
+        let mut conn = self.connection.clone();
+        let reply: Value = redis::Script::new(LUA_UPDATE_IF_VERSION)
+            .key(self.redis_key(id))
+            .key(self.principal_meta_key(id))
+            .key(self.timestamps_key(id))
+            .arg(record.encode_state()?.into_bytes())
+            .arg(record.ttl.as_secs())
+            .arg(format!("{expected_version:016x}"))
+            .arg(Timestamp::now().as_second())
+            .invoke_async(&mut conn)
+            .await
+            .map_err(|e| UpdateError::Other(e.into()))?;
+        match reply {
+            Value::BulkString(version) => parse_version(&version).ok_or_else(|| {
+                UpdateError::Other(anyhow::anyhow!(
+                    "Failed to parse the version of the updated session record"
+                ))
+            }),
+            Value::Int(-1) => Err(err_unknown(id).into()),
+            Value::Int(-2) => Err(VersionConflictError { id: *id }.into()),
+            val => Err(UpdateError::Other(anyhow::anyhow!(
+                "Redis replied with {}. Expected BulkString, -1 or -2",
+                redis_value_type_name(val)
+            ))),
+        }
+    }
+
     /// Update the TTL of an existing session record in the store.
     ///
     /// It leaves the session state unchanged.
@@ -191,6 +426,14 @@ impl SessionStorageBackend for RedisSessionStore {
             .cmd("EXPIRE")
             .arg(&k)
             .arg(ttl.as_secs())
+            .cmd("EVAL")
+            .arg(LUA_TOUCH)
+            .arg(2)
+            .arg(self.timestamps_key(id))
+            .arg(self.principal_meta_key(id))
+            .arg(Timestamp::now().as_second())
+            .arg(ttl.as_secs())
+            .ignore()
             .query_async(&mut conn)
             .await
             .map_err(|e| UpdateTtlError::Other(e.into()))?
@@ -220,14 +463,19 @@ impl SessionStorageBackend for RedisSessionStore {
     async fn load(&self, session_id: &SessionId) -> Result<Option<SessionRecord>, LoadError> {
         let mut conn = self.connection.clone();
         let k = self.redis_key(session_id);
-        let (ttl_reply, get_reply): (Value, Value) = redis::pipe()
-            .cmd("TTL")
-            .arg(&k)
-            .cmd("GET")
-            .arg(&k)
-            .query_async(&mut conn)
+        let reply: Vec<Value> = redis::Script::new(LUA_LOAD)
+            .key(&k)
+            .key(self.timestamps_key(session_id))
+            .arg(Timestamp::now().as_second())
+            .invoke_async(&mut conn)
             .await
             .map_err(|e| LoadError::Other(e.into()))?;
+        let mut reply = reply.into_iter();
+        let ttl_reply = reply.next().unwrap_or(Value::Nil);
+        let get_reply = reply.next().unwrap_or(Value::Nil);
+        let version_reply = reply.next().unwrap_or(Value::Nil);
+        let created_at = reply.next().and_then(parse_timestamp);
+        let last_seen_at = reply.next().and_then(parse_timestamp);
 
         let ttl = match ttl_reply {
             Value::Int(s) if s >= 0 => std::time::Duration::from_secs(s as u64),
@@ -246,7 +494,7 @@ impl SessionStorageBackend for RedisSessionStore {
         };
 
         let state = match get_reply {
-            Value::BulkString(raw) => serde_json::from_slice(&raw)
+            Value::BulkString(raw) => SessionCodec::decode(&raw)
                 .context("Failed to deserialize the retrieved session state")
                 .map_err(LoadError::DeserializationError)?,
             _ => {
@@ -257,7 +505,18 @@ impl SessionStorageBackend for RedisSessionStore {
             }
         };
 
-        Ok(Some(SessionRecord { ttl, state }))
+        let version = match version_reply {
+            Value::BulkString(raw) => parse_version(&raw),
+            _ => None,
+        };
+
+        Ok(Some(SessionRecord {
+            ttl,
+            state,
+            version,
+            created_at,
+            last_seen_at,
+        }))
     }
 
     /// Deletes a session record from the store using the provided ID.
@@ -265,10 +524,14 @@ impl SessionStorageBackend for RedisSessionStore {
     /// If the session exists, it is removed from the store.
     #[tracing::instrument(name = "Delete server-side session record", level = tracing::Level::INFO, skip_all)]
     async fn delete(&self, id: &SessionId) -> Result<(), DeleteError> {
-        let ndeleted: u64 = self
-            .connection
-            .clone()
+        let mut conn = self.connection.clone();
+        let (ndeleted,): (u64,) = redis::pipe()
             .del(self.redis_key(id))
+            .del(self.principal_meta_key(id))
+            .ignore()
+            .del(self.timestamps_key(id))
+            .ignore()
+            .query_async(&mut conn)
             .await
             .map_err(|e| DeleteError::Other(e.into()))?;
 
@@ -290,10 +553,23 @@ impl SessionStorageBackend for RedisSessionStore {
     #[tracing::instrument(name = "Change id for server-side session record", level = tracing::Level::INFO, skip_all)]
     async fn change_id(&self, old_id: &SessionId, new_id: &SessionId) -> Result<(), ChangeIdError> {
         // Atomically check whether a key exists and then rename if it does.
+        // The timestamps and the principal metadata, if any, are moved over to the new id.
         const LUA_RENAME_IF_EXISTS: &str = r#"
             if redis.call('EXISTS', KEYS[1]) == 1 then
                 -- returns 1 on success or 0 if fails because KEYS[2] already exists
-                return redis.call('RENAMENX', KEYS[1], KEYS[2])
+                local renamed = redis.call('RENAMENX', KEYS[1], KEYS[2])
+                if renamed == 1 and redis.call('EXISTS', KEYS[5]) == 1 then
+                    redis.call('RENAME', KEYS[5], KEYS[6])
+                end
+                if renamed == 1 and redis.call('EXISTS', KEYS[3]) == 1 then
+                    redis.call('RENAME', KEYS[3], KEYS[4])
+                    local principal = redis.call('HGET', KEYS[4], 'principal_id')
+                    if principal then
+                        redis.call('SREM', ARGV[1] .. principal, ARGV[2])
+                        redis.call('SADD', ARGV[1] .. principal, ARGV[3])
+                    end
+                end
+                return renamed
             else
                 return -1
             end
@@ -306,6 +582,13 @@ impl SessionStorageBackend for RedisSessionStore {
         let result: i32 = script
             .key(&old_key)
             .key(&new_key)
+            .key(self.principal_meta_key(old_id))
+            .key(self.principal_meta_key(new_id))
+            .key(self.timestamps_key(old_id))
+            .key(self.timestamps_key(new_id))
+            .arg(self.principal_set_prefix())
+            .arg(old_id.inner().to_string())
+            .arg(new_id.inner().to_string())
             .invoke_async(&mut conn)
             .await
             .map_err(|e| ChangeIdError::Other(e.into()))?;
@@ -331,4 +614,112 @@ impl SessionStorageBackend for RedisSessionStore {
     ) -> Result<usize, DeleteExpiredError> {
         Ok(0)
     }
+
+    /// Associate an existing session record with a principal.
+    #[tracing::instrument(name = "Associate server-side session record with a principal", level = tracing::Level::INFO, skip_all)]
+    async fn associate_principal(
+        &self,
+        id: &SessionId,
+        principal_id: &str,
+        user_agent: Option<&str>,
+    ) -> Result<(), AssociatePrincipalError> {
+        // Atomically store the metadata, aligning its TTL with the one of the session key,
+        // and add the session to the principal set.
+        const LUA_ASSOCIATE: &str = r#"
+            local ttl = redis.call('PTTL', KEYS[1])
+            if ttl < 0 then
+                return 0
+            end
+            redis.call('DEL', KEYS[2])
+            redis.call('HSET', KEYS[2], 'principal_id', ARGV[2], 'associated_at', ARGV[3], 'last_seen_at', ARGV[3])
+            if ARGV[4] == '1' then
+                redis.call('HSET', KEYS[2], 'user_agent', ARGV[5])
+            end
+            redis.call('PEXPIRE', KEYS[2], ttl)
+            redis.call('SADD', KEYS[3], ARGV[1])
+            return 1
+        "#;
+
+        let mut conn = self.connection.clone();
+        let result: i32 = redis::Script::new(LUA_ASSOCIATE)
+            .key(self.redis_key(id))
+            .key(self.principal_meta_key(id))
+            .key(self.principal_set_key(principal_id))
+            .arg(id.inner().to_string())
+            .arg(principal_id)
+            .arg(Timestamp::now().as_second())
+            .arg(if user_agent.is_some() { "1" } else { "0" })
+            .arg(user_agent.unwrap_or_default())
+            .invoke_async(&mut conn)
+            .await
+            .map_err(|e| AssociatePrincipalError::Other(e.into()))?;
+        match result {
+            1 => Ok(()),
+            0 => Err(err_unknown(id).into()),
+            other => Err(AssociatePrincipalError::Other(anyhow::anyhow!(
+                "The association script replied {:?}. Expected INT 0 or 1",
+                other
+            ))),
+        }
+    }
+
+    /// List all the active sessions associated with the given principal.
+    #[tracing::instrument(name = "List server-side session records for a principal", level = tracing::Level::INFO, skip_all)]
+    async fn list_by_principal(
+        &self,
+        principal_id: &str,
+    ) -> Result<Vec<SessionMetadata>, ListByPrincipalError> {
+        let (sessions, stale) = self
+            .principal_sessions(principal_id)
+            .await
+            .map_err(ListByPrincipalError::Other)?;
+        if !stale.is_empty() {
+            // Pruning is best-effort: it'll be retried the next time the sessions are listed.
+            let outcome: Result<(), _> = self
+                .connection
+                .clone()
+                .srem(self.principal_set_key(principal_id), stale)
+                .await;
+            if let Err(e) = outcome {
+                tracing::warn!(error.message = %e, "Failed to prune stale members of a principal set");
+            }
+        }
+        Ok(sessions)
+    }
+
+    /// Delete all the sessions associated with the given principal.
+    #[tracing::instrument(name = "Delete server-side session records for a principal", level = tracing::Level::INFO, skip_all)]
+    async fn delete_by_principal(
+        &self,
+        principal_id: &str,
+    ) -> Result<usize, DeleteByPrincipalError> {
+        let (sessions, stale) = self
+            .principal_sessions(principal_id)
+            .await
+            .map_err(DeleteByPrincipalError::Other)?;
+        if sessions.is_empty() && stale.is_empty() {
+            return Ok(0);
+        }
+        let mut members = stale;
+        let mut pipe = redis::pipe();
+        for session in &sessions {
+            pipe.del(self.redis_key(&session.id))
+                .del(self.principal_meta_key(&session.id))
+                .ignore()
+                .del(self.timestamps_key(&session.id))
+                .ignore();
+            members.push(session.id.inner().to_string());
+        }
+        pipe.srem(self.principal_set_key(principal_id), members)
+            .ignore();
+        let deleted: Vec<u64> = pipe
+            .query_async(&mut self.connection.clone())
+            .await
+            .map_err(|e| DeleteByPrincipalError::Other(e.into()))?;
+        Ok(deleted
+            .into_iter()
+            .sum::<u64>()
+            .try_into()
+            .unwrap_or(usize::MAX))
+    }
 }
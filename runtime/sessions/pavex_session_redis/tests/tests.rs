@@ -1,5 +1,6 @@
+use pavex_session::codec::SessionCodec;
 use pavex_session::store::{SessionRecordRef, SessionStorageBackend};
-use pavex_session::{SessionId, store::errors::*};
+use pavex_session::{SessionId, Value, store::errors::*};
 use pavex_session_redis::{RedisSessionStore, RedisSessionStoreConfig};
 use redis::aio::ConnectionManager;
 use std::borrow::Cow;
@@ -26,22 +27,20 @@ async fn test_store() -> RedisSessionStore {
     RedisSessionStore::new(test_redis_connection().await, config)
 }
 
-fn create_test_record(
-    _ttl_secs: u64,
-) -> (SessionId, HashMap<Cow<'static, str>, serde_json::Value>) {
+fn create_test_record(_ttl_secs: u64) -> (SessionId, HashMap<Cow<'static, str>, Value>) {
     let session_id = SessionId::random();
     let mut state = HashMap::new();
     state.insert(
         Cow::Borrowed("user_id"),
-        serde_json::Value::String("test-user-123".to_string()),
+        Value::String("test-user-123".to_string()),
     );
     state.insert(
         Cow::Borrowed("login_time"),
-        serde_json::Value::String("2024-01-01T00:00:00Z".to_string()),
+        Value::String("2024-01-01T00:00:00Z".to_string()),
     );
     state.insert(
         Cow::Borrowed("permissions"),
-        serde_json::json!(["read", "write"]),
+        serde_json::json!(["read", "write"]).into(),
     );
     state.insert(
         Cow::Borrowed("metadata"),
@@ -49,7 +48,8 @@ fn create_test_record(
             "ip": "192.168.1.1",
             "user_agent": "test-agent",
             "session_start": 1640995200
-        }),
+        })
+        .into(),
     );
     (session_id, state)
 }
@@ -62,6 +62,7 @@ async fn test_create_and_load_roundtrip() {
     let record = SessionRecordRef {
         state: Cow::Borrowed(&state),
         ttl: Duration::from_secs(3600),
+        codec: SessionCodec::Json,
     };
 
     // Create session
@@ -99,6 +100,7 @@ async fn test_update_roundtrip() {
     let record = SessionRecordRef {
         state: Cow::Borrowed(&state),
         ttl: Duration::from_secs(3600),
+        codec: SessionCodec::Json,
     };
 
     // Create initial session
@@ -107,11 +109,11 @@ async fn test_update_roundtrip() {
     // Update the state
     state.insert(
         Cow::Borrowed("updated_field"),
-        serde_json::Value::String("new_value".to_string()),
+        Value::String("new_value".to_string()),
     );
     state.insert(
         Cow::Borrowed("user_id"),
-        serde_json::Value::String("updated-user-456".to_string()),
+        Value::String("updated-user-456".to_string()),
     );
     state.insert(
         Cow::Borrowed("new_metadata"),
@@ -123,12 +125,14 @@ async fn test_update_roundtrip() {
                     "deeply": ["nested", "array", 123, true]
                 }
             }
-        }),
+        })
+        .into(),
     );
 
     let updated_record = SessionRecordRef {
         state: Cow::Borrowed(&state),
         ttl: Duration::from_secs(7200),
+        codec: SessionCodec::Json,
     };
 
     // Update session
@@ -162,6 +166,7 @@ async fn test_ttl_expiry() {
     let record = SessionRecordRef {
         state: Cow::Borrowed(&state),
         ttl: Duration::from_secs(1), // Very short TTL
+        codec: SessionCodec::Json,
     };
 
     // Create session with short TTL
@@ -187,6 +192,7 @@ async fn test_update_ttl_roundtrip() {
     let record = SessionRecordRef {
         state: Cow::Borrowed(&state),
         ttl: Duration::from_secs(3600),
+        codec: SessionCodec::Json,
     };
 
     // Create session
@@ -221,6 +227,7 @@ async fn test_delete_roundtrip() {
     let record = SessionRecordRef {
         state: Cow::Borrowed(&state),
         ttl: Duration::from_secs(3600),
+        codec: SessionCodec::Json,
     };
 
     // Create session
@@ -247,6 +254,7 @@ async fn test_change_id_roundtrip() {
     let record = SessionRecordRef {
         state: Cow::Borrowed(&state),
         ttl: Duration::from_secs(3600),
+        codec: SessionCodec::Json,
     };
 
     // Create session with old ID
@@ -290,14 +298,12 @@ async fn test_concurrent_operations() {
         let handle = tokio::spawn(async move {
             let (session_id, state) = create_test_record(3600);
             let mut modified_state = state;
-            modified_state.insert(
-                Cow::Borrowed("thread_id"),
-                serde_json::Value::Number(i.into()),
-            );
+            modified_state.insert(Cow::Borrowed("thread_id"), Value::from(i));
 
             let record = SessionRecordRef {
                 state: Cow::Borrowed(&modified_state),
                 ttl: Duration::from_secs(3600),
+                codec: SessionCodec::Json,
             };
 
             store_clone.create(&session_id, record).await.unwrap();
@@ -357,16 +363,19 @@ async fn test_namespace_isolation() {
     let record_a = SessionRecordRef {
         state: Cow::Borrowed(&state_a),
         ttl: Duration::from_secs(3600),
+        codec: SessionCodec::Json,
     };
     let (session_b, state_b) = create_test_record(3600);
     let record_b = SessionRecordRef {
         state: Cow::Borrowed(&state_b),
         ttl: Duration::from_secs(3600),
+        codec: SessionCodec::Json,
     };
     let (session_c, state_c) = create_test_record(3600);
     let record_c = SessionRecordRef {
         state: Cow::Borrowed(&state_c),
         ttl: Duration::from_secs(3600),
+        codec: SessionCodec::Json,
     };
 
     store_a.create(&session_a, record_a).await.unwrap();
@@ -397,6 +406,7 @@ async fn test_create_duplicate_id_error() {
     let record = SessionRecordRef {
         state: Cow::Borrowed(&state),
         ttl: Duration::from_secs(3600),
+        codec: SessionCodec::Json,
     };
 
     // Create initial session
@@ -407,12 +417,13 @@ async fn test_create_duplicate_id_error() {
     let mut conflicting_state = different_state;
     conflicting_state.insert(
         Cow::Borrowed("conflict_field"),
-        serde_json::Value::String("this should conflict".to_string()),
+        Value::String("this should conflict".to_string()),
     );
 
     let conflicting_record = SessionRecordRef {
         state: Cow::Borrowed(&conflicting_state),
         ttl: Duration::from_secs(1), // Short TTL to force conflict
+        codec: SessionCodec::Json,
     };
 
     // Verify the original session exists
@@ -448,6 +459,7 @@ async fn test_update_unknown_id_error() {
     let record = SessionRecordRef {
         state: Cow::Borrowed(&state),
         ttl: Duration::from_secs(3600),
+        codec: SessionCodec::Json,
     };
 
     // Try to update a session that doesn't exist
@@ -526,10 +538,12 @@ async fn test_change_id_duplicate_new_id_error() {
     let record_1 = SessionRecordRef {
         state: Cow::Borrowed(&state_1),
         ttl: Duration::from_secs(3600),
+        codec: SessionCodec::Json,
     };
     let record_2 = SessionRecordRef {
         state: Cow::Borrowed(&state_2),
         ttl: Duration::from_secs(3600),
+        codec: SessionCodec::Json,
     };
 
     store.create(&session_id_1, record_1).await.unwrap();
@@ -559,6 +573,7 @@ async fn test_operations_on_expired_session() {
     let record = SessionRecordRef {
         state: Cow::Borrowed(&state),
         ttl: Duration::from_secs(1), // Very short TTL
+        codec: SessionCodec::Json,
     };
 
     // Create session with short TTL
@@ -572,6 +587,7 @@ async fn test_operations_on_expired_session() {
     let new_record = SessionRecordRef {
         state: Cow::Borrowed(&new_state),
         ttl: Duration::from_secs(3600),
+        codec: SessionCodec::Json,
     };
 
     let update_result = store.update(&session_id, new_record).await;
@@ -638,11 +654,12 @@ async fn test_serialization_error() {
     let mut state = HashMap::new();
 
     // JSON serialization should handle this fine, but let's test with some edge cases
-    state.insert(Cow::Borrowed("inf_value"), serde_json::json!(f64::INFINITY));
+    state.insert(Cow::Borrowed("inf_value"), Value::Float(f64::INFINITY));
 
     let record = SessionRecordRef {
         state: Cow::Borrowed(&state),
         ttl: Duration::from_secs(3600),
+        codec: SessionCodec::Json,
     };
 
     // This should succeed because serde_json handles infinity as null in JSON
@@ -662,3 +679,153 @@ async fn test_serialization_error() {
         Err(other) => panic!("Unexpected error type: {:?}", other),
     }
 }
+
+#[tokio::test]
+async fn test_principal_index_roundtrip() {
+    let store = test_store().await;
+    let (session_a, state) = create_test_record(3600);
+    let session_b = SessionId::random();
+    for id in [&session_a, &session_b] {
+        let record = SessionRecordRef {
+            state: Cow::Borrowed(&state),
+            ttl: Duration::from_secs(3600),
+            codec: SessionCodec::Json,
+        };
+        store.create(id, record).await.unwrap();
+    }
+    store
+        .associate_principal(&session_a, "alice", Some("Firefox"))
+        .await
+        .unwrap();
+    store
+        .associate_principal(&session_b, "alice", None)
+        .await
+        .unwrap();
+
+    let sessions = store.list_by_principal("alice").await.unwrap();
+    assert_eq!(sessions.len(), 2);
+    let a = sessions.iter().find(|s| s.id == session_a).unwrap();
+    assert_eq!(a.user_agent.as_deref(), Some("Firefox"));
+    assert!(a.expires_at > a.last_seen_at);
+
+    // The association follows the session record when its id changes...
+    let session_a2 = SessionId::random();
+    store.change_id(&session_a, &session_a2).await.unwrap();
+    let sessions = store.list_by_principal("alice").await.unwrap();
+    assert!(sessions.iter().any(|s| s.id == session_a2));
+    assert!(!sessions.iter().any(|s| s.id == session_a));
+
+    // ...and it goes away when the session record is deleted.
+    store.delete(&session_b).await.unwrap();
+    assert_eq!(store.list_by_principal("alice").await.unwrap().len(), 1);
+
+    assert_eq!(store.delete_by_principal("alice").await.unwrap(), 1);
+    assert!(store.list_by_principal("alice").await.unwrap().is_empty());
+    assert!(store.load(&session_a2).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_associate_principal_unknown_id_error() {
+    let store = test_store().await;
+    let session_id = SessionId::random();
+
+    let err = store
+        .associate_principal(&session_id, "alice", None)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, AssociatePrincipalError::UnknownId(_)));
+}
+
+#[tokio::test]
+async fn test_update_if_version_detects_concurrent_updates() {
+    let store = test_store().await;
+    let (session_id, state) = create_test_record(3600);
+    let record = |state| SessionRecordRef {
+        state: Cow::Borrowed(state),
+        ttl: Duration::from_secs(3600),
+        codec: SessionCodec::Json,
+    };
+    store.create(&session_id, record(&state)).await.unwrap();
+    let version = store.load(&session_id).await.unwrap().unwrap().version;
+    let version = version.expect("Redis records are versioned");
+
+    // Someone else modifies the state...
+    let mut modified = state.clone();
+    modified.insert(Cow::Borrowed("theme"), Value::from("dark"));
+    store.update(&session_id, record(&modified)).await.unwrap();
+    let err = store
+        .update_if_version(&session_id, record(&state), version)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, UpdateError::Conflict(_)));
+
+    // ...but conditional updates succeed against the latest version.
+    let latest = store.load(&session_id).await.unwrap().unwrap().version;
+    let new_version = store
+        .update_if_version(&session_id, record(&state), latest.unwrap())
+        .await
+        .unwrap();
+    assert_eq!(
+        store.load(&session_id).await.unwrap().unwrap().version,
+        Some(new_version)
+    );
+
+    let err = store
+        .update_if_version(&SessionId::random(), record(&state), version)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, UpdateError::UnknownIdError(_)));
+}
+
+#[tokio::test]
+async fn test_binary_codecs_roundtrip() {
+    let store = test_store().await;
+    let (_, mut state) = create_test_record(3600);
+    state.insert(Cow::Borrowed("avatar"), Value::Bytes(vec![0, 1, 2, 255]));
+
+    for codec in [
+        SessionCodec::MessagePack,
+        SessionCodec::Cbor,
+        SessionCodec::Postcard,
+    ] {
+        let session_id = SessionId::random();
+        let record = SessionRecordRef {
+            state: Cow::Borrowed(&state),
+            ttl: Duration::from_secs(3600),
+            codec,
+        };
+        store.create(&session_id, record).await.unwrap();
+
+        let loaded = store.load(&session_id).await.unwrap().unwrap();
+        assert_eq!(loaded.state, state, "Mismatch for codec: {codec:?}");
+    }
+}
+
+#[tokio::test]
+async fn test_json_records_are_readable_after_switching_codec() {
+    let store = test_store().await;
+    let (session_id, state) = create_test_record(3600);
+    let record = |state, codec| SessionRecordRef {
+        state: Cow::Borrowed(state),
+        ttl: Duration::from_secs(3600),
+        codec,
+    };
+    store
+        .create(&session_id, record(&state, SessionCodec::Json))
+        .await
+        .unwrap();
+
+    // A JSON record can still be loaded...
+    let loaded = store.load(&session_id).await.unwrap().unwrap();
+    assert_eq!(loaded.state, state);
+
+    // ...and it's re-encoded using the new codec when it's updated.
+    let mut updated = state.clone();
+    updated.insert(Cow::Borrowed("theme"), Value::from("dark"));
+    store
+        .update(&session_id, record(&updated, SessionCodec::MessagePack))
+        .await
+        .unwrap();
+    let loaded = store.load(&session_id).await.unwrap().unwrap();
+    assert_eq!(loaded.state, updated);
+}
@@ -4,11 +4,13 @@ use pavex::time::Timestamp;
 use pavex_session::SessionStore;
 use pavex_session::{
     SessionId,
+    codec::{EncodedState, SessionCodec},
     store::{
-        SessionRecord, SessionRecordRef, SessionStorageBackend,
+        SessionMetadata, SessionRecord, SessionRecordRef, SessionStorageBackend,
         errors::{
-            ChangeIdError, CreateError, DeleteError, DeleteExpiredError, DuplicateIdError,
-            LoadError, UnknownIdError, UpdateError, UpdateTtlError,
+            AssociatePrincipalError, ChangeIdError, CreateError, DeleteByPrincipalError,
+            DeleteError, DeleteExpiredError, DuplicateIdError, ListByPrincipalError, LoadError,
+            UnknownIdError, UpdateError, UpdateTtlError, VersionConflictError,
         },
     },
 };
@@ -31,6 +33,19 @@ use std::num::NonZeroUsize;
 /// to get the SQL query that creates the table and index in order to run it yourself
 /// (e.g. as part of your database migration scripts).
 ///
+/// The association between sessions and principals is stored in a separate table,
+/// `session_principals`. It references the sessions table, so that it follows
+/// the session record when its id changes or when it is deleted.
+///
+/// Each session record has a `version` column, incremented every time its state is
+/// updated, to detect concurrent updates to the same session.
+/// The `created_at` and `last_seen_at` columns are used to enforce
+/// [session timeouts](pavex_session::SessionTimeout).
+///
+/// The session state is stored in the `state` column, as `JSON`, when using the
+/// default [JSON codec](pavex_session::codec::SessionCodec::Json).
+/// It's stored in the `encoded_state` column, as `LONGBLOB`, when using a binary codec.
+///
 /// # MySQL version requirements
 ///
 /// This implementation requires MySQL 5.7.8+ or MariaDB 10.2+ for JSON support.
@@ -78,8 +93,56 @@ impl MySqlSessionStore {
 CREATE TABLE IF NOT EXISTS sessions (
     id CHAR(36) PRIMARY KEY,
     deadline BIGINT NOT NULL,
-    state JSON NOT NULL,
+    state JSON,
+    version BIGINT NOT NULL DEFAULT 0,
+    encoded_state LONGBLOB,
+    created_at BIGINT,
+    last_seen_at BIGINT,
     INDEX idx_sessions_deadline (deadline)
+);
+
+-- Add the version column to sessions tables created by older versions of this store
+SET @add_version_column = IF(
+    (SELECT COUNT(*) FROM information_schema.columns
+     WHERE table_schema = DATABASE() AND table_name = 'sessions' AND column_name = 'version') = 0,
+    'ALTER TABLE sessions ADD COLUMN version BIGINT NOT NULL DEFAULT 0',
+    'DO 0'
+);
+PREPARE add_version_column FROM @add_version_column;
+EXECUTE add_version_column;
+DEALLOCATE PREPARE add_version_column;
+
+-- Allow sessions tables created by older versions of this store to hold binary-encoded states
+SET @add_encoded_state_column = IF(
+    (SELECT COUNT(*) FROM information_schema.columns
+     WHERE table_schema = DATABASE() AND table_name = 'sessions' AND column_name = 'encoded_state') = 0,
+    'ALTER TABLE sessions ADD COLUMN encoded_state LONGBLOB',
+    'DO 0'
+);
+PREPARE add_encoded_state_column FROM @add_encoded_state_column;
+EXECUTE add_encoded_state_column;
+DEALLOCATE PREPARE add_encoded_state_column;
+ALTER TABLE sessions MODIFY COLUMN state JSON NULL;
+
+-- Add the timestamp columns to sessions tables created by older versions of this store
+SET @add_timestamp_columns = IF(
+    (SELECT COUNT(*) FROM information_schema.columns
+     WHERE table_schema = DATABASE() AND table_name = 'sessions' AND column_name = 'created_at') = 0,
+    'ALTER TABLE sessions ADD COLUMN created_at BIGINT, ADD COLUMN last_seen_at BIGINT',
+    'DO 0'
+);
+PREPARE add_timestamp_columns FROM @add_timestamp_columns;
+EXECUTE add_timestamp_columns;
+DEALLOCATE PREPARE add_timestamp_columns;
+
+-- Create the table associating sessions with principals if it doesn't exist
+CREATE TABLE IF NOT EXISTS session_principals (
+    session_id CHAR(36) PRIMARY KEY,
+    principal_id VARCHAR(255) NOT NULL,
+    associated_at BIGINT NOT NULL,
+    user_agent TEXT,
+    INDEX idx_session_principals_principal_id (principal_id),
+    FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE ON UPDATE CASCADE
 );"
     }
 
@@ -96,6 +159,36 @@ CREATE TABLE IF NOT EXISTS sessions (
         self.0.execute(Self::migration_query()).await?;
         Ok(())
     }
+
+    /// Set the timestamps of a record created before the `created_at` and `last_seen_at`
+    /// columns were introduced to the current time, so that the configured timeouts can be
+    /// enforced from now on.
+    async fn backfill_timestamps(
+        &self,
+        session_id: &SessionId,
+        record: &mut SessionRecord,
+    ) -> Result<(), LoadError> {
+        if record.created_at.is_some() && record.last_seen_at.is_some() {
+            return Ok(());
+        }
+        // Truncate to whole seconds, so that the returned record matches the stored one.
+        let now = Timestamp::now();
+        let now = Timestamp::from_second(now.as_second()).unwrap_or(now);
+        sqlx::query(
+            "UPDATE sessions \
+            SET created_at = COALESCE(created_at, ?), last_seen_at = COALESCE(last_seen_at, ?) \
+            WHERE id = ?",
+        )
+        .bind(now.as_second())
+        .bind(now.as_second())
+        .bind(session_id.inner().to_string())
+        .execute(&self.0)
+        .await
+        .map_err(|e| LoadError::Other(e.into()))?;
+        record.created_at.get_or_insert(now);
+        record.last_seen_at.get_or_insert(now);
+        Ok(())
+    }
 }
 
 #[async_trait::async_trait]
@@ -118,24 +211,45 @@ impl SessionStorageBackend for MySqlSessionStore {
         id: &SessionId,
         record: SessionRecordRef<'_>,
     ) -> Result<(), CreateError> {
-        let deadline = Timestamp::now() + record.ttl;
+        let now = Timestamp::now();
+        let deadline = now + record.ttl;
         let deadline_unix = deadline.as_second();
-        let state = serde_json::to_value(record.state)?;
+        let (state, encoded_state) = state_columns(record.encode_state()?);
         let query = sqlx::query(
-            "INSERT INTO sessions (id, deadline, state) \
-            VALUES (?, ?, ?) \
+            "INSERT INTO sessions (id, deadline, state, encoded_state, created_at, last_seen_at) \
+            VALUES (?, ?, ?, ?, ?, ?) \
             ON DUPLICATE KEY UPDATE \
-            deadline = VALUES(deadline), state = VALUES(state)",
+            deadline = VALUES(deadline), state = VALUES(state), \
+            encoded_state = VALUES(encoded_state), version = version + 1, \
+            created_at = VALUES(created_at), last_seen_at = VALUES(last_seen_at)",
         )
         .bind(id.inner().to_string())
         .bind(deadline_unix)
-        .bind(state);
+        .bind(state)
+        .bind(encoded_state)
+        .bind(now.as_second())
+        .bind(now.as_second());
 
-        match query.execute(&self.0).await {
-            // All good, we created the session record.
-            Ok(_) => Ok(()),
-            Err(e) => Err(CreateError::Other(e.into())),
-        }
+        // The record we overwrote, if any, may have been associated with a principal:
+        // the association must go away with it.
+        let mut transaction = self
+            .0
+            .begin()
+            .await
+            .map_err(|e| CreateError::Other(e.into()))?;
+        query
+            .execute(&mut *transaction)
+            .await
+            .map_err(|e| CreateError::Other(e.into()))?;
+        sqlx::query("DELETE FROM session_principals WHERE session_id = ?")
+            .bind(id.inner().to_string())
+            .execute(&mut *transaction)
+            .await
+            .map_err(|e| CreateError::Other(e.into()))?;
+        transaction
+            .commit()
+            .await
+            .map_err(|e| CreateError::Other(e.into()))
     }
 
     /// Update the state of an existing session in the store.
@@ -149,14 +263,16 @@ impl SessionStorageBackend for MySqlSessionStore {
     ) -> Result<(), UpdateError> {
         let new_deadline = Timestamp::now() + record.ttl;
         let new_deadline_unix = new_deadline.as_second();
-        let new_state = serde_json::to_value(record.state)?;
+        let (new_state, new_encoded_state) = state_columns(record.encode_state()?);
         let query = sqlx::query(
             "UPDATE sessions \
-            SET deadline = ?, state = ? \
+            SET deadline = ?, state = ?, encoded_state = ?, version = version + 1, \
+                last_seen_at = UNIX_TIMESTAMP() \
             WHERE id = ? AND deadline > UNIX_TIMESTAMP()",
         )
         .bind(new_deadline_unix)
         .bind(new_state)
+        .bind(new_encoded_state)
         .bind(id.inner().to_string());
 
         match query.execute(&self.0).await {
@@ -165,6 +281,53 @@ impl SessionStorageBackend for MySqlSessionStore {
         }
     }
 
+    /// Update the state of an existing session in the store, if its
+    /// version matches the expected one.
+    #[tracing::instrument(name = "Conditionally update server-side session record", level = tracing::Level::INFO, skip_all)]
+    async fn update_if_version(
+        &self,
+        id: &SessionId,
+        record: SessionRecordRef<'_>,
+        expected_version: u64,
+    ) -> Result<u64, UpdateError> {
+        let new_deadline = Timestamp::now() + record.ttl;
+        let new_deadline_unix = new_deadline.as_second();
+        let (new_state, new_encoded_state) = state_columns(record.encode_state()?);
+        let version = i64::try_from(expected_version).map_err(|e| UpdateError::Other(e.into()))?;
+        let query = sqlx::query(
+            "UPDATE sessions \
+            SET deadline = ?, state = ?, encoded_state = ?, version = version + 1, \
+                last_seen_at = UNIX_TIMESTAMP() \
+            WHERE id = ? AND deadline > UNIX_TIMESTAMP() AND version = ?",
+        )
+        .bind(new_deadline_unix)
+        .bind(new_state)
+        .bind(new_encoded_state)
+        .bind(id.inner().to_string())
+        .bind(version);
+
+        let r = query
+            .execute(&self.0)
+            .await
+            .map_err(|e| UpdateError::Other(e.into()))?;
+        if r.rows_affected() > 0 {
+            return Ok(expected_version + 1);
+        }
+        // Nothing was updated: either the record is gone or its version changed.
+        let exists: bool = sqlx::query_scalar(
+            "SELECT EXISTS(SELECT 1 FROM sessions WHERE id = ? AND deadline > UNIX_TIMESTAMP())",
+        )
+        .bind(id.inner().to_string())
+        .fetch_one(&self.0)
+        .await
+        .map_err(|e| UpdateError::Other(e.into()))?;
+        if exists {
+            Err(VersionConflictError { id: *id }.into())
+        } else {
+            Err(UnknownIdError { id: *id }.into())
+        }
+    }
+
     /// Update the TTL of an existing session record in the store.
     ///
     /// It leaves the session state unchanged.
@@ -178,7 +341,7 @@ impl SessionStorageBackend for MySqlSessionStore {
         let new_deadline_unix = new_deadline.as_second();
         let query = sqlx::query(
             "UPDATE sessions \
-            SET deadline = ? \
+            SET deadline = ?, last_seen_at = UNIX_TIMESTAMP() \
             WHERE id = ? AND deadline > UNIX_TIMESTAMP()",
         )
         .bind(new_deadline_unix)
@@ -197,7 +360,7 @@ impl SessionStorageBackend for MySqlSessionStore {
     #[tracing::instrument(name = "Load server-side session record", level = tracing::Level::INFO, skip_all)]
     async fn load(&self, session_id: &SessionId) -> Result<Option<SessionRecord>, LoadError> {
         let row = sqlx::query(
-            "SELECT deadline, state \
+            "SELECT deadline, state, version, encoded_state, created_at, last_seen_at \
             FROM sessions \
             WHERE id = ? AND deadline > UNIX_TIMESTAMP()",
         )
@@ -205,31 +368,60 @@ impl SessionStorageBackend for MySqlSessionStore {
         .fetch_optional(&self.0)
         .await
         .map_err(|e| LoadError::Other(e.into()))?;
-        row.map(|r| {
-            use anyhow::Context as _;
-            use sqlx::Row as _;
+        let mut record = row
+            .map(|r| {
+                use anyhow::Context as _;
+                use sqlx::Row as _;
 
-            let deadline_unix: i64 = r
-                .try_get(0)
-                .context("Failed to deserialize the retrieved session deadline")
-                .map_err(LoadError::DeserializationError)?;
-            let deadline = Timestamp::from_second(deadline_unix)
-                .context("Failed to parse the retrieved session deadline")
-                .map_err(LoadError::DeserializationError)?;
-            let state: serde_json::Value = r
-                .try_get(1)
+                let deadline_unix: i64 = r
+                    .try_get(0)
+                    .context("Failed to deserialize the retrieved session deadline")
+                    .map_err(LoadError::DeserializationError)?;
+                let deadline = Timestamp::from_second(deadline_unix)
+                    .context("Failed to parse the retrieved session deadline")
+                    .map_err(LoadError::DeserializationError)?;
+                let encoded_state: Option<Vec<u8>> = r
+                    .try_get(3)
+                    .context("Failed to deserialize the retrieved session state")
+                    .map_err(LoadError::DeserializationError)?;
+                let state = match encoded_state {
+                    Some(encoded_state) => {
+                        SessionCodec::decode(&encoded_state).map_err(anyhow::Error::from)
+                    }
+                    None => r
+                        .try_get::<serde_json::Value, _>(1)
+                        .map_err(anyhow::Error::from)
+                        .and_then(|state| {
+                            serde_json::from_value(state).map_err(anyhow::Error::from)
+                        }),
+                }
                 .context("Failed to deserialize the retrieved session state")
                 .map_err(LoadError::DeserializationError)?;
-            let ttl = deadline - Timestamp::now();
-            Ok(SessionRecord {
-                // This conversion only fails if the duration is negative, which should not happen
-                ttl: ttl.try_into().unwrap_or(std::time::Duration::ZERO),
-                state: serde_json::from_value(state)
-                    .context("Failed to deserialize the retrieved session state")
-                    .map_err(LoadError::DeserializationError)?,
+                let version: i64 = r
+                    .try_get(2)
+                    .context("Failed to deserialize the retrieved session version")
+                    .map_err(LoadError::DeserializationError)?;
+                let [created_at, last_seen_at] = [4, 5].map(|i| {
+                    r.try_get::<Option<i64>, _>(i)
+                        .context("Failed to deserialize the retrieved session timestamps")
+                        .map_err(LoadError::DeserializationError)
+                        .map(|ts| ts.and_then(|ts| Timestamp::from_second(ts).ok()))
+                });
+                let ttl = deadline - Timestamp::now();
+                Ok(SessionRecord {
+                    // This conversion only fails if the duration is negative, which should not happen
+                    ttl: ttl.try_into().unwrap_or(std::time::Duration::ZERO),
+                    state,
+                    version: u64::try_from(version).ok(),
+                    created_at: created_at?,
+                    last_seen_at: last_seen_at?,
+                })
             })
-        })
-        .transpose()
+            .transpose()?;
+        if let Some(record) = &mut record {
+            self.backfill_timestamps(session_id, record).await?;
+        }
+        Ok(record)
     }
 
     /// Deletes a session record from the store using the provided ID.
@@ -334,6 +526,114 @@ impl SessionStorageBackend for MySqlSessionStore {
         })?;
         Ok(r.rows_affected().try_into().unwrap_or(usize::MAX))
     }
+
+    /// Associate an existing session record with a principal.
+    #[tracing::instrument(name = "Associate server-side session record with a principal", level = tracing::Level::INFO, skip_all)]
+    async fn associate_principal(
+        &self,
+        id: &SessionId,
+        principal_id: &str,
+        user_agent: Option<&str>,
+    ) -> Result<(), AssociatePrincipalError> {
+        let query = sqlx::query(
+            "REPLACE INTO session_principals (session_id, principal_id, associated_at, user_agent) \
+            SELECT id, ?, UNIX_TIMESTAMP(), ? \
+            FROM sessions \
+            WHERE id = ? AND deadline > UNIX_TIMESTAMP()",
+        )
+        .bind(principal_id)
+        .bind(user_agent)
+        .bind(id.inner().to_string());
+        match query.execute(&self.0).await {
+            // `REPLACE` reports two affected rows if it overwrote a pre-existing association.
+            Ok(r) if r.rows_affected() == 0 => Err(UnknownIdError { id: id.to_owned() }.into()),
+            Ok(_) => Ok(()),
+            Err(e) => Err(AssociatePrincipalError::Other(e.into())),
+        }
+    }
+
+    /// List all the active sessions associated with the given principal.
+    #[tracing::instrument(name = "List server-side session records for a principal", level = tracing::Level::INFO, skip_all)]
+    async fn list_by_principal(
+        &self,
+        principal_id: &str,
+    ) -> Result<Vec<SessionMetadata>, ListByPrincipalError> {
+        use anyhow::Context as _;
+        use sqlx::Row as _;
+
+        let rows = sqlx::query(
+            "SELECT s.id, p.associated_at, COALESCE(s.last_seen_at, p.associated_at), s.deadline, p.user_agent, s.created_at \
+            FROM session_principals p \
+            JOIN sessions s ON s.id = p.session_id \
+            WHERE p.principal_id = ? AND s.deadline > UNIX_TIMESTAMP()",
+        )
+        .bind(principal_id)
+        .fetch_all(&self.0)
+        .await
+        .map_err(|e| ListByPrincipalError::Other(e.into()))?;
+        rows.into_iter()
+            .map(|r| {
+                let timestamp = |i: usize| -> Result<Timestamp, anyhow::Error> {
+                    let unix: i64 = r
+                        .try_get(i)
+                        .context("Failed to deserialize the retrieved session metadata")?;
+                    Timestamp::from_second(unix)
+                        .context("Failed to parse the retrieved session metadata")
+                };
+                let id: String = r
+                    .try_get(0)
+                    .context("Failed to deserialize the retrieved session id")?;
+                let created_at = r
+                    .try_get::<Option<i64>, _>(5)
+                    .context("Failed to deserialize the retrieved session metadata")?
+                    .map(Timestamp::from_second)
+                    .transpose()
+                    .context("Failed to parse the retrieved session metadata")?;
+                Ok(SessionMetadata {
+                    id: SessionId::from_uuid(
+                        id.parse()
+                            .context("Failed to parse the retrieved session id")?,
+                    ),
+                    principal_id: principal_id.to_owned(),
+                    created_at,
+                    associated_at: timestamp(1)?,
+                    last_seen_at: timestamp(2)?,
+                    expires_at: timestamp(3)?,
+                    user_agent: r
+                        .try_get(4)
+                        .context("Failed to deserialize the retrieved user agent")?,
+                })
+            })
+            .collect::<Result<_, anyhow::Error>>()
+            .map_err(ListByPrincipalError::Other)
+    }
+
+    /// Delete all the sessions associated with the given principal.
+    #[tracing::instrument(name = "Delete server-side session records for a principal", level = tracing::Level::INFO, skip_all)]
+    async fn delete_by_principal(
+        &self,
+        principal_id: &str,
+    ) -> Result<usize, DeleteByPrincipalError> {
+        let query = sqlx::query(
+            "DELETE FROM sessions \
+            WHERE id IN (SELECT session_id FROM session_principals WHERE principal_id = ?)",
+        )
+        .bind(principal_id);
+        let r = query
+            .execute(&self.0)
+            .await
+            .map_err(|e| DeleteByPrincipalError::Other(e.into()))?;
+        Ok(r.rows_affected().try_into().unwrap_or(usize::MAX))
+    }
+}
+
+/// JSON-encoded states are stored in the `state` column,
+/// binary-encoded states in the `encoded_state` column.
+fn state_columns(state: EncodedState) -> (Option<String>, Option<Vec<u8>>) {
+    match state {
+        EncodedState::Json(s) => (Some(s), None),
+        EncodedState::Binary(b) => (None, Some(b)),
+    }
 }
 
 fn as_duplicated_id_error(e: &sqlx::Error, id: &SessionId) -> Result<(), DuplicateIdError> {
@@ -5,11 +5,13 @@ use pavex::time::Timestamp;
 use pavex_session::SessionStore;
 use pavex_session::{
     SessionId,
+    codec::{EncodedState, SessionCodec},
     store::{
-        SessionRecord, SessionRecordRef, SessionStorageBackend,
+        SessionMetadata, SessionRecord, SessionRecordRef, SessionStorageBackend,
         errors::{
-            ChangeIdError, CreateError, DeleteError, DeleteExpiredError, DuplicateIdError,
-            LoadError, UnknownIdError, UpdateError, UpdateTtlError,
+            AssociatePrincipalError, ChangeIdError, CreateError, DeleteByPrincipalError,
+            DeleteError, DeleteExpiredError, DuplicateIdError, ListByPrincipalError, LoadError,
+            UnknownIdError, UpdateError, UpdateTtlError, VersionConflictError,
         },
     },
 };
@@ -31,6 +33,19 @@ use std::num::NonZeroUsize;
 /// Alternatively, you can use [`migration_query`](Self::migration_query)
 /// to get the SQL query that creates the table and index in order to run it yourself
 /// (e.g. as part of your database migration scripts).
+///
+/// The association between sessions and principals is stored in a separate table,
+/// `session_principals`. It references the sessions table, so that it follows
+/// the session record when its id changes or when it is deleted.
+///
+/// Each session record has a `version` column, incremented every time its state is
+/// updated, to detect concurrent updates to the same session.
+/// The `created_at` and `last_seen_at` columns are used to enforce
+/// [session timeouts](pavex_session::SessionTimeout).
+///
+/// The session state is stored in the `state` column, as `JSONB`, when using the
+/// default [JSON codec](pavex_session::codec::SessionCodec::Json).
+/// It's stored in the `encoded_state` column, as `BYTEA`, when using a binary codec.
 pub struct PostgresSessionStore(sqlx::PgPool);
 
 #[methods]
@@ -70,9 +85,24 @@ impl PostgresSessionStore {
 CREATE TABLE IF NOT EXISTS sessions (
     id UUID PRIMARY KEY,
     deadline TIMESTAMPTZ NOT NULL,
-    state JSONB NOT NULL
+    state JSONB,
+    version BIGINT NOT NULL DEFAULT 0,
+    encoded_state BYTEA,
+    created_at TIMESTAMPTZ,
+    last_seen_at TIMESTAMPTZ
 );
 
+-- Add the version column to sessions tables created by older versions of this store
+ALTER TABLE sessions ADD COLUMN IF NOT EXISTS version BIGINT NOT NULL DEFAULT 0;
+
+-- Allow sessions tables created by older versions of this store to hold binary-encoded states
+ALTER TABLE sessions ADD COLUMN IF NOT EXISTS encoded_state BYTEA;
+ALTER TABLE sessions ALTER COLUMN state DROP NOT NULL;
+
+-- Add the timestamp columns to sessions tables created by older versions of this store
+ALTER TABLE sessions ADD COLUMN IF NOT EXISTS created_at TIMESTAMPTZ;
+ALTER TABLE sessions ADD COLUMN IF NOT EXISTS last_seen_at TIMESTAMPTZ;
+
 -- Create the index on the deadline column if it doesn’t exist
 DO $$
 BEGIN
@@ -84,7 +114,18 @@ BEGIN
     ) THEN
         CREATE INDEX idx_sessions_deadline ON sessions(deadline);
     END IF;
-END $$;"
+END $$;
+
+-- Create the table associating sessions with principals if it doesn’t exist
+CREATE TABLE IF NOT EXISTS session_principals (
+    session_id UUID PRIMARY KEY REFERENCES sessions(id) ON DELETE CASCADE ON UPDATE CASCADE,
+    principal_id TEXT NOT NULL,
+    associated_at TIMESTAMPTZ NOT NULL,
+    user_agent TEXT
+);
+
+-- Create the index on the principal_id column if it doesn’t exist
+CREATE INDEX IF NOT EXISTS idx_session_principals_principal_id ON session_principals(principal_id);"
     }
 
     /// Create the sessions table and index in the database.
@@ -100,6 +141,35 @@ END $$;"
         self.0.execute(Self::migration_query()).await?;
         Ok(())
     }
+
+    /// Set the timestamps of a record created before the `created_at` and `last_seen_at`
+    /// columns were introduced to the current time, so that the configured timeouts can be
+    /// enforced from now on.
+    async fn backfill_timestamps(
+        &self,
+        session_id: &SessionId,
+        record: &mut SessionRecord,
+    ) -> Result<(), LoadError> {
+        if record.created_at.is_some() && record.last_seen_at.is_some() {
+            return Ok(());
+        }
+        // Truncate to whole seconds, so that the returned record matches the stored one.
+        let now = Timestamp::now();
+        let now = Timestamp::from_second(now.as_second()).unwrap_or(now);
+        sqlx::query(
+            "UPDATE sessions \
+            SET created_at = COALESCE(created_at, $1), last_seen_at = COALESCE(last_seen_at, $1) \
+            WHERE id = $2",
+        )
+        .bind(now.to_sqlx())
+        .bind(session_id.inner())
+        .execute(&self.0)
+        .await
+        .map_err(|e| LoadError::Other(e.into()))?;
+        record.created_at.get_or_insert(now);
+        record.last_seen_at.get_or_insert(now);
+        Ok(())
+    }
 }
 
 #[async_trait::async_trait]
@@ -111,18 +181,28 @@ impl SessionStorageBackend for PostgresSessionStore {
         id: &SessionId,
         record: SessionRecordRef<'_>,
     ) -> Result<(), CreateError> {
-        let deadline = Timestamp::now() + record.ttl;
-        let state = serde_json::to_value(record.state)?;
+        let now = Timestamp::now();
+        let deadline = now + record.ttl;
+        let (state, encoded_state) = state_columns(record.encode_state()?);
+        // An expired record may be overwritten: its association with a principal,
+        // if any, must go away with it.
         let query = sqlx::query(
-            "INSERT INTO sessions (id, deadline, state) \
-            VALUES ($1, $2, $3) \
-            ON CONFLICT (id) DO UPDATE \
-            SET deadline = EXCLUDED.deadline, state = EXCLUDED.state \
-            WHERE sessions.deadline < (now() AT TIME ZONE 'UTC')",
+            "WITH upserted AS ( \
+                INSERT INTO sessions (id, deadline, state, encoded_state, created_at, last_seen_at) \
+                VALUES ($1, $2, $3::jsonb, $4, $5, $5) \
+                ON CONFLICT (id) DO UPDATE \
+                SET deadline = EXCLUDED.deadline, state = EXCLUDED.state, encoded_state = EXCLUDED.encoded_state, \
+                    created_at = EXCLUDED.created_at, last_seen_at = EXCLUDED.last_seen_at \
+                WHERE sessions.deadline < (now() AT TIME ZONE 'UTC') \
+                RETURNING id \
+            ) \
+            DELETE FROM session_principals WHERE session_id IN (SELECT id FROM upserted)",
         )
         .bind(id.inner())
         .bind(deadline.to_sqlx())
-        .bind(state);
+        .bind(state)
+        .bind(encoded_state)
+        .bind(now.to_sqlx());
 
         match query.execute(&self.0).await {
             // All good, we created the session record.
@@ -148,14 +228,15 @@ impl SessionStorageBackend for PostgresSessionStore {
         record: SessionRecordRef<'_>,
     ) -> Result<(), UpdateError> {
         let new_deadline = Timestamp::now() + record.ttl;
-        let new_state = serde_json::to_value(record.state)?;
+        let (new_state, new_encoded_state) = state_columns(record.encode_state()?);
         let query = sqlx::query(
             "UPDATE sessions \
-            SET deadline = $1, state = $2 \
-            WHERE id = $3 AND deadline > (now() AT TIME ZONE 'UTC')",
+            SET deadline = $1, state = $2::jsonb, encoded_state = $3, version = version + 1, last_seen_at = now() \
+            WHERE id = $4 AND deadline > (now() AT TIME ZONE 'UTC')",
         )
         .bind(new_deadline.to_sqlx())
         .bind(new_state)
+        .bind(new_encoded_state)
         .bind(id.inner());
 
         match query.execute(&self.0).await {
@@ -164,6 +245,51 @@ impl SessionStorageBackend for PostgresSessionStore {
         }
     }
 
+    /// Update the state of an existing session in the store, if its
+    /// version matches the expected one.
+    #[tracing::instrument(name = "Conditionally update server-side session record", level = tracing::Level::INFO, skip_all)]
+    async fn update_if_version(
+        &self,
+        id: &SessionId,
+        record: SessionRecordRef<'_>,
+        expected_version: u64,
+    ) -> Result<u64, UpdateError> {
+        let new_deadline = Timestamp::now() + record.ttl;
+        let (new_state, new_encoded_state) = state_columns(record.encode_state()?);
+        let version = i64::try_from(expected_version).map_err(|e| UpdateError::Other(e.into()))?;
+        let query = sqlx::query(
+            "UPDATE sessions \
+            SET deadline = $1, state = $2::jsonb, encoded_state = $3, version = version + 1, last_seen_at = now() \
+            WHERE id = $4 AND deadline > (now() AT TIME ZONE 'UTC') AND version = $5",
+        )
+        .bind(new_deadline.to_sqlx())
+        .bind(new_state)
+        .bind(new_encoded_state)
+        .bind(id.inner())
+        .bind(version);
+
+        let r = query
+            .execute(&self.0)
+            .await
+            .map_err(|e| UpdateError::Other(e.into()))?;
+        if r.rows_affected() > 0 {
+            return Ok(expected_version + 1);
+        }
+        // Nothing was updated: either the record is gone or its version changed.
+        let exists: bool = sqlx::query_scalar(
+            "SELECT EXISTS(SELECT 1 FROM sessions WHERE id = $1 AND deadline > (now() AT TIME ZONE 'UTC'))",
+        )
+        .bind(id.inner())
+        .fetch_one(&self.0)
+        .await
+        .map_err(|e| UpdateError::Other(e.into()))?;
+        if exists {
+            Err(VersionConflictError { id: *id }.into())
+        } else {
+            Err(UnknownIdError { id: *id }.into())
+        }
+    }
+
     /// Update the TTL of an existing session record in the store.
     ///
     /// It leaves the session state unchanged.
@@ -176,7 +302,7 @@ impl SessionStorageBackend for PostgresSessionStore {
         let new_deadline = Timestamp::now() + ttl;
         let query = sqlx::query(
             "UPDATE sessions \
-            SET deadline = $1 \
+            SET deadline = $1, last_seen_at = now() \
             WHERE id = $2 AND deadline > (now() AT TIME ZONE 'UTC')",
         )
         .bind(new_deadline.to_sqlx())
@@ -195,7 +321,7 @@ impl SessionStorageBackend for PostgresSessionStore {
     #[tracing::instrument(name = "Load server-side session record", level = tracing::Level::INFO, skip_all)]
     async fn load(&self, session_id: &SessionId) -> Result<Option<SessionRecord>, LoadError> {
         let row = sqlx::query(
-            "SELECT deadline, state \
+            "SELECT deadline, state, version, encoded_state, created_at, last_seen_at \
             FROM sessions \
             WHERE id = $1 AND deadline > (now() AT TIME ZONE 'UTC')",
         )
@@ -203,29 +329,58 @@ impl SessionStorageBackend for PostgresSessionStore {
         .fetch_optional(&self.0)
         .await
         .map_err(|e| LoadError::Other(e.into()))?;
-        row.map(|r| {
-            use anyhow::Context as _;
-            use sqlx::Row as _;
-
-            let deadline = r
-                .try_get::<jiff_sqlx::Timestamp, _>(0)
-                .context("Failed to deserialize the retrieved session deadline")
-                .map_err(LoadError::DeserializationError)?
-                .to_jiff();
-            let state: serde_json::Value = r
-                .try_get(1)
+        let mut record = row
+            .map(|r| {
+                use anyhow::Context as _;
+                use sqlx::Row as _;
+
+                let deadline = r
+                    .try_get::<jiff_sqlx::Timestamp, _>(0)
+                    .context("Failed to deserialize the retrieved session deadline")
+                    .map_err(LoadError::DeserializationError)?
+                    .to_jiff();
+                let encoded_state: Option<Vec<u8>> = r
+                    .try_get(3)
+                    .context("Failed to deserialize the retrieved session state")
+                    .map_err(LoadError::DeserializationError)?;
+                let state = match encoded_state {
+                    Some(encoded_state) => {
+                        SessionCodec::decode(&encoded_state).map_err(anyhow::Error::from)
+                    }
+                    None => r
+                        .try_get::<serde_json::Value, _>(1)
+                        .map_err(anyhow::Error::from)
+                        .and_then(|state| {
+                            serde_json::from_value(state).map_err(anyhow::Error::from)
+                        }),
+                }
                 .context("Failed to deserialize the retrieved session state")
                 .map_err(LoadError::DeserializationError)?;
-            let ttl = deadline - Timestamp::now();
-            Ok(SessionRecord {
-                // This conversion only fails if the duration is negative, which should not happen
-                ttl: ttl.try_into().unwrap_or(std::time::Duration::ZERO),
-                state: serde_json::from_value(state)
-                    .context("Failed to deserialize the retrieved session state")
-                    .map_err(LoadError::DeserializationError)?,
+                let version: i64 = r
+                    .try_get(2)
+                    .context("Failed to deserialize the retrieved session version")
+                    .map_err(LoadError::DeserializationError)?;
+                let [created_at, last_seen_at] = [4, 5].map(|i| {
+                    r.try_get::<Option<jiff_sqlx::Timestamp>, _>(i)
+                        .context("Failed to deserialize the retrieved session timestamps")
+                        .map_err(LoadError::DeserializationError)
+                        .map(|ts| ts.map(|ts| ts.to_jiff()))
+                });
+                let ttl = deadline - Timestamp::now();
+                Ok(SessionRecord {
+                    // This conversion only fails if the duration is negative, which should not happen
+                    ttl: ttl.try_into().unwrap_or(std::time::Duration::ZERO),
+                    state,
+                    version: u64::try_from(version).ok(),
+                    created_at: created_at?,
+                    last_seen_at: last_seen_at?,
+                })
             })
-        })
-        .transpose()
+            .transpose()?;
+        if let Some(record) = &mut record {
+            self.backfill_timestamps(session_id, record).await?;
+        }
+        Ok(record)
     }
 
     /// Deletes a session record from the store using the provided ID.
@@ -329,6 +484,107 @@ impl SessionStorageBackend for PostgresSessionStore {
         })?;
         Ok(r.rows_affected().try_into().unwrap_or(usize::MAX))
     }
+
+    /// Associate an existing session record with a principal.
+    #[tracing::instrument(name = "Associate server-side session record with a principal", level = tracing::Level::INFO, skip_all)]
+    async fn associate_principal(
+        &self,
+        id: &SessionId,
+        principal_id: &str,
+        user_agent: Option<&str>,
+    ) -> Result<(), AssociatePrincipalError> {
+        let query = sqlx::query(
+            "INSERT INTO session_principals (session_id, principal_id, associated_at, user_agent) \
+            SELECT id, $1, now(), $2 \
+            FROM sessions \
+            WHERE id = $3 AND deadline > (now() AT TIME ZONE 'UTC') \
+            ON CONFLICT (session_id) DO UPDATE \
+            SET principal_id = EXCLUDED.principal_id, associated_at = EXCLUDED.associated_at, \
+            user_agent = EXCLUDED.user_agent",
+        )
+        .bind(principal_id)
+        .bind(user_agent)
+        .bind(id.inner());
+        match query.execute(&self.0).await {
+            Ok(r) => as_unknown_id_error(&r, id).map_err(Into::into),
+            Err(e) => Err(AssociatePrincipalError::Other(e.into())),
+        }
+    }
+
+    /// List all the active sessions associated with the given principal.
+    #[tracing::instrument(name = "List server-side session records for a principal", level = tracing::Level::INFO, skip_all)]
+    async fn list_by_principal(
+        &self,
+        principal_id: &str,
+    ) -> Result<Vec<SessionMetadata>, ListByPrincipalError> {
+        use anyhow::Context as _;
+        use sqlx::Row as _;
+
+        let rows = sqlx::query(
+            "SELECT s.id, p.associated_at, COALESCE(s.last_seen_at, p.associated_at), s.deadline, p.user_agent, s.created_at \
+            FROM session_principals p \
+            JOIN sessions s ON s.id = p.session_id \
+            WHERE p.principal_id = $1 AND s.deadline > (now() AT TIME ZONE 'UTC')",
+        )
+        .bind(principal_id)
+        .fetch_all(&self.0)
+        .await
+        .map_err(|e| ListByPrincipalError::Other(e.into()))?;
+        rows.into_iter()
+            .map(|r| {
+                let timestamp = |i: usize| {
+                    r.try_get::<jiff_sqlx::Timestamp, _>(i)
+                        .map(|t| t.to_jiff())
+                        .context("Failed to deserialize the retrieved session metadata")
+                };
+                Ok(SessionMetadata {
+                    id: SessionId::from_uuid(
+                        r.try_get(0)
+                            .context("Failed to deserialize the retrieved session id")?,
+                    ),
+                    principal_id: principal_id.to_owned(),
+                    created_at: r
+                        .try_get::<Option<jiff_sqlx::Timestamp>, _>(5)
+                        .context("Failed to deserialize the retrieved session metadata")?
+                        .map(|t| t.to_jiff()),
+                    associated_at: timestamp(1)?,
+                    last_seen_at: timestamp(2)?,
+                    expires_at: timestamp(3)?,
+                    user_agent: r
+                        .try_get(4)
+                        .context("Failed to deserialize the retrieved user agent")?,
+                })
+            })
+            .collect::<Result<_, anyhow::Error>>()
+            .map_err(ListByPrincipalError::Other)
+    }
+
+    /// Delete all the sessions associated with the given principal.
+    #[tracing::instrument(name = "Delete server-side session records for a principal", level = tracing::Level::INFO, skip_all)]
+    async fn delete_by_principal(
+        &self,
+        principal_id: &str,
+    ) -> Result<usize, DeleteByPrincipalError> {
+        let query = sqlx::query(
+            "DELETE FROM sessions \
+            WHERE id IN (SELECT session_id FROM session_principals WHERE principal_id = $1)",
+        )
+        .bind(principal_id);
+        let r = query
+            .execute(&self.0)
+            .await
+            .map_err(|e| DeleteByPrincipalError::Other(e.into()))?;
+        Ok(r.rows_affected().try_into().unwrap_or(usize::MAX))
+    }
+}
+
+/// JSON-encoded states are stored in the `state` column,
+/// binary-encoded states in the `encoded_state` column.
+fn state_columns(state: EncodedState) -> (Option<String>, Option<Vec<u8>>) {
+    match state {
+        EncodedState::Json(s) => (Some(s), None),
+        EncodedState::Binary(b) => (None, Some(b)),
+    }
 }
 
 fn as_duplicated_id_error(e: &sqlx::Error, id: &SessionId) -> Result<(), DuplicateIdError> {
@@ -5,18 +5,21 @@ use pavex::time::Timestamp;
 use pavex_session::SessionStore;
 use pavex_session::{
     SessionId,
+    codec::{EncodedState, SessionCodec},
     store::{
-        SessionRecord, SessionRecordRef, SessionStorageBackend,
+        SessionMetadata, SessionRecord, SessionRecordRef, SessionStorageBackend,
         errors::{
-            ChangeIdError, CreateError, DeleteError, DeleteExpiredError, DuplicateIdError,
-            LoadError, UnknownIdError, UpdateError, UpdateTtlError,
+            AssociatePrincipalError, ChangeIdError, CreateError, DeleteByPrincipalError,
+            DeleteError, DeleteExpiredError, DuplicateIdError, ListByPrincipalError, LoadError,
+            UnknownIdError, UpdateError, UpdateTtlError, VersionConflictError,
         },
     },
 };
 use sqlx::{
-    SqlitePool,
+    Sqlite, SqlitePool,
     error::DatabaseError,
-    sqlite::{SqliteError, SqliteQueryResult},
+    query::Query,
+    sqlite::{SqliteArguments, SqliteError, SqliteQueryResult},
 };
 use std::num::NonZeroUsize;
 
@@ -34,11 +37,23 @@ use std::num::NonZeroUsize;
 /// to get the SQL query that creates the table and index in order to run it yourself
 /// (e.g. as part of your database migration scripts).
 ///
+/// The association between sessions and principals is stored in a separate table,
+/// `session_principals`. It is kept in sync with the sessions table using triggers,
+/// rather than foreign keys, since foreign key enforcement is opt-in in SQLite.
+///
+/// Each session record has a `version` column, incremented every time its state is
+/// updated, to detect concurrent updates to the same session.
+/// The `created_at` and `last_seen_at` columns are used to enforce
+/// [session timeouts](pavex_session::SessionTimeout).
+///
 /// # JSONB Support
 ///
 /// This implementation uses SQLite's JSONB format for storing session state,
 /// which provides better performance (5-10% smaller size, ~50% faster processing)
 /// compared to plain text JSON. JSONB is supported in SQLite 3.45.0 and later.
+///
+/// When using a binary [codec](pavex_session::codec::SessionCodec), the session state
+/// is stored as a `BLOB` in the same column.
 pub struct SqliteSessionStore(sqlx::SqlitePool);
 
 #[methods]
@@ -68,6 +83,20 @@ impl SqliteSessionStore {
     /// without causing any issues. If the table and index already exist, the query
     /// does nothing.
     ///
+    /// # Upgrading
+    ///
+    /// SQLite can't add a column to a table only if it's missing.
+    /// If your `sessions` table was created by an older version of this store, you must
+    /// add the missing columns yourself:
+    ///
+    /// ```sql
+    /// ALTER TABLE sessions ADD COLUMN version INTEGER NOT NULL DEFAULT 0;
+    /// ALTER TABLE sessions ADD COLUMN created_at INTEGER;
+    /// ALTER TABLE sessions ADD COLUMN last_seen_at INTEGER;
+    /// ```
+    ///
+    /// [`migrate`](Self::migrate) takes care of it for you.
+    ///
     /// # Alternatives
     ///
     /// You can use this method to add the query to your database migration scripts.
@@ -78,17 +107,46 @@ impl SqliteSessionStore {
 CREATE TABLE IF NOT EXISTS sessions (
     id TEXT PRIMARY KEY,
     deadline INTEGER NOT NULL,
-    state JSONB NOT NULL
+    state JSONB NOT NULL,
+    version INTEGER NOT NULL DEFAULT 0,
+    created_at INTEGER,
+    last_seen_at INTEGER
 );
 
 -- Create the index on the deadline column if it doesn't exist
-CREATE INDEX IF NOT EXISTS idx_sessions_deadline ON sessions(deadline);"
+CREATE INDEX IF NOT EXISTS idx_sessions_deadline ON sessions(deadline);
+
+-- Create the table associating sessions with principals if it doesn't exist
+CREATE TABLE IF NOT EXISTS session_principals (
+    session_id TEXT PRIMARY KEY,
+    principal_id TEXT NOT NULL,
+    associated_at INTEGER NOT NULL,
+    user_agent TEXT
+);
+
+-- Create the index on the principal_id column if it doesn't exist
+CREATE INDEX IF NOT EXISTS idx_session_principals_principal_id ON session_principals(principal_id);
+
+-- Keep the associations in sync with the session records
+CREATE TRIGGER IF NOT EXISTS trg_sessions_delete_principal
+AFTER DELETE ON sessions
+BEGIN
+    DELETE FROM session_principals WHERE session_id = OLD.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_sessions_change_id_principal
+AFTER UPDATE OF id ON sessions
+BEGIN
+    UPDATE session_principals SET session_id = NEW.id WHERE session_id = OLD.id;
+END;"
     }
 
     /// Create the sessions table and index in the database.
     ///
     /// This method is idempotent, meaning it can be called multiple times without
     /// causing any issues. If the table and index already exist, this method does nothing.
+    /// It adds the `version`, `created_at` and `last_seen_at` columns to `sessions` tables
+    /// created by older versions of this store.
     ///
     /// If you prefer to run the query yourself, rely on [`migration_query`](Self::migration_query)
     /// to get the SQL that's being executed.
@@ -96,6 +154,54 @@ CREATE INDEX IF NOT EXISTS idx_sessions_deadline ON sessions(deadline);"
         use sqlx::Executor as _;
 
         self.0.execute(Self::migration_query()).await?;
+        for (column, definition) in [
+            ("version", "INTEGER NOT NULL DEFAULT 0"),
+            ("created_at", "INTEGER"),
+            ("last_seen_at", "INTEGER"),
+        ] {
+            let has_column: bool = sqlx::query_scalar(
+                "SELECT EXISTS(SELECT 1 FROM pragma_table_info('sessions') WHERE name = ?)",
+            )
+            .bind(column)
+            .fetch_one(&self.0)
+            .await?;
+            if !has_column {
+                self.0
+                    .execute(
+                        format!("ALTER TABLE sessions ADD COLUMN {column} {definition}").as_str(),
+                    )
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Set the timestamps of a record created before the `created_at` and `last_seen_at`
+    /// columns were introduced to the current time, so that the configured timeouts can be
+    /// enforced from now on.
+    async fn backfill_timestamps(
+        &self,
+        session_id: &SessionId,
+        record: &mut SessionRecord,
+    ) -> Result<(), LoadError> {
+        if record.created_at.is_some() && record.last_seen_at.is_some() {
+            return Ok(());
+        }
+        // Truncate to whole seconds, so that the returned record matches the stored one.
+        let now = Timestamp::now();
+        let now = Timestamp::from_second(now.as_second()).unwrap_or(now);
+        sqlx::query(
+            "UPDATE sessions \
+            SET created_at = COALESCE(created_at, ?), last_seen_at = COALESCE(last_seen_at, ?) \
+            WHERE id = ?",
+        )
+        .bind(now.as_second())
+        .bind(session_id.inner().to_string())
+        .execute(&self.0)
+        .await
+        .map_err(|e| LoadError::Other(e.into()))?;
+        record.created_at.get_or_insert(now);
+        record.last_seen_at.get_or_insert(now);
         Ok(())
     }
 }
@@ -109,32 +215,53 @@ impl SessionStorageBackend for SqliteSessionStore {
         id: &SessionId,
         record: SessionRecordRef<'_>,
     ) -> Result<(), CreateError> {
-        let deadline = Timestamp::now() + record.ttl;
+        let now = Timestamp::now();
+        let deadline = now + record.ttl;
         let deadline_unix = deadline.as_second();
-        let state = serde_json::to_value(record.state)?;
+        let state = record.encode_state()?;
         let query = sqlx::query(
-            "INSERT INTO sessions (id, deadline, state) \
-            VALUES (?, ?, ?) \
+            "INSERT INTO sessions (id, deadline, state, created_at, last_seen_at) \
+            VALUES (?, ?, ?, ?, ?) \
             ON CONFLICT(id) DO UPDATE \
-            SET deadline = excluded.deadline, state = excluded.state \
+            SET deadline = excluded.deadline, state = excluded.state, \
+                created_at = excluded.created_at, last_seen_at = excluded.last_seen_at \
             WHERE sessions.deadline < unixepoch()",
         )
         .bind(id.inner().to_string())
-        .bind(deadline_unix)
-        .bind(state);
+        .bind(deadline_unix);
+        let query = bind_state(query, state)
+            .bind(now.as_second())
+            .bind(now.as_second());
 
-        match query.execute(&self.0).await {
-            // All good, we created the session record.
-            Ok(_) => Ok(()),
+        let mut transaction = self
+            .0
+            .begin()
+            .await
+            .map_err(|e| CreateError::Other(e.into()))?;
+        match query.execute(&mut *transaction).await {
+            // An expired record may have been overwritten: its association with
+            // a principal, if any, must go away with it.
+            Ok(r) if r.rows_affected() > 0 => {
+                sqlx::query("DELETE FROM session_principals WHERE session_id = ?")
+                    .bind(id.inner().to_string())
+                    .execute(&mut *transaction)
+                    .await
+                    .map_err(|e| CreateError::Other(e.into()))?;
+            }
+            Ok(_) => {}
             Err(e) => {
                 // Return the specialized error variant if the ID is already in use
-                if let Err(e) = as_duplicated_id_error(&e, id) {
+                return if let Err(e) = as_duplicated_id_error(&e, id) {
                     Err(e.into())
                 } else {
                     Err(CreateError::Other(e.into()))
-                }
+                };
             }
         }
+        transaction
+            .commit()
+            .await
+            .map_err(|e| CreateError::Other(e.into()))
     }
 
     /// Update the state of an existing session in the store.
@@ -148,15 +275,14 @@ impl SessionStorageBackend for SqliteSessionStore {
     ) -> Result<(), UpdateError> {
         let new_deadline = Timestamp::now() + record.ttl;
         let new_deadline_unix = new_deadline.as_second();
-        let new_state = serde_json::to_value(record.state)?;
+        let new_state = record.encode_state()?;
         let query = sqlx::query(
             "UPDATE sessions \
-            SET deadline = ?, state = ? \
+            SET deadline = ?, state = ?, version = version + 1, last_seen_at = unixepoch() \
             WHERE id = ? AND deadline > unixepoch()",
         )
-        .bind(new_deadline_unix)
-        .bind(new_state)
-        .bind(id.inner().to_string());
+        .bind(new_deadline_unix);
+        let query = bind_state(query, new_state).bind(id.inner().to_string());
 
         match query.execute(&self.0).await {
             Ok(r) => as_unknown_id_error(&r, id).map_err(Into::into),
@@ -164,6 +290,51 @@ impl SessionStorageBackend for SqliteSessionStore {
         }
     }
 
+    /// Update the state of an existing session in the store, if its
+    /// version matches the expected one.
+    #[tracing::instrument(name = "Conditionally update server-side session record", level = tracing::Level::INFO, skip_all)]
+    async fn update_if_version(
+        &self,
+        id: &SessionId,
+        record: SessionRecordRef<'_>,
+        expected_version: u64,
+    ) -> Result<u64, UpdateError> {
+        let new_deadline = Timestamp::now() + record.ttl;
+        let new_deadline_unix = new_deadline.as_second();
+        let new_state = record.encode_state()?;
+        let version = i64::try_from(expected_version).map_err(|e| UpdateError::Other(e.into()))?;
+        let query = sqlx::query(
+            "UPDATE sessions \
+            SET deadline = ?, state = ?, version = version + 1, last_seen_at = unixepoch() \
+            WHERE id = ? AND deadline > unixepoch() AND version = ?",
+        )
+        .bind(new_deadline_unix);
+        let query = bind_state(query, new_state)
+            .bind(id.inner().to_string())
+            .bind(version);
+
+        let r = query
+            .execute(&self.0)
+            .await
+            .map_err(|e| UpdateError::Other(e.into()))?;
+        if r.rows_affected() > 0 {
+            return Ok(expected_version + 1);
+        }
+        // Nothing was updated: either the record is gone or its version changed.
+        let exists: bool = sqlx::query_scalar(
+            "SELECT EXISTS(SELECT 1 FROM sessions WHERE id = ? AND deadline > unixepoch())",
+        )
+        .bind(id.inner().to_string())
+        .fetch_one(&self.0)
+        .await
+        .map_err(|e| UpdateError::Other(e.into()))?;
+        if exists {
+            Err(VersionConflictError { id: *id }.into())
+        } else {
+            Err(UnknownIdError { id: *id }.into())
+        }
+    }
+
     /// Update the TTL of an existing session record in the store.
     ///
     /// It leaves the session state unchanged.
@@ -177,7 +348,7 @@ impl SessionStorageBackend for SqliteSessionStore {
         let new_deadline_unix = new_deadline.as_second();
         let query = sqlx::query(
             "UPDATE sessions \
-            SET deadline = ? \
+            SET deadline = ?, last_seen_at = unixepoch() \
             WHERE id = ? AND deadline > unixepoch()",
         )
         .bind(new_deadline_unix)
@@ -196,7 +367,7 @@ impl SessionStorageBackend for SqliteSessionStore {
     #[tracing::instrument(name = "Load server-side session record", level = tracing::Level::INFO, skip_all)]
     async fn load(&self, session_id: &SessionId) -> Result<Option<SessionRecord>, LoadError> {
         let row = sqlx::query(
-            "SELECT deadline, state \
+            "SELECT deadline, state, version, created_at, last_seen_at \
             FROM sessions \
             WHERE id = ? AND deadline > unixepoch()",
         )
@@ -204,31 +375,50 @@ impl SessionStorageBackend for SqliteSessionStore {
         .fetch_optional(&self.0)
         .await
         .map_err(|e| LoadError::Other(e.into()))?;
-        row.map(|r| {
-            use anyhow::Context as _;
-            use sqlx::Row as _;
-
-            let deadline_unix: i64 = r
-                .try_get(0)
-                .context("Failed to deserialize the retrieved session deadline")
-                .map_err(LoadError::DeserializationError)?;
-            let deadline = Timestamp::from_second(deadline_unix)
-                .context("Failed to parse the retrieved session deadline")
-                .map_err(LoadError::DeserializationError)?;
-            let state: serde_json::Value = r
-                .try_get(1)
-                .context("Failed to deserialize the retrieved session state")
-                .map_err(LoadError::DeserializationError)?;
-            let ttl = deadline - Timestamp::now();
-            Ok(SessionRecord {
-                // This conversion only fails if the duration is negative, which should not happen
-                ttl: ttl.try_into().unwrap_or(std::time::Duration::ZERO),
-                state: serde_json::from_value(state)
+        let mut record = row
+            .map(|r| {
+                use anyhow::Context as _;
+                use sqlx::Row as _;
+
+                let deadline_unix: i64 = r
+                    .try_get(0)
+                    .context("Failed to deserialize the retrieved session deadline")
+                    .map_err(LoadError::DeserializationError)?;
+                let deadline = Timestamp::from_second(deadline_unix)
+                    .context("Failed to parse the retrieved session deadline")
+                    .map_err(LoadError::DeserializationError)?;
+                // JSON-encoded states are stored as text, which can be read as bytes.
+                let state: Vec<u8> = r
+                    .try_get(1)
                     .context("Failed to deserialize the retrieved session state")
-                    .map_err(LoadError::DeserializationError)?,
+                    .map_err(LoadError::DeserializationError)?;
+                let version: i64 = r
+                    .try_get(2)
+                    .context("Failed to deserialize the retrieved session version")
+                    .map_err(LoadError::DeserializationError)?;
+                let [created_at, last_seen_at] = [3, 4].map(|i| {
+                    r.try_get::<Option<i64>, _>(i)
+                        .context("Failed to deserialize the retrieved session timestamps")
+                        .map_err(LoadError::DeserializationError)
+                        .map(|ts| ts.and_then(|ts| Timestamp::from_second(ts).ok()))
+                });
+                let ttl = deadline - Timestamp::now();
+                Ok(SessionRecord {
+                    // This conversion only fails if the duration is negative, which should not happen
+                    ttl: ttl.try_into().unwrap_or(std::time::Duration::ZERO),
+                    state: SessionCodec::decode(&state)
+                        .context("Failed to deserialize the retrieved session state")
+                        .map_err(LoadError::DeserializationError)?,
+                    version: u64::try_from(version).ok(),
+                    created_at: created_at?,
+                    last_seen_at: last_seen_at?,
+                })
             })
-        })
-        .transpose()
+            .transpose()?;
+        if let Some(record) = &mut record {
+            self.backfill_timestamps(session_id, record).await?;
+        }
+        Ok(record)
     }
 
     /// Deletes a session record from the store using the provided ID.
@@ -332,6 +522,114 @@ impl SessionStorageBackend for SqliteSessionStore {
         })?;
         Ok(r.rows_affected().try_into().unwrap_or(usize::MAX))
     }
+
+    /// Associate an existing session record with a principal.
+    #[tracing::instrument(name = "Associate server-side session record with a principal", level = tracing::Level::INFO, skip_all)]
+    async fn associate_principal(
+        &self,
+        id: &SessionId,
+        principal_id: &str,
+        user_agent: Option<&str>,
+    ) -> Result<(), AssociatePrincipalError> {
+        let query = sqlx::query(
+            "INSERT OR REPLACE INTO session_principals (session_id, principal_id, associated_at, user_agent) \
+            SELECT id, ?, unixepoch(), ? \
+            FROM sessions \
+            WHERE id = ? AND deadline > unixepoch()",
+        )
+        .bind(principal_id)
+        .bind(user_agent)
+        .bind(id.inner().to_string());
+        match query.execute(&self.0).await {
+            Ok(r) => as_unknown_id_error(&r, id).map_err(Into::into),
+            Err(e) => Err(AssociatePrincipalError::Other(e.into())),
+        }
+    }
+
+    /// List all the active sessions associated with the given principal.
+    #[tracing::instrument(name = "List server-side session records for a principal", level = tracing::Level::INFO, skip_all)]
+    async fn list_by_principal(
+        &self,
+        principal_id: &str,
+    ) -> Result<Vec<SessionMetadata>, ListByPrincipalError> {
+        use anyhow::Context as _;
+        use sqlx::Row as _;
+
+        let rows = sqlx::query(
+            "SELECT s.id, p.associated_at, COALESCE(s.last_seen_at, p.associated_at), s.deadline, p.user_agent, s.created_at \
+            FROM session_principals p \
+            JOIN sessions s ON s.id = p.session_id \
+            WHERE p.principal_id = ? AND s.deadline > unixepoch()",
+        )
+        .bind(principal_id)
+        .fetch_all(&self.0)
+        .await
+        .map_err(|e| ListByPrincipalError::Other(e.into()))?;
+        rows.into_iter()
+            .map(|r| {
+                let timestamp = |i: usize| -> Result<Timestamp, anyhow::Error> {
+                    let unix: i64 = r
+                        .try_get(i)
+                        .context("Failed to deserialize the retrieved session metadata")?;
+                    Timestamp::from_second(unix)
+                        .context("Failed to parse the retrieved session metadata")
+                };
+                let id: String = r
+                    .try_get(0)
+                    .context("Failed to deserialize the retrieved session id")?;
+                let created_at = r
+                    .try_get::<Option<i64>, _>(5)
+                    .context("Failed to deserialize the retrieved session metadata")?
+                    .map(Timestamp::from_second)
+                    .transpose()
+                    .context("Failed to parse the retrieved session metadata")?;
+                Ok(SessionMetadata {
+                    id: SessionId::from_uuid(
+                        id.parse()
+                            .context("Failed to parse the retrieved session id")?,
+                    ),
+                    principal_id: principal_id.to_owned(),
+                    created_at,
+                    associated_at: timestamp(1)?,
+                    last_seen_at: timestamp(2)?,
+                    expires_at: timestamp(3)?,
+                    user_agent: r
+                        .try_get(4)
+                        .context("Failed to deserialize the retrieved user agent")?,
+                })
+            })
+            .collect::<Result<_, anyhow::Error>>()
+            .map_err(ListByPrincipalError::Other)
+    }
+
+    /// Delete all the sessions associated with the given principal.
+    #[tracing::instrument(name = "Delete server-side session records for a principal", level = tracing::Level::INFO, skip_all)]
+    async fn delete_by_principal(
+        &self,
+        principal_id: &str,
+    ) -> Result<usize, DeleteByPrincipalError> {
+        let query = sqlx::query(
+            "DELETE FROM sessions \
+            WHERE id IN (SELECT session_id FROM session_principals WHERE principal_id = ?)",
+        )
+        .bind(principal_id);
+        let r = query
+            .execute(&self.0)
+            .await
+            .map_err(|e| DeleteByPrincipalError::Other(e.into()))?;
+        Ok(r.rows_affected().try_into().unwrap_or(usize::MAX))
+    }
+}
+
+/// JSON-encoded states are stored as text, binary-encoded states as blobs.
+fn bind_state<'q>(
+    query: Query<'q, Sqlite, SqliteArguments<'q>>,
+    state: EncodedState,
+) -> Query<'q, Sqlite, SqliteArguments<'q>> {
+    match state {
+        EncodedState::Json(s) => query.bind(s),
+        EncodedState::Binary(b) => query.bind(b),
+    }
 }
 
 fn as_duplicated_id_error(e: &sqlx::Error, id: &SessionId) -> Result<(), DuplicateIdError> {
@@ -1,5 +1,6 @@
-use pavex_session::SessionId;
+use pavex_session::codec::SessionCodec;
 use pavex_session::store::{SessionRecordRef, SessionStorageBackend};
+use pavex_session::{SessionId, Value};
 use pavex_session_sqlx::MySqlSessionStore;
 use serde_json;
 use sqlx::mysql::MySqlPoolOptions;
@@ -24,27 +25,19 @@ async fn create_test_store() -> MySqlSessionStore {
     store
 }
 
-fn create_test_record(
-    _ttl_seconds: u64,
-) -> (SessionId, HashMap<Cow<'static, str>, serde_json::Value>) {
+fn create_test_record(_ttl_seconds: u64) -> (SessionId, HashMap<Cow<'static, str>, Value>) {
     let session_id = SessionId::random();
     let mut state = HashMap::new();
     state.insert(
         Cow::Borrowed("user_id"),
-        serde_json::Value::String("test-user-123".to_string()),
+        Value::String("test-user-123".to_string()),
     );
     state.insert(
         Cow::Borrowed("login_time"),
-        serde_json::Value::String("2024-01-01T00:00:00Z".to_string()),
-    );
-    state.insert(
-        Cow::Borrowed("counter"),
-        serde_json::Value::Number(42.into()),
-    );
-    state.insert(
-        Cow::Borrowed("theme"),
-        serde_json::Value::String("dark".to_string()),
+        Value::String("2024-01-01T00:00:00Z".to_string()),
     );
+    state.insert(Cow::Borrowed("counter"), Value::from(42));
+    state.insert(Cow::Borrowed("theme"), Value::String("dark".to_string()));
     (session_id, state)
 }
 
@@ -66,6 +59,7 @@ async fn test_create_and_load_roundtrip() {
     let record = SessionRecordRef {
         state: Cow::Borrowed(&state),
         ttl: Duration::from_secs(3600),
+        codec: SessionCodec::Json,
     };
 
     // Create session
@@ -101,6 +95,7 @@ async fn test_update_roundtrip() {
     let initial_record = SessionRecordRef {
         state: Cow::Borrowed(&initial_state),
         ttl: Duration::from_secs(3600),
+        codec: SessionCodec::Json,
     };
 
     // Create initial session
@@ -110,20 +105,15 @@ async fn test_update_roundtrip() {
     let mut updated_state = HashMap::new();
     updated_state.insert(
         Cow::Borrowed("user_id"),
-        serde_json::Value::String("updated-user-456".to_string()),
-    );
-    updated_state.insert(
-        Cow::Borrowed("counter"),
-        serde_json::Value::Number(84.into()),
-    );
-    updated_state.insert(
-        Cow::Borrowed("theme"),
-        serde_json::Value::String("light".to_string()),
+        Value::String("updated-user-456".to_string()),
     );
+    updated_state.insert(Cow::Borrowed("counter"), Value::from(84));
+    updated_state.insert(Cow::Borrowed("theme"), Value::String("light".to_string()));
 
     let updated_record = SessionRecordRef {
         state: Cow::Borrowed(&updated_state),
         ttl: Duration::from_secs(7200),
+        codec: SessionCodec::Json,
     };
 
     // Update session
@@ -153,14 +143,12 @@ async fn test_ttl_expiry() {
 
     // Create session with very short TTL
     let mut state = HashMap::new();
-    state.insert(
-        Cow::Borrowed("test"),
-        serde_json::Value::String("data".to_string()),
-    );
+    state.insert(Cow::Borrowed("test"), Value::String("data".to_string()));
 
     let record = SessionRecordRef {
         state: Cow::Borrowed(&state),
         ttl: Duration::from_millis(100),
+        codec: SessionCodec::Json,
     };
 
     store.create(&session_id, record).await.unwrap();
@@ -181,6 +169,7 @@ async fn test_update_ttl_roundtrip() {
     let record = SessionRecordRef {
         state: Cow::Borrowed(&state),
         ttl: Duration::from_secs(3600),
+        codec: SessionCodec::Json,
     };
 
     // Create session
@@ -215,6 +204,7 @@ async fn test_delete_roundtrip() {
     let record = SessionRecordRef {
         state: Cow::Borrowed(&state),
         ttl: Duration::from_secs(3600),
+        codec: SessionCodec::Json,
     };
 
     // Create session
@@ -239,6 +229,7 @@ async fn test_change_id_roundtrip() {
     let record = SessionRecordRef {
         state: Cow::Borrowed(&state),
         ttl: Duration::from_secs(3600),
+        codec: SessionCodec::Json,
     };
 
     // Create session with old ID
@@ -276,6 +267,7 @@ async fn test_delete_expired() {
     let expired_record = SessionRecordRef {
         state: Cow::Borrowed(&expired_state),
         ttl: Duration::from_secs(1),
+        codec: SessionCodec::Json,
     };
     store
         .create(&expired_session_id, expired_record)
@@ -287,6 +279,7 @@ async fn test_delete_expired() {
     let valid_record = SessionRecordRef {
         state: Cow::Borrowed(&valid_state),
         ttl: Duration::from_secs(3600),
+        codec: SessionCodec::Json,
     };
     store.create(&valid_session_id, valid_record).await.unwrap();
 
@@ -328,6 +321,7 @@ async fn test_delete_expired_with_batch_size() {
         let record = SessionRecordRef {
             state: Cow::Borrowed(&state),
             ttl: Duration::from_secs(1),
+            codec: SessionCodec::Json,
         };
         store.create(&session_id, record).await.unwrap();
     }
@@ -358,6 +352,7 @@ async fn test_delete_expired_with_batch_size() {
         let test_record = SessionRecordRef {
             state: Cow::Borrowed(&test_state),
             ttl: Duration::from_secs(1),
+            codec: SessionCodec::Json,
         };
         store.create(&test_session_id, test_record).await.unwrap();
         tokio::time::sleep(Duration::from_secs(2)).await;
@@ -397,23 +392,21 @@ async fn test_large_json_data() {
     // Create a large JSON object
     let mut state = HashMap::new();
 
-    let large_array: Vec<serde_json::Value> = (0..1000)
+    let large_array: Vec<Value> = (0..1000)
         .map(|i| {
             serde_json::json!({
                 "index": i,
                 "name": format!("Item {}", i),
                 "description": "A".repeat(100)
             })
+            .into()
         })
         .collect();
 
-    state.insert(
-        Cow::Borrowed("large_array"),
-        serde_json::Value::Array(large_array),
-    );
+    state.insert(Cow::Borrowed("large_array"), Value::Array(large_array));
     state.insert(
         Cow::Borrowed("large_string"),
-        serde_json::Value::String("x".repeat(10000)),
+        Value::String("x".repeat(10000)),
     );
     state.insert(
         Cow::Borrowed("nested_object"),
@@ -425,12 +418,14 @@ async fn test_large_json_data() {
                     }
                 }
             }
-        }),
+        })
+        .into(),
     );
 
     let record = SessionRecordRef {
         state: Cow::Borrowed(&state),
         ttl: Duration::from_secs(3600),
+        codec: SessionCodec::Json,
     };
 
     // This should handle large JSON data without issues
@@ -460,30 +455,31 @@ async fn test_unicode_and_special_characters() {
     let mut state = HashMap::new();
     state.insert(
         Cow::Borrowed("unicode"),
-        serde_json::Value::String("Hello, 世界! 🌍 Здравствуй мир! 🎉".to_string()),
+        Value::String("Hello, 世界! 🌍 Здравствуй мир! 🎉".to_string()),
     );
     state.insert(
         Cow::Borrowed("json_string"),
-        serde_json::Value::String(r#"{"nested": "value with \"quotes\""}"#.to_string()),
+        Value::String(r#"{"nested": "value with \"quotes\""}"#.to_string()),
     );
     state.insert(
         Cow::Borrowed("special_chars"),
-        serde_json::Value::String("Special: !@#$%^&*()_+-=[]{}|;':\",./<>?".to_string()),
+        Value::String("Special: !@#$%^&*()_+-=[]{}|;':\",./<>?".to_string()),
     );
     state.insert(
         Cow::Borrowed("emoji_array"),
-        serde_json::Value::Array(vec![
-            serde_json::Value::String("🚀".to_string()),
-            serde_json::Value::String("🎉".to_string()),
-            serde_json::Value::String("🌟".to_string()),
-            serde_json::Value::String("💫".to_string()),
-            serde_json::Value::String("⭐".to_string()),
+        Value::Array(vec![
+            Value::String("🚀".to_string()),
+            Value::String("🎉".to_string()),
+            Value::String("🌟".to_string()),
+            Value::String("💫".to_string()),
+            Value::String("⭐".to_string()),
         ]),
     );
 
     let record = SessionRecordRef {
         state: Cow::Borrowed(&state),
         ttl: Duration::from_secs(3600),
+        codec: SessionCodec::Json,
     };
 
     store.create(&session_id, record).await.unwrap();
@@ -512,6 +508,7 @@ async fn test_concurrent_operations() {
     let record = SessionRecordRef {
         state: Cow::Borrowed(&state),
         ttl: Duration::from_secs(3600),
+        codec: SessionCodec::Json,
     };
 
     // Create session
@@ -545,6 +542,7 @@ async fn test_create_with_duplicate_id() {
     let record = SessionRecordRef {
         state: Cow::Borrowed(&state),
         ttl: Duration::from_secs(3600),
+        codec: SessionCodec::Json,
     };
 
     // Create initial session
@@ -555,24 +553,19 @@ async fn test_create_with_duplicate_id() {
     let mut new_state = HashMap::new();
     new_state.insert(
         Cow::Borrowed("user_id"),
-        serde_json::Value::String("different-user-id".to_string()),
+        Value::String("different-user-id".to_string()),
     );
     new_state.insert(
         Cow::Borrowed("login_time"),
-        serde_json::Value::String("2024-02-01T00:00:00Z".to_string()),
-    );
-    new_state.insert(
-        Cow::Borrowed("counter"),
-        serde_json::Value::Number(50.into()),
-    );
-    new_state.insert(
-        Cow::Borrowed("theme"),
-        serde_json::Value::String("light".to_string()),
+        Value::String("2024-02-01T00:00:00Z".to_string()),
     );
+    new_state.insert(Cow::Borrowed("counter"), Value::from(50));
+    new_state.insert(Cow::Borrowed("theme"), Value::String("light".to_string()));
 
     let conflicting_record = SessionRecordRef {
         state: Cow::Borrowed(&new_state),
         ttl: Duration::from_secs(7200),
+        codec: SessionCodec::Json,
     };
 
     // This should succeed due to ON DUPLICATE KEY UPDATE clause
@@ -599,6 +592,7 @@ async fn test_update_unknown_id_error() {
     let record = SessionRecordRef {
         state: Cow::Borrowed(&state),
         ttl: Duration::from_secs(3600),
+        codec: SessionCodec::Json,
     };
 
     // Try to update a session that doesn't exist
@@ -677,10 +671,12 @@ async fn test_change_id_duplicate_new_id_error() {
     let record_1 = SessionRecordRef {
         state: Cow::Borrowed(&state_1),
         ttl: Duration::from_secs(3600),
+        codec: SessionCodec::Json,
     };
     let record_2 = SessionRecordRef {
         state: Cow::Borrowed(&state_2),
         ttl: Duration::from_secs(3600),
+        codec: SessionCodec::Json,
     };
 
     store.create(&session_id_1, record_1).await.unwrap();
@@ -710,6 +706,7 @@ async fn test_operations_on_expired_session() {
     let record = SessionRecordRef {
         state: Cow::Borrowed(&state),
         ttl: Duration::from_secs(1), // Very short TTL
+        codec: SessionCodec::Json,
     };
 
     // Create session with short TTL
@@ -723,6 +720,7 @@ async fn test_operations_on_expired_session() {
     let new_record = SessionRecordRef {
         state: Cow::Borrowed(&new_state),
         ttl: Duration::from_secs(3600),
+        codec: SessionCodec::Json,
     };
 
     let update_result = store.update(&session_id, new_record).await;
@@ -789,11 +787,12 @@ async fn test_serialization_error() {
     let mut state = HashMap::new();
 
     // JSON serialization should handle this fine, but let's test with some edge cases
-    state.insert(Cow::Borrowed("inf_value"), serde_json::json!(f64::INFINITY));
+    state.insert(Cow::Borrowed("inf_value"), Value::Float(f64::INFINITY));
 
     let record = SessionRecordRef {
         state: Cow::Borrowed(&state),
         ttl: Duration::from_secs(3600),
+        codec: SessionCodec::Json,
     };
 
     // This should succeed because serde_json handles infinity as null in JSON
@@ -841,6 +840,7 @@ async fn test_database_unavailable_error() {
             SessionRecordRef {
                 state: Cow::Borrowed(&state),
                 ttl: Duration::from_secs(3600),
+                codec: SessionCodec::Json,
             },
         )
         .await;
@@ -865,3 +865,103 @@ async fn test_database_unavailable_error() {
         ),
     }
 }
+
+#[tokio::test]
+async fn test_principal_index_roundtrip() {
+    let store = create_test_store().await;
+    // The database is shared across test runs: use a unique principal id.
+    let alice = format!("alice-{}", uuid::Uuid::new_v4());
+    let (session_a, state) = create_test_record(3600);
+    let session_b = SessionId::random();
+    for id in [&session_a, &session_b] {
+        let record = SessionRecordRef {
+            state: Cow::Borrowed(&state),
+            ttl: Duration::from_secs(3600),
+            codec: SessionCodec::Json,
+        };
+        store.create(id, record).await.unwrap();
+    }
+    store
+        .associate_principal(&session_a, &alice, Some("Firefox"))
+        .await
+        .unwrap();
+    // Associating twice replaces the previous association.
+    store
+        .associate_principal(&session_a, &alice, Some("Firefox"))
+        .await
+        .unwrap();
+    store
+        .associate_principal(&session_b, &alice, None)
+        .await
+        .unwrap();
+    let sessions = store.list_by_principal(&alice).await.unwrap();
+    assert_eq!(sessions.len(), 2);
+    assert!(sessions.iter().all(|s| s.created_at.is_some()));
+
+    // The association follows the session record when its id changes.
+    let session_a2 = SessionId::random();
+    store.change_id(&session_a, &session_a2).await.unwrap();
+    let sessions = store.list_by_principal(&alice).await.unwrap();
+    assert!(sessions.iter().any(|s| s.id == session_a2));
+    assert!(!sessions.iter().any(|s| s.id == session_a));
+
+    assert_eq!(store.delete_by_principal(&alice).await.unwrap(), 2);
+    assert!(store.list_by_principal(&alice).await.unwrap().is_empty());
+    assert!(store.load(&session_a2).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_update_if_version_detects_concurrent_updates() {
+    use pavex_session::store::errors::UpdateError;
+
+    let store = create_test_store().await;
+    let (session_id, state) = create_test_record(3600);
+    let record = |state| SessionRecordRef {
+        state: Cow::Borrowed(state),
+        ttl: Duration::from_secs(3600),
+        codec: SessionCodec::Json,
+    };
+    store.create(&session_id, record(&state)).await.unwrap();
+    let version = store.load(&session_id).await.unwrap().unwrap().version;
+    let version = version.expect("MySQL records are versioned");
+
+    store.update(&session_id, record(&state)).await.unwrap();
+    let err = store
+        .update_if_version(&session_id, record(&state), version)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, UpdateError::Conflict(_)));
+
+    let new_version = store
+        .update_if_version(&session_id, record(&state), version + 1)
+        .await
+        .unwrap();
+    assert_eq!(
+        store.load(&session_id).await.unwrap().unwrap().version,
+        Some(new_version)
+    );
+}
+
+#[tokio::test]
+async fn test_binary_codecs_roundtrip() {
+    let store = create_test_store().await;
+    let (_, mut state) = create_test_record(3600);
+    state.insert(Cow::Borrowed("avatar"), Value::Bytes(vec![0, 1, 2, 255]));
+
+    for codec in [
+        SessionCodec::MessagePack,
+        SessionCodec::Cbor,
+        SessionCodec::Postcard,
+    ] {
+        let session_id = SessionId::random();
+        let record = SessionRecordRef {
+            state: Cow::Borrowed(&state),
+            ttl: Duration::from_secs(3600),
+            codec,
+        };
+        store.create(&session_id, record).await.unwrap();
+
+        let loaded = store.load(&session_id).await.unwrap().unwrap();
+        assert_eq!(loaded.state, state, "Mismatch for codec: {codec:?}");
+    }
+}
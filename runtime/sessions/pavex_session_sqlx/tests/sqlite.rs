@@ -1,5 +1,7 @@
-use pavex_session::SessionId;
+use pavex_session::codec::SessionCodec;
+use pavex_session::store::errors::UpdateError;
 use pavex_session::store::{SessionRecordRef, SessionStorageBackend};
+use pavex_session::{SessionId, Value};
 use pavex_session_sqlx::SqliteSessionStore;
 use sqlx::SqlitePool;
 use std::borrow::Cow;
@@ -14,22 +16,20 @@ async fn create_test_store() -> SqliteSessionStore {
     store
 }
 
-fn create_test_record(
-    _ttl_secs: u64,
-) -> (SessionId, HashMap<Cow<'static, str>, serde_json::Value>) {
+fn create_test_record(_ttl_secs: u64) -> (SessionId, HashMap<Cow<'static, str>, Value>) {
     let session_id = SessionId::random();
     let mut state = HashMap::new();
     state.insert(
         Cow::Borrowed("user_id"),
-        serde_json::Value::String("test-user-123".to_string()),
+        Value::String("test-user-123".to_string()),
     );
     state.insert(
         Cow::Borrowed("login_time"),
-        serde_json::Value::String("2024-01-01T00:00:00Z".to_string()),
+        Value::String("2024-01-01T00:00:00Z".to_string()),
     );
     state.insert(
         Cow::Borrowed("permissions"),
-        serde_json::json!(["read", "write"]),
+        serde_json::json!(["read", "write"]).into(),
     );
     state.insert(
         Cow::Borrowed("metadata"),
@@ -37,7 +37,8 @@ fn create_test_record(
             "ip": "192.168.1.1",
             "user_agent": "test-agent",
             "session_start": 1640995200
-        }),
+        })
+        .into(),
     );
     (session_id, state)
 }
@@ -58,6 +59,7 @@ async fn test_migration_idempotency() {
     let record = SessionRecordRef {
         state: Cow::Borrowed(&state),
         ttl: Duration::from_secs(3600),
+        codec: SessionCodec::Json,
     };
 
     // If this succeeds, the migration worked properly
@@ -74,6 +76,7 @@ async fn test_create_and_load_roundtrip() {
     let record = SessionRecordRef {
         state: Cow::Borrowed(&state),
         ttl: Duration::from_secs(3600),
+        codec: SessionCodec::Json,
     };
 
     // Create session
@@ -110,6 +113,7 @@ async fn test_update_roundtrip() {
     let record = SessionRecordRef {
         state: Cow::Borrowed(&state),
         ttl: Duration::from_secs(3600),
+        codec: SessionCodec::Json,
     };
 
     // Create initial session
@@ -118,11 +122,11 @@ async fn test_update_roundtrip() {
     // Update the state
     state.insert(
         Cow::Borrowed("updated_field"),
-        serde_json::Value::String("new_value".to_string()),
+        Value::String("new_value".to_string()),
     );
     state.insert(
         Cow::Borrowed("user_id"),
-        serde_json::Value::String("updated-user-456".to_string()),
+        Value::String("updated-user-456".to_string()),
     );
     state.insert(
         Cow::Borrowed("new_metadata"),
@@ -134,12 +138,14 @@ async fn test_update_roundtrip() {
                     "deeply": ["nested", "array", 123, true]
                 }
             }
-        }),
+        })
+        .into(),
     );
 
     let updated_record = SessionRecordRef {
         state: Cow::Borrowed(&state),
         ttl: Duration::from_secs(7200),
+        codec: SessionCodec::Json,
     };
 
     // Update session
@@ -172,6 +178,7 @@ async fn test_ttl_expiry() {
     let record = SessionRecordRef {
         state: Cow::Borrowed(&state),
         ttl: Duration::from_secs(1), // Very short TTL
+        codec: SessionCodec::Json,
     };
 
     // Create session with short TTL
@@ -197,6 +204,7 @@ async fn test_update_ttl_roundtrip() {
     let record = SessionRecordRef {
         state: Cow::Borrowed(&state),
         ttl: Duration::from_secs(3600),
+        codec: SessionCodec::Json,
     };
 
     // Create session
@@ -230,6 +238,7 @@ async fn test_delete_roundtrip() {
     let record = SessionRecordRef {
         state: Cow::Borrowed(&state),
         ttl: Duration::from_secs(3600),
+        codec: SessionCodec::Json,
     };
 
     // Create session
@@ -256,6 +265,7 @@ async fn test_change_id_roundtrip() {
     let record = SessionRecordRef {
         state: Cow::Borrowed(&state),
         ttl: Duration::from_secs(3600),
+        codec: SessionCodec::Json,
     };
 
     // Create session with old ID
@@ -297,6 +307,7 @@ async fn test_delete_expired() {
         let record = SessionRecordRef {
             state: Cow::Borrowed(&state),
             ttl: Duration::from_secs(if i < 3 { 1 } else { 3600 }),
+            codec: SessionCodec::Json,
         };
         store.create(&session_id, record).await.unwrap();
     }
@@ -323,6 +334,7 @@ async fn test_delete_expired_with_batch_size() {
         let record = SessionRecordRef {
             state: Cow::Borrowed(&state),
             ttl: Duration::from_secs(1),
+            codec: SessionCodec::Json,
         };
         store.create(&session_id, record).await.unwrap();
     }
@@ -353,7 +365,7 @@ async fn test_large_jsonb_data() {
     // Create large, complex JSON structure
     let mut state = HashMap::new();
     let large_string = "x".repeat(10000);
-    let large_array: Vec<serde_json::Value> = (0..1000)
+    let large_array: Vec<Value> = (0..1000)
         .map(|i| {
             serde_json::json!({
                 "index": i,
@@ -363,17 +375,15 @@ async fn test_large_jsonb_data() {
                     "value": i * 2
                 }
             })
+            .into()
         })
         .collect();
 
     state.insert(
         Cow::Borrowed("large_string"),
-        serde_json::Value::String(large_string.clone()),
-    );
-    state.insert(
-        Cow::Borrowed("large_array"),
-        serde_json::Value::Array(large_array),
+        Value::String(large_string.clone()),
     );
+    state.insert(Cow::Borrowed("large_array"), Value::Array(large_array));
     state.insert(
         Cow::Borrowed("complex_object"),
         serde_json::json!({
@@ -389,12 +399,14 @@ async fn test_large_jsonb_data() {
                     }
                 }
             }
-        }),
+        })
+        .into(),
     );
 
     let record = SessionRecordRef {
         state: Cow::Borrowed(&state),
         ttl: Duration::from_secs(3600),
+        codec: SessionCodec::Json,
     };
 
     // Create and load large session
@@ -422,27 +434,29 @@ async fn test_unicode_and_special_characters() {
     let mut state = HashMap::new();
     state.insert(
         Cow::Borrowed("unicode"),
-        serde_json::Value::String("Hello, 世界! 🌍 Здравствуй мир! 🎉".to_string()),
+        Value::String("Hello, 世界! 🌍 Здравствуй мир! 🎉".to_string()),
     );
     state.insert(
         Cow::Borrowed("json_string"),
-        serde_json::Value::String(r#"{"nested": "json", "quotes": "\"escaped\""}"#.to_string()),
+        Value::String(r#"{"nested": "json", "quotes": "\"escaped\""}"#.to_string()),
     );
     state.insert(
         Cow::Borrowed("special_chars"),
-        serde_json::Value::String("Line1\nLine2\tTabbed\rCarriage\"Quoted\"".to_string()),
+        Value::String("Line1\nLine2\tTabbed\rCarriage\"Quoted\"".to_string()),
     );
     state.insert(
         Cow::Borrowed("emoji_data"),
         serde_json::json!({
             "reactions": ["👍", "👎", "❤️", "😂", "😮", "🎉"],
             "message": "Unicode test with émojis and àccénts"
-        }),
+        })
+        .into(),
     );
 
     let record = SessionRecordRef {
         state: Cow::Borrowed(&state),
         ttl: Duration::from_secs(3600),
+        codec: SessionCodec::Json,
     };
 
     store.create(&session_id, record).await.unwrap();
@@ -478,14 +492,12 @@ async fn test_concurrent_operations() {
             let store_clone = SqliteSessionStore::new(pool_clone);
             let (session_id, state) = create_test_record(3600);
             let mut modified_state = state;
-            modified_state.insert(
-                Cow::Borrowed("thread_id"),
-                serde_json::Value::Number(i.into()),
-            );
+            modified_state.insert(Cow::Borrowed("thread_id"), Value::from(i));
 
             let record = SessionRecordRef {
                 state: Cow::Borrowed(&modified_state),
                 ttl: Duration::from_secs(3600),
+                codec: SessionCodec::Json,
             };
 
             store_clone.create(&session_id, record).await.unwrap();
@@ -530,6 +542,7 @@ async fn test_create_duplicate_id_error() {
     let record = SessionRecordRef {
         state: Cow::Borrowed(&state),
         ttl: Duration::from_secs(3600),
+        codec: SessionCodec::Json,
     };
 
     // Create initial session
@@ -540,12 +553,13 @@ async fn test_create_duplicate_id_error() {
     let mut conflicting_state = different_state;
     conflicting_state.insert(
         Cow::Borrowed("conflict_field"),
-        serde_json::Value::String("this should conflict".to_string()),
+        Value::String("this should conflict".to_string()),
     );
 
     let conflicting_record = SessionRecordRef {
         state: Cow::Borrowed(&conflicting_state),
         ttl: Duration::from_secs(1), // Short TTL to force conflict
+        codec: SessionCodec::Json,
     };
 
     // This should succeed due to ON CONFLICT clause with deadline check
@@ -584,6 +598,7 @@ async fn test_update_unknown_id_error() {
     let record = SessionRecordRef {
         state: Cow::Borrowed(&state),
         ttl: Duration::from_secs(3600),
+        codec: SessionCodec::Json,
     };
 
     // Try to update a session that doesn't exist
@@ -662,10 +677,12 @@ async fn test_change_id_duplicate_new_id_error() {
     let record_1 = SessionRecordRef {
         state: Cow::Borrowed(&state_1),
         ttl: Duration::from_secs(3600),
+        codec: SessionCodec::Json,
     };
     let record_2 = SessionRecordRef {
         state: Cow::Borrowed(&state_2),
         ttl: Duration::from_secs(3600),
+        codec: SessionCodec::Json,
     };
 
     store.create(&session_id_1, record_1).await.unwrap();
@@ -695,6 +712,7 @@ async fn test_operations_on_expired_session() {
     let record = SessionRecordRef {
         state: Cow::Borrowed(&state),
         ttl: Duration::from_secs(1), // Very short TTL
+        codec: SessionCodec::Json,
     };
 
     // Create session with short TTL
@@ -708,6 +726,7 @@ async fn test_operations_on_expired_session() {
     let new_record = SessionRecordRef {
         state: Cow::Borrowed(&new_state),
         ttl: Duration::from_secs(3600),
+        codec: SessionCodec::Json,
     };
 
     let update_result = store.update(&session_id, new_record).await;
@@ -762,11 +781,12 @@ async fn test_serialization_error() {
     let mut state = HashMap::new();
 
     // JSON serialization should handle this fine, but let's test with some edge cases
-    state.insert(Cow::Borrowed("inf_value"), serde_json::json!(f64::INFINITY));
+    state.insert(Cow::Borrowed("inf_value"), Value::Float(f64::INFINITY));
 
     let record = SessionRecordRef {
         state: Cow::Borrowed(&state),
         ttl: Duration::from_secs(3600),
+        codec: SessionCodec::Json,
     };
 
     // This should succeed because serde_json handles infinity as null in JSON
@@ -801,6 +821,7 @@ async fn test_database_unavailable_error() {
     let record = SessionRecordRef {
         state: Cow::Borrowed(&state),
         ttl: Duration::from_secs(3600),
+        codec: SessionCodec::Json,
     };
 
     // Operations should fail with database errors
@@ -822,3 +843,303 @@ async fn test_database_unavailable_error() {
         other => panic!("Expected Other error for database unavailability, got: {other:?}"),
     }
 }
+
+#[tokio::test]
+async fn test_principal_index_roundtrip() {
+    let store = create_test_store().await;
+    let record = |state| SessionRecordRef {
+        state: Cow::Borrowed(state),
+        ttl: Duration::from_secs(3600),
+        codec: SessionCodec::Json,
+    };
+
+    let (session_a, state) = create_test_record(3600);
+    let session_b = SessionId::random();
+    let session_c = SessionId::random();
+    for id in [&session_a, &session_b, &session_c] {
+        store.create(id, record(&state)).await.unwrap();
+    }
+    store
+        .associate_principal(&session_a, "alice", Some("Firefox"))
+        .await
+        .unwrap();
+    store
+        .associate_principal(&session_b, "alice", None)
+        .await
+        .unwrap();
+    store
+        .associate_principal(&session_c, "bob", None)
+        .await
+        .unwrap();
+
+    let mut sessions = store.list_by_principal("alice").await.unwrap();
+    sessions.sort_by_key(|s| s.user_agent.is_none());
+    assert_eq!(sessions.len(), 2);
+    assert!(sessions[0].id == session_a);
+    assert_eq!(sessions[0].principal_id, "alice");
+    assert_eq!(sessions[0].user_agent.as_deref(), Some("Firefox"));
+    assert!(sessions[0].created_at.is_some());
+    assert!(sessions[1].id == session_b);
+
+    // The association follows the session record when its id changes...
+    let session_a2 = SessionId::random();
+    store.change_id(&session_a, &session_a2).await.unwrap();
+    let ids: Vec<_> = store
+        .list_by_principal("alice")
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|s| s.id)
+        .collect();
+    assert!(ids.contains(&session_a2));
+    assert!(!ids.contains(&session_a));
+
+    // ...and it goes away when the session record is deleted.
+    store.delete(&session_b).await.unwrap();
+    assert_eq!(store.list_by_principal("alice").await.unwrap().len(), 1);
+
+    assert_eq!(store.delete_by_principal("alice").await.unwrap(), 1);
+    assert!(store.list_by_principal("alice").await.unwrap().is_empty());
+    assert!(store.load(&session_a2).await.unwrap().is_none());
+    // Other principals are not affected.
+    assert_eq!(store.list_by_principal("bob").await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_recreating_an_expired_session_drops_its_principal() {
+    let store = create_test_store().await;
+    let (session_id, state) = create_test_record(1);
+    let record = |ttl| SessionRecordRef {
+        state: Cow::Borrowed(&state),
+        ttl,
+        codec: SessionCodec::Json,
+    };
+    store
+        .create(&session_id, record(Duration::from_secs(1)))
+        .await
+        .unwrap();
+    store
+        .associate_principal(&session_id, "alice", None)
+        .await
+        .unwrap();
+
+    tokio::time::sleep(Duration::from_secs(2)).await;
+
+    // The expired record is overwritten by a brand-new session.
+    store
+        .create(&session_id, record(Duration::from_secs(3600)))
+        .await
+        .unwrap();
+    assert!(store.list_by_principal("alice").await.unwrap().is_empty());
+    assert_eq!(store.delete_by_principal("alice").await.unwrap(), 0);
+    assert!(store.load(&session_id).await.unwrap().is_some());
+}
+
+#[tokio::test]
+async fn test_associate_principal_unknown_id_error() {
+    let store = create_test_store().await;
+    let session_id = SessionId::random();
+
+    let result = store.associate_principal(&session_id, "alice", None).await;
+    match result.unwrap_err() {
+        pavex_session::store::errors::AssociatePrincipalError::UnknownId(err) => {
+            assert!(err.id == session_id);
+        }
+        other => panic!("Expected UnknownId error, got: {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_update_if_version_detects_concurrent_updates() {
+    let store = create_test_store().await;
+    let (session_id, state) = create_test_record(3600);
+    let record = |state| SessionRecordRef {
+        state: Cow::Borrowed(state),
+        ttl: Duration::from_secs(3600),
+        codec: SessionCodec::Json,
+    };
+    store.create(&session_id, record(&state)).await.unwrap();
+    let version = store.load(&session_id).await.unwrap().unwrap().version;
+    let version = version.expect("SQLite records are versioned");
+
+    // A plain update bumps the version...
+    store.update(&session_id, record(&state)).await.unwrap();
+    let err = store
+        .update_if_version(&session_id, record(&state), version)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, UpdateError::Conflict(_)));
+
+    // ...and conditional updates succeed against the latest one.
+    let latest = store.load(&session_id).await.unwrap().unwrap().version;
+    let latest = latest.unwrap();
+    let new_version = store
+        .update_if_version(&session_id, record(&state), latest)
+        .await
+        .unwrap();
+    assert_eq!(
+        store.load(&session_id).await.unwrap().unwrap().version,
+        Some(new_version)
+    );
+
+    // Unknown ids are reported as such.
+    let err = store
+        .update_if_version(&SessionId::random(), record(&state), 0)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, UpdateError::UnknownIdError(_)));
+}
+
+#[tokio::test]
+async fn test_migration_adds_version_column_to_existing_tables() {
+    use sqlx::Executor as _;
+
+    let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+    // The schema created by previous versions of the store.
+    pool.execute(
+        "CREATE TABLE sessions (
+            id TEXT PRIMARY KEY,
+            deadline INTEGER NOT NULL,
+            state JSONB NOT NULL
+        )",
+    )
+    .await
+    .unwrap();
+    let store = SqliteSessionStore::new(pool);
+    store.migrate().await.unwrap();
+    store.migrate().await.unwrap();
+
+    let (session_id, state) = create_test_record(3600);
+    let record = SessionRecordRef {
+        state: Cow::Borrowed(&state),
+        ttl: Duration::from_secs(3600),
+        codec: SessionCodec::Json,
+    };
+    store.create(&session_id, record).await.unwrap();
+    let loaded = store.load(&session_id).await.unwrap().unwrap();
+    assert_eq!(loaded.version, Some(0));
+    assert!(loaded.created_at.is_some());
+}
+
+#[tokio::test]
+async fn test_binary_codecs_roundtrip() {
+    let store = create_test_store().await;
+    let (_, mut state) = create_test_record(3600);
+    state.insert(Cow::Borrowed("avatar"), Value::Bytes(vec![0, 1, 2, 255]));
+
+    for codec in [
+        SessionCodec::MessagePack,
+        SessionCodec::Cbor,
+        SessionCodec::Postcard,
+    ] {
+        let session_id = SessionId::random();
+        let record = SessionRecordRef {
+            state: Cow::Borrowed(&state),
+            ttl: Duration::from_secs(3600),
+            codec,
+        };
+        store.create(&session_id, record).await.unwrap();
+
+        let loaded = store.load(&session_id).await.unwrap().unwrap();
+        assert_eq!(loaded.state, state, "Mismatch for codec: {codec:?}");
+    }
+}
+
+#[tokio::test]
+async fn test_json_records_are_readable_after_switching_codec() {
+    use sqlx::Executor as _;
+
+    let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+    let store = SqliteSessionStore::new(pool.clone());
+    store.migrate().await.unwrap();
+
+    // A record written by a previous version of the store.
+    let session_id = SessionId::random();
+    pool.execute(
+        sqlx::query(
+            "INSERT INTO sessions (id, deadline, state) \
+            VALUES (?, unixepoch() + 3600, ?)",
+        )
+        .bind(session_id.inner().to_string())
+        .bind(r#"{"user_id": "test-user-123", "visits": 3}"#),
+    )
+    .await
+    .unwrap();
+
+    let loaded = store.load(&session_id).await.unwrap().unwrap();
+    assert_eq!(loaded.state["user_id"], Value::from("test-user-123"));
+    assert_eq!(loaded.state["visits"], Value::from(3));
+
+    // The record is re-encoded using the new codec when it's updated.
+    let mut updated = loaded.state.clone();
+    updated.insert(Cow::Borrowed("theme"), Value::from("dark"));
+    let record = SessionRecordRef {
+        state: Cow::Borrowed(&updated),
+        ttl: Duration::from_secs(3600),
+        codec: SessionCodec::Cbor,
+    };
+    store.update(&session_id, record).await.unwrap();
+    let loaded = store.load(&session_id).await.unwrap().unwrap();
+    assert_eq!(loaded.state, updated);
+}
+
+#[tokio::test]
+async fn test_timestamps_roundtrip() {
+    let store = create_test_store().await;
+    let (session_id, state) = create_test_record(3600);
+    let record = || SessionRecordRef {
+        state: Cow::Borrowed(&state),
+        ttl: Duration::from_secs(3600),
+        codec: SessionCodec::Json,
+    };
+    let before = pavex::time::Timestamp::now().as_second();
+    store.create(&session_id, record()).await.unwrap();
+
+    let loaded = store.load(&session_id).await.unwrap().unwrap();
+    let created_at = loaded.created_at.unwrap();
+    assert!(created_at.as_second() >= before);
+    assert_eq!(loaded.last_seen_at, Some(created_at));
+
+    // The creation timestamp follows the record when its id changes.
+    let new_id = SessionId::random();
+    store.change_id(&session_id, &new_id).await.unwrap();
+    store.update(&new_id, record()).await.unwrap();
+    store
+        .update_ttl(&new_id, Duration::from_secs(7200))
+        .await
+        .unwrap();
+    let loaded = store.load(&new_id).await.unwrap().unwrap();
+    assert_eq!(loaded.created_at, Some(created_at));
+    assert!(loaded.last_seen_at.unwrap() >= created_at);
+}
+
+#[tokio::test]
+async fn test_records_without_timestamps_are_backfilled_on_load() {
+    use sqlx::Executor as _;
+
+    let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+    let store = SqliteSessionStore::new(pool.clone());
+    store.migrate().await.unwrap();
+
+    // A record written by a previous version of the store.
+    let session_id = SessionId::random();
+    pool.execute(
+        sqlx::query(
+            "INSERT INTO sessions (id, deadline, state) \
+            VALUES (?, unixepoch() + 3600, ?)",
+        )
+        .bind(session_id.inner().to_string())
+        .bind(r#"{"user_id": "test-user-123"}"#),
+    )
+    .await
+    .unwrap();
+
+    let loaded = store.load(&session_id).await.unwrap().unwrap();
+    let created_at = loaded.created_at.unwrap();
+    assert_eq!(loaded.last_seen_at, Some(created_at));
+
+    // The backfilled timestamps are persisted.
+    let loaded = store.load(&session_id).await.unwrap().unwrap();
+    assert_eq!(loaded.created_at, Some(created_at));
+    assert_eq!(loaded.last_seen_at, Some(created_at));
+}